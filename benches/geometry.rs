@@ -0,0 +1,127 @@
+//! Benchmarks of the geometry operations, which are the most expensive parts
+//! of a pipeline run.
+//!
+//! Run with `cargo bench --features bench`. Criterion compares each run with
+//! the previous one, or with a saved baseline (`-- --save-baseline <name>`
+//! and `-- --baseline <name>`), and reports changes beyond the noise
+//! threshold as regressions or improvements. HTML reports are written to
+//! `target/criterion/report`.
+
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use hurban_selector::bench::{self, Mesh};
+
+/// Changes in the mean time smaller than this fraction are considered noise.
+const NOISE_THRESHOLD: f64 = 0.05;
+
+/// The probability of reporting a change, when there is none.
+const SIGNIFICANCE_LEVEL: f64 = 0.01;
+
+const MEASUREMENT_TIME: Duration = Duration::from_secs(10);
+
+/// Meshes representative of what the operations get in practice: small
+/// architectural and context models from the bundled library and denser
+/// smooth geometry.
+fn meshes() -> Vec<(&'static str, Mesh)> {
+    vec![
+        (
+            "human_figure",
+            bench::import_obj(include_bytes!("../resources/library/human_figure.obj")),
+        ),
+        (
+            "tree_deciduous",
+            bench::import_obj(include_bytes!("../resources/library/tree_deciduous.obj")),
+        ),
+        (
+            "apartment_block",
+            bench::import_obj(include_bytes!("../resources/library/apartment_block.obj")),
+        ),
+        ("uv_sphere_64", bench::uv_sphere(64, 64)),
+    ]
+}
+
+/// Voxel size giving each mesh roughly the same number of voxels along its
+/// longest side.
+fn voxel_size(mesh: &Mesh) -> f32 {
+    const VOXELS_ALONG_LONGEST_SIDE: f32 = 64.0;
+
+    let diagonal = mesh.bounding_box().diagonal();
+    diagonal.x.max(diagonal.y).max(diagonal.z) / VOXELS_ALONG_LONGEST_SIDE
+}
+
+fn bench_voxelize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("voxelize");
+    for (name, mesh) in &meshes() {
+        let voxel_size = voxel_size(mesh);
+        group.bench_with_input(BenchmarkId::from_parameter(name), mesh, |b, mesh| {
+            b.iter(|| bench::voxelize(black_box(mesh), voxel_size, 1))
+        });
+    }
+    group.finish();
+}
+
+fn bench_distance_field(c: &mut Criterion) {
+    let mut group = c.benchmark_group("distance_field");
+    for (name, mesh) in &meshes() {
+        let voxel_size = voxel_size(mesh);
+        group.bench_with_input(BenchmarkId::from_parameter(name), mesh, |b, mesh| {
+            b.iter(|| bench::distance_field(black_box(mesh), voxel_size, 8))
+        });
+    }
+    group.finish();
+}
+
+fn bench_weld(c: &mut Criterion) {
+    let mut group = c.benchmark_group("weld");
+    for (name, mesh) in &meshes() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), mesh, |b, mesh| {
+            b.iter(|| bench::weld(black_box(mesh), 0.001))
+        });
+    }
+    group.finish();
+}
+
+fn bench_loop_subdivision(c: &mut Criterion) {
+    // The operation needs manifold meshes, which the library models are not
+    let mut group = c.benchmark_group("loop_subdivision");
+    for &resolution in &[16, 64] {
+        let mesh = bench::uv_sphere(resolution, resolution);
+        group.bench_with_input(
+            BenchmarkId::new("uv_sphere", resolution),
+            &mesh,
+            |b, mesh| b.iter(|| bench::loop_subdivision(black_box(mesh))),
+        );
+    }
+    group.finish();
+}
+
+fn bench_export_obj(c: &mut Criterion) {
+    let mut group = c.benchmark_group("export_obj");
+    for (name, mesh) in &meshes() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), mesh, |b, mesh| {
+            b.iter(|| bench::export_obj(black_box(mesh)))
+        });
+    }
+    group.finish();
+}
+
+fn config() -> Criterion {
+    Criterion::default()
+        .noise_threshold(NOISE_THRESHOLD)
+        .significance_level(SIGNIFICANCE_LEVEL)
+        .measurement_time(MEASUREMENT_TIME)
+}
+
+criterion_group! {
+    name = benches;
+    config = config();
+    targets =
+        bench_voxelize,
+        bench_distance_field,
+        bench_weld,
+        bench_loop_subdivision,
+        bench_export_obj,
+}
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 
 use crate::bounding_box::BoundingBox;
-use crate::interpreter::{LogMessage, MeshArrayValue};
+use crate::interpreter::{Dimension, LogMessage, MeshArrayValue};
 use crate::mesh::{analysis, Mesh, UnorientedEdge};
 
 pub fn report_bounding_box_analysis(mesh: &Mesh, log: &mut dyn FnMut(LogMessage)) {
@@ -19,10 +19,11 @@ pub fn report_bounding_box_analysis(mesh: &Mesh, log: &mut dyn FnMut(LogMessage)
         "Dimensions = [{:.2}, {:.2}, {:.2}]",
         bbox_dimensions.x, bbox_dimensions.y, bbox_dimensions.z,
     )));
-    log(LogMessage::info(format!(
-        "Diagonal length = {:.2}",
-        bbox_diagonal_length
-    )));
+    log(LogMessage::measurement(
+        "Diagonal length",
+        bbox_diagonal_length,
+        Dimension::Length,
+    ));
 }
 
 pub fn report_mesh_analysis(mesh: &Mesh, log: &mut dyn FnMut(LogMessage)) {
@@ -87,7 +88,19 @@ pub fn report_mesh_analysis(mesh: &Mesh, log: &mut dyn FnMut(LogMessage)) {
         LogMessage::warn(quality_report)
     });
 
+    log(LogMessage::measurement(
+        "Surface area",
+        analysis::mesh_surface_area(mesh),
+        Dimension::Area,
+    ));
+
     if is_watertight {
+        log(LogMessage::measurement(
+            "Volume",
+            analysis::mesh_volume(mesh),
+            Dimension::Volume,
+        ));
+
         let genus = analysis::triangulated_mesh_genus(vertex_count, edge_count, face_count);
         log(LogMessage::info(format!(
             "Genus {} (number of topological holes)",
@@ -160,8 +173,9 @@ pub fn report_group_analysis(group: &MeshArrayValue, log: &mut dyn FnMut(LogMess
         "Dimensions = [{:.2}, {:.2}, {:.2}]",
         bbox_dimensions.x, bbox_dimensions.y, bbox_dimensions.z,
     )));
-    log(LogMessage::info(format!(
-        "Diagonal length = {:.2}",
-        bbox_diagonal_length
-    )));
+    log(LogMessage::measurement(
+        "Diagonal length",
+        bbox_diagonal_length,
+        Dimension::Length,
+    ));
 }
@@ -0,0 +1,815 @@
+use nalgebra::{Matrix4, Point3, Vector2, Vector3};
+
+use crate::camera::Camera;
+use crate::convert::cast_usize;
+
+pub const DEFAULT_TEMPLATE: &str = "{project}\n{variant}\n{date}";
+
+const GLYPH_WIDTH: i32 = 5;
+const GLYPH_HEIGHT: i32 = 7;
+const GLYPH_SPACING: i32 = 1;
+const LINE_SPACING: i32 = 3;
+
+/// Sizes of the annotation parts in font pixels.
+const PADDING: i32 = 4;
+const MARGIN: i32 = 8;
+const NORTH_ARROW_SIZE: i32 = 20;
+const SCALE_BAR_HEIGHT: i32 = 3;
+
+/// The annotation is scaled with the image, so that it is equally readable in
+/// small and large screenshots. This is the image height, at which a single
+/// font pixel is a single image pixel.
+const REFERENCE_IMAGE_HEIGHT: u32 = 360;
+
+/// The longest the scale bar can be, relative to the image width.
+const SCALE_BAR_MAX_WIDTH_RATIO: f32 = 0.2;
+
+const COLOR_BACKGROUND: [u8; 4] = [255, 255, 255, 200];
+const COLOR_FOREGROUND: [u8; 4] = [30, 30, 30, 255];
+const COLOR_FOREGROUND_LIGHT: [u8; 4] = [150, 150, 150, 255];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl AnnotationCorner {
+    pub const ALL: [AnnotationCorner; 4] = [
+        AnnotationCorner::TopLeft,
+        AnnotationCorner::TopRight,
+        AnnotationCorner::BottomLeft,
+        AnnotationCorner::BottomRight,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AnnotationCorner::TopLeft => "Top Left",
+            AnnotationCorner::TopRight => "Top Right",
+            AnnotationCorner::BottomLeft => "Bottom Left",
+            AnnotationCorner::BottomRight => "Bottom Right",
+        }
+    }
+}
+
+/// Configuration of the annotation overlay drawn into screenshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotationOptions {
+    pub enabled: bool,
+    pub corner: AnnotationCorner,
+    /// Lines of text of the annotation. The placeholders `{project}`,
+    /// `{date}` and `{variant}` are replaced with the project name, current
+    /// date and the name of the active variant. Lines that are empty after
+    /// the replacement are omitted.
+    pub template: String,
+    pub north_arrow: bool,
+    pub scale_bar: bool,
+    /// Whether the labels of the values shown in the viewport are drawn.
+    pub value_labels: bool,
+}
+
+impl Default for AnnotationOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            corner: AnnotationCorner::BottomRight,
+            template: String::from(DEFAULT_TEMPLATE),
+            north_arrow: true,
+            scale_bar: true,
+            value_labels: true,
+        }
+    }
+}
+
+/// The values replacing the placeholders in the annotation template.
+#[derive(Debug, Clone, Copy)]
+pub struct AnnotationContext<'a> {
+    pub project_name: &'a str,
+    pub date: &'a str,
+    pub variant_name: Option<&'a str>,
+    /// Texts of the value labels and the scene points they are drawn above.
+    pub value_labels: &'a [(Point3<f32>, String)],
+}
+
+/// An annotation prepared for a screenshot of specific dimensions taken by a
+/// specific camera.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    corner: AnnotationCorner,
+    lines: Vec<String>,
+    /// Direction of the north in the image, with the Y axis pointing down.
+    north_direction: Option<Vector2<f32>>,
+    /// Length of the scale bar in scene units and in image pixels.
+    scale_bar: Option<(f32, f32)>,
+    /// Value labels and the image points they are drawn above.
+    value_labels: Vec<(Vector2<f32>, String)>,
+}
+
+impl Annotation {
+    pub fn new(
+        options: &AnnotationOptions,
+        context: &AnnotationContext,
+        camera: &Camera,
+        north: &Vector3<f32>,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let view_matrix = camera.view_matrix();
+        let view_projection_matrix = camera.projection_matrix() * view_matrix;
+        let (origin, _) = camera.visible_sphere();
+
+        let origin_image = project_to_image(&view_projection_matrix, &origin, width, height);
+
+        let north_direction = if options.north_arrow {
+            image_north_direction(camera, north, width, height)
+        } else {
+            None
+        };
+
+        let scale_bar = if options.scale_bar {
+            origin_image.and_then(|origin_image| {
+                // The first row of the view matrix is the camera right vector
+                let right = Vector3::new(
+                    view_matrix[(0, 0)],
+                    view_matrix[(0, 1)],
+                    view_matrix[(0, 2)],
+                );
+                let right_image =
+                    project_to_image(&view_projection_matrix, &(origin + right), width, height)?;
+                let pixels_per_unit = (right_image - origin_image).norm();
+
+                if pixels_per_unit.is_finite() && pixels_per_unit > 0.0 {
+                    let max_length = width as f32 * SCALE_BAR_MAX_WIDTH_RATIO / pixels_per_unit;
+                    let length = nice_length(max_length);
+
+                    Some((length, length * pixels_per_unit))
+                } else {
+                    None
+                }
+            })
+        } else {
+            None
+        };
+
+        let value_labels = if options.value_labels {
+            context
+                .value_labels
+                .iter()
+                .filter_map(|(point, text)| {
+                    let point_image =
+                        project_to_image(&view_projection_matrix, point, width, height)?;
+                    Some((point_image, text.clone()))
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            corner: options.corner,
+            lines: expand_template(&options.template, context),
+            north_direction,
+            scale_bar,
+            value_labels,
+        }
+    }
+
+    /// Draws the annotation into tightly packed RGBA image data.
+    pub fn draw(&self, rgba: &mut [u8], width: u32, height: u32) {
+        assert_eq!(
+            rgba.len(),
+            cast_usize(width) * cast_usize(height) * 4,
+            "Image data must match the dimensions",
+        );
+
+        let scale = (height / REFERENCE_IMAGE_HEIGHT).max(1) as i32;
+        let padding = PADDING * scale;
+        let line_height = (GLYPH_HEIGHT + LINE_SPACING) * scale;
+
+        let mut canvas = Canvas {
+            rgba,
+            width: width as i32,
+            height: height as i32,
+        };
+
+        // Value labels are drawn centered above their points, under the
+        // annotation panel.
+        for (point, text) in &self.value_labels {
+            let label_width = text_width(text, scale) + 2 * padding;
+            let label_height = GLYPH_HEIGHT * scale + 2 * padding;
+            let label_x = point.x.round() as i32 - label_width / 2;
+            let label_y = point.y.round() as i32 - label_height - padding;
+
+            canvas.fill_rect(
+                label_x,
+                label_y,
+                label_width,
+                label_height,
+                COLOR_BACKGROUND,
+            );
+            canvas.draw_text(
+                label_x + padding,
+                label_y + padding,
+                scale,
+                text,
+                COLOR_FOREGROUND,
+            );
+        }
+
+        let lines_width = self
+            .lines
+            .iter()
+            .map(|line| text_width(line, scale))
+            .max()
+            .unwrap_or(0);
+        let text_height = if self.lines.is_empty() {
+            0
+        } else {
+            self.lines.len() as i32 * line_height - LINE_SPACING * scale
+        };
+
+        let north_arrow_size = NORTH_ARROW_SIZE * scale;
+        let north_label_width = text_width("N", scale);
+        let north_arrow_width = if self.north_direction.is_some() {
+            north_arrow_size + padding + north_label_width
+        } else {
+            0
+        };
+
+        let scale_bar_label = self
+            .scale_bar
+            .map(|(length, _)| format_length(length))
+            .unwrap_or_default();
+        let scale_bar_width = match self.scale_bar {
+            Some((_, bar_width)) => {
+                bar_width.round() as i32 + padding + text_width(&scale_bar_label, scale)
+            }
+            None => 0,
+        };
+
+        let graphics_width = if north_arrow_width > 0 && scale_bar_width > 0 {
+            north_arrow_width + 2 * padding + scale_bar_width
+        } else {
+            north_arrow_width + scale_bar_width
+        };
+        let graphics_height = if self.north_direction.is_some() {
+            north_arrow_size
+        } else if self.scale_bar.is_some() {
+            GLYPH_HEIGHT * scale
+        } else {
+            0
+        };
+
+        if text_height == 0 && graphics_height == 0 {
+            return;
+        }
+
+        let content_width = lines_width.max(graphics_width);
+        let content_height = if text_height > 0 && graphics_height > 0 {
+            text_height + padding + graphics_height
+        } else {
+            text_height + graphics_height
+        };
+
+        let panel_width = content_width + 2 * padding;
+        let panel_height = content_height + 2 * padding;
+        let margin = MARGIN * scale;
+
+        let panel_x = match self.corner {
+            AnnotationCorner::TopLeft | AnnotationCorner::BottomLeft => margin,
+            AnnotationCorner::TopRight | AnnotationCorner::BottomRight => {
+                width as i32 - margin - panel_width
+            }
+        };
+        let panel_y = match self.corner {
+            AnnotationCorner::TopLeft | AnnotationCorner::TopRight => margin,
+            AnnotationCorner::BottomLeft | AnnotationCorner::BottomRight => {
+                height as i32 - margin - panel_height
+            }
+        };
+
+        canvas.fill_rect(
+            panel_x,
+            panel_y,
+            panel_width,
+            panel_height,
+            COLOR_BACKGROUND,
+        );
+
+        let content_x = panel_x + padding;
+        let mut y = panel_y + padding;
+        for line in &self.lines {
+            canvas.draw_text(content_x, y, scale, line, COLOR_FOREGROUND);
+            y += line_height;
+        }
+
+        if text_height > 0 {
+            y += padding - LINE_SPACING * scale;
+        }
+
+        let mut x = content_x;
+        let text_y = y + (graphics_height - GLYPH_HEIGHT * scale) / 2;
+
+        if let Some(direction) = self.north_direction {
+            let half_size = north_arrow_size as f32 / 2.0;
+            let center = Vector2::new(x as f32 + half_size, y as f32 + half_size);
+            let perpendicular = Vector2::new(-direction.y, direction.x);
+
+            let tip = center + direction * half_size;
+            let notch = center - direction * half_size * 0.3;
+            let base_left = center - direction * half_size * 0.7 + perpendicular * half_size * 0.6;
+            let base_right = center - direction * half_size * 0.7 - perpendicular * half_size * 0.6;
+
+            canvas.fill_triangle([tip, base_left, notch], COLOR_FOREGROUND);
+            canvas.fill_triangle([tip, notch, base_right], COLOR_FOREGROUND_LIGHT);
+
+            x += north_arrow_size + padding;
+            canvas.draw_text(x, text_y, scale, "N", COLOR_FOREGROUND);
+            x += north_label_width + 2 * padding;
+        }
+
+        if let Some((_, bar_width)) = self.scale_bar {
+            let bar_width = bar_width.round() as i32;
+            let bar_height = SCALE_BAR_HEIGHT * scale;
+            let bar_y = y + (graphics_height - bar_height) / 2;
+
+            // Alternate light and dark segments, like on a printed plan
+            let half_width = bar_width / 2;
+            canvas.fill_rect(x, bar_y, half_width, bar_height, COLOR_FOREGROUND);
+            canvas.fill_rect(
+                x + half_width,
+                bar_y,
+                bar_width - half_width,
+                bar_height,
+                COLOR_FOREGROUND_LIGHT,
+            );
+
+            x += bar_width + padding;
+            canvas.draw_text(x, text_y, scale, &scale_bar_label, COLOR_FOREGROUND);
+        }
+    }
+}
+
+/// Returns the direction of the north in the image taken by the camera, with
+/// the Y axis pointing down. Returns `None` when looking along the north, as
+/// it points into the screen and can not be shown.
+pub fn image_north_direction(
+    camera: &Camera,
+    north: &Vector3<f32>,
+    width: u32,
+    height: u32,
+) -> Option<Vector2<f32>> {
+    let view_projection_matrix = camera.projection_matrix() * camera.view_matrix();
+    let (origin, _) = camera.visible_sphere();
+
+    let origin_image = project_to_image(&view_projection_matrix, &origin, width, height)?;
+    let north_image = project_to_image(&view_projection_matrix, &(origin + north), width, height)?;
+    let direction = north_image - origin_image;
+
+    if direction.norm() > 0.001 {
+        Some(direction.normalize())
+    } else {
+        None
+    }
+}
+
+/// Replaces placeholders in each line of the template, skipping lines that
+/// end up empty.
+fn expand_template(template: &str, context: &AnnotationContext) -> Vec<String> {
+    template
+        .lines()
+        .map(|line| {
+            line.replace("{project}", context.project_name)
+                .replace("{date}", context.date)
+                .replace("{variant}", context.variant_name.unwrap_or(""))
+                .trim()
+                .to_string()
+        })
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Returns the largest length of the form 1, 2 or 5 times a power of ten,
+/// which is not greater than the maximum length.
+fn nice_length(max_length: f32) -> f32 {
+    let magnitude = 10_f32.powf(max_length.log10().floor());
+    let normalized = max_length / magnitude;
+
+    let multiplier = if normalized >= 5.0 {
+        5.0
+    } else if normalized >= 2.0 {
+        2.0
+    } else {
+        1.0
+    };
+
+    multiplier * magnitude
+}
+
+fn format_length(length: f32) -> String {
+    let decimal_places = (-length.log10().floor()).max(0.0) as usize;
+    format!("{:.*}", decimal_places, length)
+}
+
+/// Projects the point to image pixel coordinates, or returns `None` if the
+/// point is behind the camera.
+pub fn project_to_image(
+    view_projection_matrix: &Matrix4<f32>,
+    point: &Point3<f32>,
+    width: u32,
+    height: u32,
+) -> Option<Vector2<f32>> {
+    let clip = view_projection_matrix * point.to_homogeneous();
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    let ndc_x = clip.x / clip.w;
+    let ndc_y = clip.y / clip.w;
+
+    Some(Vector2::new(
+        (ndc_x + 1.0) / 2.0 * width as f32,
+        (1.0 - ndc_y) / 2.0 * height as f32,
+    ))
+}
+
+fn text_width(text: &str, scale: i32) -> i32 {
+    let char_count = text.chars().count() as i32;
+    if char_count == 0 {
+        0
+    } else {
+        (char_count * (GLYPH_WIDTH + GLYPH_SPACING) - GLYPH_SPACING) * scale
+    }
+}
+
+/// Tightly packed RGBA image data drawn over with alpha blending. Drawing
+/// outside of the image is clipped.
+struct Canvas<'a> {
+    rgba: &'a mut [u8],
+    width: i32,
+    height: i32,
+}
+
+impl Canvas<'_> {
+    fn blend_pixel(&mut self, x: i32, y: i32, color: [u8; 4]) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+
+        let index = cast_usize((y * self.width + x) as u32) * 4;
+        let pixel = &mut self.rgba[index..index + 4];
+
+        let src_alpha = f32::from(color[3]) / 255.0;
+        let dst_alpha = f32::from(pixel[3]) / 255.0;
+        let out_alpha = src_alpha + dst_alpha * (1.0 - src_alpha);
+        if out_alpha <= 0.0 {
+            return;
+        }
+
+        for (component, src) in pixel.iter_mut().zip(color.iter()).take(3) {
+            let src = f32::from(*src);
+            let dst = f32::from(*component);
+            let out = (src * src_alpha + dst * dst_alpha * (1.0 - src_alpha)) / out_alpha;
+            *component = out.round() as u8;
+        }
+        pixel[3] = (out_alpha * 255.0).round() as u8;
+    }
+
+    fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, color: [u8; 4]) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.blend_pixel(px, py, color);
+            }
+        }
+    }
+
+    fn fill_triangle(&mut self, vertices: [Vector2<f32>; 3], color: [u8; 4]) {
+        let [a, b, c] = vertices;
+        let min_x = a.x.min(b.x).min(c.x).floor() as i32;
+        let max_x = a.x.max(b.x).max(c.x).ceil() as i32;
+        let min_y = a.y.min(b.y).min(c.y).floor() as i32;
+        let max_y = a.y.max(b.y).max(c.y).ceil() as i32;
+
+        let edge = |from: Vector2<f32>, to: Vector2<f32>, p: Vector2<f32>| {
+            (to.x - from.x) * (p.y - from.y) - (to.y - from.y) * (p.x - from.x)
+        };
+
+        for py in min_y..=max_y {
+            for px in min_x..=max_x {
+                let p = Vector2::new(px as f32 + 0.5, py as f32 + 0.5);
+                let e0 = edge(a, b, p);
+                let e1 = edge(b, c, p);
+                let e2 = edge(c, a, p);
+
+                // Accept both windings
+                if (e0 >= 0.0 && e1 >= 0.0 && e2 >= 0.0) || (e0 <= 0.0 && e1 <= 0.0 && e2 <= 0.0) {
+                    self.blend_pixel(px, py, color);
+                }
+            }
+        }
+    }
+
+    fn draw_text(&mut self, x: i32, y: i32, scale: i32, text: &str, color: [u8; 4]) {
+        let mut glyph_x = x;
+        for c in text.chars() {
+            let columns = glyph(c);
+            for (column_index, column) in columns.iter().enumerate() {
+                for row in 0..GLYPH_HEIGHT {
+                    if column & (1 << row) != 0 {
+                        self.fill_rect(
+                            glyph_x + column_index as i32 * scale,
+                            y + row * scale,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+
+            glyph_x += (GLYPH_WIDTH + GLYPH_SPACING) * scale;
+        }
+    }
+}
+
+/// Returns the columns of the 5x7 glyph for the character, the lowest bit
+/// being the top row. Characters outside of printable ASCII are drawn as
+/// question marks.
+fn glyph(c: char) -> &'static [u8; 5] {
+    let code = c as u32;
+    if (0x20..0x7f).contains(&code) {
+        &FONT[cast_usize(code - 0x20)]
+    } else {
+        &FONT[cast_usize(u32::from(b'?') - 0x20)]
+    }
+}
+
+#[rustfmt::skip]
+static FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // '!'
+    [0x00, 0x07, 0x00, 0x07, 0x00], // '"'
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // '#'
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // '$'
+    [0x23, 0x13, 0x08, 0x64, 0x62], // '%'
+    [0x36, 0x49, 0x55, 0x22, 0x50], // '&'
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '''
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // '('
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // ')'
+    [0x14, 0x08, 0x3e, 0x08, 0x14], // '*'
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // '+'
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ','
+    [0x08, 0x08, 0x08, 0x08, 0x08], // '-'
+    [0x00, 0x60, 0x60, 0x00, 0x00], // '.'
+    [0x20, 0x10, 0x08, 0x04, 0x02], // '/'
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // '0'
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // '1'
+    [0x42, 0x61, 0x51, 0x49, 0x46], // '2'
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // '3'
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // '4'
+    [0x27, 0x45, 0x45, 0x45, 0x39], // '5'
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // '6'
+    [0x01, 0x71, 0x09, 0x05, 0x03], // '7'
+    [0x36, 0x49, 0x49, 0x49, 0x36], // '8'
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // '9'
+    [0x00, 0x36, 0x36, 0x00, 0x00], // ':'
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ';'
+    [0x08, 0x14, 0x22, 0x41, 0x00], // '<'
+    [0x14, 0x14, 0x14, 0x14, 0x14], // '='
+    [0x00, 0x41, 0x22, 0x14, 0x08], // '>'
+    [0x02, 0x01, 0x51, 0x09, 0x06], // '?'
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // '@'
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // 'A'
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // 'B'
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // 'C'
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // 'D'
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // 'E'
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // 'F'
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // 'G'
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // 'H'
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // 'I'
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // 'J'
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // 'K'
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // 'L'
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // 'M'
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // 'N'
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // 'O'
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // 'P'
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // 'Q'
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // 'R'
+    [0x46, 0x49, 0x49, 0x49, 0x31], // 'S'
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // 'T'
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // 'U'
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // 'V'
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // 'W'
+    [0x63, 0x14, 0x08, 0x14, 0x63], // 'X'
+    [0x07, 0x08, 0x70, 0x08, 0x07], // 'Y'
+    [0x61, 0x51, 0x49, 0x45, 0x43], // 'Z'
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // '['
+    [0x02, 0x04, 0x08, 0x10, 0x20], // '\'
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ']'
+    [0x04, 0x02, 0x01, 0x02, 0x04], // '^'
+    [0x40, 0x40, 0x40, 0x40, 0x40], // '_'
+    [0x00, 0x01, 0x02, 0x04, 0x00], // '`'
+    [0x20, 0x54, 0x54, 0x54, 0x78], // 'a'
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // 'b'
+    [0x38, 0x44, 0x44, 0x44, 0x20], // 'c'
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // 'd'
+    [0x38, 0x54, 0x54, 0x54, 0x18], // 'e'
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // 'f'
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // 'g'
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // 'h'
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // 'i'
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // 'j'
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // 'k'
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // 'l'
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // 'm'
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // 'n'
+    [0x38, 0x44, 0x44, 0x44, 0x38], // 'o'
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // 'p'
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // 'q'
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // 'r'
+    [0x48, 0x54, 0x54, 0x54, 0x20], // 's'
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // 't'
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // 'u'
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // 'v'
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // 'w'
+    [0x44, 0x28, 0x10, 0x28, 0x44], // 'x'
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // 'y'
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // 'z'
+    [0x00, 0x08, 0x36, 0x41, 0x00], // '{'
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // '|'
+    [0x00, 0x41, 0x36, 0x08, 0x00], // '}'
+    [0x08, 0x04, 0x08, 0x10, 0x08], // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use crate::camera::CameraOptions;
+
+    use super::*;
+
+    fn camera(width: u32, height: u32, azimuthal_angle: f32, polar_angle: f32) -> Camera {
+        Camera::new(
+            width,
+            height,
+            50.0,
+            azimuthal_angle,
+            polar_angle,
+            CameraOptions {
+                radius_min: 1.0,
+                radius_max: 1000.0,
+                polar_angle_distance_min: 1_f32.to_radians(),
+                speed_pan: 10.0,
+                speed_rotate: 0.005,
+                speed_zoom: 0.01,
+                speed_zoom_step: 1.0,
+                fovy: 45_f32.to_radians(),
+                znear: 0.1,
+                zfar: 2000.0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_expand_template_replaces_placeholders_and_skips_empty_lines() {
+        let context = AnnotationContext {
+            project_name: "Tower",
+            date: "2021-02-03",
+            variant_name: None,
+            value_labels: &[],
+        };
+
+        let lines = expand_template("{project}\n{variant}\nDate: {date}", &context);
+        assert_eq!(lines, vec!["Tower", "Date: 2021-02-03"]);
+    }
+
+    #[test]
+    fn test_nice_length() {
+        approx::assert_relative_eq!(nice_length(7.3), 5.0, max_relative = 0.0001);
+        approx::assert_relative_eq!(nice_length(250.0), 200.0, max_relative = 0.0001);
+        approx::assert_relative_eq!(nice_length(19.0), 10.0, max_relative = 0.0001);
+        approx::assert_relative_eq!(nice_length(0.034), 0.02, max_relative = 0.0001);
+    }
+
+    #[test]
+    fn test_format_length() {
+        assert_eq!(format_length(200.0), "200");
+        assert_eq!(format_length(5.0), "5");
+        assert_eq!(format_length(0.02), "0.02");
+    }
+
+    #[test]
+    fn test_annotation_north_points_up_when_looking_north() {
+        // The camera is south of the origin, looking north
+        let camera = camera(400, 300, -FRAC_PI_2, FRAC_PI_2 / 2.0);
+        let context = AnnotationContext {
+            project_name: "Tower",
+            date: "2021-02-03",
+            variant_name: None,
+            value_labels: &[],
+        };
+
+        let annotation = Annotation::new(
+            &AnnotationOptions::default(),
+            &context,
+            &camera,
+            &Vector3::y(),
+            400,
+            300,
+        );
+        let north_direction = annotation
+            .north_direction
+            .expect("North must be visible from above");
+
+        approx::assert_relative_eq!(north_direction.x, 0.0, epsilon = 0.001);
+        approx::assert_relative_eq!(north_direction.y, -1.0, epsilon = 0.001);
+
+        let (length, bar_width) = annotation.scale_bar.expect("Must have a scale bar");
+        assert!(length > 0.0);
+        assert!(bar_width <= 400.0 * SCALE_BAR_MAX_WIDTH_RATIO);
+    }
+
+    #[test]
+    fn test_image_north_direction_follows_rotated_north() {
+        // The camera is south of the origin, looking along the Y axis, with
+        // the true north rotated towards the X axis
+        let camera = camera(400, 300, -FRAC_PI_2, FRAC_PI_2 / 2.0);
+
+        let north_direction = image_north_direction(&camera, &Vector3::x(), 400, 300)
+            .expect("North must be visible from above");
+
+        approx::assert_relative_eq!(north_direction.x, 1.0, epsilon = 0.001);
+        approx::assert_relative_eq!(north_direction.y, 0.0, epsilon = 0.001);
+    }
+
+    #[test]
+    fn test_annotation_value_labels_are_projected() {
+        let camera = camera(400, 300, -FRAC_PI_2, FRAC_PI_2 / 2.0);
+        let (origin, _) = camera.visible_sphere();
+        let value_labels = [(origin, String::from("#1 Box"))];
+        let context = AnnotationContext {
+            project_name: "Tower",
+            date: "2021-02-03",
+            variant_name: None,
+            value_labels: &value_labels,
+        };
+
+        let annotation = Annotation::new(
+            &AnnotationOptions::default(),
+            &context,
+            &camera,
+            &Vector3::y(),
+            400,
+            300,
+        );
+        assert_eq!(annotation.value_labels.len(), 1);
+
+        let (point, text) = &annotation.value_labels[0];
+        assert_eq!(text, "#1 Box");
+        approx::assert_relative_eq!(point.x, 200.0, epsilon = 0.01);
+        approx::assert_relative_eq!(point.y, 150.0, epsilon = 0.01);
+
+        let annotation = Annotation::new(
+            &AnnotationOptions {
+                value_labels: false,
+                ..AnnotationOptions::default()
+            },
+            &context,
+            &camera,
+            &Vector3::y(),
+            400,
+            300,
+        );
+        assert!(annotation.value_labels.is_empty());
+    }
+
+    #[test]
+    fn test_annotation_draws_only_into_its_corner() {
+        let annotation = Annotation {
+            corner: AnnotationCorner::BottomRight,
+            lines: vec![String::from("Tower")],
+            north_direction: Some(Vector2::new(0.0, -1.0)),
+            scale_bar: Some((10.0, 50.0)),
+            value_labels: Vec::new(),
+        };
+
+        let width = 400;
+        let height = 300;
+        let mut rgba = vec![0; width * height * 4];
+        annotation.draw(&mut rgba, width as u32, height as u32);
+
+        let alpha = |x: usize, y: usize| rgba[(y * width + x) * 4 + 3];
+
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(width - 1, height - 1), 0);
+        assert_ne!(
+            alpha(width - 1 - MARGIN as usize, height - 1 - MARGIN as usize),
+            0
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Entry points into the geometry kernel for the benchmarks in `benches/`.
+//!
+//! The benchmarks are built as a separate crate and only see the public API,
+//! so the operations they measure are exposed here, behind the `bench`
+//! feature. Each entry point does the same work as the corresponding
+//! operation in `interpreter_funcs`, without the argument checks and the
+//! logging.
+
+use std::iter;
+use std::ops::Bound;
+
+use nalgebra::{Point3, Rotation3, Vector3};
+
+use crate::exporter;
+use crate::importer;
+use crate::mesh::voxel_cloud::{FalloffFunction, ScalarField};
+use crate::mesh::{primitive, smoothing, tools, topology, NormalStrategy};
+
+pub use crate::mesh::Mesh;
+
+/// Parses the OBJ data and joins all its models into a single mesh.
+///
+/// # Panics
+///
+/// Panics if the data is not a valid OBJ.
+pub fn import_obj(obj_data: &[u8]) -> Mesh {
+    let (tobj_models, _) =
+        importer::obj_buf_into_tobj(&mut &obj_data[..]).expect("Benchmark OBJ must parse");
+    let models = importer::tobj_to_internal(tobj_models).expect("Benchmark OBJ must be valid");
+
+    tools::join_multiple_meshes(models.iter().map(|model| &model.mesh))
+}
+
+/// Creates a unit UV sphere with smooth normals.
+pub fn uv_sphere(n_parallels: u32, n_meridians: u32) -> Mesh {
+    primitive::create_uv_sphere(
+        Point3::origin(),
+        Rotation3::identity(),
+        Vector3::new(1.0, 1.0, 1.0),
+        n_parallels,
+        n_meridians,
+        NormalStrategy::Smooth,
+    )
+}
+
+/// Computes the distance field of the voxelized mesh, grown by the number of
+/// voxels, as the Voxelize operation does before meshing.
+pub fn distance_field(mesh: &Mesh, voxel_size: f32, growth: u32) -> ScalarField {
+    let voxel_dimensions = Vector3::new(voxel_size, voxel_size, voxel_size);
+    let mut scalar_field = ScalarField::from_mesh(mesh, &voxel_dimensions, 0.0, growth);
+    scalar_field.compute_distance_field(&(0.0..=0.0), FalloffFunction::Linear(1.0));
+
+    scalar_field
+}
+
+/// Voxelizes the mesh into a filled block mesh, grown by the number of voxels.
+pub fn voxelize(mesh: &Mesh, voxel_size: f32, growth: u32) -> Option<Mesh> {
+    let scalar_field = distance_field(mesh, voxel_size, growth);
+    scalar_field.to_mesh(&(Bound::Unbounded, Bound::Included(growth as f32)))
+}
+
+pub fn weld(mesh: &Mesh, tolerance: f32) -> Option<Mesh> {
+    tools::weld(mesh, tolerance)
+}
+
+/// Runs one iteration of loop subdivision with smooth normals, including
+/// the computation of the topology it needs.
+pub fn loop_subdivision(mesh: &Mesh) -> Option<Mesh> {
+    let vertex_to_vertex_topology = topology::compute_vertex_to_vertex_topology(mesh);
+    let vertex_to_face_topology = topology::compute_vertex_to_face_topology(mesh);
+    let face_to_face_topology =
+        topology::compute_face_to_face_topology(mesh, &vertex_to_face_topology);
+
+    smoothing::loop_subdivision(
+        mesh,
+        &vertex_to_vertex_topology,
+        &face_to_face_topology,
+        NormalStrategy::Smooth,
+    )
+}
+
+/// Exports the mesh to OBJ in memory with the precision used by the app.
+pub fn export_obj(mesh: &Mesh) -> Vec<u8> {
+    let mut obj = Vec::new();
+    exporter::export_obj(&mut obj, iter::once(("mesh", mesh)), f32::DIGITS, None)
+        .expect("Writing to memory must not fail");
+
+    obj
+}
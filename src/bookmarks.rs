@@ -0,0 +1,80 @@
+use crate::camera::CameraView;
+
+/// A named camera view saved in the project, e.g. for a presentation sheet.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub view: CameraView,
+}
+
+/// Returns a name for the next added bookmark, unique unless the user renamed
+/// other bookmarks to look like generated names.
+pub fn suggest_name(bookmarks: &[CameraBookmark]) -> String {
+    let mut number = bookmarks.len() + 1;
+    loop {
+        let name = format!("View {}", number);
+        if bookmarks.iter().all(|bookmark| bookmark.name != name) {
+            return name;
+        }
+
+        number += 1;
+    }
+}
+
+/// Returns the name of the image file the bookmark at the index is rendered
+/// to. The names are numbered in the order of the bookmarks, so that they sort
+/// the same way in file browsers, and contain the bookmark name with
+/// characters unsafe in file names replaced.
+pub fn render_file_name(index: usize, name: &str) -> String {
+    let mut safe_name = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() || c == '-' {
+            safe_name.extend(c.to_lowercase());
+        } else if !safe_name.ends_with('_') {
+            safe_name.push('_');
+        }
+    }
+
+    let safe_name = safe_name.trim_matches('_');
+    if safe_name.is_empty() {
+        format!("view-{:02}.png", index + 1)
+    } else {
+        format!("view-{:02}-{}.png", index + 1, safe_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(name: &str) -> CameraBookmark {
+        CameraBookmark {
+            name: String::from(name),
+            view: CameraView {
+                origin: [0.0; 3],
+                radius: 1.0,
+                azimuthal_angle: 0.0,
+                polar_angle: 1.0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_suggest_name_skips_taken_names() {
+        assert_eq!(suggest_name(&[]), "View 1");
+        assert_eq!(
+            suggest_name(&[bookmark("Entrance"), bookmark("View 3")]),
+            "View 4"
+        );
+    }
+
+    #[test]
+    fn test_render_file_name_is_numbered_and_safe() {
+        assert_eq!(
+            render_file_name(0, "Entrance / Street View"),
+            "view-01-entrance_street_view.png"
+        );
+        assert_eq!(render_file_name(11, "Court-yard"), "view-12-court-yard.png");
+        assert_eq!(render_file_name(2, " ?? "), "view-03.png");
+    }
+}
@@ -0,0 +1,247 @@
+use nalgebra::{Point3, Rotation3, Vector3};
+
+use crate::interpreter::ast;
+use crate::mesh::{primitive, Mesh};
+
+/// Index of the "Center" param of the Create Box func.
+const CREATE_BOX_PARAM_INDEX_CENTER: usize = 0;
+
+/// Index of the "Scale" param of the Create Box func.
+const CREATE_BOX_PARAM_INDEX_SCALE: usize = 2;
+
+/// Bases and heights smaller than this are considered accidental clicks and
+/// are not drawn.
+const MIN_SIZE: f32 = 0.000_001;
+
+/// A box drawn in the viewport: a base rectangle dragged on the ground, which
+/// is then pulled up to a height with the cursor.
+#[derive(Debug, Clone, Default)]
+pub struct BoxDrawing {
+    corners: Option<(Point3<f32>, Point3<f32>)>,
+    base_finished: bool,
+    height: f32,
+}
+
+impl BoxDrawing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.corners = None;
+        self.base_finished = false;
+        self.height = 0.0;
+    }
+
+    /// Sets the opposite corners of the base rectangle being dragged.
+    /// Dragging after the base was finished starts a new box.
+    pub fn drag_base(&mut self, first_corner: Point3<f32>, second_corner: Point3<f32>) {
+        if self.base_finished {
+            self.reset();
+        }
+
+        self.corners = Some((first_corner, second_corner));
+    }
+
+    /// Finishes dragging the base, after which the height is pulled up.
+    /// Bases without an area are discarded.
+    pub fn finish_base(&mut self) {
+        let has_area = self.dimensions().map_or(false, |dimensions| {
+            dimensions.x > MIN_SIZE && dimensions.y > MIN_SIZE
+        });
+
+        if has_area {
+            self.base_finished = true;
+        } else {
+            self.reset();
+        }
+    }
+
+    /// Whether the base is finished and the height is being pulled up.
+    pub fn pulling_height(&self) -> bool {
+        self.base_finished
+    }
+
+    /// The height the cursor ray points to: the height of the point on the
+    /// vertical line through the second corner of the base closest to the
+    /// ray. Returns `None` before the base is finished or if the ray is
+    /// vertical.
+    pub fn height_for_ray(&self, origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        if !self.base_finished {
+            return None;
+        }
+
+        let (_, second_corner) = self.corners?;
+        height_from_ray(&second_corner, origin, direction)
+    }
+
+    pub fn set_height(&mut self, height: f32) {
+        if self.base_finished {
+            self.height = height;
+        }
+    }
+
+    /// The width, depth and height of the box drawn so far.
+    pub fn dimensions(&self) -> Option<Vector3<f32>> {
+        let (first_corner, second_corner) = self.corners?;
+        Some(Vector3::new(
+            (second_corner.x - first_corner.x).abs(),
+            (second_corner.y - first_corner.y).abs(),
+            self.height.abs(),
+        ))
+    }
+
+    /// Returns the box drawn so far, for previewing it in the viewport.
+    pub fn mesh(&self) -> Option<Mesh> {
+        let (center, scale) = self.center_and_scale()?;
+        Some(primitive::create_box(center, Rotation3::identity(), scale))
+    }
+
+    /// Returns the args of a new Create Box producing the drawn box, once it
+    /// has a height. The remaining args are kept at their defaults.
+    pub fn create_box_args(&self, mut default_args: Vec<ast::Expr>) -> Option<Vec<ast::Expr>> {
+        if !self.base_finished || self.height.abs() <= MIN_SIZE {
+            return None;
+        }
+
+        let (center, scale) = self.center_and_scale()?;
+        default_args[CREATE_BOX_PARAM_INDEX_CENTER] =
+            ast::Expr::Lit(ast::LitExpr::Float3([center.x, center.y, center.z]));
+        default_args[CREATE_BOX_PARAM_INDEX_SCALE] =
+            ast::Expr::Lit(ast::LitExpr::Float3([scale.x, scale.y, scale.z]));
+
+        Some(default_args)
+    }
+
+    fn center_and_scale(&self) -> Option<(Point3<f32>, Vector3<f32>)> {
+        let (first_corner, second_corner) = self.corners?;
+        let dimensions = self.dimensions()?;
+        let center = Point3::new(
+            (first_corner.x + second_corner.x) / 2.0,
+            (first_corner.y + second_corner.y) / 2.0,
+            first_corner.z + self.height / 2.0,
+        );
+
+        Some((center, dimensions))
+    }
+}
+
+/// Returns the height above the base point of the point on the vertical line
+/// through the base point, which is the closest to the ray. Returns `None` if
+/// the ray is vertical.
+fn height_from_ray(
+    base_point: &Point3<f32>,
+    origin: &Point3<f32>,
+    direction: &Vector3<f32>,
+) -> Option<f32> {
+    let up = Vector3::z();
+    let direction = direction.normalize();
+    let to_base = base_point - origin;
+
+    // The closest points of two lines, one of them vertical, both with unit
+    // directions
+    let cos = up.dot(&direction);
+    let denominator = 1.0 - cos * cos;
+    if denominator.abs() < f32::EPSILON {
+        return None;
+    }
+
+    Some((cos * direction.dot(&to_base) - up.dot(&to_base)) / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_create_box_args() -> Vec<ast::Expr> {
+        vec![
+            ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, 0.0])),
+            ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, 0.0])),
+            ast::Expr::Lit(ast::LitExpr::Float3([1.0, 1.0, 1.0])),
+            ast::Expr::Lit(ast::LitExpr::Boolean(false)),
+        ]
+    }
+
+    #[test]
+    fn test_box_drawing_height_from_ray() {
+        let base_point = Point3::new(2.0, 0.0, 0.0);
+
+        let height = height_from_ray(
+            &base_point,
+            &Point3::new(-8.0, 0.0, 1.0),
+            &Vector3::new(1.0, 0.0, 0.5),
+        )
+        .expect("Must find height for a slanted ray");
+        approx::assert_relative_eq!(height, 6.0, epsilon = 0.0001);
+
+        // A ray passing by the line reaches its height at the closest point
+        let height = height_from_ray(
+            &base_point,
+            &Point3::new(2.0, -10.0, 3.0),
+            &Vector3::new(0.0, 1.0, 0.0),
+        )
+        .expect("Must find height for a horizontal ray");
+        approx::assert_relative_eq!(height, 3.0, epsilon = 0.0001);
+
+        assert!(height_from_ray(
+            &base_point,
+            &Point3::new(0.0, 0.0, 10.0),
+            &Vector3::new(0.0, 0.0, -1.0),
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_box_drawing_create_box_args() {
+        let mut drawing = BoxDrawing::new();
+        drawing.drag_base(Point3::new(4.0, 1.0, 0.0), Point3::new(0.0, 3.0, 0.0));
+        drawing.finish_base();
+        assert!(drawing.pulling_height());
+        assert!(drawing.create_box_args(default_create_box_args()).is_none());
+
+        drawing.set_height(5.0);
+        let args = drawing
+            .create_box_args(default_create_box_args())
+            .expect("Must create box with height");
+
+        assert_eq!(
+            args[0],
+            ast::Expr::Lit(ast::LitExpr::Float3([2.0, 2.0, 2.5])),
+        );
+        assert_eq!(
+            args[1],
+            ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, 0.0])),
+        );
+        assert_eq!(
+            args[2],
+            ast::Expr::Lit(ast::LitExpr::Float3([4.0, 2.0, 5.0])),
+        );
+        assert_eq!(args[3], ast::Expr::Lit(ast::LitExpr::Boolean(false)));
+    }
+
+    #[test]
+    fn test_box_drawing_base_without_area_is_discarded() {
+        let mut drawing = BoxDrawing::new();
+        drawing.drag_base(Point3::new(1.0, 1.0, 0.0), Point3::new(1.0, 5.0, 0.0));
+        drawing.finish_base();
+
+        assert!(!drawing.pulling_height());
+        assert!(drawing.dimensions().is_none());
+    }
+
+    #[test]
+    fn test_box_drawing_drag_after_finished_base_starts_over() {
+        let mut drawing = BoxDrawing::new();
+        drawing.drag_base(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 0.0));
+        drawing.finish_base();
+        drawing.set_height(2.0);
+
+        drawing.drag_base(Point3::new(5.0, 5.0, 0.0), Point3::new(6.0, 7.0, 0.0));
+
+        assert!(!drawing.pulling_height());
+        approx::assert_relative_eq!(
+            drawing.dimensions().expect("Must have a base"),
+            Vector3::new(1.0, 2.0, 0.0),
+        );
+    }
+}
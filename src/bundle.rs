@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::interpreter::ast::{Expr, LitExpr, Stmt};
+use crate::interpreter::{Func, FuncIdent, ParamRefinement};
+use crate::project::{self, Project, ProjectError};
+
+pub const EXTENSION: &str = "hurbanz";
+pub const EXTENSION_FILTER: &[&str] = &["*.hurbanz"];
+pub const EXTENSION_DESCRIPTION: &str = "H.U.R.B.A.N. selector project bundle (.hurbanz)";
+
+/// Name of the project file inside the bundle.
+const PROJECT_FILE_NAME: &str = "project.hurban";
+
+/// Directory inside the bundle, where the files referenced by the project
+/// are stored.
+const ASSETS_DIRECTORY: &str = "assets";
+
+/// Directory in the user cache directory, where opened bundles are
+/// extracted.
+const CACHE_DIRECTORY: &str = "H.U.R.B.A.N. selector/bundles";
+
+#[derive(Debug)]
+pub enum BundleError {
+    ProjectError(ProjectError),
+    ZipError(zip::result::ZipError),
+    IoError(io::Error),
+    NoCacheDirectory,
+    NoProjectFile,
+}
+
+impl error::Error for BundleError {}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BundleError::ProjectError(err) => write!(f, "{}", err),
+            BundleError::ZipError(err) => {
+                write!(f, "An error occurred while accessing the bundle: {}", err)
+            }
+            BundleError::IoError(err) => {
+                write!(f, "An error occurred while accessing bundle files: {}", err)
+            }
+            BundleError::NoCacheDirectory => {
+                write!(f, "No cache directory to extract the bundle to.")
+            }
+            BundleError::NoProjectFile => write!(f, "The bundle does not contain a project."),
+        }
+    }
+}
+
+impl From<ProjectError> for BundleError {
+    fn from(err: ProjectError) -> Self {
+        BundleError::ProjectError(err)
+    }
+}
+
+impl From<zip::result::ZipError> for BundleError {
+    fn from(err: zip::result::ZipError) -> Self {
+        BundleError::ZipError(err)
+    }
+}
+
+impl From<io::Error> for BundleError {
+    fn from(err: io::Error) -> Self {
+        BundleError::IoError(err)
+    }
+}
+
+/// Saves the project into a single zip bundle together with the files it
+/// references: the files imported by operations and the reference images.
+/// The paths in the bundled project are rewritten to point into the bundle.
+/// Files that don't exist are skipped and their paths kept.
+///
+/// Display materials, including matcaps, are built in and need no files.
+/// Variant thumbnails are not saved with projects and are not bundled
+/// either.
+///
+/// Returns the path of the bundle, which has the bundle extension added,
+/// if it was missing.
+pub fn save(
+    path: &Path,
+    mut project: Project,
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+) -> Result<PathBuf, BundleError> {
+    let path_buf = project::path_with_extension(path, EXTENSION);
+
+    // Each distinct file is bundled once, even if referenced many times
+    let mut asset_names: HashMap<String, String> = HashMap::new();
+    let mut assets: Vec<(String, PathBuf)> = Vec::new();
+    for_each_asset_path(&mut project, function_table, |asset_path| {
+        if asset_path.is_empty() {
+            return;
+        }
+
+        if let Some(asset_name) = asset_names.get(asset_path.as_str()) {
+            *asset_path = asset_name.clone();
+            return;
+        }
+
+        let source_path = PathBuf::from(asset_path.as_str());
+        if !source_path.is_file() {
+            log::warn!("Not bundling missing file {}", asset_path);
+            return;
+        }
+
+        let asset_name = bundled_asset_name(assets.len(), &source_path);
+        asset_names.insert(asset_path.clone(), asset_name.clone());
+        assets.push((asset_name.clone(), source_path));
+        *asset_path = asset_name;
+    });
+
+    let project_contents = project::serialize(project, false)?;
+
+    let file = File::create(&path_buf)?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(file));
+    let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file(PROJECT_FILE_NAME, options)?;
+    zip.write_all(&project_contents)?;
+
+    for (asset_name, source_path) in &assets {
+        zip.start_file(asset_name.as_str(), options)?;
+        let mut source = BufReader::new(File::open(source_path)?);
+        io::copy(&mut source, &mut zip)?;
+    }
+
+    zip.finish()?.flush()?;
+
+    Ok(path_buf)
+}
+
+/// Opens the project from the bundle. The bundle is extracted to a new
+/// directory in the user cache directory and the paths of the project are
+/// pointed to the extracted files.
+pub fn open(
+    path: &Path,
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+) -> Result<Project, BundleError> {
+    let bundle_name = path.file_stem().map_or_else(
+        || String::from("bundle"),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    let directory = dirs::cache_dir()
+        .ok_or(BundleError::NoCacheDirectory)?
+        .join(CACHE_DIRECTORY)
+        .join(format!(
+            "{}-{}",
+            bundle_name,
+            chrono::Local::now().format("%Y-%m-%d-%H-%M-%S"),
+        ));
+
+    extract_to(path, &directory, function_table)
+}
+
+/// Extracts the bundle into the directory and opens its project with the
+/// paths pointed to the extracted files.
+fn extract_to(
+    path: &Path,
+    directory: &Path,
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+) -> Result<Project, BundleError> {
+    let file = File::open(path)?;
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))?;
+
+    fs::create_dir_all(directory)?;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        // Entries escaping the directory, e.g. with "..", are not extracted
+        let entry_path = match entry.enclosed_name() {
+            Some(entry_path) => directory.join(entry_path),
+            None => {
+                log::warn!("Not extracting bundle entry {}", entry.name());
+                continue;
+            }
+        };
+
+        if entry.is_dir() {
+            fs::create_dir_all(&entry_path)?;
+        } else {
+            if let Some(parent) = entry_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut output = File::create(&entry_path)?;
+            io::copy(&mut entry, &mut output)?;
+        }
+    }
+
+    let project_path = directory.join(PROJECT_FILE_NAME);
+    if !project_path.is_file() {
+        return Err(BundleError::NoProjectFile);
+    }
+
+    // Only the paths of files extracted into the assets directory are
+    // rewritten. Other paths, such as those of files missing when the bundle
+    // was saved, are kept as they were.
+    let assets_directory = directory.join(ASSETS_DIRECTORY);
+    let mut project = project::open(&project_path)?;
+    for_each_asset_path(&mut project, function_table, |asset_path| {
+        let extracted_path = directory.join(asset_path.as_str());
+        if extracted_path.parent() == Some(assets_directory.as_path()) && extracted_path.is_file() {
+            *asset_path = extracted_path.to_string_lossy().into_owned();
+        }
+    });
+
+    Ok(project)
+}
+
+/// Returns the name of the bundle entry of the file, unique thanks to the
+/// index of the file.
+fn bundled_asset_name(index: usize, source_path: &Path) -> String {
+    let file_name = source_path.file_name().map_or_else(
+        || String::from("asset"),
+        |file_name| file_name.to_string_lossy().into_owned(),
+    );
+
+    format!("{}/{}-{}", ASSETS_DIRECTORY, index, file_name)
+}
+
+/// Calls the closure on each path of a file referenced by the project: the
+/// file path arguments of the statements and variants, and the paths of the
+/// reference images.
+fn for_each_asset_path<F>(
+    project: &mut Project,
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+    mut f: F,
+) where
+    F: FnMut(&mut String),
+{
+    let variant_stmts = project
+        .variants
+        .iter_mut()
+        .map(|variant| &mut variant.stmts);
+    for stmts in std::iter::once(&mut project.stmts).chain(variant_stmts) {
+        for stmt in stmts.iter_mut() {
+            let Stmt::VarDecl(var_decl) = stmt;
+            let func = match function_table.get(&var_decl.init_expr().ident()) {
+                Some(func) => func,
+                None => continue,
+            };
+
+            let arg_count = var_decl.init_expr().args().len();
+            let mut init_expr = var_decl.init_expr().clone();
+            for arg_index in 0..arg_count {
+                let param_index = match func.param_index_for_arg(arg_index, arg_count) {
+                    Some(param_index) => param_index,
+                    None => continue,
+                };
+
+                let param = &func.param_info()[param_index];
+                if let (ParamRefinement::FilePath(_), Expr::Lit(LitExpr::String(string))) =
+                    (&param.refinement, &init_expr.args()[arg_index])
+                {
+                    let mut asset_path = string.clone();
+                    f(&mut asset_path);
+                    init_expr = init_expr
+                        .clone_with_arg_at(arg_index, Expr::Lit(LitExpr::String(asset_path)));
+                }
+            }
+
+            *var_decl = var_decl.clone_with_init_expr(init_expr);
+        }
+    }
+
+    for reference_image in &mut project.reference_images {
+        f(&mut reference_image.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::ast::{CallExpr, VarDeclStmt};
+    use crate::interpreter::VarIdent;
+    use crate::interpreter_funcs::{self, FUNC_ID_IMPORT_OBJ_JOIN};
+
+    use super::*;
+
+    fn import_stmt(var_ident: u64, path: &str) -> Stmt {
+        Stmt::VarDecl(VarDeclStmt::new(
+            VarIdent(var_ident),
+            CallExpr::new(
+                FUNC_ID_IMPORT_OBJ_JOIN,
+                vec![
+                    Expr::Lit(LitExpr::String(path.to_string())),
+                    Expr::Lit(LitExpr::Boolean(true)),
+                    Expr::Lit(LitExpr::Boolean(true)),
+                    Expr::Lit(LitExpr::Boolean(false)),
+                    Expr::Lit(LitExpr::Float(1.0)),
+                ],
+            ),
+        ))
+    }
+
+    fn import_path(stmt: &Stmt) -> &str {
+        let Stmt::VarDecl(var_decl) = stmt;
+        var_decl.init_expr().args()[0]
+            .unwrap_literal()
+            .unwrap_string()
+    }
+
+    #[test]
+    fn test_bundle_bundled_asset_name() {
+        assert_eq!(
+            bundled_asset_name(3, Path::new("/models/tower.obj")),
+            "assets/3-tower.obj",
+        );
+    }
+
+    #[test]
+    fn test_bundle_save_and_extract_round_trip() {
+        let function_table = interpreter_funcs::create_function_table();
+        let directory = std::env::temp_dir().join(format!(
+            "hurban-selector-test-bundle-{}",
+            std::process::id(),
+        ));
+        let source_directory = directory.join("source");
+        fs::create_dir_all(&source_directory).unwrap();
+
+        let obj_path = source_directory.join("tower.obj");
+        fs::write(&obj_path, "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        let obj_path = obj_path.to_string_lossy().into_owned();
+        let missing_path = source_directory.join("missing.obj");
+        let missing_path = missing_path.to_string_lossy().into_owned();
+        let relative_missing_path = "assets/missing.obj";
+
+        let project = Project {
+            stmts: vec![
+                import_stmt(0, &obj_path),
+                import_stmt(1, &missing_path),
+                import_stmt(2, relative_missing_path),
+            ],
+            variants: vec![project::ProjectVariant {
+                name: String::from("Variant"),
+                stmts: vec![import_stmt(0, &obj_path)],
+            }],
+            ..Project::default()
+        };
+
+        let bundle_path = save(&directory.join("tower"), project, &function_table).unwrap();
+        assert_eq!(bundle_path, directory.join("tower.hurbanz"));
+
+        let extracted_directory = directory.join("extracted");
+        let project = extract_to(&bundle_path, &extracted_directory, &function_table).unwrap();
+
+        let extracted_obj_path = extracted_directory.join("assets").join("0-tower.obj");
+        assert_eq!(
+            import_path(&project.stmts[0]),
+            extracted_obj_path.to_string_lossy(),
+        );
+        assert_eq!(import_path(&project.stmts[1]), missing_path);
+        assert_eq!(import_path(&project.stmts[2]), relative_missing_path);
+        assert_eq!(
+            import_path(&project.variants[0].stmts[0]),
+            extracted_obj_path.to_string_lossy(),
+        );
+        assert_eq!(
+            fs::read_to_string(&extracted_obj_path).unwrap(),
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n",
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
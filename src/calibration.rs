@@ -0,0 +1,213 @@
+use nalgebra::Point3;
+
+use crate::interpreter::{ast, VarIdent};
+use crate::interpreter_funcs;
+
+/// Index of the "Mesh" param of the Transform func.
+const TRANSFORM_PARAM_INDEX_MESH: usize = 0;
+
+/// Index of the "Scale" param of the Transform func.
+const TRANSFORM_PARAM_INDEX_SCALE: usize = 3;
+
+/// Picked points closer than this can not be used for calibration, as the
+/// resulting scale would be unreasonably large.
+const MIN_MEASURED_DISTANCE: f32 = 0.000_001;
+
+/// Two points picked on a mesh in the viewport, whose distance is compared to
+/// a real world distance to calibrate the scale of the mesh.
+#[derive(Debug, Clone, Default)]
+pub struct ScaleCalibration {
+    target: Option<VarIdent>,
+    points: Vec<Point3<f32>>,
+}
+
+impl ScaleCalibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a point picked on the mesh of the variable. The first picked
+    /// point decides which variable gets calibrated. Picking after both
+    /// points were picked starts over.
+    pub fn pick(&mut self, var_ident: VarIdent, point: Point3<f32>) {
+        if self.points.len() >= 2 {
+            self.reset();
+        }
+
+        if self.target.is_none() {
+            self.target = Some(var_ident);
+        }
+        self.points.push(point);
+    }
+
+    pub fn reset(&mut self) {
+        self.target = None;
+        self.points.clear();
+    }
+
+    /// The variable holding the mesh being calibrated.
+    pub fn target(&self) -> Option<VarIdent> {
+        self.target
+    }
+
+    pub fn points(&self) -> &[Point3<f32>] {
+        &self.points
+    }
+
+    /// The distance of the picked points in the current scale of the mesh,
+    /// once both points were picked.
+    pub fn measured_distance(&self) -> Option<f32> {
+        match self.points.as_slice() {
+            [first, second] => Some(nalgebra::distance(first, second)),
+            _ => None,
+        }
+    }
+}
+
+/// Computes the uniform scale, which makes the measured distance equal to
+/// the real distance. Returns `None` if the distances can not be used.
+pub fn scale_factor(measured_distance: f32, real_distance: f32) -> Option<f32> {
+    if measured_distance.is_finite()
+        && real_distance.is_finite()
+        && measured_distance > MIN_MEASURED_DISTANCE
+        && real_distance > 0.0
+    {
+        Some(real_distance / measured_distance)
+    } else {
+        None
+    }
+}
+
+/// If the variable is declared by a Transform statement, returns the index of
+/// the statement and its copy with the scale multiplied by the factor.
+pub fn rescaled_transform_stmt(
+    stmts: &[ast::Stmt],
+    var_ident: VarIdent,
+    factor: f32,
+) -> Option<(usize, ast::Stmt)> {
+    stmts.iter().enumerate().find_map(|(stmt_index, stmt)| {
+        let ast::Stmt::VarDecl(var_decl) = stmt;
+        let init_expr = var_decl.init_expr();
+        if var_decl.ident() != var_ident
+            || init_expr.ident() != interpreter_funcs::FUNC_ID_TRANSFORM
+        {
+            return None;
+        }
+
+        let scale = match init_expr.args().get(TRANSFORM_PARAM_INDEX_SCALE) {
+            Some(ast::Expr::Lit(ast::LitExpr::Float3(scale))) => *scale,
+            _ => return None,
+        };
+        let init_expr = init_expr.clone_with_arg_at(
+            TRANSFORM_PARAM_INDEX_SCALE,
+            ast::Expr::Lit(ast::LitExpr::Float3([
+                scale[0] * factor,
+                scale[1] * factor,
+                scale[2] * factor,
+            ])),
+        );
+
+        Some((
+            stmt_index,
+            ast::Stmt::VarDecl(var_decl.clone_with_init_expr(init_expr)),
+        ))
+    })
+}
+
+/// Returns the args of a new Transform, uniformly scaling the mesh of the
+/// variable by the factor. The remaining args are kept at their defaults.
+pub fn new_transform_args(
+    mut default_args: Vec<ast::Expr>,
+    var_ident: VarIdent,
+    factor: f32,
+) -> Vec<ast::Expr> {
+    default_args[TRANSFORM_PARAM_INDEX_MESH] = ast::Expr::Var(ast::VarExpr::new(var_ident));
+    default_args[TRANSFORM_PARAM_INDEX_SCALE] =
+        ast::Expr::Lit(ast::LitExpr::Float3([factor, factor, factor]));
+
+    default_args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform_args(var_ident: VarIdent, scale: [f32; 3]) -> Vec<ast::Expr> {
+        vec![
+            ast::Expr::Var(ast::VarExpr::new(var_ident)),
+            ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, 0.0])),
+            ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, 0.0])),
+            ast::Expr::Lit(ast::LitExpr::Float3(scale)),
+            ast::Expr::Lit(ast::LitExpr::Boolean(true)),
+            ast::Expr::Lit(ast::LitExpr::Boolean(false)),
+        ]
+    }
+
+    #[test]
+    fn test_scale_calibration_measures_distance_of_two_picks() {
+        let mut calibration = ScaleCalibration::new();
+        assert_eq!(calibration.measured_distance(), None);
+
+        calibration.pick(VarIdent(0), Point3::new(1.0, 0.0, 0.0));
+        assert_eq!(calibration.target(), Some(VarIdent(0)));
+        assert_eq!(calibration.measured_distance(), None);
+
+        calibration.pick(VarIdent(1), Point3::new(1.0, 3.0, 4.0));
+        assert_eq!(calibration.target(), Some(VarIdent(0)));
+        approx::assert_relative_eq!(calibration.measured_distance().unwrap(), 5.0);
+
+        // The third pick starts over
+        calibration.pick(VarIdent(1), Point3::origin());
+        assert_eq!(calibration.target(), Some(VarIdent(1)));
+        assert_eq!(calibration.points().len(), 1);
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        approx::assert_relative_eq!(scale_factor(2.0, 5.0).unwrap(), 2.5);
+        assert_eq!(scale_factor(0.0, 5.0), None);
+        assert_eq!(scale_factor(2.0, 0.0), None);
+        assert_eq!(scale_factor(2.0, f32::NAN), None);
+    }
+
+    #[test]
+    fn test_rescaled_transform_stmt_multiplies_existing_scale() {
+        let stmts = vec![
+            ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                VarIdent(0),
+                ast::CallExpr::new(interpreter_funcs::FUNC_ID_CREATE_BOX, vec![]),
+            )),
+            ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                VarIdent(1),
+                ast::CallExpr::new(
+                    interpreter_funcs::FUNC_ID_TRANSFORM,
+                    transform_args(VarIdent(0), [1.0, 2.0, 3.0]),
+                ),
+            )),
+        ];
+
+        let (stmt_index, stmt) =
+            rescaled_transform_stmt(&stmts, VarIdent(1), 2.0).expect("Must rescale transform");
+        assert_eq!(stmt_index, 1);
+
+        let ast::Stmt::VarDecl(var_decl) = stmt;
+        assert_eq!(var_decl.ident(), VarIdent(1));
+        assert_eq!(
+            var_decl.init_expr().args(),
+            transform_args(VarIdent(0), [2.0, 4.0, 6.0]).as_slice(),
+        );
+
+        assert!(rescaled_transform_stmt(&stmts, VarIdent(0), 2.0).is_none());
+    }
+
+    #[test]
+    fn test_new_transform_args_scale_the_variable_uniformly() {
+        let args = new_transform_args(
+            transform_args(VarIdent(7), [1.0, 1.0, 1.0]),
+            VarIdent(3),
+            0.5,
+        );
+
+        assert_eq!(args, transform_args(VarIdent(3), [0.5, 0.5, 0.5]));
+    }
+}
@@ -21,6 +21,16 @@ pub struct CameraOptions {
     pub zfar: f32,
 }
 
+/// The placement of the camera around its origin, independent of the screen
+/// dimensions and camera options.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CameraView {
+    pub origin: [f32; 3],
+    pub radius: f32,
+    pub azimuthal_angle: f32,
+    pub polar_angle: f32,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Camera {
     screen_width: u32,
@@ -69,6 +79,32 @@ impl Camera {
         self.origin + Vector3::new(x, y, z)
     }
 
+    pub fn view(&self) -> CameraView {
+        CameraView {
+            origin: self.origin.coords.into(),
+            radius: self.radius,
+            azimuthal_angle: self.azimuthal_angle,
+            polar_angle: self.polar_angle,
+        }
+    }
+
+    /// Places the camera to the view. The radius and polar angle are clamped
+    /// to the limits of the camera.
+    pub fn set_view(&mut self, view: &CameraView) {
+        self.origin = Point3::from(view.origin);
+        self.radius = clamp(
+            view.radius,
+            self.options.radius_min,
+            self.options.radius_max,
+        );
+        self.azimuthal_angle = view.azimuthal_angle % TAU;
+        self.polar_angle = clamp(
+            view.polar_angle,
+            self.options.polar_angle_distance_min,
+            f32::consts::PI - self.options.polar_angle_distance_min,
+        );
+    }
+
     pub fn set_screen_dimensions(&mut self, screen_width: u32, screen_height: u32) {
         self.screen_width = screen_width;
         self.screen_height = screen_height;
@@ -235,6 +271,31 @@ impl Camera {
         }
     }
 
+    /// Returns the origin and the unit direction of the ray going from the
+    /// camera through the screenspace point `(x,y)`.
+    pub fn screen_ray(&self, x: f32, y: f32) -> Option<(Point3<f32>, Vector3<f32>)> {
+        let proj_inv = self.projection_matrix().try_inverse()?;
+        let view_inv = self.view_matrix().try_inverse()?;
+
+        let screen_width = self.screen_width as f32;
+        let screen_height = self.screen_height as f32;
+
+        let x_ndc = x / screen_width * 2.0 - 1.0;
+        let y_ndc = (screen_height - y) / screen_height * 2.0 - 1.0;
+
+        let near_world =
+            view_inv.transform_point(&proj_inv.transform_point(&Point3::new(x_ndc, y_ndc, -1.0)));
+        let far_world =
+            view_inv.transform_point(&proj_inv.transform_point(&Point3::new(x_ndc, y_ndc, 1.0)));
+
+        let direction = far_world - near_world;
+        if direction.norm_squared() > 0.0 {
+            Some((near_world, direction.normalize()))
+        } else {
+            None
+        }
+    }
+
     /// Rotates the camera by changing azimuthal (theta) and polar (phi)
     /// angles. `dx` and `dy` are in screen space.
     pub fn rotate(&mut self, dx: f32, dy: f32) {
@@ -321,6 +382,23 @@ impl Camera {
         )
     }
 
+    /// Returns the direction the camera looks in.
+    pub fn direction(&self) -> Vector3<f32> {
+        (self.origin - self.position()).normalize()
+    }
+
+    /// Returns the projection matrix covering one face of a cube around the
+    /// camera position, i.e. with a square screen and a 90 degree field of
+    /// view.
+    pub fn cube_face_projection_matrix(&self) -> Matrix4<f32> {
+        Matrix4::new_perspective(
+            1.0,
+            f32::consts::FRAC_PI_2,
+            self.options.znear,
+            self.options.zfar,
+        )
+    }
+
     fn compute_visible_sphere_angle(&self) -> f32 {
         let fovy = self.options.fovy;
         let fovx = fovy * self.screen_aspect_ratio();
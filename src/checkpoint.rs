@@ -0,0 +1,751 @@
+use std::convert::TryInto;
+use std::error;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::str;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel as channel;
+use nalgebra::{Point3, Vector3};
+
+use crate::convert::{cast_u32, cast_usize};
+use crate::crash;
+use crate::interpreter::ast::{CallExpr, Stmt};
+use crate::interpreter::{MeshArrayValue, Value, VarIdent};
+use crate::mesh::{Face, Mesh, TriangleFace};
+
+/// Name of the checkpoint file in the crash directory.
+const FILE_NAME: &str = "checkpoint.bin";
+
+/// How long a run goes on before its values are first written, and how
+/// often they are written after that. Shorter runs are never checkpointed.
+pub const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+const VALUE_TAG_NIL: u8 = 0;
+const VALUE_TAG_BOOLEAN: u8 = 1;
+const VALUE_TAG_INT: u8 = 2;
+const VALUE_TAG_UINT: u8 = 3;
+const VALUE_TAG_FLOAT: u8 = 4;
+const VALUE_TAG_FLOAT2: u8 = 5;
+const VALUE_TAG_FLOAT3: u8 = 6;
+const VALUE_TAG_STRING: u8 = 7;
+const VALUE_TAG_MESH: u8 = 8;
+const VALUE_TAG_MESH_ARRAY: u8 = 9;
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    SerializeError(ron::error::Error),
+    IoError(io::Error),
+}
+
+impl error::Error for CheckpointError {}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckpointError::SerializeError(err) => write!(
+                f,
+                "An error occurred while serializing or deserializing checkpoint: {}",
+                err
+            ),
+            CheckpointError::IoError(err) => {
+                write!(f, "An error occurred while accessing checkpoint: {}", err)
+            }
+        }
+    }
+}
+
+impl From<ron::error::Error> for CheckpointError {
+    fn from(err: ron::error::Error) -> Self {
+        CheckpointError::SerializeError(err)
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(err: io::Error) -> Self {
+        CheckpointError::IoError(err)
+    }
+}
+
+/// A value of a completed statement, as sent to the checkpoint writer.
+type CheckpointEntry = (VarIdent, CallExpr, Value);
+
+/// Encodes the value of a completed statement as a record of the checkpoint
+/// file.
+///
+/// The record starts with the length and the checksum of its content, so
+/// that a record damaged by a crash while writing it is recognized when
+/// loading. The content is the variable, the call in RON and the value. The
+/// value is encoded in a binary form, as it is mostly large meshes.
+fn encode_record(
+    var_ident: VarIdent,
+    call: &CallExpr,
+    value: &Value,
+    output: &mut Vec<u8>,
+) -> Result<(), CheckpointError> {
+    let mut content = Vec::new();
+    content.extend_from_slice(&var_ident.0.to_le_bytes());
+    encode_bytes(ron::ser::to_string(call)?.as_bytes(), &mut content);
+    encode_value(value, &mut content);
+
+    output.extend_from_slice(&cast_u32(content.len()).to_le_bytes());
+    output.extend_from_slice(&crc32fast::hash(&content).to_le_bytes());
+    output.extend_from_slice(&content);
+
+    Ok(())
+}
+
+fn encode_bytes(bytes: &[u8], output: &mut Vec<u8>) {
+    output.extend_from_slice(&cast_u32(bytes.len()).to_le_bytes());
+    output.extend_from_slice(bytes);
+}
+
+fn encode_floats(floats: &[f32], output: &mut Vec<u8>) {
+    for float in floats {
+        output.extend_from_slice(&float.to_le_bytes());
+    }
+}
+
+fn encode_value(value: &Value, output: &mut Vec<u8>) {
+    match value {
+        Value::Nil => output.push(VALUE_TAG_NIL),
+        Value::Boolean(boolean) => {
+            output.push(VALUE_TAG_BOOLEAN);
+            output.push(u8::from(*boolean));
+        }
+        Value::Int(int) => {
+            output.push(VALUE_TAG_INT);
+            output.extend_from_slice(&int.to_le_bytes());
+        }
+        Value::Uint(uint) => {
+            output.push(VALUE_TAG_UINT);
+            output.extend_from_slice(&uint.to_le_bytes());
+        }
+        Value::Float(float) => {
+            output.push(VALUE_TAG_FLOAT);
+            encode_floats(&[*float], output);
+        }
+        Value::Float2(float2) => {
+            output.push(VALUE_TAG_FLOAT2);
+            encode_floats(float2, output);
+        }
+        Value::Float3(float3) => {
+            output.push(VALUE_TAG_FLOAT3);
+            encode_floats(float3, output);
+        }
+        Value::String(string) => {
+            output.push(VALUE_TAG_STRING);
+            encode_bytes(string.as_bytes(), output);
+        }
+        Value::Mesh(mesh) => {
+            output.push(VALUE_TAG_MESH);
+            encode_mesh(mesh, output);
+        }
+        Value::MeshArray(mesh_array) => {
+            output.push(VALUE_TAG_MESH_ARRAY);
+            output.extend_from_slice(&mesh_array.len().to_le_bytes());
+            for mesh in mesh_array.iter_refcounted() {
+                encode_mesh(&mesh, output);
+            }
+        }
+    }
+}
+
+/// Encodes the mesh data. Each face is the three vertex indices followed by
+/// the three normal indices.
+fn encode_mesh(mesh: &Mesh, output: &mut Vec<u8>) {
+    output.extend_from_slice(&cast_u32(mesh.faces().len()).to_le_bytes());
+    for face in mesh.faces() {
+        match face {
+            Face::Triangle(triangle_face) => {
+                let v = triangle_face.vertices;
+                let n = triangle_face.normals;
+                for index in &[v.0, v.1, v.2, n.0, n.1, n.2] {
+                    output.extend_from_slice(&index.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    output.extend_from_slice(&cast_u32(mesh.vertices().len()).to_le_bytes());
+    for vertex in mesh.vertices() {
+        encode_floats(&[vertex.x, vertex.y, vertex.z], output);
+    }
+
+    output.extend_from_slice(&cast_u32(mesh.normals().len()).to_le_bytes());
+    for normal in mesh.normals() {
+        encode_floats(&[normal.x, normal.y, normal.z], output);
+    }
+
+    match mesh.vertex_colors() {
+        Some(vertex_colors) => {
+            output.push(1);
+            for color in vertex_colors {
+                output.extend_from_slice(color);
+            }
+        }
+        None => output.push(0),
+    }
+}
+
+/// Reads the data encoded in the checkpoint file. Each read returns `None`,
+/// if there is not enough data left, e.g. because the file was cut short.
+struct Decoder<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { remaining: bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.remaining.len() < len {
+            return None;
+        }
+
+        let (taken, remaining) = self.remaining.split_at(len);
+        self.remaining = remaining;
+
+        Some(taken)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn i32(&mut self) -> Option<i32> {
+        self.take(4)?.try_into().ok().map(i32::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)?.try_into().ok().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)?.try_into().ok().map(u64::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.take(4)?.try_into().ok().map(f32::from_le_bytes)
+    }
+
+    fn float3(&mut self) -> Option<[f32; 3]> {
+        Some([self.f32()?, self.f32()?, self.f32()?])
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()?;
+        self.take(cast_usize(len))
+    }
+
+    /// Reads the number of the items following it. Returns `None`, if there
+    /// is not enough data left for the items, so that a damaged count
+    /// doesn't allocate excessive memory.
+    fn count(&mut self, item_len: usize) -> Option<usize> {
+        let count = cast_usize(self.u32()?);
+        if count > self.remaining.len() / item_len {
+            return None;
+        }
+
+        Some(count)
+    }
+}
+
+/// Decodes the content of a record of the checkpoint file. Returns `None`,
+/// if the content is not a valid record.
+fn decode_record(content: &[u8]) -> Option<CheckpointEntry> {
+    let mut decoder = Decoder::new(content);
+    let var_ident = VarIdent(decoder.u64()?);
+    let call = str::from_utf8(decoder.bytes()?).ok()?;
+    let call = ron::de::from_str(call).ok()?;
+    let value = decode_value(&mut decoder)?;
+
+    if decoder.is_empty() {
+        Some((var_ident, call, value))
+    } else {
+        None
+    }
+}
+
+fn decode_value(decoder: &mut Decoder) -> Option<Value> {
+    let value = match decoder.u8()? {
+        VALUE_TAG_NIL => Value::Nil,
+        VALUE_TAG_BOOLEAN => match decoder.u8()? {
+            0 => Value::Boolean(false),
+            1 => Value::Boolean(true),
+            _ => return None,
+        },
+        VALUE_TAG_INT => Value::Int(decoder.i32()?),
+        VALUE_TAG_UINT => Value::Uint(decoder.u32()?),
+        VALUE_TAG_FLOAT => Value::Float(decoder.f32()?),
+        VALUE_TAG_FLOAT2 => Value::Float2([decoder.f32()?, decoder.f32()?]),
+        VALUE_TAG_FLOAT3 => Value::Float3(decoder.float3()?),
+        VALUE_TAG_STRING => {
+            let string = str::from_utf8(decoder.bytes()?).ok()?;
+            Value::String(Arc::new(String::from(string)))
+        }
+        VALUE_TAG_MESH => Value::Mesh(Arc::new(decode_mesh(decoder)?)),
+        VALUE_TAG_MESH_ARRAY => {
+            // Each mesh takes at least its three counts and the colors flag
+            let mesh_count = decoder.count(13)?;
+            let mut mesh_array = Vec::with_capacity(mesh_count);
+            for _ in 0..mesh_count {
+                mesh_array.push(Arc::new(decode_mesh(decoder)?));
+            }
+            Value::MeshArray(Arc::new(MeshArrayValue::new(mesh_array)))
+        }
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Decodes the mesh data. Returns `None` if the data would not make a valid
+/// mesh, e.g. because the file was damaged.
+fn decode_mesh(decoder: &mut Decoder) -> Option<Mesh> {
+    let face_count = decoder.count(24)?;
+    let mut faces = Vec::with_capacity(face_count);
+    for _ in 0..face_count {
+        let mut face = [0; 6];
+        for index in &mut face {
+            *index = decoder.u32()?;
+        }
+        faces.push(face);
+    }
+
+    let vertex_count = decoder.count(12)?;
+    let mut vertices = Vec::with_capacity(vertex_count);
+    for _ in 0..vertex_count {
+        vertices.push(decoder.float3()?);
+    }
+
+    let normal_count = decoder.count(12)?;
+    let mut normals = Vec::with_capacity(normal_count);
+    for _ in 0..normal_count {
+        normals.push(decoder.float3()?);
+    }
+
+    let vertex_colors = match decoder.u8()? {
+        0 => None,
+        1 => {
+            let colors = decoder.take(vertex_count.checked_mul(3)?)?;
+            Some(
+                colors
+                    .chunks_exact(3)
+                    .map(|color| [color[0], color[1], color[2]])
+                    .collect(),
+            )
+        }
+        _ => return None,
+    };
+
+    let valid = !faces.is_empty()
+        && faces.iter().all(|face| {
+            face[..3].iter().all(|&i| cast_usize(i) < vertex_count)
+                && face[3..].iter().all(|&i| cast_usize(i) < normal_count)
+        });
+    if !valid {
+        return None;
+    }
+
+    let faces = faces.iter().map(|face| {
+        Face::Triangle(TriangleFace {
+            vertices: (face[0], face[1], face[2]),
+            normals: (face[3], face[4], face[5]),
+        })
+    });
+    let vertices = vertices.iter().map(|v| Point3::new(v[0], v[1], v[2]));
+    let normals = normals.iter().map(|n| Vector3::new(n[0], n[1], n[2]));
+
+    let mesh = Mesh::from_faces_with_vertices_and_normals(faces, vertices, normals);
+    match vertex_colors {
+        Some(vertex_colors) => Some(mesh.with_vertex_colors(vertex_colors)),
+        None => Some(mesh),
+    }
+}
+
+/// Writes the values completed by a long run of the program to the
+/// checkpoint file as the run goes, so that if the app crashes during the
+/// run, the values don't have to be computed again.
+///
+/// The values are encoded and written on a thread of its own, so that
+/// writing large meshes doesn't hold up the interpreter. They are collected
+/// and written all at once in intervals, each write appending to the file.
+pub struct CheckpointWriter {
+    path: PathBuf,
+    entry_sender: channel::Sender<CheckpointEntry>,
+    thread: Option<thread::JoinHandle<bool>>,
+}
+
+impl CheckpointWriter {
+    /// Starts a checkpoint of a run. Nothing is written to the file until
+    /// the interval passes.
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        let (entry_sender, entry_receiver) = channel::unbounded();
+        let thread_path = path.clone();
+
+        let spawn_result = thread::Builder::new()
+            .name(String::from("hurban_selector_checkpoint_writer"))
+            .spawn(move || {
+                match write_entries(&thread_path, interval, &entry_receiver) {
+                    Ok(written) => written,
+                    Err(err) => {
+                        log::error!("Failed to write checkpoint: {}", err);
+                        // The failed write may have left a part of the
+                        // file behind
+                        true
+                    }
+                }
+            });
+
+        // Without the thread, the run is simply not checkpointed
+        let thread = match spawn_result {
+            Ok(thread) => Some(thread),
+            Err(err) => {
+                log::error!("Failed to start checkpoint writer: {}", err);
+                None
+            }
+        };
+
+        Self {
+            path,
+            entry_sender,
+            thread,
+        }
+    }
+
+    /// Sends the value of a completed statement to be written with the
+    /// values sent so far, once the interval passes.
+    pub fn push(&self, var_ident: VarIdent, call: &CallExpr, value: &Value) {
+        // The thread is gone only if it failed, which it already logged
+        let _ = self
+            .entry_sender
+            .send((var_ident, call.clone(), value.clone()));
+    }
+
+    /// Removes the checkpoint file, once the run completed without a crash,
+    /// if the run wrote any. Values not written yet are dropped.
+    pub fn finish(self) -> Result<(), CheckpointError> {
+        drop(self.entry_sender);
+
+        let written = match self.thread {
+            // A panicked thread may have left a part of the file behind
+            Some(thread) => thread.join().unwrap_or(true),
+            None => false,
+        };
+        if written {
+            remove(&self.path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Receives the values of completed statements and writes them to the file,
+/// each time the interval passes. Ends once the sender disconnects, dropping
+/// the values not written yet. Returns whether any values were written.
+fn write_entries(
+    path: &Path,
+    interval: Duration,
+    entry_receiver: &channel::Receiver<CheckpointEntry>,
+) -> Result<bool, CheckpointError> {
+    let mut pending = Vec::new();
+    let mut written = false;
+    let mut write_at = Instant::now() + interval;
+
+    loop {
+        // There is no need to wake up for the write, if there is nothing
+        // to write
+        let received = if pending.is_empty() {
+            entry_receiver
+                .recv()
+                .map_err(|_| channel::RecvTimeoutError::Disconnected)
+        } else {
+            entry_receiver.recv_timeout(write_at.saturating_duration_since(Instant::now()))
+        };
+
+        match received {
+            Ok(entry) => pending.push(entry),
+            Err(channel::RecvTimeoutError::Timeout) => {}
+            Err(channel::RecvTimeoutError::Disconnected) => return Ok(written),
+        }
+
+        if Instant::now() >= write_at {
+            // The first write of the run replaces the checkpoint of an
+            // earlier run
+            write_pending(path, !written, &mut pending)?;
+            written = true;
+            write_at = Instant::now() + interval;
+        }
+    }
+}
+
+fn write_pending(
+    path: &Path,
+    first_write: bool,
+    pending: &mut Vec<CheckpointEntry>,
+) -> Result<(), CheckpointError> {
+    let mut output = Vec::new();
+    for (var_ident, call, value) in pending.drain(..) {
+        encode_record(var_ident, &call, &value, &mut output)?;
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(!first_write)
+        .truncate(first_write)
+        .open(path)?;
+    file.write_all(&output)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Returns the path of the checkpoint file.
+pub fn path() -> Option<PathBuf> {
+    crash::crash_directory().map(|directory| directory.join(FILE_NAME))
+}
+
+/// Loads the values from the checkpoint file, which still match the
+/// statements, i.e. a statement declares their variable with the same call.
+/// Returns no values if there is no file.
+///
+/// Damaged records are skipped, as is the last one, if the crash happened
+/// while writing it.
+pub fn load(
+    path: &Path,
+    stmts: &[Stmt],
+) -> Result<Vec<(VarIdent, CallExpr, Value)>, CheckpointError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut decoder = Decoder::new(&bytes);
+    let mut values = Vec::new();
+    while !decoder.is_empty() {
+        let record = decoder.u32().and_then(|content_len| {
+            let checksum = decoder.u32()?;
+            let content = decoder.take(cast_usize(content_len))?;
+            Some((checksum, content))
+        });
+        let (checksum, content) = match record {
+            Some(record) => record,
+            None => {
+                log::warn!("Skipping checkpoint record cut short");
+                break;
+            }
+        };
+
+        if crc32fast::hash(content) != checksum {
+            log::warn!("Skipping damaged checkpoint record");
+            continue;
+        }
+
+        let (var_ident, call, value) = match decode_record(content) {
+            Some(entry) => entry,
+            None => {
+                log::warn!("Skipping checkpoint record that failed to load");
+                continue;
+            }
+        };
+
+        let matches_stmt = stmts.iter().any(|stmt| {
+            let Stmt::VarDecl(var_decl) = stmt;
+            var_decl.ident() == var_ident && *var_decl.init_expr() == call
+        });
+        if matches_stmt {
+            values.push((var_ident, call, value));
+        }
+    }
+
+    Ok(values)
+}
+
+/// Removes the checkpoint file, if there is one.
+pub fn remove(path: &Path) -> Result<(), CheckpointError> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Rotation3;
+
+    use crate::interpreter::ast::{Expr, LitExpr, VarDeclStmt};
+    use crate::interpreter::FuncIdent;
+    use crate::mesh::primitive;
+
+    use super::*;
+
+    fn call(float: f32) -> CallExpr {
+        CallExpr::new(FuncIdent(0), vec![Expr::Lit(LitExpr::Float(float))])
+    }
+
+    fn stmt(var_ident: u64, float: f32) -> Stmt {
+        Stmt::VarDecl(VarDeclStmt::new(VarIdent(var_ident), call(float)))
+    }
+
+    fn test_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "hurban-selector-test-checkpoint-{}-{}.bin",
+            name,
+            std::process::id(),
+        ))
+    }
+
+    fn record(var_ident: u64, float: f32) -> Vec<u8> {
+        let mut output = Vec::new();
+        encode_record(
+            VarIdent(var_ident),
+            &call(float),
+            &Value::Float(float),
+            &mut output,
+        )
+        .unwrap();
+
+        output
+    }
+
+    #[test]
+    fn test_checkpoint_value_round_trip() {
+        let mesh = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let vertex_count = mesh.vertices().len();
+        let mesh = mesh.with_vertex_colors(vec![[255, 0, 0]; vertex_count]);
+        let values = vec![
+            Value::Nil,
+            Value::Boolean(true),
+            Value::Int(-3),
+            Value::Uint(7),
+            Value::Float3([0.1, -2.5, 1e-7]),
+            Value::String(Arc::new(String::from("tower"))),
+            Value::Mesh(Arc::new(mesh.clone())),
+            Value::MeshArray(Arc::new(MeshArrayValue::new(vec![
+                Arc::new(mesh.clone()),
+                Arc::new(mesh),
+            ]))),
+        ];
+
+        for value in values {
+            let mut output = Vec::new();
+            encode_value(&value, &mut output);
+
+            let mut decoder = Decoder::new(&output);
+            assert_eq!(decode_value(&mut decoder), Some(value));
+            assert!(decoder.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_mesh_with_out_of_bounds_face_is_invalid() {
+        let mut output = Vec::new();
+        output.extend_from_slice(&1u32.to_le_bytes());
+        for index in &[0u32, 1, 3, 0, 0, 0] {
+            output.extend_from_slice(&index.to_le_bytes());
+        }
+        output.extend_from_slice(&3u32.to_le_bytes());
+        encode_floats(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0], &mut output);
+        output.extend_from_slice(&1u32.to_le_bytes());
+        encode_floats(&[0.0, 0.0, 1.0], &mut output);
+        output.push(0);
+
+        assert_eq!(decode_mesh(&mut Decoder::new(&output)), None);
+    }
+
+    #[test]
+    fn test_checkpoint_write_entries_writes_values_matching_stmts() {
+        let path = test_path("write");
+        let (entry_sender, entry_receiver) = channel::unbounded();
+        entry_sender
+            .send((VarIdent(0), call(1.0), Value::Float(2.0)))
+            .unwrap();
+        entry_sender
+            .send((VarIdent(1), call(3.0), Value::Float(4.0)))
+            .unwrap();
+        drop(entry_sender);
+
+        let written = write_entries(&path, Duration::from_secs(0), &entry_receiver).unwrap();
+        assert!(written);
+
+        let values = load(&path, &[stmt(0, 1.0), stmt(1, 5.0)]).unwrap();
+        assert_eq!(values, vec![(VarIdent(0), call(1.0), Value::Float(2.0))]);
+
+        remove(&path).unwrap();
+        assert_eq!(load(&path, &[stmt(0, 1.0)]).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_checkpoint_writer_finish_removes_written_file() {
+        let path = test_path("finish");
+        let writer = CheckpointWriter::new(path.clone(), Duration::from_secs(0));
+        writer.push(VarIdent(0), &call(1.0), &Value::Float(2.0));
+
+        writer.finish().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_checkpoint_writer_does_not_write_before_interval() {
+        let path = test_path("interval");
+        let writer = CheckpointWriter::new(path.clone(), Duration::from_secs(3600));
+        writer.push(VarIdent(0), &call(1.0), &Value::Float(2.0));
+
+        // Finishing waits for the thread, which drops the values not written
+        writer.finish().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_checkpoint_load_skips_damaged_records() {
+        let path = test_path("damaged");
+
+        let mut damaged_record = record(1, 1.0);
+        let last_index = damaged_record.len() - 1;
+        damaged_record[last_index] ^= 0xff;
+
+        let mut bytes = record(0, 1.0);
+        bytes.extend(damaged_record);
+        bytes.extend(record(2, 1.0));
+        // The record being written when the crash happened
+        bytes.extend(&record(3, 1.0)[..10]);
+        fs::write(&path, bytes).unwrap();
+
+        let values = load(
+            &path,
+            &[stmt(0, 1.0), stmt(1, 1.0), stmt(2, 1.0), stmt(3, 1.0)],
+        )
+        .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                (VarIdent(0), call(1.0), Value::Float(1.0)),
+                (VarIdent(2), call(1.0), Value::Float(1.0)),
+            ],
+        );
+
+        remove(&path).unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+use nalgebra::Point3;
+
+use crate::bounding_box::BoundingBox;
+
+/// How much the box fitted to the scene is larger than the scene, relative to
+/// the scene diagonal, so that surfaces on the scene boundary are not clipped.
+const FIT_MARGIN_RELATIVE: f32 = 0.01;
+
+/// Box hiding the geometry outside of it in the viewport, so that the interior
+/// of dense values, e.g. voxel clouds, can be inspected. Does not affect the
+/// geometry of the values, nor exports.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ClipBox {
+    pub enabled: bool,
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl Default for ClipBox {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min: [0.0; 3],
+            max: [0.0; 3],
+        }
+    }
+}
+
+impl ClipBox {
+    /// Whether the box has not been sized yet, or was collapsed to a point.
+    pub fn is_empty(&self) -> bool {
+        self.min == self.max
+    }
+
+    /// Resizes the box to contain the whole scene.
+    pub fn fit(&mut self, scene_bounding_box: &BoundingBox<f32>) {
+        let margin = scene_bounding_box.diagonal().norm() * FIT_MARGIN_RELATIVE;
+        let min = scene_bounding_box.minimum_point();
+        let max = scene_bounding_box.maximum_point();
+
+        self.min = [min.x - margin, min.y - margin, min.z - margin];
+        self.max = [max.x + margin, max.y + margin, max.z + margin];
+    }
+
+    /// Returns the minimum and maximum corner of the box, if it is enabled.
+    /// The coordinates are sorted, so that the box doesn't hide everything
+    /// when a minimum is dragged over its maximum.
+    pub fn corners(&self) -> Option<(Point3<f32>, Point3<f32>)> {
+        if !self.enabled {
+            return None;
+        }
+
+        let mut min = Point3::origin();
+        let mut max = Point3::origin();
+        for axis in 0..3 {
+            min[axis] = f32::min(self.min[axis], self.max[axis]);
+            max[axis] = f32::max(self.min[axis], self.max[axis]);
+        }
+
+        Some((min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip_box_fit_contains_scene() {
+        let scene_bounding_box =
+            BoundingBox::new(&Point3::new(0.0, 0.0, 0.0), &Point3::new(30.0, 40.0, 0.0));
+
+        let mut clip_box = ClipBox::default();
+        assert!(clip_box.is_empty());
+
+        clip_box.fit(&scene_bounding_box);
+        assert!(!clip_box.is_empty());
+        approx::assert_relative_eq!(
+            Point3::from(clip_box.min),
+            Point3::new(-0.5, -0.5, -0.5),
+            epsilon = 0.0001
+        );
+        approx::assert_relative_eq!(
+            Point3::from(clip_box.max),
+            Point3::new(30.5, 40.5, 0.5),
+            epsilon = 0.0001
+        );
+    }
+
+    #[test]
+    fn test_clip_box_corners_are_sorted() {
+        let mut clip_box = ClipBox {
+            enabled: false,
+            min: [2.0, 0.0, -1.0],
+            max: [-2.0, 1.0, 1.0],
+        };
+        assert_eq!(clip_box.corners(), None);
+
+        clip_box.enabled = true;
+        assert_eq!(
+            clip_box.corners(),
+            Some((Point3::new(-2.0, 0.0, -1.0), Point3::new(2.0, 1.0, 1.0))),
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use copypasta::{ClipboardContext, ClipboardProvider};
+
+/// Access to the system clipboard.
+///
+/// On some platforms (notably X11) the clipboard contents are owned by the
+/// application that copied them, so the clipboard must be kept alive for as
+/// long as the copied text should be available to other applications.
+pub struct Clipboard {
+    context: Option<ClipboardContext>,
+}
+
+impl Clipboard {
+    /// Connects to the system clipboard. If the clipboard is not available,
+    /// all operations will fail, but the application can continue running.
+    pub fn new() -> Self {
+        let context = match ClipboardContext::new() {
+            Ok(context) => Some(context),
+            Err(err) => {
+                log::error!("Failed to access system clipboard: {}", err);
+                None
+            }
+        };
+
+        Self { context }
+    }
+
+    /// Returns the text currently in the clipboard, or `None` if the
+    /// clipboard is unavailable or does not contain text.
+    pub fn text(&mut self) -> Option<String> {
+        let context = self.context.as_mut()?;
+        match context.get_contents() {
+            Ok(text) => Some(text),
+            Err(err) => {
+                log::warn!("Failed to read system clipboard: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Places the text into the clipboard. Returns whether it succeeded.
+    pub fn set_text(&mut self, text: String) -> bool {
+        match self.context.as_mut() {
+            Some(context) => match context.set_contents(text) {
+                Ok(()) => true,
+                Err(err) => {
+                    log::error!("Failed to write system clipboard: {}", err);
+                    false
+                }
+            },
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::panic::{self, PanicInfo};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::logger::RecentLog;
+use crate::project::{self, Project, ProjectError};
+
+/// Directory in the user data directory, where the crash bundles and the
+/// project autosave are kept.
+const CRASH_DIRECTORY: &str = "H.U.R.B.A.N. selector/Crashes";
+
+/// Name of the file pointing to the crash bundle not yet seen by the user.
+const PENDING_FILE_NAME: &str = "pending";
+
+const AUTOSAVE_FILE_NAME: &str = "autosave.hurban";
+
+const REPORT_FILE_NAME: &str = "report.txt";
+const LOG_FILE_NAME: &str = "log.txt";
+const PROJECT_FILE_NAME: &str = "project.hurban";
+
+/// A crash bundle written by the panic hook. The bundle is a directory
+/// containing the crash report with the backtrace, the recent log and a copy
+/// of the last autosaved project.
+#[derive(Debug, Clone)]
+pub struct CrashBundle {
+    pub path: PathBuf,
+    pub report: String,
+}
+
+/// Returns the directory, where the crash bundles are written.
+pub fn crash_directory() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|data_dir| data_dir.join(CRASH_DIRECTORY))
+}
+
+/// Installs a panic hook writing a crash bundle, in addition to what the
+/// default hook does. The bundle is offered to the user on the next start.
+pub fn install_panic_hook(recent_log: RecentLog) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        default_hook(panic_info);
+
+        let report = format_report(
+            env!("CARGO_PKG_VERSION"),
+            thread::current().name().unwrap_or("<unnamed>"),
+            &panic_message(panic_info),
+            &format!("{:?}", backtrace::Backtrace::new()),
+        );
+
+        match write_bundle(&report, &recent_log.lines()) {
+            Ok(path) => eprintln!("Crash bundle written to {}", path.to_string_lossy()),
+            Err(err) => eprintln!("Failed to write crash bundle: {}", err),
+        }
+    }));
+}
+
+/// Saves a copy of the project, which is attached to the crash bundle in case
+/// of a crash.
+pub fn autosave_project(project: Project) -> Result<(), ProjectError> {
+    let directory = crash_directory().ok_or(ProjectError::UnexpectedError)?;
+    fs::create_dir_all(&directory)?;
+    project::save(directory.join(AUTOSAVE_FILE_NAME), project, false)?;
+
+    Ok(())
+}
+
+/// Returns the crash bundle written since the user last dismissed one, if
+/// any.
+pub fn pending_bundle() -> Option<CrashBundle> {
+    let directory = crash_directory()?;
+    let bundle_name = fs::read_to_string(directory.join(PENDING_FILE_NAME)).ok()?;
+    let path = directory.join(bundle_name.trim());
+
+    match fs::read_to_string(path.join(REPORT_FILE_NAME)) {
+        Ok(report) => Some(CrashBundle { path, report }),
+        Err(err) => {
+            log::warn!("Failed to read pending crash report: {}", err);
+            None
+        }
+    }
+}
+
+/// Stops offering the pending crash bundle. The bundle itself is kept.
+pub fn dismiss_pending_bundle() -> io::Result<()> {
+    if let Some(directory) = crash_directory() {
+        match fs::remove_file(directory.join(PENDING_FILE_NAME)) {
+            Ok(()) => (),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies the crash bundle into the directory, e.g. so that it can be
+/// attached to a bug report. Returns the path of the copy.
+pub fn save_bundle_to(bundle: &CrashBundle, directory: &Path) -> io::Result<PathBuf> {
+    let bundle_name = bundle
+        .path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid bundle path"))?;
+    let target = directory.join(bundle_name);
+    fs::create_dir_all(&target)?;
+
+    for entry in fs::read_dir(&bundle.path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            fs::copy(entry.path(), target.join(entry.file_name()))?;
+        }
+    }
+
+    Ok(target)
+}
+
+fn write_bundle(report: &str, log_lines: &[String]) -> io::Result<PathBuf> {
+    let directory = crash_directory()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No user data directory"))?;
+    let bundle_name = format!("crash-{}", chrono::Local::now().format("%Y-%m-%d-%H-%M-%S"));
+    let path = directory.join(&bundle_name);
+    fs::create_dir_all(&path)?;
+
+    fs::write(path.join(REPORT_FILE_NAME), report)?;
+    fs::write(path.join(LOG_FILE_NAME), log_lines.join("\n"))?;
+
+    let autosave_path = directory.join(AUTOSAVE_FILE_NAME);
+    if autosave_path.exists() {
+        fs::copy(autosave_path, path.join(PROJECT_FILE_NAME))?;
+    }
+
+    fs::write(directory.join(PENDING_FILE_NAME), &bundle_name)?;
+
+    Ok(path)
+}
+
+fn panic_message(panic_info: &PanicInfo) -> String {
+    let payload = panic_info.payload();
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        *message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "<unknown>"
+    };
+
+    match panic_info.location() {
+        Some(location) => format!("{} at {}", message, location),
+        None => message.to_string(),
+    }
+}
+
+fn format_report(version: &str, thread_name: &str, message: &str, backtrace: &str) -> String {
+    format!(
+        "H.U.R.B.A.N. selector {} crashed on {} {}\n\
+         Time: {}\n\
+         Thread: {}\n\
+         Panic: {}\n\
+         \n\
+         Backtrace:\n\
+         {}\n",
+        version,
+        env::consts::OS,
+        env::consts::ARCH,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        thread_name,
+        message,
+        backtrace,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report_contains_panic_and_backtrace() {
+        let report = format_report("1.2.3", "main", "Oh no at src/lib.rs:1:1", "0: main");
+
+        assert!(report.starts_with("H.U.R.B.A.N. selector 1.2.3 crashed on "));
+        assert!(report.contains("Thread: main\n"));
+        assert!(report.contains("Panic: Oh no at src/lib.rs:1:1\n"));
+        assert!(report.ends_with("Backtrace:\n0: main\n"));
+    }
+}
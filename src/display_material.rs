@@ -0,0 +1,86 @@
+use crate::renderer::Material;
+
+/// How a display material shades the surface of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum DisplayMaterialKind {
+    FlatColor,
+    Matcap,
+    Glass,
+}
+
+impl DisplayMaterialKind {
+    pub const ALL: [DisplayMaterialKind; 3] = [
+        DisplayMaterialKind::FlatColor,
+        DisplayMaterialKind::Matcap,
+        DisplayMaterialKind::Glass,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::FlatColor => "Flat color",
+            Self::Matcap => "Matcap",
+            Self::Glass => "Glass",
+        }
+    }
+}
+
+/// Material overriding how the geometry of a pipeline value is drawn in the
+/// viewport, so that e.g. context geometry can be told apart from the
+/// designed object. Does not affect exports.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DisplayMaterial {
+    pub kind: DisplayMaterialKind,
+    pub color: [f32; 3],
+}
+
+impl Default for DisplayMaterial {
+    fn default() -> Self {
+        Self {
+            kind: DisplayMaterialKind::FlatColor,
+            color: [0.8, 0.8, 0.8],
+        }
+    }
+}
+
+impl DisplayMaterial {
+    /// Returns the renderer material to draw the geometry with. The color of
+    /// the geometry must be set to `DisplayMaterial::gpu_color` first.
+    pub fn renderer_material(&self) -> Material {
+        match self.kind {
+            DisplayMaterialKind::FlatColor => Material::ColoredFlat,
+            DisplayMaterialKind::Matcap => Material::ColoredMatcapShaded,
+            DisplayMaterialKind::Glass => Material::ColoredGlass,
+        }
+    }
+
+    /// Whether the geometry casts shadows. Glass is see-through, so it
+    /// doesn't.
+    pub fn casts_shadows(&self) -> bool {
+        self.kind != DisplayMaterialKind::Glass
+    }
+
+    pub fn gpu_color(&self) -> [f64; 3] {
+        [
+            f64::from(self.color[0]),
+            f64::from(self.color[1]),
+            f64::from(self.color[2]),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_material_glass_is_transparent_without_shadows() {
+        let material = DisplayMaterial {
+            kind: DisplayMaterialKind::Glass,
+            ..DisplayMaterial::default()
+        };
+
+        assert_eq!(material.renderer_material(), Material::ColoredGlass);
+        assert!(!material.casts_shadows());
+        assert!(DisplayMaterial::default().casts_shadows());
+    }
+}
@@ -0,0 +1,303 @@
+use std::error;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::prelude::*;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::interpreter::ast::Stmt;
+
+/// Extension of the event log file, appended to the project file name.
+const EXTENSION: &str = "events";
+
+/// Edits of the same statement following each other within this interval are
+/// recorded as one event, so that dragging a slider doesn't flood the log.
+const COALESCE_INTERVAL_MS: i64 = 1000;
+
+/// A mutation of the program of a session.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum EventKind {
+    /// The session started with the statements, e.g. because a project was
+    /// opened. Logs of multiple sessions can follow each other in one file,
+    /// each starting with a checkpoint.
+    Checkpoint(Vec<Stmt>),
+    PushStmt(Stmt),
+    PopStmt,
+    SetStmtAt(usize, Stmt),
+    SetStmts(Vec<Stmt>),
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventKind::Checkpoint(stmts) => write!(f, "Started with {} operations", stmts.len()),
+            EventKind::PushStmt(_) => write!(f, "Added an operation"),
+            EventKind::PopStmt => write!(f, "Removed the last operation"),
+            EventKind::SetStmtAt(stmt_index, _) => {
+                write!(f, "Edited operation #{}", stmt_index + 1)
+            }
+            EventKind::SetStmts(stmts) => {
+                write!(f, "Replaced the pipeline with {} operations", stmts.len())
+            }
+        }
+    }
+}
+
+/// A recorded mutation of the program.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Event {
+    /// When the mutation happened, in milliseconds since the Unix epoch.
+    pub timestamp_ms: i64,
+    pub kind: EventKind,
+}
+
+#[derive(Debug)]
+pub enum EventLogError {
+    SerializeError(ron::error::Error),
+    IoError(io::Error),
+}
+
+impl error::Error for EventLogError {}
+
+impl fmt::Display for EventLogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventLogError::SerializeError(err) => write!(
+                f,
+                "An error occurred while serializing or deserializing event log: {}",
+                err
+            ),
+            EventLogError::IoError(err) => {
+                write!(f, "An error occurred while accessing event log: {}", err)
+            }
+        }
+    }
+}
+
+impl From<ron::error::Error> for EventLogError {
+    fn from(err: ron::error::Error) -> Self {
+        EventLogError::SerializeError(err)
+    }
+}
+
+impl From<io::Error> for EventLogError {
+    fn from(err: io::Error) -> Self {
+        EventLogError::IoError(err)
+    }
+}
+
+/// Append-only log of the mutations of a program. The log is saved next to
+/// the project file, so that any historical state of the project can be
+/// reconstructed, e.g. to go back further than undo would or to study the
+/// design process.
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    events: Vec<Event>,
+    /// How many of the events are already saved in the file.
+    saved_count: usize,
+}
+
+impl EventLog {
+    /// Creates a log starting with a checkpoint of the statements.
+    pub fn with_checkpoint(stmts: Vec<Stmt>) -> Self {
+        let mut event_log = Self::default();
+        event_log.record(EventKind::Checkpoint(stmts));
+        event_log
+    }
+
+    /// Records the mutation as happening now.
+    pub fn record(&mut self, kind: EventKind) {
+        let timestamp_ms = chrono::Utc::now().timestamp_millis();
+
+        if let EventKind::SetStmtAt(stmt_index, _) = kind {
+            if self.events.len() > self.saved_count {
+                if let Some(last_event) = self.events.last_mut() {
+                    if let EventKind::SetStmtAt(last_stmt_index, _) = last_event.kind {
+                        if last_stmt_index == stmt_index
+                            && timestamp_ms - last_event.timestamp_ms < COALESCE_INTERVAL_MS
+                        {
+                            *last_event = Event { timestamp_ms, kind };
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.events.push(Event { timestamp_ms, kind });
+    }
+
+    /// Returns the events, the oldest first.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Appends the events recorded since the last save to the file.
+    pub fn save(&mut self, path: &Path) -> Result<(), EventLogError> {
+        self.write(path, self.saved_count)
+    }
+
+    /// Writes all events to the file, replacing its contents, e.g. when the
+    /// project is saved to a new path.
+    pub fn save_all(&mut self, path: &Path) -> Result<(), EventLogError> {
+        self.write(path, 0)
+    }
+
+    fn write(&mut self, path: &Path, first_event_index: usize) -> Result<(), EventLogError> {
+        let mut output = String::new();
+        for event in &self.events[first_event_index..] {
+            output.push_str(&ron::ser::to_string(event)?);
+            output.push('\n');
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(first_event_index > 0)
+            .truncate(first_event_index == 0)
+            .open(path)?;
+        file.write_all(output.as_bytes())?;
+        file.flush()?;
+
+        self.saved_count = self.events.len();
+
+        Ok(())
+    }
+}
+
+/// Returns the path of the event log of the project.
+pub fn path_for_project(project_path: &Path) -> PathBuf {
+    let mut file_name = project_path
+        .file_name()
+        .map(|file_name| file_name.to_os_string())
+        .unwrap_or_default();
+    file_name.push(".");
+    file_name.push(EXTENSION);
+
+    project_path.with_file_name(file_name)
+}
+
+/// Loads the event log saved in the file. Returns an empty log if there is
+/// no file yet.
+pub fn load(path: &Path) -> Result<EventLog, EventLogError> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(EventLog::default()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut events = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            events.push(ron::de::from_str(&line)?);
+        }
+    }
+
+    Ok(EventLog {
+        saved_count: events.len(),
+        events,
+    })
+}
+
+/// Reconstructs the statements of the program after the events. Events that
+/// don't fit the reconstructed program, e.g. because the file was edited by
+/// hand, are skipped.
+pub fn replay(events: &[Event]) -> Vec<Stmt> {
+    let mut stmts = Vec::new();
+    for event in events {
+        match &event.kind {
+            EventKind::Checkpoint(checkpoint_stmts) | EventKind::SetStmts(checkpoint_stmts) => {
+                stmts = checkpoint_stmts.clone();
+            }
+            EventKind::PushStmt(stmt) => stmts.push(stmt.clone()),
+            EventKind::PopStmt => {
+                stmts.pop();
+            }
+            EventKind::SetStmtAt(stmt_index, stmt) => {
+                if let Some(current_stmt) = stmts.get_mut(*stmt_index) {
+                    *current_stmt = stmt.clone();
+                }
+            }
+        }
+    }
+
+    stmts
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::ast::{CallExpr, Expr, LitExpr, VarDeclStmt};
+    use crate::interpreter::{FuncIdent, VarIdent};
+
+    use super::*;
+
+    fn stmt(var_ident: u64, float: f32) -> Stmt {
+        Stmt::VarDecl(VarDeclStmt::new(
+            VarIdent(var_ident),
+            CallExpr::new(FuncIdent(0), vec![Expr::Lit(LitExpr::Float(float))]),
+        ))
+    }
+
+    #[test]
+    fn test_event_log_replay() {
+        let mut event_log = EventLog::with_checkpoint(vec![stmt(0, 1.0)]);
+        event_log.record(EventKind::PushStmt(stmt(1, 2.0)));
+        event_log.record(EventKind::SetStmtAt(0, stmt(0, 3.0)));
+        event_log.record(EventKind::PushStmt(stmt(2, 4.0)));
+        event_log.record(EventKind::PopStmt);
+        event_log.record(EventKind::SetStmtAt(5, stmt(5, 5.0)));
+
+        let events = event_log.events();
+        assert_eq!(replay(&events[..1]), vec![stmt(0, 1.0)]);
+        assert_eq!(replay(&events[..3]), vec![stmt(0, 3.0), stmt(1, 2.0)]);
+        assert_eq!(
+            replay(&events[..4]),
+            vec![stmt(0, 3.0), stmt(1, 2.0), stmt(2, 4.0)],
+        );
+        assert_eq!(replay(events), vec![stmt(0, 3.0), stmt(1, 2.0)]);
+    }
+
+    #[test]
+    fn test_event_log_replay_from_last_checkpoint() {
+        let mut event_log = EventLog::with_checkpoint(vec![stmt(0, 1.0)]);
+        event_log.record(EventKind::PushStmt(stmt(1, 2.0)));
+        event_log.record(EventKind::Checkpoint(vec![stmt(3, 3.0)]));
+        event_log.record(EventKind::PushStmt(stmt(4, 4.0)));
+
+        assert_eq!(replay(event_log.events()), vec![stmt(3, 3.0), stmt(4, 4.0)],);
+    }
+
+    #[test]
+    fn test_event_log_record_coalesces_edits_of_same_stmt() {
+        let mut event_log = EventLog::with_checkpoint(vec![stmt(0, 1.0), stmt(1, 1.0)]);
+        event_log.record(EventKind::SetStmtAt(0, stmt(0, 2.0)));
+        event_log.record(EventKind::SetStmtAt(0, stmt(0, 3.0)));
+        event_log.record(EventKind::SetStmtAt(1, stmt(1, 4.0)));
+
+        assert_eq!(event_log.events().len(), 3);
+        assert_eq!(replay(event_log.events()), vec![stmt(0, 3.0), stmt(1, 4.0)],);
+    }
+
+    #[test]
+    fn test_event_log_event_serializes_to_single_line() {
+        let event = Event {
+            timestamp_ms: 1_600_000_000_000,
+            kind: EventKind::SetStmts(vec![stmt(0, 1.0), stmt(1, 2.0)]),
+        };
+
+        let line = ron::ser::to_string(&event).unwrap();
+        assert!(!line.contains('\n'));
+
+        let deserialized: Event = ron::de::from_str(&line).unwrap();
+        assert_eq!(deserialized, event);
+    }
+
+    #[test]
+    fn test_event_log_path_for_project() {
+        assert_eq!(
+            path_for_project(Path::new("/projects/tower.hurban")),
+            PathBuf::from("/projects/tower.hurban.events"),
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use crate::project::{Project, ProjectError};
+
+/// A curated project bundled with the application, showing a typical use of
+/// the operations. Examples only use built-in geometry, so that they open
+/// without any external files.
+#[derive(Debug, Clone, Copy)]
+pub struct ExampleProject {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    project_data: &'static str,
+}
+
+pub static EXAMPLES: &[ExampleProject] = &[
+    ExampleProject {
+        id: "voxel-blend",
+        name: "Voxel blend",
+        description: "A cube morphed into a sphere with a voxel morph.",
+        project_data: include_str!("../resources/examples/voxel_blend.hurban"),
+    },
+    ExampleProject {
+        id: "shrink-wrap-study",
+        name: "Shrink wrap study",
+        description: "A cluster of volumes wrapped in a single smooth envelope \
+                      by voxelizing and relaxing it.",
+        project_data: include_str!("../resources/examples/shrink_wrap_study.hurban"),
+    },
+    ExampleProject {
+        id: "scatter-composition",
+        name: "Scatter composition",
+        description: "Trees and human figures from the library scattered around \
+                      an apartment block.",
+        project_data: include_str!("../resources/examples/scatter_composition.hurban"),
+    },
+];
+
+impl ExampleProject {
+    /// Parses the embedded project of the example.
+    pub fn open(&self) -> Result<Project, ProjectError> {
+        let mut project: Project = ron::de::from_str(self.project_data)?;
+        project.upgrade_retired_funcs();
+
+        Ok(project)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::interpreter::ast::{Expr, LitExpr, Stmt};
+    use crate::interpreter::Ty;
+    use crate::interpreter_funcs;
+
+    use super::*;
+
+    fn lit_ty(lit: &LitExpr) -> Ty {
+        match lit {
+            LitExpr::Boolean(_) => Ty::Boolean,
+            LitExpr::Int(_) => Ty::Int,
+            LitExpr::Uint(_) => Ty::Uint,
+            LitExpr::Float(_) => Ty::Float,
+            LitExpr::Float2(_) => Ty::Float2,
+            LitExpr::Float3(_) => Ty::Float3,
+            LitExpr::String(_) => Ty::String,
+        }
+    }
+
+    #[test]
+    fn test_examples_open_with_well_typed_stmts() {
+        let function_table = interpreter_funcs::create_function_table();
+
+        for example in EXAMPLES {
+            let project = example.open().expect("Example must open");
+            assert!(!project.stmts.is_empty(), "Example {} is empty", example.id);
+
+            let mut var_tys = HashMap::new();
+            for stmt in &project.stmts {
+                let Stmt::VarDecl(var_decl) = stmt;
+                let init_expr = var_decl.init_expr();
+                let func = function_table
+                    .get(&init_expr.ident())
+                    .expect("Example must only call existing functions");
+
+                let arg_count = init_expr.args().len();
+                assert!(
+                    func.accepts_arg_count(arg_count),
+                    "Example {} calls {} with {} args",
+                    example.id,
+                    func.info().name,
+                    arg_count,
+                );
+
+                for (arg_index, arg) in init_expr.args().iter().enumerate() {
+                    let param_index = func
+                        .param_index_for_arg(arg_index, arg_count)
+                        .expect("Argument must have a parameter");
+                    let arg_ty = match arg {
+                        Expr::Lit(lit) => lit_ty(lit),
+                        Expr::Var(var) => *var_tys
+                            .get(&var.ident())
+                            .expect("Example must only use previously declared vars"),
+                    };
+                    assert_eq!(
+                        arg_ty,
+                        func.param_info()[param_index].refinement.ty(),
+                        "Example {} passes a mismatched arg {} to {}",
+                        example.id,
+                        arg_index,
+                        func.info().name,
+                    );
+                }
+
+                var_tys.insert(var_decl.ident(), func.return_ty());
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::convert::{cast_u32, cast_usize};
+use crate::mesh::{Face, Mesh, TriangleFace};
+
+/// Largest explode factor offered in the viewport. With this factor, the
+/// parts are moved twice their distance from the group centroid.
+pub const EXPLODE_FACTOR_MAX: f32 = 2.0;
+
+/// Returns the offsets moving the parts of a group away from the centroid of
+/// the group. Each part moves along the direction from the group centroid to
+/// its own centroid, by the factor times their distance.
+pub fn explode_offsets(part_centroids: &[Point3<f32>], factor: f32) -> Vec<Vector3<f32>> {
+    if part_centroids.is_empty() {
+        return Vec::new();
+    }
+
+    let group_centroid = centroid(part_centroids);
+    part_centroids
+        .iter()
+        .map(|part_centroid| (part_centroid - group_centroid) * factor)
+        .collect()
+}
+
+/// Returns the centroid of the vertices of the mesh, which the mesh moves
+/// away from the group centroid by when exploded.
+pub fn mesh_centroid(mesh: &Mesh) -> Point3<f32> {
+    centroid(mesh.vertices())
+}
+
+/// Splits the mesh into its disjoint components, so that they can be moved
+/// apart when a single mesh is exploded. Components are the groups of faces
+/// connected by shared vertices. A mesh with a single component is returned
+/// as the only part.
+///
+/// The returned meshes are meant for display only, the geometry of the value
+/// is not changed.
+pub fn split_components(mesh: &Mesh) -> Vec<Mesh> {
+    let (vertex_components, component_count) = vertex_components(mesh);
+    if component_count <= 1 {
+        return vec![mesh.clone()];
+    }
+
+    let mut component_faces = vec![Vec::new(); component_count];
+    for face in mesh.faces() {
+        match face {
+            Face::Triangle(triangle_face) => {
+                let component = vertex_components[cast_usize(triangle_face.vertices.0)];
+                component_faces[cast_usize(component)].push(*triangle_face);
+            }
+        }
+    }
+
+    component_faces
+        .iter()
+        .filter(|faces| !faces.is_empty())
+        .map(|faces| component_mesh(mesh, faces))
+        .collect()
+}
+
+/// Returns the mesh made of the faces of a single component, keeping only
+/// the vertices, normals and vertex colors the faces use.
+fn component_mesh(mesh: &Mesh, faces: &[TriangleFace]) -> Mesh {
+    let mut vertex_index_map = HashMap::new();
+    let mut vertex_indices = Vec::new();
+    let mut normal_index_map = HashMap::new();
+    let mut normal_indices = Vec::new();
+
+    let component_faces: Vec<_> = faces
+        .iter()
+        .map(|face| {
+            let (v0, v1, v2) = face.vertices;
+            let (n0, n1, n2) = face.normals;
+            let mut vertex = |index| remap_index(&mut vertex_index_map, &mut vertex_indices, index);
+            let (v0, v1, v2) = (vertex(v0), vertex(v1), vertex(v2));
+            let mut normal = |index| remap_index(&mut normal_index_map, &mut normal_indices, index);
+            let (n0, n1, n2) = (normal(n0), normal(n1), normal(n2));
+
+            Face::Triangle(TriangleFace::new(v0, v1, v2, n0, n1, n2))
+        })
+        .collect();
+
+    let component = Mesh::from_faces_with_vertices_and_normals(
+        component_faces,
+        vertex_indices
+            .iter()
+            .map(|vertex_index| mesh.vertices()[cast_usize(*vertex_index)]),
+        normal_indices
+            .iter()
+            .map(|normal_index| mesh.normals()[cast_usize(*normal_index)]),
+    );
+
+    match mesh.vertex_colors() {
+        Some(vertex_colors) => component.with_vertex_colors(
+            vertex_indices
+                .iter()
+                .map(|vertex_index| vertex_colors[cast_usize(*vertex_index)])
+                .collect(),
+        ),
+        None => component,
+    }
+}
+
+/// Returns the new index of the old index, assigning the next new index to
+/// old indices seen for the first time. The old indices are collected in the
+/// order of their new indices.
+fn remap_index(index_map: &mut HashMap<u32, u32>, old_indices: &mut Vec<u32>, index: u32) -> u32 {
+    *index_map.entry(index).or_insert_with(|| {
+        old_indices.push(index);
+        cast_u32(old_indices.len() - 1)
+    })
+}
+
+/// Assigns each vertex the index of the component it belongs to. Returns the
+/// component indices of the vertices and the number of components.
+fn vertex_components(mesh: &Mesh) -> (Vec<u32>, usize) {
+    let mut parents: Vec<u32> = (0..cast_u32(mesh.vertices().len())).collect();
+
+    for face in mesh.faces() {
+        match face {
+            Face::Triangle(triangle_face) => {
+                let (v0, v1, v2) = triangle_face.vertices;
+                let root0 = find_root(&mut parents, v0);
+                for vertex_index in &[v1, v2] {
+                    let root = find_root(&mut parents, *vertex_index);
+                    parents[cast_usize(root)] = root0;
+                }
+            }
+        }
+    }
+
+    let mut component_indices = vec![u32::MAX; parents.len()];
+    let mut component_count = 0;
+    let vertex_components = (0..cast_u32(parents.len()))
+        .map(|vertex_index| {
+            let root = cast_usize(find_root(&mut parents, vertex_index));
+            if component_indices[root] == u32::MAX {
+                component_indices[root] = cast_u32(component_count);
+                component_count += 1;
+            }
+
+            component_indices[root]
+        })
+        .collect();
+
+    (vertex_components, component_count)
+}
+
+/// Finds the root of the vertex in the union-find forest of the parents.
+fn find_root(parents: &mut [u32], vertex_index: u32) -> u32 {
+    let mut root = vertex_index;
+    while parents[cast_usize(root)] != root {
+        root = parents[cast_usize(root)];
+    }
+
+    // Compress the path, so that the next lookups are fast
+    let mut current = vertex_index;
+    while parents[cast_usize(current)] != root {
+        let next = parents[cast_usize(current)];
+        parents[cast_usize(current)] = root;
+        current = next;
+    }
+
+    root
+}
+
+fn centroid(points: &[Point3<f32>]) -> Point3<f32> {
+    let sum = points
+        .iter()
+        .fold(Vector3::zeros(), |sum, point| sum + point.coords);
+
+    Point3::from(sum / points.len().max(1) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Rotation3;
+
+    use crate::mesh::{primitive, tools};
+
+    use super::*;
+
+    #[test]
+    fn test_explode_offsets_move_parts_away_from_group_centroid() {
+        let offsets = explode_offsets(
+            &[Point3::new(-1.0, 0.0, 0.0), Point3::new(3.0, 0.0, 0.0)],
+            0.5,
+        );
+
+        assert_eq!(
+            offsets,
+            vec![Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0)],
+        );
+    }
+
+    #[test]
+    fn test_explode_split_components_of_disjoint_boxes() {
+        let left = primitive::create_box(
+            Point3::new(-1.0, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let right = primitive::create_box(
+            Point3::new(1.0, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let mesh = tools::join_multiple_meshes(&[left.clone(), right.clone()]);
+
+        let parts = split_components(&mesh);
+        assert_eq!(parts.len(), 2);
+        for (part, expected_part) in parts.iter().zip(&[left, right]) {
+            assert_eq!(part.faces().len(), expected_part.faces().len());
+            assert_eq!(part.vertices().len(), expected_part.vertices().len());
+            assert_eq!(part.bounding_box(), expected_part.bounding_box());
+        }
+
+        let centroids: Vec<_> = parts.iter().map(mesh_centroid).collect();
+        let offsets = explode_offsets(&centroids, 1.0);
+        for (offset, expected_offset) in offsets
+            .iter()
+            .zip(&[Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0)])
+        {
+            approx::assert_relative_eq!(*offset, *expected_offset, epsilon = 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_explode_split_components_keeps_connected_mesh() {
+        let mesh = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+
+        assert_eq!(split_components(&mesh), vec![mesh]);
+    }
+}
@@ -0,0 +1,384 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::annotation::Annotation;
+use crate::clipboard::Clipboard;
+use crate::convert::cast_usize;
+use crate::export_job::{ExportJob, ExportOutcome};
+use crate::exporter;
+use crate::interpreter::VarIdent;
+use crate::mesh::Mesh;
+use crate::notifications::{NotificationLevel, Notifications};
+use crate::scene_graph::SceneGraph;
+use crate::session::Session;
+use crate::units::Unit;
+
+/// Returns the named meshes of the scene to export, split into unused and
+/// used ones.
+#[allow(clippy::type_complexity)]
+pub fn collect_export_models(
+    session: &Session,
+    scene_graph: &SceneGraph,
+) -> (Vec<(String, Arc<Mesh>)>, Vec<(String, Arc<Mesh>)>) {
+    let mut unused_models = Vec::new();
+    let mut used_models = Vec::new();
+    for (value_path, value_node, mesh_node) in scene_graph.iter() {
+        // Values of removed variables stay displayed until the interpreter
+        // runs again, but the session still knows their names
+        let var_name = session
+            .var_name_for_ident(value_path.0)
+            .expect("Displayed values must have a name");
+        let name = if value_path.1 == 0 {
+            // Do not suffix zero mesh-array index
+            var_name.to_string()
+        } else {
+            // Suffix mesh-array index if nonzero
+            format!("{} [{}]", var_name, value_path.1)
+        };
+
+        if value_node.used {
+            used_models.push((name, Arc::clone(&mesh_node.mesh)));
+        } else {
+            unused_models.push((name, Arc::clone(&mesh_node.mesh)));
+        }
+    }
+
+    (unused_models, used_models)
+}
+
+/// Copies the meshes of the variable to the clipboard as OBJ text.
+pub fn copy_var_as_obj(
+    time: Instant,
+    clipboard: &mut Clipboard,
+    session: &Session,
+    scene_graph: &SceneGraph,
+    var_ident: VarIdent,
+    project_unit: Unit,
+    notifications: &mut Notifications,
+) {
+    let meshes: Vec<_> = scene_graph
+        .iter_var(var_ident)
+        .map(|(value_path, mesh_node)| (value_path.1, Arc::clone(&mesh_node.mesh)))
+        .collect();
+
+    if meshes.is_empty() {
+        notifications.push(
+            time,
+            NotificationLevel::Warn,
+            "Nothing to copy. Execute the operation first.",
+        );
+    } else {
+        let name = session
+            .var_name_for_ident(var_ident)
+            .expect("Displayed values must have a name")
+            .to_string();
+        let models = meshes.iter().map(|(index, mesh)| {
+            if *index == 0 {
+                (Cow::Borrowed(name.as_str()), mesh.as_ref())
+            } else {
+                (Cow::Owned(format!("{} [{}]", name, index)), mesh.as_ref())
+            }
+        });
+
+        let mut obj = Vec::new();
+        exporter::export_obj(&mut obj, models, f32::DIGITS, Some(project_unit))
+            .expect("Writing OBJ to memory must not fail");
+        let obj_text = String::from_utf8(obj).expect("OBJ text must be valid UTF-8");
+
+        if clipboard.set_text(obj_text) {
+            notifications.push(
+                time,
+                NotificationLevel::Info,
+                format!("{} copied to clipboard as OBJ.", name),
+            );
+        } else {
+            notifications.push(
+                time,
+                NotificationLevel::Error,
+                "Failed to copy to clipboard",
+            );
+        }
+    }
+}
+
+/// Collects the progress of the exports running on worker threads and
+/// reports the finished and failed ones.
+pub fn poll_export_jobs(
+    time: Instant,
+    export_jobs: &mut Vec<ExportJob>,
+    notifications: &mut Notifications,
+) {
+    let mut export_job_index = 0;
+    while export_job_index < export_jobs.len() {
+        match export_jobs[export_job_index].poll() {
+            ExportOutcome::Pending => export_job_index += 1,
+            ExportOutcome::Finished => {
+                let export_job = export_jobs.remove(export_job_index);
+                log::info!(
+                    "{} exported to: {}",
+                    export_job.description(),
+                    export_job.path(),
+                );
+                notifications.push(
+                    time,
+                    NotificationLevel::Info,
+                    format!(
+                        "{} exported to: {}",
+                        export_job.description(),
+                        export_job.path(),
+                    ),
+                );
+            }
+            ExportOutcome::Failed(err) => {
+                let export_job = export_jobs.remove(export_job_index);
+                log::error!("{} export failed: {}", export_job.description(), err);
+                notifications.push(
+                    time,
+                    NotificationLevel::Error,
+                    format!("{} export failed: {}", export_job.description(), err),
+                );
+            }
+        }
+    }
+}
+
+/// Asks for a file and exports the measurements of the session into it, as
+/// JSON or CSV depending on the file extension.
+pub fn export_measurements(
+    time: Instant,
+    session: &Session,
+    project_path: Option<&Path>,
+    project_unit: Unit,
+    notifications: &mut Notifications,
+) {
+    let suggested_filename = match project_path {
+        Some(path) => match path.file_stem() {
+            Some(file_stem) => {
+                Cow::Owned(format!("{}-measurements.csv", file_stem.to_string_lossy(),))
+            }
+            None => Cow::Borrowed("measurements.csv"),
+        },
+        None => Cow::Borrowed("measurements.csv"),
+    };
+    if let Some(path) = tinyfiledialogs::save_file_dialog_with_filter(
+        "Export Measurements",
+        &suggested_filename,
+        &["*.csv", "*.json"],
+        "CSV or JSON (.csv, .json)",
+    ) {
+        let measurements = session.measurements();
+        let is_json = Path::new(&path).extension().map_or(false, |extension| {
+            extension.to_string_lossy().eq_ignore_ascii_case("json")
+        });
+
+        let result = File::create(&path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            if is_json {
+                exporter::export_measurements_json(&mut writer, measurements, project_unit)
+            } else {
+                exporter::export_measurements_csv(&mut writer, measurements, project_unit)
+            }
+        });
+
+        match result {
+            Ok(()) => {
+                log::info!("Measurements exported to: {}", path);
+                notifications.push(
+                    time,
+                    NotificationLevel::Info,
+                    format!("Measurements exported to: {}", path),
+                );
+            }
+            Err(err) => {
+                log::error!("Measurements export failed: {}", err);
+                notifications.push(
+                    time,
+                    NotificationLevel::Error,
+                    format!("Measurements export failed: {}", err),
+                );
+            }
+        }
+    }
+}
+
+/// Saves the screenshot read from the renderer into the picture directory,
+/// with the annotation drawn over it, if given.
+#[allow(clippy::too_many_arguments)]
+pub fn save_screenshot(
+    time: Instant,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    bytes_per_row_unpadded: u32,
+    bytes_per_row_padded: u32,
+    annotation: Option<Annotation>,
+    notifications: &mut Notifications,
+) {
+    let mut path = match dirs::picture_dir() {
+        Some(picture_dir) => picture_dir,
+        None => {
+            log::error!("Failed to find picture directory");
+            notifications.push(
+                time,
+                NotificationLevel::Warn,
+                "Failed to find picture directory",
+            );
+            return;
+        }
+    };
+    path.push(format!(
+        "hurban_selector-{}.png",
+        chrono::Local::now().format("%Y-%m-%d-%H-%M-%S"),
+    ));
+
+    let result = match annotation {
+        Some(annotation) => {
+            let mut rgba = unpad_image_rows(data, bytes_per_row_unpadded, bytes_per_row_padded);
+            annotation.draw(&mut rgba, width, height);
+
+            encode_and_write_png(
+                &path,
+                &rgba,
+                width,
+                height,
+                bytes_per_row_unpadded,
+                bytes_per_row_unpadded,
+            )
+        }
+        None => encode_and_write_png(
+            &path,
+            data,
+            width,
+            height,
+            bytes_per_row_unpadded,
+            bytes_per_row_padded,
+        ),
+    };
+
+    match result {
+        Ok(()) => {
+            let path_str = path.to_string_lossy();
+            log::info!("Screenshot saved in {}", path_str);
+            notifications.push(
+                time,
+                NotificationLevel::Info,
+                format!("Screenshot saved in {}", path_str),
+            );
+        }
+        Err(err) => {
+            log::error!("Failed writing screenshot: {}", err);
+            notifications.push(
+                time,
+                NotificationLevel::Error,
+                format!("Failed writing screenshot: {}", err),
+            );
+        }
+    }
+}
+
+/// Saves the stitched panorama into the picture directory.
+pub fn save_panorama(
+    time: Instant,
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    notifications: &mut Notifications,
+) {
+    let mut path = match dirs::picture_dir() {
+        Some(picture_dir) => picture_dir,
+        None => {
+            log::error!("Failed to find picture directory");
+            notifications.push(
+                time,
+                NotificationLevel::Warn,
+                "Failed to find picture directory",
+            );
+            return;
+        }
+    };
+    path.push(format!(
+        "hurban_selector-panorama-{}.png",
+        chrono::Local::now().format("%Y-%m-%d-%H-%M-%S"),
+    ));
+
+    let bytes_per_row = width * 4;
+    match encode_and_write_png(&path, rgba, width, height, bytes_per_row, bytes_per_row) {
+        Ok(()) => {
+            let path_str = path.to_string_lossy();
+            log::info!("Panorama saved in {}", path_str);
+            notifications.push(
+                time,
+                NotificationLevel::Info,
+                format!("Panorama saved in {}", path_str),
+            );
+        }
+        Err(err) => {
+            log::error!("Failed writing panorama: {}", err);
+            notifications.push(
+                time,
+                NotificationLevel::Error,
+                format!("Failed writing panorama: {}", err),
+            );
+        }
+    }
+}
+
+pub fn encode_and_write_png(
+    path: &Path,
+    data: &[u8],
+    width: u32,
+    height: u32,
+    bytes_per_row_unpadded: u32,
+    bytes_per_row_padded: u32,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path)?;
+
+    let mut png_encoder = png::Encoder::new(file, width, height);
+    png_encoder.set_color(png::ColorType::RGBA);
+    png_encoder.set_depth(png::BitDepth::Eight);
+
+    // The data we got back from the renderer can
+    // contain padding between rows. We iteratate over
+    // rows and make sure not to copy the padding bytes.
+    let bpr_unpadded = cast_usize(bytes_per_row_unpadded);
+    let bpr_padded = cast_usize(bytes_per_row_padded);
+
+    let mut png_writer = png_encoder
+        .write_header()?
+        .into_stream_writer_with_size(bpr_unpadded);
+
+    for chunk in data.chunks(bpr_padded) {
+        let mut bytes_written = 0;
+        while bytes_written < bpr_unpadded {
+            let written = png_writer.write(&chunk[bytes_written..bpr_unpadded])?;
+            bytes_written += written;
+        }
+    }
+
+    png_writer.finish()?;
+
+    Ok(())
+}
+
+/// Copies image data read from the renderer into a tightly packed buffer,
+/// skipping the padding bytes at the end of each row.
+pub fn unpad_image_rows(
+    data: &[u8],
+    bytes_per_row_unpadded: u32,
+    bytes_per_row_padded: u32,
+) -> Vec<u8> {
+    let bpr_unpadded = cast_usize(bytes_per_row_unpadded);
+    let bpr_padded = cast_usize(bytes_per_row_padded);
+
+    let mut rgba = Vec::with_capacity(data.len() / bpr_padded * bpr_unpadded);
+    for chunk in data.chunks(bpr_padded) {
+        rgba.extend_from_slice(&chunk[..bpr_unpadded]);
+    }
+
+    rgba
+}
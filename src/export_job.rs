@@ -0,0 +1,106 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::thread;
+
+use crossbeam_channel as channel;
+
+/// How an export running on a worker thread ended.
+#[derive(Debug)]
+pub enum ExportOutcome {
+    Pending,
+    Finished,
+    Failed(io::Error),
+}
+
+enum ExportMessage {
+    Progress(f32),
+    Finished(io::Result<()>),
+}
+
+/// An export running on a worker thread, so that exporting large meshes does
+/// not freeze the UI. The job writes the file and reports its progress, which
+/// is collected by polling the job.
+pub struct ExportJob {
+    description: String,
+    path: String,
+    progress: f32,
+    receiver: channel::Receiver<ExportMessage>,
+}
+
+impl ExportJob {
+    /// Spawns a worker thread creating the file at the path and running the
+    /// export function on it. The export function reports progress of the
+    /// export as a fraction between 0 and 1 to its callback.
+    pub fn spawn<F>(description: String, path: String, export: F) -> Self
+    where
+        F: FnOnce(&mut BufWriter<File>, &mut dyn FnMut(f32)) -> io::Result<()> + Send + 'static,
+    {
+        let (sender, receiver) = channel::unbounded();
+        let thread_sender = sender.clone();
+        let thread_path = path.clone();
+
+        let spawn_result = thread::Builder::new()
+            .name(String::from("hurban_selector_exporter"))
+            .spawn(move || {
+                let result = File::create(&thread_path).and_then(|file| {
+                    let mut writer = BufWriter::new(file);
+                    export(&mut writer, &mut |progress| {
+                        // The job may be dropped before the export finishes,
+                        // in which case there is nobody to report to.
+                        let _ = thread_sender.send(ExportMessage::Progress(progress));
+                    })
+                });
+
+                let _ = thread_sender.send(ExportMessage::Finished(result));
+            });
+
+        // The failure to start the export is reported when polling, as any
+        // other export failure.
+        if let Err(err) = spawn_result {
+            let _ = sender.send(ExportMessage::Finished(Err(err)));
+        }
+
+        Self {
+            description,
+            path,
+            progress: 0.0,
+            receiver,
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the last reported progress of the export as a fraction between
+    /// 0 and 1.
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Collects the progress reported by the worker thread. Returns whether
+    /// the export finished or failed.
+    pub fn poll(&mut self) -> ExportOutcome {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(ExportMessage::Progress(progress)) => self.progress = progress,
+                Ok(ExportMessage::Finished(Ok(()))) => {
+                    self.progress = 1.0;
+                    return ExportOutcome::Finished;
+                }
+                Ok(ExportMessage::Finished(Err(err))) => return ExportOutcome::Failed(err),
+                Err(channel::TryRecvError::Empty) => return ExportOutcome::Pending,
+                Err(channel::TryRecvError::Disconnected) => {
+                    return ExportOutcome::Failed(io::Error::new(
+                        io::ErrorKind::Other,
+                        "Exporter thread terminated unexpectedly",
+                    ));
+                }
+            }
+        }
+    }
+}
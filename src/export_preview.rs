@@ -0,0 +1,239 @@
+use std::sync::Arc;
+
+use crate::exporter;
+use crate::mesh::{analysis, Mesh};
+use crate::units::Unit;
+
+/// Prefix of the names of exported used values, so that they can be told
+/// apart from the unused values in other tools.
+const USED_VALUE_NAME_PREFIX: &str = "used_";
+
+/// A value offered for export in the export preview.
+pub struct ExportPreviewValue {
+    pub name: String,
+    pub mesh: Arc<Mesh>,
+    pub triangle_count: usize,
+    /// Whether the value is used by a later operation, i.e. it is an
+    /// intermediate state of the pipeline.
+    pub used: bool,
+    /// Whether the mesh has no border or non-manifold edges. Meshes that are
+    /// not watertight can be rejected by 3D printing and analysis tools.
+    pub watertight: bool,
+    pub included: bool,
+}
+
+/// Values about to be exported. The preview is shown before anything is
+/// written, so that the user can review the values and exclude some of them
+/// from the export.
+pub struct ExportPreview {
+    values: Vec<ExportPreviewValue>,
+    /// Whether used values are exported in addition to the unused ones.
+    pub include_used_values: bool,
+}
+
+impl ExportPreview {
+    /// Creates the preview of the named models, all of them included. Names
+    /// of the used models get prefixed. Only the unused models are exported,
+    /// unless the used values are included too.
+    ///
+    /// Checks the watertightness of each mesh, which takes time for large
+    /// meshes.
+    pub fn new(
+        unused_models: Vec<(String, Arc<Mesh>)>,
+        used_models: Vec<(String, Arc<Mesh>)>,
+    ) -> Self {
+        let unused_values = unused_models
+            .into_iter()
+            .map(|(name, mesh)| (name, mesh, false));
+        let used_values = used_models
+            .into_iter()
+            .map(|(name, mesh)| (format!("{}{}", USED_VALUE_NAME_PREFIX, name), mesh, true));
+
+        let values = unused_values
+            .chain(used_values)
+            .map(|(name, mesh, used)| {
+                let oriented_edges: Vec<_> = mesh.oriented_edges_iter().collect();
+                let edge_sharing_map = analysis::edge_sharing(&oriented_edges);
+                let watertight = analysis::is_mesh_watertight(&edge_sharing_map);
+
+                ExportPreviewValue {
+                    name,
+                    triangle_count: mesh.faces().len(),
+                    used,
+                    mesh,
+                    watertight,
+                    included: true,
+                }
+            })
+            .collect();
+
+        Self {
+            values,
+            include_used_values: false,
+        }
+    }
+
+    /// Returns the values offered for export. The used values are only
+    /// offered if they are included.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut ExportPreviewValue> {
+        let include_used_values = self.include_used_values;
+        self.values
+            .iter_mut()
+            .filter(move |value| include_used_values || !value.used)
+    }
+
+    pub fn has_used_values(&self) -> bool {
+        self.values.iter().any(|value| value.used)
+    }
+
+    /// Whether any value will be exported.
+    pub fn has_included_values(&self) -> bool {
+        self.included_values().next().is_some()
+    }
+
+    pub fn included_triangle_count(&self) -> usize {
+        self.included_values()
+            .map(|value| value.triangle_count)
+            .sum()
+    }
+
+    /// How many of the included values are not watertight.
+    pub fn included_non_watertight_count(&self) -> usize {
+        self.included_values()
+            .filter(|value| !value.watertight)
+            .count()
+    }
+
+    /// Estimates the size in bytes of the OBJ file containing the included
+    /// values.
+    pub fn estimated_obj_size(&self, decimal_precision: u32, unit: Option<Unit>) -> u64 {
+        exporter::estimate_obj_size(
+            self.included_values()
+                .map(|value| (value.name.as_str(), value.mesh.as_ref())),
+            decimal_precision,
+            unit,
+        )
+    }
+
+    /// Returns the names and meshes of the included values.
+    pub fn into_included_models(self) -> Vec<(String, Arc<Mesh>)> {
+        let include_used_values = self.include_used_values;
+        self.values
+            .into_iter()
+            .filter(|value| value.included && (include_used_values || !value.used))
+            .map(|value| (value.name, value.mesh))
+            .collect()
+    }
+
+    fn included_values(&self) -> impl Iterator<Item = &ExportPreviewValue> {
+        self.values
+            .iter()
+            .filter(move |value| value.included && (self.include_used_values || !value.used))
+    }
+}
+
+/// Formats a size in bytes for humans, e.g. `1.5 MB`. A kilobyte is 1024
+/// bytes.
+pub fn format_file_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+
+    let mut size = size as f64 / 1024.0;
+    let mut unit_index = 0;
+    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    format!("{:.1} {}", size, UNITS[unit_index])
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point3, Rotation3, Vector2, Vector3};
+
+    use crate::mesh::primitive;
+    use crate::plane::Plane;
+
+    use super::*;
+
+    #[test]
+    fn test_export_preview_included_values() {
+        let cube = Arc::new(primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        ));
+        let plane = Arc::new(primitive::create_mesh_plane(
+            Plane::from_origin_and_normal(&Point3::origin(), &Vector3::z()),
+            Vector2::new(1.0, 1.0),
+        ));
+
+        let mut export_preview = ExportPreview::new(
+            vec![
+                (String::from("Cube"), Arc::clone(&cube)),
+                (String::from("Plane"), Arc::clone(&plane)),
+            ],
+            Vec::new(),
+        );
+
+        let values: Vec<_> = export_preview.values_mut().collect();
+        assert!(values[0].watertight);
+        assert!(!values[1].watertight);
+        assert_eq!(export_preview.included_triangle_count(), 14);
+        assert_eq!(export_preview.included_non_watertight_count(), 1);
+
+        export_preview
+            .values_mut()
+            .find(|value| value.name == "Plane")
+            .expect("Plane must be offered")
+            .included = false;
+
+        assert_eq!(export_preview.included_triangle_count(), 12);
+        assert_eq!(export_preview.included_non_watertight_count(), 0);
+
+        let models = export_preview.into_included_models();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].0, "Cube");
+    }
+
+    #[test]
+    fn test_export_preview_include_used_values() {
+        let cube = Arc::new(primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        ));
+
+        let mut export_preview = ExportPreview::new(
+            vec![(String::from("Cube"), Arc::clone(&cube))],
+            vec![(String::from("Box"), Arc::clone(&cube))],
+        );
+
+        assert!(export_preview.has_used_values());
+        assert_eq!(export_preview.values_mut().count(), 1);
+        assert_eq!(export_preview.included_triangle_count(), 12);
+
+        export_preview.include_used_values = true;
+
+        assert_eq!(export_preview.values_mut().count(), 2);
+        assert_eq!(export_preview.included_triangle_count(), 24);
+
+        let names: Vec<_> = export_preview
+            .into_included_models()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec![String::from("Cube"), String::from("used_Box")]);
+    }
+
+    #[test]
+    fn test_format_file_size() {
+        assert_eq!(format_file_size(512), "512 B");
+        assert_eq!(format_file_size(1536), "1.5 kB");
+        assert_eq!(format_file_size(5 * 1024 * 1024), "5.0 MB");
+    }
+}
@@ -1,9 +1,15 @@
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
 use std::convert::TryFrom;
 use std::io::{self, Write};
 
-use crate::convert::cast_u32;
+use crate::convert::{cast_u32, cast_u64};
+use crate::interpreter::Measurement;
 use crate::mesh::{Face, Mesh};
+use crate::units::Unit;
+
+/// How many written vertices, normals or faces of an OBJ export make a
+/// progress report.
+const PROGRESS_REPORT_INTERVAL: usize = 65536;
 
 // FIXME: Mesh arrays are currently exported as objects (o). Export them as
 // groups (g).
@@ -11,17 +17,55 @@ use crate::mesh::{Face, Mesh};
 /// Write mesh models serialized in OBJ format to provided output writer.
 ///
 /// Flushes `writer` at least once - after all data has been written. Formats
-/// each floating point number `decimal_precision` digits.
+/// each floating point number `decimal_precision` digits. If `unit` is
+/// provided, it is declared in a comment in the header, so that importing
+/// software can convert the geometry to its own units.
 pub fn export_obj<'a, I, N, W>(
     writer: &mut W,
     models: I,
     decimal_precision: u32,
+    unit: Option<Unit>,
+) -> Result<(), io::Error>
+where
+    I: IntoIterator<Item = (N, &'a Mesh)>,
+    N: Borrow<str>,
+    W: Write,
+{
+    export_obj_with_progress(writer, models, decimal_precision, unit, |_| ())
+}
+
+/// Same as `export_obj`, but also reports the progress of the export as a
+/// fraction between 0 and 1 to the `progress` callback. The progress is
+/// reported periodically, not after each written line.
+pub fn export_obj_with_progress<'a, I, N, W, P>(
+    writer: &mut W,
+    models: I,
+    decimal_precision: u32,
+    unit: Option<Unit>,
+    mut progress: P,
 ) -> Result<(), io::Error>
 where
     I: IntoIterator<Item = (N, &'a Mesh)>,
     N: Borrow<str>,
     W: Write,
+    P: FnMut(f32),
 {
+    let models: Vec<_> = models.into_iter().collect();
+    let element_count: usize = models
+        .iter()
+        .map(|(_, mesh)| mesh.vertices().len() + mesh.normals().len() + mesh.faces().len())
+        .sum();
+    let mut elements_written: usize = 0;
+    let mut report_progress = |elements: usize| {
+        let elements_written_before = elements_written;
+        elements_written += elements;
+        if elements_written / PROGRESS_REPORT_INTERVAL
+            != elements_written_before / PROGRESS_REPORT_INTERVAL
+        {
+            progress(elements_written as f32 / element_count as f32);
+        }
+    };
+
     // usize to u32 conversion can fail on 16-bit systems, in which case we'll
     // use the max value of usize. This probably won't ever happen.
     let decimal_precision = usize::try_from(decimal_precision).unwrap_or(usize::max_value());
@@ -34,6 +78,9 @@ where
     let mut normal_index_offset = 1;
 
     writeln!(writer, "# Exported by H.U.R.B.A.N selector")?;
+    if let Some(unit) = unit {
+        writeln!(writer, "# Units: {}", unit.abbreviation())?;
+    }
     writeln!(writer)?;
 
     for (name, mesh) in models {
@@ -46,6 +93,7 @@ where
                 "v {1:.0$} {2:.0$} {3:.0$}",
                 decimal_precision, vertex.x, vertex.y, vertex.z,
             )?;
+            report_progress(1);
         }
         writeln!(writer)?;
 
@@ -55,6 +103,7 @@ where
                 "vn {1:.0$} {2:.0$} {3:.0$}",
                 decimal_precision, normal.x, normal.y, normal.z,
             )?;
+            report_progress(1);
         }
         writeln!(writer)?;
 
@@ -74,6 +123,7 @@ where
                 normals.1 + normal_index_offset,
                 normals.2 + normal_index_offset,
             )?;
+            report_progress(1);
         }
         writeln!(writer)?;
 
@@ -81,16 +131,185 @@ where
         normal_index_offset += cast_u32(mesh.normals().len());
     }
 
+    writer.flush()?;
+    progress(1.0);
+
+    Ok(())
+}
+
+/// Estimates the size in bytes of the OBJ file `export_obj` writes for the
+/// models, without formatting any of the numbers. All coordinates are assumed
+/// to be as long as the largest coordinate of their mesh, so the estimate is
+/// usually slightly larger than the actual size.
+pub fn estimate_obj_size<'a, I, N>(models: I, decimal_precision: u32, unit: Option<Unit>) -> u64
+where
+    I: IntoIterator<Item = (N, &'a Mesh)>,
+    N: Borrow<str>,
+{
+    let fraction_len = if decimal_precision == 0 {
+        0
+    } else {
+        u64::from(decimal_precision) + 1
+    };
+
+    let mut size = cast_u64("# Exported by H.U.R.B.A.N selector\n\n".len());
+    if let Some(unit) = unit {
+        size += cast_u64(format!("# Units: {}\n", unit.abbreviation()).len());
+    }
+
+    let mut vertex_count = 0;
+    let mut normal_count = 0;
+    for (name, mesh) in models {
+        // "o <name>" followed by an empty line
+        size += cast_u64(name.borrow().len()) + 4;
+
+        let max_coordinate = mesh
+            .vertices()
+            .iter()
+            .map(|vertex| vertex.x.abs().max(vertex.y.abs()).max(vertex.z.abs()))
+            .fold(0.0, f32::max);
+        // Sign, integer part and fraction
+        let coordinate_len = 1 + digit_count(max_coordinate as u64) + fraction_len;
+        let normal_coordinate_len = 2 + fraction_len;
+
+        vertex_count += cast_u64(mesh.vertices().len());
+        normal_count += cast_u64(mesh.normals().len());
+        let face_index_len = digit_count(vertex_count) + 2 + digit_count(normal_count);
+
+        // Each section is followed by an empty line
+        size += cast_u64(mesh.vertices().len()) * (3 * coordinate_len + 5) + 1;
+        size += cast_u64(mesh.normals().len()) * (3 * normal_coordinate_len + 6) + 1;
+        size += cast_u64(mesh.faces().len()) * (3 * face_index_len + 5) + 1;
+    }
+
+    size
+}
+
+fn digit_count(n: u64) -> u64 {
+    let mut count = 1;
+    let mut n = n / 10;
+    while n > 0 {
+        count += 1;
+        n /= 10;
+    }
+
+    count
+}
+
+/// Write measurements serialized as CSV to provided output writer. Each
+/// measurement is paired with the name of the statement that reported it.
+/// Measurements of lengths, areas and volumes are in the given project unit.
+///
+/// Flushes `writer` at least once - after all data has been written.
+pub fn export_measurements_csv<'a, I, N, W>(
+    writer: &mut W,
+    measurements: I,
+    unit: Unit,
+) -> Result<(), io::Error>
+where
+    I: IntoIterator<Item = (N, &'a Measurement)>,
+    N: Borrow<str>,
+    W: Write,
+{
+    writeln!(writer, "statement,measurement,value,unit")?;
+
+    for (stmt_name, measurement) in measurements {
+        writeln!(
+            writer,
+            "{},{},{},{}",
+            csv_field(stmt_name.borrow()),
+            csv_field(&measurement.name),
+            measurement.value,
+            csv_field(&unit.symbol(measurement.dimension)),
+        )?;
+    }
+
+    writer.flush()
+}
+
+/// Write measurements serialized as a JSON array of objects to provided output
+/// writer. Each measurement is paired with the name of the statement that
+/// reported it. Non-finite values are written as `null`. Measurements of
+/// lengths, areas and volumes are in the given project unit, dimensionless
+/// measurements have an empty unit.
+///
+/// Flushes `writer` at least once - after all data has been written.
+pub fn export_measurements_json<'a, I, N, W>(
+    writer: &mut W,
+    measurements: I,
+    unit: Unit,
+) -> Result<(), io::Error>
+where
+    I: IntoIterator<Item = (N, &'a Measurement)>,
+    N: Borrow<str>,
+    W: Write,
+{
+    write!(writer, "[")?;
+
+    for (i, (stmt_name, measurement)) in measurements.into_iter().enumerate() {
+        if i > 0 {
+            write!(writer, ",")?;
+        }
+
+        write!(
+            writer,
+            "\n  {{\"statement\": {}, \"measurement\": {}, \"value\": ",
+            json_string(stmt_name.borrow()),
+            json_string(&measurement.name),
+        )?;
+        if measurement.value.is_finite() {
+            write!(writer, "{}", measurement.value)?;
+        } else {
+            write!(writer, "null")?;
+        }
+        write!(
+            writer,
+            ", \"unit\": {}}}",
+            json_string(&unit.symbol(measurement.dimension)),
+        )?;
+    }
+
+    writeln!(writer, "\n]")?;
+
     writer.flush()
 }
 
+/// Quotes the CSV field, if it contains characters with special meaning.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains(|c: char| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut json = String::with_capacity(string.len() + 2);
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
 #[cfg(test)]
 mod tests {
     use std::iter;
 
-    use nalgebra::{Point3, Vector3};
+    use nalgebra::{Point3, Rotation3, Vector3};
 
-    use crate::mesh::TriangleFace;
+    use crate::interpreter::Dimension;
+    use crate::mesh::{primitive, NormalStrategy, TriangleFace};
 
     use super::*;
 
@@ -124,7 +343,7 @@ mod tests {
             \n";
 
         let mut output = Vec::new();
-        export_obj(&mut output, iter::once((name, &mesh)), 5).unwrap();
+        export_obj(&mut output, iter::once((name, &mesh)), 5, None).unwrap();
 
         assert_eq!(output, Vec::from(expected_output));
     }
@@ -176,9 +395,165 @@ mod tests {
             &mut output,
             [(name1, &mesh1), (name2, &mesh2)].iter().copied(),
             5,
+            None,
         )
         .unwrap();
 
         assert_eq!(output, Vec::from(expected_output));
     }
+
+    #[test]
+    fn test_export_obj_declares_unit() {
+        let mesh = Mesh::from_triangle_faces_with_vertices_and_normals(
+            [TriangleFace::new(0, 1, 2, 0, 0, 0)].iter().copied(),
+            [
+                Point3::new(-0.3, -0.3, 0.0),
+                Point3::new(0.3, -0.3, 0.0),
+                Point3::new(0.0, 0.4, 0.0),
+            ]
+            .iter()
+            .copied(),
+            [Vector3::new(0.0, 0.0, 1.0)].iter().copied(),
+        );
+
+        let expected_header: &[u8] = b"\
+            # Exported by H.U.R.B.A.N selector\n\
+            # Units: mm\n\
+            \n\
+            o Triangle\n";
+
+        let mut output = Vec::new();
+        export_obj(
+            &mut output,
+            iter::once(("Triangle", &mesh)),
+            5,
+            Some(Unit::Millimeter),
+        )
+        .unwrap();
+
+        assert!(output.starts_with(expected_header));
+    }
+
+    #[test]
+    fn test_estimate_obj_size_is_close_to_actual_size() {
+        let sphere = primitive::create_uv_sphere(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(20.0, 20.0, 20.0),
+            16,
+            32,
+            NormalStrategy::Smooth,
+        );
+        let cube = primitive::create_box(
+            Point3::new(5.0, 5.0, 5.0),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let models = vec![("Sphere", &sphere), ("Cube", &cube)];
+
+        let mut output = Vec::new();
+        export_obj(&mut output, models.iter().copied(), 6, Some(Unit::Meter))
+            .expect("Failed to export OBJ");
+        let estimated_size = estimate_obj_size(models.iter().copied(), 6, Some(Unit::Meter));
+
+        let actual_size = output.len() as f64;
+        assert!(estimated_size as f64 >= actual_size * 0.95);
+        assert!(estimated_size as f64 <= actual_size * 1.2);
+    }
+
+    #[test]
+    fn test_export_obj_with_progress_reports_completion() {
+        let mesh = Mesh::from_triangle_faces_with_vertices_and_normals(
+            [TriangleFace::new(0, 1, 2, 0, 0, 0)].iter().copied(),
+            [
+                Point3::new(-0.3, -0.3, 0.0),
+                Point3::new(0.3, -0.3, 0.0),
+                Point3::new(0.0, 0.4, 0.0),
+            ]
+            .iter()
+            .copied(),
+            [Vector3::new(0.0, 0.0, 1.0)].iter().copied(),
+        );
+
+        let mut reported_progress = Vec::new();
+        let mut output = Vec::new();
+        export_obj_with_progress(
+            &mut output,
+            iter::once(("Triangle", &mesh)),
+            5,
+            None,
+            |progress| reported_progress.push(progress),
+        )
+        .unwrap();
+
+        assert_eq!(reported_progress, vec![1.0]);
+    }
+
+    #[test]
+    fn test_export_measurements_csv() {
+        let area = Measurement {
+            name: Cow::Borrowed("Gross floor area"),
+            value: 1250.5,
+            dimension: Dimension::Area,
+        };
+        let volume = Measurement {
+            name: Cow::Borrowed("Volume, total"),
+            value: 3.0,
+            dimension: Dimension::Dimensionless,
+        };
+
+        let expected_output = "\
+            statement,measurement,value,unit\n\
+            Floor Areas #2,Gross floor area,1250.5,m²\n\
+            \"Mesh \"\"A\"\" #3\",\"Volume, total\",3,\n";
+
+        let mut output = Vec::new();
+        export_measurements_csv(
+            &mut output,
+            vec![("Floor Areas #2", &area), ("Mesh \"A\" #3", &volume)],
+            Unit::Meter,
+        )
+        .unwrap();
+
+        assert_eq!(output, Vec::from(expected_output.as_bytes()));
+    }
+
+    #[test]
+    fn test_export_measurements_json() {
+        let area = Measurement {
+            name: Cow::Borrowed("Gross floor area"),
+            value: 1250.5,
+            dimension: Dimension::Area,
+        };
+        let volume = Measurement {
+            name: Cow::Borrowed("Volume"),
+            value: f32::NAN,
+            dimension: Dimension::Volume,
+        };
+
+        let expected_output = "[\n  \
+            {\"statement\": \"Floor Areas #2\", \"measurement\": \"Gross floor area\", \"value\": 1250.5, \"unit\": \"cm²\"},\n  \
+            {\"statement\": \"Mesh \\\"A\\\" #3\", \"measurement\": \"Volume\", \"value\": null, \"unit\": \"cm³\"}\n\
+            ]\n";
+
+        let mut output = Vec::new();
+        export_measurements_json(
+            &mut output,
+            vec![("Floor Areas #2", &area), ("Mesh \"A\" #3", &volume)],
+            Unit::Centimeter,
+        )
+        .unwrap();
+
+        assert_eq!(output, Vec::from(expected_output.as_bytes()));
+    }
+
+    #[test]
+    fn test_export_measurements_json_empty() {
+        let measurements: Vec<(&str, &Measurement)> = Vec::new();
+
+        let mut output = Vec::new();
+        export_measurements_json(&mut output, measurements, Unit::Meter).unwrap();
+
+        assert_eq!(output, Vec::from(&b"[\n]\n"[..]));
+    }
 }
@@ -0,0 +1,512 @@
+//! Math expressions typed into numeric parameter fields.
+//!
+//! Expressions combine numbers with `+`, `-`, `*`, `/`, `%` and `^`
+//! (power), parentheses, the constants `pi` and `e`, and the functions
+//! `sqrt`, `abs`, `sin`, `cos`, `tan`, `asin`, `acos`, `atan`, `ln`, `log`,
+//! `exp`, `floor`, `ceil` and `round`, e.g. `12*0.45+3` or `sqrt(2)/2`.
+//! Trigonometric functions work in degrees, as do the angle parameters.
+//! Parameters with multiple components take one expression per component,
+//! separated by commas.
+
+use std::error;
+use std::f64;
+use std::fmt;
+
+use crate::interpreter::ast::LitExpr;
+use crate::interpreter::ParamRefinement;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+    Empty,
+    UnexpectedEnd,
+    UnexpectedChar(char),
+    InvalidNumber(String),
+    UnknownName(String),
+    NotFinite,
+    OutOfRange(f64),
+    ComponentCount(usize, usize),
+}
+
+impl error::Error for ExpressionError {}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpressionError::Empty => write!(f, "Expression is empty"),
+            ExpressionError::UnexpectedEnd => write!(f, "Expression ends unexpectedly"),
+            ExpressionError::UnexpectedChar(c) => write!(f, "Unexpected \"{}\"", c),
+            ExpressionError::InvalidNumber(number) => write!(f, "Invalid number \"{}\"", number),
+            ExpressionError::UnknownName(name) => {
+                write!(f, "Unknown function or constant \"{}\"", name)
+            }
+            ExpressionError::NotFinite => write!(f, "Result is not a finite number"),
+            ExpressionError::OutOfRange(value) => write!(f, "{} is out of range", value),
+            ExpressionError::ComponentCount(expected, found) => write!(
+                f,
+                "Expected {} comma separated values, found {}",
+                expected, found,
+            ),
+        }
+    }
+}
+
+/// Evaluates the expression to a finite number.
+pub fn evaluate(text: &str) -> Result<f64, ExpressionError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        position: 0,
+    };
+
+    if parser.peek().is_none() {
+        return Err(ExpressionError::Empty);
+    }
+
+    let value = parser.sum()?;
+    if let Some(c) = parser.peek() {
+        return Err(ExpressionError::UnexpectedChar(c));
+    }
+
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(ExpressionError::NotFinite)
+    }
+}
+
+/// Evaluates comma separated expressions, one for each of the components.
+pub fn evaluate_components(
+    text: &str,
+    component_count: usize,
+) -> Result<Vec<f64>, ExpressionError> {
+    let expressions: Vec<_> = text.split(',').collect();
+    if expressions.len() != component_count {
+        return Err(ExpressionError::ComponentCount(
+            component_count,
+            expressions.len(),
+        ));
+    }
+
+    expressions.into_iter().map(evaluate).collect()
+}
+
+/// Returns the number of components of the numeric parameter, or `None` if
+/// the parameter is not numeric.
+pub fn component_count(refinement: &ParamRefinement) -> Option<usize> {
+    match refinement {
+        ParamRefinement::Int(_) | ParamRefinement::Uint(_) | ParamRefinement::Float(_) => Some(1),
+        ParamRefinement::Float2(_) => Some(2),
+        ParamRefinement::Float3(_) => Some(3),
+        _ => None,
+    }
+}
+
+/// Formats the numeric literal as text, which evaluates back to it. Returns
+/// `None` if the literal is not numeric.
+pub fn format_lit(lit: &LitExpr) -> Option<String> {
+    match lit {
+        LitExpr::Int(value) => Some(value.to_string()),
+        LitExpr::Uint(value) => Some(value.to_string()),
+        LitExpr::Float(value) => Some(value.to_string()),
+        LitExpr::Float2([x, y]) => Some(format!("{}, {}", x, y)),
+        LitExpr::Float3([x, y, z]) => Some(format!("{}, {}, {}", x, y, z)),
+        LitExpr::Boolean(_) | LitExpr::String(_) => None,
+    }
+}
+
+/// Evaluates the expression to a literal of the numeric parameter, clamped
+/// to the range of the parameter. Integers are rounded.
+///
+/// # Panics
+///
+/// Panics if the parameter is not numeric.
+pub fn evaluate_lit(text: &str, refinement: &ParamRefinement) -> Result<LitExpr, ExpressionError> {
+    let count = component_count(refinement).expect("Parameter must be numeric");
+    let components = evaluate_components(text, count)?;
+
+    lit_from_components(&components, refinement)
+}
+
+/// Returns the components of the numeric literal, or `None` if the literal
+/// is not numeric.
+pub fn lit_components(lit: &LitExpr) -> Option<Vec<f64>> {
+    match lit {
+        LitExpr::Int(value) => Some(vec![f64::from(*value)]),
+        LitExpr::Uint(value) => Some(vec![f64::from(*value)]),
+        LitExpr::Float(value) => Some(vec![f64::from(*value)]),
+        LitExpr::Float2(value) => Some(value.iter().copied().map(f64::from).collect()),
+        LitExpr::Float3(value) => Some(value.iter().copied().map(f64::from).collect()),
+        LitExpr::Boolean(_) | LitExpr::String(_) => None,
+    }
+}
+
+/// Returns the literal of the numeric parameter with the components,
+/// clamped to the range of the parameter. Integers are rounded.
+///
+/// # Panics
+///
+/// Panics if the parameter is not numeric or if the number of components
+/// doesn't match the parameter.
+pub fn lit_from_components(
+    components: &[f64],
+    refinement: &ParamRefinement,
+) -> Result<LitExpr, ExpressionError> {
+    assert_eq!(
+        Some(components.len()),
+        component_count(refinement),
+        "Components must match the numeric parameter",
+    );
+
+    if let Some(component) = components
+        .iter()
+        .find(|component| (**component as f32).is_infinite())
+    {
+        return Err(ExpressionError::OutOfRange(*component));
+    }
+
+    let lit = match refinement {
+        ParamRefinement::Int(refinement) => {
+            let value = components[0].round();
+            if value < f64::from(i32::MIN) || value > f64::from(i32::MAX) {
+                return Err(ExpressionError::OutOfRange(value));
+            }
+            LitExpr::Int(refinement.clamp(value as i32))
+        }
+        ParamRefinement::Uint(refinement) => {
+            let value = components[0].round();
+            if value < 0.0 || value > f64::from(u32::MAX) {
+                return Err(ExpressionError::OutOfRange(value));
+            }
+            LitExpr::Uint(refinement.clamp(value as u32))
+        }
+        ParamRefinement::Float(refinement) => {
+            LitExpr::Float(refinement.clamp(components[0] as f32))
+        }
+        ParamRefinement::Float2(refinement) => {
+            LitExpr::Float2(refinement.clamp([components[0] as f32, components[1] as f32]))
+        }
+        ParamRefinement::Float3(refinement) => LitExpr::Float3(refinement.clamp([
+            components[0] as f32,
+            components[1] as f32,
+            components[2] as f32,
+        ])),
+        _ => unreachable!("Parameter must be numeric"),
+    };
+
+    Ok(lit)
+}
+
+/// A recursive descent parser evaluating the expression while parsing it.
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    /// Returns the next char, which is not whitespace.
+    fn peek(&mut self) -> Option<char> {
+        while self
+            .chars
+            .get(self.position)
+            .map_or(false, |c| c.is_whitespace())
+        {
+            self.position += 1;
+        }
+
+        self.chars.get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ExpressionError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(c) => Err(ExpressionError::UnexpectedChar(c)),
+            None => Err(ExpressionError::UnexpectedEnd),
+        }
+    }
+
+    /// sum = product { ("+" | "-") product }
+    fn sum(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.product()?;
+        loop {
+            match self.peek() {
+                Some('+') => {
+                    self.position += 1;
+                    value += self.product()?;
+                }
+                Some('-') => {
+                    self.position += 1;
+                    value -= self.product()?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    /// product = unary { ("*" | "/" | "%") unary }
+    fn product(&mut self) -> Result<f64, ExpressionError> {
+        let mut value = self.unary()?;
+        loop {
+            match self.peek() {
+                Some('*') => {
+                    self.position += 1;
+                    value *= self.unary()?;
+                }
+                Some('/') => {
+                    self.position += 1;
+                    value /= self.unary()?;
+                }
+                Some('%') => {
+                    self.position += 1;
+                    value %= self.unary()?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    /// unary = ("-" | "+") unary | power
+    fn unary(&mut self) -> Result<f64, ExpressionError> {
+        match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                Ok(-self.unary()?)
+            }
+            Some('+') => {
+                self.position += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    /// power = atom [ "^" unary ]
+    ///
+    /// The exponent is parsed as unary, so that the power is right
+    /// associative and `-2^2` is `-4`.
+    fn power(&mut self) -> Result<f64, ExpressionError> {
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.position += 1;
+            Ok(base.powf(self.unary()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// atom = number | "(" sum ")" | name [ "(" sum ")" ]
+    fn atom(&mut self) -> Result<f64, ExpressionError> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let value = self.sum()?;
+                self.expect(')')?;
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() => {
+                let name = self.name();
+                if self.peek() == Some('(') {
+                    self.position += 1;
+                    let argument = self.sum()?;
+                    self.expect(')')?;
+                    call(&name, argument)
+                } else {
+                    constant(&name)
+                }
+            }
+            Some(c) => Err(ExpressionError::UnexpectedChar(c)),
+            None => Err(ExpressionError::UnexpectedEnd),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, ExpressionError> {
+        let start = self.position;
+        while self
+            .chars
+            .get(self.position)
+            .map_or(false, |c| c.is_ascii_digit() || *c == '.')
+        {
+            self.position += 1;
+        }
+
+        // Scientific notation, e.g. 1.5e-3
+        if let Some('e') | Some('E') = self.chars.get(self.position) {
+            let mut exponent_end = self.position + 1;
+            if let Some('-') | Some('+') = self.chars.get(exponent_end) {
+                exponent_end += 1;
+            }
+            if self
+                .chars
+                .get(exponent_end)
+                .map_or(false, |c| c.is_ascii_digit())
+            {
+                self.position = exponent_end;
+                while self
+                    .chars
+                    .get(self.position)
+                    .map_or(false, |c| c.is_ascii_digit())
+                {
+                    self.position += 1;
+                }
+            }
+        }
+
+        let text: String = self.chars[start..self.position].iter().collect();
+        text.parse()
+            .map_err(|_| ExpressionError::InvalidNumber(text))
+    }
+
+    fn name(&mut self) -> String {
+        let start = self.position;
+        while self
+            .chars
+            .get(self.position)
+            .map_or(false, |c| c.is_alphanumeric() || *c == '_')
+        {
+            self.position += 1;
+        }
+
+        self.chars[start..self.position]
+            .iter()
+            .collect::<String>()
+            .to_lowercase()
+    }
+}
+
+fn constant(name: &str) -> Result<f64, ExpressionError> {
+    match name {
+        "pi" => Ok(f64::consts::PI),
+        "e" => Ok(f64::consts::E),
+        _ => Err(ExpressionError::UnknownName(name.to_string())),
+    }
+}
+
+fn call(name: &str, argument: f64) -> Result<f64, ExpressionError> {
+    let value = match name {
+        "sqrt" => argument.sqrt(),
+        "abs" => argument.abs(),
+        "sin" => argument.to_radians().sin(),
+        "cos" => argument.to_radians().cos(),
+        "tan" => argument.to_radians().tan(),
+        "asin" => argument.asin().to_degrees(),
+        "acos" => argument.acos().to_degrees(),
+        "atan" => argument.atan().to_degrees(),
+        "ln" => argument.ln(),
+        "log" => argument.log10(),
+        "exp" => argument.exp(),
+        "floor" => argument.floor(),
+        "ceil" => argument.ceil(),
+        "round" => argument.round(),
+        _ => return Err(ExpressionError::UnknownName(name.to_string())),
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{
+        Float2ParamRefinement, Float3ParamRefinement, IntParamRefinement, UintParamRefinement,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_expression_evaluate_arithmetic() {
+        approx::assert_relative_eq!(evaluate("12*0.45+3").unwrap(), 8.4, epsilon = 1e-12);
+        approx::assert_relative_eq!(evaluate(" 2 + 3 * (4 - 1) ").unwrap(), 11.0);
+        approx::assert_relative_eq!(evaluate("-2^2").unwrap(), -4.0);
+        approx::assert_relative_eq!(evaluate("2^3^2").unwrap(), 512.0);
+        approx::assert_relative_eq!(evaluate("2^-1").unwrap(), 0.5);
+        approx::assert_relative_eq!(evaluate("7 % 4").unwrap(), 3.0);
+        approx::assert_relative_eq!(evaluate("1.5e-3*1E3").unwrap(), 1.5);
+        approx::assert_relative_eq!(evaluate(".5").unwrap(), 0.5);
+    }
+
+    #[test]
+    fn test_expression_evaluate_functions_and_constants() {
+        approx::assert_relative_eq!(evaluate("sqrt(2)").unwrap(), 2f64.sqrt());
+        approx::assert_relative_eq!(evaluate("2*PI").unwrap(), 2.0 * f64::consts::PI);
+        approx::assert_relative_eq!(evaluate("sin(30)").unwrap(), 0.5, epsilon = 1e-12);
+        approx::assert_relative_eq!(evaluate("atan(1)").unwrap(), 45.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(evaluate("round(2.6) + abs(-1)").unwrap(), 4.0);
+    }
+
+    #[test]
+    fn test_expression_evaluate_errors() {
+        assert_eq!(evaluate("  "), Err(ExpressionError::Empty));
+        assert_eq!(evaluate("2 +"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(evaluate("(2"), Err(ExpressionError::UnexpectedEnd));
+        assert_eq!(evaluate("2 3"), Err(ExpressionError::UnexpectedChar('3')));
+        assert_eq!(evaluate("2 $ 3"), Err(ExpressionError::UnexpectedChar('$')));
+        assert_eq!(
+            evaluate("1.2.3"),
+            Err(ExpressionError::InvalidNumber(String::from("1.2.3"))),
+        );
+        assert_eq!(
+            evaluate("foo(2)"),
+            Err(ExpressionError::UnknownName(String::from("foo"))),
+        );
+        assert_eq!(evaluate("1/0"), Err(ExpressionError::NotFinite));
+        assert_eq!(evaluate("sqrt(-1)"), Err(ExpressionError::NotFinite));
+    }
+
+    #[test]
+    fn test_expression_evaluate_components() {
+        assert_eq!(
+            evaluate_components("1, 2*2, 3^2", 3),
+            Ok(vec![1.0, 4.0, 9.0]),
+        );
+        assert_eq!(
+            evaluate_components("1, 2", 3),
+            Err(ExpressionError::ComponentCount(3, 2)),
+        );
+    }
+
+    #[test]
+    fn test_expression_evaluate_lit_clamps_and_rounds() {
+        let uint_refinement = ParamRefinement::Uint(UintParamRefinement {
+            default_value: Some(1),
+            min_value: Some(1),
+            max_value: Some(10),
+        });
+        assert_eq!(
+            evaluate_lit("2.6*2", &uint_refinement),
+            Ok(LitExpr::Uint(5))
+        );
+        assert_eq!(
+            evaluate_lit("-3", &uint_refinement),
+            Err(ExpressionError::OutOfRange(-3.0))
+        );
+        assert_eq!(evaluate_lit("100", &uint_refinement), Ok(LitExpr::Uint(10)));
+
+        let int_refinement = ParamRefinement::Int(IntParamRefinement::default());
+        assert_eq!(evaluate_lit("-2^2", &int_refinement), Ok(LitExpr::Int(-4)));
+        assert_eq!(
+            evaluate_lit("3e9", &int_refinement),
+            Err(ExpressionError::OutOfRange(3e9)),
+        );
+
+        let float3_refinement = ParamRefinement::Float3(Float3ParamRefinement {
+            min_value: Some(0.0),
+            ..Float3ParamRefinement::default()
+        });
+        assert_eq!(
+            evaluate_lit("1/4, -1, 12*0.5", &float3_refinement),
+            Ok(LitExpr::Float3([0.25, 0.0, 6.0])),
+        );
+    }
+
+    #[test]
+    fn test_expression_format_lit_evaluates_back() {
+        let float2_refinement = ParamRefinement::Float2(Float2ParamRefinement::default());
+        let lit = LitExpr::Float2([0.1, -2.5]);
+        let text = format_lit(&lit).expect("Float2 must be numeric");
+
+        assert_eq!(text, "0.1, -2.5");
+        assert_eq!(evaluate_lit(&text, &float2_refinement), Ok(lit));
+        assert!(format_lit(&LitExpr::Boolean(true)).is_none());
+    }
+}
@@ -0,0 +1,340 @@
+//! Bulk changes of numeric parameter values across the whole pipeline.
+//!
+//! A query finds the literal arguments of numeric parameters, optionally
+//! narrowed down to an operation, a parameter name and a value, e.g. every
+//! Relax iteration count of 3. The found values are then replaced by a new
+//! value, typed as a math expression evaluated for each of the parameters.
+
+use std::collections::BTreeMap;
+
+use crate::expression::{self, ExpressionError};
+use crate::interpreter::ast::{Expr, LitExpr, Stmt};
+use crate::interpreter::{Func, FuncIdent, ParamInfo};
+
+/// Narrows down the parameters to find. Parts of the query which are `None`
+/// match anything.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FindQuery<'a> {
+    pub func_ident: Option<FuncIdent>,
+    pub param_name: Option<&'static str>,
+    /// The value as a math expression, evaluated separately for each
+    /// parameter, so that e.g. `3` finds both integer and float values.
+    pub value: Option<&'a str>,
+}
+
+/// A found parameter and its value after the replacement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub stmt_index: usize,
+    pub arg_index: usize,
+    pub value: LitExpr,
+    /// The replacing value clamped to the range of the parameter, or the
+    /// reason the replacing expression doesn't fit the parameter.
+    pub new_value: Result<LitExpr, ExpressionError>,
+}
+
+/// Returns the idents of the operations used in the pipeline, which have
+/// numeric parameters, in the order of their first use.
+pub fn used_funcs(
+    stmts: &[Stmt],
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+) -> Vec<FuncIdent> {
+    let mut func_idents = Vec::new();
+    for stmt in stmts {
+        let Stmt::VarDecl(var_decl) = stmt;
+        let func_ident = var_decl.init_expr().ident();
+        let func = &function_table[&func_ident];
+
+        if !func_idents.contains(&func_ident) && func.param_info().iter().any(numeric) {
+            func_idents.push(func_ident);
+        }
+    }
+
+    func_idents
+}
+
+/// Returns the sorted names of the numeric parameters of the operation, or of
+/// all operations used in the pipeline.
+pub fn param_names(
+    stmts: &[Stmt],
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+    func_ident: Option<FuncIdent>,
+) -> Vec<&'static str> {
+    let func_idents = match func_ident {
+        Some(func_ident) => vec![func_ident],
+        None => used_funcs(stmts, function_table),
+    };
+
+    let mut names: Vec<_> = func_idents
+        .iter()
+        .flat_map(|func_ident| function_table[func_ident].param_info())
+        .filter(|param_info| numeric(param_info))
+        .map(|param_info| param_info.name)
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+
+    names
+}
+
+/// Finds the literal arguments of the numeric parameters matching the query
+/// and evaluates their replacing value.
+///
+/// The value of the query is evaluated the same way as the replacing value,
+/// including clamping to the range of the parameter. Parameters for which it
+/// doesn't evaluate, e.g. because of a different number of components, are
+/// not found.
+pub fn find_replacements(
+    stmts: &[Stmt],
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+    query: FindQuery,
+    new_value: &str,
+) -> Vec<Replacement> {
+    let mut replacements = Vec::new();
+
+    for (stmt_index, stmt) in stmts.iter().enumerate() {
+        let Stmt::VarDecl(var_decl) = stmt;
+        let init_expr = var_decl.init_expr();
+        if query
+            .func_ident
+            .map_or(false, |func_ident| func_ident != init_expr.ident())
+        {
+            continue;
+        }
+
+        let func = &function_table[&init_expr.ident()];
+        let arg_count = init_expr.args().len();
+        for (arg_index, arg) in init_expr.args().iter().enumerate() {
+            let lit = match arg {
+                Expr::Lit(lit) => lit,
+                Expr::Var(_) => continue,
+            };
+            let param_info = match func.param_index_for_arg(arg_index, arg_count) {
+                Some(param_index) => &func.param_info()[param_index],
+                None => continue,
+            };
+
+            if !numeric(param_info)
+                || query
+                    .param_name
+                    .map_or(false, |param_name| param_name != param_info.name)
+            {
+                continue;
+            }
+
+            if let Some(value) = query.value {
+                match expression::evaluate_lit(value, &param_info.refinement) {
+                    Ok(value_lit) if value_lit == *lit => (),
+                    _ => continue,
+                }
+            }
+
+            replacements.push(Replacement {
+                stmt_index,
+                arg_index,
+                value: lit.clone(),
+                new_value: expression::evaluate_lit(new_value, &param_info.refinement),
+            });
+        }
+    }
+
+    replacements
+}
+
+/// Returns the statement with the valid replacements of its arguments
+/// applied, or `None` if they don't change the statement. The replacements
+/// of other statements are ignored.
+pub fn replaced_stmt(
+    stmts: &[Stmt],
+    stmt_index: usize,
+    replacements: &[Replacement],
+) -> Option<Stmt> {
+    let Stmt::VarDecl(var_decl) = &stmts[stmt_index];
+    let mut init_expr = var_decl.init_expr().clone();
+
+    for replacement in replacements
+        .iter()
+        .filter(|replacement| replacement.stmt_index == stmt_index)
+    {
+        if let Ok(new_value) = &replacement.new_value {
+            init_expr =
+                init_expr.clone_with_arg_at(replacement.arg_index, Expr::Lit(new_value.clone()));
+        }
+    }
+
+    if init_expr == *var_decl.init_expr() {
+        None
+    } else {
+        Some(Stmt::VarDecl(var_decl.clone_with_init_expr(init_expr)))
+    }
+}
+
+/// Whether the parameter takes a numeric value typed into a plain input,
+/// unlike e.g. choices of a combo box.
+fn numeric(param_info: &ParamInfo) -> bool {
+    param_info.widget.choices().is_none()
+        && expression::component_count(&param_info.refinement).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::ast::{CallExpr, VarDeclStmt, VarExpr, VarIdent};
+    use crate::interpreter_funcs;
+
+    use super::*;
+
+    fn create_box_stmt(var: u64, scale: f32) -> Stmt {
+        Stmt::VarDecl(VarDeclStmt::new(
+            VarIdent(var),
+            CallExpr::new(
+                interpreter_funcs::FUNC_ID_CREATE_BOX,
+                vec![
+                    Expr::Lit(LitExpr::Float3([0.0, 0.0, 0.0])),
+                    Expr::Lit(LitExpr::Float3([0.0, 0.0, 0.0])),
+                    Expr::Lit(LitExpr::Float3([scale, scale, scale])),
+                    Expr::Lit(LitExpr::Boolean(false)),
+                ],
+            ),
+        ))
+    }
+
+    fn relax_stmt(var: u64, mesh_var: u64, iterations: u32) -> Stmt {
+        Stmt::VarDecl(VarDeclStmt::new(
+            VarIdent(var),
+            CallExpr::new(
+                interpreter_funcs::FUNC_ID_LAPLACIAN_SMOOTHING,
+                vec![
+                    Expr::Var(VarExpr::new(VarIdent(mesh_var))),
+                    Expr::Lit(LitExpr::Uint(iterations)),
+                    Expr::Lit(LitExpr::Uint(1)),
+                    Expr::Lit(LitExpr::Boolean(false)),
+                ],
+            ),
+        ))
+    }
+
+    fn stmts() -> Vec<Stmt> {
+        vec![
+            create_box_stmt(0, 3.0),
+            relax_stmt(1, 0, 3),
+            relax_stmt(2, 1, 2),
+            relax_stmt(3, 2, 3),
+        ]
+    }
+
+    #[test]
+    fn test_find_replace_find_replacements_by_value() {
+        let function_table = interpreter_funcs::create_function_table();
+        let query = FindQuery {
+            func_ident: Some(interpreter_funcs::FUNC_ID_LAPLACIAN_SMOOTHING),
+            param_name: Some("Iterations"),
+            value: Some("1+2"),
+        };
+
+        let replacements = find_replacements(&stmts(), &function_table, query, "5");
+
+        assert_eq!(
+            replacements,
+            vec![
+                Replacement {
+                    stmt_index: 1,
+                    arg_index: 1,
+                    value: LitExpr::Uint(3),
+                    new_value: Ok(LitExpr::Uint(5)),
+                },
+                Replacement {
+                    stmt_index: 3,
+                    arg_index: 1,
+                    value: LitExpr::Uint(3),
+                    new_value: Ok(LitExpr::Uint(5)),
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_find_replace_find_replacements_across_funcs() {
+        let function_table = interpreter_funcs::create_function_table();
+        let query = FindQuery {
+            value: Some("3"),
+            ..FindQuery::default()
+        };
+
+        let replacements = find_replacements(&stmts(), &function_table, query, "-1");
+        let found: Vec<_> = replacements
+            .iter()
+            .map(|replacement| (replacement.stmt_index, replacement.arg_index))
+            .collect();
+
+        // The single value doesn't evaluate for the box scale with three
+        // components
+        assert_eq!(found, vec![(1, 1), (3, 1)]);
+        assert_eq!(
+            replacements[0].new_value,
+            Err(ExpressionError::OutOfRange(-1.0)),
+        );
+
+        let replacements = find_replacements(
+            &stmts(),
+            &function_table,
+            FindQuery {
+                value: Some("3, 3, 3"),
+                ..FindQuery::default()
+            },
+            "1, 2, 3",
+        );
+        assert_eq!(replacements.len(), 1);
+        assert_eq!(replacements[0].stmt_index, 0);
+        assert_eq!(
+            replacements[0].new_value,
+            Ok(LitExpr::Float3([1.0, 2.0, 3.0])),
+        );
+    }
+
+    #[test]
+    fn test_find_replace_replaced_stmt() {
+        let function_table = interpreter_funcs::create_function_table();
+        let stmts = stmts();
+        let query = FindQuery {
+            func_ident: Some(interpreter_funcs::FUNC_ID_LAPLACIAN_SMOOTHING),
+            ..FindQuery::default()
+        };
+
+        let replacements = find_replacements(&stmts, &function_table, query, "3");
+        assert_eq!(replacements.len(), 3);
+
+        // Values already equal to the replacement are not changed
+        assert_eq!(replaced_stmt(&stmts, 1, &replacements), None);
+        assert_eq!(
+            replaced_stmt(&stmts, 2, &replacements),
+            Some(relax_stmt(2, 1, 3)),
+        );
+        assert_eq!(replaced_stmt(&stmts, 0, &replacements), None);
+    }
+
+    #[test]
+    fn test_find_replace_used_funcs_and_param_names() {
+        let function_table = interpreter_funcs::create_function_table();
+        let stmts = stmts();
+
+        assert_eq!(
+            used_funcs(&stmts, &function_table),
+            vec![
+                interpreter_funcs::FUNC_ID_CREATE_BOX,
+                interpreter_funcs::FUNC_ID_LAPLACIAN_SMOOTHING,
+            ],
+        );
+        assert_eq!(
+            param_names(
+                &stmts,
+                &function_table,
+                Some(interpreter_funcs::FUNC_ID_LAPLACIAN_SMOOTHING),
+            ),
+            vec!["Iterations"],
+        );
+        assert_eq!(
+            param_names(&stmts, &function_table, None),
+            vec!["Center", "Iterations", "Rotate", "Scale"],
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::time::{Duration, Instant};
+
+const DEFAULT_IDLE_FPS: u32 = 4;
+
+/// How long the application stays active after the last input, so that UI
+/// hover effects and tooltips have time to react.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Limits how often frames are drawn, so that the application doesn't spend
+/// a full CPU core and battery redrawing a scene that doesn't change.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FrameLimiter {
+    /// Maximum frame rate while the application is active. Zero means no
+    /// limit other than the display refresh rate.
+    pub max_fps: u32,
+    /// Whether to drop to the idle frame rate, when there is no input and
+    /// nothing is running or animating.
+    pub idle_enabled: bool,
+    pub idle_fps: u32,
+}
+
+impl Default for FrameLimiter {
+    fn default() -> Self {
+        Self {
+            max_fps: 0,
+            idle_enabled: true,
+            idle_fps: DEFAULT_IDLE_FPS,
+        }
+    }
+}
+
+impl FrameLimiter {
+    /// Returns when the frame after the frame started at `frame_time` should
+    /// be drawn, or `None` if it should be drawn as soon as possible.
+    ///
+    /// The application is idle, if it is not `busy` running or animating
+    /// anything and there was no input for a while since `last_input_time`.
+    pub fn next_frame_time(
+        &self,
+        frame_time: Instant,
+        last_input_time: Instant,
+        busy: bool,
+    ) -> Option<Instant> {
+        let idle = self.idle_enabled
+            && !busy
+            && frame_time.saturating_duration_since(last_input_time) >= IDLE_TIMEOUT;
+        let fps = if idle { self.idle_fps } else { self.max_fps };
+
+        if fps == 0 {
+            None
+        } else {
+            Some(frame_time + Duration::from_secs_f64(1.0 / f64::from(fps)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_limiter_next_frame_time_idle() {
+        let frame_limiter = FrameLimiter::default();
+        let last_input_time = Instant::now();
+        let frame_time = last_input_time + Duration::from_secs(10);
+
+        assert_eq!(
+            frame_limiter.next_frame_time(frame_time, last_input_time, false),
+            Some(frame_time + Duration::from_millis(250)),
+        );
+        assert_eq!(
+            frame_limiter.next_frame_time(frame_time, last_input_time, true),
+            None,
+        );
+        assert_eq!(
+            frame_limiter.next_frame_time(frame_time, frame_time, false),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_frame_limiter_next_frame_time_max_fps() {
+        let frame_limiter = FrameLimiter {
+            max_fps: 50,
+            idle_enabled: false,
+            idle_fps: DEFAULT_IDLE_FPS,
+        };
+        let last_input_time = Instant::now();
+        let frame_time = last_input_time + Duration::from_secs(10);
+
+        assert_eq!(
+            frame_limiter.next_frame_time(frame_time, last_input_time, false),
+            Some(frame_time + Duration::from_millis(20)),
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use nalgebra::Vector3;
+
+use crate::math;
+use crate::sun::{self, SunPosition};
+
+/// The longitude used until the user sets their own, roughly that of central
+/// Europe.
+pub const DEFAULT_LONGITUDE: f32 = 17.11;
+
+/// Where the project site is on Earth and how the scene is oriented towards
+/// the true north.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Geolocation {
+    /// Latitude in degrees, positive on the northern hemisphere.
+    pub latitude: f32,
+    /// Longitude in degrees, positive east of Greenwich.
+    pub longitude: f32,
+    /// Angle of the true north from the scene +Y axis in degrees, measured
+    /// clockwise when looking down at the ground.
+    pub north_angle: f32,
+}
+
+impl Default for Geolocation {
+    fn default() -> Self {
+        Self {
+            latitude: sun::DEFAULT_LATITUDE,
+            longitude: DEFAULT_LONGITUDE,
+            north_angle: 0.0,
+        }
+    }
+}
+
+impl Geolocation {
+    /// Returns the unit direction of the true north in the scene. The
+    /// direction lies in the ground plane.
+    pub fn north_direction(&self) -> Vector3<f32> {
+        let angle = self.north_angle.to_radians();
+        Vector3::new(angle.sin(), angle.cos(), 0.0)
+    }
+
+    /// Converts the sun position with azimuth measured from the true north
+    /// to the sun position with azimuth measured from the scene +Y axis.
+    pub fn scene_sun_position(&self, sun_position: SunPosition) -> SunPosition {
+        let azimuth = (sun_position.azimuth + self.north_angle.to_radians()) % math::TAU;
+
+        SunPosition {
+            azimuth: if azimuth < 0.0 {
+                azimuth + math::TAU
+            } else {
+                azimuth
+            },
+            altitude: sun_position.altitude,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32;
+
+    use super::*;
+
+    #[test]
+    fn test_geolocation_north_direction() {
+        let geolocation = Geolocation {
+            north_angle: 90.0,
+            ..Geolocation::default()
+        };
+
+        let north = geolocation.north_direction();
+        approx::assert_relative_eq!(north, Vector3::x(), epsilon = 0.0001);
+
+        let north = Geolocation::default().north_direction();
+        approx::assert_relative_eq!(north, Vector3::y(), epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_geolocation_scene_sun_position_follows_north() {
+        let geolocation = Geolocation {
+            north_angle: -90.0,
+            ..Geolocation::default()
+        };
+
+        // The true north is along the scene -X axis, so the sun in the true
+        // south is in the direction of the scene +X axis
+        let sun_position = SunPosition {
+            azimuth: f32::consts::PI,
+            altitude: 0.0,
+        };
+        let scene_sun_position = geolocation.scene_sun_position(sun_position);
+        let light_direction = scene_sun_position.light_direction();
+        let expected_azimuth = f32::consts::FRAC_PI_2;
+
+        approx::assert_relative_eq!(
+            scene_sun_position.azimuth,
+            expected_azimuth,
+            epsilon = 0.0001
+        );
+        approx::assert_relative_eq!(light_direction, -Vector3::x(), epsilon = 0.0001);
+    }
+}
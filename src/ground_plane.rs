@@ -0,0 +1,114 @@
+use nalgebra::{Point3, Vector2, Vector3};
+
+use crate::bounding_box::BoundingBox;
+use crate::mesh::{primitive, Mesh};
+use crate::plane::Plane;
+
+/// Smallest side length of the automatically sized ground plane.
+const AUTO_EXTENT_MIN: f32 = 1000.0;
+
+/// How many times the automatically sized ground plane is larger than the
+/// scene diagonal.
+const AUTO_EXTENT_SCENE_MULTIPLIER: f32 = 100.0;
+
+/// How the ground plane is drawn in the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct GroundPlaneOptions {
+    pub visible: bool,
+    /// Whether only the shadows cast on the ground plane are drawn, not the
+    /// plane itself.
+    pub shadow_catcher_only: bool,
+    /// Whether the size of the ground plane follows the size of the scene.
+    pub auto_extent: bool,
+    /// Side length of the ground plane, if not sized automatically.
+    pub fixed_extent: f32,
+    /// Color of the ground plane, including its opacity.
+    pub color: [f32; 4],
+}
+
+impl Default for GroundPlaneOptions {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            shadow_catcher_only: false,
+            auto_extent: true,
+            fixed_extent: AUTO_EXTENT_MIN,
+            color: [0.0, 0.0, 0.0, 0.1],
+        }
+    }
+}
+
+impl GroundPlaneOptions {
+    /// Returns the side length of the ground plane for the scene.
+    pub fn extent(&self, scene_bounding_box: &BoundingBox<f32>) -> f32 {
+        if self.auto_extent {
+            f32::max(
+                AUTO_EXTENT_MIN,
+                scene_bounding_box.diagonal().norm() * AUTO_EXTENT_SCENE_MULTIPLIER,
+            )
+        } else {
+            self.fixed_extent
+        }
+    }
+
+    /// Returns the color to draw the ground plane with. The shadow catcher is
+    /// fully transparent, so that only the shadows show.
+    pub fn material_color(&self) -> [f64; 4] {
+        if self.shadow_catcher_only {
+            [0.0, 0.0, 0.0, 0.0]
+        } else {
+            [
+                f64::from(self.color[0]),
+                f64::from(self.color[1]),
+                f64::from(self.color[2]),
+                f64::from(self.color[3]),
+            ]
+        }
+    }
+}
+
+/// Creates the ground plane mesh centered at the origin.
+pub fn compute_ground_plane_mesh(
+    options: &GroundPlaneOptions,
+    scene_bounding_box: &BoundingBox<f32>,
+) -> Mesh {
+    let extent = options.extent(scene_bounding_box);
+    primitive::create_mesh_plane(
+        Plane::new(
+            &Point3::origin(),
+            &Vector3::new(1.0, 0.0, 0.0),
+            &Vector3::new(0.0, 1.0, 0.0),
+        ),
+        Vector2::new(extent, extent),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ground_plane_options_extent() {
+        let scene_bounding_box =
+            BoundingBox::new(&Point3::new(0.0, 0.0, 0.0), &Point3::new(30.0, 40.0, 0.0));
+
+        let mut options = GroundPlaneOptions::default();
+        approx::assert_relative_eq!(options.extent(&scene_bounding_box), 5000.0);
+        approx::assert_relative_eq!(options.extent(&BoundingBox::unit()), 1000.0);
+
+        options.auto_extent = false;
+        options.fixed_extent = 50.0;
+        approx::assert_relative_eq!(options.extent(&scene_bounding_box), 50.0);
+    }
+
+    #[test]
+    fn test_ground_plane_options_shadow_catcher_is_transparent() {
+        let options = GroundPlaneOptions {
+            shadow_catcher_only: true,
+            ..GroundPlaneOptions::default()
+        };
+
+        assert_eq!(options.material_color(), [0.0, 0.0, 0.0, 0.0]);
+    }
+}
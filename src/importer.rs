@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::error;
 use std::fmt;
 use std::fs;
-use std::io::{self, Read};
+use std::io::{self, BufRead, Read};
 use std::time::SystemTime;
 
 #[cfg(test)]
@@ -10,6 +10,7 @@ use mockall::{automock, lazy_static, predicate};
 use nalgebra::{Point3, Vector3};
 
 use crate::mesh::{Mesh, NormalStrategy, TriangleFace};
+use crate::units::Unit;
 
 #[derive(Debug, PartialEq)]
 pub enum InvalidStructureError {
@@ -293,6 +294,44 @@ pub fn calculate_checksum(string: &[u8]) -> u32 {
     hasher.finalize()
 }
 
+/// Finds the unit of length declared in the comments at the beginning of an
+/// OBJ file, e.g. `# Units: millimeters`. The OBJ format itself has no notion
+/// of units, but some software records them this way.
+pub fn obj_declared_unit<R: BufRead>(reader: R) -> io::Result<Option<Unit>> {
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if !line.starts_with('#') {
+            break;
+        }
+
+        let comment = line[1..].trim().to_ascii_lowercase();
+        let declaration = comment
+            .strip_prefix("units")
+            .or_else(|| comment.strip_prefix("unit"));
+
+        if let Some(declaration) = declaration {
+            let value =
+                declaration.trim_start_matches(|c: char| c == ':' || c == '=' || c.is_whitespace());
+            if let Some(unit) = value.split_whitespace().next().and_then(Unit::parse) {
+                return Ok(Some(unit));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Finds the unit of length declared in the OBJ file at given `path`. Only
+/// the comments at the beginning of the file are read.
+pub fn obj_file_declared_unit(path: &str) -> io::Result<Option<Unit>> {
+    let file = fs::File::open(path)?;
+    obj_declared_unit(io::BufReader::new(file))
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -703,4 +742,23 @@ mod tests {
             .import_obj(&path)
             .expect("Valid obj should be loaded");
     }
+
+    #[test]
+    fn test_obj_declared_unit_finds_unit_in_header_comment() {
+        let obj: &[u8] = b"# Exported by H.U.R.B.A.N selector\n\
+                           # Units: mm\n\
+                           \n\
+                           o Triangle\n";
+
+        assert_eq!(obj_declared_unit(obj).unwrap(), Some(Unit::Millimeter));
+    }
+
+    #[test]
+    fn test_obj_declared_unit_ignores_comments_after_geometry() {
+        let obj: &[u8] = b"# Some software\n\
+                           v 0.0 0.0 0.0\n\
+                           # units = feet\n";
+
+        assert_eq!(obj_declared_unit(obj).unwrap(), None);
+    }
 }
@@ -1,4 +1,18 @@
 use std::cmp::Ordering;
+use std::f64;
+use std::path::PathBuf;
+
+/// How many pixels of horizontal mouse drag a full radian of two-finger twist
+/// corresponds to when rotating the camera.
+const TOUCH_TWIST_PIXELS_PER_RADIAN: f64 = 250.0;
+
+/// How much camera zoom a pixel of change in distance between two pinching
+/// fingers corresponds to.
+const TOUCH_PINCH_ZOOM_SCALE: f64 = 0.5;
+
+/// How many pixels the mouse can move between pressing and releasing the left
+/// button for it to still count as a click in the viewport rather than a drag.
+const CLICK_MAX_DISTANCE_PIXELS: f64 = 4.0;
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct InputState {
@@ -8,21 +22,52 @@ pub struct InputState {
     pub camera_zoom: f32,
     pub camera_zoom_steps: i32,
     pub camera_reset_viewport: bool,
+    pub viewport_click: Option<[f32; 2]>,
+    /// The cursor position, if the cursor moved over the viewport without
+    /// any buttons pressed.
+    pub viewport_hover: Option<[f32; 2]>,
+    /// The cursor positions where the left button was pressed and where the
+    /// cursor is now, while dragging over the viewport with viewport drags
+    /// enabled.
+    pub viewport_drag: Option<([f32; 2], [f32; 2])>,
+    /// The cursor positions where the left button was pressed and released,
+    /// if a viewport drag ended.
+    pub viewport_drag_end: Option<([f32; 2], [f32; 2])>,
     #[cfg(not(feature = "dist"))]
     pub debug_view_cycle: bool,
     pub prog_run_requested: bool,
     pub prog_pop_requested: bool,
+    pub new_project_requested: bool,
+    pub open_project_requested: bool,
+    pub save_project_requested: bool,
     pub close_requested: bool,
     pub open_screenshot_options: bool,
+    pub exit_presentation_requested: bool,
+    pub fullscreen_toggle_requested: bool,
     pub window_resized: Option<winit::dpi::PhysicalSize<u32>>,
 }
 
+/// A finger currently touching the screen (or a touch-enabled trackpad
+/// reporting raw touches).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ActiveTouch {
+    id: u64,
+    position: [f64; 2],
+}
+
 #[derive(Debug, Default)]
 pub struct InputManager {
     lmb_down: bool,
+    lmb_down_position: Option<[f64; 2]>,
     rmb_down: bool,
+    /// Whether dragging with the left button is reported as a viewport drag
+    /// instead of rotating the camera.
+    viewport_drag_enabled: bool,
     modifiers: winit::event::ModifiersState,
+    touches: Vec<ActiveTouch>,
     input_state: InputState,
+    /// Files dropped onto the window during the frame.
+    dropped_files: Vec<PathBuf>,
     mouse_x_frame_start: f64,
     mouse_y_frame_start: f64,
     mouse_x_frame_end: f64,
@@ -33,9 +78,13 @@ impl InputManager {
     pub fn new() -> Self {
         Self {
             lmb_down: false,
+            lmb_down_position: None,
             rmb_down: false,
+            viewport_drag_enabled: false,
             modifiers: winit::event::ModifiersState::empty(),
+            touches: Vec::with_capacity(2),
             input_state: InputState::default(),
+            dropped_files: Vec::new(),
             mouse_x_frame_start: 0.0,
             mouse_y_frame_start: 0.0,
             mouse_x_frame_end: 0.0,
@@ -47,8 +96,20 @@ impl InputManager {
         &self.input_state
     }
 
+    pub fn dropped_files(&self) -> &[PathBuf] {
+        &self.dropped_files
+    }
+
+    /// Enables reporting left button drags over the viewport, e.g. for
+    /// drawing in the viewport. The camera can still be rotated with both
+    /// buttons or touches.
+    pub fn set_viewport_drag_enabled(&mut self, enabled: bool) {
+        self.viewport_drag_enabled = enabled;
+    }
+
     pub fn start_frame(&mut self) {
         self.input_state = InputState::default();
+        self.dropped_files.clear();
 
         self.mouse_x_frame_start = self.mouse_x_frame_end;
         self.mouse_y_frame_start = self.mouse_y_frame_end;
@@ -70,6 +131,10 @@ impl InputManager {
                     self.input_state.close_requested = true;
                 }
 
+                winit::event::WindowEvent::DroppedFile(path) => {
+                    self.dropped_files.push(path.clone());
+                }
+
                 winit::event::WindowEvent::KeyboardInput { input, .. } => {
                     let winit::event::KeyboardInput {
                         virtual_keycode,
@@ -107,9 +172,55 @@ impl InputManager {
                         }
                     }
 
+                    // Respond to F5 even if GUI has focus, so that the
+                    // pipeline can be run while typing parameter values
+                    if let (
+                        Some(winit::event::VirtualKeyCode::F5),
+                        winit::event::ElementState::Pressed,
+                    ) = (virtual_keycode, state)
+                    {
+                        self.input_state.prog_run_requested = true;
+                    }
+
+                    // Respond to F11 even if GUI has focus, as it is the
+                    // usual fullscreen shortcut across applications
+                    if let (
+                        Some(winit::event::VirtualKeyCode::F11),
+                        winit::event::ElementState::Pressed,
+                    ) = (virtual_keycode, state)
+                    {
+                        self.input_state.fullscreen_toggle_requested = true;
+                    }
+
+                    // Respond to the project shortcuts even if GUI has focus,
+                    // so that they work while navigating the GUI with the
+                    // keyboard
+                    if self.command_modifier_pressed() {
+                        match (virtual_keycode, state) {
+                            (
+                                Some(winit::event::VirtualKeyCode::N),
+                                winit::event::ElementState::Pressed,
+                            ) => {
+                                self.input_state.new_project_requested = true;
+                            }
+                            (
+                                Some(winit::event::VirtualKeyCode::O),
+                                winit::event::ElementState::Pressed,
+                            ) => {
+                                self.input_state.open_project_requested = true;
+                            }
+                            (
+                                Some(winit::event::VirtualKeyCode::S),
+                                winit::event::ElementState::Pressed,
+                            ) => {
+                                self.input_state.save_project_requested = true;
+                            }
+                            _ => (),
+                        }
+                    }
+
                     // These events are responded to only when gui doesn't have
-                    // focus and there are no active modifiers (we currently
-                    // have no keyboard shortcuts with modifiers)
+                    // focus and there are no active modifiers
                     if !ui_captured_keyboard
                         && self.modifiers == winit::event::ModifiersState::empty()
                     {
@@ -133,6 +244,12 @@ impl InputManager {
                             ) => {
                                 self.input_state.open_screenshot_options = true;
                             }
+                            (
+                                Some(winit::event::VirtualKeyCode::Escape),
+                                winit::event::ElementState::Pressed,
+                            ) => {
+                                self.input_state.exit_presentation_requested = true;
+                            }
                             (
                                 Some(winit::event::VirtualKeyCode::Return),
                                 winit::event::ElementState::Pressed,
@@ -160,9 +277,35 @@ impl InputManager {
                     match (state, button) {
                         (winit::event::ElementState::Pressed, winit::event::MouseButton::Left) => {
                             self.lmb_down = true;
+                            self.lmb_down_position = if ui_captured_mouse {
+                                None
+                            } else {
+                                Some([self.mouse_x_frame_end, self.mouse_y_frame_end])
+                            };
                         }
                         (winit::event::ElementState::Released, winit::event::MouseButton::Left) => {
                             self.lmb_down = false;
+
+                            if let Some([x, y]) = self.lmb_down_position.take() {
+                                let distance =
+                                    (self.mouse_x_frame_end - x).hypot(self.mouse_y_frame_end - y);
+                                if !ui_captured_mouse && distance <= CLICK_MAX_DISTANCE_PIXELS {
+                                    self.input_state.viewport_click = Some([
+                                        self.mouse_x_frame_end as f32,
+                                        self.mouse_y_frame_end as f32,
+                                    ]);
+                                } else if self.viewport_drag_enabled
+                                    && distance > CLICK_MAX_DISTANCE_PIXELS
+                                {
+                                    self.input_state.viewport_drag_end = Some((
+                                        [x as f32, y as f32],
+                                        [
+                                            self.mouse_x_frame_end as f32,
+                                            self.mouse_y_frame_end as f32,
+                                        ],
+                                    ));
+                                }
+                            }
                         }
                         (winit::event::ElementState::Pressed, winit::event::MouseButton::Right) => {
                             self.rmb_down = true;
@@ -185,8 +328,22 @@ impl InputManager {
                     let dy = (self.mouse_y_frame_end - self.mouse_y_frame_start) as f32;
 
                     if !ui_captured_mouse {
+                        if !self.lmb_down && !self.rmb_down {
+                            self.input_state.viewport_hover = Some([
+                                self.mouse_x_frame_end as f32,
+                                self.mouse_y_frame_end as f32,
+                            ]);
+                        }
+
                         if self.lmb_down && self.rmb_down {
                             self.input_state.camera_zoom = dy;
+                        } else if self.lmb_down && self.viewport_drag_enabled {
+                            if let Some([x, y]) = self.lmb_down_position {
+                                self.input_state.viewport_drag = Some((
+                                    [x as f32, y as f32],
+                                    [self.mouse_x_frame_end as f32, self.mouse_y_frame_end as f32],
+                                ));
+                            }
                         } else if self.lmb_down {
                             self.input_state.camera_rotate[0] = dx;
                             self.input_state.camera_rotate[1] = dy;
@@ -237,6 +394,16 @@ impl InputManager {
                     }
                 },
 
+                // FIXME: @Correctness winit (as of 0.24) does not report
+                // macOS trackpad magnify and rotate gestures. Two-finger
+                // trackpad scrolling arrives as `MouseScrollDelta::PixelDelta`
+                // and zooms, but pinch and twist only work on devices
+                // reporting raw touches. Handle the gesture events once winit
+                // exposes them.
+                winit::event::WindowEvent::Touch(touch) => {
+                    self.process_touch(touch, ui_captured_mouse);
+                }
+
                 winit::event::WindowEvent::Resized(physical_size) => {
                     // Even if the window resized multiple times, only
                     // take the last one into account.
@@ -247,4 +414,163 @@ impl InputManager {
             }
         }
     }
+
+    /// Whether only the modifier of the platform's application shortcuts is
+    /// pressed: Cmd on macOS, Ctrl elsewhere.
+    fn command_modifier_pressed(&self) -> bool {
+        #[cfg(target_os = "macos")]
+        let command_modifier = winit::event::ModifiersState::LOGO;
+        #[cfg(not(target_os = "macos"))]
+        let command_modifier = winit::event::ModifiersState::CTRL;
+
+        self.modifiers == command_modifier
+    }
+
+    /// Translates raw touches to camera navigation.
+    ///
+    /// A single finger drag rotates the camera just like dragging with the left
+    /// mouse button. With two fingers down, moving both fingers pans the
+    /// camera in screen space, pinching zooms and twisting rotates the camera
+    /// around the vertical axis. Touches beyond the second finger are tracked,
+    /// but ignored for navigation.
+    fn process_touch(&mut self, touch: &winit::event::Touch, ui_captured_mouse: bool) {
+        let id = touch.id;
+        let position = [touch.location.x, touch.location.y];
+
+        match touch.phase {
+            winit::event::TouchPhase::Started => {
+                if !ui_captured_mouse && self.touches.iter().all(|t| t.id != id) {
+                    self.touches.push(ActiveTouch { id, position });
+                }
+            }
+            winit::event::TouchPhase::Ended | winit::event::TouchPhase::Cancelled => {
+                self.touches.retain(|t| t.id != id);
+            }
+            winit::event::TouchPhase::Moved => {
+                let index = match self.touches.iter().position(|t| t.id == id) {
+                    Some(index) => index,
+                    None => return,
+                };
+
+                let old = self.touches[index].position;
+                self.touches[index].position = position;
+
+                match self.touches.len() {
+                    1 => {
+                        self.input_state.camera_rotate[0] += (position[0] - old[0]) as f32;
+                        self.input_state.camera_rotate[1] += (position[1] - old[1]) as f32;
+                    }
+                    2 => {
+                        let other = self.touches[1 - index].position;
+
+                        let centroid_old = [(old[0] + other[0]) / 2.0, (old[1] + other[1]) / 2.0];
+                        let centroid_new = [
+                            (position[0] + other[0]) / 2.0,
+                            (position[1] + other[1]) / 2.0,
+                        ];
+
+                        // Multiple touch move events can arrive within a
+                        // single frame. Keep the pan origin from the first one.
+                        let pan_start = match self.input_state.camera_pan_screen {
+                            Some((start, _)) => start,
+                            None => [centroid_old[0] as f32, centroid_old[1] as f32],
+                        };
+                        self.input_state.camera_pan_screen =
+                            Some((pan_start, [centroid_new[0] as f32, centroid_new[1] as f32]));
+
+                        let offset_old = [old[0] - other[0], old[1] - other[1]];
+                        let offset_new = [position[0] - other[0], position[1] - other[1]];
+
+                        // Spreading the fingers apart zooms in, which is a
+                        // negative zoom for the camera.
+                        let distance_old = offset_old[0].hypot(offset_old[1]);
+                        let distance_new = offset_new[0].hypot(offset_new[1]);
+                        self.input_state.camera_zoom -=
+                            ((distance_new - distance_old) * TOUCH_PINCH_ZOOM_SCALE) as f32;
+
+                        let angle_old = offset_old[1].atan2(offset_old[0]);
+                        let angle_new = offset_new[1].atan2(offset_new[0]);
+                        let mut angle_delta = angle_new - angle_old;
+                        if angle_delta > f64::consts::PI {
+                            angle_delta -= 2.0 * f64::consts::PI;
+                        } else if angle_delta < -f64::consts::PI {
+                            angle_delta += 2.0 * f64::consts::PI;
+                        }
+                        self.input_state.camera_rotate[0] +=
+                            (angle_delta * TOUCH_TWIST_PIXELS_PER_RADIAN) as f32;
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::TouchPhase;
+
+    use super::*;
+
+    fn touch(id: u64, phase: TouchPhase, x: f64, y: f64) -> winit::event::Touch {
+        winit::event::Touch {
+            // The device is never queried, so a dummy ID is enough
+            device_id: unsafe { winit::event::DeviceId::dummy() },
+            phase,
+            location: winit::dpi::PhysicalPosition::new(x, y),
+            force: None,
+            id,
+        }
+    }
+
+    #[test]
+    fn test_input_manager_process_touch_pinch_zooms() {
+        let mut input_manager = InputManager::new();
+        input_manager.start_frame();
+        input_manager.process_touch(&touch(0, TouchPhase::Started, 100.0, 100.0), false);
+        input_manager.process_touch(&touch(1, TouchPhase::Started, 200.0, 100.0), false);
+
+        // Spreading the fingers apart zooms in
+        input_manager.process_touch(&touch(1, TouchPhase::Moved, 300.0, 100.0), false);
+        assert_eq!(input_manager.input_state().camera_zoom, -50.0);
+        assert_eq!(input_manager.input_state().camera_rotate, [0.0, 0.0]);
+
+        // Pinching them together again within the frame zooms out
+        input_manager.process_touch(&touch(1, TouchPhase::Moved, 250.0, 100.0), false);
+        assert_eq!(input_manager.input_state().camera_zoom, -25.0);
+
+        input_manager.start_frame();
+        input_manager.process_touch(&touch(0, TouchPhase::Moved, 150.0, 100.0), false);
+        assert_eq!(input_manager.input_state().camera_zoom, 25.0);
+    }
+
+    #[test]
+    fn test_input_manager_process_touch_two_finger_drag_pans() {
+        let mut input_manager = InputManager::new();
+        input_manager.start_frame();
+        input_manager.process_touch(&touch(0, TouchPhase::Started, 100.0, 100.0), false);
+        input_manager.process_touch(&touch(1, TouchPhase::Started, 200.0, 100.0), false);
+
+        // Both fingers move down, one after the other within the frame
+        input_manager.process_touch(&touch(0, TouchPhase::Moved, 100.0, 150.0), false);
+        input_manager.process_touch(&touch(1, TouchPhase::Moved, 200.0, 150.0), false);
+
+        let input_state = input_manager.input_state();
+        assert_eq!(
+            input_state.camera_pan_screen,
+            Some(([150.0, 100.0], [150.0, 150.0])),
+        );
+        assert!(input_state.camera_zoom.abs() < 0.0001);
+        assert!(input_state.camera_rotate[0].abs() < 0.0001);
+        assert_eq!(input_state.camera_rotate[1], 0.0);
+
+        // Lifting a finger ends the pan, the remaining one rotates
+        input_manager.start_frame();
+        input_manager.process_touch(&touch(1, TouchPhase::Ended, 200.0, 150.0), false);
+        input_manager.process_touch(&touch(0, TouchPhase::Moved, 110.0, 160.0), false);
+
+        let input_state = input_manager.input_state();
+        assert_eq!(input_state.camera_pan_screen, None);
+        assert_eq!(input_state.camera_rotate, [10.0, 10.0]);
+    }
 }
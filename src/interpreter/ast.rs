@@ -27,7 +27,9 @@ impl fmt::Display for FuncIdent {
 /// Has to stay stable for the lifetime of the interpreter and program using
 /// it. Internally has 64-bits of precision, but no assumptions must be made
 /// about the meaning of those bits.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub struct VarIdent(pub(crate) u64);
 
 impl fmt::Display for VarIdent {
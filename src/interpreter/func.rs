@@ -1,7 +1,19 @@
+use std::error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
 use bitflags::bitflags;
 
+use crate::mesh::{analysis, Mesh};
+
+use super::ast::LitExpr;
 use super::{FuncError, LogMessage, Ty, Value};
 
+/// How many times larger the voxels are in draft mode.
+const DRAFT_VOXEL_SIZE_MULT: f32 = 2.0;
+
 /// Textual information about the function.
 pub struct FuncInfo {
     /// The function's name.
@@ -12,6 +24,17 @@ pub struct FuncInfo {
     pub return_value_name: &'static str,
 }
 
+/// Long-form documentation of the function, shown in the help panel together
+/// with the descriptions of the function and its parameters.
+pub struct FuncDocs {
+    /// Explanation of typical uses of the function and of the parameter
+    /// settings for them. Paragraphs are separated by empty lines.
+    pub details: &'static str,
+    /// An illustration of what the function does, as PNG data embedded in
+    /// the binary.
+    pub image: Option<&'static [u8]>,
+}
+
 bitflags! {
     /// Information about the function behavior.
     ///
@@ -31,6 +54,60 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Properties the meshes given to a function parameter must have. The
+    /// interpreter checks them before calling the function, so that the user
+    /// gets a clear error instead of a failed or slow computation.
+    ///
+    /// Meshes are always triangulated, so there is no requirement for that.
+    pub struct MeshRequirements: u8 {
+        /// Every edge is shared by exactly two faces.
+        const WATERTIGHT = 0b_0000_0001;
+        /// No edge is shared by more than two faces.
+        const MANIFOLD = 0b_0000_0010;
+    }
+}
+
+impl MeshRequirements {
+    /// Returns the requirements the mesh doesn't meet.
+    pub fn unmet_by(self, mesh: &Mesh) -> MeshRequirements {
+        if self.is_empty() {
+            return MeshRequirements::empty();
+        }
+
+        let oriented_edges: Vec<_> = mesh.oriented_edges_iter().collect();
+        let edge_sharing_map = analysis::edge_sharing(&oriented_edges);
+
+        let mut unmet = MeshRequirements::empty();
+        if self.contains(MeshRequirements::WATERTIGHT)
+            && !analysis::is_mesh_watertight(&edge_sharing_map)
+        {
+            unmet |= MeshRequirements::WATERTIGHT;
+        }
+        if self.contains(MeshRequirements::MANIFOLD)
+            && !analysis::is_mesh_manifold(&edge_sharing_map)
+        {
+            unmet |= MeshRequirements::MANIFOLD;
+        }
+
+        unmet
+    }
+}
+
+impl fmt::Display for MeshRequirements {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names = Vec::with_capacity(2);
+        if self.contains(MeshRequirements::WATERTIGHT) {
+            names.push("watertight");
+        }
+        if self.contains(MeshRequirements::MANIFOLD) {
+            names.push("manifold");
+        }
+
+        write!(f, "{}", names.join(" and "))
+    }
+}
+
 /// Information about a function parameter.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParamInfo {
@@ -48,6 +125,35 @@ pub struct ParamInfo {
     ///
     /// [`Nil`]: ../value.enum.Ty.html#variant.Nil
     pub optional: bool,
+    /// How the parameter is presented in the user interface. See
+    /// [`ParamWidget`] for more.
+    ///
+    /// [`ParamWidget`]: enum.ParamWidget.html
+    pub widget: ParamWidget,
+}
+
+/// The user interface widget of a parameter. Widgets other than `Default`
+/// only change how the value is presented and edited, not its type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamWidget {
+    /// The default widget of the parameter type.
+    Default,
+    /// A combo box choosing one of the named options. Only for `Uint`
+    /// parameters, whose value is the index of the chosen option.
+    Choice(&'static [&'static str]),
+    /// Separate inputs for the X, Y and Z components. Only for `Float3`
+    /// parameters.
+    Vector3,
+}
+
+impl ParamWidget {
+    /// Returns the names of the options, if the widget chooses one of them.
+    pub fn choices(&self) -> Option<&'static [&'static str]> {
+        match self {
+            Self::Choice(choices) => Some(choices),
+            Self::Default | Self::Vector3 => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -61,6 +167,7 @@ pub enum ParamRefinement {
     Float2(Float2ParamRefinement),
     Float3(Float3ParamRefinement),
     String(StringParamRefinement),
+    FilePath(FilePathParamRefinement),
     Mesh,
     MeshArray,
 }
@@ -74,11 +181,42 @@ impl ParamRefinement {
             Self::Float(_) => Ty::Float,
             Self::Float2(_) => Ty::Float2,
             Self::Float3(_) => Ty::Float3,
-            Self::String(_) => Ty::String,
+            Self::String(_) | Self::FilePath(_) => Ty::String,
             Self::Mesh => Ty::Mesh,
             Self::MeshArray => Ty::MeshArray,
         }
     }
+
+    /// Returns the default value of the parameter, or `None` if the
+    /// parameter takes the value of another statement.
+    pub fn default_lit(&self) -> Option<LitExpr> {
+        match self {
+            Self::Boolean(refinement) => Some(LitExpr::Boolean(refinement.default_value)),
+            Self::Int(refinement) => {
+                Some(LitExpr::Int(refinement.default_value.unwrap_or_default()))
+            }
+            Self::Uint(refinement) => {
+                Some(LitExpr::Uint(refinement.default_value.unwrap_or_default()))
+            }
+            Self::Float(refinement) => {
+                Some(LitExpr::Float(refinement.default_value.unwrap_or_default()))
+            }
+            Self::Float2(refinement) => Some(LitExpr::Float2([
+                refinement.default_value_x.unwrap_or_default(),
+                refinement.default_value_y.unwrap_or_default(),
+            ])),
+            Self::Float3(refinement) => Some(LitExpr::Float3([
+                refinement.default_value_x.unwrap_or_default(),
+                refinement.default_value_y.unwrap_or_default(),
+                refinement.default_value_z.unwrap_or_default(),
+            ])),
+            Self::String(refinement) => {
+                Some(LitExpr::String(String::from(refinement.default_value)))
+            }
+            Self::FilePath(_) => Some(LitExpr::String(String::new())),
+            Self::Mesh | Self::MeshArray => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -110,6 +248,24 @@ impl IntParamRefinement {
     }
 }
 
+/// The physical dimension of a numeric value. Lengths, areas and volumes are
+/// expressed in the project units, which are not known to the interpreter.
+/// Angles are always in degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Dimensionless,
+    Length,
+    Area,
+    Volume,
+    Angle,
+}
+
+impl Default for Dimension {
+    fn default() -> Self {
+        Dimension::Dimensionless
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct UintParamRefinement {
     pub default_value: Option<u32>,
@@ -139,6 +295,7 @@ pub struct FloatParamRefinement {
     pub default_value: Option<f32>,
     pub min_value: Option<f32>,
     pub max_value: Option<f32>,
+    pub dimension: Dimension,
 }
 
 impl FloatParamRefinement {
@@ -164,6 +321,7 @@ pub struct Float2ParamRefinement {
     pub max_value: Option<f32>,
     pub default_value_x: Option<f32>,
     pub default_value_y: Option<f32>,
+    pub dimension: Dimension,
 }
 
 impl Float2ParamRefinement {
@@ -199,6 +357,7 @@ pub struct Float3ParamRefinement {
     pub default_value_x: Option<f32>,
     pub default_value_y: Option<f32>,
     pub default_value_z: Option<f32>,
+    pub dimension: Dimension,
 }
 
 impl Float3ParamRefinement {
@@ -240,10 +399,67 @@ impl Float3ParamRefinement {
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct StringParamRefinement {
     pub default_value: &'static str,
-    pub file_path: bool,
+}
+
+/// A path to a file, passed to the function as a string.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FilePathParamRefinement {
+    /// Patterns of the accepted file names, such as `*.obj`, and their
+    /// description shown in the file dialog.
     pub file_ext_filter: Option<(&'static [&'static str], &'static str)>,
 }
 
+impl FilePathParamRefinement {
+    /// Checks that the path leads to an existing file accepted by the
+    /// extension filter.
+    pub fn validate(&self, path: &str) -> Result<(), FilePathError> {
+        if path.is_empty() {
+            return Err(FilePathError::Empty);
+        }
+
+        let path = Path::new(path);
+        if let Some((patterns, _)) = self.file_ext_filter {
+            let extension = path.extension().and_then(OsStr::to_str).unwrap_or("");
+            let accepted = patterns.iter().any(|pattern| {
+                pattern
+                    .strip_prefix("*.")
+                    .map_or(false, |pattern_extension| {
+                        pattern_extension.eq_ignore_ascii_case(extension)
+                    })
+            });
+
+            if !accepted {
+                return Err(FilePathError::UnexpectedExtension);
+            }
+        }
+
+        if !path.is_file() {
+            return Err(FilePathError::NotFound);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilePathError {
+    Empty,
+    UnexpectedExtension,
+    NotFound,
+}
+
+impl fmt::Display for FilePathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilePathError::Empty => write!(f, "No file selected"),
+            FilePathError::UnexpectedExtension => write!(f, "Unexpected file extension"),
+            FilePathError::NotFound => write!(f, "File not found"),
+        }
+    }
+}
+
+impl error::Error for FilePathError {}
+
 /// An interface describing a function as seen by the interpreter.
 ///
 /// Functions are pieces of callable code. They can receive parameters
@@ -261,6 +477,11 @@ pub trait Func {
         }
     }
 
+    /// Long-form documentation of the function, if it has any.
+    fn docs(&self) -> Option<&FuncDocs> {
+        None
+    }
+
     /// Information about the function behaviour.
     ///
     /// See [`FuncFlags`] for more.
@@ -276,6 +497,82 @@ pub trait Func {
     /// [`ParamInfo`]: struct.ParamInfo.html
     fn param_info(&self) -> &[ParamInfo];
 
+    /// Index of the variadic parameter, if any.
+    ///
+    /// A variadic parameter takes one or more arguments, e.g. any number of
+    /// meshes to join. The arguments following them are given to the
+    /// parameters declared after the variadic parameter.
+    fn variadic_param_index(&self) -> Option<usize> {
+        None
+    }
+
+    /// Properties the meshes given to the parameter at the index must have.
+    /// Checked by the interpreter before calling the function. See
+    /// [`MeshRequirements`] for more.
+    ///
+    /// [`MeshRequirements`]: struct.MeshRequirements.html
+    fn mesh_requirements(&self, _param_index: usize) -> MeshRequirements {
+        MeshRequirements::empty()
+    }
+
+    /// Index of the `Float3` parameter setting the voxel size, if the
+    /// function works with voxels. The voxel size of such functions can be
+    /// linked to the project voxel size, so that the quality of a whole
+    /// pipeline is tuned in one place.
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns a cheaper value of the argument given to the parameter at the
+    /// index, used instead of it in draft mode. Draft mode trades quality for
+    /// speed while the pipeline is being edited.
+    ///
+    /// By default, functions working with voxels get voxels of double size.
+    fn draft_arg(&self, param_index: usize, value: &Value) -> Option<Value> {
+        match value {
+            Value::Float3(voxel_size) if self.voxel_size_param_index() == Some(param_index) => {
+                Some(Value::Float3([
+                    voxel_size[0] * DRAFT_VOXEL_SIZE_MULT,
+                    voxel_size[1] * DRAFT_VOXEL_SIZE_MULT,
+                    voxel_size[2] * DRAFT_VOXEL_SIZE_MULT,
+                ]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns whether the function can be called with the number of
+    /// arguments.
+    fn accepts_arg_count(&self, arg_count: usize) -> bool {
+        let param_count = self.param_info().len();
+        match self.variadic_param_index() {
+            Some(_) => arg_count >= param_count,
+            None => arg_count == param_count,
+        }
+    }
+
+    /// Returns the index of the parameter taking the argument at the index,
+    /// if the function can be called with the number of arguments.
+    fn param_index_for_arg(&self, arg_index: usize, arg_count: usize) -> Option<usize> {
+        if arg_index >= arg_count || !self.accepts_arg_count(arg_count) {
+            return None;
+        }
+
+        match self.variadic_param_index() {
+            Some(variadic_param_index) => {
+                let variadic_arg_count = arg_count + 1 - self.param_info().len();
+                if arg_index < variadic_param_index {
+                    Some(arg_index)
+                } else if arg_index < variadic_param_index + variadic_arg_count {
+                    Some(variadic_param_index)
+                } else {
+                    Some(arg_index + 1 - variadic_arg_count)
+                }
+            }
+            None => Some(arg_index),
+        }
+    }
+
     /// Information about the function's return type.
     ///
     /// Used for static and dynamic typecheking. See [`Ty`] for more.
@@ -293,4 +590,55 @@ pub trait Func {
     /// [`return_ty`]: trait.Func.html#tymethod.return_ty
     fn call(&mut self, args: &[Value], log: &mut dyn FnMut(LogMessage))
         -> Result<Value, FuncError>;
+
+    /// Call the function like [`call`], additionally passing each element
+    /// of the returned mesh array to `stream` as soon as it is generated, so
+    /// that it can be displayed before the function finishes.
+    ///
+    /// If `stream` returns `false`, the interpretation was cancelled. The
+    /// function should stop generating and return early. Its return value is
+    /// discarded in that case.
+    ///
+    /// The default implementation doesn't stream and just calls [`call`].
+    ///
+    /// [`call`]: trait.Func.html#tymethod.call
+    fn call_streaming(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+        _stream: &mut dyn FnMut(Arc<Mesh>) -> bool,
+    ) -> Result<Value, FuncError> {
+        self.call(args, log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_path_param_refinement_validate() {
+        let refinement = FilePathParamRefinement {
+            file_ext_filter: Some((&["*.toml"], "TOML")),
+        };
+
+        assert_eq!(refinement.validate(""), Err(FilePathError::Empty));
+        assert_eq!(
+            refinement.validate("Cargo.lock"),
+            Err(FilePathError::UnexpectedExtension),
+        );
+        assert_eq!(
+            refinement.validate("Does-not-exist.TOML"),
+            Err(FilePathError::NotFound),
+        );
+        assert_eq!(refinement.validate("Cargo.toml"), Ok(()));
+    }
+
+    #[test]
+    fn test_file_path_param_refinement_validate_without_filter() {
+        let refinement = FilePathParamRefinement::default();
+
+        assert_eq!(refinement.validate("Cargo.lock"), Ok(()));
+        assert_eq!(refinement.validate("src"), Err(FilePathError::NotFound));
+    }
 }
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+
+use crate::mesh::Mesh;
+
+use super::value::{MeshArrayValue, Value};
+
+/// The meshes of the interpreter values, looked up by their content hashes.
+///
+/// Pipelines often carry byte-identical copies of large meshes, e.g. a mesh
+/// extracted from an array and joined back, or transformed by an identity.
+/// Values deduplicated by the pool share a single copy of such meshes, which
+/// saves memory and lets the viewport keep the GPU mesh of a value it already
+/// displays.
+///
+/// The pool only keeps weak references, so that the meshes are freed once no
+/// value holds them.
+#[derive(Debug, Default)]
+pub struct MeshPool {
+    meshes: HashMap<u64, Vec<Weak<Mesh>>>,
+}
+
+impl MeshPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pooled mesh equal to the mesh, if any. Otherwise adds the
+    /// mesh to the pool and returns it.
+    pub fn dedup_mesh(&mut self, mesh: Arc<Mesh>) -> Arc<Mesh> {
+        let candidates = self.meshes.entry(mesh.content_hash()).or_default();
+
+        let pooled = candidates
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|candidate| Arc::ptr_eq(candidate, &mesh) || **candidate == *mesh);
+
+        match pooled {
+            Some(pooled) => pooled,
+            None => {
+                candidates.push(Arc::downgrade(&mesh));
+                mesh
+            }
+        }
+    }
+
+    /// Replaces the meshes of the value by the pooled meshes equal to them.
+    /// Other values, including lazy mesh arrays whose meshes are yet to be
+    /// generated, are returned as they are.
+    pub fn dedup_value(&mut self, value: Value) -> Value {
+        self.remove_freed();
+
+        match value {
+            Value::Mesh(mesh) => Value::Mesh(self.dedup_mesh(mesh)),
+            Value::MeshArray(mesh_array) if !mesh_array.is_lazy() => {
+                let meshes = mesh_array
+                    .iter_refcounted()
+                    .map(|mesh| self.dedup_mesh(mesh))
+                    .collect();
+                Value::MeshArray(Arc::new(MeshArrayValue::new(meshes)))
+            }
+            value => value,
+        }
+    }
+
+    /// Forgets the meshes no longer held by any value.
+    fn remove_freed(&mut self) {
+        self.meshes.retain(|_, candidates| {
+            candidates.retain(|candidate| candidate.strong_count() > 0);
+            !candidates.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point3, Rotation3, Vector3};
+
+    use crate::mesh::primitive;
+
+    use super::*;
+
+    fn cube(x: f32) -> Mesh {
+        primitive::create_box(
+            Point3::new(x, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn test_mesh_pool_dedup_value_shares_equal_meshes() {
+        let mut mesh_pool = MeshPool::new();
+
+        let original = Arc::new(cube(0.0));
+        mesh_pool.dedup_value(Value::Mesh(Arc::clone(&original)));
+
+        let mesh_array = MeshArrayValue::new(vec![Arc::new(cube(0.0)), Arc::new(cube(2.0))]);
+        let deduplicated = mesh_pool.dedup_value(Value::MeshArray(Arc::new(mesh_array)));
+        let meshes: Vec<_> = deduplicated.unwrap_mesh_array().iter_refcounted().collect();
+
+        assert!(Arc::ptr_eq(&meshes[0], &original));
+        assert!(!Arc::ptr_eq(&meshes[1], &original));
+    }
+
+    #[test]
+    fn test_mesh_pool_dedup_mesh_forgets_freed_meshes() {
+        let mut mesh_pool = MeshPool::new();
+
+        let original = Arc::new(cube(0.0));
+        mesh_pool.dedup_mesh(Arc::clone(&original));
+        drop(original);
+
+        let copy = Arc::new(cube(0.0));
+        let deduplicated = mesh_pool.dedup_mesh(Arc::clone(&copy));
+
+        assert!(Arc::ptr_eq(&deduplicated, &copy));
+    }
+}
@@ -5,19 +5,26 @@ use std::collections::{BTreeMap, HashSet};
 use std::error;
 use std::fmt;
 use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use crate::mesh::Mesh;
 
 pub use self::ast::{FuncIdent, VarIdent};
 pub use self::func::{
-    BooleanParamRefinement, Float2ParamRefinement, Float3ParamRefinement, FloatParamRefinement,
-    Func, FuncFlags, FuncInfo, IntParamRefinement, ParamInfo, ParamRefinement,
+    BooleanParamRefinement, Dimension, FilePathParamRefinement, Float2ParamRefinement,
+    Float3ParamRefinement, FloatParamRefinement, Func, FuncDocs, FuncFlags, FuncInfo,
+    IntParamRefinement, MeshRequirements, ParamInfo, ParamRefinement, ParamWidget,
     StringParamRefinement, UintParamRefinement,
 };
 pub use self::value::{MeshArrayValue, Ty, Value};
 
+use self::mesh_pool::MeshPool;
+
 pub mod ast;
 pub mod func;
+mod mesh_pool;
 pub mod value;
 
 // FIXME: All of the `Display` impls below for the error types were changed to
@@ -115,6 +122,12 @@ pub enum RuntimeError {
         ty_expected: Ty,
         ty_provided: Ty,
     },
+    ArgMeshRequirementsUnmet {
+        stmt_index: usize,
+        call: ast::CallExpr,
+        param_name: &'static str,
+        unmet: MeshRequirements,
+    },
     ReturnTyMismatch {
         stmt_index: usize,
         call: ast::CallExpr,
@@ -126,6 +139,10 @@ pub enum RuntimeError {
         call: ast::CallExpr,
         func_error: FuncError,
     },
+    Cancelled {
+        stmt_index: usize,
+        call: ast::CallExpr,
+    },
 }
 
 impl RuntimeError {
@@ -133,8 +150,10 @@ impl RuntimeError {
         match self {
             RuntimeError::ArgCountMismatch { stmt_index, .. } => *stmt_index,
             RuntimeError::ArgTyMismatch { stmt_index, .. } => *stmt_index,
+            RuntimeError::ArgMeshRequirementsUnmet { stmt_index, .. } => *stmt_index,
             RuntimeError::ReturnTyMismatch { stmt_index, .. } => *stmt_index,
             RuntimeError::Func { stmt_index, .. } => *stmt_index,
+            RuntimeError::Cancelled { stmt_index, .. } => *stmt_index,
         }
     }
 }
@@ -170,6 +189,20 @@ impl fmt::Display for RuntimeError {
                 ty_provided,
                 stmt_index + 1,
             ),
+            RuntimeError::ArgMeshRequirementsUnmet {
+                stmt_index,
+                call,
+                param_name,
+                unmet,
+            } => write!(
+                f,
+                "Function {} requires the {} on input {} to be {}, \
+                 insert Weld or Voxelize Mesh before it to repair the mesh",
+                call.ident(),
+                param_name,
+                stmt_index + 1,
+                unmet,
+            ),
             RuntimeError::ReturnTyMismatch {
                 stmt_index,
                 call,
@@ -194,6 +227,12 @@ impl fmt::Display for RuntimeError {
                 func_error,
                 stmt_index + 1,
             ),
+            RuntimeError::Cancelled { stmt_index, call } => write!(
+                f,
+                "Function {} was cancelled on input {}",
+                call.ident(),
+                stmt_index + 1,
+            ),
         }
     }
 }
@@ -205,6 +244,12 @@ impl error::Error for RuntimeError {}
 pub enum InterpretError {
     Resolve(ResolveError),
     Runtime(RuntimeError),
+    /// The statement didn't finish within the timeout and the interpreter
+    /// evaluating it was abandoned. Reported by the interpreter server.
+    Timeout {
+        stmt_index: usize,
+        timeout: Duration,
+    },
 }
 
 impl InterpretError {
@@ -212,6 +257,7 @@ impl InterpretError {
         match self {
             InterpretError::Resolve(resolve_error) => resolve_error.stmt_index(),
             InterpretError::Runtime(runtime_error) => runtime_error.stmt_index(),
+            InterpretError::Timeout { stmt_index, .. } => *stmt_index,
         }
     }
 }
@@ -221,6 +267,16 @@ impl fmt::Display for InterpretError {
         match self {
             InterpretError::Resolve(resolve_error) => f.write_str(&resolve_error.to_string()),
             InterpretError::Runtime(runtime_error) => f.write_str(&runtime_error.to_string()),
+            InterpretError::Timeout {
+                stmt_index,
+                timeout,
+            } => write!(
+                f,
+                "Input {} did not finish within {} s and was abandoned. Lower its parameters \
+                 (e.g. resolution or count) or increase the operation timeout in Preferences",
+                stmt_index + 1,
+                timeout.as_secs(),
+            ),
         }
     }
 }
@@ -247,10 +303,21 @@ pub enum LogMessageLevel {
     Error,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// A named numeric result of a func, e.g. an area or a volume.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub name: Cow<'static, str>,
+    pub value: f32,
+    pub dimension: Dimension,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct LogMessage {
     pub level: LogMessageLevel,
     pub message: Cow<'static, str>,
+    /// The measurement reported by this message, if any. Measurements are
+    /// collected from the log for export.
+    pub measurement: Option<Measurement>,
 }
 
 impl LogMessage {
@@ -258,6 +325,7 @@ impl LogMessage {
         Self {
             level: LogMessageLevel::Info,
             message: message.into(),
+            measurement: None,
         }
     }
 
@@ -266,6 +334,7 @@ impl LogMessage {
         Self {
             level: LogMessageLevel::Warn,
             message: message.into(),
+            measurement: None,
         }
     }
 
@@ -273,6 +342,28 @@ impl LogMessage {
         Self {
             level: LogMessageLevel::Error,
             message: message.into(),
+            measurement: None,
+        }
+    }
+
+    /// Creates an info message reporting the measurement, displayed as
+    /// `name = value`. The unit of the dimension is not known to the
+    /// interpreter and is added when the message is displayed.
+    pub fn measurement<S: Into<Cow<'static, str>>>(
+        name: S,
+        value: f32,
+        dimension: Dimension,
+    ) -> Self {
+        let name = name.into();
+
+        Self {
+            level: LogMessageLevel::Info,
+            message: Cow::Owned(format!("{} = {:.2}", name, value)),
+            measurement: Some(Measurement {
+                name,
+                value,
+                dimension,
+            }),
         }
     }
 }
@@ -326,6 +417,22 @@ struct VarValue {
 
     /// Current value of this variable.
     value: Value,
+
+    /// Whether the value of an impure func must be computed again, before
+    /// it can be used. The value is kept to be compared with the new one, so
+    /// that its dependents are only invalidated if the value changed.
+    revalidate: bool,
+}
+
+/// How many speculatively evaluated programs the interpreter keeps.
+const SPECULATIONS_CAPACITY: usize = 2;
+
+/// The values of a program evaluated ahead of time, in case it becomes the
+/// current program. See `Interpreter::speculate`.
+struct Speculation {
+    prog: ast::Prog,
+    env: HashMap<VarIdent, VarValue>,
+    log_messages: Vec<Vec<LogMessage>>,
 }
 
 /// Interpreter of a list of statements.
@@ -356,6 +463,41 @@ pub struct Interpreter {
     /// The last epoch for which name resolution succeeded. Initially
     /// 0, since empty program is by default resolved.
     last_resolve_epoch: u64,
+
+    /// Called with the index of each statement right before it is
+    /// evaluated, so that long running statements can be detected.
+    stmt_started_callback: Option<Box<dyn FnMut(usize)>>,
+
+    /// Called with the index of the statement being evaluated and an
+    /// element of the mesh array it generates, as soon as the element is
+    /// generated.
+    mesh_stream_callback: Option<Box<dyn FnMut(usize, Arc<Mesh>)>>,
+
+    /// Called with the variable, the call creating it and the value of each
+    /// statement right after it is evaluated, so that the values can be
+    /// saved for later. Values taken from cache are left out, so that each
+    /// value is only passed by the run computing it.
+    checkpoint_callback: Option<Box<dyn FnMut(VarIdent, &ast::CallExpr, &Value)>>,
+
+    /// When set, interpreting stops before the next statement and streaming
+    /// funcs are asked to stop generating.
+    cancel_flag: Option<Arc<AtomicBool>>,
+
+    /// Whether funcs are called with their cheaper draft arguments. See
+    /// `Func::draft_arg`.
+    draft: bool,
+
+    /// Programs evaluated ahead of time, the most recent last.
+    speculations: Vec<Speculation>,
+
+    /// When set, speculative evaluation stops before the next statement.
+    /// Kept apart from the cancel flag, so that starting to interpret
+    /// doesn't lower it.
+    speculation_cancel_flag: Option<Arc<AtomicBool>>,
+
+    /// The meshes of the evaluated values, so that values with equal meshes
+    /// share them.
+    mesh_pool: MeshPool,
 }
 
 impl Interpreter {
@@ -367,7 +509,171 @@ impl Interpreter {
             log_messages: Vec::new(),
             epoch: 0,
             last_resolve_epoch: 0,
+            stmt_started_callback: None,
+            mesh_stream_callback: None,
+            checkpoint_callback: None,
+            cancel_flag: None,
+            draft: false,
+            speculations: Vec::new(),
+            speculation_cancel_flag: None,
+            mesh_pool: MeshPool::new(),
+        }
+    }
+
+    pub fn set_stmt_started_callback(&mut self, callback: Box<dyn FnMut(usize)>) {
+        self.stmt_started_callback = Some(callback);
+    }
+
+    pub fn set_mesh_stream_callback(&mut self, callback: Box<dyn FnMut(usize, Arc<Mesh>)>) {
+        self.mesh_stream_callback = Some(callback);
+    }
+
+    pub fn set_checkpoint_callback(
+        &mut self,
+        callback: Box<dyn FnMut(VarIdent, &ast::CallExpr, &Value)>,
+    ) {
+        self.checkpoint_callback = Some(callback);
+    }
+
+    /// Takes over values saved from an earlier interpreter, e.g. one that
+    /// crashed during a long run. The values are only used if their
+    /// statements are still declared with the same calls, otherwise they
+    /// are invalidated. Values already in the environment are kept.
+    pub fn restore_values(&mut self, values: Vec<(VarIdent, ast::CallExpr, Value)>) {
+        for (var_ident, created_call, value) in values {
+            self.env.entry(var_ident).or_insert(VarValue {
+                created_call,
+                created_epoch: self.epoch,
+                value,
+                revalidate: false,
+            });
+        }
+
+        // Values computed from now on are newer than the restored ones, so
+        // that the restored values depending on them are invalidated
+        self.epoch += 1;
+    }
+
+    /// Sets the flag, which cancels the interpretation when raised by another
+    /// thread. The flag is never lowered by the interpreter.
+    pub fn set_cancel_flag(&mut self, cancel_flag: Arc<AtomicBool>) {
+        self.cancel_flag = Some(cancel_flag);
+    }
+
+    /// Sets the flag, which cancels speculative evaluation when raised by
+    /// another thread. The flag is never lowered by the interpreter.
+    pub fn set_speculation_cancel_flag(&mut self, cancel_flag: Arc<AtomicBool>) {
+        self.speculation_cancel_flag = Some(cancel_flag);
+    }
+
+    /// Switches draft mode on or off. Values computed in the other mode are
+    /// forgotten, so that the whole program is evaluated again.
+    pub fn set_draft(&mut self, draft: bool) {
+        if self.draft != draft {
+            self.draft = draft;
+            self.env.clear();
+            self.speculations.clear();
+        }
+    }
+
+    /// Evaluates a program that is likely to become the current program,
+    /// e.g. with a parameter the user is scrubbing set to a neighboring
+    /// value. If it does become the current program, interpreting takes the
+    /// values from the speculation instead of evaluating it again.
+    ///
+    /// The values of the current program are reused, but not modified. Only
+    /// the most recent speculations are kept.
+    ///
+    /// Statements calling impure funcs and the statements depending on them
+    /// are not evaluated, as they are evaluated again when interpreting.
+    ///
+    /// Returns whether the program was evaluated. The evaluation stops at
+    /// the first error or when the speculation cancel flag is raised.
+    pub fn speculate(&mut self, prog: ast::Prog) -> bool {
+        if self
+            .speculations
+            .iter()
+            .any(|speculation| speculation.prog == prog)
+        {
+            return true;
+        }
+        if resolve_prog(&prog, &self.funcs).is_err() {
+            return false;
+        }
+
+        // Values of impure funcs and their dependents are evaluated again
+        // when interpreting anyway, so speculating them would only waste
+        // time. The remaining statements never depend on them.
+        let mut unspeculable_vars = HashSet::new();
+        for stmt in prog.stmts() {
+            match stmt {
+                ast::Stmt::VarDecl(var_decl) => {
+                    let init_expr = var_decl.init_expr();
+                    let impure = !self.funcs[&init_expr.ident()]
+                        .flags()
+                        .contains(FuncFlags::PURE);
+                    let depends_on_unspeculable = init_expr.args().iter().any(|arg| match arg {
+                        ast::Expr::Var(var) => unspeculable_vars.contains(&var.ident()),
+                        ast::Expr::Lit(_) => false,
+                    });
+
+                    if impure || depends_on_unspeculable {
+                        unspeculable_vars.insert(var_decl.ident());
+                    }
+                }
+            }
+        }
+        if unspeculable_vars.len() == prog.stmts().len() {
+            return false;
+        }
+
+        let mut env = self.env.clone();
+        invalidate_env(&prog, &self.funcs, &mut env);
+
+        let mut log_messages = vec![Vec::new(); prog.stmts().len()];
+        let cancel_flag = self.speculation_cancel_flag.as_ref();
+
+        log::debug!("Starting speculative program evaluation");
+
+        for (stmt_index, stmt) in prog.stmts().iter().enumerate() {
+            if is_cancelled(cancel_flag) {
+                log::debug!("Cancelled speculative program evaluation");
+                return false;
+            }
+
+            let result = match stmt {
+                ast::Stmt::VarDecl(var_decl) if unspeculable_vars.contains(&var_decl.ident()) => {
+                    continue;
+                }
+                ast::Stmt::VarDecl(var_decl) => eval_var_decl_stmt(
+                    stmt_index,
+                    var_decl,
+                    self.epoch,
+                    self.draft,
+                    &mut self.funcs,
+                    &mut env,
+                    &mut self.mesh_pool,
+                    &mut |message| log_messages[stmt_index].push(message),
+                    &mut |_| !is_cancelled(cancel_flag),
+                ),
+            };
+
+            if result.is_err() {
+                log::debug!("Speculative program evaluation failed, discarding it");
+                return false;
+            }
         }
+
+        if self.speculations.len() >= SPECULATIONS_CAPACITY {
+            self.speculations.remove(0);
+        }
+        self.speculations.push(Speculation {
+            prog,
+            env,
+            log_messages,
+        });
+
+        true
     }
 
     #[allow(dead_code)]
@@ -427,38 +733,7 @@ impl Interpreter {
             return Ok(());
         }
 
-        let mut var_scope = HashSet::new();
-
-        for (stmt_index, stmt) in self.prog.stmts().iter().enumerate() {
-            match stmt {
-                ast::Stmt::VarDecl(var_decl) => {
-                    if var_scope.contains(&var_decl.ident()) {
-                        return Err(ResolveError::VarRedefinition {
-                            stmt_index,
-                            var: var_decl.ident(),
-                        });
-                    }
-
-                    let func = var_decl.init_expr().ident();
-                    if self.funcs.get(&func).is_none() {
-                        return Err(ResolveError::UndeclaredFuncUse { stmt_index, func });
-                    }
-
-                    for arg in var_decl.init_expr().args() {
-                        if let ast::Expr::Var(var) = arg {
-                            if !var_scope.contains(&var.ident()) {
-                                return Err(ResolveError::UndeclaredVarUse {
-                                    stmt_index,
-                                    var: var.ident(),
-                                });
-                            }
-                        }
-                    }
-
-                    var_scope.insert(var_decl.ident());
-                }
-            }
-        }
+        resolve_prog(&self.prog, &self.funcs)?;
 
         // Mark current epoch as name-resolved.
         self.last_resolve_epoch = self.epoch;
@@ -503,27 +778,140 @@ impl Interpreter {
 
         index = cmp::min(index, self.prog.stmts().len().saturating_sub(1));
 
-        self.invalidate();
         for log_messages in &mut self.log_messages {
             log_messages.clear();
         }
 
+        // The values of a speculation are valid for the program as a whole,
+        // so they replace the current values.
+        let prog = &self.prog;
+        if let Some(speculation_index) = self
+            .speculations
+            .iter()
+            .position(|speculation| speculation.prog == *prog)
+        {
+            log::debug!("Taking values from speculative program evaluation");
+
+            let speculation = self.speculations.remove(speculation_index);
+            self.env = speculation.env;
+            for (log_messages, speculation_log_messages) in
+                self.log_messages.iter_mut().zip(speculation.log_messages)
+            {
+                log_messages.extend(speculation_log_messages);
+            }
+        }
+
+        self.invalidate();
+
         log::debug!("Starting program evaluation with PC: 0");
 
+        let cancel_flag = self.cancel_flag.as_ref();
+        let mesh_stream_callback = &mut self.mesh_stream_callback;
+        let checkpoint_callback = &mut self.checkpoint_callback;
+
         for (stmt_index, stmt) in self.prog.stmts()[0..=index].iter().enumerate() {
-            if let Err(err) = eval_stmt(
+            if is_cancelled(cancel_flag) {
+                log::debug!("Cancelled program evaluation with PC: {}", stmt_index);
+
+                let call = match stmt {
+                    ast::Stmt::VarDecl(var_decl) => var_decl.init_expr().clone(),
+                };
+
+                return InterpretOutcome {
+                    result: Err(InterpretError::from(RuntimeError::Cancelled {
+                        stmt_index,
+                        call,
+                    })),
+                    pc: stmt_index,
+                    log_messages: self.log_messages.clone(),
+                };
+            }
+
+            if let Some(stmt_started_callback) = &mut self.stmt_started_callback {
+                stmt_started_callback(stmt_index);
+            }
+
+            let cached = match eval_stmt(
                 stmt_index,
                 stmt,
                 self.epoch,
+                self.draft,
                 &mut self.funcs,
                 &mut self.env,
+                &mut self.mesh_pool,
                 &mut self.log_messages,
+                &mut |mesh| {
+                    if let Some(mesh_stream_callback) = mesh_stream_callback {
+                        mesh_stream_callback(stmt_index, mesh);
+                    }
+
+                    !is_cancelled(cancel_flag)
+                },
             ) {
-                return InterpretOutcome {
-                    result: Err(InterpretError::from(err)),
-                    pc: stmt_index + 1,
-                    log_messages: self.log_messages.clone(),
-                };
+                Ok(cached) => cached,
+                Err(err) => {
+                    return InterpretOutcome {
+                        result: Err(InterpretError::from(err)),
+                        pc: stmt_index + 1,
+                        log_messages: self.log_messages.clone(),
+                    };
+                }
+            };
+
+            if !cached {
+                if let Some(checkpoint_callback) = checkpoint_callback {
+                    match stmt {
+                        ast::Stmt::VarDecl(var_decl) => {
+                            let var_info = &self.env[&var_decl.ident()];
+                            if !generate_lazy_meshes(&var_info.value, cancel_flag) {
+                                return InterpretOutcome {
+                                    result: Err(InterpretError::from(RuntimeError::Cancelled {
+                                        stmt_index,
+                                        call: var_decl.init_expr().clone(),
+                                    })),
+                                    pc: stmt_index + 1,
+                                    log_messages: self.log_messages.clone(),
+                                };
+                            }
+
+                            checkpoint_callback(
+                                var_decl.ident(),
+                                &var_info.created_call,
+                                &var_info.value,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        // The values are sent to other threads, which must not run the
+        // generators of lazy mesh arrays, as neither cancellation nor the
+        // statement timeout would apply there. The meshes are generated here
+        // on behalf of the statements producing them.
+        for (stmt_index, stmt) in self.prog.stmts()[0..=index].iter().enumerate() {
+            match stmt {
+                ast::Stmt::VarDecl(var_decl) => {
+                    let value = &self.env[&var_decl.ident()].value;
+                    if let Value::MeshArray(mesh_array) = value {
+                        if mesh_array.is_lazy() {
+                            if let Some(stmt_started_callback) = &mut self.stmt_started_callback {
+                                stmt_started_callback(stmt_index);
+                            }
+                        }
+                    }
+
+                    if !generate_lazy_meshes(value, cancel_flag) {
+                        return InterpretOutcome {
+                            result: Err(InterpretError::from(RuntimeError::Cancelled {
+                                stmt_index,
+                                call: var_decl.init_expr().clone(),
+                            })),
+                            pc: stmt_index + 1,
+                            log_messages: self.log_messages.clone(),
+                        };
+                    }
+                }
             }
         }
 
@@ -615,7 +1003,9 @@ impl Interpreter {
     /// There are 3 types of variable invalidation:
     ///
     /// 1) Impurity invalidation: the function producing the variable
-    ///    is not pure (import, random, etc.)
+    ///    is not pure (import, random, etc.). The value is not removed, but
+    ///    computed again before it is used. Its dependents are only
+    ///    invalidated, if the new value differs.
     /// 2) Definition invalidation: the call expression definition has
     ///    changed (either the function or the parameters),
     /// 3) Dependency invalidation: dependencies (variables referenced in the
@@ -630,113 +1020,201 @@ impl Interpreter {
     ///    statement that produces a newer variable without any other
     ///    invalidation being triggered.
     fn invalidate(&mut self) {
-        // FIXME: We'd like to have this return an execution plan so
-        // that we don't necessarily try to execute stmts only to find
-        // that we already have the results in cache.
+        if invalidate_env(&self.prog, &self.funcs, &mut self.env) {
+            // Values of impure funcs computed again in this run must be newer
+            // than the values computed from them in earlier runs, so that
+            // those are invalidated, if the values changed.
+            self.epoch += 1;
+        }
+    }
+}
 
-        // FIXME: This is still very pessimistic, we should support an
-        // incremental computation model with fact verification a-lá
-        // salsa. https://github.com/salsa-rs/salsa
+/// Statically verifies the program. See `Interpreter::resolve`.
+fn resolve_prog(
+    prog: &ast::Prog,
+    funcs: &BTreeMap<FuncIdent, Box<dyn Func>>,
+) -> Result<(), ResolveError> {
+    let mut var_scope = HashSet::new();
 
-        for stmt in self.prog.stmts() {
-            match stmt {
-                ast::Stmt::VarDecl(var_decl) => {
-                    let var_ident = var_decl.ident();
-                    let init_expr = var_decl.init_expr();
-                    let func_ident = init_expr.ident();
+    for (stmt_index, stmt) in prog.stmts().iter().enumerate() {
+        match stmt {
+            ast::Stmt::VarDecl(var_decl) => {
+                if var_scope.contains(&var_decl.ident()) {
+                    return Err(ResolveError::VarRedefinition {
+                        stmt_index,
+                        var: var_decl.ident(),
+                    });
+                }
 
-                    // Perform 1) Impurity invalidation
+                let func = var_decl.init_expr().ident();
+                if funcs.get(&func).is_none() {
+                    return Err(ResolveError::UndeclaredFuncUse { stmt_index, func });
+                }
 
-                    if !self.funcs[&func_ident].flags().contains(FuncFlags::PURE) {
-                        log::debug!("Performing impurity invalidation of {}", var_ident);
-                        self.env.remove(&var_ident);
+                for arg in var_decl.init_expr().args() {
+                    if let ast::Expr::Var(var) = arg {
+                        if !var_scope.contains(&var.ident()) {
+                            return Err(ResolveError::UndeclaredVarUse {
+                                stmt_index,
+                                var: var.ident(),
+                            });
+                        }
+                    }
+                }
 
-                        continue;
+                var_scope.insert(var_decl.ident());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Invalidates the variables in the environment, which may change when
+/// evaluating the program. See `Interpreter::invalidate`.
+///
+/// Returns whether any values of impure funcs are to be computed again.
+fn invalidate_env(
+    prog: &ast::Prog,
+    funcs: &BTreeMap<FuncIdent, Box<dyn Func>>,
+    env: &mut HashMap<VarIdent, VarValue>,
+) -> bool {
+    // FIXME: We'd like to have this return an execution plan so
+    // that we don't necessarily try to execute stmts only to find
+    // that we already have the results in cache.
+
+    // FIXME: This is still very pessimistic, we should support an
+    // incremental computation model with fact verification a-lá
+    // salsa. https://github.com/salsa-rs/salsa
+
+    let mut revalidating = false;
+
+    for stmt in prog.stmts() {
+        match stmt {
+            ast::Stmt::VarDecl(var_decl) => {
+                let var_ident = var_decl.ident();
+                let init_expr = var_decl.init_expr();
+                let func_ident = init_expr.ident();
+
+                // Perform 1) Impurity invalidation
+
+                if !funcs[&func_ident].flags().contains(FuncFlags::PURE) {
+                    if let Some(var_info) = env.get_mut(&var_ident) {
+                        log::debug!("Performing impurity invalidation of {}", var_ident);
+                        var_info.revalidate = true;
+                        revalidating = true;
                     }
+                }
 
-                    // Perform 2) Definition invalidation
+                // Perform 2) Definition invalidation
 
-                    if let Entry::Occupied(occupied) = self.env.entry(var_ident) {
-                        let var_info = occupied.get();
-                        let created_call = &var_info.created_call;
+                if let Entry::Occupied(occupied) = env.entry(var_ident) {
+                    let var_info = occupied.get();
+                    let created_call = &var_info.created_call;
 
-                        if created_call != init_expr {
-                            log::debug!("Performing definition invalidation of {}", var_ident);
-                            occupied.remove_entry();
+                    if created_call != init_expr {
+                        log::debug!("Performing definition invalidation of {}", var_ident);
+                        occupied.remove_entry();
 
-                            continue;
-                        }
+                        continue;
                     }
+                }
 
-                    // Perform 3) Dependency invalidation
-
-                    for dependency_expr in var_decl.init_expr().args() {
-                        if let ast::Expr::Var(dependency_var) = dependency_expr {
-                            if let Some(dependency) = self.env.get(&dependency_var.ident()) {
-                                let dependency_created_epoch = dependency.created_epoch;
-                                // FIXME: While it would have been simpler to
-                                // remove stale variables from the cache when
-                                // statements are popped from the program
-                                // (pop_prog_stmt), we might want to make cache
-                                // clearing explicit so that the user can
-                                // potentially keep the cache warm and still get
-                                // the correct results. Note that we do clear
-                                // all stored logs in all program manipulation
-                                // methods and perhaps we also shouldn't, and
-                                // instead of storing the logs in the
-                                // interpreter just publish them to a callback
-                                // as they are being produced. This would also
-                                // potentially alleviate the need for some
-                                // clones, and even save allocations in case the
-                                // log messages are completely static.
-
-                                if let Entry::Occupied(occupied) = self.env.entry(var_ident) {
-                                    let dependent = occupied.get();
-
-                                    if dependency_created_epoch > dependent.created_epoch {
-                                        log::debug!(
-                                            "Performing dependency invalidation of {} (dep newer)",
-                                            var_ident,
-                                        );
-                                        occupied.remove_entry();
-
-                                        break;
-                                    }
+                // Perform 3) Dependency invalidation
+
+                for dependency_expr in var_decl.init_expr().args() {
+                    if let ast::Expr::Var(dependency_var) = dependency_expr {
+                        if let Some(dependency) = env.get(&dependency_var.ident()) {
+                            let dependency_created_epoch = dependency.created_epoch;
+                            // FIXME: While it would have been simpler to
+                            // remove stale variables from the cache when
+                            // statements are popped from the program
+                            // (pop_prog_stmt), we might want to make cache
+                            // clearing explicit so that the user can
+                            // potentially keep the cache warm and still get
+                            // the correct results. Note that we do clear
+                            // all stored logs in all program manipulation
+                            // methods and perhaps we also shouldn't, and
+                            // instead of storing the logs in the
+                            // interpreter just publish them to a callback
+                            // as they are being produced. This would also
+                            // potentially alleviate the need for some
+                            // clones, and even save allocations in case the
+                            // log messages are completely static.
+
+                            if let Entry::Occupied(occupied) = env.entry(var_ident) {
+                                let dependent = occupied.get();
+
+                                if dependency_created_epoch > dependent.created_epoch {
+                                    log::debug!(
+                                        "Performing dependency invalidation of {} (dep newer)",
+                                        var_ident,
+                                    );
+                                    occupied.remove_entry();
+
+                                    break;
                                 }
-                            } else {
-                                log::debug!(
-                                    "Performing dependency invalidation of {} (dep invalidated)",
-                                    var_ident,
-                                );
-                                self.env.remove(&var_ident);
-
-                                break;
                             }
+                        } else {
+                            log::debug!(
+                                "Performing dependency invalidation of {} (dep invalidated)",
+                                var_ident,
+                            );
+                            env.remove(&var_ident);
+
+                            break;
                         }
                     }
                 }
             }
         }
     }
+
+    revalidating
+}
+
+fn is_cancelled(cancel_flag: Option<&Arc<AtomicBool>>) -> bool {
+    cancel_flag.map_or(false, |cancel_flag| cancel_flag.load(Ordering::SeqCst))
+}
+
+/// Generates the meshes of the value, if it is a lazy mesh array, unless
+/// cancelled. Returns whether all meshes are generated.
+fn generate_lazy_meshes(value: &Value, cancel_flag: Option<&Arc<AtomicBool>>) -> bool {
+    match value {
+        Value::MeshArray(mesh_array) => mesh_array.generate(&mut || !is_cancelled(cancel_flag)),
+        _ => true,
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn eval_stmt(
     stmt_index: usize,
     stmt: &ast::Stmt,
     epoch: u64,
+    draft: bool,
     funcs: &mut BTreeMap<FuncIdent, Box<dyn Func>>,
     env: &mut HashMap<VarIdent, VarValue>,
+    mesh_pool: &mut MeshPool,
     log_messages: &mut [Vec<LogMessage>],
-) -> Result<(), RuntimeError> {
+    stream: &mut dyn FnMut(Arc<Mesh>) -> bool,
+) -> Result<bool, RuntimeError> {
     let time_start = Instant::now();
     log::debug!("Evaluating stmt {}: {}", stmt_index, stmt);
 
     let result = match stmt {
-        ast::Stmt::VarDecl(var_decl) => {
-            eval_var_decl_stmt(stmt_index, var_decl, epoch, funcs, env, &mut |message| {
+        ast::Stmt::VarDecl(var_decl) => eval_var_decl_stmt(
+            stmt_index,
+            var_decl,
+            epoch,
+            draft,
+            funcs,
+            env,
+            mesh_pool,
+            &mut |message| {
                 log_messages[stmt_index].push(message);
-            })
-        }
+            },
+            stream,
+        ),
     };
 
     let elapsed_ms = time_start.elapsed().as_secs_f32() * 1000.0;
@@ -754,7 +1232,7 @@ fn eval_stmt(
                     .push(LogMessage::info(format!(">>> Took {:.2}ms", elapsed_ms)));
             }
 
-            Ok(())
+            Ok(cached)
         }
         Err(err) => {
             log_messages[stmt_index].push(LogMessage::error(format!(
@@ -767,50 +1245,71 @@ fn eval_stmt(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn eval_var_decl_stmt(
     stmt_index: usize,
     var_decl: &ast::VarDeclStmt,
     epoch: u64,
+    draft: bool,
     funcs: &mut BTreeMap<FuncIdent, Box<dyn Func>>,
     env: &mut HashMap<VarIdent, VarValue>,
+    mesh_pool: &mut MeshPool,
     log: &mut dyn FnMut(LogMessage),
+    stream: &mut dyn FnMut(Arc<Mesh>) -> bool,
 ) -> Result<bool, RuntimeError> {
     let var_ident = var_decl.ident();
 
-    // This is a false positive. Bad Clippy, bad! Rewriting the code
-    // to use the entry API would fail borrowchecking (and cause
-    // pointer invalidation if it didn't!). The entry would create a
-    // long-lived mutable borrow which simply can't be live when we
-    // call into `eval_call_expr`, which borrows again. Note that
-    // having `eval_call_expr` guarded by the map access is the whole
-    // point here.
-
-    // FIXME: remove allow after
-    // https://github.com/rust-lang/rust-clippy/issues/4674 is
-    // resolved
-
-    #[allow(clippy::map_entry)]
-    {
-        if env.contains_key(&var_ident) {
-            Ok(true)
-        } else {
-            let init_expr = var_decl.init_expr();
-            let value = eval_call_expr(stmt_index, init_expr, funcs, env, log)?;
+    if is_cached(var_decl, env) {
+        Ok(true)
+    } else {
+        let init_expr = var_decl.init_expr();
+        let value = eval_call_expr(stmt_index, init_expr, draft, funcs, env, log, stream)?;
+        let value = mesh_pool.dedup_value(value);
+
+        let unchanged = match env.get_mut(&var_ident) {
+            Some(var_info) if var_info.revalidate && var_info.value == value => {
+                // The impure func computed the same value again, so the
+                // values computed from the kept value remain valid
+                var_info.revalidate = false;
+                true
+            }
+            _ => false,
+        };
 
+        if !unchanged {
             env.insert(
                 var_ident,
                 VarValue {
                     created_call: init_expr.clone(),
                     created_epoch: epoch,
                     value,
+                    revalidate: false,
                 },
             );
-
-            Ok(false)
         }
+
+        Ok(false)
     }
 }
 
+/// Checks whether the stored value of the variable can be used as it is,
+/// i.e. it is not to be computed again and none of its dependencies has been
+/// computed after it.
+fn is_cached(var_decl: &ast::VarDeclStmt, env: &HashMap<VarIdent, VarValue>) -> bool {
+    let var_info = match env.get(&var_decl.ident()) {
+        Some(var_info) => var_info,
+        None => return false,
+    };
+
+    !var_info.revalidate
+        && var_decl.init_expr().args().iter().all(|arg| match arg {
+            ast::Expr::Var(var) => env.get(&var.ident()).map_or(false, |dependency| {
+                dependency.created_epoch <= var_info.created_epoch
+            }),
+            ast::Expr::Lit(_) => true,
+        })
+}
+
 fn eval_expr(
     expr: &ast::Expr,
     env: &mut HashMap<VarIdent, VarValue>,
@@ -849,9 +1348,11 @@ fn eval_var_expr(
 fn eval_call_expr(
     stmt_index: usize,
     call: &ast::CallExpr,
+    draft: bool,
     funcs: &mut BTreeMap<FuncIdent, Box<dyn Func>>,
     env: &mut HashMap<VarIdent, VarValue>,
     log: &mut dyn FnMut(LogMessage),
+    stream: &mut dyn FnMut(Arc<Mesh>) -> bool,
 ) -> Result<Value, RuntimeError> {
     // FIXME: @Diagnostics use the func name and the param names in
     // the reported errors
@@ -859,7 +1360,7 @@ fn eval_call_expr(
     let func = funcs.get_mut(&call.ident()).expect("Failed to find func");
 
     let arg_exprs = call.args();
-    if func.param_info().len() != arg_exprs.len() {
+    if !func.accepts_arg_count(arg_exprs.len()) {
         return Err(RuntimeError::ArgCountMismatch {
             stmt_index,
             call: call.clone(),
@@ -874,7 +1375,11 @@ fn eval_call_expr(
         args.push(arg);
     }
 
-    for (info, value) in func.param_info().iter().zip(args.iter()) {
+    for (arg_index, value) in args.iter().enumerate() {
+        let param_index = func
+            .param_index_for_arg(arg_index, args.len())
+            .expect("Argument count must have been checked");
+        let info = &func.param_info()[param_index];
         let param_ty = info.refinement.ty();
         let value_ty = value.ty();
 
@@ -894,7 +1399,73 @@ fn eval_call_expr(
         }
     }
 
-    match func.call(&args, log) {
+    if draft {
+        let arg_count = args.len();
+        let mut drafted = false;
+        for (arg_index, arg) in args.iter_mut().enumerate() {
+            let param_index = func
+                .param_index_for_arg(arg_index, arg_count)
+                .expect("Argument count must have been checked");
+            if let Some(draft_arg) = func.draft_arg(param_index, arg) {
+                *arg = draft_arg;
+                drafted = true;
+            }
+        }
+
+        if drafted {
+            log(LogMessage::info(
+                "Draft quality, some parameters were lowered",
+            ));
+        }
+    }
+
+    // Cheap checks of the meshes spare the user a failed or slow computation
+    for (arg_index, value) in args.iter().enumerate() {
+        let param_index = func
+            .param_index_for_arg(arg_index, args.len())
+            .expect("Argument count must have been checked");
+        let requirements = func.mesh_requirements(param_index);
+        if requirements.is_empty() {
+            continue;
+        }
+
+        let unmet = match value {
+            Value::Mesh(mesh) => requirements.unmet_by(mesh),
+            Value::MeshArray(mesh_array) => mesh_array
+                .iter_refcounted()
+                .fold(MeshRequirements::empty(), |unmet, mesh| {
+                    unmet | requirements.unmet_by(&mesh)
+                }),
+            _ => MeshRequirements::empty(),
+        };
+
+        if !unmet.is_empty() {
+            return Err(RuntimeError::ArgMeshRequirementsUnmet {
+                stmt_index,
+                call: call.clone(),
+                param_name: func.param_info()[param_index].name,
+                unmet,
+            });
+        }
+    }
+
+    let mut cancelled = false;
+    let result = func.call_streaming(&args, log, &mut |mesh| {
+        let go_on = stream(mesh);
+        cancelled |= !go_on;
+        go_on
+    });
+
+    // Whatever the func returned after being told to stop is discarded, as
+    // it may be incomplete.
+    if cancelled {
+        return Err(RuntimeError::Cancelled {
+            stmt_index,
+            call: call.clone(),
+        });
+    }
+
+    match result {
         Ok(value) => {
             let return_ty = func.return_ty();
             let value_ty = value.ty();
@@ -920,8 +1491,14 @@ fn eval_call_expr(
 
 #[cfg(test)]
 mod tests {
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
+    use std::sync::atomic::AtomicU32;
+
+    use nalgebra::{Point3, Rotation3, Vector2, Vector3};
+
+    use crate::mesh::primitive;
+    use crate::plane::Plane;
 
     use super::*;
 
@@ -942,6 +1519,7 @@ mod tests {
                 Ty::MeshArray => ParamRefinement::MeshArray,
             },
             optional,
+            widget: ParamWidget::Default,
         }
     }
 
@@ -952,6 +1530,9 @@ mod tests {
         func: F,
         flags: FuncFlags,
         param_info: Vec<ParamInfo>,
+        variadic_param_index: Option<usize>,
+        mesh_requirements: MeshRequirements,
+        voxel_size_param_index: Option<usize>,
         return_ty: Ty,
     }
 
@@ -964,6 +1545,9 @@ mod tests {
                 flags,
                 func,
                 param_info,
+                variadic_param_index: None,
+                mesh_requirements: MeshRequirements::empty(),
+                voxel_size_param_index: None,
                 return_ty,
             }
         }
@@ -981,6 +1565,18 @@ mod tests {
             &self.param_info
         }
 
+        fn variadic_param_index(&self) -> Option<usize> {
+            self.variadic_param_index
+        }
+
+        fn mesh_requirements(&self, _param_index: usize) -> MeshRequirements {
+            self.mesh_requirements
+        }
+
+        fn voxel_size_param_index(&self) -> Option<usize> {
+            self.voxel_size_param_index
+        }
+
         fn return_ty(&self) -> Ty {
             self.return_ty
         }
@@ -1577,7 +2173,7 @@ mod tests {
             TestFunc::new(
                 move |_| {
                     c1.inc();
-                    Ok(Value::Boolean(true))
+                    Ok(Value::Boolean(c1.get() % 2 == 1))
                 },
                 FuncFlags::empty(),
                 vec![],
@@ -1623,14 +2219,78 @@ mod tests {
         assert_eq!(value.last_value, Some(Value::Boolean(true)));
 
         let value = interpreter.interpret().result.unwrap();
-        assert_eq!(value.last_value, Some(Value::Boolean(true)));
+        assert_eq!(value.last_value, Some(Value::Boolean(false)));
 
         assert_eq!(n_calls1.get(), 2);
         assert_eq!(n_calls2.get(), 2);
     }
 
     #[test]
-    fn test_interpreter_interpret_newer_parameter_value_dependency_invalidation() {
+    fn test_interpreter_interpret_func_chain_unchanged_impure_dependency() {
+        let n_calls1 = Rc::new(CallCount::new());
+        let n_calls2 = Rc::new(CallCount::new());
+        let c1 = Rc::clone(&n_calls1);
+        let c2 = Rc::clone(&n_calls2);
+
+        let (func_id1, func1) = (
+            FuncIdent(0),
+            TestFunc::new(
+                move |_| {
+                    c1.inc();
+                    Ok(Value::Boolean(true))
+                },
+                FuncFlags::empty(),
+                vec![],
+                Ty::Boolean,
+            ),
+        );
+
+        let (func_id2, func2) = (
+            FuncIdent(1),
+            TestFunc::new(
+                move |values| {
+                    c2.inc();
+                    Ok(Value::Boolean(values[0].unwrap_boolean()))
+                },
+                FuncFlags::PURE,
+                vec![param_info(Ty::Boolean, false)],
+                Ty::Boolean,
+            ),
+        );
+
+        let prog = ast::Prog::new(vec![
+            ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                VarIdent(0),
+                ast::CallExpr::new(func_id1, vec![]),
+            )),
+            ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                VarIdent(1),
+                ast::CallExpr::new(
+                    func_id2,
+                    vec![ast::Expr::Var(ast::VarExpr::new(VarIdent(0)))],
+                ),
+            )),
+        ]);
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(func_id1, Box::new(func1));
+        funcs.insert(func_id2, Box::new(func2));
+
+        let mut interpreter = Interpreter::new(funcs);
+        interpreter.set_prog(prog);
+
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.last_value, Some(Value::Boolean(true)));
+
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.last_value, Some(Value::Boolean(true)));
+
+        assert_eq!(n_calls1.get(), 2);
+        assert_eq!(n_calls2.get(), 1);
+    }
+
+    #[test]
+    fn test_interpreter_interpret_newer_parameter_value_dependency_invalidation() {
         // We have 3 pure functions. Functions 1 and 2 have the same signature
         // except for the ident. When we do the following, we test that when a
         // dependency has a newer value, the dependent get's invalidated:
@@ -1975,6 +2635,433 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_interpreter_interpret_mesh_requirements() {
+        let (create_func_id, create_func) = (
+            FuncIdent(0),
+            TestFunc::new(
+                |values| {
+                    let mesh = if values[0].unwrap_boolean() {
+                        primitive::create_box(
+                            Point3::origin(),
+                            Rotation3::identity(),
+                            Vector3::new(1.0, 1.0, 1.0),
+                        )
+                    } else {
+                        primitive::create_mesh_plane(
+                            Plane::from_origin_and_normal(&Point3::origin(), &Vector3::z()),
+                            Vector2::new(1.0, 1.0),
+                        )
+                    };
+
+                    Ok(Value::Mesh(Arc::new(mesh)))
+                },
+                FuncFlags::PURE,
+                vec![param_info(Ty::Boolean, false)],
+                Ty::Mesh,
+            ),
+        );
+        let (consume_func_id, mut consume_func) = (
+            FuncIdent(1),
+            TestFunc::new(
+                |_| Ok(Value::Nil),
+                FuncFlags::PURE,
+                vec![param_info(Ty::Mesh, false)],
+                Ty::Nil,
+            ),
+        );
+        consume_func.mesh_requirements = MeshRequirements::WATERTIGHT;
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(create_func_id, Box::new(create_func));
+        funcs.insert(consume_func_id, Box::new(consume_func));
+
+        let mut interpreter = Interpreter::new(funcs);
+
+        let prog = |closed: bool| {
+            ast::Prog::new(vec![
+                ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                    VarIdent(0),
+                    ast::CallExpr::new(
+                        create_func_id,
+                        vec![ast::Expr::Lit(ast::LitExpr::Boolean(closed))],
+                    ),
+                )),
+                ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                    VarIdent(1),
+                    ast::CallExpr::new(
+                        consume_func_id,
+                        vec![ast::Expr::Var(ast::VarExpr::new(VarIdent(0)))],
+                    ),
+                )),
+            ])
+        };
+
+        interpreter.set_prog(prog(true));
+        assert!(interpreter.interpret().result.is_ok());
+
+        interpreter.set_prog(prog(false));
+        let err = interpreter.interpret().result.unwrap_err();
+        assert_eq!(
+            err,
+            InterpretError::from(RuntimeError::ArgMeshRequirementsUnmet {
+                stmt_index: 1,
+                call: ast::CallExpr::new(
+                    consume_func_id,
+                    vec![ast::Expr::Var(ast::VarExpr::new(VarIdent(0)))],
+                ),
+                param_name: "<anonymous>",
+                unmet: MeshRequirements::WATERTIGHT,
+            }),
+        );
+    }
+
+    #[test]
+    fn test_interpreter_interpret_draft() {
+        let call_count = Rc::new(CallCount::new());
+        let call_count_in_func = Rc::clone(&call_count);
+        let (func_id, mut func) = (
+            FuncIdent(0),
+            TestFunc::new(
+                move |values| {
+                    call_count_in_func.inc();
+                    Ok(values[0].clone())
+                },
+                FuncFlags::PURE,
+                vec![param_info(Ty::Float3, false)],
+                Ty::Float3,
+            ),
+        );
+        func.voxel_size_param_index = Some(0);
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(func_id, Box::new(func));
+
+        let mut interpreter = Interpreter::new(funcs);
+        interpreter.set_prog(ast::Prog::new(vec![ast::Stmt::VarDecl(
+            ast::VarDeclStmt::new(
+                VarIdent(0),
+                ast::CallExpr::new(
+                    func_id,
+                    vec![ast::Expr::Lit(ast::LitExpr::Float3([0.5, 1.0, 2.0]))],
+                ),
+            ),
+        )]));
+
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.last_value, Some(Value::Float3([0.5, 1.0, 2.0])));
+        assert_eq!(call_count.get(), 1);
+
+        interpreter.set_draft(true);
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.last_value, Some(Value::Float3([1.0, 2.0, 4.0])));
+        assert_eq!(call_count.get(), 2);
+
+        // Switching to the current mode keeps the values
+        interpreter.set_draft(true);
+        interpreter.interpret().result.unwrap();
+        assert_eq!(call_count.get(), 2);
+
+        interpreter.set_draft(false);
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.last_value, Some(Value::Float3([0.5, 1.0, 2.0])));
+        assert_eq!(call_count.get(), 3);
+    }
+
+    #[test]
+    fn test_interpreter_interpret_speculated_prog() {
+        let call_count = Rc::new(CallCount::new());
+        let call_count_in_func = Rc::clone(&call_count);
+        let (func_id, func) = (
+            FuncIdent(0),
+            TestFunc::new(
+                move |values| {
+                    call_count_in_func.inc();
+                    Ok(Value::Float(values[0].unwrap_float() * 2.0))
+                },
+                FuncFlags::PURE,
+                vec![param_info(Ty::Float, false)],
+                Ty::Float,
+            ),
+        );
+        let (impure_func_id, impure_func) = (
+            FuncIdent(1),
+            TestFunc::new(
+                |_| Ok(Value::Nil),
+                FuncFlags::empty(),
+                vec![param_info(Ty::Float, false)],
+                Ty::Nil,
+            ),
+        );
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(func_id, Box::new(func));
+        funcs.insert(impure_func_id, Box::new(impure_func));
+
+        let prog = |func_id: FuncIdent, float: f32| {
+            ast::Prog::new(vec![ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                VarIdent(0),
+                ast::CallExpr::new(func_id, vec![ast::Expr::Lit(ast::LitExpr::Float(float))]),
+            ))])
+        };
+
+        let mut interpreter = Interpreter::new(funcs);
+        interpreter.set_prog(prog(func_id, 1.0));
+        interpreter.interpret().result.unwrap();
+        assert_eq!(call_count.get(), 1);
+
+        assert!(interpreter.speculate(prog(func_id, 2.0)));
+        assert!(interpreter.speculate(prog(func_id, 2.0)));
+        assert_eq!(call_count.get(), 2);
+
+        interpreter.set_prog(prog(func_id, 2.0));
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.last_value, Some(Value::Float(4.0)));
+        assert_eq!(call_count.get(), 2);
+
+        assert!(!interpreter.speculate(prog(impure_func_id, 3.0)));
+    }
+
+    #[test]
+    fn test_interpreter_interpret_speculated_prog_with_impure_stmt() {
+        let call_count = Rc::new(CallCount::new());
+        let call_count_in_func = Rc::clone(&call_count);
+        let (func_id, func) = (
+            FuncIdent(0),
+            TestFunc::new(
+                move |values| {
+                    call_count_in_func.inc();
+                    Ok(Value::Float(values[0].unwrap_float() * 2.0))
+                },
+                FuncFlags::PURE,
+                vec![param_info(Ty::Float, false)],
+                Ty::Float,
+            ),
+        );
+        let impure_call_count = Rc::new(CallCount::new());
+        let impure_call_count_in_func = Rc::clone(&impure_call_count);
+        let (impure_func_id, impure_func) = (
+            FuncIdent(1),
+            TestFunc::new(
+                move |values| {
+                    impure_call_count_in_func.inc();
+                    Ok(Value::Float(values[0].unwrap_float()))
+                },
+                FuncFlags::empty(),
+                vec![param_info(Ty::Float, false)],
+                Ty::Float,
+            ),
+        );
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(func_id, Box::new(func));
+        funcs.insert(impure_func_id, Box::new(impure_func));
+
+        let prog = |float: f32| {
+            ast::Prog::new(vec![
+                ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                    VarIdent(0),
+                    ast::CallExpr::new(
+                        impure_func_id,
+                        vec![ast::Expr::Lit(ast::LitExpr::Float(1.0))],
+                    ),
+                )),
+                ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                    VarIdent(1),
+                    ast::CallExpr::new(func_id, vec![ast::Expr::Lit(ast::LitExpr::Float(float))]),
+                )),
+                ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                    VarIdent(2),
+                    ast::CallExpr::new(
+                        func_id,
+                        vec![ast::Expr::Var(ast::VarExpr::new(VarIdent(0)))],
+                    ),
+                )),
+            ])
+        };
+
+        let mut interpreter = Interpreter::new(funcs);
+        interpreter.set_prog(prog(1.0));
+        interpreter.interpret().result.unwrap();
+        assert_eq!(call_count.get(), 2);
+        assert_eq!(impure_call_count.get(), 1);
+
+        // Only the pure statement not depending on the impure one is
+        // evaluated ahead of time
+        assert!(interpreter.speculate(prog(2.0)));
+        assert_eq!(call_count.get(), 3);
+        assert_eq!(impure_call_count.get(), 1);
+
+        interpreter.set_prog(prog(2.0));
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.used_values, vec![(VarIdent(0), Value::Float(1.0))],);
+        assert_eq!(
+            value.unused_values,
+            vec![
+                (VarIdent(1), Value::Float(4.0)),
+                (VarIdent(2), Value::Float(2.0)),
+            ],
+        );
+        // The impure func computed the same value again, so the value
+        // depending on it is kept
+        assert_eq!(call_count.get(), 3);
+        assert_eq!(impure_call_count.get(), 2);
+    }
+
+    #[test]
+    fn test_interpreter_interpret_restored_values() {
+        let call_count = Rc::new(CallCount::new());
+        let call_count_in_func = Rc::clone(&call_count);
+        let (func_id, func) = (
+            FuncIdent(0),
+            TestFunc::new(
+                move |values| {
+                    call_count_in_func.inc();
+                    Ok(Value::Float(values[0].unwrap_float() * 2.0))
+                },
+                FuncFlags::PURE,
+                vec![param_info(Ty::Float, false)],
+                Ty::Float,
+            ),
+        );
+        let (impure_func_id, impure_func) = (
+            FuncIdent(1),
+            TestFunc::new(
+                |values| Ok(Value::Float(values[0].unwrap_float())),
+                FuncFlags::empty(),
+                vec![param_info(Ty::Float, false)],
+                Ty::Float,
+            ),
+        );
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(func_id, Box::new(func));
+        funcs.insert(impure_func_id, Box::new(impure_func));
+
+        let call = |func_id: FuncIdent, float: f32| {
+            ast::CallExpr::new(func_id, vec![ast::Expr::Lit(ast::LitExpr::Float(float))])
+        };
+        let var_call = ast::CallExpr::new(
+            func_id,
+            vec![ast::Expr::Var(ast::VarExpr::new(VarIdent(2)))],
+        );
+        let prog = ast::Prog::new(vec![
+            ast::Stmt::VarDecl(ast::VarDeclStmt::new(VarIdent(0), call(func_id, 1.0))),
+            ast::Stmt::VarDecl(ast::VarDeclStmt::new(VarIdent(1), call(func_id, 2.0))),
+            ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                VarIdent(2),
+                call(impure_func_id, 3.0),
+            )),
+            ast::Stmt::VarDecl(ast::VarDeclStmt::new(VarIdent(3), var_call.clone())),
+        ]);
+
+        let checkpointed = Rc::new(RefCell::new(Vec::new()));
+        let checkpointed_in_callback = Rc::clone(&checkpointed);
+
+        let mut interpreter = Interpreter::new(funcs);
+        interpreter.set_checkpoint_callback(Box::new(move |var_ident, _, value| {
+            checkpointed_in_callback
+                .borrow_mut()
+                .push((var_ident, value.clone()));
+        }));
+        interpreter.set_prog(prog);
+        // The value of the changed call is not taken over. The value
+        // computed from the impure value is, as the impure func computes the
+        // same value again.
+        interpreter.restore_values(vec![
+            (VarIdent(0), call(func_id, 1.0), Value::Float(5.0)),
+            (VarIdent(1), call(func_id, 7.0), Value::Float(14.0)),
+            (VarIdent(2), call(impure_func_id, 3.0), Value::Float(3.0)),
+            (VarIdent(3), var_call, Value::Float(9.0)),
+        ]);
+
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.used_values, vec![(VarIdent(2), Value::Float(3.0))]);
+        assert_eq!(
+            value.unused_values,
+            vec![
+                (VarIdent(0), Value::Float(5.0)),
+                (VarIdent(1), Value::Float(4.0)),
+                (VarIdent(3), Value::Float(9.0)),
+            ],
+        );
+        assert_eq!(call_count.get(), 1);
+        assert_eq!(
+            *checkpointed.borrow(),
+            vec![
+                (VarIdent(1), Value::Float(4.0)),
+                (VarIdent(2), Value::Float(3.0)),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_interpreter_interpret_single_func_variadic_args() {
+        let (func_id, mut func) = (
+            FuncIdent(0),
+            TestFunc::new(
+                |values| {
+                    let (negate, summands) = values.split_last().unwrap();
+                    let sum: f32 = summands.iter().map(Value::unwrap_float).sum();
+                    if negate.unwrap_boolean() {
+                        Ok(Value::Float(-sum))
+                    } else {
+                        Ok(Value::Float(sum))
+                    }
+                },
+                FuncFlags::PURE,
+                vec![param_info(Ty::Float, false), param_info(Ty::Boolean, false)],
+                Ty::Float,
+            ),
+        );
+        func.variadic_param_index = Some(0);
+
+        assert_eq!(func.param_index_for_arg(0, 1), None);
+        assert_eq!(func.param_index_for_arg(0, 4), Some(0));
+        assert_eq!(func.param_index_for_arg(2, 4), Some(0));
+        assert_eq!(func.param_index_for_arg(3, 4), Some(1));
+        assert_eq!(func.param_index_for_arg(4, 4), None);
+
+        let call = ast::CallExpr::new(
+            func_id,
+            vec![
+                ast::Expr::Lit(ast::LitExpr::Float(1.0)),
+                ast::Expr::Lit(ast::LitExpr::Float(2.0)),
+                ast::Expr::Lit(ast::LitExpr::Float(3.0)),
+                ast::Expr::Lit(ast::LitExpr::Boolean(true)),
+            ],
+        );
+        let prog = ast::Prog::new(vec![ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+            VarIdent(0),
+            call,
+        ))]);
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(func_id, Box::new(func));
+
+        let mut interpreter = Interpreter::new(funcs);
+        interpreter.set_prog(prog);
+
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(value.last_value, Some(Value::Float(-6.0)));
+
+        let call = ast::CallExpr::new(func_id, vec![ast::Expr::Lit(ast::LitExpr::Boolean(true))]);
+        interpreter.set_prog(ast::Prog::new(vec![ast::Stmt::VarDecl(
+            ast::VarDeclStmt::new(VarIdent(1), call.clone()),
+        )]));
+
+        let err = interpreter.interpret().result.unwrap_err();
+        assert_eq!(
+            err,
+            InterpretError::from(RuntimeError::ArgCountMismatch {
+                stmt_index: 0,
+                call,
+                args_expected: 2,
+                args_provided: 1,
+            }),
+        );
+    }
+
     #[test]
     fn test_interpreter_interpret_single_func_dynamic_arg_ty_error() {
         let (func_id, func) = (
@@ -2238,4 +3325,163 @@ mod tests {
         assert_eq!(interpret_outcome.pc, 4);
         assert_eq!(interpret_outcome.log_messages.len(), 4);
     }
+
+    /// Streams up to ten boxes, until told to stop.
+    struct StreamingTestFunc;
+
+    impl Func for StreamingTestFunc {
+        fn flags(&self) -> FuncFlags {
+            FuncFlags::PURE
+        }
+
+        fn param_info(&self) -> &[ParamInfo] {
+            &[]
+        }
+
+        fn return_ty(&self) -> Ty {
+            Ty::MeshArray
+        }
+
+        fn call(
+            &mut self,
+            values: &[Value],
+            log: &mut dyn FnMut(LogMessage),
+        ) -> Result<Value, FuncError> {
+            self.call_streaming(values, log, &mut |_| true)
+        }
+
+        fn call_streaming(
+            &mut self,
+            _values: &[Value],
+            _log: &mut dyn FnMut(LogMessage),
+            stream: &mut dyn FnMut(Arc<Mesh>) -> bool,
+        ) -> Result<Value, FuncError> {
+            let mut meshes = Vec::new();
+            for _ in 0..10 {
+                let mesh = Arc::new(primitive::create_box(
+                    Point3::origin(),
+                    Rotation3::identity(),
+                    Vector3::new(1.0, 1.0, 1.0),
+                ));
+                meshes.push(Arc::clone(&mesh));
+
+                if !stream(mesh) {
+                    break;
+                }
+            }
+
+            Ok(Value::MeshArray(Arc::new(MeshArrayValue::new(meshes))))
+        }
+    }
+
+    #[test]
+    fn test_interpreter_interpret_streaming_func_cancelled() {
+        let func_id = FuncIdent(0);
+        let prog = ast::Prog::new(vec![ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+            VarIdent(0),
+            ast::CallExpr::new(func_id, vec![]),
+        ))]);
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(func_id, Box::new(StreamingTestFunc));
+
+        let mut interpreter = Interpreter::new(funcs);
+        interpreter.set_prog(prog);
+
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        interpreter.set_cancel_flag(Arc::clone(&cancel_flag));
+
+        let streamed_count = Rc::new(Cell::new(0));
+        let callback_streamed_count = Rc::clone(&streamed_count);
+        let callback_cancel_flag = Arc::clone(&cancel_flag);
+        interpreter.set_mesh_stream_callback(Box::new(move |stmt_index, _| {
+            assert_eq!(stmt_index, 0);
+            callback_streamed_count.set(callback_streamed_count.get() + 1);
+            if callback_streamed_count.get() == 3 {
+                callback_cancel_flag.store(true, Ordering::SeqCst);
+            }
+        }));
+
+        let interpret_outcome = interpreter.interpret();
+        assert_eq!(streamed_count.get(), 3);
+        assert_eq!(
+            interpret_outcome.result,
+            Err(InterpretError::Runtime(RuntimeError::Cancelled {
+                stmt_index: 0,
+                call: ast::CallExpr::new(func_id, vec![]),
+            })),
+        );
+
+        // The cancelled value is not cached, so it is generated again.
+        cancel_flag.store(false, Ordering::SeqCst);
+        let interpret_outcome = interpreter.interpret();
+        assert_eq!(streamed_count.get(), 13);
+        assert!(interpret_outcome.result.is_ok());
+    }
+
+    #[test]
+    fn test_interpreter_interpret_generates_lazy_mesh_arrays_until_cancelled() {
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let generated_count = Arc::new(AtomicU32::new(0));
+        let func_cancel_flag = Arc::clone(&cancel_flag);
+        let func_generated_count = Arc::clone(&generated_count);
+        let (func_id, func) = (
+            FuncIdent(0),
+            TestFunc::new(
+                move |_| {
+                    let generator_cancel_flag = Arc::clone(&func_cancel_flag);
+                    let generator_generated_count = Arc::clone(&func_generated_count);
+                    let mesh_array = MeshArrayValue::lazy(5, move |_| {
+                        if generator_generated_count.fetch_add(1, Ordering::SeqCst) == 1 {
+                            generator_cancel_flag.store(true, Ordering::SeqCst);
+                        }
+
+                        primitive::create_box(
+                            Point3::origin(),
+                            Rotation3::identity(),
+                            Vector3::new(1.0, 1.0, 1.0),
+                        )
+                    });
+
+                    Ok(Value::MeshArray(Arc::new(mesh_array)))
+                },
+                FuncFlags::PURE,
+                vec![],
+                Ty::MeshArray,
+            ),
+        );
+
+        let prog = ast::Prog::new(vec![ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+            VarIdent(0),
+            ast::CallExpr::new(func_id, vec![]),
+        ))]);
+
+        let mut funcs: BTreeMap<FuncIdent, Box<dyn Func>> = BTreeMap::new();
+        funcs.insert(func_id, Box::new(func));
+
+        let mut interpreter = Interpreter::new(funcs);
+        interpreter.set_prog(prog);
+        interpreter.set_cancel_flag(Arc::clone(&cancel_flag));
+
+        // The meshes are generated by the interpreter before the values are
+        // returned, so the generation is cancelled like the statement
+        let interpret_outcome = interpreter.interpret();
+        assert_eq!(generated_count.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            interpret_outcome.result,
+            Err(InterpretError::Runtime(RuntimeError::Cancelled {
+                stmt_index: 0,
+                call: ast::CallExpr::new(func_id, vec![]),
+            })),
+        );
+
+        // The cached value keeps its generated meshes
+        cancel_flag.store(false, Ordering::SeqCst);
+        let value = interpreter.interpret().result.unwrap();
+        assert_eq!(generated_count.load(Ordering::SeqCst), 5);
+        match value.last_value {
+            Some(Value::MeshArray(mesh_array)) => assert_eq!(mesh_array.len(), 5),
+            last_value => panic!("Unexpected last value {:?}", last_value),
+        }
+    }
 }
@@ -1,6 +1,5 @@
 use std::fmt;
-use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::convert::{cast_u32, cast_usize};
 use crate::mesh::Mesh;
@@ -185,34 +184,155 @@ impl Value {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct MeshArrayValue(Vec<Arc<Mesh>>);
+/// A function generating the mesh at an index of a lazy mesh array.
+type MeshGenerator = dyn Fn(u32) -> Mesh + Send + Sync;
+
+/// An array of meshes.
+///
+/// The meshes are either all present, or generated on demand by a function
+/// when first accessed, and kept afterwards. Lazy arrays let funcs producing
+/// many large meshes skip the work for meshes nobody looks at. Clones of a
+/// lazy array share the generated meshes.
+#[derive(Clone)]
+pub struct MeshArrayValue(MeshArrayElements);
+
+#[derive(Clone)]
+enum MeshArrayElements {
+    Materialized(Vec<Arc<Mesh>>),
+    Lazy {
+        generator: Arc<MeshGenerator>,
+        meshes: Arc<[Mutex<Option<Arc<Mesh>>>]>,
+    },
+}
 
 impl MeshArrayValue {
     pub fn new(meshes: Vec<Arc<Mesh>>) -> Self {
-        Self(meshes)
+        Self(MeshArrayElements::Materialized(meshes))
+    }
+
+    /// Creates an array of `len` meshes, each generated by the generator
+    /// from its index once it is accessed for the first time.
+    pub fn lazy<F>(len: u32, generator: F) -> Self
+    where
+        F: Fn(u32) -> Mesh + Send + Sync + 'static,
+    {
+        let meshes = (0..len).map(|_| Mutex::new(None)).collect();
+
+        Self(MeshArrayElements::Lazy {
+            generator: Arc::new(generator),
+            meshes,
+        })
     }
 
     pub fn get_refcounted(&self, index: u32) -> Option<Arc<Mesh>> {
-        self.0.get(cast_usize(index)).map(Arc::clone)
+        match &self.0 {
+            MeshArrayElements::Materialized(meshes) => {
+                meshes.get(cast_usize(index)).map(Arc::clone)
+            }
+            MeshArrayElements::Lazy { generator, meshes } => {
+                let slot = meshes.get(cast_usize(index))?;
+                let mut slot = slot.lock().expect("Failed to lock mesh array element");
+                let mesh = slot.get_or_insert_with(|| Arc::new(generator(index)));
+
+                Some(Arc::clone(mesh))
+            }
+        }
     }
 
     pub fn len(&self) -> u32 {
-        cast_u32(self.0.len())
+        match &self.0 {
+            MeshArrayElements::Materialized(meshes) => cast_u32(meshes.len()),
+            MeshArrayElements::Lazy { meshes, .. } => cast_u32(meshes.len()),
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.len() == 0
+    }
+
+    /// Whether the meshes of the array are generated on demand.
+    pub fn is_lazy(&self) -> bool {
+        matches!(self.0, MeshArrayElements::Lazy { .. })
     }
 
+    /// Generates the meshes of a lazy array, which were not generated yet.
+    /// Stops early, if `should_continue` returns false before generating a
+    /// mesh. Returns whether all meshes of the array are generated.
+    pub fn generate(&self, should_continue: &mut dyn FnMut() -> bool) -> bool {
+        if let MeshArrayElements::Lazy { generator, meshes } = &self.0 {
+            for (index, slot) in meshes.iter().enumerate() {
+                let mut slot = slot.lock().expect("Failed to lock mesh array element");
+                if slot.is_none() {
+                    if !should_continue() {
+                        return false;
+                    }
+                    *slot = Some(Arc::new(generator(cast_u32(index))));
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Iterates over the meshes of the array, generating the meshes of a lazy
+    /// array as needed.
     pub fn iter_refcounted(&self) -> impl Iterator<Item = Arc<Mesh>> + '_ {
-        self.0.iter().cloned()
+        (0..self.len()).map(move |index| {
+            self.get_refcounted(index)
+                .expect("Mesh array index out of bounds")
+        })
     }
+}
+
+impl PartialEq for MeshArrayValue {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        // Arrays generated by the same generator are equal without having to
+        // generate their meshes.
+        if let (
+            MeshArrayElements::Lazy { generator, .. },
+            MeshArrayElements::Lazy {
+                generator: other_generator,
+                ..
+            },
+        ) = (&self.0, &other.0)
+        {
+            if Arc::ptr_eq(generator, other_generator) {
+                return true;
+            }
+        }
 
-    pub fn iter(&self) -> impl Iterator<Item = &Mesh> + '_ {
-        self.0
-            .iter()
-            .map(|refcounted_mesh| Arc::deref(refcounted_mesh))
+        self.iter_refcounted()
+            .zip(other.iter_refcounted())
+            .all(|(mesh, other_mesh)| Arc::ptr_eq(&mesh, &other_mesh) || mesh == other_mesh)
+    }
+}
+
+impl fmt::Debug for MeshArrayValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            MeshArrayElements::Materialized(meshes) => {
+                f.debug_tuple("MeshArrayValue").field(meshes).finish()
+            }
+            MeshArrayElements::Lazy { meshes, .. } => {
+                let generated_count = meshes
+                    .iter()
+                    .filter(|slot| {
+                        slot.lock()
+                            .expect("Failed to lock mesh array element")
+                            .is_some()
+                    })
+                    .count();
+
+                f.debug_struct("MeshArrayValue")
+                    .field("len", &meshes.len())
+                    .field("generated", &generated_count)
+                    .finish()
+            }
+        }
     }
 }
 
@@ -243,3 +363,89 @@ impl fmt::Display for Value {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use nalgebra::{Point3, Rotation3, Vector3};
+
+    use crate::mesh::primitive;
+
+    use super::*;
+
+    fn cube(x: f32) -> Mesh {
+        primitive::create_box(
+            Point3::new(x, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn test_mesh_array_value_lazy_generates_accessed_meshes_once() {
+        let generated_count = Arc::new(AtomicU32::new(0));
+        let generated_count_clone = Arc::clone(&generated_count);
+        let mesh_array = MeshArrayValue::lazy(100, move |index| {
+            generated_count_clone.fetch_add(1, Ordering::SeqCst);
+            cube(index as f32)
+        });
+
+        let first = mesh_array.get_refcounted(3).expect("Index out of bounds");
+        let second = mesh_array.get_refcounted(3).expect("Index out of bounds");
+
+        assert_eq!(mesh_array.len(), 100);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(*first, cube(3.0));
+        assert_eq!(generated_count.load(Ordering::SeqCst), 1);
+        assert!(mesh_array.get_refcounted(100).is_none());
+    }
+
+    #[test]
+    fn test_mesh_array_value_lazy_equals_materialized() {
+        let lazy = MeshArrayValue::lazy(3, |index| cube(index as f32));
+        let materialized =
+            MeshArrayValue::new((0..3).map(|index| Arc::new(cube(index as f32))).collect());
+
+        assert_eq!(lazy, materialized);
+        assert_eq!(materialized, lazy.clone());
+    }
+
+    #[test]
+    fn test_mesh_array_value_lazy_clone_shares_generated_meshes() {
+        let generated_count = Arc::new(AtomicU32::new(0));
+        let generated_count_clone = Arc::clone(&generated_count);
+        let mesh_array = MeshArrayValue::lazy(3, move |index| {
+            generated_count_clone.fetch_add(1, Ordering::SeqCst);
+            cube(index as f32)
+        });
+        let mesh_array_clone = mesh_array.clone();
+
+        assert!(mesh_array_clone.generate(&mut || true));
+
+        let first = mesh_array.get_refcounted(1).expect("Index out of bounds");
+        let second = mesh_array_clone
+            .get_refcounted(1)
+            .expect("Index out of bounds");
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(generated_count.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_mesh_array_value_generate_stops_early() {
+        let mesh_array = MeshArrayValue::lazy(3, |index| cube(index as f32));
+        mesh_array.get_refcounted(0);
+
+        let mut continue_count = 0;
+        let generated = mesh_array.generate(&mut || {
+            continue_count += 1;
+            continue_count < 2
+        });
+
+        assert!(!generated);
+        assert_eq!(continue_count, 2);
+        assert!(mesh_array.generate(&mut || true));
+        assert!(MeshArrayValue::new(Vec::new()).generate(&mut || false));
+    }
+}
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use crate::analytics;
 use crate::interpreter::{
     BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, Value,
+    ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::tools;
 
@@ -40,12 +40,14 @@ impl Func for FuncAlign {
                 description: "Mesh to be transformed to align to another mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Align to mesh",
                 description: "The target mesh, towards which the source mesh should be aligned.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -55,6 +57,7 @@ impl Func for FuncAlign {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
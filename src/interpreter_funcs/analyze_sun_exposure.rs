@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use crate::analytics;
+use crate::interpreter::{
+    BooleanParamRefinement, Dimension, FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
+};
+use crate::mesh::analysis;
+use crate::sun::{self, SunPosition};
+
+/// Color of vertices with no hours of sun.
+const COLOR_SHADE: [f32; 3] = [40.0, 60.0, 160.0];
+
+/// Color of vertices lit for the whole day.
+const COLOR_SUN: [f32; 3] = [255.0, 220.0, 60.0];
+
+pub struct FuncAnalyzeSunExposure;
+
+impl Func for FuncAnalyzeSunExposure {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Analyze Sun Exposure",
+            description: "ANALYZE HOURS OF SUN ON MESH SURFACE\n\
+                          \n\
+                          Computes for how many hours each vertex of the mesh is \
+                          exposed to direct sunlight on the given day and latitude, \
+                          taking into account the shadows the mesh casts on itself.\n\
+                          \n\
+                          The result is shown as vertex colors, ranging from blue \
+                          for vertices in the shade for the whole day to yellow \
+                          for vertices in the sun from sunrise to sunset.\n\
+                          \n\
+                          The scene north is along the Y axis. The analysis is \
+                          approximate and only meant for quick feedback on early \
+                          design studies.\n\
+                          \n\
+                          The input mesh will be marked used and thus invisible in the viewport. \
+                          It can still be used in subsequent operations.\n\
+                          \n\
+                          The resulting mesh geometry will be named 'Sun Exposure Mesh'.",
+            return_value_name: "Sun Exposure Mesh",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Mesh",
+                description: "Input mesh.",
+                refinement: ParamRefinement::Mesh,
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Latitude",
+                description: "Latitude of the site in degrees, \
+                              positive on the northern hemisphere.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(sun::DEFAULT_LATITUDE),
+                    min_value: Some(-90.0),
+                    max_value: Some(90.0),
+                    dimension: Dimension::Dimensionless,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Day of year",
+                description: "The analyzed day, starting at 1 for January 1st.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(sun::DEFAULT_DAY_OF_YEAR),
+                    min_value: Some(1),
+                    max_value: Some(sun::DAYS_IN_YEAR),
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Time step (minutes)",
+                description: "The time between two analyzed sun positions.\n\
+                              Shorter steps give more precise results, \
+                              but the analysis takes longer.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(30),
+                    min_value: Some(1),
+                    max_value: Some(240),
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Mesh Analysis",
+                description: "Reports detailed analytic information on the created mesh.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let mesh = args[0].unwrap_mesh();
+        let latitude = args[1].unwrap_float();
+        let day_of_year = args[2].unwrap_uint();
+        let step_minutes = args[3].unwrap_uint().max(1);
+        let analyze_mesh = args[4].unwrap_boolean();
+
+        let step_hours = step_minutes as f32 / 60.0;
+        let sample_count = (24 * 60) / step_minutes;
+
+        // Sample in the middle of each time step, so that each sample
+        // represents a whole step of sunlight.
+        let directions_towards_sun: Vec<_> = (0..sample_count)
+            .map(|i| SunPosition::compute(latitude, day_of_year, (i as f32 + 0.5) * step_hours))
+            .filter(SunPosition::is_above_horizon)
+            .map(|sun_position| -sun_position.light_direction())
+            .collect();
+
+        let daylight_hours = directions_towards_sun.len() as f32 * step_hours;
+        log(LogMessage::measurement(
+            "Daylight hours",
+            daylight_hours,
+            Dimension::Dimensionless,
+        ));
+
+        let exposure = analysis::vertex_sun_exposure(mesh, &directions_towards_sun);
+
+        let min_count = exposure.iter().copied().min().unwrap_or(0);
+        let max_count = exposure.iter().copied().max().unwrap_or(0);
+        log(LogMessage::measurement(
+            "Minimum hours of sun",
+            min_count as f32 * step_hours,
+            Dimension::Dimensionless,
+        ));
+        log(LogMessage::measurement(
+            "Maximum hours of sun",
+            max_count as f32 * step_hours,
+            Dimension::Dimensionless,
+        ));
+
+        let vertex_colors = exposure
+            .iter()
+            .map(|&count| {
+                let t = if directions_towards_sun.is_empty() {
+                    0.0
+                } else {
+                    count as f32 / directions_towards_sun.len() as f32
+                };
+
+                exposure_color(t)
+            })
+            .collect();
+
+        let value = Arc::new(mesh.clone().with_vertex_colors(vertex_colors));
+
+        if analyze_mesh {
+            analytics::report_bounding_box_analysis(&value, log);
+            analytics::report_mesh_analysis(&value, log);
+        }
+
+        Ok(Value::Mesh(value))
+    }
+}
+
+/// Interpolates the color for the given ratio of the sun exposure between the
+/// shade and the sun color.
+fn exposure_color(t: f32) -> [u8; 3] {
+    let mut color = [0; 3];
+    for (i, component) in color.iter_mut().enumerate() {
+        let value = COLOR_SHADE[i] + (COLOR_SUN[i] - COLOR_SHADE[i]) * t;
+        *component = value.round() as u8;
+    }
+
+    color
+}
@@ -0,0 +1,218 @@
+use std::cmp;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::analytics;
+use crate::interpreter::{
+    BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, MeshRequirements,
+    ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
+};
+use crate::interpreter_funcs::{normal_strategy_from_choice, NORMAL_STRATEGY_CHOICES};
+use crate::mesh::smoothing::{self, BorderStrategy};
+use crate::mesh::topology;
+
+#[derive(Debug, PartialEq)]
+pub enum FuncCatmullClarkSubdivisionError {
+    InvalidMesh,
+}
+
+impl fmt::Display for FuncCatmullClarkSubdivisionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidMesh => write!(f, "The mesh is not triangulated."),
+        }
+    }
+}
+
+impl error::Error for FuncCatmullClarkSubdivisionError {}
+
+pub struct FuncCatmullClarkSubdivision;
+
+impl FuncCatmullClarkSubdivision {
+    const MAX_ITERATIONS: u32 = 3;
+}
+
+impl Func for FuncCatmullClarkSubdivision {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Smoothen Catmull-Clark",
+            description: "SMOOTHEN MESH WITH CATMULL-CLARK SUBDIVISION\n\
+                 \n\
+                 Creates a new smoothened mesh geometry using Catmull-Clark subdivision \
+                 algorithm. Catmull-Clark subdivision surface is an approximating \
+                 subdivision scheme developed by Edwin Catmull and Jim Clark in 1978. \
+                 Each triangle is divided into three quads around its center, each made \
+                 of two triangles. Compared to Loop subdivision, the result is rounder \
+                 and more organic, especially on welded voxelized meshes. \
+                 The face count will increase 6 times with each iteration. Too many \
+                 iterations may take long time and produce an unnecessarily heavy mesh. \
+                 Therefore the number of iterations is limited to 3.\n\
+                 \n\
+                 The input mesh may be open. The vertices on its borders can either \
+                 be smoothened along the border or stay fixed, so that the resulting mesh \
+                 still fits to adjacent geometry. \
+                 Smoothing operations are usually placed at the end of mesh manipulation \
+                 pipeline because the subdivided dense meshes are not suitable for geometry \
+                 manipulation.\n\
+                 \n\
+                 The input mesh will be marked used and thus invisible in the viewport. \
+                 It can still be used in subsequent operations.\n\
+                 \n\
+                 The resulting mesh geometry will be named 'Smooth Mesh'.",
+            return_value_name: "Smooth Mesh",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Mesh",
+                description: "Input mesh.\n\
+                              The mesh should be manifold.",
+                refinement: ParamRefinement::Mesh,
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Iterations",
+                description:
+                    "Number of iterations (repetitions) of the Catmull-Clark subdivision algorithm.\n\
+                     \n\
+                     The number of iterations is limited to 3. \
+                     Too many iterations may take long time and produce a heavy dense mesh.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(1),
+                    min_value: Some(0),
+                    max_value: Some(Self::MAX_ITERATIONS),
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Border Vertices",
+                description: "Treatment of the vertices on the borders of open meshes.\n\
+                     \n\
+                     Smooth vertices are relocated along the border curve, rounding the \
+                     border corners. Fixed vertices keep their original positions.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(0),
+                    min_value: Some(0),
+                    max_value: Some(1),
+                }),
+                optional: false,
+                widget: ParamWidget::Choice(&["Smooth", "Fixed"]),
+            },
+            ParamInfo {
+                name: "Normals",
+                description: "Chooses how the per-vertex mesh normals are computed.\n\
+                     \n\
+                     Smooth normals are interpolated from connected face normals. As a result, \
+                     the rendered geometry will have a smooth surface material even though \
+                     the mesh itself may be coarse.\n\
+                     \n\
+                     With sharp normals, the geometry will be rendered as angular: each face \
+                     will appear flat, exposing edges as sharp creases.\n\
+                     \n\
+                     The normal strategy does not affect the geometry itself.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(1),
+                    min_value: Some(0),
+                    max_value: Some(1),
+                }),
+                optional: false,
+                widget: ParamWidget::Choice(NORMAL_STRATEGY_CHOICES),
+            },
+            ParamInfo {
+                name: "Mesh Analysis",
+                description: "Reports detailed analytic information on the created mesh.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn mesh_requirements(&self, param_index: usize) -> MeshRequirements {
+        if param_index == 0 {
+            MeshRequirements::MANIFOLD
+        } else {
+            MeshRequirements::empty()
+        }
+    }
+
+    fn draft_arg(&self, param_index: usize, value: &Value) -> Option<Value> {
+        // Each iteration multiplies the face count by 6
+        match (param_index, value) {
+            (1, Value::Uint(iterations)) => Some(Value::Uint(cmp::min(*iterations, 1))),
+            _ => None,
+        }
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let mesh = args[0].unwrap_refcounted_mesh();
+        let iterations = cmp::min(args[1].unwrap_uint(), Self::MAX_ITERATIONS);
+        if args[1].unwrap_uint() > Self::MAX_ITERATIONS {
+            log(LogMessage::warn(format!(
+                "Iterations clamped to {}",
+                Self::MAX_ITERATIONS
+            )));
+        }
+        let border_strategy = if args[2].unwrap_uint() == 0 {
+            BorderStrategy::Smooth
+        } else {
+            BorderStrategy::Fixed
+        };
+        let normal_strategy = normal_strategy_from_choice(args[3].unwrap_uint());
+        let analyze_mesh = args[4].unwrap_boolean();
+
+        if iterations == 0 {
+            log(LogMessage::info("Zero iterations, the mesh hasn't changed"));
+            return Ok(Value::Mesh(mesh));
+        }
+
+        let mut current_mesh = Arc::clone(&mesh);
+        for _ in 0..iterations {
+            let vertex_to_vertex_topology =
+                topology::compute_vertex_to_vertex_topology(&current_mesh);
+            let vertex_to_face_topology = topology::compute_vertex_to_face_topology(&current_mesh);
+
+            current_mesh = match smoothing::catmull_clark_subdivision(
+                &current_mesh,
+                &vertex_to_vertex_topology,
+                &vertex_to_face_topology,
+                border_strategy,
+                normal_strategy,
+            ) {
+                Some(m) => Arc::new(m),
+                None => {
+                    let error = FuncError::new(FuncCatmullClarkSubdivisionError::InvalidMesh);
+                    log(LogMessage::error(format!("Error: {}", error)));
+                    return Err(error);
+                }
+            }
+        }
+
+        if analyze_mesh {
+            analytics::report_bounding_box_analysis(&current_mesh, log);
+            analytics::report_mesh_analysis(&current_mesh, log);
+        }
+
+        Ok(Value::Mesh(current_mesh))
+    }
+}
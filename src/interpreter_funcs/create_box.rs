@@ -4,8 +4,8 @@ use nalgebra::{Point3, Rotation3, Vector3};
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
-    LogMessage, ParamInfo, ParamRefinement, Ty, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::primitive;
 
@@ -41,11 +41,13 @@ impl Func for FuncCreateBox {
                     default_value_x: Some(0.0),
                     default_value_y: Some(0.0),
                     default_value_z: Some(0.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
-                name: "Rotate (deg)",
+                name: "Rotate",
                 description: "Rotation of the box in degrees.",
                 refinement: ParamRefinement::Float3(Float3ParamRefinement {
                     min_value: None,
@@ -53,8 +55,10 @@ impl Func for FuncCreateBox {
                     default_value_x: Some(0.0),
                     default_value_y: Some(0.0),
                     default_value_z: Some(0.0),
+                    dimension: Dimension::Angle,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Scale",
@@ -66,8 +70,10 @@ impl Func for FuncCreateBox {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -77,6 +83,7 @@ impl Func for FuncCreateBox {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
@@ -0,0 +1,147 @@
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use nalgebra::Point2;
+
+use crate::analytics;
+use crate::interpreter::{
+    BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
+    ParamRefinement, ParamWidget, StringParamRefinement, Ty, Value,
+};
+use crate::mesh::primitive;
+
+#[derive(Debug, PartialEq)]
+pub enum FuncCreatePolygonError {
+    InvalidPoint(String),
+    InvalidOutline,
+}
+
+impl fmt::Display for FuncCreatePolygonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidPoint(point) => write!(f, "Point '{}' is not an 'x y' pair", point),
+            Self::InvalidOutline => write!(
+                f,
+                "Outline must have at least 3 points, enclose an area and not intersect itself"
+            ),
+        }
+    }
+}
+
+impl error::Error for FuncCreatePolygonError {}
+
+/// Formats the outline points as the Outline argument of Create Polygon.
+pub fn format_outline(outline: &[Point2<f32>]) -> String {
+    outline
+        .iter()
+        .map(|point| format!("{} {}", point.x, point.y))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Parses the Outline argument of Create Polygon: `x y` pairs separated by
+/// semicolons. The coordinates may also be separated by a comma.
+fn parse_outline(outline: &str) -> Result<Vec<Point2<f32>>, FuncCreatePolygonError> {
+    outline
+        .split(';')
+        .map(str::trim)
+        .filter(|point| !point.is_empty())
+        .map(|point| {
+            let coordinates: Vec<_> = point
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|coordinate| !coordinate.is_empty())
+                .map(str::parse::<f32>)
+                .collect();
+
+            match coordinates.as_slice() {
+                [Ok(x), Ok(y)] if x.is_finite() && y.is_finite() => Ok(Point2::new(*x, *y)),
+                _ => Err(FuncCreatePolygonError::InvalidPoint(point.to_string())),
+            }
+        })
+        .collect()
+}
+
+pub struct FuncCreatePolygon;
+
+impl Func for FuncCreatePolygon {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Create Polygon",
+            description: "CREATE FLAT POLYGON ON THE GROUND\n\
+                          \n\
+                          Creates a new flat mesh polygon on the ground from the points \
+                          of its outline, e.g. a building footprint. The outline may be \
+                          concave, but must not intersect itself. The polygon faces up.\n\
+                          \n\
+                          Footprints can be sketched in the viewport with the Sketch footprint \
+                          window.\n\
+                          \n\
+                          The resulting mesh geometry will be named 'Polygon'.",
+            return_value_name: "Polygon",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Outline",
+                description: "Points of the outline in absolute model units, \
+                              as 'x y' pairs separated by semicolons, e.g. '0 0; 10 0; 10 6; 0 6'.",
+                refinement: ParamRefinement::String(StringParamRefinement {
+                    default_value: "0 0; 1 0; 1 1; 0 1",
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Mesh Analysis",
+                description: "Reports detailed analytic information on the created mesh.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let outline = args[0].unwrap_string();
+        let analyze_mesh = args[1].unwrap_boolean();
+
+        let value = match parse_outline(outline) {
+            Ok(outline) => primitive::create_polygon(&outline)
+                .ok_or_else(|| FuncError::new(FuncCreatePolygonError::InvalidOutline)),
+            Err(err) => Err(FuncError::new(err)),
+        };
+
+        match value {
+            Ok(value) => {
+                if analyze_mesh {
+                    analytics::report_bounding_box_analysis(&value, log);
+                    analytics::report_mesh_analysis(&value, log);
+                }
+
+                Ok(Value::Mesh(Arc::new(value)))
+            }
+            Err(error) => {
+                log(LogMessage::error(format!("Error: {}", error)));
+                Err(error)
+            }
+        }
+    }
+}
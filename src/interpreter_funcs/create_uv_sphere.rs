@@ -6,10 +6,11 @@ use nalgebra::{Point3, Rotation3, Vector3};
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
-    LogMessage, ParamInfo, ParamRefinement, Ty, UintParamRefinement, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
-use crate::mesh::{primitive, NormalStrategy};
+use crate::interpreter_funcs::{normal_strategy_from_choice, NORMAL_STRATEGY_CHOICES};
+use crate::mesh::primitive;
 
 #[derive(Debug, PartialEq)]
 pub enum FuncCreateUvSphereError {
@@ -75,11 +76,13 @@ impl Func for FuncCreateUvSphere {
                     default_value_x: Some(0.0),
                     default_value_y: Some(0.0),
                     default_value_z: Some(0.0),
+                                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
-                name: "Rotate (deg)",
+                name: "Rotate",
                 description: "Rotation of the sphere in degrees.",
                 refinement: ParamRefinement::Float3(Float3ParamRefinement {
                     min_value: None,
@@ -87,8 +90,10 @@ impl Func for FuncCreateUvSphere {
                     default_value_x: Some(0.0),
                     default_value_y: Some(0.0),
                     default_value_z: Some(0.0),
+                                    dimension: Dimension::Angle,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Scale",
@@ -100,8 +105,10 @@ impl Func for FuncCreateUvSphere {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Parallels",
@@ -113,6 +120,7 @@ impl Func for FuncCreateUvSphere {
                     max_value: None,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Meridians",
@@ -124,21 +132,27 @@ impl Func for FuncCreateUvSphere {
                     max_value: None,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
-                name: "Smooth normals",
-                description: "Sets the per-vertex mesh normals to be interpolated from \
-                connected face normals. As a result, the rendered geometry will have \
-                a smooth surface material even though the mesh itself may be coarse.\n\
+                name: "Normals",
+                description: "Chooses how the per-vertex mesh normals are computed.\n\
                 \n\
-                When disabled, the geometry will be rendered as angular: each face will \
-                appear flat, exposing edges as sharp creases.\n\
+                Smooth normals are interpolated from connected face normals. As a result, \
+                the rendered geometry will have a smooth surface material even though \
+                the mesh itself may be coarse.\n\
                 \n\
-                The normal smoothing strategy does not affect the geometry itself.",
-                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
-                    default_value: true,
+                With sharp normals, the geometry will be rendered as angular: each face \
+                will appear flat, exposing edges as sharp creases.\n\
+                \n\
+                The normal strategy does not affect the geometry itself.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(1),
+                    min_value: Some(0),
+                    max_value: Some(1),
                 }),
                 optional: false,
+                widget: ParamWidget::Choice(NORMAL_STRATEGY_CHOICES),
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -148,6 +162,7 @@ impl Func for FuncCreateUvSphere {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
@@ -166,7 +181,7 @@ impl Func for FuncCreateUvSphere {
         let scale = args[2].unwrap_float3();
         let n_parallels = args[3].unwrap_uint();
         let n_meridians = args[4].unwrap_uint();
-        let smooth = args[5].unwrap_boolean();
+        let normal_strategy = normal_strategy_from_choice(args[5].unwrap_uint());
         let analyze_mesh = args[6].unwrap_boolean();
 
         if n_parallels < Self::MIN_PARALLELS {
@@ -185,12 +200,6 @@ impl Func for FuncCreateUvSphere {
             return Err(error);
         }
 
-        let normal_strategy = if smooth {
-            NormalStrategy::Smooth
-        } else {
-            NormalStrategy::Sharp
-        };
-
         let value = primitive::create_uv_sphere(
             Point3::from(center),
             Rotation3::from_euler_angles(
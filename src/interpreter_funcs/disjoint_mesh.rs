@@ -3,9 +3,9 @@ use std::sync::Arc;
 use crate::analytics;
 use crate::interpreter::{
     BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, MeshArrayValue,
-    ParamInfo, ParamRefinement, Ty, Value,
+    ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
 };
-use crate::mesh::tools;
+use crate::mesh::{tools, Mesh};
 
 pub struct FuncDisjointMesh;
 
@@ -42,6 +42,7 @@ impl Func for FuncDisjointMesh {
                 description: "Input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Group Analysis",
@@ -50,6 +51,7 @@ impl Func for FuncDisjointMesh {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
@@ -62,12 +64,26 @@ impl Func for FuncDisjointMesh {
         &mut self,
         args: &[Value],
         log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        self.call_streaming(args, log, &mut |_| true)
+    }
+
+    fn call_streaming(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+        stream: &mut dyn FnMut(Arc<Mesh>) -> bool,
     ) -> Result<Value, FuncError> {
         let mesh = args[0].unwrap_mesh();
         let analyze = args[1].unwrap_boolean();
 
-        let meshes = tools::disjoint_mesh(&mesh);
-        let value = MeshArrayValue::new(meshes.into_iter().map(Arc::new).collect());
+        let mut meshes = Vec::new();
+        tools::disjoint_mesh_with_callback(&mesh, |patch| {
+            let patch = Arc::new(patch);
+            meshes.push(Arc::clone(&patch));
+            stream(patch)
+        });
+        let value = MeshArrayValue::new(meshes);
 
         if analyze {
             analytics::report_group_analysis(&value, log);
@@ -4,7 +4,7 @@ use std::fmt;
 use crate::analytics;
 use crate::interpreter::{
     BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, UintParamRefinement, Value,
+    ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
 
 #[derive(Debug, PartialEq)]
@@ -61,6 +61,7 @@ impl Func for FuncExtract {
                 description: "Input mesh group.",
                 refinement: ParamRefinement::MeshArray,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Index",
@@ -71,6 +72,7 @@ impl Func for FuncExtract {
                     max_value: None,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -80,6 +82,7 @@ impl Func for FuncExtract {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
@@ -4,7 +4,7 @@ use std::fmt;
 use crate::analytics;
 use crate::interpreter::{
     BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, Value,
+    ParamRefinement, ParamWidget, Ty, Value,
 };
 
 #[derive(Debug, PartialEq)]
@@ -54,6 +54,7 @@ impl Func for FuncExtractLargest {
                 description: "Input mesh group.",
                 refinement: ParamRefinement::MeshArray,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -63,6 +64,7 @@ impl Func for FuncExtractLargest {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
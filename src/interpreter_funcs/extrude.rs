@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use nalgebra::Vector3;
+
+use crate::analytics;
+use crate::interpreter::{
+    BooleanParamRefinement, Dimension, FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
+};
+use crate::mesh::tools;
+
+pub struct FuncExtrude;
+
+impl Func for FuncExtrude {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Extrude",
+            description: "EXTRUDE MESH VERTICALLY INTO A SOLID\n\
+                          \n\
+                          Extrudes the mesh up by the height into a closed solid: \
+                          the mesh is copied to the height and its open borders are \
+                          connected to the copy by vertical walls. \
+                          Extruding a flat polygon, e.g. from Create Polygon, produces \
+                          a massing block with the polygon as its footprint.\n\
+                          \n\
+                          The input mesh will be marked used and thus invisible in the viewport. \
+                          It can still be used in subsequent operations.\n\
+                          \n\
+                          The resulting mesh geometry will be named 'Extruded Mesh'.",
+            return_value_name: "Extruded Mesh",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Mesh",
+                description: "Input mesh.",
+                refinement: ParamRefinement::Mesh,
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Height",
+                description: "Height of the extrusion in model units.\n\
+                              Negative heights extrude down.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(3.0),
+                    min_value: None,
+                    max_value: None,
+                    dimension: Dimension::Length,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Mesh Analysis",
+                description: "Reports detailed analytic information on the created mesh.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let mesh = args[0].unwrap_mesh();
+        let height = args[1].unwrap_float();
+        let analyze_mesh = args[2].unwrap_boolean();
+
+        let value = tools::extrude(&mesh, &Vector3::new(0.0, 0.0, height));
+
+        if analyze_mesh {
+            analytics::report_bounding_box_analysis(&value, log);
+            analytics::report_mesh_analysis(&value, log);
+        }
+
+        Ok(Value::Mesh(Arc::new(value)))
+    }
+}
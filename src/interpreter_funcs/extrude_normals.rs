@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use crate::analytics;
+use crate::interpreter::{
+    BooleanParamRefinement, Dimension, FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
+};
+use crate::mesh::tools;
+
+pub struct FuncExtrudeNormals;
+
+impl Func for FuncExtrudeNormals {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Extrude Normals",
+            description: "EXTRUDE MESH ALONG ITS NORMALS\n\
+                          \n\
+                          Pushes every vertex of the mesh along the average normal of its \
+                          faces by the distance and connects the open borders of the mesh \
+                          to the pushed copy by side walls. \
+                          Optionally caps the extrusion with the original mesh, so that \
+                          an open mesh becomes a closed solid and a closed mesh becomes \
+                          a hollow solid.\n\
+                          \n\
+                          The input mesh should have consistently oriented faces, \
+                          otherwise the mesh is pushed in inconsistent directions.\n\
+                          \n\
+                          The input mesh will be marked used and thus invisible in the viewport. \
+                          It can still be used in subsequent operations.\n\
+                          \n\
+                          The resulting mesh geometry will be named 'Extruded Mesh'.",
+            return_value_name: "Extruded Mesh",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Mesh",
+                description: "Input mesh.",
+                refinement: ParamRefinement::Mesh,
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Distance",
+                description: "Distance of the extrusion in model units.\n\
+                              Negative distances extrude against the normals.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(1.0),
+                    min_value: None,
+                    max_value: None,
+                    dimension: Dimension::Length,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Cap",
+                description: "Closes the extrusion with the original mesh.\n\
+                              \n\
+                              If this option is off, only the pushed faces and the side \
+                              walls are created.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: true,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Mesh Analysis",
+                description: "Reports detailed analytic information on the created mesh.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let mesh = args[0].unwrap_mesh();
+        let distance = args[1].unwrap_float();
+        let cap = args[2].unwrap_boolean();
+        let analyze_mesh = args[3].unwrap_boolean();
+
+        let value = tools::extrude_along_normals(&mesh, distance, cap);
+
+        if analyze_mesh {
+            analytics::report_bounding_box_analysis(&value, log);
+            analytics::report_mesh_analysis(&value, log);
+        }
+
+        Ok(Value::Mesh(Arc::new(value)))
+    }
+}
@@ -0,0 +1,196 @@
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::interpreter::{
+    Dimension, FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
+    ParamRefinement, ParamWidget, StringParamRefinement, Ty, Value,
+};
+use crate::mesh::analysis;
+
+/// Storeys lower than this part of the storey height are not counted. Prevents
+/// a sliver of a storey from appearing due to floating point imprecision.
+const STOREY_HEIGHT_TOLERANCE: f32 = 0.001;
+
+#[derive(Debug)]
+pub enum FuncFloorAreasError {
+    CsvExport(io::Error),
+}
+
+impl fmt::Display for FuncFloorAreasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::CsvExport(err) => write!(f, "Failed to export CSV: {}", err),
+        }
+    }
+}
+
+impl error::Error for FuncFloorAreasError {}
+
+pub struct FuncFloorAreas;
+
+impl Func for FuncFloorAreas {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Floor Areas",
+            description: "COMPUTE FLOOR AREAS OF MASSING PER STOREY\n\
+                          \n\
+                          Slices the mesh geometry into storeys of the given height, \
+                          starting at its bottommost vertex, and reports the area of \
+                          each floor plate and the gross floor area (GFA) of all storeys. \
+                          If the site area is set, the floor area ratio (FAR) is also \
+                          reported.\n\
+                          \n\
+                          Each floor plate is measured in the middle of its storey. \
+                          The mesh geometry must be watertight for the areas to be correct.\n\
+                          \n\
+                          Optionally, the areas can be exported to a CSV file for further \
+                          processing in a spreadsheet.\n\
+                          \n\
+                          The input mesh will be marked used and thus invisible in the viewport. \
+                          It can still be used in subsequent operations.\n\
+                          \n\
+                          The resulting mesh geometry is the unchanged input mesh \
+                          and will be named 'Measured Mesh'.",
+            return_value_name: "Measured Mesh",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        // Not pure, because it may write the CSV export.
+        FuncFlags::empty()
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Mesh",
+                description: "Input mesh.",
+                refinement: ParamRefinement::Mesh,
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Storey height",
+                description: "The height of a single storey.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(3.0),
+                    min_value: Some(0.1),
+                    max_value: None,
+                    dimension: Dimension::Length,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Site area",
+                description: "The area of the site used to compute the floor area ratio.\n\
+                              The floor area ratio is not computed if the site area is zero.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(0.0),
+                    min_value: Some(0.0),
+                    max_value: None,
+                    dimension: Dimension::Area,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "CSV export path",
+                description: "Path to the CSV file the floor areas will be written to.\n\
+                              Nothing is exported if left empty.",
+                refinement: ParamRefinement::String(StringParamRefinement { default_value: "" }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let mesh = args[0].unwrap_refcounted_mesh();
+        let storey_height = args[1].unwrap_float();
+        let site_area = args[2].unwrap_float();
+        let csv_path = args[3].unwrap_string();
+
+        let bbox = mesh.bounding_box();
+        let bottom = bbox.minimum_point().z;
+        let top = bbox.maximum_point().z;
+
+        let storey_count = ((top - bottom) / storey_height - STOREY_HEIGHT_TOLERANCE)
+            .ceil()
+            .max(0.0) as usize;
+
+        let storeys: Vec<(f32, f32)> = (0..storey_count)
+            .map(|i| {
+                let elevation = bottom + i as f32 * storey_height;
+                let storey_top = (elevation + storey_height).min(top);
+                let area = analysis::mesh_cross_section_area(&mesh, (elevation + storey_top) / 2.0);
+
+                (elevation, area)
+            })
+            .collect();
+
+        for (i, (elevation, area)) in storeys.iter().enumerate() {
+            log(LogMessage::info(format!(
+                "Storey {} at elevation {:.2}",
+                i + 1,
+                elevation,
+            )));
+            log(LogMessage::measurement(
+                format!("Storey {} area", i + 1),
+                *area,
+                Dimension::Area,
+            ));
+        }
+
+        let gross_floor_area: f32 = storeys.iter().map(|(_, area)| area).sum();
+        log(LogMessage::measurement(
+            "Gross floor area",
+            gross_floor_area,
+            Dimension::Area,
+        ));
+
+        if site_area > 0.0 {
+            log(LogMessage::measurement(
+                "Floor area ratio",
+                gross_floor_area / site_area,
+                Dimension::Dimensionless,
+            ));
+        }
+
+        if !csv_path.is_empty() {
+            if let Err(err) = export_csv(Path::new(csv_path), &storeys, gross_floor_area) {
+                let error = FuncError::new(FuncFloorAreasError::CsvExport(err));
+                log(LogMessage::error(format!("Error: {}", error)));
+                return Err(error);
+            }
+
+            log(LogMessage::info(format!(
+                "Floor areas exported to {}",
+                csv_path
+            )));
+        }
+
+        Ok(Value::Mesh(mesh))
+    }
+}
+
+fn export_csv(path: &Path, storeys: &[(f32, f32)], gross_floor_area: f32) -> io::Result<()> {
+    let mut csv = String::from("storey,elevation,area\n");
+    for (i, (elevation, area)) in storeys.iter().enumerate() {
+        csv.push_str(&format!("{},{},{}\n", i + 1, elevation, area));
+    }
+    csv.push_str(&format!("total,,{}\n", gross_floor_area));
+
+    fs::write(path, csv)
+}
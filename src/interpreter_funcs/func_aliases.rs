@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+
+use crate::interpreter::ast::{CallExpr, Expr, Stmt};
+use crate::interpreter::FuncIdent;
+
+/// A retired operation, whose calls are replaced by calls of another
+/// operation, so that projects using it still open.
+pub struct FuncAlias {
+    replacement: FuncIdent,
+    migrate_args: Box<dyn Fn(Vec<Expr>) -> Vec<Expr>>,
+}
+
+impl FuncAlias {
+    /// Creates an alias of an operation replaced by an operation taking the
+    /// same arguments.
+    // Only used once operations get retired
+    #[allow(dead_code)]
+    pub fn new(replacement: FuncIdent) -> Self {
+        Self::with_migration(replacement, |args| args)
+    }
+
+    /// Creates an alias of an operation replaced by an operation taking
+    /// different arguments. The migration turns the arguments of the retired
+    /// operation into the arguments of the replacement. Trailing arguments
+    /// of the replacement may be left out, those get their default values.
+    #[allow(dead_code)]
+    pub fn with_migration<F>(replacement: FuncIdent, migrate_args: F) -> Self
+    where
+        F: Fn(Vec<Expr>) -> Vec<Expr> + 'static,
+    {
+        Self {
+            replacement,
+            migrate_args: Box::new(migrate_args),
+        }
+    }
+}
+
+/// Replaces the calls of retired operations in the statements by calls of
+/// their replacements, migrating the arguments. Operations retired in favor
+/// of operations retired later are replaced by the latest replacement.
+pub fn upgrade_stmts(aliases: &BTreeMap<FuncIdent, FuncAlias>, stmts: &mut [Stmt]) {
+    for stmt in stmts.iter_mut() {
+        let Stmt::VarDecl(var_decl) = stmt;
+        let mut func_ident = var_decl.init_expr().ident();
+        let mut args = var_decl.init_expr().args().to_vec();
+
+        // Each alias is followed at most once, so that a cyclic table can't
+        // hang the upgrade
+        let mut upgrade_count = 0;
+        while let Some(alias) = aliases.get(&func_ident) {
+            if upgrade_count == aliases.len() {
+                log::error!("Retired operation {} has cyclic replacements", func_ident);
+                break;
+            }
+
+            log::info!(
+                "Upgrading retired operation {} to {}",
+                func_ident,
+                alias.replacement,
+            );
+            func_ident = alias.replacement;
+            args = (alias.migrate_args)(args);
+            upgrade_count += 1;
+        }
+
+        if upgrade_count > 0 {
+            *var_decl = var_decl.clone_with_init_expr(CallExpr::new(func_ident, args));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::ast::{LitExpr, VarDeclStmt, VarExpr, VarIdent};
+
+    use super::*;
+
+    fn stmt(var: u64, func: u64, args: Vec<Expr>) -> Stmt {
+        Stmt::VarDecl(VarDeclStmt::new(
+            VarIdent(var),
+            CallExpr::new(FuncIdent(func), args),
+        ))
+    }
+
+    #[test]
+    fn test_func_aliases_upgrade_stmts_migrates_args() {
+        let mut aliases = BTreeMap::new();
+        // The retired operation took the iterations as a float
+        aliases.insert(
+            FuncIdent(100),
+            FuncAlias::with_migration(FuncIdent(200), |args| {
+                args.into_iter()
+                    .map(|arg| match arg {
+                        Expr::Lit(LitExpr::Float(value)) => Expr::Lit(LitExpr::Uint(value as u32)),
+                        arg => arg,
+                    })
+                    .collect()
+            }),
+        );
+
+        let mut stmts = vec![
+            stmt(0, 1, vec![Expr::Lit(LitExpr::Float(1.0))]),
+            stmt(
+                1,
+                100,
+                vec![
+                    Expr::Var(VarExpr::new(VarIdent(0))),
+                    Expr::Lit(LitExpr::Float(3.0)),
+                ],
+            ),
+        ];
+        upgrade_stmts(&aliases, &mut stmts);
+
+        assert_eq!(
+            stmts,
+            vec![
+                stmt(0, 1, vec![Expr::Lit(LitExpr::Float(1.0))]),
+                stmt(
+                    1,
+                    200,
+                    vec![
+                        Expr::Var(VarExpr::new(VarIdent(0))),
+                        Expr::Lit(LitExpr::Uint(3)),
+                    ],
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_func_aliases_upgrade_stmts_follows_chained_aliases() {
+        let mut aliases = BTreeMap::new();
+        aliases.insert(
+            FuncIdent(100),
+            FuncAlias::with_migration(FuncIdent(101), |mut args| {
+                args.push(Expr::Lit(LitExpr::Boolean(true)));
+                args
+            }),
+        );
+        aliases.insert(FuncIdent(101), FuncAlias::new(FuncIdent(102)));
+
+        let mut stmts = vec![stmt(0, 100, vec![Expr::Lit(LitExpr::Uint(2))])];
+        upgrade_stmts(&aliases, &mut stmts);
+
+        assert_eq!(
+            stmts,
+            vec![stmt(
+                0,
+                102,
+                vec![
+                    Expr::Lit(LitExpr::Uint(2)),
+                    Expr::Lit(LitExpr::Boolean(true)),
+                ],
+            )],
+        );
+    }
+
+    #[test]
+    fn test_func_aliases_upgrade_stmts_stops_at_cycle() {
+        let mut aliases = BTreeMap::new();
+        aliases.insert(FuncIdent(100), FuncAlias::new(FuncIdent(101)));
+        aliases.insert(FuncIdent(101), FuncAlias::new(FuncIdent(100)));
+
+        let mut stmts = vec![stmt(0, 100, Vec::new())];
+        upgrade_stmts(&aliases, &mut stmts);
+
+        assert_eq!(stmts, vec![stmt(0, 100, Vec::new())]);
+    }
+}
@@ -5,10 +5,10 @@ use std::sync::Arc;
 use nalgebra::{Matrix4, Point3, Vector3};
 
 use crate::analytics;
-use crate::importer::{Importer, ImporterError, ObjCache};
+use crate::importer::{self, Importer, ImporterError, Model, ObjCache};
 use crate::interpreter::{
-    BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, StringParamRefinement, Ty, Value,
+    BooleanParamRefinement, Dimension, FilePathParamRefinement, FloatParamRefinement, Func,
+    FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::{tools, Mesh};
 
@@ -64,12 +64,11 @@ impl<C: ObjCache> Func for FuncImportObjJoin<C> {
             ParamInfo {
                 name: "Path",
                 description: "Path to the OBJ file.",
-                refinement: ParamRefinement::String(StringParamRefinement {
-                    default_value: "",
-                    file_path: true,
+                refinement: ParamRefinement::FilePath(FilePathParamRefinement {
                     file_ext_filter: Some((&["*.obj", "*.OBJ"], "Wavefront (.obj)")),
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Move to origin",
@@ -79,6 +78,7 @@ impl<C: ObjCache> Func for FuncImportObjJoin<C> {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Snap to ground",
@@ -88,6 +88,7 @@ impl<C: ObjCache> Func for FuncImportObjJoin<C> {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -97,6 +98,22 @@ impl<C: ObjCache> Func for FuncImportObjJoin<C> {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Unit scale",
+                description: "Scales the imported geometry, e.g. to convert it from the units \
+                              of the OBJ file to the project units.\n\
+                              If the file declares its units, conversion to the project units \
+                              is offered automatically.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(1.0),
+                    min_value: Some(0.000_001),
+                    max_value: None,
+                    dimension: Dimension::Dimensionless,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
@@ -114,6 +131,16 @@ impl<C: ObjCache> Func for FuncImportObjJoin<C> {
         let move_to_origin = args[1].unwrap_boolean();
         let snap_to_ground = args[2].unwrap_boolean();
         let analyze_mesh = args[3].unwrap_boolean();
+        let unit_scale = args[4].unwrap_float();
+
+        match importer::obj_file_declared_unit(path) {
+            Ok(Some(unit)) => log(LogMessage::info(format!(
+                "The OBJ file declares its units as {}",
+                unit.name().to_lowercase(),
+            ))),
+            Ok(None) => (),
+            Err(err) => log::debug!("Failed to read units declared in OBJ file: {}", err),
+        }
 
         let result = self.importer.import_obj(path);
         match result {
@@ -123,6 +150,19 @@ impl<C: ObjCache> Func for FuncImportObjJoin<C> {
                     log(LogMessage::error(format!("Error: {}", error)));
                     Err(error)
                 } else {
+                    let models: Vec<_> = if (unit_scale - 1.0).abs() > f32::EPSILON {
+                        log(LogMessage::info(format!("Scaled by {}", unit_scale)));
+                        models
+                            .into_iter()
+                            .map(|model| Model {
+                                name: model.name,
+                                mesh: tools::scale_uniformly(&model.mesh, unit_scale),
+                            })
+                            .collect()
+                    } else {
+                        models
+                    };
+
                     let imported_meshes: Vec<_> =
                         models.into_iter().map(|model| model.mesh).collect();
                     let single_mesh = tools::join_multiple_meshes(imported_meshes.iter());
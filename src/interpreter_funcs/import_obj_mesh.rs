@@ -6,12 +6,14 @@ use nalgebra::{Matrix4, Point3, Vector3};
 
 use crate::analytics;
 use crate::bounding_box::BoundingBox;
-use crate::importer::{Importer, ImporterError, ObjCache};
+use crate::convert::{cast_u32, cast_usize};
+use crate::importer::{self, Importer, ImporterError, Model, ObjCache};
 use crate::interpreter::{
-    BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, MeshArrayValue,
-    ParamInfo, ParamRefinement, StringParamRefinement, Ty, Value,
+    BooleanParamRefinement, Dimension, FilePathParamRefinement, FloatParamRefinement, Func,
+    FuncError, FuncFlags, FuncInfo, LogMessage, MeshArrayValue, ParamInfo, ParamRefinement,
+    ParamWidget, Ty, Value,
 };
-use crate::mesh::Mesh;
+use crate::mesh::{tools, Mesh};
 
 #[derive(Debug, PartialEq)]
 pub enum FuncImportObjMeshError {
@@ -69,12 +71,11 @@ impl<C: ObjCache> Func for FuncImportObjMesh<C> {
             ParamInfo {
                 name: "Path",
                 description: "Path to the OBJ file.",
-                refinement: ParamRefinement::String(StringParamRefinement {
-                    default_value: "",
-                    file_path: true,
+                refinement: ParamRefinement::FilePath(FilePathParamRefinement {
                     file_ext_filter: Some((&["*.obj", "*.OBJ"], "Wavefront (.obj)")),
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Move to origin",
@@ -84,6 +85,7 @@ impl<C: ObjCache> Func for FuncImportObjMesh<C> {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Snap to ground",
@@ -93,6 +95,7 @@ impl<C: ObjCache> Func for FuncImportObjMesh<C> {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Group Analysis",
@@ -101,6 +104,22 @@ impl<C: ObjCache> Func for FuncImportObjMesh<C> {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Unit scale",
+                description: "Scales the imported geometry, e.g. to convert it from the units \
+                              of the OBJ file to the project units.\n\
+                              If the file declares its units, conversion to the project units \
+                              is offered automatically.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(1.0),
+                    min_value: Some(0.000_001),
+                    max_value: None,
+                    dimension: Dimension::Dimensionless,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
@@ -118,6 +137,16 @@ impl<C: ObjCache> Func for FuncImportObjMesh<C> {
         let move_to_origin = args[1].unwrap_boolean();
         let snap_to_ground = args[2].unwrap_boolean();
         let analyze = args[3].unwrap_boolean();
+        let unit_scale = args[4].unwrap_float();
+
+        match importer::obj_file_declared_unit(path) {
+            Ok(Some(unit)) => log(LogMessage::info(format!(
+                "The OBJ file declares its units as {}",
+                unit.name().to_lowercase(),
+            ))),
+            Ok(None) => (),
+            Err(err) => log::debug!("Failed to read units declared in OBJ file: {}", err),
+        }
 
         let result = self.importer.import_obj(path);
         match result {
@@ -127,10 +156,23 @@ impl<C: ObjCache> Func for FuncImportObjMesh<C> {
                     log(LogMessage::error(format!("Error: {}", error)));
                     Err(error)
                 } else {
-                    let meshes: Vec<_> = if move_to_origin || snap_to_ground {
-                        let meshes_iter = models.into_iter().map(|model| model.mesh);
+                    let models: Vec<_> = if (unit_scale - 1.0).abs() > f32::EPSILON {
+                        log(LogMessage::info(format!("Scaled by {}", unit_scale)));
+                        models
+                            .into_iter()
+                            .map(|model| Model {
+                                name: model.name,
+                                mesh: tools::scale_uniformly(&model.mesh, unit_scale),
+                            })
+                            .collect()
+                    } else {
+                        models
+                    };
+
+                    let value = if move_to_origin || snap_to_ground {
+                        let meshes: Vec<_> = models.into_iter().map(|model| model.mesh).collect();
 
-                        let bboxes = meshes_iter.clone().map(|mesh| mesh.bounding_box());
+                        let bboxes = meshes.iter().map(|mesh| mesh.bounding_box());
                         let union_box = BoundingBox::union(bboxes).expect("No valid meshes");
 
                         let translation_vector = match (move_to_origin, snap_to_ground) {
@@ -148,28 +190,30 @@ impl<C: ObjCache> Func for FuncImportObjMesh<C> {
                         };
                         let translation = Matrix4::new_translation(&translation_vector);
 
-                        meshes_iter
-                            .map(|mesh| {
-                                let vertices_iter = mesh
-                                    .vertices()
-                                    .iter()
-                                    .map(|v| translation.transform_point(v));
-
-                                Arc::new(Mesh::from_faces_with_vertices_and_normals(
-                                    mesh.faces().iter().copied(),
-                                    vertices_iter,
-                                    mesh.normals().iter().copied(),
-                                ))
-                            })
-                            .collect()
+                        // Files with many groups would otherwise hold a
+                        // translated copy of every group, even if only a few
+                        // get extracted.
+                        MeshArrayValue::lazy(cast_u32(meshes.len()), move |index| {
+                            let mesh = &meshes[cast_usize(index)];
+                            let vertices_iter = mesh
+                                .vertices()
+                                .iter()
+                                .map(|v| translation.transform_point(v));
+
+                            Mesh::from_faces_with_vertices_and_normals(
+                                mesh.faces().iter().copied(),
+                                vertices_iter,
+                                mesh.normals().iter().copied(),
+                            )
+                        })
                     } else {
-                        models
+                        let meshes = models
                             .into_iter()
                             .map(|model| Arc::new(model.mesh))
-                            .collect()
-                    };
+                            .collect();
 
-                    let value = MeshArrayValue::new(meshes);
+                        MeshArrayValue::new(meshes)
+                    };
 
                     if analyze {
                         analytics::report_group_analysis(&value, log);
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use crate::analytics;
 use crate::interpreter::{
     BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, Value,
+    ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::tools;
 
@@ -41,6 +41,7 @@ impl Func for FuncJoinGroup {
                 description: "Input mesh group.",
                 refinement: ParamRefinement::MeshArray,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -50,6 +51,7 @@ impl Func for FuncJoinGroup {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
@@ -66,8 +68,8 @@ impl Func for FuncJoinGroup {
         let mesh_arc_array = args[0].unwrap_mesh_array();
         let analyze_mesh = args[1].unwrap_boolean();
 
-        let meshes = mesh_arc_array.iter();
-        let value = tools::join_multiple_meshes(meshes);
+        let meshes: Vec<_> = mesh_arc_array.iter_refcounted().collect();
+        let value = tools::join_multiple_meshes(meshes.iter().map(Arc::as_ref));
 
         if analyze_mesh {
             analytics::report_bounding_box_analysis(&value, log);
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use crate::analytics;
 use crate::interpreter::{
     BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, Value,
+    ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::tools;
 
@@ -13,11 +13,12 @@ impl Func for FuncJoinMeshes {
     fn info(&self) -> &FuncInfo {
         &FuncInfo {
             name: "Join Meshes",
-            description: "JOIN TWO MESH GEOMETRIES INTO ONE\n\
+            description: "JOIN MESH GEOMETRIES INTO ONE\n\
                           \n\
                           Creates a new mesh containing vertices and triangles \
-                          from both input meshes. \
-                          The two meshes will not be welded.\n\
+                          from all input meshes. \
+                          More input meshes can be added to the operation. \
+                          The meshes will not be welded.\n\
                           \n\
                           The input meshes will be marked used \
                           and thus invisible in the viewport. \
@@ -39,12 +40,14 @@ impl Func for FuncJoinMeshes {
                 description: "First input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
-                name: "Mesh 2",
-                description: "Second input mesh.",
+                name: "Mesh",
+                description: "Another input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -54,10 +57,15 @@ impl Func for FuncJoinMeshes {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn variadic_param_index(&self) -> Option<usize> {
+        Some(1)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
@@ -67,10 +75,12 @@ impl Func for FuncJoinMeshes {
         args: &[Value],
         log: &mut dyn FnMut(LogMessage),
     ) -> Result<Value, FuncError> {
-        let meshes = [args[0].unwrap_mesh(), args[1].unwrap_mesh()];
-        let analyze_mesh = args[2].unwrap_boolean();
+        let (analyze_mesh, meshes) = args
+            .split_last()
+            .expect("Join Meshes must have been called with arguments");
+        let analyze_mesh = analyze_mesh.unwrap_boolean();
 
-        let value = tools::join_multiple_meshes(meshes.iter().copied());
+        let value = tools::join_multiple_meshes(meshes.iter().map(Value::unwrap_mesh));
 
         if analyze_mesh {
             analytics::report_bounding_box_analysis(&value, log);
@@ -3,10 +3,11 @@ use std::sync::Arc;
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, UintParamRefinement, Value,
+    BooleanParamRefinement, Func, FuncDocs, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
+    ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
-use crate::mesh::{smoothing, topology, NormalStrategy};
+use crate::interpreter_funcs::{normal_strategy_from_choice, NORMAL_STRATEGY_CHOICES};
+use crate::mesh::{smoothing, topology};
 
 pub struct FuncLaplacianSmoothing;
 
@@ -34,6 +35,23 @@ impl Func for FuncLaplacianSmoothing {
         }
     }
 
+    fn docs(&self) -> Option<&FuncDocs> {
+        Some(&FuncDocs {
+            details: "Relax is typically used after voxel operations to smooth away the \
+            staircase artifacts of the voxel grid, or after Smoothen to round \
+            off a coarse mesh.\n\
+            \n\
+            A few iterations usually suffice to remove grain. Each further iteration \
+            moves the vertices less, but keeps shrinking the mesh, so thin parts \
+            eventually disappear. Relaxing a mesh with few faces makes it lose its \
+            volume quickly, subdivide it first to keep its shape.\n\
+            \n\
+            Relax only moves the vertices. To get rid of small disconnected pieces, use \
+            Extract Largest, to fill in holes, voxelize the mesh first.",
+            image: Some(include_bytes!("../../resources/docs/relax.png")),
+        })
+    }
+
     fn flags(&self) -> FuncFlags {
         FuncFlags::PURE
     }
@@ -45,6 +63,7 @@ impl Func for FuncLaplacianSmoothing {
                 description: "Input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Iterations",
@@ -57,21 +76,27 @@ impl Func for FuncLaplacianSmoothing {
                     max_value: Some(255),
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
-                name: "Smooth normals",
-                description: "Sets the per-vertex mesh normals to be interpolated from \
-                     connected face normals. As a result, the rendered geometry will have \
-                     a smooth surface material even though the mesh itself may be coarse.\n\
+                name: "Normals",
+                description: "Chooses how the per-vertex mesh normals are computed.\n\
                      \n\
-                     When disabled, the geometry will be rendered as angular: each face will \
-                     appear flat, exposing edges as sharp creases.\n\
+                     Smooth normals are interpolated from connected face normals. As a result, \
+                     the rendered geometry will have a smooth surface material even though \
+                     the mesh itself may be coarse.\n\
                      \n\
-                     The normal smoothing strategy does not affect the geometry itself.",
-                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
-                    default_value: true,
+                     With sharp normals, the geometry will be rendered as angular: each face \
+                     will appear flat, exposing edges as sharp creases.\n\
+                     \n\
+                     The normal strategy does not affect the geometry itself.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(1),
+                    min_value: Some(0),
+                    max_value: Some(1),
                 }),
                 optional: false,
+                widget: ParamWidget::Choice(NORMAL_STRATEGY_CHOICES),
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -81,10 +106,19 @@ impl Func for FuncLaplacianSmoothing {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn draft_arg(&self, param_index: usize, value: &Value) -> Option<Value> {
+        // Half of the iterations, but at least one, if any
+        match (param_index, value) {
+            (1, Value::Uint(iterations)) => Some(Value::Uint(iterations - iterations / 2)),
+            _ => None,
+        }
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
@@ -96,17 +130,11 @@ impl Func for FuncLaplacianSmoothing {
     ) -> Result<Value, FuncError> {
         let mesh = args[0].unwrap_mesh();
         let iterations = args[1].unwrap_uint();
-        let smooth = args[2].unwrap_boolean();
+        let normal_strategy = normal_strategy_from_choice(args[2].unwrap_uint());
         let analyze_mesh = args[3].unwrap_boolean();
 
         let vertex_to_vertex_topology = topology::compute_vertex_to_vertex_topology(mesh);
 
-        let normal_strategy = if smooth {
-            NormalStrategy::Smooth
-        } else {
-            NormalStrategy::Sharp
-        };
-
         let (value, _, _) = smoothing::laplacian_smoothing(
             mesh,
             &vertex_to_vertex_topology,
@@ -0,0 +1,136 @@
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::analytics;
+use crate::importer::ImporterError;
+use crate::interpreter::{
+    BooleanParamRefinement, Dimension, FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, StringParamRefinement, Ty, Value,
+};
+use crate::library;
+use crate::mesh::tools;
+
+#[derive(Debug, PartialEq)]
+pub enum FuncLibraryMeshError {
+    UnknownAsset(String),
+    Importer(ImporterError),
+}
+
+impl fmt::Display for FuncLibraryMeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownAsset(id) => write!(f, "No library asset named '{}'", id),
+            Self::Importer(importer_error) => f.write_str(&importer_error.to_string()),
+        }
+    }
+}
+
+impl error::Error for FuncLibraryMeshError {}
+
+pub struct FuncLibraryMesh;
+
+impl Func for FuncLibraryMesh {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Library Mesh",
+            description: "INSERT A MESH FROM THE BUILT-IN LIBRARY\n\
+                          \n\
+                          Creates a mesh of the built-in library of context geometry, \
+                          such as trees, human figures and simple buildings. The mesh \
+                          is centered on the world origin and stands on the ground.\n\
+                          \n\
+                          The library is best browsed in the Library window.\n\
+                          \n\
+                          The resulting mesh geometry will be named 'Library Mesh'.",
+            return_value_name: "Library Mesh",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Asset",
+                description: "Identifier of the library asset, e.g. 'tree-deciduous'.",
+                refinement: ParamRefinement::String(StringParamRefinement {
+                    default_value: "tree-deciduous",
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Unit scale",
+                description: "Scales the library mesh, e.g. to convert it from meters \
+                              to the project units.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(1.0),
+                    min_value: Some(0.000_001),
+                    max_value: None,
+                    dimension: Dimension::Dimensionless,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Mesh Analysis",
+                description: "Reports detailed analytic information on the created mesh.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let id = args[0].unwrap_string();
+        let unit_scale = args[1].unwrap_float();
+        let analyze_mesh = args[2].unwrap_boolean();
+
+        let asset = match library::find_asset(id) {
+            Some(asset) => asset,
+            None => {
+                let error = FuncError::new(FuncLibraryMeshError::UnknownAsset(id.to_string()));
+                log(LogMessage::error(format!("Error: {}", error)));
+                return Err(error);
+            }
+        };
+
+        let models = match asset.import() {
+            Ok(models) => models,
+            Err(err) => {
+                let error = FuncError::new(FuncLibraryMeshError::Importer(err));
+                log(LogMessage::error(format!("Error: {}", error)));
+                return Err(error);
+            }
+        };
+
+        let meshes: Vec<_> = models.into_iter().map(|model| model.mesh).collect();
+        let mut value = tools::join_multiple_meshes(meshes.iter());
+        if (unit_scale - 1.0).abs() > f32::EPSILON {
+            log(LogMessage::info(format!("Scaled by {}", unit_scale)));
+            value = tools::scale_uniformly(&value, unit_scale);
+        }
+
+        if analyze_mesh {
+            analytics::report_bounding_box_analysis(&value, log);
+            analytics::report_mesh_analysis(&value, log);
+        }
+
+        Ok(Value::Mesh(Arc::new(value)))
+    }
+}
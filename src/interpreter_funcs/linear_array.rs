@@ -0,0 +1,217 @@
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use nalgebra::{Matrix4, Rotation, Vector3};
+
+use crate::analytics;
+use crate::convert::cast_i32;
+use crate::interpreter::{
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, MeshArrayValue, ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement,
+    Value,
+};
+use crate::mesh::Mesh;
+
+#[derive(Debug, PartialEq)]
+pub enum FuncLinearArrayError {
+    CountZero,
+}
+
+impl fmt::Display for FuncLinearArrayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FuncLinearArrayError::CountZero => write!(f, "The count of copies is zero"),
+        }
+    }
+}
+
+impl error::Error for FuncLinearArrayError {}
+
+pub struct FuncLinearArray;
+
+impl Func for FuncLinearArray {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Linear Array",
+            description: "LINEAR ARRAY OF MESH COPIES\n\
+                          \n\
+                          Duplicates the mesh geometry into a group of copies. Each copy \
+                          is moved, rotated and scaled by one more step than the previous one, \
+                          the first copy being the original mesh geometry. \
+                          The copies can be extracted from the group or joined \
+                          into a single mesh geometry.\n\
+                          \n\
+                          The input mesh will be marked used and thus invisible in the viewport. \
+                          It can still be used in subsequent operations.\n\
+                          \n\
+                          The resulting group will be named 'Array'.",
+            return_value_name: "Array",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Mesh",
+                description: "Input mesh.",
+                refinement: ParamRefinement::Mesh,
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Count",
+                description: "Number of copies in the array, including the original.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(3),
+                    min_value: Some(1),
+                    max_value: None,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Move Step",
+                description: "Translation (movement) in X, Y and Z direction \
+                              between two subsequent copies.",
+                refinement: ParamRefinement::Float3(Float3ParamRefinement {
+                    min_value: None,
+                    max_value: None,
+                    default_value_x: Some(1.0),
+                    default_value_y: Some(0.0),
+                    default_value_z: Some(0.0),
+                    dimension: Dimension::Length,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Rotate Step",
+                description: "Rotation around the X, Y and Z axis in degrees \
+                              between two subsequent copies.",
+                refinement: ParamRefinement::Float3(Float3ParamRefinement {
+                    min_value: None,
+                    max_value: None,
+                    default_value_x: Some(0.0),
+                    default_value_y: Some(0.0),
+                    default_value_z: Some(0.0),
+                    dimension: Dimension::Angle,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Scale Step",
+                description: "Relative scaling factors for the world X, Y and Z axis \
+                              between two subsequent copies.",
+                refinement: ParamRefinement::Float3(Float3ParamRefinement {
+                    min_value: None,
+                    max_value: None,
+                    default_value_x: Some(1.0),
+                    default_value_y: Some(1.0),
+                    default_value_z: Some(1.0),
+                    dimension: Dimension::Dimensionless,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Transform around object center",
+                description: "Rotates and scales each copy around its own center \
+                              instead of global world origin.\n\
+                              \n\
+                              If this option is off, the steps accumulate around global \
+                              world origin, e.g. producing spirals.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: true,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Group Analysis",
+                description: "Reports detailed analytic information on the created mesh group.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::MeshArray
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let mesh = args[0].unwrap_refcounted_mesh();
+        let count = args[1].unwrap_uint();
+        let translate = Vector3::from(args[2].unwrap_float3());
+        let rotate = args[3].unwrap_float3();
+        let scale = Vector3::from(args[4].unwrap_float3());
+        let transform_around_local_center = args[5].unwrap_boolean();
+        let analyze = args[6].unwrap_boolean();
+
+        if count == 0 {
+            let error = FuncError::new(FuncLinearArrayError::CountZero);
+            log(LogMessage::error(format!("Error: {}", error)));
+            return Err(error);
+        }
+
+        let step_rotation = Rotation::from_euler_angles(
+            rotate[0].to_radians(),
+            rotate[1].to_radians(),
+            rotate[2].to_radians(),
+        );
+        let center = mesh.bounding_box().center();
+
+        // Copies are generated once they are accessed, so that funcs using
+        // only some of them don't wait for all of them. The interpreter
+        // generates the rest before the value leaves its thread.
+        let value = MeshArrayValue::lazy(count, move |index| {
+            let t = if transform_around_local_center {
+                // Move to the origin, scale and rotate by all the steps so
+                // far, then move back and by all the translation steps.
+                let step_count = index as f32;
+                let rotation = step_rotation.powf(step_count);
+                let scaling = scale.map(|factor| factor.powi(cast_i32(index)));
+
+                Matrix4::new_translation(&(center.coords + translate * step_count))
+                    * Matrix4::from(rotation)
+                    * Matrix4::new_nonuniform_scaling(&scaling)
+                    * Matrix4::new_translation(&-center.coords)
+            } else {
+                let step = Matrix4::new_translation(&translate)
+                    * Matrix4::from(step_rotation)
+                    * Matrix4::new_nonuniform_scaling(&scale);
+
+                (0..index).fold(Matrix4::identity(), |t, _| step * t)
+            };
+
+            let vertices_iter = mesh.vertices().iter().map(|v| t.transform_point(v));
+            let normals_iter = mesh.normals().iter().map(|n| t.transform_vector(n));
+
+            Mesh::from_faces_with_vertices_and_normals(
+                mesh.faces().iter().copied(),
+                vertices_iter,
+                normals_iter,
+            )
+        });
+
+        if analyze {
+            analytics::report_group_analysis(&value, log);
+        }
+
+        Ok(Value::MeshArray(Arc::new(value)))
+    }
+}
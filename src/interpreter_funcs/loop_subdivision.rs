@@ -5,10 +5,11 @@ use std::sync::Arc;
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, UintParamRefinement, Value,
+    BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, MeshRequirements,
+    ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
-use crate::mesh::{smoothing, topology, NormalStrategy};
+use crate::interpreter_funcs::{normal_strategy_from_choice, NORMAL_STRATEGY_CHOICES};
+use crate::mesh::{smoothing, topology};
 
 #[derive(Debug, PartialEq)]
 pub enum FuncLoopSubdivisionError {
@@ -76,6 +77,7 @@ impl Func for FuncLoopSubdivision {
                               The mesh must be triangulated, watertight and manifold.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Iterations",
@@ -90,21 +92,27 @@ impl Func for FuncLoopSubdivision {
                     max_value: Some(Self::MAX_ITERATIONS),
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
-                name: "Smooth normals",
-                description: "Sets the per-vertex mesh normals to be interpolated from \
-                     connected face normals. As a result, the rendered geometry will have \
-                     a smooth surface material even though the mesh itself may be coarse.\n\
+                name: "Normals",
+                description: "Chooses how the per-vertex mesh normals are computed.\n\
                      \n\
-                     When disabled, the geometry will be rendered as angular: each face will \
-                     appear flat, exposing edges as sharp creases.\n\
+                     Smooth normals are interpolated from connected face normals. As a result, \
+                     the rendered geometry will have a smooth surface material even though \
+                     the mesh itself may be coarse.\n\
                      \n\
-                     The normal smoothing strategy does not affect the geometry itself.",
-                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
-                    default_value: true,
+                     With sharp normals, the geometry will be rendered as angular: each face \
+                     will appear flat, exposing edges as sharp creases.\n\
+                     \n\
+                     The normal strategy does not affect the geometry itself.",
+                refinement: ParamRefinement::Uint(UintParamRefinement {
+                    default_value: Some(1),
+                    min_value: Some(0),
+                    max_value: Some(1),
                 }),
                 optional: false,
+                widget: ParamWidget::Choice(NORMAL_STRATEGY_CHOICES),
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -114,10 +122,27 @@ impl Func for FuncLoopSubdivision {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn mesh_requirements(&self, param_index: usize) -> MeshRequirements {
+        if param_index == 0 {
+            MeshRequirements::WATERTIGHT | MeshRequirements::MANIFOLD
+        } else {
+            MeshRequirements::empty()
+        }
+    }
+
+    fn draft_arg(&self, param_index: usize, value: &Value) -> Option<Value> {
+        // Each iteration quadruples the face count
+        match (param_index, value) {
+            (1, Value::Uint(iterations)) => Some(Value::Uint(cmp::min(*iterations, 1))),
+            _ => None,
+        }
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
@@ -129,7 +154,13 @@ impl Func for FuncLoopSubdivision {
     ) -> Result<Value, FuncError> {
         let mesh = args[0].unwrap_refcounted_mesh();
         let iterations = cmp::min(args[1].unwrap_uint(), Self::MAX_ITERATIONS);
-        let smooth = args[2].unwrap_boolean();
+        if args[1].unwrap_uint() > Self::MAX_ITERATIONS {
+            log(LogMessage::warn(format!(
+                "Iterations clamped to {}",
+                Self::MAX_ITERATIONS
+            )));
+        }
+        let normal_strategy = normal_strategy_from_choice(args[2].unwrap_uint());
         let analyze_mesh = args[3].unwrap_boolean();
 
         if iterations == 0 {
@@ -142,12 +173,6 @@ impl Func for FuncLoopSubdivision {
         let mut face_to_face_topology =
             topology::compute_face_to_face_topology(&mesh, &vertex_to_face_topology);
 
-        let normal_strategy = if smooth {
-            NormalStrategy::Smooth
-        } else {
-            NormalStrategy::Sharp
-        };
-
         if let Some(mut current_mesh) = smoothing::loop_subdivision(
             &mesh,
             &vertex_to_vertex_topology,
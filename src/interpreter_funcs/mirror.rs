@@ -0,0 +1,186 @@
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::analytics;
+use crate::interpreter::{
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, FloatParamRefinement, Func,
+    FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
+};
+use crate::mesh::tools;
+use crate::plane::Plane;
+
+#[derive(Debug, PartialEq)]
+pub enum FuncMirrorError {
+    NormalZero,
+    MeshInPlane,
+}
+
+impl fmt::Display for FuncMirrorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FuncMirrorError::NormalZero => write!(f, "The plane normal is a zero vector"),
+            FuncMirrorError::MeshInPlane => write!(
+                f,
+                "The mesh lies entirely in the plane, nothing is left after joining"
+            ),
+        }
+    }
+}
+
+impl error::Error for FuncMirrorError {}
+
+pub struct FuncMirror;
+
+impl Func for FuncMirror {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Mirror",
+            description: "MIRROR MESH BY A PLANE\n\
+                          \n\
+                          Creates a mirrored copy of the mesh geometry. The mirror plane \
+                          is defined by a point and a normal vector.\n\
+                          \n\
+                          Optionally joins the mirrored copy with the original mesh and \
+                          welds them along the plane. This is useful for modeling symmetric \
+                          geometries by their halves: vertices close to the plane are shared \
+                          by both halves and faces lying in the plane are removed, so that \
+                          a watertight half cut by the plane produces a watertight whole.\n\
+                          \n\
+                          The input mesh will be marked used and thus invisible in the viewport. \
+                          It can still be used in subsequent operations.\n\
+                          \n\
+                          The resulting mesh geometry will be named 'Mirrored Mesh'.",
+            return_value_name: "Mirrored Mesh",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Mesh",
+                description: "Input mesh.",
+                refinement: ParamRefinement::Mesh,
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Plane Point",
+                description: "A point the mirror plane passes through, in absolute model units.",
+                refinement: ParamRefinement::Float3(Float3ParamRefinement {
+                    min_value: None,
+                    max_value: None,
+                    default_value_x: Some(0.0),
+                    default_value_y: Some(0.0),
+                    default_value_z: Some(0.0),
+                    dimension: Dimension::Length,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Plane Normal",
+                description: "Direction perpendicular to the mirror plane.\n\
+                              The length of the vector doesn't matter, but it can't be zero.",
+                refinement: ParamRefinement::Float3(Float3ParamRefinement {
+                    min_value: None,
+                    max_value: None,
+                    default_value_x: Some(1.0),
+                    default_value_y: Some(0.0),
+                    default_value_z: Some(0.0),
+                    dimension: Dimension::Dimensionless,
+                }),
+                optional: false,
+                widget: ParamWidget::Vector3,
+            },
+            ParamInfo {
+                name: "Join and Weld",
+                description: "Joins the mirrored copy with the original mesh and welds them \
+                              along the plane.\n\
+                              \n\
+                              If this option is off, only the mirrored copy is created.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Weld Tolerance",
+                description: "Limit distance of a vertex from the plane to be welded \
+                              with its mirrored copy.\n\
+                              \n\
+                              Only used when joining with the original mesh.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(0.001),
+                    min_value: Some(0.0),
+                    max_value: None,
+                    dimension: Dimension::Length,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Mesh Analysis",
+                description: "Reports detailed analytic information on the created mesh.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let mesh = args[0].unwrap_mesh();
+        let plane_point = Point3::from_slice(&args[1].unwrap_float3());
+        let plane_normal = Vector3::from(args[2].unwrap_float3());
+        let join = args[3].unwrap_boolean();
+        let tolerance = args[4].unwrap_float();
+        let analyze_mesh = args[5].unwrap_boolean();
+
+        if plane_normal == Vector3::zeros() {
+            let error = FuncError::new(FuncMirrorError::NormalZero);
+            log(LogMessage::error(format!("Error: {}", error)));
+            return Err(error);
+        }
+
+        let plane = Plane::from_origin_and_normal(&plane_point, &plane_normal);
+
+        let value = if join {
+            match tools::mirror_and_join(&mesh, &plane, tolerance) {
+                Some(value) => value,
+                None => {
+                    let error = FuncError::new(FuncMirrorError::MeshInPlane);
+                    log(LogMessage::error(format!("Error: {}", error)));
+                    return Err(error);
+                }
+            }
+        } else {
+            tools::mirror(&mesh, &plane)
+        };
+
+        if analyze_mesh {
+            analytics::report_bounding_box_analysis(&value, log);
+            analytics::report_mesh_analysis(&value, log);
+        }
+
+        Ok(Value::Mesh(Arc::new(value)))
+    }
+}
@@ -2,26 +2,38 @@ use std::collections::BTreeMap;
 
 use crate::importer::{EndlessCache, Importer};
 use crate::interpreter::{Func, FuncIdent};
+use crate::mesh::NormalStrategy;
 
 use self::align::FuncAlign;
+use self::analyze_sun_exposure::FuncAnalyzeSunExposure;
+use self::catmull_clark_subdivision::FuncCatmullClarkSubdivision;
 use self::create_box::FuncCreateBox;
 use self::create_plane::FuncCreatePlane;
+use self::create_polygon::FuncCreatePolygon;
 use self::create_uv_sphere::FuncCreateUvSphere;
 use self::disjoint_mesh::FuncDisjointMesh;
 use self::extract::FuncExtract;
 use self::extract_largest::FuncExtractLargest;
+use self::extrude::FuncExtrude;
+use self::extrude_normals::FuncExtrudeNormals;
+use self::floor_areas::FuncFloorAreas;
 use self::import_obj_join::FuncImportObjJoin;
 use self::import_obj_mesh::FuncImportObjMesh;
 use self::join_group::FuncJoinGroup;
 use self::join_meshes::FuncJoinMeshes;
 use self::laplacian_smoothing::FuncLaplacianSmoothing;
+use self::library_mesh::FuncLibraryMesh;
+use self::linear_array::FuncLinearArray;
 use self::loop_subdivision::FuncLoopSubdivision;
+use self::mirror::FuncMirror;
 use self::revert_mesh_faces::FuncRevertMeshFaces;
 use self::snap_to_ground::FuncSnapToGround;
 use self::synchronize_mesh_faces::FuncSynchronizeMeshFaces;
+use self::thicken::FuncThicken;
 use self::transform::FuncTransform;
 use self::voxel_boolean_difference::FuncBooleanDifference;
 use self::voxel_boolean_intersection::FuncBooleanIntersection;
+use self::voxel_boolean_meshes::FuncBooleanMeshes;
 use self::voxel_boolean_union::FuncBooleanUnion;
 use self::voxel_interpolated_union::FuncInterpolatedUnion;
 use self::voxel_metaballs::FuncVoxelMetaballs;
@@ -30,25 +42,40 @@ use self::voxel_transform::FuncVoxelTransform;
 use self::voxelize::FuncVoxelize;
 use self::weld::FuncWeld;
 
+pub use self::create_polygon::format_outline;
+pub use self::func_aliases::{upgrade_stmts, FuncAlias};
+
 mod align;
+mod analyze_sun_exposure;
+mod catmull_clark_subdivision;
 mod create_box;
 mod create_plane;
+mod create_polygon;
 mod create_uv_sphere;
 mod disjoint_mesh;
 mod extract;
 mod extract_largest;
+mod extrude;
+mod extrude_normals;
+mod floor_areas;
+mod func_aliases;
 mod import_obj_join;
 mod import_obj_mesh;
 mod join_group;
 mod join_meshes;
 mod laplacian_smoothing;
+mod library_mesh;
+mod linear_array;
 mod loop_subdivision;
+mod mirror;
 mod revert_mesh_faces;
 mod snap_to_ground;
 mod synchronize_mesh_faces;
+mod thicken;
 mod transform;
 mod voxel_boolean_difference;
 mod voxel_boolean_intersection;
+mod voxel_boolean_meshes;
 mod voxel_boolean_union;
 mod voxel_interpolated_union;
 mod voxel_metaballs;
@@ -66,21 +93,38 @@ mod weld;
 pub const FUNC_ID_CREATE_PLANE: FuncIdent = FuncIdent(0);
 pub const FUNC_ID_CREATE_BOX: FuncIdent = FuncIdent(1);
 pub const FUNC_ID_CREATE_UV_SPHERE: FuncIdent = FuncIdent(2);
+pub const FUNC_ID_CREATE_POLYGON: FuncIdent = FuncIdent(3);
+
+/// Index of the "Outline" param of the Create Polygon func.
+pub const CREATE_POLYGON_PARAM_INDEX_OUTLINE: usize = 0;
 
 // Import/Export funcs: 2xxx
 pub const FUNC_ID_IMPORT_OBJ_MESH: FuncIdent = FuncIdent(2000);
 pub const FUNC_ID_EXTRACT: FuncIdent = FuncIdent(2001);
 pub const FUNC_ID_EXTRACT_LARGEST: FuncIdent = FuncIdent(2002);
 pub const FUNC_ID_IMPORT_OBJ_JOIN: FuncIdent = FuncIdent(2003);
+pub const FUNC_ID_LIBRARY_MESH: FuncIdent = FuncIdent(2004);
+
+/// Index of the "Unit scale" param shared by the OBJ import funcs.
+pub const IMPORT_OBJ_PARAM_INDEX_UNIT_SCALE: usize = 4;
+
+/// Index of the "Asset" param of the Library Mesh func.
+pub const LIBRARY_MESH_PARAM_INDEX_ASSET: usize = 0;
+
+/// Index of the "Unit scale" param of the Library Mesh func.
+pub const LIBRARY_MESH_PARAM_INDEX_UNIT_SCALE: usize = 1;
 
 // Manipulation funcs: 4xxx
 pub const FUNC_ID_TRANSFORM: FuncIdent = FuncIdent(4000);
 pub const FUNC_ID_ALIGN: FuncIdent = FuncIdent(4001);
 pub const FUNC_ID_SNAP_TO_GROUND: FuncIdent = FuncIdent(4002);
+pub const FUNC_ID_MIRROR: FuncIdent = FuncIdent(4003);
+pub const FUNC_ID_LINEAR_ARRAY: FuncIdent = FuncIdent(4004);
 
 // Smoothing funcs: 6xxx
 pub const FUNC_ID_LAPLACIAN_SMOOTHING: FuncIdent = FuncIdent(6000);
 pub const FUNC_ID_LOOP_SUBDIVISION: FuncIdent = FuncIdent(6001);
+pub const FUNC_ID_CATMULL_CLARK_SUBDIVISION: FuncIdent = FuncIdent(6002);
 
 // Voxel-based funcs: 8xxx
 pub const FUNC_ID_VOXELIZE: FuncIdent = FuncIdent(8000);
@@ -90,6 +134,7 @@ pub const FUNC_ID_BOOLEAN_DIFFERENCE: FuncIdent = FuncIdent(8003);
 pub const FUNC_ID_VOXEL_NOISE: FuncIdent = FuncIdent(8004);
 pub const FUNC_ID_VOXEL_TRANSFORM: FuncIdent = FuncIdent(8005);
 pub const FUNC_ID_VOXEL_METABALLS: FuncIdent = FuncIdent(8006);
+pub const FUNC_ID_BOOLEAN_MESHES: FuncIdent = FuncIdent(8007);
 
 // Hybridization funcs: 10xxx
 pub const FUNC_ID_INTERPOLATED_UNION: FuncIdent = FuncIdent(10000);
@@ -101,6 +146,30 @@ pub const FUNC_ID_JOIN_GROUP: FuncIdent = FuncIdent(12002);
 pub const FUNC_ID_WELD: FuncIdent = FuncIdent(12003);
 pub const FUNC_ID_REVERT_MESH_FACES: FuncIdent = FuncIdent(12004);
 pub const FUNC_ID_SYNCHRONIZE_MESH_FACES: FuncIdent = FuncIdent(12005);
+pub const FUNC_ID_EXTRUDE: FuncIdent = FuncIdent(12006);
+pub const FUNC_ID_EXTRUDE_NORMALS: FuncIdent = FuncIdent(12007);
+pub const FUNC_ID_THICKEN: FuncIdent = FuncIdent(12008);
+
+/// Index of the "Mesh" param of the Extrude func.
+pub const EXTRUDE_PARAM_INDEX_MESH: usize = 0;
+
+/// Index of the "Height" param of the Extrude func.
+pub const EXTRUDE_PARAM_INDEX_HEIGHT: usize = 1;
+
+// Analysis funcs: 14xxx
+pub const FUNC_ID_ANALYZE_SUN_EXPOSURE: FuncIdent = FuncIdent(14000);
+pub const FUNC_ID_FLOOR_AREAS: FuncIdent = FuncIdent(14001);
+
+/// Options of the params choosing how the normals of the resulting mesh are
+/// computed. The value of such a param is the index of the chosen option.
+const NORMAL_STRATEGY_CHOICES: &[&str] = &["Sharp", "Smooth"];
+
+fn normal_strategy_from_choice(choice_index: u32) -> NormalStrategy {
+    match choice_index {
+        0 => NormalStrategy::Sharp,
+        _ => NormalStrategy::Smooth,
+    }
+}
 
 /// Returns the global set of function definitions available to the
 /// editor.
@@ -115,6 +184,7 @@ pub fn create_function_table() -> BTreeMap<FuncIdent, Box<dyn Func>> {
     funcs.insert(FUNC_ID_CREATE_PLANE, Box::new(FuncCreatePlane));
     funcs.insert(FUNC_ID_CREATE_BOX, Box::new(FuncCreateBox));
     funcs.insert(FUNC_ID_CREATE_UV_SPHERE, Box::new(FuncCreateUvSphere));
+    funcs.insert(FUNC_ID_CREATE_POLYGON, Box::new(FuncCreatePolygon));
 
     // Import/Export funcs
     funcs.insert(
@@ -131,11 +201,14 @@ pub fn create_function_table() -> BTreeMap<FuncIdent, Box<dyn Func>> {
             EndlessCache::default(),
         ))),
     );
+    funcs.insert(FUNC_ID_LIBRARY_MESH, Box::new(FuncLibraryMesh));
 
     // Manipulation funcs
     funcs.insert(FUNC_ID_TRANSFORM, Box::new(FuncTransform));
     funcs.insert(FUNC_ID_ALIGN, Box::new(FuncAlign));
     funcs.insert(FUNC_ID_SNAP_TO_GROUND, Box::new(FuncSnapToGround));
+    funcs.insert(FUNC_ID_MIRROR, Box::new(FuncMirror));
+    funcs.insert(FUNC_ID_LINEAR_ARRAY, Box::new(FuncLinearArray));
 
     // Smoothing funcs
     funcs.insert(
@@ -143,6 +216,10 @@ pub fn create_function_table() -> BTreeMap<FuncIdent, Box<dyn Func>> {
         Box::new(FuncLaplacianSmoothing),
     );
     funcs.insert(FUNC_ID_LOOP_SUBDIVISION, Box::new(FuncLoopSubdivision));
+    funcs.insert(
+        FUNC_ID_CATMULL_CLARK_SUBDIVISION,
+        Box::new(FuncCatmullClarkSubdivision),
+    );
 
     // Voxel-based funcs
     funcs.insert(FUNC_ID_VOXELIZE, Box::new(FuncVoxelize));
@@ -155,6 +232,7 @@ pub fn create_function_table() -> BTreeMap<FuncIdent, Box<dyn Func>> {
     funcs.insert(FUNC_ID_VOXEL_NOISE, Box::new(FuncVoxelNoise));
     funcs.insert(FUNC_ID_VOXEL_TRANSFORM, Box::new(FuncVoxelTransform));
     funcs.insert(FUNC_ID_VOXEL_METABALLS, Box::new(FuncVoxelMetaballs));
+    funcs.insert(FUNC_ID_BOOLEAN_MESHES, Box::new(FuncBooleanMeshes));
 
     // Hybridization funcs
     funcs.insert(FUNC_ID_INTERPOLATED_UNION, Box::new(FuncInterpolatedUnion));
@@ -169,6 +247,29 @@ pub fn create_function_table() -> BTreeMap<FuncIdent, Box<dyn Func>> {
         FUNC_ID_SYNCHRONIZE_MESH_FACES,
         Box::new(FuncSynchronizeMeshFaces),
     );
+    funcs.insert(FUNC_ID_EXTRUDE, Box::new(FuncExtrude));
+    funcs.insert(FUNC_ID_EXTRUDE_NORMALS, Box::new(FuncExtrudeNormals));
+    funcs.insert(FUNC_ID_THICKEN, Box::new(FuncThicken));
+
+    // Analysis funcs
+    funcs.insert(
+        FUNC_ID_ANALYZE_SUN_EXPOSURE,
+        Box::new(FuncAnalyzeSunExposure),
+    );
+    funcs.insert(FUNC_ID_FLOOR_AREAS, Box::new(FuncFloorAreas));
 
     funcs
 }
+
+/// Returns the retired operations, which are replaced by other operations
+/// when opening projects, so that projects using them still open.
+///
+/// IMPORTANT: Never remove an alias and never reuse the identifier of a
+/// retired operation for a new function. When retiring a function, remove it
+/// from the function table and add an alias to its replacement here, e.g.
+/// `FuncAlias::new(FUNC_ID_LAPLACIAN_SMOOTHING)`, or
+/// `FuncAlias::with_migration` if the parameters of the replacement differ.
+pub fn create_func_alias_table() -> BTreeMap<FuncIdent, FuncAlias> {
+    // No operations have been retired yet
+    BTreeMap::new()
+}
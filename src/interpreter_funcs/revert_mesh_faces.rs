@@ -3,7 +3,7 @@ use std::sync::Arc;
 use crate::analytics;
 use crate::interpreter::{
     BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, Value,
+    ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::tools;
 
@@ -45,6 +45,7 @@ impl Func for FuncRevertMeshFaces {
                 description: "Input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -54,6 +55,7 @@ impl Func for FuncRevertMeshFaces {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
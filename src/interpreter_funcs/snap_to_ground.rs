@@ -5,7 +5,7 @@ use nalgebra::{Matrix4, Point3, Vector3};
 use crate::analytics;
 use crate::interpreter::{
     BooleanParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
-    ParamRefinement, Ty, Value,
+    ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::Mesh;
 
@@ -43,6 +43,7 @@ impl Func for FuncSnapToGround {
                 description: "Input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Move to origin",
@@ -52,6 +53,7 @@ impl Func for FuncSnapToGround {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Snap to ground",
@@ -61,6 +63,7 @@ impl Func for FuncSnapToGround {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -70,6 +73,7 @@ impl Func for FuncSnapToGround {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
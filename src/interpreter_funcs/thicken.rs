@@ -0,0 +1,118 @@
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::analytics;
+use crate::interpreter::{
+    BooleanParamRefinement, Dimension, FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
+};
+use crate::mesh::tools;
+
+#[derive(Debug, PartialEq)]
+pub enum FuncThickenError {
+    ThicknessZeroOrLess,
+}
+
+impl fmt::Display for FuncThickenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FuncThickenError::ThicknessZeroOrLess => write!(f, "Thickness is zero or less"),
+        }
+    }
+}
+
+impl error::Error for FuncThickenError {}
+
+pub struct FuncThicken;
+
+impl Func for FuncThicken {
+    fn info(&self) -> &FuncInfo {
+        &FuncInfo {
+            name: "Thicken",
+            description: "THICKEN MESH INTO A SOLID SHELL\n\
+                          \n\
+                          Offsets the mesh along its normals by half of the thickness \
+                          to both sides and connects the open borders of both offsets \
+                          by side walls. A thin open surface, e.g. an imported terrain or \
+                          facade, becomes a solid plate suitable for 3D printing. \
+                          A closed mesh becomes a hollow solid with walls of the thickness.\n\
+                          \n\
+                          The input mesh should have consistently oriented faces, \
+                          otherwise the offsets go in inconsistent directions.\n\
+                          \n\
+                          The input mesh will be marked used and thus invisible in the viewport. \
+                          It can still be used in subsequent operations.\n\
+                          \n\
+                          The resulting mesh geometry will be named 'Shell'.",
+            return_value_name: "Shell",
+        }
+    }
+
+    fn flags(&self) -> FuncFlags {
+        FuncFlags::PURE
+    }
+
+    fn param_info(&self) -> &[ParamInfo] {
+        &[
+            ParamInfo {
+                name: "Mesh",
+                description: "Input mesh.",
+                refinement: ParamRefinement::Mesh,
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Thickness",
+                description: "Thickness of the shell in model units.",
+                refinement: ParamRefinement::Float(FloatParamRefinement {
+                    default_value: Some(0.2),
+                    min_value: Some(0.001),
+                    max_value: None,
+                    dimension: Dimension::Length,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+            ParamInfo {
+                name: "Mesh Analysis",
+                description: "Reports detailed analytic information on the created mesh.\n\
+                              The analysis may be slow, turn it on only when needed.",
+                refinement: ParamRefinement::Boolean(BooleanParamRefinement {
+                    default_value: false,
+                }),
+                optional: false,
+                widget: ParamWidget::Default,
+            },
+        ]
+    }
+
+    fn return_ty(&self) -> Ty {
+        Ty::Mesh
+    }
+
+    fn call(
+        &mut self,
+        args: &[Value],
+        log: &mut dyn FnMut(LogMessage),
+    ) -> Result<Value, FuncError> {
+        let mesh = args[0].unwrap_mesh();
+        let thickness = args[1].unwrap_float();
+        let analyze_mesh = args[2].unwrap_boolean();
+
+        if thickness <= 0.0 {
+            let error = FuncError::new(FuncThickenError::ThicknessZeroOrLess);
+            log(LogMessage::error(format!("Error: {}", error)));
+            return Err(error);
+        }
+
+        let value = tools::thicken(&mesh, thickness);
+
+        if analyze_mesh {
+            analytics::report_bounding_box_analysis(&value, log);
+            analytics::report_mesh_analysis(&value, log);
+        }
+
+        Ok(Value::Mesh(Arc::new(value)))
+    }
+}
@@ -4,8 +4,8 @@ use nalgebra::{Matrix4, Rotation, Vector3};
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
-    LogMessage, ParamInfo, ParamRefinement, Ty, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::Mesh;
 
@@ -39,6 +39,7 @@ impl Func for FuncTransform {
                 description: "Input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Move",
@@ -49,11 +50,13 @@ impl Func for FuncTransform {
                     default_value_x: Some(0.0),
                     default_value_y: Some(0.0),
                     default_value_z: Some(0.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
-                name: "Rotate (deg)",
+                name: "Rotate",
                 description: "Rotation around the X, Y and Z axis in degrees.",
                 refinement: ParamRefinement::Float3(Float3ParamRefinement {
                     min_value: None,
@@ -61,8 +64,10 @@ impl Func for FuncTransform {
                     default_value_x: Some(0.0),
                     default_value_y: Some(0.0),
                     default_value_z: Some(0.0),
+                    dimension: Dimension::Angle,
                 }),
                 optional: false,
+                widget: ParamWidget::Vector3,
             },
             ParamInfo {
                 name: "Scale",
@@ -73,8 +78,10 @@ impl Func for FuncTransform {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Transform around object center",
@@ -84,6 +91,7 @@ impl Func for FuncTransform {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -93,6 +101,7 @@ impl Func for FuncTransform {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
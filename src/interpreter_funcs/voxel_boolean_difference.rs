@@ -8,8 +8,8 @@ use nalgebra::Vector3;
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
-    LogMessage, ParamInfo, ParamRefinement, Ty, UintParamRefinement, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
 use crate::mesh::voxel_cloud::{self, FalloffFunction, ScalarField};
 
@@ -83,12 +83,14 @@ impl Func for FuncBooleanDifference {
                 description: "First input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh 2",
                 description: "Second input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Voxel Size",
@@ -103,8 +105,10 @@ impl Func for FuncBooleanDifference {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Grow",
@@ -121,6 +125,7 @@ impl Func for FuncBooleanDifference {
                     max_value: None,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Fill Closed Volumes",
@@ -133,6 +138,7 @@ impl Func for FuncBooleanDifference {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Marching Cubes",
@@ -143,6 +149,7 @@ impl Func for FuncBooleanDifference {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Prevent Unsafe Settings",
@@ -151,6 +158,7 @@ impl Func for FuncBooleanDifference {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -160,10 +168,15 @@ impl Func for FuncBooleanDifference {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        Some(2)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
@@ -8,8 +8,8 @@ use nalgebra::Vector3;
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
-    LogMessage, ParamInfo, ParamRefinement, Ty, UintParamRefinement, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
 use crate::mesh::voxel_cloud::{self, FalloffFunction, ScalarField};
 
@@ -82,12 +82,14 @@ impl Func for FuncBooleanIntersection {
                 description: "First input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh 2",
                 description: "Second input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Voxel Size",
@@ -102,8 +104,10 @@ impl Func for FuncBooleanIntersection {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Grow",
@@ -120,6 +124,7 @@ impl Func for FuncBooleanIntersection {
                     max_value: None,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Fill Closed Volumes",
@@ -132,6 +137,7 @@ impl Func for FuncBooleanIntersection {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Marching Cubes",
@@ -142,6 +148,7 @@ impl Func for FuncBooleanIntersection {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Prevent Unsafe Settings",
@@ -150,6 +157,7 @@ impl Func for FuncBooleanIntersection {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -159,10 +167,15 @@ impl Func for FuncBooleanIntersection {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        Some(2)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
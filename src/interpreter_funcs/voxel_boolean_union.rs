@@ -9,8 +9,8 @@ use nalgebra::Vector3;
 use crate::analytics;
 use crate::bounding_box::BoundingBox;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
-    LogMessage, ParamInfo, ParamRefinement, Ty, UintParamRefinement, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
 use crate::mesh::voxel_cloud::{self, FalloffFunction, ScalarField};
 
@@ -86,12 +86,14 @@ impl Func for FuncBooleanUnion {
                 description: "First input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh 2",
                 description: "Second input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Voxel Size",
@@ -106,8 +108,10 @@ impl Func for FuncBooleanUnion {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Grow",
@@ -124,6 +128,7 @@ impl Func for FuncBooleanUnion {
                     max_value: None,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Fill Closed Volumes",
@@ -136,6 +141,7 @@ impl Func for FuncBooleanUnion {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Marching Cubes",
@@ -146,6 +152,7 @@ impl Func for FuncBooleanUnion {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Prevent Unsafe Settings",
@@ -154,6 +161,7 @@ impl Func for FuncBooleanUnion {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -163,10 +171,15 @@ impl Func for FuncBooleanUnion {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        Some(2)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
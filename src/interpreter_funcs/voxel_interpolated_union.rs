@@ -9,8 +9,8 @@ use nalgebra::Vector3;
 use crate::analytics;
 use crate::bounding_box::BoundingBox;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, FloatParamRefinement, Func, FuncError,
-    FuncFlags, FuncInfo, LogMessage, ParamInfo, ParamRefinement, Ty, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, FloatParamRefinement, Func, FuncDocs,
+    FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::voxel_cloud::{self, FalloffFunction, ScalarField};
 
@@ -75,6 +75,20 @@ impl Func for FuncInterpolatedUnion {
         }
     }
 
+    fn docs(&self) -> Option<&FuncDocs> {
+        Some(&FuncDocs {
+            details: "Voxel Morph blends two shapes into an intermediate shape, e.g. a \
+            cube into a sphere or an existing building into a proposed one. The meshes \
+            are not moved, so place them over each other first, e.g. with Align, \
+            otherwise the intermediate shapes split into two shrinking volumes.\n\
+            \n\
+            Run a design sweep of the factor to lay out a whole series of intermediate \
+            shapes next to each other. Factors outside of the range from 0 to 1 \
+            extrapolate the shapes, exaggerating the differences between them.",
+            image: None,
+        })
+    }
+
     fn flags(&self) -> FuncFlags {
         FuncFlags::PURE
     }
@@ -86,12 +100,14 @@ impl Func for FuncInterpolatedUnion {
                 description: "First input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh 2",
                 description: "Second input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Voxel Size",
@@ -106,8 +122,10 @@ impl Func for FuncInterpolatedUnion {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Fill Closed Volumes",
@@ -120,6 +138,7 @@ impl Func for FuncInterpolatedUnion {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Factor",
@@ -133,8 +152,10 @@ impl Func for FuncInterpolatedUnion {
                     default_value: Some(0.5),
                     min_value: Some(0.0),
                     max_value: Some(1.0),
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Marching Cubes",
@@ -145,6 +166,7 @@ impl Func for FuncInterpolatedUnion {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Prevent Unsafe Settings",
@@ -153,6 +175,7 @@ impl Func for FuncInterpolatedUnion {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -162,10 +185,15 @@ impl Func for FuncInterpolatedUnion {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        Some(2)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
@@ -9,8 +9,9 @@ use nalgebra::Vector3;
 use crate::analytics;
 use crate::bounding_box::BoundingBox;
 use crate::interpreter::{
-    BooleanParamRefinement, Float2ParamRefinement, Float3ParamRefinement, FloatParamRefinement,
-    Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo, ParamRefinement, Ty, Value,
+    BooleanParamRefinement, Dimension, Float2ParamRefinement, Float3ParamRefinement,
+    FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
+    ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::voxel_cloud::{self, FalloffFunction, ScalarField};
 
@@ -88,12 +89,14 @@ impl Func for FuncVoxelMetaballs {
                 description: "First input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh 2",
                 description: "Second input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Voxel Size",
@@ -108,8 +111,10 @@ impl Func for FuncVoxelMetaballs {
                     default_value_x: Some(0.1),
                     default_value_y: Some(0.1),
                     default_value_z: Some(0.1),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Fill Closed Volumes",
@@ -122,6 +127,7 @@ impl Func for FuncVoxelMetaballs {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Distance Multiplier",
@@ -135,8 +141,10 @@ impl Func for FuncVoxelMetaballs {
                     default_value: Some(0.5),
                     min_value: None,
                     max_value: None,
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Volume range",
@@ -146,8 +154,10 @@ impl Func for FuncVoxelMetaballs {
                     max_value: Some(100.0),
                     default_value_x: Some(0.5),
                     default_value_y: Some(2.0),
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Marching Cubes",
@@ -158,6 +168,7 @@ impl Func for FuncVoxelMetaballs {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Prevent Unsafe Settings",
@@ -166,6 +177,7 @@ impl Func for FuncVoxelMetaballs {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -175,10 +187,15 @@ impl Func for FuncVoxelMetaballs {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        Some(2)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
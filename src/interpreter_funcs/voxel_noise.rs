@@ -8,8 +8,9 @@ use nalgebra::{Point3, Vector3};
 use crate::analytics;
 use crate::bounding_box::BoundingBox;
 use crate::interpreter::{
-    BooleanParamRefinement, Float2ParamRefinement, Float3ParamRefinement, FloatParamRefinement,
-    Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo, ParamRefinement, Ty, Value,
+    BooleanParamRefinement, Dimension, Float2ParamRefinement, Float3ParamRefinement,
+    FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage, ParamInfo,
+    ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::voxel_cloud::{self, ScalarField};
 
@@ -69,8 +70,10 @@ impl Func for FuncVoxelNoise {
                     default_value_x: Some(-10.0),
                     default_value_y: Some(-10.0),
                     default_value_z: Some(0.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Block End",
@@ -81,8 +84,10 @@ impl Func for FuncVoxelNoise {
                     default_value_x: Some(10.0),
                     default_value_y: Some(10.0),
                     default_value_z: Some(20.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Voxel Size",
@@ -97,8 +102,10 @@ impl Func for FuncVoxelNoise {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Noise Scale",
@@ -107,8 +114,10 @@ impl Func for FuncVoxelNoise {
                     default_value: Some(1.0),
                     min_value: Some(f32::MIN_POSITIVE),
                     max_value: None,
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Time offset",
@@ -117,8 +126,10 @@ impl Func for FuncVoxelNoise {
                     default_value: Some(1.0),
                     min_value: None,
                     max_value: None,
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Volume range",
@@ -128,8 +139,10 @@ impl Func for FuncVoxelNoise {
                     max_value: Some(1.0),
                     default_value_x: Some(-0.25),
                     default_value_y: Some(0.25),
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Marching Cubes",
@@ -140,6 +153,7 @@ impl Func for FuncVoxelNoise {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Prevent Unsafe Settings",
@@ -148,6 +162,7 @@ impl Func for FuncVoxelNoise {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -157,10 +172,15 @@ impl Func for FuncVoxelNoise {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        Some(2)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
@@ -9,8 +9,8 @@ use nalgebra::{Rotation, Vector3};
 use crate::analytics;
 use crate::convert::cast_i32;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
-    LogMessage, ParamInfo, ParamRefinement, Ty, UintParamRefinement, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
 use crate::mesh::voxel_cloud::{self, FalloffFunction, ScalarField};
 
@@ -84,6 +84,7 @@ impl Func for FuncVoxelTransform {
                 description: "Input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Voxel Dimensions",
@@ -98,8 +99,10 @@ impl Func for FuncVoxelTransform {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Grow",
@@ -116,6 +119,7 @@ impl Func for FuncVoxelTransform {
                     max_value: None,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Fill closed volumes",
@@ -128,6 +132,7 @@ impl Func for FuncVoxelTransform {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Move",
@@ -138,11 +143,13 @@ impl Func for FuncVoxelTransform {
                     default_value_y: Some(0.0),
                     default_value_x: Some(0.0),
                     default_value_z: Some(0.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
-                name: "Rotate (deg)",
+                name: "Rotate",
                 description: "Rotation around the X, Y and Z axis in degrees.",
                 refinement: ParamRefinement::Float3(Float3ParamRefinement {
                     min_value: None,
@@ -150,8 +157,10 @@ impl Func for FuncVoxelTransform {
                     default_value_x: Some(0.0),
                     default_value_y: Some(0.0),
                     default_value_z: Some(0.0),
+                    dimension: Dimension::Angle,
                 }),
                 optional: false,
+                widget: ParamWidget::Vector3,
             },
             ParamInfo {
                 name: "Scale",
@@ -162,8 +171,10 @@ impl Func for FuncVoxelTransform {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Dimensionless,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Marching Cubes",
@@ -174,6 +185,7 @@ impl Func for FuncVoxelTransform {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Prevent Unsafe Settings",
@@ -182,6 +194,7 @@ impl Func for FuncVoxelTransform {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Bounding Box Analysis",
@@ -190,6 +203,7 @@ impl Func for FuncVoxelTransform {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -199,10 +213,15 @@ impl Func for FuncVoxelTransform {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        Some(1)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
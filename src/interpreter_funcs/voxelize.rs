@@ -8,8 +8,8 @@ use nalgebra::Vector3;
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, Float3ParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
-    LogMessage, ParamInfo, ParamRefinement, Ty, UintParamRefinement, Value,
+    BooleanParamRefinement, Dimension, Float3ParamRefinement, Func, FuncDocs, FuncError, FuncFlags,
+    FuncInfo, LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, UintParamRefinement, Value,
 };
 use crate::mesh::voxel_cloud::{self, FalloffFunction, ScalarField};
 
@@ -68,6 +68,26 @@ impl Func for FuncVoxelize {
         }
     }
 
+    fn docs(&self) -> Option<&FuncDocs> {
+        Some(&FuncDocs {
+            details: "Voxelizing is the first step of most voxel workflows. It turns \
+            any mesh, including meshes with holes, self-intersections or many separate \
+            parts, into a single clean and welded volume, which the other voxel \
+            operations, such as booleans or morphs, expect.\n\
+            \n\
+            Start with a voxel size of about a hundredth of the size of the geometry and \
+            make it smaller only once the result has the desired shape, because halving \
+            the voxel size makes the computation about eight times slower. For thin parts \
+            of the geometry, such as walls, use a voxel size smaller than their thickness \
+            or grow the volume, otherwise the parts get holes.\n\
+            \n\
+            The blocky result without marching cubes is well suited for pixelated, \
+            Minecraft-like designs. Smooth organic shapes are made with marching cubes, \
+            followed by Relax.",
+            image: Some(include_bytes!("../../resources/docs/voxelize.png")),
+        })
+    }
+
     fn flags(&self) -> FuncFlags {
         FuncFlags::PURE
     }
@@ -79,6 +99,7 @@ impl Func for FuncVoxelize {
                 description: "Input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Voxel Size",
@@ -93,8 +114,10 @@ impl Func for FuncVoxelize {
                     default_value_x: Some(1.0),
                     default_value_y: Some(1.0),
                     default_value_z: Some(1.0),
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Grow",
@@ -111,6 +134,7 @@ impl Func for FuncVoxelize {
                     max_value: None,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Fill Closed Volumes",
@@ -123,6 +147,7 @@ impl Func for FuncVoxelize {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Marching Cubes",
@@ -133,6 +158,7 @@ impl Func for FuncVoxelize {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Prevent Unsafe Settings",
@@ -141,6 +167,7 @@ impl Func for FuncVoxelize {
                     default_value: true,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -150,10 +177,15 @@ impl Func for FuncVoxelize {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
 
+    fn voxel_size_param_index(&self) -> Option<usize> {
+        Some(1)
+    }
+
     fn return_ty(&self) -> Ty {
         Ty::Mesh
     }
@@ -4,8 +4,8 @@ use std::sync::Arc;
 
 use crate::analytics;
 use crate::interpreter::{
-    BooleanParamRefinement, FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo, LogMessage,
-    ParamInfo, ParamRefinement, Ty, Value,
+    BooleanParamRefinement, Dimension, FloatParamRefinement, Func, FuncError, FuncFlags, FuncInfo,
+    LogMessage, ParamInfo, ParamRefinement, ParamWidget, Ty, Value,
 };
 use crate::mesh::tools;
 
@@ -62,6 +62,7 @@ impl Func for FuncWeld {
                 description: "Input mesh.",
                 refinement: ParamRefinement::Mesh,
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Tolerance",
@@ -73,8 +74,10 @@ impl Func for FuncWeld {
                     default_value: Some(0.001),
                     min_value: Some(0.0),
                     max_value: None,
+                    dimension: Dimension::Length,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
             ParamInfo {
                 name: "Mesh Analysis",
@@ -84,6 +87,7 @@ impl Func for FuncWeld {
                     default_value: false,
                 }),
                 optional: false,
+                widget: ParamWidget::Default,
             },
         ]
     }
@@ -102,6 +106,19 @@ impl Func for FuncWeld {
         let analyze_mesh = args[2].unwrap_boolean();
 
         if let Some(value) = tools::weld(&mesh, tolerance) {
+            let collapsed_face_count = mesh.faces().len().saturating_sub(value.faces().len());
+            if collapsed_face_count > 0 {
+                log(LogMessage::warn(format!(
+                    "Weld collapsed {} degenerate {}",
+                    collapsed_face_count,
+                    if collapsed_face_count == 1 {
+                        "face"
+                    } else {
+                        "faces"
+                    },
+                )));
+            }
+
             if analyze_mesh {
                 analytics::report_bounding_box_analysis(&value, log);
                 analytics::report_mesh_analysis(&value, log);
@@ -1,11 +1,51 @@
+use std::cell::RefCell;
 use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel as channel;
 
-use crate::interpreter::ast::{Prog, Stmt};
-use crate::interpreter::{InterpretOutcome, Interpreter};
+use crate::checkpoint::{self, CheckpointWriter};
+use crate::interpreter::ast::{CallExpr, Prog, Stmt};
+use crate::interpreter::{InterpretError, InterpretOutcome, Interpreter, Value, VarIdent};
 use crate::interpreter_funcs;
+use crate::mesh::Mesh;
+
+const DEFAULT_STMT_TIMEOUT_SECS: u32 = 120;
+
+/// Operation timeout settings kept in user preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TimeoutPreferences {
+    pub enabled: bool,
+    /// How long a single statement may run before the interpreter evaluating
+    /// it is abandoned.
+    pub timeout_secs: u32,
+}
+
+impl Default for TimeoutPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_secs: DEFAULT_STMT_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl TimeoutPreferences {
+    /// Returns the timeout for `InterpreterServer::set_stmt_timeout`, or
+    /// `None` if statements may run forever.
+    pub fn stmt_timeout(&self) -> Option<Duration> {
+        if self.enabled {
+            Some(Duration::from_secs(u64::from(self.timeout_secs)))
+        } else {
+            None
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct RequestId(u64);
@@ -28,7 +68,6 @@ pub enum PollResponseError {
 /// the interpreter.
 #[derive(Debug)]
 pub enum InterpreterRequest {
-    #[allow(dead_code)]
     SetProg(Prog),
     #[allow(dead_code)]
     ClearProg,
@@ -38,6 +77,14 @@ pub enum InterpreterRequest {
     Interpret,
     #[allow(dead_code)]
     InterpretUpUntil(usize),
+    /// Switches draft mode of the interpreter on or off.
+    SetDraft(bool),
+    /// Evaluates a program likely to become the current program ahead of
+    /// time. See `Interpreter::speculate`.
+    Speculate(Prog),
+    /// Takes over values saved from an earlier interpreter. See
+    /// `Interpreter::restore_values`.
+    RestoreValues(Vec<(VarIdent, CallExpr, Value)>),
 }
 
 /// An interpreter response.
@@ -46,8 +93,21 @@ pub enum InterpreterResponse {
     /// Interpreter completed program edit request.
     CompletedEditProg,
 
+    /// Interpreter generated an element of a mesh array while working on
+    /// interpret request. The request is still running and the element
+    /// should only be displayed until it completes.
+    StreamedMesh { stmt_index: usize, mesh: Arc<Mesh> },
+
     /// Interpreter completed interpret request.
     CompletedInterpret(InterpretOutcome),
+
+    /// Interpreter completed speculate request, successfully or not.
+    CompletedSpeculate,
+
+    /// Interpreter didn't complete interpret request, because a statement
+    /// ran longer than the timeout. The interpreter was replaced with a new
+    /// one with an empty program, which has to be set again.
+    AbandonedInterpret(InterpretError),
 }
 
 enum Request {
@@ -63,32 +123,63 @@ struct Response {
     data: InterpreterResponse,
 }
 
-/// An asynchronous server for an interpreter instance running in a
-/// separate thread.
-///
-/// Allows for sending requests to the interpreter and waiting for
-/// related responses.
-pub struct InterpreterServer {
-    next_request_id: u64,
+/// The statement currently being evaluated by the interpreter thread.
+#[derive(Debug, Clone, Copy)]
+struct RunningStmt {
+    request_id: RequestId,
+    stmt_index: usize,
+    started_at: Instant,
+}
+
+/// An interpreter thread and the channels to talk to it.
+struct Worker {
     thread: Option<thread::JoinHandle<()>>,
     request_sender: channel::Sender<Request>,
     response_receiver: channel::Receiver<Response>,
+    running_stmt: Arc<Mutex<Option<RunningStmt>>>,
+    cancel_flag: Arc<AtomicBool>,
+    speculation_cancel_flag: Arc<AtomicBool>,
 }
 
-impl InterpreterServer {
-    pub fn new() -> Self {
+impl Worker {
+    fn spawn() -> Self {
         let (request_sender, request_receiver) = channel::unbounded();
         let (response_sender, response_receiver) = channel::unbounded();
+        let running_stmt = Arc::new(Mutex::new(None));
+        let thread_running_stmt = Arc::clone(&running_stmt);
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let thread_cancel_flag = Arc::clone(&cancel_flag);
+        let speculation_cancel_flag = Arc::new(AtomicBool::new(false));
+        let thread_speculation_cancel_flag = Arc::clone(&speculation_cancel_flag);
 
         let thread = thread::spawn(move || {
             log::info!("Interpreter server starting up");
 
             let mut interpreter = Interpreter::new(interpreter_funcs::create_function_table());
+            interpreter.set_cancel_flag(thread_cancel_flag);
+            interpreter.set_speculation_cancel_flag(thread_speculation_cancel_flag);
+
+            // Values completed by a long interpret request are written to
+            // the checkpoint, so that they survive a crash. Values computed
+            // in draft mode are not worth it.
+            let mut draft_mode = false;
+            let checkpoint_writer: Rc<RefCell<Option<CheckpointWriter>>> =
+                Rc::new(RefCell::new(None));
+            let callback_checkpoint_writer = Rc::clone(&checkpoint_writer);
+            interpreter.set_checkpoint_callback(Box::new(move |var_ident, call, value| {
+                if let Some(writer) = callback_checkpoint_writer.borrow().as_ref() {
+                    writer.push(var_ident, call, value);
+                }
+            }));
 
             loop {
-                let request: Request = request_receiver
-                    .recv()
-                    .expect("Interpreter server failed to receive request");
+                let request: Request = match request_receiver.recv() {
+                    Ok(request) => request,
+                    Err(_) => {
+                        log::info!("Interpreter server was abandoned");
+                        break;
+                    }
+                };
 
                 let (request_id, data) = match request {
                     Request::Command { request_id, data } => (request_id, data),
@@ -97,6 +188,36 @@ impl InterpreterServer {
 
                 // FIXME: handle potential interpreter panic?
 
+                if let InterpreterRequest::Interpret | InterpreterRequest::InterpretUpUntil(_) =
+                    data
+                {
+                    let running_stmt = Arc::clone(&thread_running_stmt);
+                    interpreter.set_stmt_started_callback(Box::new(move |stmt_index| {
+                        *running_stmt.lock().unwrap_or_else(PoisonError::into_inner) =
+                            Some(RunningStmt {
+                                request_id,
+                                stmt_index,
+                                started_at: Instant::now(),
+                            });
+                    }));
+
+                    let stream_sender = response_sender.clone();
+                    interpreter.set_mesh_stream_callback(Box::new(move |stmt_index, mesh| {
+                        // A failed send is noticed when sending the final
+                        // response.
+                        let _ = stream_sender.send(Response {
+                            request_id,
+                            data: InterpreterResponse::StreamedMesh { stmt_index, mesh },
+                        });
+                    }));
+
+                    if !draft_mode {
+                        *checkpoint_writer.borrow_mut() = checkpoint::path().map(|path| {
+                            CheckpointWriter::new(path, checkpoint::CHECKPOINT_INTERVAL)
+                        });
+                    }
+                }
+
                 let response = match data {
                     InterpreterRequest::SetProg(prog) => {
                         log::info!(
@@ -148,6 +269,37 @@ impl InterpreterServer {
                             data: InterpreterResponse::CompletedEditProg,
                         }
                     }
+                    InterpreterRequest::SetDraft(draft) => {
+                        log::info!("Interpreter server received request 'SetDraft({})'", draft);
+                        interpreter.set_draft(draft);
+                        draft_mode = draft;
+                        Response {
+                            request_id,
+                            data: InterpreterResponse::CompletedEditProg,
+                        }
+                    }
+                    InterpreterRequest::Speculate(prog) => {
+                        log::info!(
+                            "Interpreter server received request 'Speculate' with {}",
+                            prog,
+                        );
+                        interpreter.speculate(prog);
+                        Response {
+                            request_id,
+                            data: InterpreterResponse::CompletedSpeculate,
+                        }
+                    }
+                    InterpreterRequest::RestoreValues(values) => {
+                        log::info!(
+                            "Interpreter server received request 'RestoreValues' with {} values",
+                            values.len(),
+                        );
+                        interpreter.restore_values(values);
+                        Response {
+                            request_id,
+                            data: InterpreterResponse::CompletedEditProg,
+                        }
+                    }
                     InterpreterRequest::Interpret => {
                         log::info!("Interpreter server received request 'Interpret'");
                         let interpret_outcome = interpreter.interpret();
@@ -169,22 +321,82 @@ impl InterpreterServer {
                     }
                 };
 
-                response_sender
-                    .send(response)
-                    .expect("Interpreter server failed to send response");
+                *thread_running_stmt
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = None;
+
+                // The request completed without crashing, so its checkpoint
+                // is not needed anymore
+                if let Some(writer) = checkpoint_writer.borrow_mut().take() {
+                    if let Err(err) = writer.finish() {
+                        log::error!("Failed to remove checkpoint: {}", err);
+                    }
+                }
+
+                // The response can't be delivered, if the server abandoned
+                // this thread while it was interpreting.
+                if response_sender.send(response).is_err() {
+                    log::info!("Interpreter server was abandoned, discarding response");
+                    break;
+                }
             }
 
             log::info!("Interpreter server shutting down");
         });
 
         Self {
-            next_request_id: 0,
             thread: Some(thread),
             request_sender,
             response_receiver,
+            running_stmt,
+            cancel_flag,
+            speculation_cancel_flag,
+        }
+    }
+
+    /// Returns the statement currently being evaluated, if any.
+    fn running_stmt(&self) -> Option<RunningStmt> {
+        *self
+            .running_stmt
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// An asynchronous server for an interpreter instance running in a
+/// separate thread.
+///
+/// Allows for sending requests to the interpreter and waiting for
+/// related responses.
+///
+/// Threads can not be killed, so if a statement runs longer than the
+/// statement timeout, the thread is abandoned to finish the evaluation on
+/// its own and a new thread takes its place.
+pub struct InterpreterServer {
+    next_request_id: u64,
+    stmt_timeout: Option<Duration>,
+    worker: Worker,
+}
+
+impl InterpreterServer {
+    pub fn new() -> Self {
+        Self {
+            next_request_id: 0,
+            stmt_timeout: None,
+            worker: Worker::spawn(),
         }
     }
 
+    pub fn stmt_timeout(&self) -> Option<Duration> {
+        self.stmt_timeout
+    }
+
+    /// Sets how long a single statement may run before the interpreter is
+    /// abandoned. `None` lets statements run forever.
+    pub fn set_stmt_timeout(&mut self, stmt_timeout: Option<Duration>) {
+        self.stmt_timeout = stmt_timeout;
+    }
+
     /// Submit a new request for the interpreter to work on.
     ///
     /// The corresponding response can be paired with based on the
@@ -193,7 +405,19 @@ impl InterpreterServer {
         let request_id = RequestId(self.next_request_id);
         self.next_request_id += 1;
 
-        self.request_sender
+        // A cancellation only applies to the interpret requests submitted
+        // before it.
+        if let InterpreterRequest::Interpret | InterpreterRequest::InterpretUpUntil(_) = request {
+            self.worker.cancel_flag.store(false, Ordering::SeqCst);
+        }
+        if let InterpreterRequest::Speculate(_) = request {
+            self.worker
+                .speculation_cancel_flag
+                .store(false, Ordering::SeqCst);
+        }
+
+        self.worker
+            .request_sender
             .send(Request::Command {
                 request_id,
                 data: request,
@@ -203,16 +427,67 @@ impl InterpreterServer {
         request_id
     }
 
+    /// Asks the interpreter to stop working on the current interpret
+    /// request. The interpreter stops before the next statement, or sooner,
+    /// if the current statement streams its results. The request then
+    /// completes with a cancellation error.
+    pub fn cancel_interpret(&self) {
+        self.worker.cancel_flag.store(true, Ordering::SeqCst);
+    }
+
+    /// Asks the interpreter to stop working on the speculate requests
+    /// submitted so far, e.g. because there is an interpret request waiting
+    /// behind them.
+    pub fn cancel_speculation(&self) {
+        self.worker
+            .speculation_cancel_flag
+            .store(true, Ordering::SeqCst);
+    }
+
     /// Poll the server for a possible response.
     ///
     /// In case of no response, [`PollResponseError::Pending`] is returned.
     ///
+    /// If the statement being interpreted exceeded the statement timeout, the
+    /// interpreter is abandoned and
+    /// [`InterpreterResponse::AbandonedInterpret`] is returned for the
+    /// interpret request. Requests submitted after the interpret request are
+    /// lost with the abandoned interpreter.
+    ///
     /// [`PollResponseError::Pending`]: enum.PollresponseError.html#variant.Pending
-    pub fn poll_response(&self) -> Result<(RequestId, InterpreterResponse), PollResponseError> {
-        if self.response_receiver.is_empty() {
+    /// [`InterpreterResponse::AbandonedInterpret`]: enum.InterpreterResponse.html#variant.AbandonedInterpret
+    pub fn poll_response(&mut self) -> Result<(RequestId, InterpreterResponse), PollResponseError> {
+        if self.worker.response_receiver.is_empty() {
+            if let (Some(stmt_timeout), Some(running_stmt)) =
+                (self.stmt_timeout, self.worker.running_stmt())
+            {
+                if running_stmt.started_at.elapsed() > stmt_timeout {
+                    log::error!(
+                        "Interpreter exceeded timeout of {:?} on statement {} of request {}, \
+                         abandoning it",
+                        stmt_timeout,
+                        running_stmt.stmt_index,
+                        running_stmt.request_id,
+                    );
+
+                    // Dropping the worker detaches its thread, which exits
+                    // once the statement finishes and it fails to respond.
+                    self.worker = Worker::spawn();
+
+                    return Ok((
+                        running_stmt.request_id,
+                        InterpreterResponse::AbandonedInterpret(InterpretError::Timeout {
+                            stmt_index: running_stmt.stmt_index,
+                            timeout: stmt_timeout,
+                        }),
+                    ));
+                }
+            }
+
             Err(PollResponseError::Pending)
         } else {
             let response = self
+                .worker
                 .response_receiver
                 .recv()
                 .expect("Interpreter client failed to receive response");
@@ -224,11 +499,12 @@ impl InterpreterServer {
 
 impl Drop for InterpreterServer {
     fn drop(&mut self) {
-        self.request_sender
+        self.worker
+            .request_sender
             .send(Request::Shutdown)
             .expect("Interpreter client failed to send shutdown request");
 
-        if let Some(thread) = self.thread.take() {
+        if let Some(thread) = self.worker.thread.take() {
             log::info!("Waiting for interpreter server to shut down");
             thread
                 .join()
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use crate::display_material::{DisplayMaterial, DisplayMaterialKind};
+use crate::interpreter::ast::VarIdent;
+
+/// Opaque identifier of a layer. Identifiers are never reused within one
+/// `Layers` collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct LayerId(u64);
+
+/// A named group of pipeline values, which are shown, colored and locked
+/// together.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Layer {
+    pub name: String,
+    pub visible: bool,
+    /// Whether the values on the layer, which don't have a display material
+    /// of their own, are drawn in the layer color.
+    pub colored: bool,
+    pub color: [f32; 3],
+    /// Whether the parameters of the operations on the layer are protected
+    /// from changes.
+    pub locked: bool,
+    /// Whether the values on the layer are labeled in the viewport.
+    pub labels: bool,
+}
+
+impl Default for Layer {
+    fn default() -> Self {
+        Self {
+            name: String::from("Layer"),
+            visible: true,
+            colored: false,
+            color: [0.8, 0.8, 0.8],
+            locked: false,
+            labels: true,
+        }
+    }
+}
+
+impl Layer {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            ..Self::default()
+        }
+    }
+}
+
+/// An ordered collection of layers and the assignment of variables to them.
+/// Variables not assigned to any layer are visible and unlocked.
+#[derive(Debug, Default)]
+pub struct Layers {
+    layers: Vec<(LayerId, Layer)>,
+    var_layers: HashMap<VarIdent, LayerId>,
+    next_id: u64,
+}
+
+impl Layers {
+    pub fn new() -> Self {
+        Self {
+            layers: Vec::new(),
+            var_layers: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Creates the collection from layers and assignments stored in a
+    /// project. Assignments to layers that don't exist are dropped.
+    pub fn from_parts(layers: Vec<(LayerId, Layer)>, var_layers: Vec<(VarIdent, LayerId)>) -> Self {
+        let next_id = layers
+            .iter()
+            .map(|(layer_id, _)| layer_id.0 + 1)
+            .max()
+            .unwrap_or(0);
+        let var_layers = var_layers
+            .into_iter()
+            .filter(|(_, layer_id)| layers.iter().any(|(id, _)| id == layer_id))
+            .collect();
+
+        Self {
+            layers,
+            var_layers,
+            next_id,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (LayerId, &Layer)> {
+        self.layers
+            .iter()
+            .map(|(layer_id, layer)| (*layer_id, layer))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (LayerId, &mut Layer)> {
+        self.layers
+            .iter_mut()
+            .map(|(layer_id, layer)| (*layer_id, layer))
+    }
+
+    pub fn get(&self, layer_id: LayerId) -> Option<&Layer> {
+        self.layers
+            .iter()
+            .find(|(id, _)| *id == layer_id)
+            .map(|(_, layer)| layer)
+    }
+
+    /// Returns a name for the next added layer, unique unless the user
+    /// renamed other layers to look like generated names.
+    pub fn suggest_name(&self) -> String {
+        let mut number = self.layers.len() + 1;
+        loop {
+            let name = format!("Layer {}", number);
+            if self.layers.iter().all(|(_, layer)| layer.name != name) {
+                return name;
+            }
+
+            number += 1;
+        }
+    }
+
+    pub fn add(&mut self, layer: Layer) -> LayerId {
+        let layer_id = LayerId(self.next_id);
+        self.next_id += 1;
+
+        self.layers.push((layer_id, layer));
+
+        layer_id
+    }
+
+    /// Removes a layer and returns it. Variables assigned to the layer are no
+    /// longer assigned to any layer.
+    pub fn remove(&mut self, layer_id: LayerId) -> Option<Layer> {
+        let index = self.layers.iter().position(|(id, _)| *id == layer_id)?;
+        self.var_layers.retain(|_, id| *id != layer_id);
+
+        Some(self.layers.remove(index).1)
+    }
+
+    pub fn var_layer(&self, var_ident: VarIdent) -> Option<LayerId> {
+        self.var_layers.get(&var_ident).copied()
+    }
+
+    /// Assigns the variable to a layer, or to no layer.
+    pub fn set_var_layer(&mut self, var_ident: VarIdent, layer_id: Option<LayerId>) {
+        match layer_id {
+            Some(layer_id) => {
+                self.var_layers.insert(var_ident, layer_id);
+            }
+            None => {
+                self.var_layers.remove(&var_ident);
+            }
+        }
+    }
+
+    /// Returns the variable assignments in no particular order.
+    pub fn var_layers(&self) -> impl Iterator<Item = (VarIdent, LayerId)> + '_ {
+        self.var_layers
+            .iter()
+            .map(|(var_ident, layer_id)| (*var_ident, *layer_id))
+    }
+
+    /// Keeps only the assignments of variables matching the predicate.
+    pub fn retain_vars<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(VarIdent) -> bool,
+    {
+        self.var_layers.retain(|var_ident, _| predicate(*var_ident));
+    }
+
+    pub fn var_visible(&self, var_ident: VarIdent) -> bool {
+        self.var_layer_props(var_ident)
+            .map_or(true, |layer| layer.visible)
+    }
+
+    pub fn var_locked(&self, var_ident: VarIdent) -> bool {
+        self.var_layer_props(var_ident)
+            .map_or(false, |layer| layer.locked)
+    }
+
+    pub fn var_labeled(&self, var_ident: VarIdent) -> bool {
+        self.var_layer_props(var_ident)
+            .map_or(true, |layer| layer.labels)
+    }
+
+    /// Returns the material the value of the variable is drawn in, if its
+    /// layer is colored.
+    pub fn var_display_material(&self, var_ident: VarIdent) -> Option<DisplayMaterial> {
+        self.var_layer_props(var_ident)
+            .filter(|layer| layer.colored)
+            .map(|layer| DisplayMaterial {
+                kind: DisplayMaterialKind::Matcap,
+                color: layer.color,
+            })
+    }
+
+    fn var_layer_props(&self, var_ident: VarIdent) -> Option<&Layer> {
+        self.var_layer(var_ident)
+            .and_then(|layer_id| self.get(layer_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_var_props_follow_layer() {
+        let mut layers = Layers::new();
+        let layer_id = layers.add(Layer::new(String::from("Context")));
+        let var_ident = VarIdent(0);
+
+        assert!(layers.var_visible(var_ident));
+        assert!(!layers.var_locked(var_ident));
+
+        layers.set_var_layer(var_ident, Some(layer_id));
+        for (_, layer) in layers.iter_mut() {
+            layer.visible = false;
+            layer.locked = true;
+            layer.colored = true;
+            layer.labels = false;
+        }
+
+        assert!(!layers.var_visible(var_ident));
+        assert!(layers.var_locked(var_ident));
+        assert!(!layers.var_labeled(var_ident));
+        assert_eq!(
+            layers
+                .var_display_material(var_ident)
+                .map(|display_material| display_material.kind),
+            Some(DisplayMaterialKind::Matcap),
+        );
+
+        layers.remove(layer_id);
+
+        assert_eq!(layers.var_layer(var_ident), None);
+        assert!(layers.var_visible(var_ident));
+    }
+
+    #[test]
+    fn test_layers_from_parts_does_not_reuse_ids() {
+        let mut layers = Layers::from_parts(
+            vec![(LayerId(3), Layer::new(String::from("Context")))],
+            vec![(VarIdent(0), LayerId(3)), (VarIdent(1), LayerId(7))],
+        );
+
+        assert_eq!(layers.var_layer(VarIdent(0)), Some(LayerId(3)));
+        assert_eq!(layers.var_layer(VarIdent(1)), None);
+
+        let layer_id = layers.add(Layer::new(layers.suggest_name()));
+        assert_eq!(layer_id, LayerId(4));
+        assert_eq!(
+            layers.get(layer_id).map(|layer| layer.name.as_str()),
+            Some("Layer 2")
+        );
+    }
+}
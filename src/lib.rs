@@ -1,57 +1,160 @@
 pub use crate::logger::LogLevel;
-pub use crate::renderer::{GpuBackend, GpuPowerPreference, Msaa};
+pub use crate::renderer::{GpuBackend, GpuPowerPreference, Msaa, RendererError};
 
 use std::borrow::Cow;
-use std::collections::HashMap;
-use std::error::Error;
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::iter;
+use std::mem;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use image::{GenericImageView, Pixel};
-use nalgebra::{Point3, Vector2, Vector3};
+use nalgebra::{Point3, Vector3};
 
+use crate::annotation::{Annotation, AnnotationContext, AnnotationOptions};
+use crate::bookmarks::{self, CameraBookmark};
 use crate::bounding_box::BoundingBox;
-use crate::camera::{Camera, CameraOptions};
-use crate::convert::cast_usize;
+use crate::box_drawing::BoxDrawing;
+use crate::bundle;
+use crate::calibration::ScaleCalibration;
+use crate::camera::{Camera, CameraOptions, CameraView};
+use crate::clip_box::ClipBox;
+use crate::clipboard::Clipboard;
+use crate::event_log::{self, EventKind, EventLog};
+use crate::examples::ExampleProject;
+use crate::export::{self, collect_export_models, encode_and_write_png, unpad_image_rows};
+use crate::export_job::ExportJob;
+use crate::export_preview::ExportPreview;
+use crate::geolocation::Geolocation;
+use crate::ground_plane::GroundPlaneOptions;
 use crate::input::InputManager;
-use crate::interpreter::{Value, VarIdent};
+use crate::interpreter::{ast, Dimension, Ty, Value, VarIdent};
+use crate::layers::Layers;
+use crate::live_link::LiveLink;
+use crate::logger::ModuleFilter;
+use crate::mesh::bvh::Bvh;
 use crate::mesh::Mesh;
 use crate::notifications::{NotificationLevel, Notifications};
-use crate::plane::Plane;
-use crate::project::ProjectStatus;
+use crate::optimization::Optimization;
+use crate::panorama::{CubeFace, Panorama};
+use crate::param_defaults::ParamDefaults;
+use crate::pending_reads::{PendingRead, PendingReads, ThumbnailTarget};
+use crate::preferences::Preferences;
+use crate::profiler::{Profiler, Stage};
+use crate::project::{ProjectStatus, ProjectViewport};
+use crate::reference_images::ReferenceImage;
+use crate::remote::{RemoteCommand, RemoteRequest, RemoteResponse, RemoteServer};
 use crate::renderer::{
-    DirectionalLight, GpuMesh, GpuMeshHandle, Material, OffscreenRenderTargetHandle,
-    Options as RendererOptions, PollNotification as RendererPollNotification, Renderer,
+    CommandBuffer, DirectionalLight, GpuImageHandle, GpuMesh, GpuMeshHandle, Material,
+    OffscreenRenderTargetHandle, Options as RendererOptions,
+    PollNotification as RendererPollNotification, Renderer, Viewport,
 };
+use crate::scene_bounds::SceneBounds;
+use crate::scene_graph::{SceneGraph, ValueNode};
 use crate::session::{PollNotification as SessionPollNotification, Session};
-use crate::ui::{OverwriteModalTrigger, SaveModalResult, Ui};
+use crate::single_instance::InstanceListener;
+use crate::sketch::{self, FootprintSketch};
+use crate::snapping::{self, Snapping};
+use crate::statistics::{Statistics, StatisticsConsent};
+use crate::sun::{ShadowStudyOptions, SunOptions, SunPosition};
+use crate::sweep::{PollNotification as SweepPollNotification, Sweep};
+use crate::template::{ProjectTemplate, TemplateViewport};
+use crate::ui::{
+    CrashModalResult, ExampleModalResult, ExportPreviewModalResult, MenuStatus,
+    OverwriteModalTrigger, PipelineStatus, PresentationStatus, SaveModalResult, Ui,
+    UnitConversionModalResult, VariantsStatus,
+};
+use crate::ui_actions::{
+    self, add_drawn_box, add_sketched_footprint, apply_remote_edit, complete_missing_args,
+    import_obj_file, insert_library_asset, place_library_mesh, replace_param_values,
+};
+use crate::units::Unit;
+use crate::variants::{VariantId, Variants};
+use crate::window::{change_window_title, set_fullscreen, DetachedViewport, BASE_WINDOW_TITLE};
+use crate::window_state::WindowState;
 
+#[cfg(feature = "bench")]
+pub mod bench;
 pub mod geometry;
 pub mod importer;
 pub mod renderer;
 
 mod analytics;
+mod annotation;
+mod bookmarks;
 mod bounding_box;
+mod box_drawing;
+mod bundle;
+mod calibration;
 mod camera;
+mod checkpoint;
+mod clip_box;
+mod clipboard;
 mod convert;
+mod crash;
+mod display_material;
+mod event_log;
+mod examples;
+mod explode;
+mod export;
+mod export_job;
+mod export_preview;
 mod exporter;
+mod expression;
+mod find_replace;
+mod frame_limiter;
+mod geolocation;
+mod ground_plane;
 mod imgui_winit_support;
 mod input;
 mod interpreter;
 mod interpreter_funcs;
 mod interpreter_server;
+mod layers;
+mod library;
+mod live_link;
 mod logger;
+mod matcap;
 mod math;
 mod mesh;
 mod notifications;
+mod number_format;
+mod optimization;
+mod panorama;
+mod param_defaults;
+mod param_links;
+mod pending_reads;
+mod pipeline_text;
+mod placement;
 mod plane;
+mod preferences;
+mod profiler;
 mod project;
 mod pull;
+#[cfg(feature = "python")]
+mod python;
+mod reference_images;
+mod remote;
+mod scene_bounds;
+mod scene_graph;
 mod session;
+mod single_instance;
+mod sketch;
+mod snapping;
+mod speculation;
+mod statistics;
+mod stereo;
+mod sun;
+mod sweep;
+mod template;
 mod ui;
+mod ui_actions;
+mod units;
+mod variants;
+mod window;
+mod window_state;
 
 static IMAGE_DATA_ICON: &[u8] = include_bytes!("../icons/64x64.ico");
 static IMAGE_DATA_SCHEME: &[u8] = include_bytes!("../resources/scheme.png");
@@ -60,13 +163,25 @@ static IMAGE_DATA_LOGOS_WHITE: &[u8] = include_bytes!("../resources/logos_white.
 static IMAGE_DATA_SUBDIGITAL_LOGO: &[u8] = include_bytes!("../resources/subdigital_grey.png");
 
 const DURATION_CAMERA_INTERPOLATION: Duration = Duration::from_millis(300);
+const DURATION_LIGHT_INTERPOLATION: Duration = Duration::from_millis(200);
 const DURATION_NOTIFICATION: Duration = Duration::from_millis(5000);
 const DURATION_AUTORUN_DELAY: Duration = Duration::from_millis(100);
-const BASE_WINDOW_TITLE: &str = "H.U.R.B.A.N. selector";
 
-#[derive(Debug, Clone, Copy, PartialEq, clap::Clap)]
+/// The lowest sun altitude (in radians) used for casting shadows.
+const SUN_MIN_SHADOW_ALTITUDE: f32 = 5.0 * std::f32::consts::PI / 180.0;
+/// How fast the time passes when the sun movement is animated.
+const SUN_ANIMATION_HOURS_PER_SECOND: f32 = 1.0;
+
+#[derive(Debug, Clone, PartialEq, clap::Clap)]
 #[clap(name = "HURBAN selector", version, author)]
 pub struct Options {
+    /// Project file to open on start.
+    #[clap(parse(from_os_str))]
+    pub project: Option<PathBuf>,
+    /// Exports the unused geometry of the project to the OBJ file, once its
+    /// operation pipeline finishes running, and exits.
+    #[clap(long, parse(from_os_str), requires = "project")]
+    pub export_obj: Option<PathBuf>,
     /// Theme for the editor.
     #[clap(long, arg_enum, env = "HS_THEME", default_value = "dark")]
     pub theme: Theme,
@@ -85,7 +200,13 @@ pub struct Options {
     )]
     pub gpu_power_preference: GpuPowerPreference,
     /// Level of multi-sampling based anti-aliasing to use in rendering.
-    #[clap(long, arg_enum, env = "HS_GPU_MSAA", default_value = "disabled")]
+    #[clap(
+        long,
+        alias = "msaa",
+        arg_enum,
+        env = "HS_GPU_MSAA",
+        default_value = "disabled"
+    )]
     pub gpu_msaa: Msaa,
     /// Logging level for the editor.
     #[clap(long, arg_enum, env = "HS_LOG_LEVEL_APP", default_value = "info")]
@@ -93,17 +214,36 @@ pub struct Options {
     /// Logging level for external libraries.
     #[clap(long, arg_enum, env = "HS_LOG_LEVEL_LIB", default_value = "warn")]
     pub log_level_lib: LogLevel,
+    /// Port of the local WebSocket server accepting commands to edit and run
+    /// the pipeline from other tools. The server is not started, if not set.
+    #[clap(long, env = "HS_REMOTE_PORT")]
+    pub remote_port: Option<u16>,
+    /// Maximum GPU memory in MiB taken by the geometry of the viewport.
+    /// Geometry not drawn recently is moved out of GPU memory when over
+    /// budget, and uploaded again when needed. Unlimited, if not set.
+    #[clap(long, env = "HS_GPU_MESH_MEMORY_BUDGET")]
+    pub gpu_mesh_memory_budget: Option<u64>,
+    /// Checks the use of the GPU with the validation layers of the graphics
+    /// API, avoids risky features, such as multi-sampling and large
+    /// textures, and logs every operation on GPU resources to the log file.
+    /// Meant for producing GPU bug reports, rendering is slower.
+    #[clap(long, env = "HS_GPU_VALIDATION")]
+    pub gpu_validation: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::Clap)]
 pub enum Theme {
     Dark,
     Light,
+    /// Opaque black windows with white text and yellow highlights.
+    DarkHighContrast,
+    /// Opaque white windows with black text and blue highlights.
+    LightHighContrast,
 }
 
 /// The draw mode applied to a group of objects in the viewport. Not always a
 /// 1:1 mapping with renderer materials.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum ViewportDrawMode {
     Wireframe,
     Shaded,
@@ -111,25 +251,24 @@ pub enum ViewportDrawMode {
     ShadedWireframeXray,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ScreenshotOptions {
     pub width: u32,
     pub height: u32,
     pub transparent: bool,
+    pub annotation: AnnotationOptions,
+    /// Whether the screenshot is a side-by-side stereo image, the left eye
+    /// in the left half.
+    pub stereo: bool,
+    /// Distance between the eyes of stereo screenshots and of the stereo
+    /// viewport.
+    pub stereo_eye_separation: f32,
+    /// Whether the screenshot is a 360 degree equirectangular panorama taken
+    /// from the camera position. Panoramas are as wide as the screenshot,
+    /// and half as high.
+    pub panorama: bool,
 }
 
-/// A unique identifier assigned to a value or subvalue for purposes
-/// of displaying in the viewport.
-///
-/// Since we support value arrays, there can be multiple geometries
-/// contained in a single value that all need to be treated separately
-/// for purposes of scene geometry analysis and rendering.
-///
-/// For simple values, the path is always `(var_ident, 0)`. For array
-/// element values, the path is `(var_ident, array_index)`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct ValuePath(VarIdent, usize);
-
 #[cfg(not(feature = "dist"))]
 #[derive(Debug, Clone, Copy)]
 enum RendererDebugView {
@@ -150,12 +289,93 @@ impl RendererDebugView {
 /// Initialize the window and run in infinite loop.
 ///
 /// Will continue running until a close request is received from the
-/// created window.
-pub fn init_and_run(options: Options) -> ! {
-    logger::init(options.log_level_app, options.log_level_lib);
+/// created window. Returns only if the renderer fails to initialize.
+pub fn init_and_run(options: Options) -> Result<Infallible, RendererError> {
+    // Preferences are loaded before the logger is initialized, because they
+    // configure it. Errors are only logged afterwards.
+    let (mut preferences, preferences_error) = match preferences::load() {
+        Ok(preferences) => (preferences, None),
+        Err(err) => (Preferences::default(), Some(err)),
+    };
+
+    // The validation mode writes the renderer and wgpu logs to the log file,
+    // so that they can be attached to bug reports
+    let mut logging_preferences = preferences.logging.clone();
+    if options.gpu_validation {
+        logging_preferences.to_file = true;
+        for module in &["hurban_selector::renderer", "wgpu_core"] {
+            logging_preferences.module_filters.push(ModuleFilter {
+                module: String::from(*module),
+                level: LogLevel::Info,
+            });
+        }
+    }
+
+    let recent_log = logger::init(
+        options.log_level_app,
+        options.log_level_lib,
+        &logging_preferences,
+    );
+    crash::install_panic_hook(recent_log);
+
+    if let Some(err) = preferences_error {
+        log::warn!("Failed to load preferences, using defaults: {}", err);
+    }
+
+    // Projects opened from the file manager are opened by the already
+    // running instance, if any. Exports from the command line always run in
+    // their own instance.
+    if let Some(project_path) = &options.project {
+        if options.export_obj.is_none() && single_instance::forward(project_path) {
+            log::info!("Project forwarded to the running instance");
+            std::process::exit(0);
+        }
+    }
+
+    let gpu_adapters = renderer::enumerate_gpu_adapters(options.gpu_backend);
+    for gpu_adapter in &gpu_adapters {
+        log::info!("Available GPU adapter: {:?}", gpu_adapter);
+    }
 
     let event_loop = winit::event_loop::EventLoop::new();
 
+    let remote_server = options.remote_port.and_then(|port| {
+        let event_loop_proxy = event_loop.create_proxy();
+        let wake = move || {
+            // The event only wakes the event loop up to process the command
+            let _ = event_loop_proxy.send_event(());
+        };
+
+        match RemoteServer::start(port, wake) {
+            Ok(remote_server) => {
+                log::info!("Remote control server listening on port {}", port);
+                Some(remote_server)
+            }
+            Err(err) => {
+                log::error!("Failed to start remote control server: {}", err);
+                None
+            }
+        }
+    });
+    let instance_listener = {
+        let event_loop_proxy = event_loop.create_proxy();
+        let wake = move || {
+            let _ = event_loop_proxy.send_event(());
+        };
+
+        match InstanceListener::start(wake) {
+            Ok(instance_listener) => Some(instance_listener),
+            Err(err) => {
+                log::info!("Not receiving projects opened by other instances: {}", err);
+                None
+            }
+        }
+    };
+
+    // Remote commands replied to once the pipeline finishes running
+    let mut remote_requests_awaiting_run: Vec<RemoteRequest> = Vec::new();
+    let mut remote_screenshot_queue: Vec<RemoteRequest> = Vec::new();
+
     let (img_icon, width_icon, height_icon) = decode_image_rgba8_unorm(IMAGE_DATA_ICON);
     let (img_scheme, width_scheme, height_scheme) = decode_image_rgba8_unorm(IMAGE_DATA_SCHEME);
     let (img_logos_black, width_logos_black, height_logos_black) =
@@ -168,11 +388,61 @@ pub fn init_and_run(options: Options) -> ! {
     let icon = winit::window::Icon::from_rgba(img_icon, width_icon, height_icon)
         .expect("Failed to create icon.");
 
-    let window = winit::window::WindowBuilder::new()
+    let saved_window_state = match window_state::load() {
+        Ok(saved_window_state) => saved_window_state,
+        Err(err) => {
+            log::warn!("Failed to load window state: {}", err);
+            None
+        }
+    };
+
+    let mut window_builder = winit::window::WindowBuilder::new()
         .with_title(BASE_WINDOW_TITLE)
-        .with_inner_size(winit::dpi::LogicalSize::new(1280, 720))
-        .with_maximized(true)
-        .with_window_icon(Some(icon))
+        .with_window_icon(Some(icon));
+    match &saved_window_state {
+        Some(saved_window_state) => {
+            let [width, height] = saved_window_state.size;
+            window_builder =
+                window_builder.with_inner_size(winit::dpi::PhysicalSize::new(width, height));
+
+            if let Some(position) = saved_window_state.position {
+                let monitors: Vec<_> = event_loop
+                    .available_monitors()
+                    .map(|monitor| {
+                        let monitor_position = monitor.position();
+                        let monitor_size = monitor.size();
+                        (
+                            [monitor_position.x, monitor_position.y],
+                            [monitor_size.width, monitor_size.height],
+                        )
+                    })
+                    .collect();
+
+                if window_state::position_visible(position, &monitors) {
+                    let [x, y] = position;
+                    window_builder =
+                        window_builder.with_position(winit::dpi::PhysicalPosition::new(x, y));
+                } else {
+                    log::info!("Saved window position is off-screen, ignoring it");
+                }
+            }
+
+            if saved_window_state.fullscreen {
+                let monitor = event_loop.available_monitors().find(|monitor| {
+                    monitor.name().is_some() && monitor.name() == saved_window_state.monitor
+                });
+                window_builder = window_builder
+                    .with_fullscreen(Some(winit::window::Fullscreen::Borderless(monitor)));
+            }
+        }
+        None => {
+            window_builder = window_builder
+                .with_inner_size(winit::dpi::LogicalSize::new(1280, 720))
+                .with_maximized(true);
+        }
+    }
+
+    let window = window_builder
         .build(&event_loop)
         .expect("Failed to create window");
 
@@ -180,20 +450,51 @@ pub fn init_and_run(options: Options) -> ! {
     // https://github.com/rust-windowing/winit/issues/1510
     #[cfg(target_os = "windows")]
     {
-        window.set_maximized(true);
+        if saved_window_state.is_none() {
+            window.set_maximized(true);
+        }
     }
 
+    // Whether the user switched the window to fullscreen. The presentation
+    // mode makes the window fullscreen regardless.
+    let mut window_fullscreen = saved_window_state
+        .as_ref()
+        .map_or(false, |saved_window_state| saved_window_state.fullscreen);
+    // The state of the window before it became fullscreen, so that it can be
+    // saved on exit.
+    let mut windowed_window_state = saved_window_state.filter(|_| window_fullscreen);
+
     let initial_window_size = window.inner_size();
     let initial_window_width = initial_window_size.width;
     let initial_window_height = initial_window_size.height;
 
     let mut session = Session::new();
-    session.set_autorun_delay(Some(DURATION_AUTORUN_DELAY));
+    session.set_autorun_delay(preferences.autorun.autorun_delay());
+    session.set_stmt_timeout(preferences.timeout.stmt_timeout());
 
     let mut input_manager = InputManager::new();
     let mut notifications = Notifications::with_ttl(DURATION_NOTIFICATION);
     let mut ui = Ui::new(&window, options.theme);
     let mut project_status = project::ProjectStatus::default();
+    // The opened example, for which the modal offering to save a copy of it
+    // is shown
+    let mut example_modal: Option<&'static ExampleProject> = None;
+
+    if let Some(project_path) = options.project {
+        log::info!("Opening project given on the command line");
+        project_status.open_requested_path = Some(project_path);
+        project_status.open_requested = true;
+    }
+    // OBJ file to export the project to once it is opened and run, before
+    // exiting.
+    let mut command_line_export_obj = options.export_obj;
+    let mut exit_after_export = false;
+    // Exports and screenshots requested in draft mode wait until the
+    // pipeline runs in full quality. Draft mode is switched back on once
+    // they are done.
+    let mut export_obj_awaiting_full_quality = false;
+    let mut screenshot_awaiting_full_quality = false;
+    let mut draft_suspended = false;
 
     change_window_title(&window, &project_status);
 
@@ -202,19 +503,132 @@ pub fn init_and_run(options: Options) -> ! {
         width: initial_window_width,
         height: initial_window_height,
         transparent: true,
+        annotation: AnnotationOptions::default(),
+        stereo: false,
+        stereo_eye_separation: stereo::EYE_SEPARATION_DEFAULT,
+        panorama: false,
     };
 
     let mut about_modal_open = false;
 
+    let mut variants = Variants::new();
+    let mut variant_thumbnail_requested: Option<VariantId> = None;
+    let mut comparison: Option<Comparison> = None;
+
+    let mut sweep_window_open = false;
+    let mut sweep: Option<Sweep> = None;
+    let mut sweep_generation: u64 = 0;
+    let mut sweep_thumbnails_requested: Vec<(u64, usize, Vec<GpuMeshHandle>)> = Vec::new();
+
+    let mut sun_window_open = false;
+    let mut sun_options = SunOptions::default();
+    let mut geolocation = Geolocation::default();
+    let mut viewport_north_arrow = true;
+    let mut sun_animation = false;
+    let mut shadow_study_options = ShadowStudyOptions::default();
+    let mut shadow_study_queue: Vec<(f32, PathBuf)> = Vec::new();
+    let mut matcap_bake_queue: Option<PathBuf> = None;
+    let mut matcaps_baked: Vec<(PathBuf, u32, u32, Vec<u8>)> = Vec::new();
+    let mut view_render_queue: Vec<(CameraView, PathBuf)> = Vec::new();
+    let mut live_link = LiveLink::default();
+    // Whether a live linked file changed while the pipeline was running, and
+    // it needs to run again once it finishes
+    let mut live_link_run_pending = false;
+    let mut panorama: Option<Panorama> = None;
+
+    let mut calibration_window_open = false;
+    let mut scale_calibration = ScaleCalibration::new();
+    let mut calibration_real_distance = 1.0;
+
+    let mut reference_images_window_open = false;
+    let mut reference_images: Vec<ReferenceImage> = Vec::new();
+    let mut views_window_open = false;
+    let mut camera_bookmarks: Vec<CameraBookmark> = Vec::new();
+    // Parallel to the reference images. Contains the aspect ratio and GPU
+    // handle of each image, or `None` if its file could not be loaded.
+    let mut reference_image_gpu_handles: Vec<Option<(f32, GpuImageHandle)>> = Vec::new();
+
+    let mut placement_window_open = false;
+    // The chosen OBJ file and its mesh, as the Import OBJ operation places
+    // it. Not part of the project.
+    let mut placement_library: Option<(String, Mesh)> = None;
+    let mut placement_rotation = 0.0;
+    let mut placement_on_surfaces = true;
+    let mut placement_point: Option<Point3<f32>> = None;
+    let mut placement_preview_gpu_mesh_handle: Option<GpuMeshHandle> = None;
+    let mut placement_preview_dirty = false;
+
+    let mut box_drawing_window_open = false;
+    let mut box_drawing = BoxDrawing::new();
+    let mut box_drawing_preview_gpu_mesh_handle: Option<GpuMeshHandle> = None;
+    let mut box_drawing_preview_dirty = false;
+
+    let mut sketch_window_open = false;
+    let mut sketch = FootprintSketch::new();
+    let mut sketch_height = 3.0;
+    let mut sketch_preview_gpu_mesh_handle: Option<GpuMeshHandle> = None;
+    let mut sketch_preview_dirty = false;
+
+    let mut find_replace_window_open = false;
+    let mut help_window_open = false;
+    let mut profiler_window_open = false;
+
+    let mut library_window_open = false;
+    let mut examples_window_open = false;
+    let mut layers_window_open = false;
+    let mut history_window_open = false;
+    let mut history_event_index = u32::MAX;
+    let mut clip_box_window_open = false;
+
+    let mut optimization_window_open = false;
+    let mut optimization: Option<Optimization> = None;
+
+    let mut preferences_window_open = false;
+    let mut preferences_changed = false;
+
+    let mut statistics_window_open = false;
+    let mut statistics = load_statistics(preferences.statistics);
+    let mut param_defaults = param_defaults::load().unwrap_or_else(|err| {
+        log::warn!("Failed to load remembered parameter values: {}", err);
+        ParamDefaults::default()
+    });
+    let mut pipeline_run_start: Option<Instant> = None;
+    let mut pipeline_run_finished = false;
+    let mut pipeline_warning_count = 0;
+
+    let mut crash_bundle = crash::pending_bundle();
+
+    let mut export_preview: Option<ExportPreview> = None;
+    let mut export_jobs: Vec<ExportJob> = Vec::new();
+
+    let mut pending_reads = PendingReads::new();
+    let mut thumbnails_ready: Vec<(ThumbnailTarget, u32, u32, Vec<u8>)> = Vec::new();
+
+    // UI textures can only be removed once the UI frame referencing them is
+    // drawn, otherwise the UI renderer would fail to find them.
+    let mut ui_textures_to_remove: Vec<imgui::TextureId> = Vec::new();
+
     let clear_color = match options.theme {
         Theme::Dark => [0.1, 0.1, 0.1, 1.0],
-        Theme::Light => [1.0, 1.0, 1.0, 1.0],
+        Theme::Light | Theme::LightHighContrast => [1.0, 1.0, 1.0, 1.0],
+        Theme::DarkHighContrast => [0.0, 0.0, 0.0, 1.0],
     };
 
     #[cfg(not(feature = "dist"))]
     let mut renderer_debug_view = RendererDebugView::Off;
     let mut viewport_draw_mode = ViewportDrawMode::ShadedWireframe;
     let mut viewport_draw_used_values = true;
+    let mut viewport_labels = false;
+    let mut viewport_stereo = false;
+    let mut viewport_explode_factor = 0.0;
+    // Hides the editing windows and locks the project, leaving only the
+    // viewport and the presentation panel.
+    let mut presentation_mode = false;
+    let mut detached_viewport: Option<DetachedViewport> = None;
+    let mut clip_box = ClipBox::default();
+    let mut project_unit = Unit::default();
+    let mut unit_conversion_check = UnitConversionCheck::default();
+    let mut unit_conversion_offer: Option<UnitConversionOffer> = None;
     let mut renderer = Renderer::new(
         &window,
         initial_window_width,
@@ -223,16 +637,22 @@ pub fn init_and_run(options: Options) -> ! {
         RendererOptions {
             backend: options.gpu_backend,
             power_preference: options.gpu_power_preference,
+            adapter: preferences.gpu_adapter.clone(),
             msaa: options.gpu_msaa,
-            flat_material_color: [0.0, 0.0, 0.0, 0.1],
+            flat_material_color: preferences.ground_plane.material_color(),
             // FIXME: These different alphas are to workaround a blending bug in
             // the renderer. Fix the blending bug.
             transparent_matcap_shaded_material_alpha: match options.theme {
-                Theme::Dark => 0.5,
-                Theme::Light => 0.15,
+                Theme::Dark | Theme::DarkHighContrast => 0.5,
+                Theme::Light | Theme::LightHighContrast => 0.15,
             },
+            mesh_memory_budget: options
+                .gpu_mesh_memory_budget
+                .map(|budget_mib| budget_mib.saturating_mul(1024 * 1024)),
+            shadow_quality: preferences.shadow_quality,
+            validation: options.gpu_validation,
         },
-    );
+    )?;
 
     let tex_scheme = renderer.add_ui_texture_rgba8_unorm(width_scheme, height_scheme, &img_scheme);
     let tex_logos_black = renderer.add_ui_texture_rgba8_unorm(
@@ -251,16 +671,42 @@ pub fn init_and_run(options: Options) -> ! {
         &img_subdigital_logo,
     );
 
-    let mut scene_bounding_box: BoundingBox<f32> = BoundingBox::unit();
-    let mut scene_meshes: HashMap<ValuePath, (bool, Arc<Mesh>)> = HashMap::new();
-    let mut scene_gpu_mesh_handles: HashMap<ValuePath, (bool, GpuMeshHandle)> = HashMap::new();
+    // The illustrations of the operation docs are uploaded once at startup,
+    // as there are only a few and they are small.
+    for (func_ident, func) in session.function_table() {
+        if let Some(image_data) = func.docs().and_then(|docs| docs.image) {
+            let (img, width, height) = decode_image_rgba8_unorm(image_data);
+            let texture_id = renderer.add_ui_texture_rgba8_unorm(width, height, &img);
+            ui.set_func_doc_image(*func_ident, texture_id, [width as f32, height as f32]);
+        }
+    }
 
-    let mut ground_plane_mesh = compute_ground_plane_mesh(&scene_bounding_box);
-    let mut ground_plane_mesh_bounding_box = ground_plane_mesh.bounding_box();
-    let mut ground_plane_gpu_mesh_handle = Some(
-        renderer
-            .add_scene_mesh(&GpuMesh::from_mesh(&ground_plane_mesh))
-            .expect("Failed to add ground plane mesh"),
+    if let Some(path) = &preferences.matcap {
+        if let Err(err) = set_matcap_from_file(&mut renderer, path) {
+            log::error!("Failed to load matcap {}: {}", path.display(), err);
+        }
+    }
+
+    let mut scene_bounding_box: BoundingBox<f32> = BoundingBox::unit();
+    let mut scene_bounds: SceneBounds<VarIdent> = SceneBounds::new(viewport_draw_used_values);
+    // The bounding box covered by the shadow casting light of the viewport,
+    // following the scene bounding box over a few frames
+    let mut light_bounding_box = scene_bounding_box;
+    let mut light_interpolation: Option<LightInterpolation> = None;
+    let mut scene_graph: SceneGraph = SceneGraph::new();
+    // Meshes streamed by the operation being run, displayed until the run
+    // finishes.
+    let mut streamed_gpu_mesh_handles: Vec<GpuMeshHandle> = Vec::new();
+
+    let mut clipboard = Clipboard::new();
+    let mut profiler = Profiler::new();
+
+    let mut ground_plane_gpu_mesh_handle = None;
+    rebuild_ground_plane(
+        &mut renderer,
+        &preferences.ground_plane,
+        &scene_bounding_box,
+        &mut ground_plane_gpu_mesh_handle,
     );
 
     let mut offscreen_render_target_handles_to_remove: Vec<OffscreenRenderTargetHandle> =
@@ -292,10 +738,12 @@ pub fn init_and_run(options: Options) -> ! {
 
     let time_start = Instant::now();
     let mut time = time_start;
+    let mut last_input_time = time_start;
+    let mut next_control_flow = winit::event_loop::ControlFlow::Poll;
 
     #[allow(clippy::cognitive_complexity)]
-    event_loop.run(move |event, _, control_flow| {
-        *control_flow = winit::event_loop::ControlFlow::Poll;
+    event_loop.run(move |event, event_loop_window_target, control_flow| {
+        *control_flow = next_control_flow;
 
         match event {
             winit::event::Event::NewEvents(_) => {
@@ -305,160 +753,102 @@ pub fn init_and_run(options: Options) -> ! {
 
                 ui.set_delta_time(duration_last_frame.as_secs_f32());
 
+                if sun_animation {
+                    sun_options.solar_time = (sun_options.solar_time
+                        + duration_last_frame.as_secs_f32() * SUN_ANIMATION_HOURS_PER_SECOND)
+                        % 24.0;
+                }
+
                 input_manager.start_frame();
+                if let Some(detached_viewport) = &mut detached_viewport {
+                    detached_viewport.input_manager.start_frame();
+                }
             }
             winit::event::Event::MainEventsCleared => {
+                profiler.begin_frame(time);
+                let mesh_upload_start = renderer.mesh_upload_duration();
+
+                // Runs are started while drawing the previous frame, so the
+                // measured runtime is only precise up to a frame.
+                if session.interpreter_busy() && pipeline_run_start.is_none() {
+                    pipeline_run_start = Some(time);
+                }
+
                 // Poll at the beginning of event processing, so that the
                 // pipeline UI is not lagging one frame behind.
+                let mut added_scene_vars = Vec::new();
+                let session_poll_start = Instant::now();
                 session.poll(time, |poll_notification| match poll_notification {
-                    SessionPollNotification::UsedValueAdded(var_ident, value) => match value {
-                        Value::Mesh(mesh) => {
-                            let gpu_mesh = GpuMesh::from_mesh(&mesh);
-                            let gpu_mesh_id = renderer
-                                .add_scene_mesh(&gpu_mesh)
-                                .expect("Failed to upload scene mesh");
-
-                            let path = ValuePath(var_ident, 0);
-
-                            scene_meshes.insert(path, (true, mesh));
-                            scene_gpu_mesh_handles.insert(path, (true, gpu_mesh_id));
-                        }
-                        Value::MeshArray(mesh_array) => {
-                            for (index, mesh) in mesh_array.iter_refcounted().enumerate() {
-                                let gpu_mesh = GpuMesh::from_mesh(&mesh);
-                                let gpu_mesh_id = renderer
-                                    .add_scene_mesh(&gpu_mesh)
-                                    .expect("Failed to upload scene mesh");
-
-                                let path = ValuePath(var_ident, index);
-
-                                scene_meshes.insert(path, (true, mesh));
-                                scene_gpu_mesh_handles.insert(path, (true, gpu_mesh_id));
-                            }
-                        }
-                        _ => (/* Ignore other values, we don't display them in the viewport */),
-                    },
-
-                    SessionPollNotification::UsedValueRemoved(var_ident, value) => match value {
-                        Value::Mesh(_) => {
-                            let path = ValuePath(var_ident, 0);
-
-                            scene_meshes.remove(&path);
-                            let gpu_mesh_id = scene_gpu_mesh_handles
-                                .remove(&path)
-                                .expect("Gpu mesh ID was not tracked")
-                                .1;
-
-                            renderer.remove_scene_mesh(gpu_mesh_id);
-                        }
-                        Value::MeshArray(mesh_array) => {
-                            for index in 0..mesh_array.len() {
-                                let path = ValuePath(var_ident, cast_usize(index));
-
-                                scene_meshes.remove(&path);
-                                let gpu_mesh_id = scene_gpu_mesh_handles
-                                    .remove(&path)
-                                    .expect("Gpu mesh ID was not tracked")
-                                    .1;
-
-                                renderer.remove_scene_mesh(gpu_mesh_id);
-                            }
-                        }
-                        _ => (/* Ignore other values, we don't display them in the viewport */),
-                    },
-
-                    SessionPollNotification::UnusedValueAdded(var_ident, value) => match value {
-                        Value::Mesh(mesh) => {
-                            let gpu_mesh = GpuMesh::from_mesh(&mesh);
-                            let gpu_mesh_id = renderer
-                                .add_scene_mesh(&gpu_mesh)
-                                .expect("Failed to upload scene mesh");
-
-                            let path = ValuePath(var_ident, 0);
-
-                            scene_meshes.insert(path, (false, mesh));
-                            scene_gpu_mesh_handles.insert(path, (false, gpu_mesh_id));
+                    SessionPollNotification::UsedValueAdded(var_ident, value) => {
+                        if let Some(bounding_box) = value_bounding_box(&value) {
+                            scene_bounds.insert(var_ident, true, bounding_box);
                         }
-                        Value::MeshArray(mesh_array) => {
-                            for (index, mesh) in mesh_array.iter_refcounted().enumerate() {
-                                let gpu_mesh = GpuMesh::from_mesh(&mesh);
-                                let gpu_mesh_id = renderer
-                                    .add_scene_mesh(&gpu_mesh)
-                                    .expect("Failed to upload scene mesh");
-
-                                let path = ValuePath(var_ident, index);
-
-                                scene_meshes.insert(path, (false, mesh));
-                                scene_gpu_mesh_handles.insert(path, (false, gpu_mesh_id));
-                            }
+                        add_scene_value(&mut renderer, &mut scene_graph, var_ident, value, true);
+                        added_scene_vars.push(var_ident);
+                    }
+                    SessionPollNotification::UnusedValueAdded(var_ident, value) => {
+                        if let Some(bounding_box) = value_bounding_box(&value) {
+                            scene_bounds.insert(var_ident, false, bounding_box);
                         }
-                        _ => (/* Ignore other values, we don't display them in the viewport */),
-                    },
-
-                    SessionPollNotification::UnusedValueRemoved(var_ident, value) => match value {
-                        Value::Mesh(_) => {
-                            let path = ValuePath(var_ident, 0);
-
-                            scene_meshes.remove(&path);
-                            let gpu_mesh_id = scene_gpu_mesh_handles
-                                .remove(&path)
-                                .expect("Gpu mesh ID was not tracked")
-                                .1;
+                        add_scene_value(&mut renderer, &mut scene_graph, var_ident, value, false);
+                        added_scene_vars.push(var_ident);
+                    }
+                    SessionPollNotification::UsedValueRemoved(var_ident, _)
+                    | SessionPollNotification::UnusedValueRemoved(var_ident, _) => {
+                        scene_bounds.remove(&var_ident);
+                        // Re-run statements replace their values, so the mesh
+                        // nodes of removed values are kept to be reused by
+                        // the added ones
+                        scene_graph.remove(var_ident);
+                    }
+                    SessionPollNotification::StreamedMeshAdded(mesh) => {
+                        streamed_gpu_mesh_handles.push(
+                            renderer
+                                .add_scene_mesh(&GpuMesh::from_mesh(&mesh))
+                                .expect("Failed to upload streamed mesh"),
+                        );
+                    }
 
-                            renderer.remove_scene_mesh(gpu_mesh_id);
+                    SessionPollNotification::FinishedSuccessfully => {
+                        for gpu_mesh_handle in streamed_gpu_mesh_handles.drain(..) {
+                            renderer.remove_scene_mesh(gpu_mesh_handle);
                         }
-                        Value::MeshArray(mesh_array) => {
-                            for index in 0..mesh_array.len() {
-                                let path = ValuePath(var_ident, cast_usize(index));
 
-                                scene_meshes.remove(&path);
-                                let gpu_mesh_id = scene_gpu_mesh_handles
-                                    .remove(&path)
-                                    .expect("Gpu mesh ID was not tracked")
-                                    .1;
+                        // Values that don't change the scene bounds, e.g.
+                        // when only the last operation is tweaked, keep the
+                        // ground plane as it is
+                        let new_scene_bounding_box = scene_bounds.bounding_box();
+                        if new_scene_bounding_box != scene_bounding_box {
+                            scene_bounding_box = new_scene_bounding_box;
+
+                            rebuild_ground_plane(
+                                &mut renderer,
+                                &preferences.ground_plane,
+                                &scene_bounding_box,
+                                &mut ground_plane_gpu_mesh_handle,
+                            );
 
-                                renderer.remove_scene_mesh(gpu_mesh_id);
-                            }
+                            let camera_radius_max = compute_scene_camera_radius(scene_bounding_box);
+                            camera.set_radius_min(0.001 * camera_radius_max);
+                            camera.set_radius_max(camera_radius_max);
+                            camera.set_znear(0.001 * camera_radius_max);
+                            camera.set_zfar(2.0 * camera_radius_max);
                         }
-                        _ => (/* Ignore other values, we don't display them in the viewport */),
-                    },
-
-                    SessionPollNotification::FinishedSuccessfully => {
-                        scene_bounding_box = BoundingBox::union(
-                            scene_meshes
-                                .values()
-                                .filter(|(used, _)| viewport_draw_used_values || !used)
-                                .map(|(_, mesh)| mesh.bounding_box()),
-                        )
-                        .unwrap_or_else(BoundingBox::unit);
-
-                        ground_plane_mesh = compute_ground_plane_mesh(&scene_bounding_box);
-                        ground_plane_mesh_bounding_box = ground_plane_mesh.bounding_box();
-                        renderer.remove_scene_mesh(
-                            ground_plane_gpu_mesh_handle
-                                .take()
-                                .expect("Ground plane must always be present"),
-                        );
-                        ground_plane_gpu_mesh_handle = Some(
-                            renderer
-                                .add_scene_mesh(&GpuMesh::from_mesh(&ground_plane_mesh))
-                                .expect("Failed to add ground plane mesh"),
-                        );
-
-                        let camera_radius_max = compute_scene_camera_radius(scene_bounding_box);
-                        camera.set_radius_min(0.001 * camera_radius_max);
-                        camera.set_radius_max(camera_radius_max);
-                        camera.set_znear(0.001 * camera_radius_max);
-                        camera.set_zfar(2.0 * camera_radius_max);
 
                         notifications.push(
                             time,
                             NotificationLevel::Info,
                             "Execution of the Operation pipeline finished successfully.",
                         );
+
+                        pipeline_run_finished = true;
                     }
 
                     SessionPollNotification::FinishedWithError(error_message) => {
+                        for gpu_mesh_handle in streamed_gpu_mesh_handles.drain(..) {
+                            renderer.remove_scene_mesh(gpu_mesh_handle);
+                        }
+
                         notifications.push(
                             time,
                             NotificationLevel::Error,
@@ -469,57 +859,378 @@ pub fn init_and_run(options: Options) -> ! {
                         );
                     }
                 });
+                let session_poll_mesh_upload = renderer.mesh_upload_duration() - mesh_upload_start;
+                profiler.record(
+                    Stage::SessionPoll,
+                    session_poll_start
+                        .elapsed()
+                        .checked_sub(session_poll_mesh_upload)
+                        .unwrap_or_default(),
+                );
+
+                for gpu_mesh_handle in scene_graph.collect_garbage() {
+                    renderer.remove_scene_mesh(gpu_mesh_handle);
+                }
+
+                // The session can't be asked for layers and display
+                // materials while it is being polled, so the added values
+                // are synced afterwards.
+                for var_ident in added_scene_vars {
+                    sync_scene_value(&mut renderer, &mut scene_graph, &session, var_ident);
+                }
+                scene_graph.set_order(stmt_var_idents(&session));
+
+                if pipeline_run_finished {
+                    if let (Some(statistics), Some(start)) = (&mut statistics, pipeline_run_start) {
+                        record_pipeline_run(statistics, &session, time.duration_since(start));
+                    }
+
+                    let project = create_project(
+                        &session,
+                        &variants,
+                        project_unit,
+                        geolocation,
+                        &reference_images,
+                        &camera_bookmarks,
+                        Some(ProjectViewport {
+                            draw_mode: viewport_draw_mode,
+                            draw_used_values: viewport_draw_used_values,
+                            labels: viewport_labels,
+                            clip_box,
+                            camera_view: camera.view(),
+                        }),
+                    );
+                    if let Err(err) = crash::autosave_project(project) {
+                        log::warn!("Failed to autosave project: {}", err);
+                    }
+
+                    // Only a changed number of warnings is notified, so that
+                    // autorun doesn't repeat the same notification.
+                    let warning_count = session.warning_count();
+                    if warning_count > 0 && warning_count != pipeline_warning_count {
+                        notifications.push(
+                            time,
+                            NotificationLevel::Warn,
+                            format!(
+                                "The operation pipeline finished with {} {}. \
+                                 Hover the highlighted operations for details.",
+                                warning_count,
+                                if warning_count == 1 {
+                                    "warning"
+                                } else {
+                                    "warnings"
+                                },
+                            ),
+                        );
+                    }
+                    pipeline_warning_count = warning_count;
+
+                    pipeline_run_finished = false;
+                }
+                if !session.interpreter_busy() {
+                    pipeline_run_start = None;
+                }
+
+                if let Some(comparison) = &mut comparison {
+                    let Comparison {
+                        session: comparison_session,
+                        scene_bounding_box: comparison_scene_bounding_box,
+                        scene_graph: comparison_scene_graph,
+                        ..
+                    } = comparison;
+
+                    let mut added_scene_vars = Vec::new();
+                    comparison_session.poll(time, |poll_notification| match poll_notification {
+                        SessionPollNotification::UsedValueAdded(var_ident, value) => {
+                            add_scene_value(
+                                &mut renderer,
+                                comparison_scene_graph,
+                                var_ident,
+                                value,
+                                true,
+                            );
+                            added_scene_vars.push(var_ident);
+                        }
+                        SessionPollNotification::UnusedValueAdded(var_ident, value) => {
+                            add_scene_value(
+                                &mut renderer,
+                                comparison_scene_graph,
+                                var_ident,
+                                value,
+                                false,
+                            );
+                            added_scene_vars.push(var_ident);
+                        }
+                        SessionPollNotification::UsedValueRemoved(var_ident, _)
+                        | SessionPollNotification::UnusedValueRemoved(var_ident, _) => {
+                            comparison_scene_graph.remove(var_ident);
+                        }
+                        SessionPollNotification::StreamedMeshAdded(_) => {
+                            // Only the final values of the compared variant
+                            // are displayed.
+                        }
+                        SessionPollNotification::FinishedSuccessfully => {
+                            *comparison_scene_bounding_box = BoundingBox::union(
+                                comparison_scene_graph
+                                    .iter()
+                                    .filter(|(_, value_node, _)| {
+                                        viewport_draw_used_values || !value_node.used
+                                    })
+                                    .map(|(_, _, mesh_node)| mesh_node.mesh.bounding_box()),
+                            )
+                            .unwrap_or_else(BoundingBox::unit);
+                        }
+                        SessionPollNotification::FinishedWithError(error_message) => {
+                            notifications.push(
+                                time,
+                                NotificationLevel::Error,
+                                format!(
+                                    "Execution of the compared variant finished with error: {}",
+                                    error_message
+                                ),
+                            );
+                        }
+                    });
+
+                    for gpu_mesh_handle in comparison_scene_graph.collect_garbage() {
+                        renderer.remove_scene_mesh(gpu_mesh_handle);
+                    }
+
+                    // The compared variant is drawn with the layers and
+                    // display materials of the edited design, so that both
+                    // halves look alike.
+                    for var_ident in added_scene_vars {
+                        sync_scene_value(
+                            &mut renderer,
+                            comparison_scene_graph,
+                            &session,
+                            var_ident,
+                        );
+                    }
+                    comparison_scene_graph.set_order(stmt_var_idents(comparison_session));
+                }
+
+                if let Some(sweep) = &mut sweep {
+                    sweep.poll(|poll_notification| match poll_notification {
+                        SweepPollNotification::SampleFinished(sample_index, meshes) => {
+                            let gpu_mesh_handles = meshes
+                                .iter()
+                                .map(|mesh| {
+                                    renderer
+                                        .add_scene_mesh(&GpuMesh::from_mesh(mesh))
+                                        .expect("Failed to upload sweep sample mesh")
+                                })
+                                .collect();
+
+                            sweep_thumbnails_requested.push((
+                                sweep_generation,
+                                sample_index,
+                                gpu_mesh_handles,
+                            ));
+                        }
+                        SweepPollNotification::SampleFailed(sample_index) => {
+                            log::warn!("Design sweep sample {} failed", sample_index);
+                        }
+                    });
+                }
+
+                if let Some(optimization) = &mut optimization {
+                    optimization.poll();
+                }
 
                 renderer.poll(|poll_notification| match poll_notification {
                     RendererPollNotification::OffscreenRenderTargetReadReady(handle, read) => {
                         let (width, height) = read.dimensions();
                         let data = read.data();
 
-                        if let Some(mut path) = dirs::picture_dir() {
-                            path.push(format!(
-                                "hurban_selector-{}.png",
-                                chrono::Local::now().format("%Y-%m-%d-%H-%M-%S"),
-                            ));
-
-                            match encode_and_write_png(
-                                &path,
-                                &data,
-                                width,
-                                height,
-                                read.bytes_per_row_unpadded(),
-                                read.bytes_per_row_padded(),
-                            ) {
-                                Ok(()) => {
+                        match pending_reads.remove(handle.id()) {
+                            Some(PendingRead::Screenshot(annotation)) => {
+                                export::save_screenshot(
+                                    time,
+                                    &data,
+                                    width,
+                                    height,
+                                    read.bytes_per_row_unpadded(),
+                                    read.bytes_per_row_padded(),
+                                    annotation,
+                                    &mut notifications,
+                                );
+                            }
+                            Some(PendingRead::Thumbnail(target)) => {
+                                let rgba = unpad_image_rows(
+                                    &data,
+                                    read.bytes_per_row_unpadded(),
+                                    read.bytes_per_row_padded(),
+                                );
+                                thumbnails_ready.push((target, width, height, rgba));
+                            }
+                            Some(PendingRead::ShadowStudy(path)) => {
+                                if let Err(err) = encode_and_write_png(
+                                    &path,
+                                    &data,
+                                    width,
+                                    height,
+                                    read.bytes_per_row_unpadded(),
+                                    read.bytes_per_row_padded(),
+                                ) {
+                                    log::error!("Failed writing shadow study image: {}", err);
+                                    notifications.push(
+                                        time,
+                                        NotificationLevel::Error,
+                                        format!("Failed writing shadow study image: {}", err),
+                                    );
+                                } else if shadow_study_queue.is_empty()
+                                    && !pending_reads.any(|pending_read| {
+                                        matches!(pending_read, PendingRead::ShadowStudy(_))
+                                    })
+                                {
                                     let path_str = path.to_string_lossy();
-                                    log::info!("Screenshot saved in {}", path_str);
+                                    log::info!("Shadow study saved, last image in {}", path_str);
                                     notifications.push(
                                         time,
                                         NotificationLevel::Info,
-                                        format!("Screenshot saved in {}", path_str),
+                                        format!("Shadow study saved, last image in {}", path_str),
                                     );
                                 }
-                                Err(err) => {
-                                    log::error!("Failed writing screenshot: {}", err);
+                            }
+                            Some(PendingRead::Matcap(path)) => {
+                                let rgba = matcap::flip_rows(
+                                    &unpad_image_rows(
+                                        &data,
+                                        read.bytes_per_row_unpadded(),
+                                        read.bytes_per_row_padded(),
+                                    ),
+                                    width,
+                                );
+                                let bytes_per_row = read.bytes_per_row_unpadded();
+                                if let Err(err) = encode_and_write_png(
+                                    &path,
+                                    &rgba,
+                                    width,
+                                    height,
+                                    bytes_per_row,
+                                    bytes_per_row,
+                                ) {
+                                    log::error!("Failed writing matcap image: {}", err);
+                                    notifications.push(
+                                        time,
+                                        NotificationLevel::Error,
+                                        format!("Failed writing matcap image: {}", err),
+                                    );
+                                } else {
+                                    matcaps_baked.push((path, width, height, rgba));
+                                }
+                            }
+                            Some(PendingRead::ViewRender(path)) => {
+                                if let Err(err) = encode_and_write_png(
+                                    &path,
+                                    &data,
+                                    width,
+                                    height,
+                                    read.bytes_per_row_unpadded(),
+                                    read.bytes_per_row_padded(),
+                                ) {
+                                    log::error!("Failed writing view image: {}", err);
                                     notifications.push(
                                         time,
                                         NotificationLevel::Error,
-                                        format!("Failed writing screenshot: {}", err),
+                                        format!("Failed writing view image: {}", err),
+                                    );
+                                } else if view_render_queue.is_empty()
+                                    && !pending_reads.any(|pending_read| {
+                                        matches!(pending_read, PendingRead::ViewRender(_))
+                                    })
+                                {
+                                    let path_str = path.to_string_lossy();
+                                    log::info!("Views rendered, last image in {}", path_str);
+                                    notifications.push(
+                                        time,
+                                        NotificationLevel::Info,
+                                        format!("Views rendered, last image in {}", path_str),
                                     );
                                 }
                             }
-                        } else {
-                            log::error!("Failed to find picture directory");
-                            notifications.push(
-                                time,
-                                NotificationLevel::Warn,
-                                "Failed to find picture directory",
-                            );
+                            Some(PendingRead::RemoteScreenshot(request)) => {
+                                if let RemoteCommand::Screenshot { path, .. } = &request.command {
+                                    let result = encode_and_write_png(
+                                        path,
+                                        &data,
+                                        width,
+                                        height,
+                                        read.bytes_per_row_unpadded(),
+                                        read.bytes_per_row_padded(),
+                                    );
+
+                                    let response = match result {
+                                        Ok(()) => RemoteResponse::Done,
+                                        Err(err) => RemoteResponse::Error(format!(
+                                            "Failed writing screenshot: {}",
+                                            err,
+                                        )),
+                                    };
+                                    request.reply(response);
+                                }
+                            }
+                            Some(PendingRead::PanoramaFace(face)) => {
+                                // The panorama is dropped, if reading any of
+                                // its faces failed
+                                if let Some(current_panorama) = &mut panorama {
+                                    current_panorama.set_face_image(
+                                        face,
+                                        unpad_image_rows(
+                                            &data,
+                                            read.bytes_per_row_unpadded(),
+                                            read.bytes_per_row_padded(),
+                                        ),
+                                    );
+
+                                    if let Some(rgba) = current_panorama.stitch() {
+                                        let panorama_width = current_panorama.width();
+                                        let panorama_height = current_panorama.height();
+                                        panorama = None;
+
+                                        export::save_panorama(
+                                            time,
+                                            &rgba,
+                                            panorama_width,
+                                            panorama_height,
+                                            &mut notifications,
+                                        );
+                                    }
+                                }
+                            }
+                            None => log::warn!("Received an unrequested offscreen render target"),
                         }
 
                         offscreen_render_target_handles_to_remove.push(handle);
                     }
 
                     RendererPollNotification::OffscreenRenderTargetReadFailed(handle) => {
+                        match pending_reads.remove(handle.id()) {
+                            Some(PendingRead::Screenshot(_)) => {
+                                log::error!("Failed to read screenshot");
+                            }
+                            Some(PendingRead::Thumbnail(_)) => log::warn!("Failed to read thumbnail"),
+                            Some(PendingRead::ShadowStudy(path)) => {
+                                log::error!("Failed to read shadow study image {}", path.display());
+                            }
+                            Some(PendingRead::Matcap(path)) => {
+                                log::error!("Failed to read matcap image {}", path.display());
+                            }
+                            Some(PendingRead::ViewRender(path)) => {
+                                log::error!("Failed to read view image {}", path.display());
+                            }
+                            Some(PendingRead::RemoteScreenshot(request)) => {
+                                request.reply(RemoteResponse::Error(String::from(
+                                    "Failed to read screenshot",
+                                )));
+                            }
+                            Some(PendingRead::PanoramaFace(face)) => {
+                                log::error!("Failed to read panorama face {:?}", face);
+                                panorama = None;
+                            }
+                            None => (),
+                        }
                         offscreen_render_target_handles_to_remove.push(handle);
                     }
                 });
@@ -528,7 +1239,58 @@ pub fn init_and_run(options: Options) -> ! {
                     renderer.remove_offscreen_render_target(handle);
                 }
 
+                for (path, width, height, rgba) in matcaps_baked.drain(..) {
+                    renderer.set_matcap_texture_rgba8_unorm(width, height, &rgba);
+
+                    let path_str = path.to_string_lossy();
+                    log::info!("Matcap baked to {}", path_str);
+                    notifications.push(
+                        time,
+                        NotificationLevel::Info,
+                        format!("Matcap baked to {}", path_str),
+                    );
+
+                    preferences.matcap = Some(path);
+                    preferences_changed = true;
+                }
+
+                for (target, width, height, rgba) in thumbnails_ready.drain(..) {
+                    match target {
+                        ThumbnailTarget::Variant(variant_id) => {
+                            let thumbnail =
+                                renderer.add_ui_texture_rgba8_unorm(width, height, &rgba);
+                            if let Some(texture) = variants.set_thumbnail(variant_id, thumbnail) {
+                                ui_textures_to_remove.push(texture);
+                            }
+                        }
+                        ThumbnailTarget::SweepSample(generation, sample_index) => {
+                            // The thumbnail may belong to a sweep that was
+                            // already discarded.
+                            if let Some(sweep) = &mut sweep {
+                                if generation == sweep_generation {
+                                    let thumbnail =
+                                        renderer.add_ui_texture_rgba8_unorm(width, height, &rgba);
+                                    if let Some(texture) = sweep.set_thumbnail(
+                                        sample_index,
+                                        thumbnail,
+                                        width,
+                                        height,
+                                        rgba,
+                                    ) {
+                                        ui_textures_to_remove.push(texture);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Dragging in the viewport draws the box instead of rotating
+                // the camera. Takes effect for the events of the next frame.
+                input_manager.set_viewport_drag_enabled(box_drawing_window_open);
+
                 let input_state = input_manager.input_state();
+                let ui_start = Instant::now();
                 let ui_frame = ui.prepare_frame(&window);
 
                 #[cfg(not(feature = "dist"))]
@@ -539,12 +1301,31 @@ pub fn init_and_run(options: Options) -> ! {
                     }
                 }
 
+                for path in live_link.poll(time, live_link::linked_paths(&session)) {
+                    log::info!("Live linked file changed: {}", path);
+                    notifications.push(
+                        time,
+                        NotificationLevel::Info,
+                        format!("Reloading {}", path),
+                    );
+
+                    live_link_run_pending = true;
+                }
+
+                if live_link_run_pending && !session.interpreter_busy() {
+                    session.interpret();
+                    live_link_run_pending = false;
+                }
+
                 if !session.interpreter_busy() {
                     if input_state.prog_run_requested && session.autorun_delay().is_none() {
                         session.interpret();
                     }
 
-                    if input_state.prog_pop_requested && !session.stmts().is_empty() {
+                    if input_state.prog_pop_requested
+                        && !presentation_mode
+                        && !session.stmts().is_empty()
+                    {
                         session.pop_prog_stmt(time);
                     }
                 }
@@ -563,16 +1344,273 @@ pub fn init_and_run(options: Options) -> ! {
                 camera.zoom(input_state.camera_zoom);
                 camera.zoom_step(input_state.camera_zoom_steps);
 
-                let menu_status = ui_frame.draw_menu_window(
-                    time,
-                    &mut screenshot_modal_open,
-                    &mut about_modal_open,
-                    &mut viewport_draw_mode,
-                    &mut viewport_draw_used_values,
-                    &mut project_status,
-                    &mut session,
-                    &mut notifications,
-                );
+                // Opening and importing edits the project, which is locked in
+                // the presentation mode
+                if !presentation_mode {
+                    if input_state.new_project_requested {
+                        project_status.new_requested = true;
+                    }
+                    if input_state.open_project_requested {
+                        project_status.open_requested = true;
+                    }
+                    if input_state.save_project_requested {
+                        project_status.save_requested = true;
+                    }
+
+                    if let Some(instance_listener) = &instance_listener {
+                        while let Some(path) = instance_listener.try_recv() {
+                            log::info!("Project forwarded: {}", path.to_string_lossy());
+
+                            project_status.open_requested_path = Some(path);
+                            project_status.open_requested = true;
+                        }
+                    }
+
+                    for path in input_manager.dropped_files() {
+                        let extension = path
+                            .extension()
+                            .map(|extension| extension.to_string_lossy().to_lowercase());
+
+                        match extension.as_deref() {
+                            Some(project::EXTENSION) | Some(bundle::EXTENSION) => {
+                                log::info!("Project dropped: {}", path.to_string_lossy());
+
+                                project_status.open_requested_path = Some(path.clone());
+                                project_status.open_requested = true;
+                            }
+                            Some("obj") => {
+                                log::info!("OBJ file dropped: {}", path.to_string_lossy());
+
+                                match import_obj_file(time, &mut session, path) {
+                                    Ok(()) => {
+                                        notifications.push(
+                                            time,
+                                            NotificationLevel::Info,
+                                            format!("Importing {}", path.to_string_lossy()),
+                                        );
+
+                                        project_status.changed_since_last_save = true;
+                                        change_window_title(&window, &project_status);
+                                    }
+                                    Err(message) => {
+                                        notifications.push(time, NotificationLevel::Warn, message);
+                                    }
+                                }
+                            }
+                            _ => {
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Warn,
+                                    format!(
+                                        "Can't open {}, only .hurban projects and .obj files \
+                                         can be dropped",
+                                        path.to_string_lossy(),
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                }
+
+                if let Some(remote_server) = &remote_server {
+                    while let Some(request) = remote_server.try_recv() {
+                        let response = match &request.command {
+                            RemoteCommand::GetFuncs => {
+                                Some(RemoteResponse::Funcs(remote::funcs(&session)))
+                            }
+                            RemoteCommand::GetStmts => {
+                                Some(RemoteResponse::Stmts(remote::stmts(&session)))
+                            }
+                            RemoteCommand::PushStmt { .. }
+                            | RemoteCommand::SetStmt { .. }
+                            | RemoteCommand::PopStmt => {
+                                // The presentation mode locks the project
+                                // for remote clients too
+                                let result = if presentation_mode {
+                                    Err(String::from(
+                                        "Can't edit the pipeline in the presentation mode",
+                                    ))
+                                } else {
+                                    apply_remote_edit(time, &mut session, &request.command)
+                                };
+
+                                match result {
+                                    Ok(()) => {
+                                        project_status.changed_since_last_save = true;
+                                        change_window_title(&window, &project_status);
+                                        Some(RemoteResponse::Done)
+                                    }
+                                    Err(message) => Some(RemoteResponse::Error(message)),
+                                }
+                            }
+                            RemoteCommand::Screenshot { width, height, .. }
+                                if *width == 0 || *height == 0 =>
+                            {
+                                Some(RemoteResponse::Error(String::from(
+                                    "Screenshot dimensions must not be zero",
+                                )))
+                            }
+                            RemoteCommand::Screenshot { path, .. }
+                                if !remote::is_confined_path(path) =>
+                            {
+                                Some(RemoteResponse::Error(String::from(
+                                    "Screenshot path must be relative and must not leave the working directory",
+                                )))
+                            }
+                            RemoteCommand::Interpret
+                            | RemoteCommand::ExportObj
+                            | RemoteCommand::Screenshot { .. } => None,
+                        };
+
+                        match response {
+                            Some(response) => request.reply(response),
+                            None => remote_requests_awaiting_run.push(request),
+                        }
+                    }
+                }
+
+                let detached_viewport_close_requested = match &mut detached_viewport {
+                    Some(detached_viewport) => {
+                        detached_viewport.process_input(&mut renderer, &camera)
+                    }
+                    None => false,
+                };
+
+                let presentation_status = if presentation_mode {
+                    ui_frame.draw_presentation_window(
+                        &variants,
+                        &session,
+                        &mut viewport_draw_mode,
+                        &mut viewport_labels,
+                    )
+                } else {
+                    PresentationStatus::default()
+                };
+
+                let mut menu_status = if presentation_mode {
+                    MenuStatus {
+                        reset_viewport: presentation_status.reset_viewport,
+                        ..MenuStatus::default()
+                    }
+                } else {
+                    ui_frame.draw_menu_window(
+                        time,
+                        &mut screenshot_modal_open,
+                        &mut sweep_window_open,
+                        &mut optimization_window_open,
+                        &mut sun_window_open,
+                        &mut calibration_window_open,
+                        &mut reference_images_window_open,
+                        &mut placement_window_open,
+                        &mut box_drawing_window_open,
+                        &mut sketch_window_open,
+                        &mut find_replace_window_open,
+                        &mut help_window_open,
+                        &mut profiler_window_open,
+                        &mut library_window_open,
+                        &mut examples_window_open,
+                        &mut layers_window_open,
+                        &mut history_window_open,
+                        &mut clip_box_window_open,
+                        &mut views_window_open,
+                        &mut preferences_window_open,
+                        &mut about_modal_open,
+                        &mut viewport_draw_mode,
+                        &mut viewport_draw_used_values,
+                        &mut viewport_labels,
+                        &mut viewport_stereo,
+                        &mut viewport_explode_factor,
+                        detached_viewport.is_some(),
+                        project_unit,
+                        &mut project_status,
+                        &mut session,
+                        &mut notifications,
+                    )
+                };
+
+                if let Some(example) = example_modal {
+                    match ui_frame.draw_example_modal(example) {
+                        ExampleModalResult::SaveCopy(path) => {
+                            menu_status.save_path = Some(path);
+                            example_modal = None;
+                        }
+                        ExampleModalResult::Explore => example_modal = None,
+                        ExampleModalResult::Nothing => (),
+                    }
+                }
+
+                if menu_status.toggle_detached_viewport && detached_viewport.is_none() {
+                    match DetachedViewport::open(event_loop_window_target, &mut renderer, &camera) {
+                        Ok(new_detached_viewport) => {
+                            log::info!("Detached viewport opened");
+                            detached_viewport = Some(new_detached_viewport);
+                        }
+                        Err(err) => {
+                            log::error!("Failed to open detached viewport window: {}", err);
+                            notifications.push(
+                                time,
+                                NotificationLevel::Error,
+                                format!("Failed to open detached viewport: {}", err),
+                            );
+                        }
+                    }
+                } else if menu_status.toggle_detached_viewport || detached_viewport_close_requested
+                {
+                    if let Some(detached_viewport) = detached_viewport.take() {
+                        log::info!("Detached viewport closed");
+                        detached_viewport.destroy(&mut renderer);
+                    }
+                }
+
+                if menu_status.presentation_mode {
+                    log::info!("Entering presentation mode");
+
+                    presentation_mode = true;
+
+                    // The editing windows stay closed after leaving the
+                    // presentation mode
+                    sweep_window_open = false;
+                    optimization_window_open = false;
+                    sun_window_open = false;
+                    calibration_window_open = false;
+                    reference_images_window_open = false;
+                    placement_window_open = false;
+                    box_drawing_window_open = false;
+                    sketch_window_open = false;
+                    find_replace_window_open = false;
+                    help_window_open = false;
+                    profiler_window_open = false;
+                    library_window_open = false;
+                    examples_window_open = false;
+                    layers_window_open = false;
+                    history_window_open = false;
+                    clip_box_window_open = false;
+                    views_window_open = false;
+                    preferences_window_open = false;
+                    statistics_window_open = false;
+                    session.set_focused_var(None);
+
+                    set_fullscreen(&window, true, &mut windowed_window_state);
+                }
+
+                if presentation_mode
+                    && (presentation_status.exit || input_state.exit_presentation_requested)
+                {
+                    log::info!("Leaving presentation mode");
+
+                    presentation_mode = false;
+                    set_fullscreen(&window, window_fullscreen, &mut windowed_window_state);
+                }
+
+                if input_state.fullscreen_toggle_requested {
+                    window_fullscreen = !window_fullscreen;
+                    log::info!("Toggled window fullscreen to {}", window_fullscreen);
+
+                    // The presentation mode stays fullscreen until it is left
+                    if !presentation_mode {
+                        set_fullscreen(&window, window_fullscreen, &mut windowed_window_state);
+                    }
+                }
 
                 ui_frame.draw_subdigital_logo(
                     tex_subdigital_logo,
@@ -580,213 +1618,1743 @@ pub fn init_and_run(options: Options) -> ! {
                     height_subdigital_logo,
                 );
 
-                if menu_status.viewport_draw_used_values_changed {
-                    scene_bounding_box = BoundingBox::union(
-                        scene_meshes
-                            .values()
-                            .filter(|(used, _)| viewport_draw_used_values || !used)
-                            .map(|(_, mesh)| mesh.bounding_box()),
+                let variants_status = if presentation_mode {
+                    VariantsStatus {
+                        restore: presentation_status.restore,
+                        ..VariantsStatus::default()
+                    }
+                } else {
+                    ui_frame.draw_variants_window(
+                        time,
+                        &variants,
+                        &session,
+                        comparison.as_ref().map(|comparison| comparison.variant_id),
+                        comparison
+                            .as_ref()
+                            .map_or(false, |comparison| comparison.session.interpreter_busy()),
+                        &mut notifications,
                     )
-                    .unwrap_or_else(BoundingBox::unit);
+                };
 
-                    ground_plane_mesh = compute_ground_plane_mesh(&scene_bounding_box);
-                    ground_plane_mesh_bounding_box = ground_plane_mesh.bounding_box();
-                    renderer.remove_scene_mesh(
-                        ground_plane_gpu_mesh_handle
-                            .take()
-                            .expect("Ground plane must always be present"),
-                    );
-                    ground_plane_gpu_mesh_handle = Some(
-                        renderer
-                            .add_scene_mesh(&GpuMesh::from_mesh(&ground_plane_mesh))
-                            .expect("Failed to add ground plane mesh"),
-                    );
+                if let Some(name) = variants_status.capture {
+                    let variant_id = variants.capture(name, session.stmts().to_vec());
+                    variant_thumbnail_requested = Some(variant_id);
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
                 }
 
-                if let Some(prevent_overwrite_modal_trigger) = menu_status.prevent_overwrite_modal {
-                    project_status.prevent_overwrite_status = match prevent_overwrite_modal_trigger
+                if let Some(variant_id) = variants_status.restore {
+                    let stmts = variants
+                        .get(variant_id)
+                        .expect("Restored variant must exist")
+                        .stmts()
+                        .to_vec();
+                    session.set_prog_stmts(time, stmts);
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                if let Some(variant_id) = variants_status.remove {
+                    let variant = variants
+                        .remove(variant_id)
+                        .expect("Removed variant must exist");
+                    if let Some(thumbnail) = variant.thumbnail() {
+                        ui_textures_to_remove.push(thumbnail);
+                    }
+
+                    if comparison
+                        .as_ref()
+                        .map_or(false, |comparison| comparison.variant_id == variant_id)
                     {
-                        OverwriteModalTrigger::NewProject => {
-                            Some(crate::project::NextAction::NewProject)
+                        if let Some(comparison) = comparison.take() {
+                            comparison.destroy(&mut renderer);
                         }
-                        OverwriteModalTrigger::OpenProject => {
-                            Some(crate::project::NextAction::OpenProject)
+                    }
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                if let Some(variant_id) = variants_status.compare {
+                    let stmts = variants
+                        .get(variant_id)
+                        .expect("Compared variant must exist")
+                        .stmts()
+                        .to_vec();
+
+                    // Re-use the session of the previous comparison, if any,
+                    // so that the interpreter can keep cached results of
+                    // statements the two variants have in common.
+                    match &mut comparison {
+                        Some(comparison) => {
+                            comparison.variant_id = variant_id;
+                            comparison.session.set_prog_stmts(time, stmts);
+                        }
+                        None => {
+                            comparison = Some(Comparison::new(
+                                time,
+                                variant_id,
+                                stmts,
+                                viewport_explode_factor,
+                            ));
                         }
                     }
                 }
 
-                if menu_status.new_project {
-                    scene_meshes.clear();
+                if variants_status.compare_stop {
+                    if let Some(comparison) = comparison.take() {
+                        comparison.destroy(&mut renderer);
+                    }
+                }
 
-                    for (_, (_, gpu_mesh_handle)) in scene_gpu_mesh_handles.drain() {
-                        renderer.remove_scene_mesh(gpu_mesh_handle);
+                let sweep_status = ui_frame.draw_sweep_window(
+                    time,
+                    &mut sweep_window_open,
+                    &session,
+                    sweep.as_ref(),
+                    &mut notifications,
+                    preferences.number_format,
+                );
+
+                if sweep_status.cancel {
+                    if let Some(sweep) = sweep.take() {
+                        ui_textures_to_remove.extend(sweep.thumbnails());
                     }
+                }
 
-                    scene_bounding_box = BoundingBox::union(
-                        scene_meshes
-                            .values()
-                            .filter(|(used, _)| viewport_draw_used_values || !used)
-                            .map(|(_, mesh)| mesh.bounding_box()),
-                    )
-                    .unwrap_or_else(BoundingBox::unit);
+                if let Some(axes) = sweep_status.start {
+                    if let Some(sweep) = sweep.take() {
+                        ui_textures_to_remove.extend(sweep.thumbnails());
+                    }
 
-                    ground_plane_mesh = compute_ground_plane_mesh(&scene_bounding_box);
-                    ground_plane_mesh_bounding_box = ground_plane_mesh.bounding_box();
-                    renderer.remove_scene_mesh(
-                        ground_plane_gpu_mesh_handle
-                            .take()
-                            .expect("Ground plane must always be present"),
-                    );
-                    ground_plane_gpu_mesh_handle = Some(
-                        renderer
-                            .add_scene_mesh(&GpuMesh::from_mesh(&ground_plane_mesh))
-                            .expect("Failed to add ground plane mesh"),
-                    );
+                    sweep_generation += 1;
+                    sweep = Some(Sweep::new(session.stmts(), axes, session.stmt_timeout()));
+                }
 
-                    let current_autorun_delay = session.autorun_delay();
-                    session = Session::new();
-                    session.set_autorun_delay(current_autorun_delay);
+                if let Some(sample_index) = sweep_status.keep_as_variant {
+                    let sample = &sweep
+                        .as_ref()
+                        .expect("Sweep must exist to keep its sample")
+                        .samples()[sample_index];
 
-                    project_status.path = None;
-                    project_status.changed_since_last_save = false;
+                    let name = sample
+                        .values()
+                        .iter()
+                        .map(|value| format!("{:.3}", value))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let variant_id =
+                        variants.capture(format!("Sweep [{}]", name), sample.stmts().to_vec());
+
+                    if let Some((width, height, rgba)) = sample.thumbnail_image() {
+                        let thumbnail = renderer.add_ui_texture_rgba8_unorm(*width, *height, rgba);
+                        variants.set_thumbnail(variant_id, thumbnail);
+                    }
+
+                    notifications.push(
+                        time,
+                        NotificationLevel::Info,
+                        format!("Kept sweep sample [{}] as a variant.", name),
+                    );
 
+                    project_status.changed_since_last_save = true;
                     change_window_title(&window, &project_status);
                 }
 
-                if let Some(save_path) = menu_status.save_path {
-                    log::info!("Saving project at {}", save_path.to_string_lossy());
+                if let Some([x, y]) = input_state.viewport_hover {
+                    if placement_window_open {
+                        placement_point =
+                            camera.screen_ray(x, y).and_then(|(origin, direction)| {
+                                compute_placement_point(
+                                    &scene_graph,
+                                    viewport_draw_used_values,
+                                    placement_on_surfaces,
+                                    preferences.snapping,
+                                    &origin,
+                                    &direction,
+                                )
+                            });
+                        placement_preview_dirty = true;
+                    }
 
-                    let stmts = session.stmts().to_vec();
-                    let project = project::Project { version: 1, stmts };
+                    if sketch_window_open {
+                        sketch.set_cursor(camera.screen_ray(x, y).and_then(
+                            |(origin, direction)| {
+                                compute_placement_point(
+                                    &scene_graph,
+                                    viewport_draw_used_values,
+                                    false,
+                                    preferences.snapping,
+                                    &origin,
+                                    &direction,
+                                )
+                            },
+                        ));
+                        sketch_preview_dirty = true;
+                    }
+                }
 
-                    match project::save(&save_path, project) {
-                        Ok(save_path) => {
-                            let save_path = save_path
-                                .as_os_str()
-                                .to_str()
-                                .expect("Failed to convert save path to str.");
+                if box_drawing_window_open {
+                    let drag = input_state.viewport_drag.or(input_state.viewport_drag_end);
+                    if let Some(([start_x, start_y], [end_x, end_y])) = drag {
+                        let ground_point = |x, y| {
+                            camera.screen_ray(x, y).and_then(|(origin, direction)| {
+                                compute_placement_point(
+                                    &scene_graph,
+                                    viewport_draw_used_values,
+                                    false,
+                                    preferences.snapping,
+                                    &origin,
+                                    &direction,
+                                )
+                            })
+                        };
+
+                        if let (Some(first_corner), Some(second_corner)) =
+                            (ground_point(start_x, start_y), ground_point(end_x, end_y))
+                        {
+                            box_drawing.drag_base(first_corner, second_corner);
+                            box_drawing_preview_dirty = true;
+                        }
+                    }
 
-                            project_status.save(&save_path);
+                    if input_state.viewport_drag_end.is_some() {
+                        box_drawing.finish_base();
+                        box_drawing_preview_dirty = true;
+                    }
+
+                    if let Some([x, y]) = input_state.viewport_hover {
+                        let height = camera.screen_ray(x, y).and_then(|(origin, direction)| {
+                            box_drawing.height_for_ray(&origin, &direction)
+                        });
+
+                        if let Some(height) = height {
+                            box_drawing.set_height(snap_value(
+                                preferences.snapping,
+                                Dimension::Length,
+                                height,
+                            ));
+                            box_drawing_preview_dirty = true;
+                        }
+                    }
+                }
+
+                if let Some([x, y]) = input_state.viewport_click {
+                    if placement_window_open {
+                        let point = camera.screen_ray(x, y).and_then(|(origin, direction)| {
+                            compute_placement_point(
+                                &scene_graph,
+                                viewport_draw_used_values,
+                                placement_on_surfaces,
+                                preferences.snapping,
+                                &origin,
+                                &direction,
+                            )
+                        });
+
+                        if let (Some((path, _)), Some(point)) = (&placement_library, point) {
+                            let rotation = snap_value(
+                                preferences.snapping,
+                                Dimension::Angle,
+                                placement_rotation,
+                            );
+
+                            match place_library_mesh(time, &mut session, path, &point, rotation) {
+                                Ok(()) => {
+                                    project_status.changed_since_last_save = true;
+                                    change_window_title(&window, &project_status);
+                                }
+                                Err(message) => {
+                                    notifications.push(time, NotificationLevel::Warn, message);
+                                }
+                            }
+                        }
+                    } else if box_drawing_window_open {
+                        if box_drawing.pulling_height() {
+                            match add_drawn_box(time, &mut session, &box_drawing) {
+                                Ok(()) => {
+                                    box_drawing.reset();
+                                    box_drawing_preview_dirty = true;
+
+                                    project_status.changed_since_last_save = true;
+                                    change_window_title(&window, &project_status);
+                                }
+                                Err(message) => {
+                                    notifications.push(time, NotificationLevel::Warn, message);
+                                }
+                            }
+                        }
+                    } else if sketch_window_open {
+                        let point = camera.screen_ray(x, y).and_then(|(origin, direction)| {
+                            compute_placement_point(
+                                &scene_graph,
+                                viewport_draw_used_values,
+                                false,
+                                preferences.snapping,
+                                &origin,
+                                &direction,
+                            )
+                        });
+
+                        if let Some(point) = point {
+                            let (_, visible_radius) = camera.visible_sphere();
+                            let close_distance = visible_radius * sketch::CLOSE_DISTANCE_FRACTION;
+
+                            if sketch.add_point(&point, close_distance) {
+                                match add_sketched_footprint(
+                                    time,
+                                    &mut session,
+                                    &sketch,
+                                    snap_value(
+                                        preferences.snapping,
+                                        Dimension::Length,
+                                        sketch_height,
+                                    ),
+                                ) {
+                                    Ok(()) => {
+                                        sketch.reset();
+
+                                        project_status.changed_since_last_save = true;
+                                        change_window_title(&window, &project_status);
+                                    }
+                                    Err(message) => {
+                                        notifications.push(time, NotificationLevel::Warn, message);
+                                    }
+                                }
+                            }
+                            sketch_preview_dirty = true;
+                        }
+                    } else if calibration_window_open {
+                        let picked = camera.screen_ray(x, y).and_then(|(origin, direction)| {
+                            pick_scene_mesh(
+                                &scene_graph,
+                                viewport_draw_used_values,
+                                &origin,
+                                &direction,
+                            )
+                            .map(|(var_ident, distance)| (var_ident, origin + direction * distance))
+                        });
+
+                        if let Some((var_ident, point)) = picked {
+                            scale_calibration.pick(var_ident, point);
+                        }
+                    }
+                }
+
+                if !calibration_window_open {
+                    scale_calibration.reset();
+                }
+
+                let calibration_target_name = scale_calibration
+                    .target()
+                    .and_then(|var_ident| session.var_name_for_ident(var_ident));
+                let calibration_status = ui_frame.draw_calibration_window(
+                    &mut calibration_window_open,
+                    &scale_calibration,
+                    calibration_target_name,
+                    project_unit,
+                    &mut calibration_real_distance,
+                );
+
+                if calibration_status.reset {
+                    scale_calibration.reset();
+                }
+
+                if let (Some(factor), Some(var_ident)) =
+                    (calibration_status.apply, scale_calibration.target())
+                {
+                    match apply_scale_calibration(time, &mut session, var_ident, factor) {
+                        Ok(()) => {
+                            project_status.changed_since_last_save = true;
                             change_window_title(&window, &project_status);
+
                             notifications.push(
                                 time,
                                 NotificationLevel::Info,
-                                format!("Project saved as {}", &save_path),
+                                format!("Scale calibrated, mesh scaled by {}", factor),
                             );
                         }
-                        Err(err) => {
-                            log::error!("{}", err);
-                            project_status.error = Some(err);
+                        Err(message) => {
+                            notifications.push(time, NotificationLevel::Warn, message);
                         }
                     }
-                }
 
-                if let Some(open_path) = menu_status.open_path {
-                    log::info!("Opening new project at {}", open_path.to_string_lossy());
+                    scale_calibration.reset();
+                }
 
-                    match project::open(&open_path) {
-                        Ok(project) => {
-                            scene_meshes.clear();
+                let reference_images_status = ui_frame.draw_reference_images_window(
+                    &mut reference_images_window_open,
+                    &mut reference_images,
+                    project_unit,
+                );
 
-                            for (_, gpu_mesh_handle) in scene_gpu_mesh_handles.drain() {
-                                renderer.remove_scene_mesh(gpu_mesh_handle.1);
-                            }
+                if let Some((path, placement)) = reference_images_status.add {
+                    let reference_image =
+                        ReferenceImage::new(path.to_string_lossy().into_owned(), placement);
 
-                            scene_bounding_box = BoundingBox::union(
-                                scene_meshes.values().map(|(_, mesh)| mesh.bounding_box()),
-                            )
-                            .unwrap_or_else(BoundingBox::unit);
+                    match add_scene_reference_image(&mut renderer, &reference_image) {
+                        Ok(gpu_handle) => {
+                            reference_images.push(reference_image);
+                            reference_image_gpu_handles.push(Some(gpu_handle));
 
-                            ground_plane_mesh = compute_ground_plane_mesh(&scene_bounding_box);
-                            ground_plane_mesh_bounding_box = ground_plane_mesh.bounding_box();
-                            renderer.remove_scene_mesh(
-                                ground_plane_gpu_mesh_handle
-                                    .take()
-                                    .expect("Ground plane must always be present"),
-                            );
-                            ground_plane_gpu_mesh_handle = Some(
-                                renderer
-                                    .add_scene_mesh(&GpuMesh::from_mesh(&ground_plane_mesh))
-                                    .expect("Failed to add ground plane mesh"),
+                            project_status.changed_since_last_save = true;
+                            change_window_title(&window, &project_status);
+                        }
+                        Err(err) => {
+                            log::error!("Failed to load reference image: {}", err);
+                            notifications.push(
+                                time,
+                                NotificationLevel::Error,
+                                format!("Failed to load reference image: {}", err),
                             );
+                        }
+                    }
+                }
 
-                            let current_autorun_delay = session.autorun_delay();
-                            session = Session::new();
-                            session.set_autorun_delay(current_autorun_delay);
+                if let Some(image_index) = reference_images_status.changed {
+                    let reference_image = &reference_images[image_index];
+                    if let Some((aspect_ratio, gpu_handle)) =
+                        &reference_image_gpu_handles[image_index]
+                    {
+                        renderer.set_scene_image_placement(
+                            gpu_handle,
+                            &reference_image.corners(*aspect_ratio),
+                            reference_image.opacity,
+                        );
+                    }
 
-                            for stmt in project.stmts {
-                                session.push_prog_stmt(time, stmt);
-                            }
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
 
-                            project_status.path = Some(PathBuf::from(&open_path));
-                            project_status.changed_since_last_save = false;
+                if let Some(image_index) = reference_images_status.remove {
+                    reference_images.remove(image_index);
+                    if let Some((_, gpu_handle)) = reference_image_gpu_handles.remove(image_index) {
+                        renderer.remove_scene_image(gpu_handle);
+                    }
 
-                            change_window_title(&window, &project_status);
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                let placement_status = ui_frame.draw_placement_window(
+                    &mut placement_window_open,
+                    placement_library.as_ref().map(|(path, _)| path.as_str()),
+                    &mut placement_rotation,
+                    &mut placement_on_surfaces,
+                );
 
+                if let Some(path) = &placement_status.choose {
+                    let path = path.to_string_lossy().into_owned();
+                    match placement::load_library_mesh(&path) {
+                        Ok(mesh) => {
+                            placement_library = Some((path, mesh));
+                        }
+                        Err(message) => {
+                            log::error!("Failed to load object to place: {}", message);
                             notifications.push(
                                 time,
-                                NotificationLevel::Info,
-                                format!("Opened project {}", &open_path.to_string_lossy()),
+                                NotificationLevel::Error,
+                                format!("Failed to load object to place: {}", message),
                             );
                         }
-                        Err(err) => {
-                            log::error!("{}", err);
-                            project_status.error = Some(err);
+                    }
+                }
+
+                if placement_status.changed || placement_status.choose.is_some() {
+                    placement_preview_dirty = true;
+                }
+
+                if !placement_window_open {
+                    placement_point = None;
+                    placement_preview_dirty |= placement_preview_gpu_mesh_handle.is_some();
+                }
+
+                if placement_preview_dirty {
+                    if let Some(handle) = placement_preview_gpu_mesh_handle.take() {
+                        renderer.remove_scene_mesh(handle);
+                    }
+
+                    if let (Some((_, library_mesh)), Some(point)) =
+                        (&placement_library, placement_point)
+                    {
+                        let rotation =
+                            snap_value(preferences.snapping, Dimension::Angle, placement_rotation);
+                        let preview_mesh = placement::placed_mesh(library_mesh, &point, rotation);
+
+                        match renderer.add_scene_mesh(&GpuMesh::from_mesh(&preview_mesh)) {
+                            Ok(handle) => placement_preview_gpu_mesh_handle = Some(handle),
+                            Err(err) => log::error!("Failed to add placement preview: {:?}", err),
                         }
-                    };
+                    }
+
+                    placement_preview_dirty = false;
                 }
 
-                if project_status.error.is_some()
-                    && ui_frame.draw_error_modal(&project_status.error)
-                {
-                    project_status.error = None;
+                let box_drawing_reset = ui_frame.draw_box_drawing_window(
+                    &mut box_drawing_window_open,
+                    &box_drawing,
+                    project_unit,
+                );
+
+                if box_drawing_reset || !box_drawing_window_open {
+                    box_drawing_preview_dirty |= box_drawing.dimensions().is_some();
+                    box_drawing.reset();
                 }
 
-                let window_size = window.inner_size();
-                let take_screenshot = ui_frame.draw_screenshot_window(
-                    &mut screenshot_modal_open,
-                    &mut screenshot_options,
-                    window_size.width,
-                    window_size.height,
+                if box_drawing_preview_dirty {
+                    if let Some(handle) = box_drawing_preview_gpu_mesh_handle.take() {
+                        renderer.remove_scene_mesh(handle);
+                    }
+
+                    if let Some(preview_mesh) = box_drawing.mesh() {
+                        match renderer.add_scene_mesh(&GpuMesh::from_mesh(&preview_mesh)) {
+                            Ok(handle) => box_drawing_preview_gpu_mesh_handle = Some(handle),
+                            Err(err) => log::error!("Failed to add box drawing preview: {:?}", err),
+                        }
+                    }
+
+                    box_drawing_preview_dirty = false;
+                }
+
+                let sketch_status = ui_frame.draw_sketch_window(
+                    &mut sketch_window_open,
+                    &sketch,
+                    &mut sketch_height,
+                    project_unit,
                 );
 
-                let (tex_logos, width_logos, height_logos) = match options.theme {
-                    Theme::Light => (tex_logos_black, width_logos_black, height_logos_black),
-                    Theme::Dark => (tex_logos_white, width_logos_white, height_logos_white),
-                };
-                ui_frame.draw_about_window(
-                    &mut about_modal_open,
-                    tex_scheme,
-                    width_scheme,
-                    height_scheme,
-                    tex_logos,
-                    width_logos,
-                    height_logos,
-                );
+                if sketch_status.finish {
+                    match add_sketched_footprint(
+                        time,
+                        &mut session,
+                        &sketch,
+                        snap_value(preferences.snapping, Dimension::Length, sketch_height),
+                    ) {
+                        Ok(()) => {
+                            sketch.reset();
+
+                            project_status.changed_since_last_save = true;
+                            change_window_title(&window, &project_status);
+                        }
+                        Err(message) => {
+                            notifications.push(time, NotificationLevel::Warn, message);
+                        }
+                    }
+                    sketch_preview_dirty = true;
+                }
+
+                if sketch_status.remove_last_point {
+                    sketch.remove_last_point();
+                    sketch_preview_dirty = true;
+                }
+
+                if sketch_status.reset || !sketch_window_open {
+                    sketch_preview_dirty |= sketch_preview_gpu_mesh_handle.is_some();
+                    sketch.reset();
+                }
+
+                sketch_preview_dirty |= sketch_status.height_changed;
+
+                if sketch_preview_dirty {
+                    if let Some(handle) = sketch_preview_gpu_mesh_handle.take() {
+                        renderer.remove_scene_mesh(handle);
+                    }
+
+                    let preview_height =
+                        snap_value(preferences.snapping, Dimension::Length, sketch_height);
+                    if let Some(preview_mesh) = sketch.mesh(preview_height) {
+                        match renderer.add_scene_mesh(&GpuMesh::from_mesh(&preview_mesh)) {
+                            Ok(handle) => sketch_preview_gpu_mesh_handle = Some(handle),
+                            Err(err) => log::error!("Failed to add sketch preview: {:?}", err),
+                        }
+                    }
+
+                    sketch_preview_dirty = false;
+                }
+
+                let find_replace_status =
+                    ui_frame.draw_find_replace_window(&mut find_replace_window_open, &session);
+
+                if let Some(replacements) = find_replace_status.replace {
+                    match replace_param_values(time, &mut session, &replacements) {
+                        Ok(()) => {
+                            project_status.changed_since_last_save = true;
+                            change_window_title(&window, &project_status);
+                        }
+                        Err(message) => {
+                            notifications.push(time, NotificationLevel::Warn, message);
+                        }
+                    }
+                }
+
+                ui_frame.draw_help_window(&mut help_window_open, &session);
+                ui_frame.draw_profiler_window(&mut profiler_window_open, &profiler);
+
+                let library_insert = ui_frame.draw_library_window(
+                    &mut library_window_open,
+                    library::ASSETS,
+                    session.interpreter_busy(),
+                );
+
+                let example_to_open = ui_frame.draw_examples_window(
+                    &mut examples_window_open,
+                    examples::EXAMPLES,
+                    session.interpreter_busy(),
+                );
+
+                if let Some(example) = example_to_open {
+                    if project_status.changed_since_last_save
+                        && project_status.prevent_overwrite_status.is_none()
+                    {
+                        project_status.prevent_overwrite_status =
+                            Some(project::NextAction::OpenExample(example));
+                    } else {
+                        project_status.example_requested = Some(example);
+                    }
+                }
+
+                if let Some(asset) = library_insert {
+                    match insert_library_asset(time, &mut session, asset, project_unit) {
+                        Ok(()) => {
+                            project_status.changed_since_last_save = true;
+                            change_window_title(&window, &project_status);
+                        }
+                        Err(message) => {
+                            notifications.push(time, NotificationLevel::Warn, message);
+                        }
+                    }
+                }
+
+                let layers_changed =
+                    ui_frame.draw_layers_window(&mut layers_window_open, session.layers_mut());
+
+                // The history always opens at the latest edit
+                if !history_window_open {
+                    history_event_index = u32::MAX;
+                }
+                if let Some(stmts) = ui_frame.draw_history_window(
+                    time,
+                    &mut history_window_open,
+                    &mut history_event_index,
+                    &session,
+                    &mut notifications,
+                ) {
+                    session.set_prog_stmts(time, stmts);
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                ui_frame.draw_clip_box_window(
+                    &mut clip_box_window_open,
+                    &mut clip_box,
+                    &scene_bounding_box,
+                );
+
+                let views_status = ui_frame.draw_views_window(
+                    time,
+                    &mut views_window_open,
+                    &mut camera_bookmarks,
+                    &camera.view(),
+                    &mut notifications,
+                );
+
+                if views_status.changed {
+                    project_status.changed_since_last_save = true;
+                }
+
+                if let Some(view) = views_status.go_to {
+                    camera_interpolation = None;
+                    camera.set_view(&view);
+                }
+
+                if let Some(directory) = views_status.render_all {
+                    view_render_queue = camera_bookmarks
+                        .iter()
+                        .enumerate()
+                        .map(|(index, bookmark)| {
+                            let path =
+                                directory.join(bookmarks::render_file_name(index, &bookmark.name));
+                            (bookmark.view, path)
+                        })
+                        .collect();
+
+                    // Images are taken from the back of the queue
+                    view_render_queue.reverse();
+                }
+
+                let ground_plane_options = preferences.ground_plane;
+                let matcap = preferences.matcap.clone();
+                let shadow_quality = preferences.shadow_quality;
+                let autorun = preferences.autorun;
+                let timeout = preferences.timeout;
+                preferences_changed |= ui_frame.draw_preferences_window(
+                    &mut preferences_window_open,
+                    &mut preferences,
+                    project_unit,
+                    &mut statistics_window_open,
+                    &gpu_adapters,
+                );
+
+                if preferences.ground_plane != ground_plane_options {
+                    renderer.set_flat_material_color(preferences.ground_plane.material_color());
+
+                    rebuild_ground_plane(
+                        &mut renderer,
+                        &preferences.ground_plane,
+                        &scene_bounding_box,
+                        &mut ground_plane_gpu_mesh_handle,
+                    );
+                }
+
+                if preferences.matcap != matcap {
+                    match &preferences.matcap {
+                        Some(path) => {
+                            if let Err(err) = set_matcap_from_file(&mut renderer, path) {
+                                log::error!("Failed to load matcap {}: {}", path.display(), err);
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Error,
+                                    format!("Failed to load matcap: {}", err),
+                                );
+                                preferences.matcap = matcap;
+                            }
+                        }
+                        None => renderer.reset_matcap_texture(),
+                    }
+                }
+
+                if preferences.shadow_quality != shadow_quality {
+                    renderer.set_shadow_quality(preferences.shadow_quality);
+                }
+
+                if preferences.timeout != timeout {
+                    session.set_stmt_timeout(preferences.timeout.stmt_timeout());
+                }
+
+                if preferences_changed && !preferences_window_open {
+                    if let Err(err) = preferences::save(&preferences) {
+                        log::error!("Failed to save preferences: {}", err);
+                        notifications.push(
+                            time,
+                            NotificationLevel::Error,
+                            format!("Failed to save preferences: {}", err),
+                        );
+                    }
+
+                    preferences_changed = false;
+                }
+
+                if preferences.statistics == StatisticsConsent::Undecided && crash_bundle.is_none()
+                {
+                    if let Some(consent) = ui_frame.draw_statistics_consent_modal() {
+                        preferences.statistics = if consent {
+                            StatisticsConsent::Granted
+                        } else {
+                            StatisticsConsent::Denied
+                        };
+                        if let Err(err) = preferences::save(&preferences) {
+                            log::error!("Failed to save preferences: {}", err);
+                        }
+                    }
+                }
+
+                match (preferences.statistics, statistics.is_some()) {
+                    (StatisticsConsent::Granted, false) => {
+                        statistics = load_statistics(preferences.statistics);
+                    }
+                    (StatisticsConsent::Undecided, true) | (StatisticsConsent::Denied, true) => {
+                        statistics = None;
+                        if let Err(err) = statistics::delete() {
+                            log::error!("Failed to delete usage statistics: {}", err);
+                        }
+                    }
+                    _ => (),
+                }
+
+                if ui_frame.draw_statistics_window(&mut statistics_window_open, statistics.as_ref())
+                {
+                    if let Some(statistics) = &mut statistics {
+                        *statistics = Statistics::default();
+                        save_statistics(statistics);
+                    }
+                }
+
+                let sun_status = ui_frame.draw_sun_window(
+                    time,
+                    &mut sun_window_open,
+                    &mut sun_options,
+                    &mut geolocation,
+                    &mut viewport_north_arrow,
+                    &mut sun_animation,
+                    &mut shadow_study_options,
+                    &mut notifications,
+                );
+
+                if let Some(path) = sun_status.bake_matcap {
+                    matcap_bake_queue = Some(path);
+                }
+
+                if sun_status.geolocation_changed {
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                // Labels are not drawn while comparing variants or in stereo,
+                // as their positions in the split viewport would not match.
+                if viewport_labels && comparison.is_none() && !viewport_stereo {
+                    let value_labels =
+                        collect_value_labels(&session, &scene_graph, viewport_draw_used_values);
+                    ui_frame.draw_value_labels(
+                        &(camera.projection_matrix() * camera.view_matrix()),
+                        &value_labels,
+                    );
+                }
+
+                if viewport_north_arrow {
+                    let window_size = window.inner_size();
+                    if let Some(direction) = annotation::image_north_direction(
+                        &camera,
+                        &geolocation.north_direction(),
+                        window_size.width,
+                        window_size.height,
+                    ) {
+                        ui_frame.draw_north_arrow(direction);
+                    }
+                }
+
+                if let Some(directory) = sun_status.export_shadow_study {
+                    shadow_study_queue = shadow_study_options
+                        .times()
+                        .into_iter()
+                        .filter(|solar_time| {
+                            SunPosition::compute(
+                                geolocation.latitude,
+                                sun_options.day_of_year,
+                                *solar_time,
+                            )
+                            .is_above_horizon()
+                        })
+                        .map(|solar_time| {
+                            let minutes = (solar_time * 60.0).round() as u32;
+                            let path = directory.join(format!(
+                                "shadow_study-{:03}-{:02}{:02}.png",
+                                sun_options.day_of_year,
+                                minutes / 60,
+                                minutes % 60,
+                            ));
+
+                            (solar_time, path)
+                        })
+                        .collect();
+
+                    // Images are taken from the back of the queue
+                    shadow_study_queue.reverse();
+
+                    if shadow_study_queue.is_empty() {
+                        notifications.push(
+                            time,
+                            NotificationLevel::Warn,
+                            "The sun is below the horizon for the whole shadow study",
+                        );
+                    }
+                }
+
+                let optimization_status = ui_frame.draw_optimization_window(
+                    time,
+                    &mut optimization_window_open,
+                    &session,
+                    optimization.as_ref(),
+                    &mut notifications,
+                    preferences.number_format,
+                );
+
+                if optimization_status.discard {
+                    optimization = None;
+                }
+
+                if optimization_status.stop {
+                    if let Some(optimization) = &mut optimization {
+                        optimization.stop();
+                    }
+                }
+
+                if let Some((params, objective, max_evaluations)) = optimization_status.start {
+                    optimization = Some(Optimization::new(
+                        session.stmts(),
+                        params,
+                        objective,
+                        max_evaluations,
+                        session.stmt_timeout(),
+                    ));
+                }
+
+                if optimization_status.keep_best {
+                    let optimization = optimization
+                        .as_ref()
+                        .expect("Optimization must exist to keep its candidates");
+
+                    let best_candidates =
+                        optimization.best_candidates(optimization::KEEP_BEST_CANDIDATES_COUNT);
+                    for (rank, candidate_index) in best_candidates.iter().enumerate() {
+                        let candidate = &optimization.candidates()[*candidate_index];
+                        let values = candidate
+                            .values()
+                            .iter()
+                            .map(|value| format!("{:.3}", value))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        // The geometry of the candidates is not kept, so
+                        // the variants have no thumbnails.
+                        variants.capture(
+                            format!("Optimized #{} [{}]", rank + 1, values),
+                            candidate.stmts().to_vec(),
+                        );
+                    }
+
+                    notifications.push(
+                        time,
+                        NotificationLevel::Info,
+                        format!(
+                            "Kept {} best candidates as variants.",
+                            best_candidates.len()
+                        ),
+                    );
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                if let Some(unit) = menu_status.project_unit {
+                    project_unit = unit;
+                    // Imports need to be checked again against the new units.
+                    unit_conversion_check.reset();
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+
+                    notifications.push(
+                        time,
+                        NotificationLevel::Info,
+                        format!(
+                            "Project units changed to {}. Existing geometry was not rescaled.",
+                            unit.name().to_lowercase(),
+                        ),
+                    );
+                }
+
+                if menu_status.viewport_explode_factor_changed {
+                    explode_scene_values(
+                        &mut renderer,
+                        &mut scene_graph,
+                        &session,
+                        viewport_explode_factor,
+                    );
+                    if let Some(comparison) = &mut comparison {
+                        explode_scene_values(
+                            &mut renderer,
+                            &mut comparison.scene_graph,
+                            &session,
+                            viewport_explode_factor,
+                        );
+                    }
+                }
+
+                if menu_status.viewport_draw_used_values_changed {
+                    scene_bounds.set_draw_used_values(viewport_draw_used_values);
+                    scene_bounding_box = scene_bounds.bounding_box();
+
+                    if let Some(comparison) = &mut comparison {
+                        comparison.scene_bounding_box = BoundingBox::union(
+                            comparison
+                                .scene_graph
+                                .iter()
+                                .filter(|(_, value_node, _)| {
+                                    viewport_draw_used_values || !value_node.used
+                                })
+                                .map(|(_, _, mesh_node)| mesh_node.mesh.bounding_box()),
+                        )
+                        .unwrap_or_else(BoundingBox::unit);
+                    }
+
+                    rebuild_ground_plane(
+                        &mut renderer,
+                        &preferences.ground_plane,
+                        &scene_bounding_box,
+                        &mut ground_plane_gpu_mesh_handle,
+                    );
+                }
+
+                if let Some(prevent_overwrite_modal_trigger) = menu_status.prevent_overwrite_modal {
+                    project_status.prevent_overwrite_status = match prevent_overwrite_modal_trigger
+                    {
+                        OverwriteModalTrigger::NewProject => {
+                            Some(crate::project::NextAction::NewProject)
+                        }
+                        OverwriteModalTrigger::OpenProject => {
+                            Some(crate::project::NextAction::OpenProject)
+                        }
+                    }
+                }
+
+                // The opened project, and the path it was opened from
+                let mut project_to_open: Option<(project::Project, Option<PathBuf>)> = None;
+
+                if menu_status.new_project {
+                    scene_bounds.clear();
+
+                    for gpu_mesh_handle in scene_graph.clear() {
+                        renderer.remove_scene_mesh(gpu_mesh_handle);
+                    }
+                    for gpu_mesh_handle in streamed_gpu_mesh_handles.drain(..) {
+                        renderer.remove_scene_mesh(gpu_mesh_handle);
+                    }
+
+                    scene_bounding_box = scene_bounds.bounding_box();
+
+                    rebuild_ground_plane(
+                        &mut renderer,
+                        &preferences.ground_plane,
+                        &scene_bounding_box,
+                        &mut ground_plane_gpu_mesh_handle,
+                    );
+
+                    let current_autorun_delay = session.autorun_delay();
+                    let current_stmt_timeout = session.stmt_timeout();
+                    let current_draft = session.draft();
+                    session = Session::new();
+                    session.set_autorun_delay(current_autorun_delay);
+                    session.set_stmt_timeout(current_stmt_timeout);
+                    session.set_draft(time, current_draft);
+
+                    ui_textures_to_remove.extend(variants.clear());
+                    if let Some(comparison) = comparison.take() {
+                        comparison.destroy(&mut renderer);
+                    }
+                    if let Some(sweep) = sweep.take() {
+                        ui_textures_to_remove.extend(sweep.thumbnails());
+                    }
+                    optimization = None;
+
+                    reference_images.clear();
+                    camera_bookmarks.clear();
+                    for (_, gpu_handle) in reference_image_gpu_handles.drain(..).flatten() {
+                        renderer.remove_scene_image(gpu_handle);
+                    }
+
+                    project_unit = Unit::default();
+                    geolocation = Geolocation::default();
+                    unit_conversion_check.reset();
+                    unit_conversion_offer = None;
+                    scale_calibration.reset();
+
+                    project_status.path = None;
+                    project_status.example = None;
+                    project_status.changed_since_last_save = false;
+
+                    change_window_title(&window, &project_status);
+
+                    if let Some(statistics) = &mut statistics {
+                        statistics.projects_created += 1;
+                    }
+
+                    match template::load() {
+                        Ok(Some(project_template)) => {
+                            log::info!("Creating new project from the template");
+
+                            viewport_draw_mode = project_template.viewport.draw_mode;
+                            viewport_draw_used_values = project_template.viewport.draw_used_values;
+                            scene_bounds.set_draw_used_values(viewport_draw_used_values);
+                            viewport_labels = project_template.viewport.labels;
+
+                            project_to_open = Some((project_template.project, None));
+                        }
+                        Ok(None) => (),
+                        Err(err) => {
+                            log::error!("Failed to load project template: {}", err);
+                            notifications.push(
+                                time,
+                                NotificationLevel::Warn,
+                                format!("Failed to load project template: {}", err),
+                            );
+                        }
+                    }
+                }
+
+                if menu_status.save_template {
+                    let project_template = ProjectTemplate {
+                        project: create_project(
+                            &session,
+                            &variants,
+                            project_unit,
+                            geolocation,
+                            &reference_images,
+                            &camera_bookmarks,
+                            // The template keeps its viewport settings on
+                            // its own, without the camera and clip box
+                            None,
+                        ),
+                        viewport: TemplateViewport {
+                            draw_mode: viewport_draw_mode,
+                            draw_used_values: viewport_draw_used_values,
+                            labels: viewport_labels,
+                        },
+                    };
+
+                    match template::save(project_template) {
+                        Ok(()) => {
+                            log::info!("Saved project template");
+                            notifications.push(
+                                time,
+                                NotificationLevel::Info,
+                                "New projects will start as the current project",
+                            );
+                        }
+                        Err(err) => {
+                            log::error!("Failed to save project template: {}", err);
+                            notifications.push(
+                                time,
+                                NotificationLevel::Error,
+                                format!("Failed to save project template: {}", err),
+                            );
+                        }
+                    }
+                }
+
+                if let Some(save_path) = menu_status.save_path {
+                    log::info!("Saving project at {}", save_path.to_string_lossy());
+
+                    let project = create_project(
+                        &session,
+                        &variants,
+                        project_unit,
+                        geolocation,
+                        &reference_images,
+                        &camera_bookmarks,
+                        Some(ProjectViewport {
+                            draw_mode: viewport_draw_mode,
+                            draw_used_values: viewport_draw_used_values,
+                            labels: viewport_labels,
+                            clip_box,
+                            camera_view: camera.view(),
+                        }),
+                    );
+
+                    match project::save(&save_path, project, preferences.project_files.git_friendly)
+                    {
+                        Ok(save_path) => {
+                            save_event_log(
+                                &mut session,
+                                &save_path,
+                                project_status.path.as_deref(),
+                            );
+
+                            project_status.save(&save_path);
+                            change_window_title(&window, &project_status);
+                            notifications.push(
+                                time,
+                                NotificationLevel::Info,
+                                format!("Project saved as {}", save_path.display()),
+                            );
+
+                            if let Some(statistics) = &mut statistics {
+                                statistics.projects_saved += 1;
+                                save_statistics(statistics);
+                            }
+                        }
+                        Err(err) => {
+                            log::error!("{}", err);
+                            project_status.error = Some(err);
+                        }
+                    }
+                }
+
+                if let Some(save_bundle_path) = menu_status.save_bundle_path {
+                    log::info!("Saving bundle at {}", save_bundle_path.to_string_lossy());
+
+                    let project = create_project(
+                        &session,
+                        &variants,
+                        project_unit,
+                        geolocation,
+                        &reference_images,
+                        &camera_bookmarks,
+                        Some(ProjectViewport {
+                            draw_mode: viewport_draw_mode,
+                            draw_used_values: viewport_draw_used_values,
+                            labels: viewport_labels,
+                            clip_box,
+                            camera_view: camera.view(),
+                        }),
+                    );
+
+                    match bundle::save(&save_bundle_path, project, session.function_table()) {
+                        Ok(bundle_path) => {
+                            notifications.push(
+                                time,
+                                NotificationLevel::Info,
+                                format!(
+                                    "Project bundle saved as {}",
+                                    bundle_path.to_string_lossy()
+                                ),
+                            );
+                        }
+                        Err(err) => {
+                            log::error!("Failed to save bundle: {}", err);
+                            notifications.push(
+                                time,
+                                NotificationLevel::Error,
+                                format!("Failed to save bundle: {}", err),
+                            );
+                        }
+                    }
+                }
+
+                if let Some(open_path) = menu_status.open_path {
+                    log::info!("Opening new project at {}", open_path.to_string_lossy());
+
+                    let is_bundle = open_path
+                        .extension()
+                        .map_or(false, |extension| extension == bundle::EXTENSION);
+                    if is_bundle {
+                        // Bundles are extracted into the cache and open as new
+                        // projects, so that they are saved somewhere permanent
+                        match bundle::open(&open_path, session.function_table()) {
+                            Ok(project) => {
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Info,
+                                    format!(
+                                        "Opened bundle {}. Save the project to keep changes.",
+                                        open_path.to_string_lossy(),
+                                    ),
+                                );
+                                project_to_open = Some((project, None));
+                                project_status.path = None;
+                                project_status.example = None;
+                            }
+                            Err(err) => {
+                                log::error!("Failed to open bundle: {}", err);
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Error,
+                                    format!("Failed to open bundle: {}", err),
+                                );
+                            }
+                        }
+                    } else {
+                        match project::open(&open_path) {
+                            Ok(project) => {
+                                project_to_open = Some((project, Some(open_path)));
+                                project_status.example = None;
+                            }
+                            Err(err) => {
+                                log::error!("{}", err);
+                                project_status.error = Some(err);
+                            }
+                        };
+                    }
+                }
+
+                if let Some(example) = project_status.example_requested.take() {
+                    log::info!("Opening example {}", example.id);
+
+                    // Examples open as new projects, so that saving them asks
+                    // for a location of the copy
+                    match example.open() {
+                        Ok(project) => {
+                            project_to_open = Some((project, None));
+                            project_status.path = None;
+                            project_status.example = Some(example.name);
+                            example_modal = Some(example);
+                        }
+                        Err(err) => {
+                            log::error!("Failed to open example {}: {}", example.id, err);
+                            notifications.push(
+                                time,
+                                NotificationLevel::Error,
+                                format!("Failed to open example: {}", err),
+                            );
+                        }
+                    }
+                }
+
+                if let Some((project, open_path)) = project_to_open {
+                    scene_bounds.clear();
+
+                    for gpu_mesh_handle in scene_graph.clear() {
+                        renderer.remove_scene_mesh(gpu_mesh_handle);
+                    }
+                    for gpu_mesh_handle in streamed_gpu_mesh_handles.drain(..) {
+                        renderer.remove_scene_mesh(gpu_mesh_handle);
+                    }
+
+                    scene_bounding_box = scene_bounds.bounding_box();
+
+                    rebuild_ground_plane(
+                        &mut renderer,
+                        &preferences.ground_plane,
+                        &scene_bounding_box,
+                        &mut ground_plane_gpu_mesh_handle,
+                    );
+
+                    let current_autorun_delay = session.autorun_delay();
+                    let current_stmt_timeout = session.stmt_timeout();
+                    let current_draft = session.draft();
+                    session = Session::new();
+                    session.set_autorun_delay(current_autorun_delay);
+                    session.set_stmt_timeout(current_stmt_timeout);
+                    session.set_draft(time, current_draft);
+
+                    for stmt in complete_missing_args(&session, project.stmts) {
+                        session.push_prog_stmt(time, stmt);
+                    }
+                    for var_ident in project.pinned_vars {
+                        session.set_var_pinned(var_ident, true);
+                    }
+                    for (var_ident, display_material) in project.display_materials {
+                        session.set_display_material(var_ident, Some(display_material));
+                    }
+                    session.set_layers(Layers::from_parts(project.layers, project.var_layers));
+                    for (var_ident, var_name) in project.var_names {
+                        if let Err(err) = session.set_var_name(var_ident, Some(var_name)) {
+                            log::warn!("Failed to set name from project: {}", err);
+                        }
+                    }
+                    for (var_ident, value_label) in project.value_labels {
+                        session.set_value_label(var_ident, Some(value_label));
+                    }
+                    for (var_ident, stmt_note) in project.stmt_notes {
+                        session.set_stmt_note(var_ident, Some(stmt_note));
+                    }
+                    session.set_notes(project.notes);
+                    for var_ident in project.live_linked_vars {
+                        session.set_var_live_linked(var_ident, true);
+                    }
+                    // The voxel size is set before linking the statements to
+                    // it, so that the saved arguments are kept as they are.
+                    if let Some(voxel_size) = project.voxel_size {
+                        session.set_voxel_size(time, voxel_size);
+                    }
+                    for var_ident in project.voxel_size_inheriting_vars {
+                        session.set_var_inherits_voxel_size(time, var_ident, true);
+                    }
+                    for (param, param_link) in project.param_links {
+                        session.set_param_link(time, param, Some(param_link));
+                    }
+
+                    // The edits of earlier sessions are kept in the log and
+                    // this session continues it from the opened statements.
+                    let mut event_log = match &open_path {
+                        Some(open_path) => {
+                            match event_log::load(&event_log::path_for_project(open_path)) {
+                                Ok(event_log) => event_log,
+                                Err(err) => {
+                                    log::error!("Failed to load event log: {}", err);
+                                    EventLog::default()
+                                }
+                            }
+                        }
+                        None => EventLog::default(),
+                    };
+                    event_log.record(EventKind::Checkpoint(session.stmts().to_vec()));
+                    session.set_event_log(event_log);
+
+                    // If a long run of this project was interrupted by a
+                    // crash, the run resumes from the values it completed.
+                    if let Some(checkpoint_path) = checkpoint::path() {
+                        match checkpoint::load(&checkpoint_path, session.stmts()) {
+                            Ok(values) if !values.is_empty() && !session.draft() => {
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Info,
+                                    format!(
+                                        "Resuming from {} operation results saved before a crash.",
+                                        values.len(),
+                                    ),
+                                );
+                                session.restore_values(values);
+                                if let Err(err) = checkpoint::remove(&checkpoint_path) {
+                                    log::error!("Failed to remove checkpoint: {}", err);
+                                }
+                            }
+                            Ok(_) => (),
+                            Err(err) => log::error!("Failed to load checkpoint: {}", err),
+                        }
+                    }
+
+                    ui_textures_to_remove.extend(variants.clear());
+                    if let Some(comparison) = comparison.take() {
+                        comparison.destroy(&mut renderer);
+                    }
+                    if let Some(sweep) = sweep.take() {
+                        ui_textures_to_remove.extend(sweep.thumbnails());
+                    }
+                    optimization = None;
+                    for project_variant in project.variants {
+                        variants.capture(
+                            project_variant.name,
+                            complete_missing_args(&session, project_variant.stmts),
+                        );
+                    }
+
+                    for (_, gpu_handle) in reference_image_gpu_handles.drain(..).flatten() {
+                        renderer.remove_scene_image(gpu_handle);
+                    }
+                    for reference_image in &project.reference_images {
+                        match add_scene_reference_image(&mut renderer, reference_image) {
+                            Ok(gpu_handle) => {
+                                reference_image_gpu_handles.push(Some(gpu_handle));
+                            }
+                            Err(err) => {
+                                log::error!("Failed to load reference image: {}", err);
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Warn,
+                                    format!(
+                                        "Failed to load reference image {}: {}",
+                                        reference_image.path, err,
+                                    ),
+                                );
+                                reference_image_gpu_handles.push(None);
+                            }
+                        }
+                    }
+                    reference_images = project.reference_images;
+                    camera_bookmarks = project.camera_bookmarks;
+
+                    if let Some(viewport) = project.viewport {
+                        viewport_draw_mode = viewport.draw_mode;
+                        viewport_draw_used_values = viewport.draw_used_values;
+                        scene_bounds.set_draw_used_values(viewport_draw_used_values);
+                        viewport_labels = viewport.labels;
+                        clip_box = viewport.clip_box;
+
+                        // The scene is empty until the pipeline runs, so the
+                        // camera limits are widened to fit the stored view
+                        let camera_radius_max = compute_scene_camera_radius(scene_bounding_box)
+                            .max(viewport.camera_view.radius);
+                        camera.set_radius_min(0.001 * camera_radius_max);
+                        camera.set_radius_max(camera_radius_max);
+                        camera.set_znear(0.001 * camera_radius_max);
+                        camera.set_zfar(2.0 * camera_radius_max);
+                        camera_interpolation = None;
+                        camera.set_view(&viewport.camera_view);
+                    }
+
+                    project_unit = project.unit;
+                    geolocation = project.geolocation;
+                    unit_conversion_check.reset();
+                    unit_conversion_offer = None;
+                    scale_calibration.reset();
+
+                    project_status.changed_since_last_save = false;
+
+                    // Projects created from the template are new and have no
+                    // path yet
+                    if let Some(open_path) = open_path {
+                        notifications.push(
+                            time,
+                            NotificationLevel::Info,
+                            format!("Opened project {}", &open_path.to_string_lossy()),
+                        );
+
+                        project_status.path = Some(open_path);
+
+                        if let Some(statistics) = &mut statistics {
+                            statistics.projects_opened += 1;
+                        }
+                    }
+
+                    change_window_title(&window, &project_status);
+                }
+
+                if project_status.error.is_some()
+                    && ui_frame.draw_error_modal(&project_status.error)
+                {
+                    project_status.error = None;
+                }
+
+                if let Some(bundle) = &crash_bundle {
+                    let dismiss = match ui_frame.draw_crash_modal(&bundle.path) {
+                        CrashModalResult::CopyReport => {
+                            if clipboard.set_text(bundle.report.clone()) {
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Info,
+                                    "Crash report copied to clipboard",
+                                );
+                            } else {
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Warn,
+                                    "Failed to copy crash report to clipboard",
+                                );
+                            }
+
+                            false
+                        }
+                        CrashModalResult::SaveTo(directory) => {
+                            match crash::save_bundle_to(bundle, &directory) {
+                                Ok(path) => {
+                                    notifications.push(
+                                        time,
+                                        NotificationLevel::Info,
+                                        format!("Crash bundle saved to {}", path.to_string_lossy()),
+                                    );
+
+                                    true
+                                }
+                                Err(err) => {
+                                    log::error!("Failed to save crash bundle: {}", err);
+                                    notifications.push(
+                                        time,
+                                        NotificationLevel::Error,
+                                        format!("Failed to save crash bundle: {}", err),
+                                    );
+
+                                    false
+                                }
+                            }
+                        }
+                        CrashModalResult::Dismiss => true,
+                        CrashModalResult::Nothing => false,
+                    };
+
+                    if dismiss {
+                        if let Err(err) = crash::dismiss_pending_bundle() {
+                            log::error!("Failed to dismiss crash bundle: {}", err);
+                        }
+                        crash_bundle = None;
+                    }
+                }
+
+                let window_size = window.inner_size();
+                let mut take_screenshot = ui_frame.draw_screenshot_window(
+                    &mut screenshot_modal_open,
+                    &mut screenshot_options,
+                    window_size.width,
+                    window_size.height,
+                );
+
+                let (tex_logos, width_logos, height_logos) = match options.theme {
+                    Theme::Light | Theme::LightHighContrast => {
+                        (tex_logos_black, width_logos_black, height_logos_black)
+                    }
+                    Theme::Dark | Theme::DarkHighContrast => {
+                        (tex_logos_white, width_logos_white, height_logos_white)
+                    }
+                };
+                ui_frame.draw_about_window(
+                    &mut about_modal_open,
+                    tex_scheme,
+                    width_scheme,
+                    height_scheme,
+                    tex_logos,
+                    width_logos,
+                    height_logos,
+                    renderer.adapter_info(),
+                );
+
+                ui_frame.draw_notifications_window(&notifications);
+
+                let pipeline_status = if presentation_mode {
+                    PipelineStatus::default()
+                } else {
+                    ui_frame.draw_pipeline_window(
+                        time,
+                        &mut session,
+                        project_unit,
+                        preferences.snapping,
+                        preferences.number_format,
+                        &mut param_defaults,
+                    )
+                };
+                if pipeline_status.changed {
+                    project_status.changed_since_last_save = true;
+
+                    change_window_title(&window, &project_status);
+                }
+
+                if !presentation_mode
+                    && ui_frame.draw_focus_window(
+                        time,
+                        &mut session,
+                        project_unit,
+                        preferences.snapping,
+                        preferences.number_format,
+                        &mut param_defaults,
+                    )
+                {
+                    project_status.changed_since_last_save = true;
+
+                    change_window_title(&window, &project_status);
+                }
+
+                if let Some(var_ident) = pipeline_status.display_material_changed {
+                    sync_scene_value(&mut renderer, &mut scene_graph, &session, var_ident);
+                    if let Some(comparison) = &mut comparison {
+                        sync_scene_value(
+                            &mut renderer,
+                            &mut comparison.scene_graph,
+                            &session,
+                            var_ident,
+                        );
+                    }
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                if let Some(message) = pipeline_status.var_name_error {
+                    notifications.push(time, NotificationLevel::Warn, message);
+                }
+
+                if pipeline_status.var_name_changed.is_some()
+                    || pipeline_status.value_label_changed.is_some()
+                    || pipeline_status.notes_changed
+                    || pipeline_status.live_link_changed.is_some()
+                {
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                if layers_changed || pipeline_status.layer_changed.is_some() {
+                    // Layer visibility and colors apply to many values at
+                    // once, so sync all of them.
+                    for var_ident in scene_graph.var_idents() {
+                        sync_scene_value(&mut renderer, &mut scene_graph, &session, var_ident);
+                    }
+                    if let Some(comparison) = &mut comparison {
+                        for var_ident in comparison.scene_graph.var_idents() {
+                            sync_scene_value(
+                                &mut renderer,
+                                &mut comparison.scene_graph,
+                                &session,
+                                var_ident,
+                            );
+                        }
+                    }
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                if let Some(var_ident) = pipeline_status.pinned_changed {
+                    let used = session.value_used(var_ident);
+                    scene_graph.set_used(var_ident, used);
+
+                    scene_bounds.set_used(&var_ident, used);
+                    scene_bounding_box = scene_bounds.bounding_box();
+
+                    rebuild_ground_plane(
+                        &mut renderer,
+                        &preferences.ground_plane,
+                        &scene_bounding_box,
+                        &mut ground_plane_gpu_mesh_handle,
+                    );
+
+                    project_status.changed_since_last_save = true;
+                    change_window_title(&window, &project_status);
+                }
+
+                if unit_conversion_offer.is_none() {
+                    unit_conversion_offer =
+                        unit_conversion_check.find_offer(&session, project_unit);
+                }
+
+                if let Some(offer) = &unit_conversion_offer {
+                    let file_name = Path::new(&offer.path).file_name().map_or_else(
+                        || offer.path.clone(),
+                        |file_name| file_name.to_string_lossy().into_owned(),
+                    );
+
+                    match ui_frame.draw_unit_conversion_modal(
+                        &file_name,
+                        offer.declared_unit,
+                        project_unit,
+                    ) {
+                        UnitConversionModalResult::Convert => {
+                            let stmt_index = session
+                                .var_decl_stmt_index_and_var_name_for_ident(offer.var_ident)
+                                .map(|(stmt_index, _)| stmt_index);
+
+                            if let Some(stmt_index) = stmt_index {
+                                let ast::Stmt::VarDecl(var_decl) = &session.stmts()[stmt_index];
+                                let scale = offer.declared_unit.conversion_factor(project_unit);
+                                let init_expr = var_decl.init_expr().clone_with_arg_at(
+                                    interpreter_funcs::IMPORT_OBJ_PARAM_INDEX_UNIT_SCALE,
+                                    ast::Expr::Lit(ast::LitExpr::Float(scale)),
+                                );
+                                let stmt =
+                                    ast::Stmt::VarDecl(var_decl.clone_with_init_expr(init_expr));
+
+                                session.set_prog_stmt_at(time, stmt_index, stmt);
 
-                ui_frame.draw_notifications_window(&notifications);
+                                project_status.changed_since_last_save = true;
+                                change_window_title(&window, &project_status);
 
-                if ui_frame.draw_pipeline_window(time, &mut session) {
-                    project_status.changed_since_last_save = true;
+                                notifications.push(
+                                    time,
+                                    NotificationLevel::Info,
+                                    format!(
+                                        "Geometry of {} converted from {} to {}",
+                                        file_name,
+                                        offer.declared_unit.name().to_lowercase(),
+                                        project_unit.name().to_lowercase(),
+                                    ),
+                                );
+                            }
 
-                    change_window_title(&window, &project_status);
+                            unit_conversion_offer = None;
+                        }
+                        UnitConversionModalResult::Keep => {
+                            unit_conversion_offer = None;
+                        }
+                        UnitConversionModalResult::Nothing => (),
+                    }
                 }
 
-                if ui_frame.draw_operations_window(
-                    time,
-                    &mut session,
-                    &mut notifications,
-                    DURATION_AUTORUN_DELAY,
-                ) {
+                if let Some(var_ident) = pipeline_status.copy_as_obj {
+                    export::copy_var_as_obj(
+                        time,
+                        &mut clipboard,
+                        &session,
+                        &scene_graph,
+                        var_ident,
+                        project_unit,
+                        &mut notifications,
+                    );
+                }
+
+                if !presentation_mode
+                    && ui_frame.draw_operations_window(
+                        time,
+                        &mut session,
+                        &mut notifications,
+                        &mut preferences.autorun,
+                        &param_defaults,
+                    )
+                {
                     project_status.changed_since_last_save = true;
 
                     change_window_title(&window, &project_status);
                 }
 
+                if preferences.autorun != autorun {
+                    session.set_autorun_delay(preferences.autorun.autorun_delay());
+                    preferences_changed = true;
+                }
+
                 if let Some(prevent_overwrite_status) = project_status.prevent_overwrite_status {
                     match ui_frame.draw_prevent_overwrite_modal() {
                         SaveModalResult::Cancel => {
                             project_status.prevent_overwrite_status = None;
+                            project_status.open_requested_path = None;
                         }
                         SaveModalResult::DontSave => match prevent_overwrite_status {
                             project::NextAction::Exit => {
@@ -798,6 +3366,9 @@ pub fn init_and_run(options: Options) -> ! {
                             project::NextAction::OpenProject => {
                                 project_status.open_requested = true
                             }
+                            project::NextAction::OpenExample(example) => {
+                                project_status.example_requested = Some(example);
+                            }
                         },
                         SaveModalResult::Save => {
                             let save_path = match project_status.path.clone() {
@@ -815,33 +3386,57 @@ pub fn init_and_run(options: Options) -> ! {
                             };
 
                             if let Some(save_path) = save_path {
-                                let stmts = session.stmts().to_vec();
-                                let project = project::Project { version: 1, stmts };
+                                let project = create_project(
+                                    &session,
+                                    &variants,
+                                    project_unit,
+                                    geolocation,
+                                    &reference_images,
+                                    &camera_bookmarks,
+                                    Some(ProjectViewport {
+                                        draw_mode: viewport_draw_mode,
+                                        draw_used_values: viewport_draw_used_values,
+                                        labels: viewport_labels,
+                                        clip_box,
+                                        camera_view: camera.view(),
+                                    }),
+                                );
 
-                                match project::save(&save_path, project) {
-                                    Ok(save_path) => match prevent_overwrite_status {
-                                        project::NextAction::Exit => {
-                                            *control_flow = winit::event_loop::ControlFlow::Exit
-                                        }
-                                        project::NextAction::NewProject => {
-                                            let save_path = save_path
-                                                .as_os_str()
-                                                .to_str()
-                                                .expect("Failed to convert save path to str.");
-
-                                            project_status.save(&save_path);
-                                            project_status.new_requested = true;
+                                match project::save(
+                                    &save_path,
+                                    project,
+                                    preferences.project_files.git_friendly,
+                                ) {
+                                    Ok(save_path) => {
+                                        save_event_log(
+                                            &mut session,
+                                            &save_path,
+                                            project_status.path.as_deref(),
+                                        );
+
+                                        if let Some(statistics) = &mut statistics {
+                                            statistics.projects_saved += 1;
+                                            save_statistics(statistics);
                                         }
-                                        project::NextAction::OpenProject => {
-                                            let save_path = save_path
-                                                .as_os_str()
-                                                .to_str()
-                                                .expect("Failed to convert save path to str.");
-
-                                            project_status.save(&save_path);
-                                            project_status.open_requested = true
+
+                                        match prevent_overwrite_status {
+                                            project::NextAction::Exit => {
+                                                *control_flow = winit::event_loop::ControlFlow::Exit
+                                            }
+                                            project::NextAction::NewProject => {
+                                                project_status.save(&save_path);
+                                                project_status.new_requested = true;
+                                            }
+                                            project::NextAction::OpenProject => {
+                                                project_status.save(&save_path);
+                                                project_status.open_requested = true
+                                            }
+                                            project::NextAction::OpenExample(example) => {
+                                                project_status.save(&save_path);
+                                                project_status.example_requested = Some(example);
+                                            }
                                         }
-                                    },
+                                    }
                                     Err(err) => {
                                         log::error!("Project save failed: {}", err);
 
@@ -861,90 +3456,235 @@ pub fn init_and_run(options: Options) -> ! {
                         Some(CameraInterpolation::new(&camera, &scene_bounding_box, time));
                 }
 
-                if menu_status.export_obj {
-                    let suggested_filename = match &project_status.path {
-                        Some(path) => match path.file_stem() {
-                            Some(file_stem) => {
-                                Cow::Owned(format!("{}.obj", file_stem.to_string_lossy()))
-                            }
-                            None => Cow::Borrowed("export.obj"),
-                        },
-                        None => Cow::Borrowed("export.obj"),
-                    };
-                    if let Some(path) = tinyfiledialogs::save_file_dialog_with_filter(
-                        "Export OBJ",
-                        &suggested_filename,
-                        &["*.obj"],
-                        "Wavefront (.obj)",
-                    ) {
-                        // FIXME: The session can not provide a name, if the
-                        // viewport contains an object constructed by a func
-                        // that was already removed from the program. For this
-                        // reason we are exporting just the stringified var
-                        // ident, if the name is not present.
-                        //
-                        // Note that this viewport vs session desync will also
-                        // affect features like viewport picking. We have to
-                        // assume all values in the viewport can potentially be
-                        // stale.
-                        //
-                        // What do we do?
-                        let unused_values_iter = scene_meshes
-                            .iter()
-                            .filter(|(_, (used, _))| !used)
-                            .map(|(value_path, (_, mesh))| {
-                                if value_path.1 == 0 {
-                                    // Do not suffix zero mesh-array index
-                                    let name = match session
-                                        .var_decl_stmt_index_and_var_name_for_ident(value_path.0)
-                                    {
-                                        Some((_, name)) => Cow::Borrowed(name),
-                                        None => Cow::Owned(value_path.0.to_string()),
-                                    };
+                let mut export_obj = menu_status.export_obj;
+                if session.draft() && (export_obj || take_screenshot) {
+                    notifications.push(
+                        time,
+                        NotificationLevel::Info,
+                        "Running the Operation pipeline in full quality...",
+                    );
+                    export_obj_awaiting_full_quality |= export_obj;
+                    screenshot_awaiting_full_quality |= take_screenshot;
+                    export_obj = false;
+                    take_screenshot = false;
+                }
 
-                                    (name, mesh.as_ref())
-                                } else {
-                                    // Suffix mesh-array index if nonzero
-                                    let name = match session
-                                        .var_decl_stmt_index_and_var_name_for_ident(value_path.0)
-                                    {
-                                        Some((_, name)) => {
-                                            Cow::Owned(format!("{} [{}]", name, value_path.1))
-                                        }
-                                        None => Cow::Owned(format!(
-                                            "{} [{}]",
-                                            value_path.0, value_path.1,
-                                        )),
-                                    };
+                if (export_obj_awaiting_full_quality || screenshot_awaiting_full_quality)
+                    && !session.interpreter_busy()
+                {
+                    if session.draft() {
+                        session.set_draft(time, false);
+                        draft_suspended = true;
+                    }
 
-                                    (name, mesh.as_ref())
-                                }
-                            });
+                    if session.synced() {
+                        export_obj |= mem::replace(&mut export_obj_awaiting_full_quality, false);
+                        take_screenshot |=
+                            mem::replace(&mut screenshot_awaiting_full_quality, false);
+                    } else {
+                        session.interpret();
+                    }
+                }
 
-                        let file = File::create(&path).expect("Failed to create OBJ file");
-                        let mut writer = BufWriter::new(file);
+                // Draft values only arrive with the next run, so the export
+                // and screenshot below still get the full quality values.
+                if draft_suspended
+                    && !export_obj_awaiting_full_quality
+                    && !screenshot_awaiting_full_quality
+                    && !session.interpreter_busy()
+                {
+                    session.set_draft(time, true);
+                    draft_suspended = false;
+                }
 
-                        match exporter::export_obj(&mut writer, unused_values_iter, f32::DIGITS) {
-                            Ok(()) => {
-                                log::info!("OBJ exported to: {}", path);
-                                notifications.push(
-                                    time,
-                                    NotificationLevel::Info,
-                                    format!("OBJ exported to: {}", path),
-                                );
+                if export_obj {
+                    // FIXME: The session can not provide a name, if the
+                    // viewport contains an object constructed by a func
+                    // that was already removed from the program. For this
+                    // reason we are exporting just the stringified var
+                    // ident, if the name is not present.
+                    //
+                    // Note that this viewport vs session desync will also
+                    // affect features like viewport picking. We have to
+                    // assume all values in the viewport can potentially be
+                    // stale.
+                    //
+                    // What do we do?
+                    let (unused_models, used_models) =
+                        collect_export_models(&session, &scene_graph);
+
+                    export_preview = Some(ExportPreview::new(unused_models, used_models));
+                }
+
+                if let Some(preview) = &mut export_preview {
+                    match ui_frame.draw_export_preview_modal(preview, f32::DIGITS, project_unit) {
+                        ExportPreviewModalResult::Export => {
+                            let models = export_preview
+                                .take()
+                                .expect("Export preview must be present")
+                                .into_included_models();
+
+                            let suggested_filename = match &project_status.path {
+                                Some(path) => match path.file_stem() {
+                                    Some(file_stem) => {
+                                        Cow::Owned(format!("{}.obj", file_stem.to_string_lossy()))
+                                    }
+                                    None => Cow::Borrowed("export.obj"),
+                                },
+                                None => Cow::Borrowed("export.obj"),
+                            };
+                            if let Some(path) = tinyfiledialogs::save_file_dialog_with_filter(
+                                "Export OBJ",
+                                &suggested_filename,
+                                &["*.obj"],
+                                "Wavefront (.obj)",
+                            ) {
+                                log::info!("Exporting OBJ to: {}", path);
+                                export_jobs.push(ExportJob::spawn(
+                                    String::from("OBJ"),
+                                    path,
+                                    move |writer, progress| {
+                                        exporter::export_obj_with_progress(
+                                            writer,
+                                            models
+                                                .iter()
+                                                .map(|(name, mesh)| (name.as_str(), mesh.as_ref())),
+                                            f32::DIGITS,
+                                            Some(project_unit),
+                                            progress,
+                                        )
+                                    },
+                                ));
                             }
-                            Err(err) => {
-                                log::error!("OBJ export failed: {}", err);
-                                notifications.push(
-                                    time,
-                                    NotificationLevel::Error,
-                                    "OBJ export failed",
-                                );
+                        }
+                        ExportPreviewModalResult::Cancel => export_preview = None,
+                        ExportPreviewModalResult::Nothing => (),
+                    }
+                }
+
+                if command_line_export_obj.is_some() {
+                    if project_status.error.is_some() {
+                        log::error!("Can't export project that failed to open");
+                        *control_flow = winit::event_loop::ControlFlow::Exit;
+                    } else if project_status.path.is_some() && !session.interpreter_busy() {
+                        if session.synced() {
+                            let path = command_line_export_obj
+                                .take()
+                                .expect("Export path must be present");
+                            let path = path.to_string_lossy().into_owned();
+                            let (models, _) = collect_export_models(&session, &scene_graph);
+
+                            log::info!("Exporting OBJ to: {}", path);
+                            export_jobs.push(ExportJob::spawn(
+                                String::from("OBJ"),
+                                path,
+                                move |writer, progress| {
+                                    exporter::export_obj_with_progress(
+                                        writer,
+                                        models
+                                            .iter()
+                                            .map(|(name, mesh)| (name.as_str(), mesh.as_ref())),
+                                        f32::DIGITS,
+                                        Some(project_unit),
+                                        progress,
+                                    )
+                                },
+                            ));
+                            exit_after_export = true;
+                        } else {
+                            session.interpret();
+                        }
+                    }
+                }
+
+                if !remote_requests_awaiting_run.is_empty() && !session.interpreter_busy() {
+                    if session.synced() {
+                        for request in remote_requests_awaiting_run.drain(..) {
+                            let response = match &request.command {
+                                RemoteCommand::ExportObj => {
+                                    let (models, _) = collect_export_models(&session, &scene_graph);
+
+                                    let mut obj = Vec::new();
+                                    exporter::export_obj(
+                                        &mut obj,
+                                        models
+                                            .iter()
+                                            .map(|(name, mesh)| (name.as_str(), mesh.as_ref())),
+                                        f32::DIGITS,
+                                        Some(project_unit),
+                                    )
+                                    .expect("Writing OBJ to memory must not fail");
+                                    let obj_text = String::from_utf8(obj)
+                                        .expect("OBJ text must be valid UTF-8");
+
+                                    Some(RemoteResponse::Obj(obj_text))
+                                }
+                                RemoteCommand::Screenshot { .. } => None,
+                                _ => match session.error() {
+                                    Some(err) => Some(RemoteResponse::Error(err.to_string())),
+                                    None => Some(RemoteResponse::Done),
+                                },
+                            };
+
+                            match response {
+                                Some(response) => request.reply(response),
+                                None => remote_screenshot_queue.push(request),
                             }
                         }
+                    } else {
+                        session.interpret();
                     }
                 }
 
+                export::poll_export_jobs(time, &mut export_jobs, &mut notifications);
+
+                ui_frame.draw_export_progress_window(&export_jobs);
+
+                if exit_after_export && export_jobs.is_empty() {
+                    *control_flow = winit::event_loop::ControlFlow::Exit;
+                }
+
+                if menu_status.export_measurements {
+                    export::export_measurements(
+                        time,
+                        &session,
+                        project_status.path.as_deref(),
+                        project_unit,
+                        &mut notifications,
+                    );
+                }
+
+                if menu_status.paste_obj {
+                    ui_actions::paste_obj(
+                        time,
+                        &mut clipboard,
+                        &mut session,
+                        &mut project_status,
+                        &window,
+                        &mut notifications,
+                    );
+                }
+
+                if menu_status.copy_pipeline_text {
+                    ui_actions::copy_pipeline_text(
+                        time,
+                        &mut clipboard,
+                        &session,
+                        &mut notifications,
+                    );
+                }
+
+                if menu_status.paste_pipeline_text {
+                    ui_actions::paste_pipeline_text(
+                        time,
+                        &mut clipboard,
+                        &mut session,
+                        &mut project_status,
+                        &window,
+                        &mut notifications,
+                    );
+                }
+
                 if input_state.close_requested {
                     if project_status.changed_since_last_save {
                         project_status.prevent_overwrite_status = Some(project::NextAction::Exit);
@@ -982,11 +3722,91 @@ pub fn init_and_run(options: Options) -> ! {
                         camera_interpolation = None;
                     }
                 }
+
+                let light_target_changed = match light_interpolation {
+                    Some(interp) => interp.target != scene_bounding_box,
+                    None => light_bounding_box != scene_bounding_box,
+                };
+                if light_target_changed {
+                    light_interpolation = Some(LightInterpolation::new(
+                        light_bounding_box,
+                        scene_bounding_box,
+                        time,
+                    ));
+                }
+                if let Some(interp) = light_interpolation {
+                    if interp.target_time > time {
+                        light_bounding_box = interp.update(time, &cubic_bezier);
+                    } else {
+                        light_bounding_box = interp.target;
+                        light_interpolation = None;
+                    }
+                }
                 notifications.update(time);
+                profiler.record(Stage::Ui, ui_start.elapsed());
+
+                // -- Draw to offscreen render targets for panoramas --
+
+                if take_screenshot
+                    && screenshot_options.panorama
+                    && pending_reads.any(|pending_read| {
+                        matches!(pending_read, PendingRead::PanoramaFace(_))
+                    })
+                {
+                    notifications.push(
+                        time,
+                        NotificationLevel::Warn,
+                        "Previous panorama is still being captured",
+                    );
+                } else if take_screenshot && screenshot_options.panorama {
+                    log::info!("Capturing panorama with width {}", screenshot_options.width);
+
+                    let new_panorama = Panorama::new(
+                        camera.position(),
+                        &camera.direction(),
+                        screenshot_options.width,
+                    );
+                    let face_size = new_panorama.face_size();
+
+                    // Faces are drawn like screenshots, without the ground,
+                    // but always with the background color, as 360 viewers
+                    // don't show transparency.
+                    for face in &CubeFace::ALL {
+                        let face_render_target =
+                            renderer.add_offscreen_render_target(face_size, face_size);
+
+                        let mut face_command_buffer = renderer
+                            .begin_offscreen_command_buffer(clear_color, &face_render_target);
+                        face_command_buffer.set_light(&compute_scene_light(
+                            scene_bounding_box,
+                            compute_scene_sun_position(&sun_options, &geolocation),
+                        ));
+                        face_command_buffer.set_camera_matrices(
+                            &camera.cube_face_projection_matrix(),
+                            &new_panorama.face_view_matrix(*face),
+                        );
+                        face_command_buffer.set_clip_box(clip_box.corners());
+
+                        draw_scene_meshes(
+                            &mut face_command_buffer,
+                            &scene_graph,
+                            &session,
+                            viewport_draw_mode,
+                            viewport_draw_used_values,
+                        );
+
+                        face_command_buffer.submit();
+
+                        pending_reads.insert(face_render_target.id(), PendingRead::PanoramaFace(*face));
+                        renderer.request_offscreen_render_target_read(face_render_target);
+                    }
+
+                    panorama = Some(new_panorama);
+                }
 
                 // -- Draw to offscreen render target for screenshots --
 
-                if take_screenshot {
+                if take_screenshot && !screenshot_options.panorama {
                     log::info!(
                         "Capturing screenshot with dimensions {}x{} and transparency {}",
                         screenshot_options.width,
@@ -1009,170 +3829,509 @@ pub fn init_and_run(options: Options) -> ! {
                         clear_color
                     };
 
-                    let mut screenshot_command_buffer = renderer.begin_command_buffer(
+                    let mut screenshot_command_buffer = renderer.begin_offscreen_command_buffer(
                         screenshot_clear_color,
-                        Some(&screenshot_render_target),
-                        false,
+                        &screenshot_render_target,
                     );
-                    screenshot_command_buffer.set_light(&compute_scene_light(scene_bounding_box));
-                    screenshot_command_buffer.set_camera_matrices(
-                        &screenshot_camera.projection_matrix(),
-                        &screenshot_camera.view_matrix(),
+                    screenshot_command_buffer.set_light(&compute_scene_light(
+                        scene_bounding_box,
+                        compute_scene_sun_position(&sun_options, &geolocation),
+                    ));
+                    screenshot_command_buffer.set_clip_box(clip_box.corners());
+
+                    let screenshot_views = stereo::views(
+                        &screenshot_camera,
+                        screenshot_options.width,
+                        screenshot_options.height,
+                        if screenshot_options.stereo {
+                            Some(screenshot_options.stereo_eye_separation)
+                        } else {
+                            None
+                        },
                     );
 
-                    // For screenshots, we don't need to cast shadows, and we
-                    // don't render the ground on purpose.
-                    match viewport_draw_mode {
-                        ViewportDrawMode::Wireframe => {
-                            screenshot_command_buffer.draw_meshes_to_render_target(
-                                scene_gpu_mesh_handles
-                                    .values()
-                                    .filter(|(used, _)| viewport_draw_used_values || !used)
-                                    .map(|(used, handle)| {
-                                        if *used {
-                                            (handle, Material::TransparentMatcapShaded, false)
-                                        } else {
-                                            (handle, Material::Edges, true)
-                                        }
-                                    }),
-                            );
-                        }
-                        ViewportDrawMode::Shaded => {
-                            screenshot_command_buffer.draw_meshes_to_render_target(
-                                scene_gpu_mesh_handles
-                                    .values()
-                                    .filter(|(used, _)| viewport_draw_used_values || !used)
-                                    .map(|(used, handle)| {
-                                        if *used {
-                                            (handle, Material::TransparentMatcapShaded, false)
-                                        } else {
-                                            (handle, Material::MatcapShaded, true)
-                                        }
-                                    }),
+                    for view in &screenshot_views {
+                        screenshot_command_buffer.set_viewport(view.viewport);
+                        screenshot_command_buffer
+                            .set_camera_matrices(&view.projection_matrix, &view.view_matrix);
+
+                        // For screenshots, we don't need to cast shadows, and
+                        // we don't render the ground on purpose.
+                        draw_scene_meshes(
+                            &mut screenshot_command_buffer,
+                            &scene_graph,
+                            &session,
+                            viewport_draw_mode,
+                            viewport_draw_used_values,
+                        );
+                    }
+
+                    screenshot_command_buffer.set_viewport(None);
+                    screenshot_command_buffer.submit();
+
+                    // Stereo images are not annotated, as the panel would
+                    // only fit one of the eyes.
+                    let annotation = if screenshot_options.annotation.enabled
+                        && !screenshot_options.stereo
+                    {
+                        let project_name = project_status
+                            .path
+                            .as_ref()
+                            .and_then(|path| path.file_stem())
+                            .map_or(Cow::Borrowed("Untitled"), |file_stem| {
+                                file_stem.to_string_lossy()
+                            });
+                        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+                        // The variant is active, if the current program is the
+                        // one captured in it
+                        let variant_name = variants
+                            .iter()
+                            .find(|variant| variant.stmts() == session.stmts())
+                            .map(|variant| variant.name());
+
+                        let value_labels = if viewport_labels {
+                            collect_value_labels(&session, &scene_graph, viewport_draw_used_values)
+                        } else {
+                            Vec::new()
+                        };
+
+                        Some(Annotation::new(
+                            &screenshot_options.annotation,
+                            &AnnotationContext {
+                                project_name: &project_name,
+                                date: &date,
+                                variant_name,
+                                value_labels: &value_labels,
+                            },
+                            &screenshot_camera,
+                            &geolocation.north_direction(),
+                            screenshot_options.width,
+                            screenshot_options.height,
+                        ))
+                    } else {
+                        None
+                    };
+
+                    pending_reads.insert(
+                        screenshot_render_target.id(),
+                        PendingRead::Screenshot(annotation),
+                    );
+                    renderer.request_offscreen_render_target_read(screenshot_render_target);
+                }
+
+                // -- Draw to offscreen render target for shadow studies --
+
+                // Only one image is drawn per frame, so that the application
+                // stays responsive while a long study is being exported.
+                if let Some((solar_time, path)) = shadow_study_queue.pop() {
+                    let shadow_study_render_target = renderer.add_offscreen_render_target(
+                        screenshot_options.width,
+                        screenshot_options.height,
+                    );
+
+                    let mut shadow_study_camera = camera.clone();
+                    shadow_study_camera
+                        .set_screen_dimensions(screenshot_options.width, screenshot_options.height);
+
+                    let sun_position = geolocation.scene_sun_position(SunPosition::compute(
+                        geolocation.latitude,
+                        sun_options.day_of_year,
+                        solar_time,
+                    ));
+
+                    let mut shadow_study_command_buffer = renderer
+                        .begin_offscreen_command_buffer(clear_color, &shadow_study_render_target);
+                    shadow_study_command_buffer
+                        .set_light(&compute_scene_light(scene_bounding_box, Some(sun_position)));
+                    shadow_study_command_buffer.set_camera_matrices(
+                        &shadow_study_camera.projection_matrix(),
+                        &shadow_study_camera.view_matrix(),
+                    );
+                    shadow_study_command_buffer.set_clip_box(clip_box.corners());
+
+                    // Unlike screenshots, shadow studies need the ground to
+                    // show the cast shadows, even if it is hidden in the
+                    // viewport.
+                    draw_scene_meshes(
+                        &mut shadow_study_command_buffer,
+                        &scene_graph,
+                        &session,
+                        viewport_draw_mode,
+                        viewport_draw_used_values,
+                    );
+                    shadow_study_command_buffer.draw_meshes_to_render_target(
+                        ground_plane_gpu_mesh_handle
+                            .iter()
+                            .map(|handle| (handle, Material::FlatWithShadows, false)),
+                    );
+
+                    shadow_study_command_buffer.submit();
+
+                    pending_reads.insert(
+                        shadow_study_render_target.id(),
+                        PendingRead::ShadowStudy(path),
+                    );
+                    renderer.request_offscreen_render_target_read(shadow_study_render_target);
+                }
+
+                // -- Draw to offscreen render target for baking matcaps --
+
+                if let Some(path) = matcap_bake_queue.take() {
+                    let sphere_mesh = matcap::bake_sphere_mesh();
+                    match renderer.add_scene_mesh(&GpuMesh::from_mesh(&sphere_mesh)) {
+                        Ok(sphere_gpu_mesh_handle) => {
+                            let matcap_render_target = renderer
+                                .add_offscreen_render_target(matcap::BAKE_SIZE, matcap::BAKE_SIZE);
+
+                            let mut matcap_command_buffer = renderer
+                                .begin_offscreen_command_buffer(clear_color, &matcap_render_target);
+                            matcap_command_buffer.set_light(&compute_scene_light(
+                                sphere_mesh.bounding_box(),
+                                compute_scene_sun_position(&sun_options, &geolocation),
+                            ));
+                            matcap_command_buffer.set_camera_matrices(
+                                &matcap::bake_projection_matrix(),
+                                &matcap::bake_view_matrix(&camera.direction()),
                             );
+                            matcap_command_buffer.set_clip_box(None);
+                            matcap_command_buffer.draw_meshes_to_render_target(iter::once((
+                                &sphere_gpu_mesh_handle,
+                                Material::MatcapShadedWithShadows,
+                                true,
+                            )));
+                            matcap_command_buffer.submit();
+
+                            renderer.remove_scene_mesh(sphere_gpu_mesh_handle);
+
+                            pending_reads
+                                .insert(matcap_render_target.id(), PendingRead::Matcap(path));
+                            renderer.request_offscreen_render_target_read(matcap_render_target);
                         }
-                        ViewportDrawMode::ShadedWireframe => {
-                            screenshot_command_buffer.draw_meshes_to_render_target(
-                                scene_gpu_mesh_handles
-                                    .values()
-                                    .filter(|(used, _)| viewport_draw_used_values || !used)
-                                    .map(|(used, handle)| {
-                                        if *used {
-                                            (handle, Material::TransparentMatcapShaded, false)
-                                        } else {
-                                            (handle, Material::MatcapShadedEdges, true)
-                                        }
-                                    }),
+                        Err(err) => {
+                            log::error!("Failed to add matcap sphere mesh: {}", err);
+                            notifications.push(
+                                time,
+                                NotificationLevel::Error,
+                                format!("Failed to bake matcap: {}", err),
                             );
                         }
-                        ViewportDrawMode::ShadedWireframeXray => {
-                            screenshot_command_buffer.draw_meshes_to_render_target(
-                                scene_gpu_mesh_handles
-                                    .values()
-                                    .filter(|(used, _)| viewport_draw_used_values || !used)
-                                    .map(|(used, handle)| {
-                                        if *used {
-                                            (handle, Material::TransparentMatcapShaded, false)
-                                        } else {
-                                            (handle, Material::MatcapShaded, true)
-                                        }
-                                    }),
+                    }
+                }
+
+                // -- Draw to offscreen render target for camera bookmarks --
+
+                // Like shadow studies, only one view is drawn per frame.
+                if let Some((view, path)) = view_render_queue.pop() {
+                    let view_render_target = renderer.add_offscreen_render_target(
+                        screenshot_options.width,
+                        screenshot_options.height,
+                    );
+
+                    let mut view_camera = camera.clone();
+                    view_camera
+                        .set_screen_dimensions(screenshot_options.width, screenshot_options.height);
+                    view_camera.set_view(&view);
+
+                    let view_clear_color = if screenshot_options.transparent {
+                        [0.0; 4]
+                    } else {
+                        clear_color
+                    };
+
+                    let mut view_command_buffer = renderer
+                        .begin_offscreen_command_buffer(view_clear_color, &view_render_target);
+                    view_command_buffer.set_light(&compute_scene_light(
+                        scene_bounding_box,
+                        compute_scene_sun_position(&sun_options, &geolocation),
+                    ));
+                    view_command_buffer.set_camera_matrices(
+                        &view_camera.projection_matrix(),
+                        &view_camera.view_matrix(),
+                    );
+                    view_command_buffer.set_clip_box(clip_box.corners());
+
+                    // Views are drawn like screenshots, without the ground.
+                    draw_scene_meshes(
+                        &mut view_command_buffer,
+                        &scene_graph,
+                        &session,
+                        viewport_draw_mode,
+                        viewport_draw_used_values,
+                    );
+
+                    view_command_buffer.submit();
+
+                    pending_reads.insert(view_render_target.id(), PendingRead::ViewRender(path));
+                    renderer.request_offscreen_render_target_read(view_render_target);
+                }
+
+                // -- Draw to offscreen render target for remote screenshots --
+
+                // Like views, only one screenshot is drawn per frame.
+                if !remote_screenshot_queue.is_empty() {
+                    let request = remote_screenshot_queue.remove(0);
+                    let (width, height) = match request.command {
+                        RemoteCommand::Screenshot { width, height, .. } => (width, height),
+                        _ => unreachable!("Only screenshot commands are queued"),
+                    };
+
+                    let remote_screenshot_render_target =
+                        renderer.add_offscreen_render_target(width, height);
+
+                    let mut remote_screenshot_camera = camera.clone();
+                    remote_screenshot_camera.set_screen_dimensions(width, height);
+
+                    let mut remote_screenshot_command_buffer = renderer
+                        .begin_offscreen_command_buffer(
+                            clear_color,
+                            &remote_screenshot_render_target,
+                        );
+                    remote_screenshot_command_buffer.set_light(&compute_scene_light(
+                        scene_bounding_box,
+                        compute_scene_sun_position(&sun_options, &geolocation),
+                    ));
+                    remote_screenshot_command_buffer.set_camera_matrices(
+                        &remote_screenshot_camera.projection_matrix(),
+                        &remote_screenshot_camera.view_matrix(),
+                    );
+                    remote_screenshot_command_buffer.set_clip_box(clip_box.corners());
+
+                    draw_scene_meshes(
+                        &mut remote_screenshot_command_buffer,
+                        &scene_graph,
+                        &session,
+                        viewport_draw_mode,
+                        viewport_draw_used_values,
+                    );
+
+                    remote_screenshot_command_buffer.submit();
+
+                    pending_reads.insert(
+                        remote_screenshot_render_target.id(),
+                        PendingRead::RemoteScreenshot(request),
+                    );
+                    renderer.request_offscreen_render_target_read(remote_screenshot_render_target);
+                }
+
+                // -- Draw to offscreen render targets for thumbnails --
+
+                if let Some(variant_id) = variant_thumbnail_requested.take() {
+                    let thumbnail_render_target = draw_thumbnail(
+                        &mut renderer,
+                        &camera,
+                        clear_color,
+                        scene_bounding_box,
+                        |command_buffer| {
+                            draw_scene_meshes(
+                                command_buffer,
+                                &scene_graph,
+                                &session,
+                                viewport_draw_mode,
+                                viewport_draw_used_values,
                             );
+                        },
+                    );
+
+                    pending_reads.insert(
+                        thumbnail_render_target.id(),
+                        PendingRead::Thumbnail(ThumbnailTarget::Variant(variant_id)),
+                    );
+                    renderer.request_offscreen_render_target_read(thumbnail_render_target);
+                }
 
-                            screenshot_command_buffer.draw_meshes_to_render_target(
-                                scene_gpu_mesh_handles
-                                    .values()
-                                    .filter(|(used, _)| !used)
-                                    .map(|(_, handle)| (handle, Material::EdgesXray, false)),
+                for (generation, sample_index, gpu_mesh_handles) in
+                    sweep_thumbnails_requested.drain(..)
+                {
+                    // Sweep samples are drawn with the current camera, so that
+                    // they can be compared by eye.
+                    let thumbnail_render_target = draw_thumbnail(
+                        &mut renderer,
+                        &camera,
+                        clear_color,
+                        scene_bounding_box,
+                        |command_buffer| {
+                            command_buffer.draw_meshes_to_render_target(
+                                gpu_mesh_handles
+                                    .iter()
+                                    .map(|handle| (handle, Material::MatcapShadedEdges, true)),
                             );
-                        }
-                    }
+                        },
+                    );
 
-                    screenshot_command_buffer.submit();
-                    renderer.request_offscreen_render_target_read(screenshot_render_target);
+                    pending_reads.insert(
+                        thumbnail_render_target.id(),
+                        PendingRead::Thumbnail(ThumbnailTarget::SweepSample(
+                            generation,
+                            sample_index,
+                        )),
+                    );
+                    renderer.request_offscreen_render_target_read(thumbnail_render_target);
+
+                    // The meshes are no longer needed once the drawing is
+                    // submitted.
+                    for gpu_mesh_handle in gpu_mesh_handles {
+                        renderer.remove_scene_mesh(gpu_mesh_handle);
+                    }
                 }
 
                 // -- Draw to viewport --
 
+                let ui_render_start = Instant::now();
                 let imgui_draw_data = ui_frame.render(&window);
+                profiler.record(Stage::Ui, ui_render_start.elapsed());
 
+                // Acquiring the swap chain frame blocks until the GPU has
+                // presented one of the previous frames
+                let swap_chain_wait_start = Instant::now();
                 let mut window_command_buffer =
-                    renderer.begin_command_buffer(clear_color, None, true);
-                window_command_buffer.set_light(&compute_scene_light(scene_bounding_box));
-                window_command_buffer
-                    .set_camera_matrices(&camera.projection_matrix(), &camera.view_matrix());
-
-                match viewport_draw_mode {
-                    ViewportDrawMode::Wireframe => {
-                        window_command_buffer.draw_meshes_to_render_target(
-                            scene_gpu_mesh_handles
-                                .values()
-                                .filter(|(used, _)| viewport_draw_used_values || !used)
-                                .map(|(used, handle)| {
-                                    if *used {
-                                        (handle, Material::TransparentMatcapShaded, false)
-                                    } else {
-                                        (handle, Material::Edges, true)
-                                    }
-                                }),
+                    match renderer.begin_command_buffer(clear_color, None, true) {
+                        Ok(window_command_buffer) => window_command_buffer,
+                        Err(err) => {
+                            report_renderer_error(&err);
+                            *control_flow = winit::event_loop::ControlFlow::Exit;
+                            return;
+                        }
+                    };
+                profiler.record(Stage::SwapChainWait, swap_chain_wait_start.elapsed());
+                let scene_passes_start = Instant::now();
+
+                if let Some(comparison) = &comparison {
+                    // Both halves of the split viewport are drawn with the
+                    // same camera and light, so that the compared designs
+                    // stay synchronized.
+                    let window_size = window.inner_size();
+                    let left_width = window_size.width / 2;
+                    let right_width = window_size.width - left_width;
+
+                    let mut split_camera = camera.clone();
+                    split_camera.set_screen_dimensions(left_width, window_size.height);
+
+                    let split_scene_bounding_box = BoundingBox::union(
+                        [scene_bounding_box, comparison.scene_bounding_box]
+                            .iter()
+                            .copied(),
+                    )
+                    .expect("Must produce a bounding box for non-empty iterator");
+
+                    window_command_buffer.set_light(&compute_scene_light(
+                        split_scene_bounding_box,
+                        compute_scene_sun_position(&sun_options, &geolocation),
+                    ));
+                    window_command_buffer.set_camera_matrices(
+                        &split_camera.projection_matrix(),
+                        &split_camera.view_matrix(),
+                    );
+                    window_command_buffer.set_clip_box(clip_box.corners());
+
+                    for (x, width, gpu_mesh_handles) in &[
+                        (0, left_width, &scene_graph),
+                        (left_width, right_width, &comparison.scene_graph),
+                    ] {
+                        window_command_buffer.set_viewport(Some(Viewport {
+                            x: *x,
+                            y: 0,
+                            width: *width,
+                            height: window_size.height,
+                        }));
+
+                        draw_scene_meshes(
+                            &mut window_command_buffer,
+                            gpu_mesh_handles,
+                            &session,
+                            viewport_draw_mode,
+                            viewport_draw_used_values,
                         );
-                    }
-                    ViewportDrawMode::Shaded => {
+
                         window_command_buffer.draw_meshes_to_render_target(
-                            scene_gpu_mesh_handles
-                                .values()
-                                .filter(|(used, _)| viewport_draw_used_values || !used)
-                                .map(|(used, handle)| {
-                                    if *used {
-                                        (handle, Material::TransparentMatcapShaded, false)
-                                    } else {
-                                        (handle, Material::MatcapShaded, true)
-                                    }
-                                }),
+                            placement_preview_gpu_mesh_handle
+                                .iter()
+                                .chain(box_drawing_preview_gpu_mesh_handle.iter())
+                                .chain(sketch_preview_gpu_mesh_handle.iter())
+                                .map(|handle| (handle, Material::TransparentMatcapShaded, false)),
                         );
-                    }
-                    ViewportDrawMode::ShadedWireframe => {
-                        window_command_buffer.draw_meshes_to_render_target(
-                            scene_gpu_mesh_handles
-                                .values()
-                                .filter(|(used, _)| viewport_draw_used_values || !used)
-                                .map(|(used, handle)| {
-                                    if *used {
-                                        (handle, Material::TransparentMatcapShaded, false)
-                                    } else {
-                                        (handle, Material::MatcapShadedEdges, true)
-                                    }
-                                }),
+
+                        if preferences.ground_plane.visible {
+                            window_command_buffer.draw_meshes_to_render_target(
+                                ground_plane_gpu_mesh_handle
+                                    .iter()
+                                    .map(|handle| (handle, Material::FlatWithShadows, false)),
+                            );
+                        }
+
+                        window_command_buffer.draw_images_to_render_target(
+                            reference_image_gpu_handles
+                                .iter()
+                                .flatten()
+                                .map(|(_, handle)| handle),
                         );
                     }
-                    ViewportDrawMode::ShadedWireframeXray => {
+
+                    window_command_buffer.set_viewport(None);
+                } else {
+                    window_command_buffer.set_light(&compute_scene_light(
+                        light_bounding_box,
+                        compute_scene_sun_position(&sun_options, &geolocation),
+                    ));
+                    window_command_buffer.set_clip_box(clip_box.corners());
+
+                    let window_size = window.inner_size();
+                    let window_views = stereo::views(
+                        &camera,
+                        window_size.width,
+                        window_size.height,
+                        if viewport_stereo {
+                            Some(screenshot_options.stereo_eye_separation)
+                        } else {
+                            None
+                        },
+                    );
+
+                    for view in &window_views {
+                        window_command_buffer.set_viewport(view.viewport);
+                        window_command_buffer
+                            .set_camera_matrices(&view.projection_matrix, &view.view_matrix);
+
+                        draw_scene_meshes(
+                            &mut window_command_buffer,
+                            &scene_graph,
+                            &session,
+                            viewport_draw_mode,
+                            viewport_draw_used_values,
+                        );
+
                         window_command_buffer.draw_meshes_to_render_target(
-                            scene_gpu_mesh_handles
-                                .values()
-                                .filter(|(used, _)| viewport_draw_used_values || !used)
-                                .map(|(used, handle)| {
-                                    if *used {
-                                        (handle, Material::TransparentMatcapShaded, false)
-                                    } else {
-                                        (handle, Material::MatcapShaded, true)
-                                    }
-                                }),
+                            streamed_gpu_mesh_handles
+                                .iter()
+                                .map(|handle| (handle, Material::MatcapShaded, true)),
                         );
 
                         window_command_buffer.draw_meshes_to_render_target(
-                            scene_gpu_mesh_handles
-                                .values()
-                                .filter(|(used, _)| !used)
-                                .map(|(_, handle)| (handle, Material::EdgesXray, false)),
+                            placement_preview_gpu_mesh_handle
+                                .iter()
+                                .chain(box_drawing_preview_gpu_mesh_handle.iter())
+                                .chain(sketch_preview_gpu_mesh_handle.iter())
+                                .map(|handle| (handle, Material::TransparentMatcapShaded, false)),
+                        );
+
+                        if preferences.ground_plane.visible {
+                            window_command_buffer.draw_meshes_to_render_target(
+                                ground_plane_gpu_mesh_handle
+                                    .iter()
+                                    .map(|handle| (handle, Material::FlatWithShadows, false)),
+                            );
+                        }
+
+                        window_command_buffer.draw_images_to_render_target(
+                            reference_image_gpu_handles
+                                .iter()
+                                .flatten()
+                                .map(|(_, handle)| handle),
                         );
                     }
-                }
 
-                window_command_buffer.draw_meshes_to_render_target(
-                    ground_plane_gpu_mesh_handle
-                        .iter()
-                        .map(|handle| (handle, Material::FlatWithShadows, false)),
-                );
+                    window_command_buffer.set_viewport(None);
+                }
 
                 #[cfg(not(feature = "dist"))]
                 match renderer_debug_view {
@@ -1187,8 +4346,121 @@ pub fn init_and_run(options: Options) -> ! {
                 #[cfg(feature = "dist")]
                 window_command_buffer.blit_render_target_to_swap_chain();
 
-                window_command_buffer.draw_ui_to_swap_chain(imgui_draw_data);
+                profiler.record(Stage::ScenePasses, scene_passes_start.elapsed());
+
+                let ui_pass_start = Instant::now();
+                let ui_pass_result = window_command_buffer.draw_ui_to_swap_chain(imgui_draw_data);
+                profiler.record(Stage::UiPass, ui_pass_start.elapsed());
+
+                // The command buffer must be submitted even if the UI failed
+                // to draw, so that it can be dropped
+                let submit_start = Instant::now();
                 window_command_buffer.submit();
+                profiler.record(Stage::Submit, submit_start.elapsed());
+
+                if let Err(err) = ui_pass_result {
+                    report_renderer_error(&err);
+                    *control_flow = winit::event_loop::ControlFlow::Exit;
+                    return;
+                }
+                profiler.record(
+                    Stage::MeshUpload,
+                    renderer.mesh_upload_duration() - mesh_upload_start,
+                );
+
+                // -- Draw to detached viewport --
+
+                if let Some(detached_viewport) = &detached_viewport {
+                    let mut detached_command_buffer = match renderer
+                        .begin_secondary_window_command_buffer(
+                            clear_color,
+                            &detached_viewport.handle,
+                        ) {
+                        Ok(detached_command_buffer) => detached_command_buffer,
+                        Err(err) => {
+                            report_renderer_error(&err);
+                            *control_flow = winit::event_loop::ControlFlow::Exit;
+                            return;
+                        }
+                    };
+
+                    detached_command_buffer.set_light(&compute_scene_light(
+                        light_bounding_box,
+                        compute_scene_sun_position(&sun_options, &geolocation),
+                    ));
+                    detached_command_buffer.set_camera_matrices(
+                        &detached_viewport.camera.projection_matrix(),
+                        &detached_viewport.camera.view_matrix(),
+                    );
+                    detached_command_buffer.set_clip_box(clip_box.corners());
+
+                    draw_scene_meshes(
+                        &mut detached_command_buffer,
+                        &scene_graph,
+                        &session,
+                        viewport_draw_mode,
+                        viewport_draw_used_values,
+                    );
+
+                    detached_command_buffer.draw_meshes_to_render_target(
+                        streamed_gpu_mesh_handles
+                            .iter()
+                            .map(|handle| (handle, Material::MatcapShaded, true)),
+                    );
+
+                    if preferences.ground_plane.visible {
+                        detached_command_buffer.draw_meshes_to_render_target(
+                            ground_plane_gpu_mesh_handle
+                                .iter()
+                                .map(|handle| (handle, Material::FlatWithShadows, false)),
+                        );
+                    }
+
+                    detached_command_buffer.draw_images_to_render_target(
+                        reference_image_gpu_handles
+                            .iter()
+                            .flatten()
+                            .map(|(_, handle)| handle),
+                    );
+
+                    detached_command_buffer.blit_render_target_to_swap_chain();
+                    detached_command_buffer.submit();
+                }
+
+                for texture in ui_textures_to_remove.drain(..) {
+                    renderer.remove_ui_texture(texture);
+                }
+
+                let busy = camera_interpolation.is_some()
+                    || light_interpolation.is_some()
+                    || sun_animation
+                    || !session.synced()
+                    || comparison
+                        .as_ref()
+                        .map_or(false, |comparison| !comparison.session.synced())
+                    || sweep.as_ref().map_or(false, |sweep| !sweep.finished())
+                    || !sweep_thumbnails_requested.is_empty()
+                    || optimization
+                        .as_ref()
+                        .map_or(false, |optimization| !optimization.finished())
+                    || variant_thumbnail_requested.is_some()
+                    || !pending_reads.is_empty()
+                    || !shadow_study_queue.is_empty()
+                    || !view_render_queue.is_empty()
+                    || !remote_requests_awaiting_run.is_empty()
+                    || !remote_screenshot_queue.is_empty()
+                    || !export_jobs.is_empty();
+
+                next_control_flow =
+                    match preferences
+                        .frame_limiter
+                        .next_frame_time(time, last_input_time, busy)
+                    {
+                        Some(next_frame_time) => {
+                            winit::event_loop::ControlFlow::WaitUntil(next_frame_time)
+                        }
+                        None => winit::event_loop::ControlFlow::Poll,
+                    };
             }
 
             winit::event::Event::RedrawRequested(_) => {
@@ -1202,6 +4474,30 @@ pub fn init_and_run(options: Options) -> ! {
                 // redraw.
             }
 
+            winit::event::Event::LoopDestroyed => {
+                if let Some(statistics) = &statistics {
+                    save_statistics(statistics);
+                }
+                if let Err(err) = param_defaults::save(&param_defaults) {
+                    log::error!("Failed to save remembered parameter values: {}", err);
+                }
+
+                if let Some(detached_viewport) = detached_viewport.take() {
+                    detached_viewport.destroy(&mut renderer);
+                }
+
+                let current_window_state = match (window.fullscreen(), &windowed_window_state) {
+                    (Some(_), Some(windowed_window_state)) => WindowState {
+                        fullscreen: window_fullscreen,
+                        ..windowed_window_state.clone()
+                    },
+                    _ => WindowState::capture(&window, window_fullscreen),
+                };
+                if let Err(err) = window_state::save(&current_window_state) {
+                    log::error!("Failed to save window state: {}", err);
+                }
+            }
+
             _ => (),
         }
 
@@ -1212,7 +4508,30 @@ pub fn init_and_run(options: Options) -> ! {
         // practice, latent ui capture state shouldn't be an issue.
         ui.process_event(&event, &window);
 
-        input_manager.process_event(&event, ui.want_capture_keyboard(), ui.want_capture_mouse());
+        if let winit::event::Event::WindowEvent { .. } = event {
+            last_input_time = Instant::now();
+        }
+
+        // Events of the detached viewport window only control its camera
+        let detached_viewport_event = match (&event, &mut detached_viewport) {
+            (winit::event::Event::WindowEvent { window_id, .. }, Some(detached_viewport))
+                if *window_id == detached_viewport.window.id() =>
+            {
+                detached_viewport
+                    .input_manager
+                    .process_event(&event, false, false);
+                true
+            }
+            _ => false,
+        };
+
+        if !detached_viewport_event {
+            input_manager.process_event(
+                &event,
+                ui.want_capture_keyboard(),
+                ui.want_capture_mouse(),
+            );
+        }
     });
 }
 
@@ -1259,6 +4578,459 @@ impl CameraInterpolation {
     }
 }
 
+/// Moves the bounding box covered by the shadow casting light to the scene
+/// bounding box gradually, so that the shadows don't pop when the scene
+/// changes.
+#[derive(Debug, Clone, Copy)]
+struct LightInterpolation {
+    source: BoundingBox<f32>,
+    target: BoundingBox<f32>,
+    target_time: Instant,
+}
+
+impl LightInterpolation {
+    fn new(source: BoundingBox<f32>, target: BoundingBox<f32>, time: Instant) -> Self {
+        LightInterpolation {
+            source,
+            target,
+            target_time: time + DURATION_LIGHT_INTERPOLATION,
+        }
+    }
+
+    fn update(&self, time: Instant, easing: &math::CubicBezierEasing) -> BoundingBox<f32> {
+        let duration_left = self.target_time.duration_since(time).as_secs_f32();
+        let whole_duration = DURATION_LIGHT_INTERPOLATION.as_secs_f32();
+        let t = easing.apply(1.0 - duration_left / whole_duration);
+
+        let minimum_point = self
+            .source
+            .minimum_point()
+            .coords
+            .lerp(&self.target.minimum_point().coords, t);
+        let maximum_point = self
+            .source
+            .maximum_point()
+            .coords
+            .lerp(&self.target.maximum_point().coords, t);
+        BoundingBox::new(&Point3::from(minimum_point), &Point3::from(maximum_point))
+    }
+}
+
+/// Reports a renderer failure, after which the application can't continue
+/// drawing.
+fn report_renderer_error(err: &RendererError) {
+    log::error!("Renderer failed: {}", err);
+    tinyfiledialogs::message_box_ok(
+        "Renderer failed",
+        &format!(
+            "The application can not continue drawing and will exit.\n\n{}",
+            err
+        ),
+        tinyfiledialogs::MessageBoxIcon::Error,
+    );
+}
+
+/// Draws a thumbnail to a new offscreen render target with the current camera
+/// and returns the render target handle, so that the caller can request a
+/// read.
+fn draw_thumbnail<F>(
+    renderer: &mut Renderer,
+    camera: &Camera,
+    clear_color: [f64; 4],
+    scene_bounding_box: BoundingBox<f32>,
+    draw: F,
+) -> OffscreenRenderTargetHandle
+where
+    F: FnOnce(&mut CommandBuffer),
+{
+    let thumbnail_render_target =
+        renderer.add_offscreen_render_target(variants::THUMBNAIL_WIDTH, variants::THUMBNAIL_HEIGHT);
+
+    let mut thumbnail_camera = camera.clone();
+    thumbnail_camera.set_screen_dimensions(variants::THUMBNAIL_WIDTH, variants::THUMBNAIL_HEIGHT);
+
+    let mut thumbnail_command_buffer =
+        renderer.begin_offscreen_command_buffer(clear_color, &thumbnail_render_target);
+    thumbnail_command_buffer.set_light(&compute_scene_light(scene_bounding_box, None));
+    thumbnail_command_buffer.set_camera_matrices(
+        &thumbnail_camera.projection_matrix(),
+        &thumbnail_camera.view_matrix(),
+    );
+    // Thumbnails show the whole design, regardless of the clipping box.
+    thumbnail_command_buffer.set_clip_box(None);
+
+    draw(&mut thumbnail_command_buffer);
+
+    thumbnail_command_buffer.submit();
+
+    thumbnail_render_target
+}
+
+/// The compared variant shown in the right half of the split viewport.
+///
+/// The variant is executed in its own session, independent from the session
+/// that is being edited. When switching the compared variant, the session is
+/// re-used, so that the results of statements shared by the variants stay
+/// cached in the interpreter.
+struct Comparison {
+    variant_id: VariantId,
+    session: Session,
+    scene_bounding_box: BoundingBox<f32>,
+    scene_graph: SceneGraph,
+}
+
+impl Comparison {
+    fn new(
+        time: Instant,
+        variant_id: VariantId,
+        stmts: Vec<interpreter::ast::Stmt>,
+        explode_factor: f32,
+    ) -> Self {
+        let mut session = Session::new();
+        session.set_autorun_delay(Some(DURATION_AUTORUN_DELAY));
+        session.set_prog_stmts(time, stmts);
+
+        Self {
+            variant_id,
+            session,
+            scene_bounding_box: BoundingBox::unit(),
+            scene_graph: SceneGraph::with_explode_factor(explode_factor),
+        }
+    }
+
+    /// Removes the meshes of the compared variant from the GPU.
+    fn destroy(self, renderer: &mut Renderer) {
+        for gpu_mesh_handle in self.scene_graph.clear() {
+            renderer.remove_scene_mesh(gpu_mesh_handle);
+        }
+    }
+}
+
+/// Returns the bounding box of all meshes contained in the value, if it
+/// contains any.
+fn value_bounding_box(value: &Value) -> Option<BoundingBox<f32>> {
+    match value {
+        Value::Mesh(mesh) => Some(mesh.bounding_box()),
+        Value::MeshArray(mesh_array) => {
+            BoundingBox::union(mesh_array.iter_refcounted().map(|mesh| mesh.bounding_box()))
+        }
+        _ => None,
+    }
+}
+
+/// Uploads the mesh to the GPU. Updates the GPU mesh instead, if given.
+fn upload_scene_mesh(
+    renderer: &mut Renderer,
+    mesh: &Mesh,
+    gpu_mesh_handle: Option<GpuMeshHandle>,
+) -> GpuMeshHandle {
+    let gpu_mesh = GpuMesh::from_mesh(mesh);
+    match gpu_mesh_handle {
+        Some(gpu_mesh_handle) => {
+            renderer
+                .update_scene_mesh(&gpu_mesh_handle, &gpu_mesh)
+                .expect("Failed to update scene mesh");
+            gpu_mesh_handle
+        }
+        None => renderer
+            .add_scene_mesh(&gpu_mesh)
+            .expect("Failed to upload scene mesh"),
+    }
+}
+
+/// Recomputes the ground plane mesh for the scene bounds and replaces the
+/// previous ground plane GPU mesh, if any.
+fn rebuild_ground_plane(
+    renderer: &mut Renderer,
+    options: &GroundPlaneOptions,
+    scene_bounding_box: &BoundingBox<f32>,
+    gpu_mesh_handle: &mut Option<GpuMeshHandle>,
+) {
+    let mesh = ground_plane::compute_ground_plane_mesh(options, scene_bounding_box);
+    if let Some(previous_gpu_mesh_handle) = gpu_mesh_handle.take() {
+        renderer.remove_scene_mesh(previous_gpu_mesh_handle);
+    }
+    *gpu_mesh_handle = Some(
+        renderer
+            .add_scene_mesh(&GpuMesh::from_mesh(&mesh))
+            .expect("Failed to add ground plane mesh"),
+    );
+}
+
+/// Adds the value to the scene graph and uploads all meshes contained in it
+/// to the GPU. GPU meshes of removed values at the same paths are reused.
+fn add_scene_value(
+    renderer: &mut Renderer,
+    scene_graph: &mut SceneGraph,
+    var_ident: VarIdent,
+    value: Value,
+    used: bool,
+) {
+    let meshes = match value {
+        Value::Mesh(mesh) => vec![mesh],
+        Value::MeshArray(mesh_array) => mesh_array.iter_refcounted().collect(),
+        _ => {
+            // Ignore other values, we don't display them in the viewport
+            return;
+        }
+    };
+
+    scene_graph.insert(var_ident, used, meshes, |mesh, gpu_mesh_handle| {
+        upload_scene_mesh(renderer, mesh, gpu_mesh_handle)
+    });
+}
+
+/// Updates the layer visibility and display material of the value of the
+/// variable from the session, and applies the display state of the value to
+/// its GPU meshes. The geometry is not uploaded again.
+fn sync_scene_value(
+    renderer: &mut Renderer,
+    scene_graph: &mut SceneGraph,
+    session: &Session,
+    var_ident: VarIdent,
+) {
+    scene_graph.set_visible(var_ident, session.layers().var_visible(var_ident));
+    scene_graph.set_material(var_ident, session.effective_display_material(var_ident));
+
+    if let Some(value_node) = scene_graph.get(var_ident) {
+        for mesh_node in value_node.drawn() {
+            renderer.set_scene_mesh_translation(&mesh_node.gpu, &mesh_node.translation);
+            if let Some(display_material) = value_node.material {
+                renderer.set_scene_mesh_color(&mesh_node.gpu, display_material.gpu_color());
+            }
+        }
+    }
+}
+
+/// Moves the meshes of the values apart by the explode factor. Meshes split
+/// into parts for the first time upload the parts, the GPU meshes of parts no
+/// longer exploded are removed.
+fn explode_scene_values(
+    renderer: &mut Renderer,
+    scene_graph: &mut SceneGraph,
+    session: &Session,
+    explode_factor: f32,
+) {
+    scene_graph.set_explode_factor(explode_factor, |mesh, gpu_mesh_handle| {
+        upload_scene_mesh(renderer, mesh, gpu_mesh_handle)
+    });
+    for var_ident in scene_graph.var_idents() {
+        sync_scene_value(renderer, scene_graph, session, var_ident);
+    }
+
+    for gpu_mesh_handle in scene_graph.collect_garbage() {
+        renderer.remove_scene_mesh(gpu_mesh_handle);
+    }
+}
+
+/// Returns the variables declared by the statements of the session, in the
+/// order of the statements.
+fn stmt_var_idents(session: &Session) -> impl Iterator<Item = VarIdent> + Clone + '_ {
+    session.stmts().iter().map(|stmt| {
+        let ast::Stmt::VarDecl(var_decl) = stmt;
+        var_decl.ident()
+    })
+}
+
+/// Records drawing of the scene geometry into the command buffer, picking
+/// materials based on the viewport draw mode and whether the value is used.
+/// In shaded draw modes, unused values with a display material are drawn in
+/// it instead. Values on hidden layers are not drawn.
+///
+/// The ground plane is not drawn, as not all render targets want it.
+fn draw_scene_meshes(
+    command_buffer: &mut CommandBuffer,
+    scene_graph: &SceneGraph,
+    session: &Session,
+    viewport_draw_mode: ViewportDrawMode,
+    viewport_draw_used_values: bool,
+) {
+    // Draw in the order of the statements, so that values at the same depth,
+    // e.g. overlapping transparent used values, are always blended in the
+    // same order
+    let ordered_mesh_nodes: Vec<_> = scene_graph
+        .iter_values_ordered()
+        .flat_map(|(var_ident, value_node)| {
+            value_node
+                .drawn()
+                .map(move |mesh_node| (var_ident, value_node, mesh_node))
+        })
+        .collect();
+
+    let focused_var = session.focused_var();
+    // While an operation is focused, its inputs are dimmed like used values
+    // and its output is drawn like an unused value.
+    let drawn = |var_ident: VarIdent, value_node: &ValueNode| {
+        value_drawn(session, var_ident, value_node, viewport_draw_used_values)
+    };
+    let dimmed = |var_ident: VarIdent, value_node: &ValueNode| match focused_var {
+        Some(focused_var) => var_ident != focused_var,
+        None => value_node.used,
+    };
+    let unused_material = |value_node: &ValueNode, material: Material| match value_node.material {
+        Some(display_material) => (
+            display_material.renderer_material(),
+            display_material.casts_shadows(),
+        ),
+        None => (material, true),
+    };
+
+    match viewport_draw_mode {
+        ViewportDrawMode::Wireframe => {
+            command_buffer.draw_meshes_to_render_target(
+                ordered_mesh_nodes
+                    .iter()
+                    .copied()
+                    .filter(|(var_ident, value_node, _)| drawn(*var_ident, value_node))
+                    .map(|(var_ident, value_node, mesh_node)| {
+                        let handle = &mesh_node.gpu;
+                        if dimmed(var_ident, value_node) {
+                            (handle, Material::TransparentMatcapShaded, false)
+                        } else {
+                            (handle, Material::Edges, true)
+                        }
+                    }),
+            );
+        }
+        ViewportDrawMode::Shaded => {
+            command_buffer.draw_meshes_to_render_target(
+                ordered_mesh_nodes
+                    .iter()
+                    .copied()
+                    .filter(|(var_ident, value_node, _)| drawn(*var_ident, value_node))
+                    .map(|(var_ident, value_node, mesh_node)| {
+                        let handle = &mesh_node.gpu;
+                        if dimmed(var_ident, value_node) {
+                            (handle, Material::TransparentMatcapShaded, false)
+                        } else {
+                            let (material, cast_shadows) =
+                                unused_material(value_node, Material::MatcapShaded);
+                            (handle, material, cast_shadows)
+                        }
+                    }),
+            );
+        }
+        ViewportDrawMode::ShadedWireframe => {
+            command_buffer.draw_meshes_to_render_target(
+                ordered_mesh_nodes
+                    .iter()
+                    .copied()
+                    .filter(|(var_ident, value_node, _)| drawn(*var_ident, value_node))
+                    .map(|(var_ident, value_node, mesh_node)| {
+                        let handle = &mesh_node.gpu;
+                        if dimmed(var_ident, value_node) {
+                            (handle, Material::TransparentMatcapShaded, false)
+                        } else {
+                            let (material, cast_shadows) =
+                                unused_material(value_node, Material::MatcapShadedEdges);
+                            (handle, material, cast_shadows)
+                        }
+                    }),
+            );
+        }
+        ViewportDrawMode::ShadedWireframeXray => {
+            command_buffer.draw_meshes_to_render_target(
+                ordered_mesh_nodes
+                    .iter()
+                    .copied()
+                    .filter(|(var_ident, value_node, _)| drawn(*var_ident, value_node))
+                    .map(|(var_ident, value_node, mesh_node)| {
+                        let handle = &mesh_node.gpu;
+                        if dimmed(var_ident, value_node) {
+                            (handle, Material::TransparentMatcapShaded, false)
+                        } else {
+                            let (material, cast_shadows) =
+                                unused_material(value_node, Material::MatcapShaded);
+                            (handle, material, cast_shadows)
+                        }
+                    }),
+            );
+
+            command_buffer.draw_meshes_to_render_target(
+                ordered_mesh_nodes
+                    .iter()
+                    .copied()
+                    .filter(|(var_ident, value_node, _)| {
+                        drawn(*var_ident, value_node) && !dimmed(*var_ident, value_node)
+                    })
+                    .map(|(_, _, mesh_node)| (&mesh_node.gpu, Material::EdgesXray, false)),
+            );
+        }
+    }
+}
+
+/// Returns whether the value of the variable is drawn in the viewport. While
+/// an operation is focused, only its inputs and output are drawn, regardless
+/// of their layers.
+fn value_drawn(
+    session: &Session,
+    var_ident: VarIdent,
+    value_node: &ValueNode,
+    viewport_draw_used_values: bool,
+) -> bool {
+    match session.focused_var() {
+        Some(_) => session.var_in_focus(var_ident),
+        None => (viewport_draw_used_values || !value_node.used) && value_node.visible,
+    }
+}
+
+/// Returns the labels of the values drawn in the viewport and the points
+/// above the values where they are drawn.
+fn collect_value_labels(
+    session: &Session,
+    scene_graph: &SceneGraph,
+    viewport_draw_used_values: bool,
+) -> Vec<(Point3<f32>, String)> {
+    let mut value_labels = Vec::new();
+
+    for stmt in session.stmts() {
+        let ast::Stmt::VarDecl(var_decl) = stmt;
+        let var_ident = var_decl.ident();
+
+        let value_node = match scene_graph.get(var_ident) {
+            Some(value_node) => value_node,
+            None => continue,
+        };
+        if !value_drawn(session, var_ident, value_node, viewport_draw_used_values)
+            || !session.layers().var_labeled(var_ident)
+        {
+            continue;
+        }
+
+        if let Some(text) = session.value_label_text(var_ident) {
+            value_labels.push((value_node.label_anchor, text));
+        }
+    }
+
+    value_labels
+}
+
+/// Loads the image file of the reference image and uploads it to the GPU.
+/// Returns the aspect ratio of the image and its GPU handle.
+fn add_scene_reference_image(
+    renderer: &mut Renderer,
+    reference_image: &ReferenceImage,
+) -> Result<(f32, GpuImageHandle), image::ImageError> {
+    let (rgba, width, height) = reference_images::load_image_rgba8_unorm(&reference_image.path)?;
+    let aspect_ratio = width as f32 / height as f32;
+    let gpu_handle = renderer.add_scene_image_rgba8_unorm(
+        width,
+        height,
+        &rgba,
+        &reference_image.corners(aspect_ratio),
+        reference_image.opacity,
+    );
+
+    Ok((aspect_ratio, gpu_handle))
+}
+
+fn set_matcap_from_file(renderer: &mut Renderer, path: &Path) -> Result<(), image::ImageError> {
+    let (rgba, width, height) = reference_images::load_image_rgba8_unorm(path)?;
+    renderer.set_matcap_texture_rgba8_unorm(width, height, &rgba);
+
+    Ok(())
+}
+
 fn decode_image_rgba8_unorm(data: &[u8]) -> (Vec<u8>, u32, u32) {
     let image = image::load_from_memory(data).expect("Failed to decode image.");
     let (width, height) = image.dimensions();
@@ -1271,48 +5043,350 @@ fn decode_image_rgba8_unorm(data: &[u8]) -> (Vec<u8>, u32, u32) {
     (rgba, width, height)
 }
 
-fn encode_and_write_png(
-    path: &Path,
-    data: &[u8],
-    width: u32,
-    height: u32,
-    bytes_per_row_unpadded: u32,
-    bytes_per_row_padded: u32,
-) -> Result<(), Box<dyn Error>> {
-    let file = File::create(path)?;
-
-    let mut png_encoder = png::Encoder::new(file, width, height);
-    png_encoder.set_color(png::ColorType::RGBA);
-    png_encoder.set_depth(png::BitDepth::Eight);
-
-    // The data we got back from the renderer can
-    // contain padding between rows. We iteratate over
-    // rows and make sure not to copy the padding bytes.
-    let bpr_unpadded = cast_usize(bytes_per_row_unpadded);
-    let bpr_padded = cast_usize(bytes_per_row_padded);
-
-    let mut png_writer = png_encoder
-        .write_header()?
-        .into_stream_writer_with_size(bpr_unpadded);
-
-    for chunk in data.chunks(bpr_padded) {
-        let mut bytes_written = 0;
-        while bytes_written < bpr_unpadded {
-            let written = png_writer.write(&chunk[bytes_written..bpr_unpadded])?;
-            bytes_written += written;
+fn create_project(
+    session: &Session,
+    variants: &Variants,
+    unit: Unit,
+    geolocation: Geolocation,
+    reference_images: &[ReferenceImage],
+    camera_bookmarks: &[CameraBookmark],
+    viewport: Option<ProjectViewport>,
+) -> project::Project {
+    let mut pinned_vars: Vec<_> = session.pinned_vars().collect();
+    pinned_vars.sort_by_key(|var_ident| var_ident.0);
+
+    let mut display_materials: Vec<_> = session
+        .display_materials()
+        .iter()
+        .map(|(var_ident, display_material)| (*var_ident, *display_material))
+        .collect();
+    display_materials.sort_by_key(|(var_ident, _)| var_ident.0);
+
+    let layers = session
+        .layers()
+        .iter()
+        .map(|(layer_id, layer)| (layer_id, layer.clone()))
+        .collect();
+    let mut var_layers: Vec<_> = session.layers().var_layers().collect();
+    var_layers.sort_by_key(|(var_ident, _)| var_ident.0);
+
+    let mut var_names: Vec<_> = session
+        .var_names()
+        .iter()
+        .map(|(var_ident, var_name)| (*var_ident, var_name.clone()))
+        .collect();
+    var_names.sort_by_key(|(var_ident, _)| var_ident.0);
+
+    let mut value_labels: Vec<_> = session
+        .value_labels()
+        .iter()
+        .map(|(var_ident, value_label)| (*var_ident, value_label.clone()))
+        .collect();
+    value_labels.sort_by_key(|(var_ident, _)| var_ident.0);
+
+    let mut live_linked_vars: Vec<_> = session.live_linked_vars().collect();
+    live_linked_vars.sort_by_key(|var_ident| var_ident.0);
+
+    let mut voxel_size_inheriting_vars: Vec<_> = session.voxel_size_inheriting_vars().collect();
+    voxel_size_inheriting_vars.sort_by_key(|var_ident| var_ident.0);
+
+    let mut param_links: Vec<_> = session.param_links().collect();
+    param_links.sort_by_key(|(param, _)| (param.var_ident.0, param.arg_index));
+
+    let mut stmt_notes: Vec<_> = session
+        .stmt_notes()
+        .iter()
+        .map(|(var_ident, stmt_note)| (*var_ident, stmt_note.clone()))
+        .collect();
+    stmt_notes.sort_by_key(|(var_ident, _)| var_ident.0);
+
+    project::Project {
+        version: 1,
+        stmts: session.stmts().to_vec(),
+        variants: variants
+            .iter()
+            .map(|variant| project::ProjectVariant {
+                name: variant.name().to_string(),
+                stmts: variant.stmts().to_vec(),
+            })
+            .collect(),
+        unit,
+        geolocation,
+        reference_images: reference_images.to_vec(),
+        pinned_vars,
+        display_materials,
+        layers,
+        var_layers,
+        var_names,
+        value_labels,
+        stmt_notes,
+        notes: session.notes().to_string(),
+        camera_bookmarks: camera_bookmarks.to_vec(),
+        live_linked_vars,
+        voxel_size: Some(session.voxel_size()),
+        voxel_size_inheriting_vars,
+        param_links,
+        viewport,
+    }
+}
+
+/// Loads the usage statistics to record to, if the user agreed to keep them,
+/// and counts the current session.
+fn load_statistics(consent: StatisticsConsent) -> Option<Statistics> {
+    if consent != StatisticsConsent::Granted {
+        return None;
+    }
+
+    let mut statistics = match statistics::load() {
+        Ok(statistics) => statistics,
+        Err(err) => {
+            log::warn!("Failed to load usage statistics, starting anew: {}", err);
+            Statistics::default()
         }
+    };
+    statistics.sessions += 1;
+
+    Some(statistics)
+}
+
+/// Saves the event log of the session next to the project. Only the new
+/// events are appended, unless the project was saved to a different path.
+fn save_event_log(
+    session: &mut Session,
+    project_path: &Path,
+    previous_project_path: Option<&Path>,
+) {
+    let event_log_path = event_log::path_for_project(project_path);
+    let result = if previous_project_path == Some(project_path) {
+        session.event_log_mut().save(&event_log_path)
+    } else {
+        session.event_log_mut().save_all(&event_log_path)
+    };
+
+    if let Err(err) = result {
+        log::error!("Failed to save event log: {}", err);
+    }
+}
+
+fn save_statistics(statistics: &Statistics) {
+    if let Err(err) = statistics::save(statistics) {
+        log::error!("Failed to save usage statistics: {}", err);
+    }
+}
+
+/// Records the finished pipeline run with the operations currently in the
+/// pipeline.
+fn record_pipeline_run(statistics: &mut Statistics, session: &Session, duration: Duration) {
+    let function_table = session.function_table();
+    let operation_names = session.stmts().iter().filter_map(|stmt| {
+        let ast::Stmt::VarDecl(var_decl) = stmt;
+        function_table
+            .get(&var_decl.init_expr().ident())
+            .map(|func| func.info().name)
+    });
+
+    statistics.record_pipeline_run(duration, operation_names);
+}
+
+/// Finds the visible scene mesh closest to the ray origin hit by the ray.
+/// Returns the variable holding the mesh and the distance of the hit.
+fn pick_scene_mesh(
+    scene_graph: &SceneGraph,
+    draw_used_values: bool,
+    origin: &Point3<f32>,
+    direction: &Vector3<f32>,
+) -> Option<(VarIdent, f32)> {
+    scene_graph
+        .iter()
+        .filter(|(_, value_node, mesh_node)| {
+            (draw_used_values || !value_node.used) && !mesh_node.mesh.faces().is_empty()
+        })
+        .filter_map(|(value_path, _, mesh_node)| {
+            Bvh::from_mesh(&mesh_node.mesh)
+                .closest_ray_distance(origin, direction)
+                .map(|distance| (value_path.0, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+}
+
+/// Scales the mesh held by the variable by the calibrated factor. Updates the
+/// scale of the Transform declaring the variable, or adds a new Transform of
+/// the variable to the pipeline.
+fn apply_scale_calibration(
+    time: Instant,
+    session: &mut Session,
+    var_ident: VarIdent,
+    factor: f32,
+) -> Result<(), String> {
+    if let Some((stmt_index, stmt)) =
+        calibration::rescaled_transform_stmt(session.stmts(), var_ident, factor)
+    {
+        session.set_prog_stmt_at(time, stmt_index, stmt);
+        return Ok(());
     }
 
-    png_writer.finish()?;
+    let (stmt_index, _) = session
+        .var_decl_stmt_index_and_var_name_for_ident(var_ident)
+        .ok_or_else(|| String::from("The calibrated mesh is no longer in the pipeline"))?;
+    let ast::Stmt::VarDecl(var_decl) = &session.stmts()[stmt_index];
+    let func_ident = var_decl.init_expr().ident();
+    if session.function_table()[&func_ident].return_ty() != Ty::Mesh {
+        return Err(String::from(
+            "Only single meshes can be calibrated. Join the meshes or extract one first.",
+        ));
+    }
+
+    let func_ident = interpreter_funcs::FUNC_ID_TRANSFORM;
+    let args =
+        calibration::new_transform_args(ui::default_args(session, func_ident), var_ident, factor);
+    let stmt = ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+        session
+            .next_free_var_ident()
+            .ok_or_else(|| String::from("Failed to find free variable identifier"))?,
+        ast::CallExpr::new(func_ident, args),
+    ));
+    session.push_prog_stmt(time, stmt);
 
     Ok(())
 }
 
+/// Finds where an object would be placed for the ray: on the closest visible
+/// mesh hit by the ray, or on the ground. The horizontal position snaps to
+/// the distance increment, if snapping is enabled.
+fn compute_placement_point(
+    scene_graph: &SceneGraph,
+    draw_used_values: bool,
+    place_on_surfaces: bool,
+    snapping: Snapping,
+    origin: &Point3<f32>,
+    direction: &Vector3<f32>,
+) -> Option<Point3<f32>> {
+    // FIXME: @Optimization This builds the BVH of every scene mesh each time
+    // the cursor moves. Cache the BVHs, if placing over large scenes gets
+    // slow.
+    let surface_point = if place_on_surfaces {
+        pick_scene_mesh(scene_graph, draw_used_values, origin, direction)
+            .map(|(_, distance)| origin + direction * distance)
+    } else {
+        None
+    };
+
+    surface_point
+        .or_else(|| placement::ground_point(origin, direction))
+        .map(|point| {
+            Point3::new(
+                snap_value(snapping, Dimension::Length, point.x),
+                snap_value(snapping, Dimension::Length, point.y),
+                point.z,
+            )
+        })
+}
+
+/// Snaps the value of the dimension to its increment, if snapping is enabled
+/// for the dimension.
+fn snap_value(snapping: Snapping, dimension: Dimension, value: f32) -> f32 {
+    match snapping.increment(dimension) {
+        Some(increment) => snapping::snap(value, increment),
+        None => value,
+    }
+}
+
+/// A file imported by an OBJ import operation declares different units than
+/// the project.
+struct UnitConversionOffer {
+    var_ident: VarIdent,
+    path: String,
+    declared_unit: Unit,
+}
+
+/// Remembers the OBJ imports already checked for declaring different units
+/// than the project.
+#[derive(Default)]
+struct UnitConversionCheck {
+    checked_imports: HashSet<(VarIdent, String)>,
+}
+
+impl UnitConversionCheck {
+    /// Forgets the checked imports, e.g. after the project units change.
+    fn reset(&mut self) {
+        self.checked_imports.clear();
+    }
+
+    /// Looks for OBJ imports with files declaring different units than the
+    /// project, which are not scaled yet. Each import is checked only once
+    /// per file path, so that the user is not asked again after declining.
+    fn find_offer(&mut self, session: &Session, project_unit: Unit) -> Option<UnitConversionOffer> {
+        for stmt in session.stmts() {
+            let ast::Stmt::VarDecl(var_decl) = stmt;
+            let init_expr = var_decl.init_expr();
+            if init_expr.ident() != interpreter_funcs::FUNC_ID_IMPORT_OBJ_MESH
+                && init_expr.ident() != interpreter_funcs::FUNC_ID_IMPORT_OBJ_JOIN
+            {
+                continue;
+            }
+
+            let args = init_expr.args();
+            let path = match args.get(0) {
+                Some(ast::Expr::Lit(ast::LitExpr::String(path))) if !path.is_empty() => path,
+                _ => continue,
+            };
+            let scale = match args.get(interpreter_funcs::IMPORT_OBJ_PARAM_INDEX_UNIT_SCALE) {
+                Some(ast::Expr::Lit(ast::LitExpr::Float(scale))) => *scale,
+                _ => continue,
+            };
+
+            if !self
+                .checked_imports
+                .insert((var_decl.ident(), path.clone()))
+            {
+                continue;
+            }
+
+            // The user already scaled the import themselves.
+            if (scale - 1.0).abs() > f32::EPSILON {
+                continue;
+            }
+
+            if let Ok(Some(declared_unit)) = importer::obj_file_declared_unit(path) {
+                if declared_unit != project_unit {
+                    return Some(UnitConversionOffer {
+                        var_ident: var_decl.ident(),
+                        path: path.clone(),
+                        declared_unit,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
 fn compute_scene_camera_radius(scene_bounding_box: BoundingBox<f32>) -> f32 {
     scene_bounding_box.diagonal().norm() * 10.0
 }
 
-fn compute_scene_light(scene_bounding_box: BoundingBox<f32>) -> DirectionalLight {
+/// Returns the sun position relative to the scene axes, or `None` if the sun
+/// model is disabled.
+fn compute_scene_sun_position(
+    sun_options: &SunOptions,
+    geolocation: &Geolocation,
+) -> Option<SunPosition> {
+    sun_options
+        .sun_position(geolocation.latitude)
+        .map(|sun_position| geolocation.scene_sun_position(sun_position))
+}
+
+/// Computes the shadow casting light for the scene. The light shines straight
+/// down, unless a sun position is given.
+fn compute_scene_light(
+    scene_bounding_box: BoundingBox<f32>,
+    sun_position: Option<SunPosition>,
+) -> DirectionalLight {
+    if let Some(sun_position) = sun_position {
+        return compute_scene_sun_light(scene_bounding_box, sun_position);
+    }
+
     // Extend the bounding box to always contain a point with Z=0 so that we can
     // cast shadows on the ground plane.
     let scene_center = scene_bounding_box.center();
@@ -1342,32 +5416,36 @@ fn compute_scene_light(scene_bounding_box: BoundingBox<f32>) -> DirectionalLight
     }
 }
 
-fn compute_ground_plane_mesh(scene_bounding_box: &BoundingBox<f32>) -> Mesh {
-    let dimension = f32::max(1000.0, scene_bounding_box.diagonal().norm() * 100.0);
-    mesh::primitive::create_mesh_plane(
-        Plane::new(
-            &Point3::origin(),
-            &Vector3::new(1.0, 0.0, 0.0),
-            &Vector3::new(0.0, 1.0, 0.0),
-        ),
-        Vector2::new(dimension, dimension),
-    )
-}
+fn compute_scene_sun_light(
+    scene_bounding_box: BoundingBox<f32>,
+    sun_position: SunPosition,
+) -> DirectionalLight {
+    // The shadows of a low sun are very long, and would spread the shadow map
+    // too thin. Also clamp below the horizon, so that the light keeps
+    // pointing down.
+    let direction = SunPosition {
+        azimuth: sun_position.azimuth,
+        altitude: sun_position.altitude.max(SUN_MIN_SHADOW_ALTITUDE),
+    }
+    .light_direction();
 
-fn change_window_title(window: &winit::window::Window, project_status: &ProjectStatus) {
-    let filename = match &project_status.path {
-        Some(project_path) => Path::new(project_path)
-            .file_name()
-            .expect("Failed to parse file name of the project.")
-            .to_str()
-            .expect("Project file name isn't valid UTF-8."),
-        None => "unsaved project",
-    };
-    let join_str = if project_status.changed_since_last_save {
-        " - *"
-    } else {
-        " - "
-    };
+    // The light must cover the scene, and the shadows the scene casts on the
+    // ground plane, i.e. the projections of the scene onto the ground along
+    // the light direction.
+    let corners = scene_bounding_box.corners();
+    let ground_projections = corners
+        .iter()
+        .map(|corner| corner - direction * (corner.z / direction.z));
+    let bounding_box = BoundingBox::from_points(corners.iter().copied().chain(ground_projections))
+        .expect("Must produce a bounding box for non-empty iterator");
 
-    window.set_title(&[BASE_WINDOW_TITLE, filename].join(join_str));
+    let radius = bounding_box.diagonal().norm() / 2.0 * 1.1;
+
+    DirectionalLight {
+        position: bounding_box.center() - direction * radius,
+        direction: direction * 2.0 * radius,
+        min_range: 0.001,
+        max_range: 2.0 * radius,
+        width: 2.0 * radius,
+    }
 }
@@ -0,0 +1,101 @@
+use crate::importer::{self, ImporterError, Model};
+
+/// A mesh bundled with the application, so that common context geometry can
+/// be used without any external files. The assets are modeled in meters.
+#[derive(Debug, Clone, Copy)]
+pub struct LibraryAsset {
+    /// Stable identifier stored in projects.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    obj_data: &'static [u8],
+}
+
+// IMPORTANT: Do not change or remove the asset IDs, ever! Projects refer to
+// the assets by them.
+pub static ASSETS: &[LibraryAsset] = &[
+    LibraryAsset {
+        id: "tree-deciduous",
+        name: "Deciduous tree",
+        description: "Tree with a round crown, 6 m tall.",
+        obj_data: include_bytes!("../resources/library/tree_deciduous.obj"),
+    },
+    LibraryAsset {
+        id: "tree-conifer",
+        name: "Conifer tree",
+        description: "Tree with a conical crown, 8 m tall.",
+        obj_data: include_bytes!("../resources/library/tree_conifer.obj"),
+    },
+    LibraryAsset {
+        id: "human-figure",
+        name: "Human figure",
+        description: "Standing person, 1.75 m tall.",
+        obj_data: include_bytes!("../resources/library/human_figure.obj"),
+    },
+    LibraryAsset {
+        id: "house-gable",
+        name: "Gable roof house",
+        description: "Family house with a gable roof, 8 x 10 m, 6.5 m tall.",
+        obj_data: include_bytes!("../resources/library/house_gable.obj"),
+    },
+    LibraryAsset {
+        id: "apartment-block",
+        name: "Apartment block",
+        description: "Five floor block with a flat roof, 12 x 24 m, 15 m tall.",
+        obj_data: include_bytes!("../resources/library/apartment_block.obj"),
+    },
+];
+
+/// Finds the asset by its identifier.
+pub fn find_asset(id: &str) -> Option<&'static LibraryAsset> {
+    ASSETS.iter().find(|asset| asset.id == id)
+}
+
+impl LibraryAsset {
+    /// Parses the embedded OBJ data of the asset with the importer.
+    pub fn import(&self) -> Result<Vec<Model>, ImporterError> {
+        let (tobj_models, _) = importer::obj_buf_into_tobj(&mut &self.obj_data[..])?;
+        let models = importer::tobj_to_internal(tobj_models)?;
+
+        Ok(models)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bounding_box::BoundingBox;
+
+    use super::*;
+
+    #[test]
+    fn test_library_assets_import_standing_on_ground() {
+        for asset in ASSETS {
+            let models = asset.import().expect("Library asset must import");
+            assert!(!models.is_empty(), "Library asset {} is empty", asset.id);
+
+            let bbox = BoundingBox::union(models.iter().map(|model| model.mesh.bounding_box()))
+                .expect("Must produce a bounding box for non-empty iterator");
+            approx::assert_relative_eq!(bbox.minimum_point().z, 0.0, epsilon = 0.001);
+        }
+    }
+
+    #[test]
+    fn test_library_asset_ids_are_unique() {
+        for (index, asset) in ASSETS.iter().enumerate() {
+            assert!(
+                ASSETS[index + 1..].iter().all(|other| other.id != asset.id),
+                "Library asset ID {} is not unique",
+                asset.id,
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_asset() {
+        assert_eq!(
+            find_asset("tree-conifer").map(|asset| asset.name),
+            Some("Conifer tree")
+        );
+        assert!(find_asset("spaceship").is_none());
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::interpreter::ast::{Expr, FuncIdent, LitExpr, Stmt};
+use crate::interpreter_funcs;
+use crate::session::Session;
+
+/// How often the modification times of the linked files are checked.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Returns whether statements calling the func can be live linked, i.e. the
+/// func imports a file given by its first argument.
+pub fn func_linkable(func_ident: FuncIdent) -> bool {
+    func_ident == interpreter_funcs::FUNC_ID_IMPORT_OBJ_MESH
+        || func_ident == interpreter_funcs::FUNC_ID_IMPORT_OBJ_JOIN
+}
+
+/// Returns the paths of the files imported by live linked statements.
+pub fn linked_paths(session: &Session) -> Vec<String> {
+    session
+        .stmts()
+        .iter()
+        .filter_map(|stmt| {
+            let Stmt::VarDecl(var_decl) = stmt;
+            let init_expr = var_decl.init_expr();
+
+            if session.var_live_linked(var_decl.ident()) && func_linkable(init_expr.ident()) {
+                match init_expr.args().first() {
+                    Some(Expr::Lit(LitExpr::String(path))) if !path.is_empty() => {
+                        Some(path.clone())
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Watches the modification times of files imported by live linked
+/// statements, so that the pipeline can be re-run when an external
+/// application, such as Rhino or Blender, saves them.
+///
+/// Importers re-read files with a changed modification time when the
+/// pipeline runs, and the results of later statements are invalidated, so
+/// re-running the pipeline is all it takes to pick up the changes.
+#[derive(Debug, Default)]
+pub struct LiveLink {
+    modified: HashMap<String, SystemTime>,
+    last_check: Option<Instant>,
+}
+
+impl LiveLink {
+    /// Checks the modification times of the files at most once per
+    /// `CHECK_INTERVAL` and returns the files modified since the last check.
+    /// Files are not reported as modified the first time they are checked.
+    pub fn poll(&mut self, time: Instant, paths: Vec<String>) -> Vec<String> {
+        if let Some(last_check) = self.last_check {
+            if time.duration_since(last_check) < CHECK_INTERVAL {
+                return Vec::new();
+            }
+        }
+        self.last_check = Some(time);
+
+        let modified: Vec<_> = paths
+            .into_iter()
+            .filter_map(|path| {
+                // Files that can't be read now may appear later, e.g. while
+                // the exporting application replaces them
+                let modified = fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()?;
+                Some((path, modified))
+            })
+            .collect();
+
+        self.update(modified)
+    }
+
+    fn update(&mut self, modified: Vec<(String, SystemTime)>) -> Vec<String> {
+        let mut changed_paths = Vec::new();
+        let mut new_modified = HashMap::with_capacity(modified.len());
+
+        for (path, modified) in modified {
+            if let Some(last_modified) = self.modified.get(&path) {
+                if *last_modified != modified {
+                    changed_paths.push(path.clone());
+                }
+            }
+            new_modified.insert(path, modified);
+        }
+
+        // Forget files no longer linked, so that they are not reported when
+        // linked again
+        self.modified = new_modified;
+
+        changed_paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_link_update_reports_only_changed_files() {
+        let mut live_link = LiveLink::default();
+        let earlier = SystemTime::UNIX_EPOCH;
+        let later = earlier + Duration::from_secs(1);
+
+        let changed = live_link.update(vec![
+            (String::from("a.obj"), earlier),
+            (String::from("b.obj"), earlier),
+        ]);
+        assert!(changed.is_empty());
+
+        let changed = live_link.update(vec![
+            (String::from("a.obj"), later),
+            (String::from("b.obj"), earlier),
+        ]);
+        assert_eq!(changed, vec![String::from("a.obj")]);
+
+        let changed = live_link.update(vec![(String::from("b.obj"), earlier)]);
+        assert!(changed.is_empty());
+
+        let changed = live_link.update(vec![(String::from("a.obj"), earlier)]);
+        assert!(changed.is_empty());
+    }
+}
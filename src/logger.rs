@@ -1,4 +1,21 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::Clap)]
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Directory in the user data directory, where the log files are kept.
+const LOG_DIRECTORY: &str = "H.U.R.B.A.N. selector/Logs";
+
+const LOG_FILE_NAME: &str = "hurban_selector.log";
+
+/// Size in bytes after which the log file is rotated.
+const LOG_FILE_MAX_SIZE: u64 = 5 * 1024 * 1024;
+
+/// How many rotated log files are kept besides the current one.
+const LOG_FILE_MAX_ROTATED: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::Clap, serde::Serialize, serde::Deserialize)]
 pub enum LogLevel {
     Off,
     Error,
@@ -8,6 +25,28 @@ pub enum LogLevel {
     Trace,
 }
 
+impl LogLevel {
+    pub const ALL: [LogLevel; 6] = [
+        LogLevel::Off,
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Error => "Error",
+            Self::Warn => "Warn",
+            Self::Info => "Info",
+            Self::Debug => "Debug",
+            Self::Trace => "Trace",
+        }
+    }
+}
+
 impl Into<log::LevelFilter> for LogLevel {
     fn into(self) -> log::LevelFilter {
         match self {
@@ -21,26 +60,134 @@ impl Into<log::LevelFilter> for LogLevel {
     }
 }
 
-/// Initializes logger for current environment.
+/// Log level of a module and its submodules, overriding the levels given on
+/// the command line.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ModuleFilter {
+    /// Module path, e.g. `hurban_selector::renderer` or `wgpu_core`.
+    pub module: String,
+    pub level: LogLevel,
+}
+
+/// Logging settings kept in user preferences. They take effect on the next
+/// start, because the logger can only be initialized once.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct LoggingPreferences {
+    /// Whether to write the log to rotating files in the user data
+    /// directory.
+    pub to_file: bool,
+    pub module_filters: Vec<ModuleFilter>,
+}
+
+impl Default for LoggingPreferences {
+    fn default() -> Self {
+        Self {
+            // Dist builds have no console to log to on Windows.
+            to_file: cfg!(feature = "dist"),
+            module_filters: Vec::new(),
+        }
+    }
+}
+
+/// How many of the most recent log lines are kept in memory.
+const RECENT_LOG_CAPACITY: usize = 500;
+
+/// The most recent log lines, kept in memory so that they can be attached to
+/// crash reports.
+#[derive(Debug, Clone, Default)]
+pub struct RecentLog(Arc<Mutex<VecDeque<String>>>);
+
+impl RecentLog {
+    fn push(&self, line: String) {
+        let mut lines = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if lines.len() >= RECENT_LOG_CAPACITY {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// Returns the kept log lines, the oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        let lines = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        lines.iter().cloned().collect()
+    }
+}
+
+/// Returns the directory, where the log files are written.
+pub fn log_directory() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|data_dir| data_dir.join(LOG_DIRECTORY))
+}
+
+/// Initializes logger for current environment. Returns the in-memory buffer
+/// of recent log lines.
 ///
-/// In case of any error, basic logger that does nothing, is returned. Errors
-/// can possibly happen in case of filesystem permission errors in dist build.
-pub fn init(log_level_app: LogLevel, log_level_lib: LogLevel) {
-    let base_logger = fern::Dispatch::new();
+/// If the log file can not be opened, e.g. because of filesystem permission
+/// errors, logging continues without it and the error is logged.
+pub fn init(
+    log_level_app: LogLevel,
+    log_level_lib: LogLevel,
+    preferences: &LoggingPreferences,
+) -> RecentLog {
     let app_level_filter: log::LevelFilter = log_level_app.into();
     let lib_level_filter: log::LevelFilter = log_level_lib.into();
 
-    let env_specific_logger = init_env_specific(base_logger, app_level_filter, lib_level_filter);
+    let mut base_logger = fern::Dispatch::new()
+        .level(lib_level_filter)
+        .level_for("hurban_selector", app_level_filter);
+    for filter in &preferences.module_filters {
+        base_logger = base_logger.level_for(filter.module.clone(), filter.level.into());
+    }
 
-    env_specific_logger.apply().expect("Failed to build logger");
+    let mut logger = init_env_specific(base_logger);
+
+    let mut file_error = None;
+    if preferences.to_file {
+        match open_log_file() {
+            Ok(file) => {
+                let file: Box<dyn Write + Send> = Box::new(file);
+                logger = logger.chain(
+                    fern::Dispatch::new()
+                        .format(|out, message, record| {
+                            out.finish(format_args!(
+                                "{} [{}] [{}] {}",
+                                chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]"),
+                                record.target(),
+                                record.level(),
+                                message
+                            ))
+                        })
+                        .chain(file),
+                );
+            }
+            Err(err) => file_error = Some(err),
+        }
+    }
+
+    let recent_log = RecentLog::default();
+    let recent_log_output = recent_log.clone();
+    logger
+        .chain(fern::Output::call(move |record| {
+            recent_log_output.push(format!(
+                "{} [{}] [{}] {}",
+                chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]"),
+                record.target(),
+                record.level(),
+                record.args()
+            ));
+        }))
+        .apply()
+        .expect("Failed to build logger");
+
+    if let Some(err) = file_error {
+        log::error!("Failed to open log file: {}", err);
+    }
+
+    recent_log
 }
 
 #[cfg(not(feature = "dist"))]
-pub fn init_env_specific(
-    base_logger: fern::Dispatch,
-    filter_app: log::LevelFilter,
-    filter_lib: log::LevelFilter,
-) -> fern::Dispatch {
+pub fn init_env_specific(base_logger: fern::Dispatch) -> fern::Dispatch {
     use fern::colors::{Color, ColoredLevelConfig};
 
     let colors = ColoredLevelConfig::new()
@@ -49,76 +196,119 @@ pub fn init_env_specific(
         .info(Color::Cyan)
         .debug(Color::BrightWhite)
         .trace(Color::White);
-    let base_logger = base_logger.format(move |out, message, record| {
-        out.finish(format_args!(
-            "{} [{}] [{}] {}",
-            chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]"),
-            record.target(),
-            colors.color(record.level()),
-            message
-        ))
-    });
 
-    base_logger
-        .level(filter_lib)
-        .level_for("hurban_selector", filter_app)
-        .chain(std::io::stdout())
+    base_logger.chain(
+        fern::Dispatch::new()
+            .format(move |out, message, record| {
+                out.finish(format_args!(
+                    "{} [{}] [{}] {}",
+                    chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]"),
+                    record.target(),
+                    colors.color(record.level()),
+                    message
+                ))
+            })
+            .chain(std::io::stdout()),
+    )
 }
 
 #[cfg(feature = "dist")]
-pub fn init_env_specific(
-    base_logger: fern::Dispatch,
-    filter_app: log::LevelFilter,
-    filter_lib: log::LevelFilter,
-) -> fern::Dispatch {
-    use std::fs;
-    use std::path::Path;
-
-    #[cfg(target_os = "windows")]
-    let path = match dirs::data_local_dir() {
-        Some(appdata_local) => Path::new(&appdata_local).join("H.U.R.B.A.N. selector/Logs"),
-        None => return base_logger,
-    };
-
-    #[cfg(target_os = "macos")]
-    let path = {
-        use std::env;
-
-        match env::var("HOME") {
-            Ok(home_dir) => Path::new(&home_dir).join("Library/Logs/H.U.R.B.A.N. selector"),
-            Err(_) => return base_logger,
+pub fn init_env_specific(base_logger: fern::Dispatch) -> fern::Dispatch {
+    // Dist builds don't log to console, only to the log file, if enabled.
+    base_logger
+}
+
+fn open_log_file() -> io::Result<RotatingFile> {
+    let directory = log_directory()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No user data directory"))?;
+    fs::create_dir_all(&directory)?;
+
+    RotatingFile::open(
+        directory.join(LOG_FILE_NAME),
+        LOG_FILE_MAX_SIZE,
+        LOG_FILE_MAX_ROTATED,
+    )
+}
+
+/// Log file, which is rotated once it grows over the maximum size. The
+/// rotated files are numbered, `name.1.log` being the most recent one, and
+/// the oldest are removed.
+struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    max_rotated: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_size: u64, max_rotated: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        let mut rotating_file = Self {
+            path,
+            max_size,
+            max_rotated,
+            file,
+            size,
+        };
+        if rotating_file.size >= rotating_file.max_size {
+            rotating_file.rotate()?;
         }
-    };
 
-    #[cfg(target_os = "linux")]
-    let path = Path::new("/var/log/H.U.R.B.A.N. selector").to_path_buf();
+        Ok(rotating_file)
+    }
 
-    if !path.exists() {
-        let result = fs::create_dir_all(&path);
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_rotated > 0 {
+            for index in (1..self.max_rotated).rev() {
+                let from = rotated_log_file_path(&self.path, index);
+                if from.exists() {
+                    fs::rename(from, rotated_log_file_path(&self.path, index + 1))?;
+                }
+            }
 
-        if result.is_err() {
-            return base_logger;
+            fs::rename(&self.path, rotated_log_file_path(&self.path, 1))?;
         }
+
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+
+        Ok(())
     }
+}
 
-    let today_format = chrono::Local::today().format("%Y-%m-%d");
-    let file_name = path.join(format!("{}.log", today_format));
-    let file = match fern::log_file(file_name) {
-        Ok(file) => file,
-        Err(_) => return base_logger,
-    };
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
 
-    base_logger
-        .format(move |out, message, record| {
-            out.finish(format_args!(
-                "{} [{}] [{}] {}",
-                chrono::Local::now().format("[%Y-%m-%d %H:%M:%S]"),
-                record.target(),
-                record.level(),
-                message
-            ))
-        })
-        .level(filter_lib)
-        .level_for("hurban_selector", filter_app)
-        .chain(file)
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn rotated_log_file_path(path: &Path, index: usize) -> PathBuf {
+    path.with_extension(format!("{}.log", index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotated_log_file_path() {
+        assert_eq!(
+            rotated_log_file_path(Path::new("logs/hurban_selector.log"), 2),
+            PathBuf::from("logs/hurban_selector.2.log"),
+        );
+    }
 }
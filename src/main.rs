@@ -1,10 +1,21 @@
 // Do not display console on windows in dist builds
 #![cfg_attr(feature = "dist", windows_subsystem = "windows")]
 
+use std::process;
+
 use clap::Clap as _;
 use hurban_selector as hs;
 
 fn main() {
     let options = hs::Options::parse();
-    hs::init_and_run(options);
+    if let Err(err) = hs::init_and_run(options) {
+        log::error!("Failed to initialize renderer: {}", err);
+        tinyfiledialogs::message_box_ok(
+            "Failed to start",
+            &format!("Failed to initialize renderer.\n\n{}", err),
+            tinyfiledialogs::MessageBoxIcon::Error,
+        );
+
+        process::exit(1);
+    }
 }
@@ -0,0 +1,117 @@
+//! Baking the lighting of the viewport into matcap images.
+//!
+//! A matcap is the image of a lit unit sphere looked at straight on. Matcap
+//! shaded surfaces take their color from the pixel of the sphere with the
+//! same view space normal. A sphere drawn with the current matcap, the
+//! shadows of the sun and the viewport background therefore makes a new
+//! matcap, which carries the lighting over to projects with other scenes.
+
+use nalgebra::{Matrix4, Point3, Rotation3, Vector3};
+
+use crate::convert::cast_usize;
+use crate::mesh::{primitive, Mesh, NormalStrategy};
+
+/// Width and height of baked matcap images.
+pub const BAKE_SIZE: u32 = 512;
+
+/// Distance of the camera from the center of the baked sphere.
+const BAKE_CAMERA_DISTANCE: f32 = 2.0;
+
+/// Returns the unit sphere, which is drawn to bake a matcap.
+pub fn bake_sphere_mesh() -> Mesh {
+    primitive::create_uv_sphere(
+        Point3::origin(),
+        Rotation3::identity(),
+        Vector3::new(1.0, 1.0, 1.0),
+        64,
+        128,
+        NormalStrategy::Smooth,
+    )
+}
+
+/// Returns the view matrix looking at the baked sphere in the direction the
+/// viewport camera looks in, so that the sphere is lit the same way the
+/// scene is.
+pub fn bake_view_matrix(camera_direction: &Vector3<f32>) -> Matrix4<f32> {
+    let direction = camera_direction.normalize();
+    Matrix4::look_at_rh(
+        &Point3::from(-direction * BAKE_CAMERA_DISTANCE),
+        &Point3::origin(),
+        &Vector3::z(),
+    )
+}
+
+/// Returns the orthographic projection matrix fitting the baked sphere to the
+/// image, so that the normal of each pixel of the sphere matches its matcap
+/// texture coordinates.
+pub fn bake_projection_matrix() -> Matrix4<f32> {
+    Matrix4::new_orthographic(
+        -1.0,
+        1.0,
+        -1.0,
+        1.0,
+        BAKE_CAMERA_DISTANCE - 1.5,
+        BAKE_CAMERA_DISTANCE + 1.5,
+    )
+}
+
+/// Flips the rows of an RGBA8 image upside down. Rendered images start with
+/// the top row, but matcaps start with the row lit by normals pointing down.
+pub fn flip_rows(rgba: &[u8], width: u32) -> Vec<u8> {
+    let bytes_per_row = cast_usize(width) * 4;
+
+    let mut flipped = Vec::with_capacity(rgba.len());
+    for row in rgba.chunks(bytes_per_row).rev() {
+        flipped.extend_from_slice(row);
+    }
+
+    flipped
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector4;
+
+    use super::*;
+
+    #[test]
+    fn test_matcap_flip_rows() {
+        let rgba = vec![
+            1, 1, 1, 1, 2, 2, 2, 2, //
+            3, 3, 3, 3, 4, 4, 4, 4, //
+            5, 5, 5, 5, 6, 6, 6, 6, //
+        ];
+
+        assert_eq!(
+            flip_rows(&rgba, 2),
+            vec![
+                5, 5, 5, 5, 6, 6, 6, 6, //
+                3, 3, 3, 3, 4, 4, 4, 4, //
+                1, 1, 1, 1, 2, 2, 2, 2, //
+            ],
+        );
+    }
+
+    #[test]
+    fn test_matcap_bake_view_matrix_faces_sphere() {
+        let direction = Vector3::new(1.0, 1.0, -1.0);
+        let view_matrix = bake_view_matrix(&direction);
+
+        // The normal facing the camera points out of the screen and the
+        // normal at the top of the sphere points up
+        let facing_normal = view_matrix * (-direction.normalize()).to_homogeneous();
+        let top_normal = view_matrix * Vector3::z().to_homogeneous();
+        assert!((facing_normal - Vector4::new(0.0, 0.0, 1.0, 0.0)).norm() < 0.0001);
+        assert!(top_normal.y > 0.0);
+        assert!(top_normal.x.abs() < 0.0001);
+
+        // The sphere spans the whole image and fits between the clip planes
+        let clip_matrix = bake_projection_matrix() * view_matrix;
+        let rightmost =
+            bake_projection_matrix() * Vector4::new(1.0, 0.0, -BAKE_CAMERA_DISTANCE, 1.0);
+        let nearest = clip_matrix * Point3::from(-direction.normalize()).to_homogeneous();
+        let farthest = clip_matrix * Point3::from(direction.normalize()).to_homogeneous();
+        assert!((rightmost.x - 1.0).abs() < 0.0001);
+        assert!(nearest.z > -1.0 && nearest.z < farthest.z && farthest.z < 1.0);
+    }
+}
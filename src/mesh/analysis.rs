@@ -1,11 +1,13 @@
 use std::collections::{HashSet, VecDeque};
 
+use arrayvec::ArrayVec;
 use nalgebra as na;
-use nalgebra::Point3;
+use nalgebra::{Point3, Vector3};
 
 use crate::convert::{cast_i32, cast_usize};
 
-use super::{Mesh, OrientedEdge, UnorientedEdge};
+use super::bvh::Bvh;
+use super::{Face, Mesh, OrientedEdge, UnorientedEdge};
 
 // FIXME: Make more generic: take &[Point] or Iterator<Item=&Point>
 #[allow(dead_code)]
@@ -362,6 +364,151 @@ pub fn triangulated_mesh_genus(vertex_count: usize, edge_count: usize, face_coun
     1 - (cast_i32(vertex_count) - cast_i32(edge_count) + cast_i32(face_count)) / 2
 }
 
+/// Computes the surface area of the mesh as the sum of areas of its faces.
+pub fn mesh_surface_area(mesh: &Mesh) -> f32 {
+    let vertices = mesh.vertices();
+    mesh.faces()
+        .iter()
+        .map(|face| match face {
+            Face::Triangle(f) => {
+                let v1 = vertices[cast_usize(f.vertices.0)];
+                let v2 = vertices[cast_usize(f.vertices.1)];
+                let v3 = vertices[cast_usize(f.vertices.2)];
+
+                (v2 - v1).cross(&(v3 - v1)).norm() / 2.0
+            }
+        })
+        .sum()
+}
+
+/// Computes the volume enclosed by the mesh.
+///
+/// The volume is computed as a sum of signed volumes of tetrahedra formed by
+/// the origin and each face of the mesh. The result is only meaningful for
+/// watertight meshes with consistent face winding. The volume of a mesh with
+/// reverted faces is negative.
+pub fn mesh_volume(mesh: &Mesh) -> f32 {
+    let vertices = mesh.vertices();
+    mesh.faces()
+        .iter()
+        .map(|face| match face {
+            Face::Triangle(f) => {
+                let v1 = vertices[cast_usize(f.vertices.0)].coords;
+                let v2 = vertices[cast_usize(f.vertices.1)].coords;
+                let v3 = vertices[cast_usize(f.vertices.2)].coords;
+
+                v1.dot(&v2.cross(&v3)) / 6.0
+            }
+        })
+        .sum()
+}
+
+/// Computes the area of the horizontal cross section of the mesh at the given
+/// elevation.
+///
+/// Each face crossing the section plane contributes a directed segment of the
+/// section outline, oriented by the face normal. The area is then the sum of
+/// the signed areas (as in the shoelace formula) of the segments, so the
+/// outline does not need to be assembled into polygons. Holes and separate
+/// parts of the section are accounted for correctly.
+///
+/// The result is only meaningful for watertight meshes with consistent face
+/// winding.
+pub fn mesh_cross_section_area(mesh: &Mesh, elevation: f32) -> f32 {
+    let vertices = mesh.vertices();
+    let up = Vector3::z();
+
+    let mut double_area = 0.0;
+    for face in mesh.faces() {
+        match face {
+            Face::Triangle(f) => {
+                let triangle = [
+                    vertices[cast_usize(f.vertices.0)],
+                    vertices[cast_usize(f.vertices.1)],
+                    vertices[cast_usize(f.vertices.2)],
+                ];
+
+                // Vertices lying on the plane count as above it, so that
+                // neighboring faces agree on which edges cross the plane.
+                let mut crossings: ArrayVec<[Point3<f32>; 2]> = ArrayVec::new();
+                for i in 0..3 {
+                    let a = triangle[i];
+                    let b = triangle[(i + 1) % 3];
+                    let a_above = a.z >= elevation;
+                    let b_above = b.z >= elevation;
+
+                    if a_above != b_above {
+                        let t = (elevation - a.z) / (b.z - a.z);
+                        crossings.push(a + (b - a) * t);
+                    }
+                }
+
+                if crossings.len() == 2 {
+                    let face_normal =
+                        (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+                    let outline_direction = up.cross(&face_normal);
+
+                    let (start, end) =
+                        if (crossings[1] - crossings[0]).dot(&outline_direction) >= 0.0 {
+                            (crossings[0], crossings[1])
+                        } else {
+                            (crossings[1], crossings[0])
+                        };
+
+                    double_area += start.x * end.y - end.x * start.y;
+                }
+            }
+        }
+    }
+
+    double_area / 2.0
+}
+
+/// Counts for each vertex of the mesh how many of the given directions
+/// towards the sun reach the vertex without being blocked by the mesh
+/// itself.
+///
+/// A vertex is lit from a direction only if the direction is above the
+/// surface at the vertex, as given by the average of the vertex normals, and
+/// the ray cast from the vertex towards the sun does not hit the mesh.
+pub fn vertex_sun_exposure(mesh: &Mesh, directions_towards_sun: &[Vector3<f32>]) -> Vec<usize> {
+    let vertices = mesh.vertices();
+    let normals = mesh.normals();
+
+    let mut vertex_normals = vec![Vector3::zeros(); vertices.len()];
+    for face in mesh.faces() {
+        match face {
+            Face::Triangle(f) => {
+                vertex_normals[cast_usize(f.vertices.0)] += normals[cast_usize(f.normals.0)];
+                vertex_normals[cast_usize(f.vertices.1)] += normals[cast_usize(f.normals.1)];
+                vertex_normals[cast_usize(f.vertices.2)] += normals[cast_usize(f.normals.2)];
+            }
+        }
+    }
+
+    // Rays start slightly above the surface so that they do not hit the
+    // faces around the vertex they start from.
+    let ray_offset = mesh.bounding_box().diagonal().norm() * 0.0001;
+    let bvh = Bvh::from_mesh(mesh);
+
+    vertices
+        .iter()
+        .zip(vertex_normals.iter())
+        .map(|(vertex, vertex_normal)| {
+            let normal = vertex_normal.try_normalize(f32::EPSILON);
+            let origin = vertex + normal.unwrap_or_else(Vector3::zeros) * ray_offset;
+
+            directions_towards_sun
+                .iter()
+                .filter(|direction| {
+                    normal.map_or(false, |normal| normal.dot(direction) > 0.0)
+                        && !bvh.intersects_ray(&origin, direction, f32::INFINITY)
+                })
+                .count()
+        })
+        .collect()
+}
+
 /// Checks if two meshes are similar.
 ///
 /// This function is slow and is therefore enabled only for tests.
@@ -406,8 +553,6 @@ pub fn are_similar(mesh1: &Mesh, mesh2: &Mesh) -> bool {
 pub fn are_visually_similar(mesh1: &Mesh, mesh2: &Mesh) -> bool {
     use nalgebra::Vector3;
 
-    use crate::mesh::Face;
-
     struct UnpackedFace {
         vertices: (Point3<f32>, Point3<f32>, Point3<f32>),
         normals: (Vector3<f32>, Vector3<f32>, Vector3<f32>),
@@ -879,6 +1024,96 @@ mod tests {
         (faces, vertices)
     }
 
+    #[test]
+    fn test_mesh_cross_section_area_box() {
+        let mesh = primitive::create_box(
+            Point3::new(0.0, 0.0, 1.5),
+            Rotation3::identity(),
+            Vector3::new(2.0, 3.0, 3.0),
+        );
+
+        approx::assert_relative_eq!(mesh_cross_section_area(&mesh, 1.0), 6.0, epsilon = 0.0001);
+        approx::assert_relative_eq!(mesh_cross_section_area(&mesh, 2.9), 6.0, epsilon = 0.0001);
+        approx::assert_relative_eq!(mesh_cross_section_area(&mesh, -1.0), 0.0);
+        approx::assert_relative_eq!(mesh_cross_section_area(&mesh, 4.0), 0.0);
+    }
+
+    #[test]
+    fn test_mesh_cross_section_area_rotated_box() {
+        let mesh = primitive::create_box(
+            Point3::origin(),
+            Rotation3::from_axis_angle(&Vector3::z_axis(), 0.7),
+            Vector3::new(2.0, 3.0, 3.0),
+        );
+
+        approx::assert_relative_eq!(mesh_cross_section_area(&mesh, 0.1), 6.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_mesh_cross_section_area_of_two_boxes_adds_up() {
+        let box1 = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let box2 = primitive::create_box(
+            Point3::new(5.0, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+        let mesh = crate::mesh::tools::join_multiple_meshes(&[box1, box2]);
+
+        approx::assert_relative_eq!(mesh_cross_section_area(&mesh, 0.0), 5.0, epsilon = 0.0001);
+        approx::assert_relative_eq!(mesh_cross_section_area(&mesh, 0.75), 4.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_vertex_sun_exposure_lights_only_vertices_facing_the_sun() {
+        let mesh = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+
+        let exposure = vertex_sun_exposure(&mesh, &[Vector3::z()]);
+
+        for (vertex, &count) in mesh.vertices().iter().zip(exposure.iter()) {
+            if vertex.z > 0.0 {
+                assert_eq!(count, 1);
+            } else {
+                assert_eq!(count, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_vertex_sun_exposure_respects_self_shadowing() {
+        let small_box = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let roof = primitive::create_box(
+            Point3::new(0.0, 0.0, 3.0),
+            Rotation3::identity(),
+            Vector3::new(4.0, 4.0, 1.0),
+        );
+        let mesh = crate::mesh::tools::join_multiple_meshes(&[small_box, roof]);
+
+        let directions = [Vector3::z(), Vector3::new(0.1, 0.1, 1.0).normalize()];
+        let exposure = vertex_sun_exposure(&mesh, &directions);
+
+        for (vertex, &count) in mesh.vertices().iter().zip(exposure.iter()) {
+            if vertex.z > 3.0 {
+                // The top of the roof is lit from both directions
+                assert_eq!(count, 2);
+            } else if vertex.z > 0.0 && vertex.z < 1.0 {
+                // The top of the small box is in the shadow of the roof
+                assert_eq!(count, 0);
+            }
+        }
+    }
+
     #[test]
     fn test_edge_sharing() {
         let (faces, vertices) = quad();
@@ -1389,4 +1624,31 @@ mod tests {
 
         assert!(!are_similar(&mesh, &mesh_d));
     }
+
+    #[test]
+    fn test_mesh_surface_area_and_volume_of_box() {
+        let mesh = primitive::create_box(
+            Point3::new(3.0, -2.0, 1.0),
+            Rotation3::from_euler_angles(0.3, 0.2, 0.1),
+            Vector3::new(1.0, 2.0, 3.0),
+        );
+
+        assert!(approx::relative_eq!(
+            mesh_surface_area(&mesh),
+            22.0,
+            epsilon = 0.001,
+        ));
+        assert!(approx::relative_eq!(
+            mesh_volume(&mesh),
+            6.0,
+            epsilon = 0.001
+        ));
+    }
+
+    #[test]
+    fn test_mesh_volume_is_zero_for_flat_mesh() {
+        let mesh = quad_with_normals();
+
+        assert!(approx::relative_eq!(mesh_volume(&mesh), 0.0));
+    }
 }
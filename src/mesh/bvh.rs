@@ -0,0 +1,388 @@
+use std::cmp::Ordering;
+use std::f32;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::bounding_box::BoundingBox;
+use crate::convert::cast_usize;
+
+use super::{Face, Mesh};
+
+/// Leaves with this many triangles or fewer are not split further.
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+/// Rays shorter than this do not intersect anything. Prevents rays cast from
+/// a point on the mesh surface from hitting the surface itself.
+const MIN_RAY_DISTANCE: f32 = 0.0001;
+
+#[derive(Debug, Clone, Copy)]
+struct Triangle {
+    vertices: [Point3<f32>; 3],
+    centroid: Point3<f32>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf {
+        bounding_box: BoundingBox<f32>,
+        triangles_start: usize,
+        triangles_end: usize,
+    },
+    Branch {
+        bounding_box: BoundingBox<f32>,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounding_box(&self) -> &BoundingBox<f32> {
+        match self {
+            Node::Leaf { bounding_box, .. } => bounding_box,
+            Node::Branch { bounding_box, .. } => bounding_box,
+        }
+    }
+}
+
+/// Bounding volume hierarchy of mesh triangles for fast ray casting.
+///
+/// The hierarchy is a binary tree of axis aligned bounding boxes, split at
+/// the median triangle centroid along the longest axis of each box.
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    triangles: Vec<Triangle>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    pub fn from_mesh(mesh: &Mesh) -> Self {
+        let vertices = mesh.vertices();
+        let mut triangles: Vec<Triangle> = mesh
+            .faces()
+            .iter()
+            .map(|face| match face {
+                Face::Triangle(triangle_face) => {
+                    let v = triangle_face.vertices;
+                    let vertices = [
+                        vertices[cast_usize(v.0)],
+                        vertices[cast_usize(v.1)],
+                        vertices[cast_usize(v.2)],
+                    ];
+                    let centroid = Point3::from(
+                        (vertices[0].coords + vertices[1].coords + vertices[2].coords) / 3.0,
+                    );
+
+                    Triangle { vertices, centroid }
+                }
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(2 * triangles.len() / MAX_LEAF_TRIANGLES + 1);
+        let triangles_len = triangles.len();
+        build_node(&mut triangles, 0, triangles_len, &mut nodes);
+
+        Self { triangles, nodes }
+    }
+
+    /// Returns whether the ray starting at `origin` hits any triangle closer
+    /// than `max_distance`. The direction must be a unit vector.
+    pub fn intersects_ray(
+        &self,
+        origin: &Point3<f32>,
+        direction: &Vector3<f32>,
+        max_distance: f32,
+    ) -> bool {
+        let inverse_direction =
+            Vector3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !ray_intersects_bounding_box(
+                origin,
+                &inverse_direction,
+                node.bounding_box(),
+                max_distance,
+            ) {
+                continue;
+            }
+
+            match node {
+                Node::Leaf {
+                    triangles_start,
+                    triangles_end,
+                    ..
+                } => {
+                    for triangle in &self.triangles[*triangles_start..*triangles_end] {
+                        if let Some(distance) = ray_triangle_distance(origin, direction, triangle) {
+                            if distance > MIN_RAY_DISTANCE && distance < max_distance {
+                                return true;
+                            }
+                        }
+                    }
+                }
+                Node::Branch { left, right, .. } => {
+                    stack.push(*left);
+                    stack.push(*right);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Returns the distance to the closest triangle hit by the ray starting
+    /// at `origin`, if any. The direction must be a unit vector.
+    pub fn closest_ray_distance(
+        &self,
+        origin: &Point3<f32>,
+        direction: &Vector3<f32>,
+    ) -> Option<f32> {
+        let inverse_direction =
+            Vector3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+
+        let mut closest_distance = f32::INFINITY;
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !ray_intersects_bounding_box(
+                origin,
+                &inverse_direction,
+                node.bounding_box(),
+                closest_distance,
+            ) {
+                continue;
+            }
+
+            match node {
+                Node::Leaf {
+                    triangles_start,
+                    triangles_end,
+                    ..
+                } => {
+                    for triangle in &self.triangles[*triangles_start..*triangles_end] {
+                        if let Some(distance) = ray_triangle_distance(origin, direction, triangle) {
+                            if distance > MIN_RAY_DISTANCE && distance < closest_distance {
+                                closest_distance = distance;
+                            }
+                        }
+                    }
+                }
+                Node::Branch { left, right, .. } => {
+                    stack.push(*left);
+                    stack.push(*right);
+                }
+            }
+        }
+
+        if closest_distance.is_finite() {
+            Some(closest_distance)
+        } else {
+            None
+        }
+    }
+}
+
+/// Builds the node for triangles in the given range, and all its children.
+/// Returns the index of the node.
+fn build_node(
+    triangles: &mut [Triangle],
+    start: usize,
+    end: usize,
+    nodes: &mut Vec<Node>,
+) -> usize {
+    let bounding_box = BoundingBox::from_points(
+        triangles[start..end]
+            .iter()
+            .flat_map(|triangle| triangle.vertices.iter().copied()),
+    )
+    .expect("Must produce a bounding box for non-empty iterator");
+
+    let node_index = nodes.len();
+
+    if end - start <= MAX_LEAF_TRIANGLES {
+        nodes.push(Node::Leaf {
+            bounding_box,
+            triangles_start: start,
+            triangles_end: end,
+        });
+
+        return node_index;
+    }
+
+    let diagonal = bounding_box.diagonal();
+    let axis = if diagonal.x >= diagonal.y && diagonal.x >= diagonal.z {
+        0
+    } else if diagonal.y >= diagonal.z {
+        1
+    } else {
+        2
+    };
+
+    triangles[start..end].sort_unstable_by(|a, b| {
+        a.centroid[axis]
+            .partial_cmp(&b.centroid[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+
+    // Reserve the place for this node, so that it precedes its children
+    nodes.push(Node::Leaf {
+        bounding_box,
+        triangles_start: start,
+        triangles_end: end,
+    });
+
+    let middle = start + (end - start) / 2;
+    let left = build_node(triangles, start, middle, nodes);
+    let right = build_node(triangles, middle, end, nodes);
+
+    nodes[node_index] = Node::Branch {
+        bounding_box,
+        left,
+        right,
+    };
+
+    node_index
+}
+
+/// The slab test of ray-box intersection.
+fn ray_intersects_bounding_box(
+    origin: &Point3<f32>,
+    inverse_direction: &Vector3<f32>,
+    bounding_box: &BoundingBox<f32>,
+    max_distance: f32,
+) -> bool {
+    let minimum_point = bounding_box.minimum_point();
+    let maximum_point = bounding_box.maximum_point();
+
+    let mut t_min = 0.0_f32;
+    let mut t_max = max_distance;
+
+    for axis in 0..3 {
+        let t1 = (minimum_point[axis] - origin[axis]) * inverse_direction[axis];
+        let t2 = (maximum_point[axis] - origin[axis]) * inverse_direction[axis];
+
+        // NaN (0 * inf) fails both comparisons and leaves the interval
+        // unchanged, which is correct for rays parallel with the slab.
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+    }
+
+    t_min <= t_max
+}
+
+/// The Möller–Trumbore ray-triangle intersection. Returns the distance along
+/// the ray to the intersection, if any. The distance may be negative.
+fn ray_triangle_distance(
+    origin: &Point3<f32>,
+    direction: &Vector3<f32>,
+    triangle: &Triangle,
+) -> Option<f32> {
+    let [v0, v1, v2] = triangle.vertices;
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+
+    let p = direction.cross(&edge2);
+    let determinant = edge1.dot(&p);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let inverse_determinant = 1.0 / determinant;
+    let s = origin - v0;
+    let u = s.dot(&p) * inverse_determinant;
+    if u < 0.0 || u > 1.0 {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let v = direction.dot(&q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    Some(edge2.dot(&q) * inverse_determinant)
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Rotation3;
+
+    use crate::mesh::primitive;
+
+    use super::*;
+
+    #[test]
+    fn test_bvh_intersects_ray_towards_box() {
+        let mesh = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+        let bvh = Bvh::from_mesh(&mesh);
+
+        let origin = Point3::new(-5.0, 0.1, 0.2);
+        assert!(bvh.intersects_ray(&origin, &Vector3::x(), f32::INFINITY));
+        assert!(!bvh.intersects_ray(&origin, &-Vector3::x(), f32::INFINITY));
+
+        // The box is farther than the maximum distance
+        assert!(!bvh.intersects_ray(&origin, &Vector3::x(), 3.0));
+    }
+
+    #[test]
+    fn test_bvh_ray_from_surface_does_not_hit_the_surface_itself() {
+        let mesh = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+        let bvh = Bvh::from_mesh(&mesh);
+
+        let on_top = Point3::new(0.1, 0.2, 1.0);
+        assert!(!bvh.intersects_ray(&on_top, &Vector3::z(), f32::INFINITY));
+
+        // Going through the box, the ray hits the opposite face
+        assert!(bvh.intersects_ray(&on_top, &-Vector3::z(), f32::INFINITY));
+    }
+
+    #[test]
+    fn test_bvh_closest_ray_distance_hits_the_nearest_face() {
+        let mesh = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+        let bvh = Bvh::from_mesh(&mesh);
+
+        let origin = Point3::new(-5.0, 0.1, 0.2);
+        let distance = bvh
+            .closest_ray_distance(&origin, &Vector3::x())
+            .expect("Ray must hit the box");
+        approx::assert_relative_eq!(distance, 4.0);
+
+        assert_eq!(bvh.closest_ray_distance(&origin, &-Vector3::x()), None);
+    }
+
+    #[test]
+    fn test_bvh_intersects_ray_with_many_triangles() {
+        let meshes: Vec<Mesh> = (0..10)
+            .map(|i| {
+                primitive::create_box(
+                    Point3::new(i as f32 * 3.0, 0.0, 0.0),
+                    Rotation3::identity(),
+                    Vector3::new(1.0, 1.0, 1.0),
+                )
+            })
+            .collect();
+        let mesh = crate::mesh::tools::join_multiple_meshes(&meshes);
+        let bvh = Bvh::from_mesh(&mesh);
+
+        for i in 0..10 {
+            let above = Point3::new(i as f32 * 3.0 + 0.1, 0.1, 5.0);
+            assert!(bvh.intersects_ray(&above, &-Vector3::z(), f32::INFINITY));
+
+            let between = Point3::new(i as f32 * 3.0 + 1.5, 0.1, 5.0);
+            assert!(!bvh.intersects_ray(&between, &-Vector3::z(), f32::INFINITY));
+        }
+    }
+}
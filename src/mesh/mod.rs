@@ -1,4 +1,5 @@
 use std::cmp;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashSet;
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -13,6 +14,7 @@ use crate::convert::{cast_u32, cast_usize};
 use crate::geometry;
 
 pub mod analysis;
+pub mod bvh;
 pub mod primitive;
 pub mod smoothing;
 pub mod tools;
@@ -37,11 +39,16 @@ pub enum NormalStrategy {
 ///
 /// The mesh data lives in right-handed coordinate space with the
 /// XY plane being the ground and Z axis growing upwards.
+///
+/// Optionally, the mesh carries a color for each vertex, e.g. to
+/// visualize results of an analysis.
 #[derive(Debug, Clone, PartialEq, serde::Serialize)]
 pub struct Mesh {
     faces: Vec<Face>,
     vertices: Vec<Point3<f32>>,
     normals: Vec<Vector3<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vertex_colors: Option<Vec<[u8; 3]>>,
 }
 
 impl Mesh {
@@ -180,6 +187,7 @@ impl Mesh {
             faces: faces_collection,
             vertices: vertices_collection,
             normals: normals_collection,
+            vertex_colors: None,
         }
     }
 
@@ -317,6 +325,7 @@ impl Mesh {
             faces: faces_collection,
             vertices: vertices_collection,
             normals: normals_collection,
+            vertex_colors: None,
         }
     }
 
@@ -356,6 +365,28 @@ impl Mesh {
         &self.normals
     }
 
+    /// Returns the vertex colors, one for each vertex, if the mesh has
+    /// any.
+    pub fn vertex_colors(&self) -> Option<&[[u8; 3]]> {
+        self.vertex_colors.as_deref()
+    }
+
+    /// Assigns a color to each vertex of the mesh.
+    ///
+    /// # Panics
+    /// Panics if the number of colors does not match the number of
+    /// vertices.
+    pub fn with_vertex_colors(mut self, vertex_colors: Vec<[u8; 3]>) -> Self {
+        assert_eq!(
+            vertex_colors.len(),
+            self.vertices.len(),
+            "Each vertex must have exactly one color",
+        );
+
+        self.vertex_colors = Some(vertex_colors);
+        self
+    }
+
     pub fn bounding_box(&self) -> BoundingBox<f32> {
         let points = self.vertices();
 
@@ -424,6 +455,48 @@ impl Mesh {
 
         used_normals.len() == self.normals().len()
     }
+
+    /// Returns the hash of the faces, vertices, normals and vertex colors of
+    /// the mesh. Equal meshes have equal hashes, so the hash can be used to
+    /// find byte-identical copies of a mesh.
+    ///
+    /// The hash is only stable within a single run of the application and
+    /// must not be persisted.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Hashes the mesh consistently with its `PartialEq` implementation. Meshes
+/// are not `Eq`, as their floats can be NaN, so the hash can't be used to look
+/// up meshes in hash maps by itself, only to find candidates for comparison.
+impl Hash for Mesh {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.faces.hash(state);
+
+        self.vertices.len().hash(state);
+        for vertex in &self.vertices {
+            hash_f32_slice(vertex.coords.as_slice(), state);
+        }
+
+        self.normals.len().hash(state);
+        for normal in &self.normals {
+            hash_f32_slice(normal.as_slice(), state);
+        }
+
+        self.vertex_colors.hash(state);
+    }
+}
+
+/// Hashes the floats by their bits. Positive and negative zeros compare
+/// equal, so they are hashed equally.
+fn hash_f32_slice<H: Hasher>(floats: &[f32], state: &mut H) {
+    for float in floats {
+        let float = if *float == 0.0 { 0.0 } else { *float };
+        float.to_bits().hash(state);
+    }
 }
 
 impl fmt::Display for Mesh {
@@ -462,7 +535,7 @@ impl fmt::Display for Mesh {
 
 /// A mesh face. Contains indices to other mesh data, such as vertices
 /// and normals.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
 pub enum Face {
     Triangle(TriangleFace),
 }
@@ -1550,4 +1623,53 @@ mod tests {
 
         assert!(!face.contains_unoriented_edge(unoriented_edge));
     }
+
+    #[test]
+    fn test_mesh_content_hash_equal_for_equal_meshes() {
+        let (faces, vertices, normals) = quad_with_normals();
+        let mesh = Mesh::from_triangle_faces_with_vertices_and_normals(
+            faces.clone(),
+            vertices.clone(),
+            normals.clone(),
+        );
+        let copy = Mesh::from_triangle_faces_with_vertices_and_normals(faces, vertices, normals);
+
+        assert_eq!(mesh.content_hash(), copy.content_hash());
+    }
+
+    #[test]
+    fn test_mesh_content_hash_equal_for_signed_zeros() {
+        let (faces, vertices, normals) = quad_with_normals();
+        let negative_zero_vertices: Vec<_> = vertices
+            .iter()
+            .map(|vertex| Point3::new(vertex.x, vertex.y, -0.0))
+            .collect();
+        let mesh = Mesh::from_triangle_faces_with_vertices_and_normals(
+            faces.clone(),
+            vertices,
+            normals.clone(),
+        );
+        let negative_zero_mesh = Mesh::from_triangle_faces_with_vertices_and_normals(
+            faces,
+            negative_zero_vertices,
+            normals,
+        );
+
+        assert_eq!(mesh, negative_zero_mesh);
+        assert_eq!(mesh.content_hash(), negative_zero_mesh.content_hash());
+    }
+
+    #[test]
+    fn test_mesh_content_hash_differs_for_moved_vertex() {
+        let (faces, mut vertices, normals) = quad_with_normals();
+        let mesh = Mesh::from_triangle_faces_with_vertices_and_normals(
+            faces.clone(),
+            vertices.clone(),
+            normals.clone(),
+        );
+        vertices[2].z = 0.5;
+        let moved = Mesh::from_triangle_faces_with_vertices_and_normals(faces, vertices, normals);
+
+        assert_ne!(mesh.content_hash(), moved.content_hash());
+    }
 }
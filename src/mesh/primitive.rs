@@ -1,4 +1,4 @@
-use nalgebra::{Matrix4, Point3, Rotation3, Vector2, Vector3};
+use nalgebra::{Matrix4, Point2, Point3, Rotation3, Vector2, Vector3};
 
 use crate::convert::{cast_u32, cast_usize};
 use crate::plane::Plane;
@@ -93,6 +93,130 @@ pub fn create_box(center: Point3<f32>, rotate: Rotation3<f32>, scale: Vector3<f3
     Mesh::from_triangle_faces_with_vertices_and_normals(faces, vertex_positions, vertex_normals)
 }
 
+/// Creates a flat polygon on the ground from its outline, facing up. The
+/// outline may be concave and wound either way, but must not intersect
+/// itself.
+///
+/// Returns `None` if the outline has less than 3 points, has no area or can
+/// not be triangulated.
+pub fn create_polygon(outline: &[Point2<f32>]) -> Option<Mesh> {
+    let triangles = triangulate_polygon(outline)?;
+
+    let vertex_positions = outline
+        .iter()
+        .map(|point| Point3::new(point.x, point.y, 0.0));
+    let vertex_normals = vec![Vector3::z()];
+    let faces = triangles
+        .into_iter()
+        .map(|(i1, i2, i3)| TriangleFace::new(cast_u32(i1), cast_u32(i2), cast_u32(i3), 0, 0, 0));
+
+    Some(Mesh::from_triangle_faces_with_vertices_and_normals(
+        faces,
+        vertex_positions,
+        vertex_normals,
+    ))
+}
+
+/// Triangulates the polygon by ear clipping. Returns triangles of indices
+/// into the outline, wound counterclockwise.
+fn triangulate_polygon(outline: &[Point2<f32>]) -> Option<Vec<(usize, usize, usize)>> {
+    if outline.len() < 3 {
+        return None;
+    }
+
+    // Shoelace formula
+    let doubled_area: f32 = (0..outline.len())
+        .map(|i| {
+            let current = outline[i];
+            let next = outline[(i + 1) % outline.len()];
+            current.x * next.y - next.x * current.y
+        })
+        .sum();
+    if doubled_area.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let mut remaining: Vec<usize> = (0..outline.len()).collect();
+    if doubled_area < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut triangles = Vec::with_capacity(outline.len() - 2);
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let corner = |i: usize| {
+            (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            )
+        };
+
+        let ear = (0..n).find(|&i| {
+            let (previous, current, next) = corner(i);
+            let (a, b, c) = (outline[previous], outline[current], outline[next]);
+            cross(&a, &b, &c) > 0.0
+                && remaining.iter().all(|&other| {
+                    other == previous
+                        || other == current
+                        || other == next
+                        || !triangle_contains(&a, &b, &c, &outline[other])
+                })
+        });
+
+        match ear {
+            Some(ear) => {
+                triangles.push(corner(ear));
+                remaining.remove(ear);
+            }
+            None => {
+                // Vertices on a straight line between their neighbors are
+                // removed without producing a triangle
+                let (straight, _) = (0..n)
+                    .map(|i| {
+                        let (previous, current, next) = corner(i);
+                        (
+                            i,
+                            cross(&outline[previous], &outline[current], &outline[next]),
+                        )
+                    })
+                    .find(|(_, cross)| cross.abs() < f32::EPSILON)?;
+                remaining.remove(straight);
+            }
+        }
+    }
+
+    let (a, b, c) = (
+        outline[remaining[0]],
+        outline[remaining[1]],
+        outline[remaining[2]],
+    );
+    if cross(&a, &b, &c) > 0.0 {
+        triangles.push((remaining[0], remaining[1], remaining[2]));
+    }
+
+    Some(triangles)
+}
+
+/// The z component of the cross product of the edges `a -> b` and `b -> c`,
+/// positive if the corner at `b` turns counterclockwise.
+fn cross(a: &Point2<f32>, b: &Point2<f32>, c: &Point2<f32>) -> f32 {
+    let ab = b - a;
+    let bc = c - b;
+    ab.x * bc.y - ab.y * bc.x
+}
+
+/// Whether the point is inside or on the border of the counterclockwise
+/// triangle.
+fn triangle_contains(
+    a: &Point2<f32>,
+    b: &Point2<f32>,
+    c: &Point2<f32>,
+    point: &Point2<f32>,
+) -> bool {
+    cross(a, b, point) >= 0.0 && cross(b, c, point) >= 0.0 && cross(c, a, point) >= 0.0
+}
+
 /// Create UV Sphere primitive at `position` with `scale`,
 /// `n_parallels` and `n_meridians`.
 ///
@@ -195,3 +319,71 @@ pub fn create_uv_sphere(
         normal_strategy,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::{analysis, Face};
+
+    use super::*;
+
+    fn face_normals_z(mesh: &Mesh) -> Vec<f32> {
+        let vertices = mesh.vertices();
+        mesh.faces()
+            .iter()
+            .map(|face| match face {
+                Face::Triangle(f) => {
+                    let v1 = vertices[cast_usize(f.vertices.0)];
+                    let v2 = vertices[cast_usize(f.vertices.1)];
+                    let v3 = vertices[cast_usize(f.vertices.2)];
+                    (v2 - v1).cross(&(v3 - v1)).z
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_create_polygon_concave_clockwise() {
+        // L-shaped outline wound clockwise
+        let outline = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(0.0, 4.0),
+            Point2::new(2.0, 4.0),
+            Point2::new(2.0, 2.0),
+            Point2::new(4.0, 2.0),
+            Point2::new(4.0, 0.0),
+        ];
+
+        let mesh = create_polygon(&outline).expect("Must create polygon");
+
+        assert_eq!(mesh.faces().len(), 4);
+        assert!(face_normals_z(&mesh).iter().all(|z| *z > 0.0));
+        approx::assert_relative_eq!(analysis::mesh_surface_area(&mesh), 12.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_create_polygon_with_straight_vertex() {
+        let outline = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(2.0, 0.0),
+            Point2::new(2.0, 2.0),
+            Point2::new(0.0, 2.0),
+        ];
+
+        let mesh = create_polygon(&outline).expect("Must create polygon");
+
+        assert!(face_normals_z(&mesh).iter().all(|z| *z > 0.0));
+        approx::assert_relative_eq!(analysis::mesh_surface_area(&mesh), 4.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_create_polygon_without_area() {
+        assert!(create_polygon(&[Point2::new(0.0, 0.0), Point2::new(1.0, 0.0)]).is_none());
+        assert!(create_polygon(&[
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(2.0, 0.0),
+        ])
+        .is_none());
+    }
+}
@@ -8,7 +8,7 @@ use nalgebra as na;
 use nalgebra::Point3;
 
 use crate::convert::{cast_u32, cast_usize};
-use crate::mesh::{topology, Face, Mesh, NormalStrategy};
+use crate::mesh::{topology, Face, Mesh, NormalStrategy, OrientedEdge, UnorientedEdge};
 
 /// Relaxes angles between mesh edges, resulting in a smoother
 /// mesh, optionally keeping some vertices anchored, resulting in
@@ -349,13 +349,235 @@ pub fn loop_subdivision(
     )
 }
 
+/// How the vertices on the mesh borders are treated by the subdivision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderStrategy {
+    /// Border vertices are smoothened along the border curve.
+    Smooth,
+    /// Border vertices keep their original positions.
+    Fixed,
+}
+
+/// Performs one iteration of Catmull-Clark Subdivision on mesh.
+///
+/// The subdivision works in three steps:
+///
+/// 1) Create a face point in the centroid of each face,
+/// 2) Create an edge point for each edge as an average of the edge end
+///    points and the face points of the two faces sharing the edge,
+/// 3) Relocate each original vertex to a weighted average of its original
+///    position and the neighboring face and edge midpoints.
+///
+/// Each triangle is then split into three quads, connecting the face point
+/// with the edge points and the original vertex. Because meshes only consist
+/// of triangles, each quad is split into two triangles, so the face count
+/// increases six times per iteration.
+///
+/// Edge points of border edges, as well as of non-manifold edges, are placed
+/// in the edge midpoints, resulting in creases. Border vertices are either
+/// smoothened along the border curve or kept in place, depending on the
+/// border strategy.
+///
+/// The mesh **must** be triangulated.
+///
+/// Implementation based on [Wikipedia]
+/// (https://en.wikipedia.org/wiki/Catmull%E2%80%93Clark_subdivision_surface).
+pub fn catmull_clark_subdivision(
+    mesh: &Mesh,
+    vertex_to_vertex_topology: &[SmallVec<[u32; topology::MAX_INLINE_NEIGHBOR_COUNT]>],
+    vertex_to_face_topology: &[SmallVec<[u32; topology::MAX_INLINE_NEIGHBOR_COUNT]>],
+    border_strategy: BorderStrategy,
+    normal_strategy: NormalStrategy,
+) -> Option<Mesh> {
+    if !mesh.is_triangulated() {
+        return None;
+    }
+
+    let vertex_count = mesh.vertices().len();
+    let face_count = mesh.faces().len();
+
+    // Face points are placed in the face centroids
+    let mut face_points: Vec<Point3<f32>> = Vec::with_capacity(face_count);
+    // Each edge is visited once per face containing it. The key is the edge
+    // and the value is the index of the edge in `edge_faces`.
+    let mut edge_indices: HashMap<UnorientedEdge, usize> = HashMap::new();
+    let mut edge_faces: Vec<(UnorientedEdge, SmallVec<[u32; 2]>)> = Vec::new();
+
+    for (face_index, face) in mesh.faces().iter().enumerate() {
+        match face {
+            Face::Triangle(triangle_face) => {
+                let (vi1, vi2, vi3) = triangle_face.vertices;
+                let v1 = mesh.vertices()[cast_usize(vi1)];
+                let v2 = mesh.vertices()[cast_usize(vi2)];
+                let v3 = mesh.vertices()[cast_usize(vi3)];
+                face_points.push(Point3::from((v1.coords + v2.coords + v3.coords) / 3.0));
+
+                for edge in &triangle_face.to_unoriented_edges() {
+                    match edge_indices.entry(*edge) {
+                        Entry::Occupied(occupied) => {
+                            edge_faces[*occupied.get()].1.push(cast_u32(face_index));
+                        }
+                        Entry::Vacant(vacant) => {
+                            vacant.insert(edge_faces.len());
+                            let mut faces = SmallVec::new();
+                            faces.push(cast_u32(face_index));
+                            edge_faces.push((*edge, faces));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let is_inner_edge = |vi1: u32, vi2: u32| {
+        let edge = OrientedEdge::new(vi1, vi2).to_unoriented();
+        edge_indices
+            .get(&edge)
+            .map_or(false, |edge_index| edge_faces[*edge_index].1.len() == 2)
+    };
+
+    let mut vertices: Vec<Point3<f32>> =
+        Vec::with_capacity(vertex_count + face_count + edge_faces.len());
+
+    // Relocate existing vertices first
+    for (vertex_index, vertex) in mesh.vertices().iter().enumerate() {
+        let neighbors = &vertex_to_vertex_topology[vertex_index];
+        let border_neighbors: SmallVec<[u32; 2]> = neighbors
+            .iter()
+            .copied()
+            .filter(|neighbor| !is_inner_edge(cast_u32(vertex_index), *neighbor))
+            .collect();
+
+        let relocated_vertex = if neighbors.is_empty() {
+            // An orphan vertex stays where it is
+            *vertex
+        } else if border_neighbors.is_empty() {
+            // For inner vertices with valency N, use (Q + 2R + (N - 3)P) / N,
+            // where Q is the average of the neighboring face points, R is the
+            // average of the neighboring edge midpoints and P is the original
+            // vertex.
+
+            let n = neighbors.len() as f32;
+            let faces = &vertex_to_face_topology[vertex_index];
+
+            let mut face_average = Point3::origin();
+            for face_index in faces {
+                face_average += face_points[cast_usize(*face_index)].coords;
+            }
+            face_average /= faces.len() as f32;
+
+            let mut edge_midpoint_average = Point3::origin();
+            for neighbor_index in neighbors {
+                let neighbor = mesh.vertices()[cast_usize(*neighbor_index)];
+                edge_midpoint_average += na::center(vertex, &neighbor).coords;
+            }
+            edge_midpoint_average /= n;
+
+            Point3::from(
+                (face_average.coords
+                    + edge_midpoint_average.coords * 2.0
+                    + vertex.coords * (n - 3.0))
+                    / n,
+            )
+        } else {
+            match (border_strategy, border_neighbors.as_slice()) {
+                // For border vertices with exactly two border neighbors, use
+                // the (3/4, 1/8, 1/8) cubic B-spline relocation scheme.
+                (BorderStrategy::Smooth, [vi1, vi2]) => {
+                    let v1 = mesh.vertices()[cast_usize(*vi1)];
+                    let v2 = mesh.vertices()[cast_usize(*vi2)];
+
+                    Point3::origin()
+                        + vertex.coords * 3.0 / 4.0
+                        + v1.coords * 1.0 / 8.0
+                        + v2.coords * 1.0 / 8.0
+                }
+                // Fixed border vertices and vertices where borders or
+                // non-manifold edges meet stay where they are
+                _ => *vertex,
+            }
+        };
+
+        vertices.push(relocated_vertex);
+    }
+
+    let first_face_point_index = cast_u32(vertices.len());
+    vertices.extend_from_slice(&face_points);
+
+    // Create the edge points
+    let first_edge_point_index = cast_u32(vertices.len());
+    for (edge, faces) in &edge_faces {
+        let (vi1, vi2) = edge.0.vertices;
+        let v1 = mesh.vertices()[cast_usize(vi1)];
+        let v2 = mesh.vertices()[cast_usize(vi2)];
+
+        let edge_point = match faces.as_slice() {
+            // For inner edges, use the (1/4, 1/4, 1/4, 1/4) scheme of the
+            // edge end points and the face points
+            [fi1, fi2] => {
+                let f1 = face_points[cast_usize(*fi1)];
+                let f2 = face_points[cast_usize(*fi2)];
+
+                Point3::from((v1.coords + v2.coords + f1.coords + f2.coords) / 4.0)
+            }
+            // For border and non-manifold edges, use the (1/2, 1/2) scheme
+            _ => na::center(&v1, &v2),
+        };
+
+        vertices.push(edge_point);
+    }
+
+    // Split each triangle into three quads, each made of two triangles
+
+    let faces_len_estimate = face_count * 6;
+    let mut faces: Vec<(u32, u32, u32)> = Vec::with_capacity(faces_len_estimate);
+
+    let edge_point_index = |vi1: u32, vi2: u32| {
+        let edge = OrientedEdge::new(vi1, vi2).to_unoriented();
+        first_edge_point_index + cast_u32(edge_indices[&edge])
+    };
+
+    for (face_index, face) in mesh.faces().iter().enumerate() {
+        match face {
+            Face::Triangle(triangle_face) => {
+                let (vi1, vi2, vi3) = triangle_face.vertices;
+                let face_point_index = first_face_point_index + cast_u32(face_index);
+                let edge_v1v2_index = edge_point_index(vi1, vi2);
+                let edge_v2v3_index = edge_point_index(vi2, vi3);
+                let edge_v3v1_index = edge_point_index(vi3, vi1);
+
+                for (vi, edge_in_index, edge_out_index) in &[
+                    (vi1, edge_v3v1_index, edge_v1v2_index),
+                    (vi2, edge_v1v2_index, edge_v2v3_index),
+                    (vi3, edge_v2v3_index, edge_v3v1_index),
+                ] {
+                    faces.push((*vi, *edge_out_index, face_point_index));
+                    faces.push((*vi, face_point_index, *edge_in_index));
+                }
+            }
+        }
+    }
+
+    assert_eq!(faces.len(), faces_len_estimate);
+    assert_eq!(faces.capacity(), faces_len_estimate);
+
+    Some(
+        Mesh::from_triangle_faces_with_vertices_and_computed_normals(
+            faces,
+            vertices,
+            normal_strategy,
+        ),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::iter::FromIterator;
 
-    use nalgebra::{Rotation3, Vector3};
+    use nalgebra::{Rotation3, Vector2, Vector3};
 
     use crate::mesh::{analysis, primitive, topology, NormalStrategy, OrientedEdge};
+    use crate::plane::Plane;
 
     use super::*;
 
@@ -865,4 +1087,73 @@ mod tests {
             &subdivided_mesh
         );
     }
+
+    #[test]
+    fn test_catmull_clark_subdivision_box_stays_watertight() {
+        let mesh = primitive::create_box(
+            Point3::origin(),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let v2v = topology::compute_vertex_to_vertex_topology(&mesh);
+        let v2f = topology::compute_vertex_to_face_topology(&mesh);
+
+        let subdivided_mesh = catmull_clark_subdivision(
+            &mesh,
+            &v2v,
+            &v2f,
+            BorderStrategy::Smooth,
+            NormalStrategy::Sharp,
+        )
+        .expect("The mesh doesn't meet the Catmull-Clark subdivision prerequisites");
+
+        let oriented_edges: Vec<OrientedEdge> = subdivided_mesh.oriented_edges_iter().collect();
+        let edge_sharing_map = analysis::edge_sharing(&oriented_edges);
+
+        assert!(analysis::is_mesh_watertight(&edge_sharing_map));
+        assert!(analysis::is_mesh_orientable(&edge_sharing_map));
+        assert_eq!(subdivided_mesh.faces().len(), mesh.faces().len() * 6);
+        assert_eq!(subdivided_mesh.vertices().len(), 8 + 12 + 18);
+
+        // The subdivision surface shrinks within the convex hull of the
+        // original mesh
+        let bounding_box = mesh.bounding_box();
+        for vertex in subdivided_mesh.vertices() {
+            for i in 0..3 {
+                assert!(vertex[i] >= bounding_box.minimum_point()[i] - 0.001);
+                assert!(vertex[i] <= bounding_box.maximum_point()[i] + 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_catmull_clark_subdivision_fixed_border_keeps_border_vertices() {
+        let plane = Plane::from_origin_and_normal(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vector3::new(0.0, 0.0, 1.0),
+        );
+        let mesh = primitive::create_mesh_plane(plane, Vector2::new(2.0, 2.0));
+        let v2v = topology::compute_vertex_to_vertex_topology(&mesh);
+        let v2f = topology::compute_vertex_to_face_topology(&mesh);
+
+        let fixed_mesh = catmull_clark_subdivision(
+            &mesh,
+            &v2v,
+            &v2f,
+            BorderStrategy::Fixed,
+            NormalStrategy::Sharp,
+        )
+        .expect("The mesh doesn't meet the Catmull-Clark subdivision prerequisites");
+        let smooth_mesh = catmull_clark_subdivision(
+            &mesh,
+            &v2v,
+            &v2f,
+            BorderStrategy::Smooth,
+            NormalStrategy::Sharp,
+        )
+        .expect("The mesh doesn't meet the Catmull-Clark subdivision prerequisites");
+
+        assert_eq!(&fixed_mesh.vertices()[..4], mesh.vertices());
+        assert_ne!(&smooth_mesh.vertices()[..4], mesh.vertices());
+    }
 }
@@ -5,8 +5,11 @@ use nalgebra::{Matrix4, Point3, Vector3};
 use smallvec::{smallvec, SmallVec};
 
 use crate::convert::{cast_u32, cast_usize};
+use crate::plane::Plane;
 
-use super::{topology, Face, Mesh, OrientedEdge, TriangleFace, UnorientedEdge};
+use super::{
+    analysis, topology, Face, Mesh, NormalStrategy, OrientedEdge, TriangleFace, UnorientedEdge,
+};
 
 /// Orients all the faces the same way - matches their winding (vertex order).
 ///
@@ -143,6 +146,259 @@ pub fn revert_mesh_faces(mesh: &Mesh) -> Mesh {
     )
 }
 
+/// Extrudes the mesh along the vector into a solid. The mesh is copied to the
+/// end of the vector and its border edges are connected to the copy by side
+/// walls. The original mesh, reverted, caps the solid from the other side.
+///
+/// The solid faces outwards regardless of which way the input mesh faces,
+/// as long as its faces have consistent winding.
+pub fn extrude(mesh: &Mesh, vector: &Vector3<f32>) -> Mesh {
+    let vertex_count = cast_u32(mesh.vertices().len());
+    let vertices: Vec<_> = mesh
+        .vertices()
+        .iter()
+        .copied()
+        .chain(mesh.vertices().iter().map(|vertex| vertex + vector))
+        .collect();
+
+    let mut faces = Vec::with_capacity(mesh.faces().len() * 2);
+    for face in mesh.faces() {
+        let Face::Triangle(triangle_face) = face;
+        let (v1, v2, v3) = triangle_face.vertices;
+        faces.push((v1, v3, v2));
+        faces.push((v1 + vertex_count, v2 + vertex_count, v3 + vertex_count));
+    }
+
+    let oriented_edges: Vec<_> = mesh.oriented_edges_iter().collect();
+    let edge_sharing = analysis::edge_sharing(&oriented_edges);
+    for edge in analysis::border_edges(&edge_sharing) {
+        let (v1, v2) = edge.vertices;
+        faces.push((v1, v2, v2 + vertex_count));
+        faces.push((v1, v2 + vertex_count, v1 + vertex_count));
+    }
+
+    let extruded = Mesh::from_triangle_faces_with_vertices_and_computed_normals(
+        faces.iter().copied(),
+        vertices.iter().copied(),
+        NormalStrategy::Sharp,
+    );
+
+    // Extruding against the direction the mesh faces turns the solid inside
+    // out
+    if analysis::mesh_volume(&extruded) < 0.0 {
+        Mesh::from_triangle_faces_with_vertices_and_computed_normals(
+            faces.iter().map(|&(v1, v2, v3)| (v1, v3, v2)),
+            vertices,
+            NormalStrategy::Sharp,
+        )
+    } else {
+        extruded
+    }
+}
+
+/// Extrudes the mesh along its vertex normals by the distance. Each vertex is
+/// pushed along the average normal of its faces and the border edges are
+/// connected to the pushed copy by side walls. With `cap` set, the original
+/// mesh, reverted, closes the extrusion from the other side, so that an open
+/// mesh becomes a solid and a closed mesh becomes a hollow solid.
+///
+/// Negative distances extrude against the normals. The pushed faces keep
+/// facing the same way as the input mesh, unless they become the bottom of a
+/// capped extrusion. Because the vertices are pushed by the distance, faces
+/// meeting at sharp angles end up slightly closer to their original position.
+pub fn extrude_along_normals(mesh: &Mesh, distance: f32, cap: bool) -> Mesh {
+    extrude_between_normal_offsets(mesh, 0.0, distance, cap)
+}
+
+/// Thickens the mesh into a solid shell. The mesh is offset along its vertex
+/// normals by half of the thickness to both sides. The outer offset faces the
+/// same way as the input mesh, the inner offset is reverted and the border
+/// edges of both are connected by side walls.
+///
+/// An open surface becomes a solid plate, a closed mesh becomes a hollow
+/// solid with walls of the thickness.
+pub fn thicken(mesh: &Mesh, thickness: f32) -> Mesh {
+    extrude_between_normal_offsets(mesh, -0.5 * thickness, 0.5 * thickness, true)
+}
+
+/// Pushes the mesh along its vertex normals by the `from` distance and by the
+/// `to` distance and connects the border edges of both copies by side walls.
+/// With `cap` set, the copy at the `from` distance is kept and reverted,
+/// closing the extrusion.
+fn extrude_between_normal_offsets(mesh: &Mesh, from: f32, to: f32, cap: bool) -> Mesh {
+    let vertex_count = cast_u32(mesh.vertices().len());
+    let vertex_to_face_topology = topology::compute_vertex_to_face_topology(mesh);
+    let vertex_normals = super::compute_smooth_normals_from_components(
+        mesh.vertices(),
+        mesh.faces(),
+        &vertex_to_face_topology,
+    );
+
+    let mut vertices = Vec::with_capacity(mesh.vertices().len() * 2);
+    for distance in &[from, to] {
+        for (vertex, normal) in mesh.vertices().iter().zip(vertex_normals.iter()) {
+            // Orphan vertices have no normal to be pushed along
+            if normal.iter().all(|coordinate| coordinate.is_finite()) {
+                vertices.push(vertex + normal * *distance);
+            } else {
+                vertices.push(*vertex);
+            }
+        }
+    }
+
+    let reverted = to < from;
+    let orient = |(v1, v2, v3): (u32, u32, u32), revert: bool| {
+        if revert {
+            (v1, v3, v2)
+        } else {
+            (v1, v2, v3)
+        }
+    };
+
+    let mut faces = Vec::with_capacity(mesh.faces().len() * 2);
+    for face in mesh.faces() {
+        let Face::Triangle(triangle_face) = face;
+        let (v1, v2, v3) = triangle_face.vertices;
+        faces.push(orient(
+            (v1 + vertex_count, v2 + vertex_count, v3 + vertex_count),
+            cap && reverted,
+        ));
+        if cap {
+            faces.push(orient((v1, v2, v3), !reverted));
+        }
+    }
+
+    let oriented_edges: Vec<_> = mesh.oriented_edges_iter().collect();
+    let edge_sharing = analysis::edge_sharing(&oriented_edges);
+    for edge in analysis::border_edges(&edge_sharing) {
+        let (v1, v2) = edge.vertices;
+        faces.push(orient((v1, v2, v2 + vertex_count), reverted));
+        faces.push(orient((v1, v2 + vertex_count, v1 + vertex_count), reverted));
+    }
+
+    Mesh::from_triangle_faces_with_vertices_and_computed_normals_remove_orphans(
+        faces,
+        vertices,
+        NormalStrategy::Sharp,
+    )
+}
+
+/// Mirrors the mesh geometry by the plane. The faces are reverted, so that the
+/// mirrored mesh faces the same way as the original one.
+pub fn mirror(mesh: &Mesh, plane: &Plane) -> Mesh {
+    let reverted_faces = mesh.faces().iter().map(|face| match face {
+        Face::Triangle(triangle_face) => triangle_face.to_reverted(),
+    });
+    Mesh::from_triangle_faces_with_vertices_and_normals(
+        reverted_faces,
+        mesh.vertices()
+            .iter()
+            .map(|vertex| mirror_point(vertex, plane)),
+        mesh.normals()
+            .iter()
+            .map(|normal| mirror_vector(normal, plane)),
+    )
+}
+
+/// Mirrors the mesh geometry by the plane and joins the mirrored copy with
+/// the original mesh.
+///
+/// Vertices within the tolerance from the plane are snapped to the plane and
+/// shared by both halves, so that the halves are welded along the plane. Faces
+/// lying in the plane would end up inside the joined mesh and are removed.
+///
+/// Returns `None` if the entire mesh lies in the plane.
+pub fn mirror_and_join(mesh: &Mesh, plane: &Plane, tolerance: f32) -> Option<Mesh> {
+    let plane_normal = plane.normal();
+    let vertex_count = mesh.vertices().len();
+    let normal_count = cast_u32(mesh.normals().len());
+
+    let mut vertices: Vec<Point3<f32>> = Vec::with_capacity(vertex_count * 2);
+    let mut mirrored_vertex_indices: Vec<u32> = Vec::with_capacity(vertex_count);
+    let mut on_plane: Vec<bool> = Vec::with_capacity(vertex_count);
+    vertices.extend_from_slice(mesh.vertices());
+
+    for (vertex_index, vertex) in mesh.vertices().iter().enumerate() {
+        let distance = (vertex - plane.origin()).dot(&plane_normal);
+        if distance.abs() <= tolerance {
+            vertices[vertex_index] = vertex - plane_normal * distance;
+            mirrored_vertex_indices.push(cast_u32(vertex_index));
+            on_plane.push(true);
+        } else {
+            mirrored_vertex_indices.push(cast_u32(vertices.len()));
+            vertices.push(mirror_point(vertex, plane));
+            on_plane.push(false);
+        }
+    }
+
+    let normals: Vec<Vector3<f32>> = mesh
+        .normals()
+        .iter()
+        .copied()
+        .chain(
+            mesh.normals()
+                .iter()
+                .map(|normal| mirror_vector(normal, plane)),
+        )
+        .collect();
+
+    let mut faces: Vec<TriangleFace> = Vec::with_capacity(mesh.faces().len() * 2);
+    for face in mesh.faces() {
+        let Face::Triangle(triangle_face) = face;
+        let (v1, v2, v3) = triangle_face.vertices;
+        let (n1, n2, n3) = triangle_face.normals;
+
+        if on_plane[cast_usize(v1)] && on_plane[cast_usize(v2)] && on_plane[cast_usize(v3)] {
+            continue;
+        }
+
+        faces.push(*triangle_face);
+        faces.push(TriangleFace::new(
+            mirrored_vertex_indices[cast_usize(v3)],
+            mirrored_vertex_indices[cast_usize(v2)],
+            mirrored_vertex_indices[cast_usize(v1)],
+            n3 + normal_count,
+            n2 + normal_count,
+            n1 + normal_count,
+        ));
+    }
+
+    if faces.is_empty() {
+        return None;
+    }
+
+    Some(
+        Mesh::from_triangle_faces_with_vertices_and_normals_remove_orphans(
+            faces, vertices, normals,
+        ),
+    )
+}
+
+fn mirror_point(point: &Point3<f32>, plane: &Plane) -> Point3<f32> {
+    let plane_normal = plane.normal();
+    point - plane_normal * 2.0 * (point - plane.origin()).dot(&plane_normal)
+}
+
+fn mirror_vector(vector: &Vector3<f32>, plane: &Plane) -> Vector3<f32> {
+    let plane_normal = plane.normal();
+    vector - plane_normal * 2.0 * vector.dot(&plane_normal)
+}
+
+/// Scales the mesh geometry uniformly around the world origin by a positive
+/// factor, e.g. to convert it between units of length. Normals are kept,
+/// because a positive uniform scaling doesn't change them.
+pub fn scale_uniformly(mesh: &Mesh, scale: f32) -> Mesh {
+    assert!(scale > 0.0, "Scale must be positive");
+
+    Mesh::from_faces_with_vertices_and_normals(
+        mesh.faces().iter().copied(),
+        mesh.vertices()
+            .iter()
+            .map(|v| Point3::from(v.coords * scale)),
+        mesh.normals().iter().copied(),
+    )
+}
+
 /// Weld similar (their distance is within the given tolerance) vertices into
 /// one and reuse such vertices in connected faces.
 ///
@@ -288,10 +544,25 @@ pub fn weld(mesh: &Mesh, tolerance: f32) -> Option<Mesh> {
 /// Crawls the mesh geometry to find continuous patches. Returns a
 /// vector mesh patches.
 pub fn disjoint_mesh(mesh: &Mesh) -> Vec<Mesh> {
+    let mut patches = Vec::new();
+    disjoint_mesh_with_callback(mesh, |patch| {
+        patches.push(patch);
+        true
+    });
+
+    patches
+}
+
+/// Crawls the mesh geometry to find continuous patches and passes each patch
+/// to the callback as soon as it is found. Stops crawling, if the callback
+/// returns `false`.
+pub fn disjoint_mesh_with_callback<F>(mesh: &Mesh, mut patch_found: F)
+where
+    F: FnMut(Mesh) -> bool,
+{
     let vertex_to_face_topology = topology::compute_vertex_to_face_topology(&mesh);
     let face_to_face = topology::compute_face_to_face_topology(mesh, &vertex_to_face_topology);
     let mut available_face_indices: HashSet<u32> = (0..cast_u32(mesh.faces().len())).collect();
-    let mut patches: Vec<Mesh> = Vec::new();
     let mut index_stack: Vec<u32> = Vec::new();
     let mut connected_face_indices = HashSet::new();
 
@@ -311,16 +582,18 @@ pub fn disjoint_mesh(mesh: &Mesh) -> Vec<Mesh> {
             }
         }
 
-        patches.push(Mesh::from_faces_with_vertices_and_normals_remove_orphans(
+        let patch = Mesh::from_faces_with_vertices_and_normals_remove_orphans(
             connected_face_indices
                 .iter()
                 .map(|face_index| mesh.faces()[cast_usize(*face_index)]),
             mesh.vertices().to_vec(),
             mesh.normals().to_vec(),
-        ));
-    }
+        );
 
-    patches
+        if !patch_found(patch) {
+            break;
+        }
+    }
 }
 
 /// Joins multiple mesh geometries into one.
@@ -406,7 +679,7 @@ pub fn align_two_meshes(mesh_to_align: &Mesh, align_to_mesh: &Mesh) -> Mesh {
 mod tests {
     use nalgebra::{Rotation3, Vector2};
 
-    use crate::mesh::{analysis, primitive, NormalStrategy};
+    use crate::mesh::primitive;
     use crate::plane::Plane;
 
     use super::*;
@@ -740,6 +1013,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_disjoint_mesh_with_callback_stops_when_asked() {
+        let mesh = tessellated_triangle_with_island_mesh();
+
+        let mut patch_count = 0;
+        disjoint_mesh_with_callback(&mesh, |_| {
+            patch_count += 1;
+            false
+        });
+
+        assert_eq!(patch_count, 1);
+    }
+
+    #[test]
+    fn test_scale_uniformly() {
+        let mesh = primitive::create_box(
+            Point3::new(1.0, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+
+        let scaled = scale_uniformly(&mesh, 0.5);
+
+        assert_eq!(scaled.faces(), mesh.faces());
+        assert_eq!(scaled.normals(), mesh.normals());
+        for (scaled_vertex, vertex) in scaled.vertices().iter().zip(mesh.vertices()) {
+            approx::assert_relative_eq!(scaled_vertex.coords, vertex.coords * 0.5);
+        }
+    }
+
     #[test]
     fn test_revert_mesh_faces() {
         let plane = Plane::from_origin_and_normal(
@@ -874,4 +1177,125 @@ mod tests {
 
         assert_eq!(&mesh_correct, &mesh_computed);
     }
+
+    #[test]
+    fn test_extrude_produces_watertight_solid_facing_outwards() {
+        let plane = Plane::new(
+            &Point3::origin(),
+            &Vector3::new(1.0, 0.0, 0.0),
+            &Vector3::new(0.0, 1.0, 0.0),
+        );
+        let mesh = primitive::create_mesh_plane(plane, Vector2::new(2.0, 2.0));
+
+        for mesh in &[mesh.clone(), revert_mesh_faces(&mesh)] {
+            let extruded = extrude(mesh, &Vector3::new(0.0, 0.0, 3.0));
+
+            let oriented_edges: Vec<_> = extruded.oriented_edges_iter().collect();
+            let edge_sharing = analysis::edge_sharing(&oriented_edges);
+
+            assert_eq!(extruded.faces().len(), 12);
+            assert!(analysis::is_mesh_watertight(&edge_sharing));
+            approx::assert_relative_eq!(analysis::mesh_volume(&extruded), 12.0, epsilon = 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_extrude_along_normals_capped_produces_watertight_solid_facing_outwards() {
+        let plane = Plane::new(
+            &Point3::origin(),
+            &Vector3::new(1.0, 0.0, 0.0),
+            &Vector3::new(0.0, 1.0, 0.0),
+        );
+        let mesh = primitive::create_mesh_plane(plane, Vector2::new(2.0, 2.0));
+
+        for distance in &[3.0, -3.0] {
+            let extruded = extrude_along_normals(&mesh, *distance, true);
+
+            let oriented_edges: Vec<_> = extruded.oriented_edges_iter().collect();
+            let edge_sharing = analysis::edge_sharing(&oriented_edges);
+
+            assert_eq!(extruded.faces().len(), 12);
+            assert!(analysis::is_mesh_watertight(&edge_sharing));
+            approx::assert_relative_eq!(analysis::mesh_volume(&extruded), 12.0, epsilon = 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_thicken_open_surface_produces_watertight_solid_facing_outwards() {
+        let plane = Plane::new(
+            &Point3::origin(),
+            &Vector3::new(1.0, 0.0, 0.0),
+            &Vector3::new(0.0, 1.0, 0.0),
+        );
+        let mesh = primitive::create_mesh_plane(plane, Vector2::new(2.0, 2.0));
+
+        let shell = thicken(&mesh, 0.5);
+
+        let oriented_edges: Vec<_> = shell.oriented_edges_iter().collect();
+        let edge_sharing = analysis::edge_sharing(&oriented_edges);
+
+        assert!(analysis::is_mesh_watertight(&edge_sharing));
+        approx::assert_relative_eq!(analysis::mesh_volume(&shell), 2.0, epsilon = 0.0001);
+        approx::assert_relative_eq!(shell.bounding_box().minimum_point().z, -0.25);
+        approx::assert_relative_eq!(shell.bounding_box().maximum_point().z, 0.25);
+    }
+
+    #[test]
+    fn test_mirror_keeps_volume_facing_outwards() {
+        let mesh = primitive::create_box(
+            Point3::new(1.0, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let plane = Plane::from_origin_and_normal(&Point3::origin(), &Vector3::new(1.0, 0.0, 0.0));
+
+        let mirrored = mirror(&mesh, &plane);
+
+        approx::assert_relative_eq!(analysis::mesh_volume(&mirrored), 1.0, epsilon = 0.0001);
+        approx::assert_relative_eq!(mirrored.bounding_box().center().x, -1.0);
+    }
+
+    #[test]
+    fn test_mirror_and_join_welds_halves_at_plane() {
+        let mesh = primitive::create_box(
+            Point3::new(0.5, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+        let plane = Plane::from_origin_and_normal(&Point3::origin(), &Vector3::new(1.0, 0.0, 0.0));
+
+        let joined = mirror_and_join(&mesh, &plane, 0.001).expect("The mesh lies in the plane");
+
+        let oriented_edges: Vec<_> = joined.oriented_edges_iter().collect();
+        let edge_sharing = analysis::edge_sharing(&oriented_edges);
+
+        // The 4 vertices on the plane are shared by both halves and the 2
+        // faces on the plane are removed from both
+        assert_eq!(joined.vertices().len(), 12);
+        assert_eq!(joined.faces().len(), 20);
+        assert!(analysis::is_mesh_watertight(&edge_sharing));
+        approx::assert_relative_eq!(analysis::mesh_volume(&joined), 2.0, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_extrude_along_normals_uncapped_pushes_faces_along_normals() {
+        let plane = Plane::new(
+            &Point3::origin(),
+            &Vector3::new(1.0, 0.0, 0.0),
+            &Vector3::new(0.0, 1.0, 0.0),
+        );
+        let mesh = primitive::create_mesh_plane(plane, Vector2::new(2.0, 2.0));
+
+        let extruded = extrude_along_normals(&mesh, 3.0, false);
+
+        let oriented_edges: Vec<_> = extruded.oriented_edges_iter().collect();
+        let edge_sharing = analysis::edge_sharing(&oriented_edges);
+
+        assert_eq!(extruded.faces().len(), 10);
+        assert!(!analysis::is_mesh_watertight(&edge_sharing));
+        assert!(extruded
+            .vertices()
+            .iter()
+            .any(|vertex| approx::relative_eq!(vertex.z, 3.0)));
+    }
 }
@@ -0,0 +1,343 @@
+//! Locale-dependent formatting of numbers displayed to the user and
+//! normalizing of numbers typed by the user.
+//!
+//! Numbers are kept and evaluated with a decimal point internally, e.g. in
+//! projects and math expressions. Only the text the user reads and types
+//! follows the chosen format.
+
+use std::fmt;
+
+/// How numbers are displayed and typed, e.g. `0.5` or `0,5` for a half.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum NumberFormat {
+    /// Decimal point, thousands separated by commas: `1,234.5`.
+    DecimalPoint,
+    /// Decimal comma, thousands separated by points: `1.234,5`.
+    DecimalCommaThousandsPoint,
+    /// Decimal comma, thousands separated by spaces: `1 234,5`.
+    DecimalCommaThousandsSpace,
+}
+
+impl NumberFormat {
+    pub const ALL: [NumberFormat; 3] = [
+        NumberFormat::DecimalPoint,
+        NumberFormat::DecimalCommaThousandsPoint,
+        NumberFormat::DecimalCommaThousandsSpace,
+    ];
+
+    pub fn decimal_separator(self) -> char {
+        match self {
+            NumberFormat::DecimalPoint => '.',
+            NumberFormat::DecimalCommaThousandsPoint | NumberFormat::DecimalCommaThousandsSpace => {
+                ','
+            }
+        }
+    }
+
+    pub fn thousands_separator(self) -> char {
+        match self {
+            NumberFormat::DecimalPoint => ',',
+            NumberFormat::DecimalCommaThousandsPoint => '.',
+            NumberFormat::DecimalCommaThousandsSpace => ' ',
+        }
+    }
+
+    /// Returns the separator of numbers listed in text, which can't be the
+    /// decimal separator.
+    pub fn list_separator(self) -> &'static str {
+        match self.decimal_separator() {
+            ',' => "; ",
+            _ => ", ",
+        }
+    }
+
+    /// Formats the number with the given count of decimal places and the
+    /// thousands separated.
+    pub fn format_float(self, value: f64, precision: usize) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+
+        let formatted = format!("{:.*}", precision, value);
+        let (sign, unsigned) = match formatted.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", formatted.as_str()),
+        };
+        let (integer, fraction) = match unsigned.find('.') {
+            Some(point_index) => (&unsigned[..point_index], &unsigned[point_index + 1..]),
+            None => (unsigned, ""),
+        };
+
+        let mut output = String::with_capacity(formatted.len() + integer.len() / 3);
+        output.push_str(sign);
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                output.push(self.thousands_separator());
+            }
+            output.push(digit);
+        }
+        if !fraction.is_empty() {
+            output.push(self.decimal_separator());
+            output.push_str(fraction);
+        }
+
+        output
+    }
+
+    /// Converts a math expression written with decimal points and commas
+    /// separating the components to this format, e.g. `0.5, 2` to `0,5; 2`.
+    /// The numbers are not grouped, so that the text can be edited and typed
+    /// back.
+    pub fn localize_expression(self, text: &str) -> String {
+        match self.decimal_separator() {
+            ',' => text
+                .chars()
+                .map(|c| match c {
+                    ',' => ';',
+                    '.' => ',',
+                    c => c,
+                })
+                .collect(),
+            _ => text.to_string(),
+        }
+    }
+
+    /// Converts a typed math expression with `component_count` components to
+    /// use decimal points and commas separating the components.
+    ///
+    /// The conversion is tolerant, so that habits from other formats still
+    /// work. Numbers grouped into thousands by this format's separator or by
+    /// spaces are accepted and so are decimal points, unless they separate
+    /// thousands in this format. With a decimal comma, components are
+    /// separated by semicolons, but if there are none, commas separate the
+    /// components of multi-component expressions.
+    pub fn delocalize_expression(self, text: &str, component_count: usize) -> String {
+        let decimal_separator = self.decimal_separator();
+        let thousands_separator = self.thousands_separator();
+        let separated_by_semicolons = text.contains(';');
+
+        let mut text = remove_digit_group_separators(text, ' ', decimal_separator);
+        // Without semicolons, commas separate the components of
+        // multi-component expressions instead of thousands.
+        if thousands_separator != ' '
+            && (thousands_separator != ',' || separated_by_semicolons || component_count == 1)
+        {
+            text = remove_digit_group_separators(&text, thousands_separator, decimal_separator);
+        }
+
+        if separated_by_semicolons {
+            text.chars()
+                .map(|c| match c {
+                    ',' if decimal_separator == ',' => '.',
+                    ';' => ',',
+                    c => c,
+                })
+                .collect()
+        } else if decimal_separator == ',' && component_count == 1 {
+            text.replace(',', ".")
+        } else {
+            text
+        }
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat::DecimalPoint
+    }
+}
+
+impl fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumberFormat::DecimalPoint => write!(f, "1,234.5"),
+            NumberFormat::DecimalCommaThousandsPoint => write!(f, "1.234,5"),
+            NumberFormat::DecimalCommaThousandsSpace => write!(f, "1 234,5"),
+        }
+    }
+}
+
+/// Removes the separator from numbers grouped into thousands, e.g. the commas
+/// in `1,234,567`.
+///
+/// A number is only considered grouped, if its first group has one to three
+/// digits without a leading zero, each of the other groups has exactly three
+/// digits and the number doesn't follow the decimal separator. Other numbers
+/// are kept as typed, so that e.g. `0.500` or `1234.567` are not mistaken for
+/// thousands separated by points.
+fn remove_digit_group_separators(text: &str, separator: char, decimal_separator: char) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+
+    let mut index = 0;
+    while index < chars.len() {
+        if !chars[index].is_ascii_digit() {
+            output.push(chars[index]);
+            index += 1;
+            continue;
+        }
+
+        // The number spans the digits and the separators between them.
+        let start = index;
+        while index < chars.len()
+            && (chars[index].is_ascii_digit()
+                || (chars[index] == separator
+                    && chars.get(index + 1).map_or(false, char::is_ascii_digit)))
+        {
+            index += 1;
+        }
+
+        let number: String = chars[start..index].iter().collect();
+        let is_fraction =
+            start > 1 && chars[start - 1] == decimal_separator && chars[start - 2].is_ascii_digit();
+
+        let mut groups = number.split(separator);
+        let first_group = groups.next().unwrap_or_default();
+        let is_grouped = number.contains(separator)
+            && !is_fraction
+            && (1..=3).contains(&first_group.len())
+            && !first_group.starts_with('0')
+            && groups.all(|group| group.len() == 3);
+
+        if is_grouped {
+            output.extend(number.chars().filter(|c| *c != separator));
+        } else {
+            output.push_str(&number);
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_format_format_float() {
+        assert_eq!(
+            NumberFormat::DecimalPoint.format_float(1234567.891, 2),
+            "1,234,567.89",
+        );
+        assert_eq!(
+            NumberFormat::DecimalCommaThousandsPoint.format_float(-1234.5, 1),
+            "-1.234,5",
+        );
+        assert_eq!(
+            NumberFormat::DecimalCommaThousandsSpace.format_float(0.5, 3),
+            "0,500",
+        );
+        assert_eq!(
+            NumberFormat::DecimalCommaThousandsSpace.format_float(123.0, 0),
+            "123",
+        );
+    }
+
+    #[test]
+    fn test_number_format_localize_expression() {
+        assert_eq!(
+            NumberFormat::DecimalCommaThousandsSpace.localize_expression("0.5, 2"),
+            "0,5; 2",
+        );
+        assert_eq!(
+            NumberFormat::DecimalPoint.localize_expression("0.5, 2"),
+            "0.5, 2",
+        );
+    }
+
+    #[test]
+    fn test_number_format_delocalize_expression_decimal_comma() {
+        let number_format = NumberFormat::DecimalCommaThousandsSpace;
+
+        assert_eq!(number_format.delocalize_expression("0,5", 1), "0.5");
+        assert_eq!(number_format.delocalize_expression("0.5", 1), "0.5");
+        assert_eq!(
+            number_format.delocalize_expression("1 234,5*2", 1),
+            "1234.5*2"
+        );
+        assert_eq!(number_format.delocalize_expression("0,5; 2", 2), "0.5, 2");
+        assert_eq!(number_format.delocalize_expression("0.5, 2", 2), "0.5, 2");
+    }
+
+    #[test]
+    fn test_number_format_delocalize_expression_decimal_comma_thousands_point() {
+        let number_format = NumberFormat::DecimalCommaThousandsPoint;
+
+        assert_eq!(number_format.delocalize_expression("1.234,5", 1), "1234.5");
+        assert_eq!(number_format.delocalize_expression("1.234", 1), "1234");
+        assert_eq!(
+            number_format.delocalize_expression("1.234.567,5", 1),
+            "1234567.5"
+        );
+        assert_eq!(number_format.delocalize_expression("0.5", 1), "0.5");
+        assert_eq!(number_format.delocalize_expression("0.500", 1), "0.500");
+        assert_eq!(
+            number_format.delocalize_expression("1234.567", 1),
+            "1234.567"
+        );
+        assert_eq!(number_format.delocalize_expression("1.5000", 1), "1.5000");
+        assert_eq!(
+            number_format.delocalize_expression("1.234,5; 2", 2),
+            "1234.5, 2"
+        );
+    }
+
+    #[test]
+    fn test_number_format_localize_delocalize_expression_round_trip() {
+        let expressions = [("0.5", 1), ("1234.5*2", 1), ("0.25, 1.5, -3", 3)];
+
+        for number_format in &NumberFormat::ALL {
+            for (expression, component_count) in &expressions {
+                let localized = number_format.localize_expression(expression);
+                assert_eq!(
+                    number_format.delocalize_expression(&localized, *component_count),
+                    *expression,
+                    "{:?} round trip of {}",
+                    number_format,
+                    expression,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_number_format_format_float_delocalize_expression_round_trip() {
+        let values = [0.5, -1234.5, 1000.0, 1234567.891];
+
+        for number_format in &NumberFormat::ALL {
+            for value in &values {
+                let formatted = number_format.format_float(*value, 3);
+                assert_eq!(
+                    number_format.delocalize_expression(&formatted, 1),
+                    format!("{:.3}", value),
+                    "{:?} round trip of {}",
+                    number_format,
+                    value,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_number_format_delocalize_expression_decimal_point() {
+        let number_format = NumberFormat::DecimalPoint;
+
+        assert_eq!(number_format.delocalize_expression("0.5", 1), "0.5");
+        assert_eq!(number_format.delocalize_expression("1 234.5", 1), "1234.5");
+        assert_eq!(number_format.delocalize_expression("1,234.5", 1), "1234.5");
+        assert_eq!(
+            number_format.delocalize_expression("1,234,567", 1),
+            "1234567"
+        );
+        assert_eq!(
+            number_format.delocalize_expression("0.123,456", 1),
+            "0.123,456"
+        );
+        assert_eq!(
+            number_format.delocalize_expression("0.5; 1,234", 2),
+            "0.5, 1234"
+        );
+        assert_eq!(number_format.delocalize_expression("0.5; 2", 2), "0.5, 2");
+        assert_eq!(number_format.delocalize_expression("0.5, 2", 2), "0.5, 2");
+    }
+}
@@ -0,0 +1,676 @@
+use std::cmp::Ordering;
+use std::mem;
+use std::time::Duration;
+
+use crate::interpreter::ast::{Prog, Stmt};
+use crate::interpreter_server::{
+    InterpreterRequest, InterpreterResponse, InterpreterServer, PollResponseError, RequestId,
+};
+use crate::sweep::{self, Measurements, ParamRef, SampleOutcome};
+
+/// The maximum number of parameters varied by a single optimization.
+pub const MAX_PARAMS: usize = 3;
+
+/// The maximum number of pipeline executions of a single optimization.
+pub const MAX_EVALUATIONS: u32 = 200;
+
+/// The number of best candidates that are kept as variants.
+pub const KEEP_BEST_CANDIDATES_COUNT: usize = 3;
+
+/// How strongly the deviation from the target volume worsens the score. A
+/// candidate deviating by 10% from the target volume scores as if its measure
+/// was twice as bad.
+const TARGET_VOLUME_PENALTY: f32 = 10.0;
+
+/// The size of the initial simplex, relative to the parameter ranges.
+const INITIAL_STEP: f32 = 0.25;
+
+// Nelder-Mead coefficients of reflection, expansion, contraction and
+// shrinking. These are the commonly used defaults.
+const ALPHA: f32 = 1.0;
+const GAMMA: f32 = 2.0;
+const RHO: f32 = 0.5;
+const SIGMA: f32 = 0.5;
+
+/// The measurement of the pipeline output the optimization minimizes or
+/// maximizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Measure {
+    Volume,
+    SurfaceArea,
+    /// Ratio of the surface area to the volume. Minimizing the ratio produces
+    /// compact shapes, maximizing it produces shapes with large envelopes,
+    /// e.g. for facade exposure.
+    SurfaceAreaToVolume,
+}
+
+impl Measure {
+    pub const ALL: [Measure; 3] = [
+        Measure::Volume,
+        Measure::SurfaceArea,
+        Measure::SurfaceAreaToVolume,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Measure::Volume => "Volume",
+            Measure::SurfaceArea => "Surface area",
+            Measure::SurfaceAreaToVolume => "Surface area to volume ratio",
+        }
+    }
+
+    pub fn measure(self, measurements: &Measurements) -> f32 {
+        match self {
+            Measure::Volume => measurements.volume,
+            Measure::SurfaceArea => measurements.surface_area,
+            Measure::SurfaceAreaToVolume => measurements.surface_area / measurements.volume,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    Minimize,
+    Maximize,
+}
+
+/// What the optimization is looking for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Objective {
+    pub measure: Measure,
+    pub goal: Goal,
+    /// If set, candidates whose volume deviates from the target are
+    /// penalized, e.g. to minimize surface area at a fixed volume.
+    pub target_volume: Option<f32>,
+}
+
+impl Objective {
+    /// Computes the score of measurements. Lower scores are better,
+    /// regardless of the goal. Measurements that can not be scored (e.g. the
+    /// ratio for zero volume) get an infinite score.
+    pub fn score(&self, measurements: &Measurements) -> f32 {
+        let measure = self.measure.measure(measurements);
+        let mut score = match self.goal {
+            Goal::Minimize => measure,
+            Goal::Maximize => -measure,
+        };
+
+        if let Some(target_volume) = self.target_volume {
+            let deviation =
+                (measurements.volume - target_volume).abs() / target_volume.abs().max(f32::EPSILON);
+            score += measure.abs() * TARGET_VOLUME_PENALTY * deviation;
+        }
+
+        if score.is_finite() {
+            score
+        } else {
+            f32::INFINITY
+        }
+    }
+}
+
+/// A parameter varied by the optimization, and the range of its values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptimizationParam {
+    pub param: ParamRef,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl OptimizationParam {
+    fn normalize(&self, value: f32) -> f32 {
+        if self.max == self.min {
+            0.0
+        } else {
+            ((value - self.min) / (self.max - self.min))
+                .max(0.0)
+                .min(1.0)
+        }
+    }
+
+    fn denormalize(&self, t: f32) -> f32 {
+        self.min + (self.max - self.min) * t
+    }
+}
+
+/// A single executed combination of the parameter values.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    values: Vec<f32>,
+    stmts: Vec<Stmt>,
+    outcome: SampleOutcome,
+    score: f32,
+}
+
+impl Candidate {
+    /// The parameter values of this candidate, one for each optimization
+    /// parameter. Values of integer parameters are already rounded.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    pub fn stmts(&self) -> &[Stmt] {
+        &self.stmts
+    }
+
+    pub fn outcome(&self) -> &SampleOutcome {
+        &self.outcome
+    }
+}
+
+/// An objective-based search of the design space.
+///
+/// Varies up to `MAX_PARAMS` numeric parameters of the pipeline program within
+/// their ranges, looking for the candidate with the best score. The search
+/// starts from the current parameter values and is driven by the Nelder-Mead
+/// method, which needs no derivatives and copes reasonably with the noise
+/// caused by voxelization and integer parameters.
+///
+/// Like `Sweep`, the candidates are executed one by one in a separate
+/// interpreter, so that the session being edited is not affected.
+pub struct Optimization {
+    params: Vec<OptimizationParam>,
+    objective: Objective,
+    stmts: Vec<Stmt>,
+    nelder_mead: NelderMead,
+    candidates: Vec<Candidate>,
+    evaluations: u32,
+    max_evaluations: u32,
+    interpreter_server: InterpreterServer,
+    interpret_request_in_flight: Option<(RequestId, usize)>,
+}
+
+impl Optimization {
+    /// Creates a new optimization of the program statements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no params or more than `MAX_PARAMS` params, or if
+    /// a param does not reference a numeric literal argument of the program.
+    pub fn new(
+        stmts: &[Stmt],
+        params: Vec<OptimizationParam>,
+        objective: Objective,
+        max_evaluations: u32,
+        stmt_timeout: Option<Duration>,
+    ) -> Self {
+        assert!(
+            !params.is_empty() && params.len() <= MAX_PARAMS,
+            "Optimization must have between one and {} params",
+            MAX_PARAMS,
+        );
+        for param in &params {
+            assert!(
+                sweep::numeric_params(stmts).any(|p| p == param.param),
+                "Optimization param must reference a numeric literal argument",
+            );
+        }
+
+        let initial_point = params
+            .iter()
+            .map(|param| param.normalize(sweep::numeric_param_value(stmts, param.param)))
+            .collect();
+
+        let mut interpreter_server = InterpreterServer::new();
+        interpreter_server.set_stmt_timeout(stmt_timeout);
+
+        Self {
+            params,
+            objective,
+            stmts: stmts.to_vec(),
+            nelder_mead: NelderMead::new(initial_point, INITIAL_STEP),
+            candidates: Vec::new(),
+            evaluations: 0,
+            max_evaluations: max_evaluations.min(MAX_EVALUATIONS),
+            interpreter_server,
+            interpret_request_in_flight: None,
+        }
+    }
+
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    /// Returns the number of evaluations of the objective so far. This can be
+    /// higher than the number of candidates, because candidates with the same
+    /// parameter values are executed only once.
+    pub fn evaluations(&self) -> u32 {
+        self.evaluations
+    }
+
+    pub fn max_evaluations(&self) -> u32 {
+        self.max_evaluations
+    }
+
+    /// Stops the optimization after the candidate currently being executed.
+    pub fn stop(&mut self) {
+        self.max_evaluations = self.evaluations;
+    }
+
+    /// Returns whether the optimization used up all its evaluations.
+    pub fn finished(&self) -> bool {
+        self.interpret_request_in_flight.is_none() && self.evaluations >= self.max_evaluations
+    }
+
+    /// Returns indices of up to `count` best successfully executed
+    /// candidates, ordered from the best.
+    pub fn best_candidates(&self, count: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.score.is_finite())
+            .map(|(index, _)| index)
+            .collect();
+
+        indices.sort_by(|a, b| {
+            self.candidates[*a]
+                .score
+                .partial_cmp(&self.candidates[*b].score)
+                .unwrap_or(Ordering::Equal)
+        });
+        indices.truncate(count);
+
+        indices
+    }
+
+    /// Polls the interpreter for results of the candidate currently being
+    /// executed and starts executing the next candidate, if the interpreter
+    /// is idle.
+    pub fn poll(&mut self) {
+        #[allow(clippy::while_let_loop)]
+        loop {
+            match self.interpreter_server.poll_response() {
+                Ok((request_id, response)) => {
+                    let result = match response {
+                        InterpreterResponse::CompletedEditProg
+                        | InterpreterResponse::StreamedMesh { .. }
+                        | InterpreterResponse::CompletedSpeculate => continue,
+                        InterpreterResponse::CompletedInterpret(interpret_outcome) => {
+                            interpret_outcome.result
+                        }
+                        InterpreterResponse::AbandonedInterpret(interpret_error) => {
+                            Err(interpret_error)
+                        }
+                    };
+
+                    let (tracked_request_id, candidate_index) = self
+                        .interpret_request_in_flight
+                        .take()
+                        .expect("The interpret request must have been tracked");
+                    assert_eq!(
+                        request_id, tracked_request_id,
+                        "Interpret response must match the request",
+                    );
+
+                    let candidate = &mut self.candidates[candidate_index];
+                    match result {
+                        Ok(interpret_value) => {
+                            let meshes = sweep::output_meshes(interpret_value.unused_values);
+                            let measurements =
+                                Measurements::from_meshes(meshes.iter().map(AsRef::as_ref));
+
+                            candidate.score = self.objective.score(&measurements);
+                            candidate.outcome = SampleOutcome::Finished(measurements);
+                        }
+                        Err(interpret_error) => {
+                            // Failed candidates are treated as the worst
+                            // possible, so that the search moves away from
+                            // them.
+                            candidate.score = f32::INFINITY;
+                            candidate.outcome = SampleOutcome::Failed(interpret_error.to_string());
+                        }
+                    }
+
+                    self.nelder_mead.tell(candidate.score);
+                    self.evaluations += 1;
+                }
+                Err(PollResponseError::Pending) => break,
+            }
+        }
+
+        while self.interpret_request_in_flight.is_none() && self.evaluations < self.max_evaluations
+        {
+            let mut stmts = self.stmts.clone();
+            let mut values = Vec::with_capacity(self.params.len());
+            for (param, t) in self.params.iter().zip(self.nelder_mead.point()) {
+                sweep::set_numeric_param(&mut stmts, param.param, param.denormalize(*t));
+                values.push(sweep::numeric_param_value(&stmts, param.param));
+            }
+
+            // Rounding of integer parameters often maps different points to
+            // the same program. There is no need to execute it again.
+            if let Some(candidate) = self
+                .candidates
+                .iter()
+                .find(|candidate| candidate.values == values)
+            {
+                let score = candidate.score;
+                self.nelder_mead.tell(score);
+                self.evaluations += 1;
+
+                continue;
+            }
+
+            self.interpreter_server
+                .submit_request(InterpreterRequest::SetProg(Prog::new(stmts.clone())));
+            let request_id = self
+                .interpreter_server
+                .submit_request(InterpreterRequest::Interpret);
+
+            self.interpret_request_in_flight = Some((request_id, self.candidates.len()));
+            self.candidates.push(Candidate {
+                values,
+                stmts,
+                outcome: SampleOutcome::Pending,
+                score: f32::INFINITY,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Vertex {
+    point: Vec<f32>,
+    score: f32,
+}
+
+#[derive(Debug)]
+enum Step {
+    Initializing,
+    Reflecting,
+    Expanding { reflected: Vertex },
+    Contracting { reflected: Vertex, outside: bool },
+    Shrinking { index: usize },
+}
+
+/// The Nelder-Mead (downhill simplex) method, minimizing a function over the
+/// unit hypercube.
+///
+/// The method is driven from the outside: the caller evaluates the function
+/// at `NelderMead::point` and reports the result back with
+/// `NelderMead::tell`, which moves the method to the next point. This lets
+/// the evaluation happen asynchronously, e.g. in the interpreter thread.
+///
+/// All points are clamped to the unit hypercube.
+#[derive(Debug)]
+pub struct NelderMead {
+    initial_points: Vec<Vec<f32>>,
+    simplex: Vec<Vertex>,
+    centroid: Vec<f32>,
+    step: Step,
+    point: Vec<f32>,
+}
+
+impl NelderMead {
+    /// Creates the method starting at the initial point, with the initial
+    /// simplex of the given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initial point has zero dimensions.
+    pub fn new(initial_point: Vec<f32>, initial_step: f32) -> Self {
+        assert!(!initial_point.is_empty(), "Point must not be empty");
+
+        let initial_point = clamp_point(initial_point);
+        let mut initial_points = Vec::with_capacity(initial_point.len() + 1);
+        initial_points.push(initial_point.clone());
+        for i in 0..initial_point.len() {
+            let mut point = initial_point.clone();
+            if point[i] + initial_step <= 1.0 {
+                point[i] += initial_step;
+            } else {
+                point[i] -= initial_step;
+            }
+
+            initial_points.push(clamp_point(point));
+        }
+
+        Self {
+            simplex: Vec::with_capacity(initial_points.len()),
+            centroid: Vec::new(),
+            step: Step::Initializing,
+            point: initial_point,
+            initial_points,
+        }
+    }
+
+    /// The point at which the function should be evaluated next.
+    pub fn point(&self) -> &[f32] {
+        &self.point
+    }
+
+    /// Reports the value of the function at the current point and moves to
+    /// the next point. NaN values are treated as infinite.
+    pub fn tell(&mut self, score: f32) {
+        let score = if score.is_nan() { f32::INFINITY } else { score };
+        let vertex = Vertex {
+            point: mem::take(&mut self.point),
+            score,
+        };
+        let worst_index = self.initial_points.len() - 1;
+
+        match mem::replace(&mut self.step, Step::Initializing) {
+            Step::Initializing => {
+                self.simplex.push(vertex);
+                if self.simplex.len() < self.initial_points.len() {
+                    self.point = self.initial_points[self.simplex.len()].clone();
+                } else {
+                    self.begin_iteration();
+                }
+            }
+            Step::Reflecting => {
+                if vertex.score < self.simplex[0].score {
+                    self.point = self.along_centroid(&vertex.point, GAMMA);
+                    self.step = Step::Expanding { reflected: vertex };
+                } else if vertex.score < self.simplex[worst_index - 1].score {
+                    self.simplex[worst_index] = vertex;
+                    self.begin_iteration();
+                } else if vertex.score < self.simplex[worst_index].score {
+                    self.point = self.along_centroid(&vertex.point, RHO);
+                    self.step = Step::Contracting {
+                        reflected: vertex,
+                        outside: true,
+                    };
+                } else {
+                    self.point = self.along_centroid(&self.simplex[worst_index].point, RHO);
+                    self.step = Step::Contracting {
+                        reflected: vertex,
+                        outside: false,
+                    };
+                }
+            }
+            Step::Expanding { reflected } => {
+                self.simplex[worst_index] = if vertex.score < reflected.score {
+                    vertex
+                } else {
+                    reflected
+                };
+                self.begin_iteration();
+            }
+            Step::Contracting { reflected, outside } => {
+                let accept = if outside {
+                    vertex.score <= reflected.score
+                } else {
+                    vertex.score < self.simplex[worst_index].score
+                };
+
+                if accept {
+                    self.simplex[worst_index] = vertex;
+                    self.begin_iteration();
+                } else {
+                    self.point = self.shrunk(1);
+                    self.step = Step::Shrinking { index: 1 };
+                }
+            }
+            Step::Shrinking { index } => {
+                self.simplex[index] = vertex;
+                if index < worst_index {
+                    self.point = self.shrunk(index + 1);
+                    self.step = Step::Shrinking { index: index + 1 };
+                } else {
+                    self.begin_iteration();
+                }
+            }
+        }
+    }
+
+    fn begin_iteration(&mut self) {
+        self.simplex
+            .sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(Ordering::Equal));
+
+        let worst_index = self.simplex.len() - 1;
+        let dimension = self.simplex[0].point.len();
+
+        self.centroid = vec![0.0; dimension];
+        for vertex in &self.simplex[..worst_index] {
+            for (c, x) in self.centroid.iter_mut().zip(&vertex.point) {
+                *c += x / worst_index as f32;
+            }
+        }
+
+        self.point = self.along_centroid(&self.simplex[worst_index].point, -ALPHA);
+        self.step = Step::Reflecting;
+    }
+
+    /// Returns the point `centroid + t * (point - centroid)`.
+    fn along_centroid(&self, point: &[f32], t: f32) -> Vec<f32> {
+        clamp_point(
+            self.centroid
+                .iter()
+                .zip(point)
+                .map(|(c, x)| c + t * (x - c))
+                .collect(),
+        )
+    }
+
+    /// Returns the simplex vertex at the index moved towards the best vertex.
+    fn shrunk(&self, index: usize) -> Vec<f32> {
+        clamp_point(
+            self.simplex[0]
+                .point
+                .iter()
+                .zip(&self.simplex[index].point)
+                .map(|(best, x)| best + SIGMA * (x - best))
+                .collect(),
+        )
+    }
+}
+
+fn clamp_point(mut point: Vec<f32>) -> Vec<f32> {
+    for x in &mut point {
+        *x = x.max(0.0).min(1.0);
+    }
+
+    point
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minimize<F>(initial_point: Vec<f32>, evaluations: usize, f: F) -> (Vec<f32>, f32)
+    where
+        F: Fn(&[f32]) -> f32,
+    {
+        let mut nelder_mead = NelderMead::new(initial_point, INITIAL_STEP);
+        let mut best = (Vec::new(), f32::INFINITY);
+
+        for _ in 0..evaluations {
+            let score = f(nelder_mead.point());
+            if score < best.1 {
+                best = (nelder_mead.point().to_vec(), score);
+            }
+
+            nelder_mead.tell(score);
+        }
+
+        best
+    }
+
+    #[test]
+    fn test_nelder_mead_finds_minimum_of_quadratic() {
+        let (point, score) = minimize(vec![0.9, 0.1], 100, |p| {
+            (p[0] - 0.3).powi(2) + (p[1] - 0.7).powi(2)
+        });
+
+        assert!(score < 0.0001, "Score {} must be close to zero", score);
+        assert!((point[0] - 0.3).abs() < 0.01);
+        assert!((point[1] - 0.7).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_nelder_mead_stays_within_unit_hypercube() {
+        let mut nelder_mead = NelderMead::new(vec![0.5, 0.5, 0.5], INITIAL_STEP);
+
+        for _ in 0..100 {
+            let point = nelder_mead.point().to_vec();
+            assert!(point.iter().all(|x| *x >= 0.0 && *x <= 1.0));
+
+            // The minimum lies outside, in the direction of negative x
+            nelder_mead.tell(point[0] + point[1] - point[2]);
+        }
+    }
+
+    #[test]
+    fn test_objective_score_prefers_lower_regardless_of_goal() {
+        let small = Measurements {
+            volume: 1.0,
+            surface_area: 6.0,
+        };
+        let large = Measurements {
+            volume: 8.0,
+            surface_area: 24.0,
+        };
+
+        let mut objective = Objective {
+            measure: Measure::Volume,
+            goal: Goal::Minimize,
+            target_volume: None,
+        };
+        assert!(objective.score(&small) < objective.score(&large));
+
+        objective.goal = Goal::Maximize;
+        assert!(objective.score(&small) > objective.score(&large));
+    }
+
+    #[test]
+    fn test_objective_score_penalizes_deviation_from_target_volume() {
+        let objective = Objective {
+            measure: Measure::SurfaceArea,
+            goal: Goal::Minimize,
+            target_volume: Some(8.0),
+        };
+
+        let on_target = Measurements {
+            volume: 8.0,
+            surface_area: 24.0,
+        };
+        let off_target = Measurements {
+            volume: 1.0,
+            surface_area: 6.0,
+        };
+
+        assert_eq!(objective.score(&on_target), 24.0);
+        assert!(objective.score(&off_target) > objective.score(&on_target));
+    }
+
+    #[test]
+    fn test_objective_score_of_degenerate_ratio_is_infinite() {
+        let objective = Objective {
+            measure: Measure::SurfaceAreaToVolume,
+            goal: Goal::Maximize,
+            target_volume: None,
+        };
+        let flat = Measurements {
+            volume: 0.0,
+            surface_area: 2.0,
+        };
+
+        assert_eq!(objective.score(&flat), f32::INFINITY);
+    }
+}
@@ -0,0 +1,236 @@
+use std::f32;
+
+use nalgebra::{Matrix4, Point3, Vector3};
+
+use crate::convert::cast_usize;
+
+/// One of the six faces of the cube rendered around the panorama position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CubeFace {
+    Right,
+    Left,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::Right,
+        CubeFace::Left,
+        CubeFace::Front,
+        CubeFace::Back,
+        CubeFace::Up,
+        CubeFace::Down,
+    ];
+
+    /// Returns the direction the face looks in and the up direction of its
+    /// image, in the frame of the panorama: x to the right, y forward and z
+    /// up.
+    fn directions(self) -> (Vector3<f32>, Vector3<f32>) {
+        match self {
+            CubeFace::Right => (Vector3::x(), Vector3::z()),
+            CubeFace::Left => (-Vector3::x(), Vector3::z()),
+            CubeFace::Front => (Vector3::y(), Vector3::z()),
+            CubeFace::Back => (-Vector3::y(), Vector3::z()),
+            CubeFace::Up => (Vector3::z(), -Vector3::y()),
+            CubeFace::Down => (-Vector3::z(), Vector3::y()),
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            CubeFace::Right => 0,
+            CubeFace::Left => 1,
+            CubeFace::Front => 2,
+            CubeFace::Back => 3,
+            CubeFace::Up => 4,
+            CubeFace::Down => 5,
+        }
+    }
+}
+
+/// A 360 degree panorama, stitched from images of the six faces of a cube
+/// around the panorama position into an equirectangular image. The image is
+/// twice as wide as it is high and its center looks forward.
+pub struct Panorama {
+    position: Point3<f32>,
+    forward: Vector3<f32>,
+    width: u32,
+    face_images: [Option<Vec<u8>>; 6],
+}
+
+impl Panorama {
+    /// Creates the panorama taken from the position and looking in the
+    /// horizontal part of the direction. If the direction is vertical, the
+    /// panorama looks along the Y axis.
+    pub fn new(position: Point3<f32>, direction: &Vector3<f32>, width: u32) -> Self {
+        let forward = Vector3::new(direction.x, direction.y, 0.0)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+
+        Self {
+            position,
+            forward,
+            width: u32::max(2, width),
+            face_images: Default::default(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.width / 2
+    }
+
+    /// Returns the side length of the face images in pixels. A quarter of the
+    /// panorama width makes the faces about as detailed as the panorama at
+    /// the horizon.
+    pub fn face_size(&self) -> u32 {
+        u32::max(1, self.width / 4)
+    }
+
+    /// Returns the view matrix to render the face with. The face must be
+    /// rendered with a square projection with a 90 degree field of view.
+    pub fn face_view_matrix(&self, face: CubeFace) -> Matrix4<f32> {
+        let (direction, up) = face.directions();
+        let direction_world = self.to_world(&direction);
+        let up_world = self.to_world(&up);
+
+        Matrix4::look_at_rh(
+            &self.position,
+            &(self.position + direction_world),
+            &up_world,
+        )
+    }
+
+    /// Stores the RGBA image of the face, with unpadded rows of
+    /// `Panorama::face_size` pixels.
+    pub fn set_face_image(&mut self, face: CubeFace, rgba: Vec<u8>) {
+        self.face_images[face.index()] = Some(rgba);
+    }
+
+    /// Returns the RGBA equirectangular image, once the images of all faces
+    /// are stored.
+    pub fn stitch(&self) -> Option<Vec<u8>> {
+        if self.face_images.iter().any(Option::is_none) {
+            return None;
+        }
+
+        let width = self.width();
+        let height = self.height();
+        let face_size = self.face_size();
+
+        let mut rgba = Vec::with_capacity(cast_usize(width) * cast_usize(height) * 4);
+        for y in 0..height {
+            let latitude =
+                f32::consts::FRAC_PI_2 - (y as f32 + 0.5) / height as f32 * f32::consts::PI;
+            for x in 0..width {
+                let longitude =
+                    (x as f32 + 0.5) / width as f32 * 2.0 * f32::consts::PI - f32::consts::PI;
+                let direction = Vector3::new(
+                    latitude.cos() * longitude.sin(),
+                    latitude.cos() * longitude.cos(),
+                    latitude.sin(),
+                );
+
+                let (face, face_x, face_y) = project_to_face(&direction, face_size);
+                let face_image = self.face_images[face.index()]
+                    .as_ref()
+                    .expect("All face images must be stored");
+                let offset = (cast_usize(face_y) * cast_usize(face_size) + cast_usize(face_x)) * 4;
+                rgba.extend_from_slice(&face_image[offset..offset + 4]);
+            }
+        }
+
+        Some(rgba)
+    }
+
+    fn to_world(&self, vector: &Vector3<f32>) -> Vector3<f32> {
+        let right = self.forward.cross(&Vector3::z());
+        right * vector.x + self.forward * vector.y + Vector3::z() * vector.z
+    }
+}
+
+/// Finds the face the direction in the panorama frame points to, and the
+/// pixel of the face image it hits.
+fn project_to_face(direction: &Vector3<f32>, face_size: u32) -> (CubeFace, u32, u32) {
+    let (face, face_direction, face_up) = CubeFace::ALL
+        .iter()
+        .map(|face| {
+            let (face_direction, face_up) = face.directions();
+            (*face, face_direction, face_up)
+        })
+        .max_by(|(_, a, _), (_, b, _)| {
+            direction
+                .dot(a)
+                .partial_cmp(&direction.dot(b))
+                .expect("Directions must not be NaN")
+        })
+        .expect("Cube must have faces");
+
+    // Same axes as the view matrix of the face, with the 90 degree field of
+    // view mapping the face to [-1, 1] in both directions
+    let face_right = face_direction.cross(&face_up);
+    let depth = direction.dot(&face_direction);
+    let u = direction.dot(&face_right) / depth;
+    let v = direction.dot(&face_up) / depth;
+
+    let size = face_size as f32;
+    let max = face_size - 1;
+    let face_x = u32::min(max, ((u + 1.0) / 2.0 * size).max(0.0) as u32);
+    let face_y = u32::min(max, ((1.0 - v) / 2.0 * size).max(0.0) as u32);
+
+    (face, face_x, face_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panorama_face_view_matrix_looks_at_face() {
+        let panorama = Panorama::new(
+            Point3::new(1.0, 2.0, 3.0),
+            &Vector3::new(0.0, -2.0, -1.0),
+            8,
+        );
+
+        let front = panorama
+            .face_view_matrix(CubeFace::Front)
+            .transform_point(&Point3::new(1.0, 1.0, 3.0));
+        approx::assert_relative_eq!(front, Point3::new(0.0, 0.0, -1.0), epsilon = 0.0001);
+
+        // Looking to -Y, the right hand side is -X
+        let right = panorama
+            .face_view_matrix(CubeFace::Right)
+            .transform_point(&Point3::new(0.0, 2.0, 3.0));
+        approx::assert_relative_eq!(right, Point3::new(0.0, 0.0, -1.0), epsilon = 0.0001);
+    }
+
+    #[test]
+    fn test_panorama_stitch_samples_faces() {
+        let mut panorama = Panorama::new(Point3::origin(), &Vector3::y(), 16);
+        assert_eq!(panorama.height(), 8);
+        assert_eq!(panorama.face_size(), 4);
+
+        let face_pixel_count = cast_usize(panorama.face_size() * panorama.face_size());
+        for (index, face) in CubeFace::ALL.iter().enumerate() {
+            assert_eq!(panorama.stitch(), None);
+            panorama.set_face_image(*face, vec![index as u8; face_pixel_count * 4]);
+        }
+
+        let rgba = panorama.stitch().expect("All faces are stored");
+        let pixel = |x: usize, y: usize| rgba[(y * 16 + x) * 4];
+
+        assert_eq!(pixel(8, 4), CubeFace::Front.index() as u8);
+        assert_eq!(pixel(12, 4), CubeFace::Right.index() as u8);
+        assert_eq!(pixel(0, 4), CubeFace::Back.index() as u8);
+        assert_eq!(pixel(4, 4), CubeFace::Left.index() as u8);
+        assert_eq!(pixel(8, 0), CubeFace::Up.index() as u8);
+        assert_eq!(pixel(8, 7), CubeFace::Down.index() as u8);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::mem;
+
+use crate::interpreter::ast::{Expr, LitExpr};
+use crate::interpreter::FuncIdent;
+use crate::preferences::{self, ConfigFileError};
+
+const PARAM_DEFAULTS_FILE_NAME: &str = "param_defaults.ron";
+
+/// How many recently used files are remembered for each file path parameter.
+const RECENT_PATHS_CAPACITY: usize = 8;
+
+/// The values the user last set to operation parameters. They are used
+/// instead of the built-in defaults when inserting a new operation, so that
+/// e.g. voxel sizes and tolerances don't have to be set up again for each
+/// statement.
+///
+/// Only numeric and boolean parameters are remembered. Strings often contain
+/// paths and are left at their built-in defaults. Instead, the recently used
+/// files of file path parameters are offered for selection.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ParamDefaults {
+    values: BTreeMap<FuncIdent, BTreeMap<usize, LitExpr>>,
+    recent_paths: BTreeMap<FuncIdent, BTreeMap<usize, Vec<String>>>,
+}
+
+impl ParamDefaults {
+    /// Remembers the value as the default of the param of the func.
+    pub fn remember(&mut self, func_ident: FuncIdent, param_index: usize, lit: &LitExpr) {
+        match lit {
+            LitExpr::Boolean(_)
+            | LitExpr::Int(_)
+            | LitExpr::Uint(_)
+            | LitExpr::Float(_)
+            | LitExpr::Float2(_)
+            | LitExpr::Float3(_) => {
+                self.values
+                    .entry(func_ident)
+                    .or_default()
+                    .insert(param_index, lit.clone());
+            }
+            LitExpr::Nil | LitExpr::String(_) => (),
+        }
+    }
+
+    /// Remembers the path as the most recently used file of the param of the
+    /// func.
+    pub fn remember_recent_path(&mut self, func_ident: FuncIdent, param_index: usize, path: &str) {
+        if path.is_empty() {
+            return;
+        }
+
+        let recent_paths = self
+            .recent_paths
+            .entry(func_ident)
+            .or_default()
+            .entry(param_index)
+            .or_default();
+
+        recent_paths.retain(|recent_path| recent_path != path);
+        recent_paths.insert(0, path.to_string());
+        recent_paths.truncate(RECENT_PATHS_CAPACITY);
+    }
+
+    /// Returns the recently used files of the param of the func, the most
+    /// recent first.
+    pub fn recent_paths(&self, func_ident: FuncIdent, param_index: usize) -> &[String] {
+        self.recent_paths
+            .get(&func_ident)
+            .and_then(|func_recent_paths| func_recent_paths.get(&param_index))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Replaces the literal arguments of a new call of the func with the
+    /// remembered values. Arguments whose remembered value has a different
+    /// type, e.g. because the func changed since, are kept.
+    pub fn apply(&self, func_ident: FuncIdent, args: &mut [Expr]) {
+        if let Some(func_values) = self.values.get(&func_ident) {
+            for (param_index, lit) in func_values {
+                if let Some(Expr::Lit(arg)) = args.get_mut(*param_index) {
+                    if mem::discriminant(arg) == mem::discriminant(lit) {
+                        *arg = lit.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Loads the remembered parameter values from the user configuration
+/// directory. Returns empty defaults if none were saved yet.
+pub fn load() -> Result<ParamDefaults, ConfigFileError> {
+    preferences::read_config_file(PARAM_DEFAULTS_FILE_NAME)
+}
+
+/// Saves the remembered parameter values to the user configuration directory.
+pub fn save(param_defaults: &ParamDefaults) -> Result<(), ConfigFileError> {
+    preferences::write_config_file(PARAM_DEFAULTS_FILE_NAME, param_defaults)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::ast::VarExpr;
+    use crate::interpreter::VarIdent;
+
+    use super::*;
+
+    #[test]
+    fn test_param_defaults_apply_remembered_values() {
+        let func_ident = FuncIdent(0);
+        let mut param_defaults = ParamDefaults::default();
+        param_defaults.remember(func_ident, 1, &LitExpr::Float(0.25));
+        param_defaults.remember(func_ident, 2, &LitExpr::Boolean(true));
+
+        let mut args = vec![
+            Expr::Var(VarExpr::new(VarIdent(0))),
+            Expr::Lit(LitExpr::Float(1.0)),
+            Expr::Lit(LitExpr::Boolean(false)),
+        ];
+        param_defaults.apply(func_ident, &mut args);
+
+        assert_eq!(
+            args,
+            vec![
+                Expr::Var(VarExpr::new(VarIdent(0))),
+                Expr::Lit(LitExpr::Float(0.25)),
+                Expr::Lit(LitExpr::Boolean(true)),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_param_defaults_apply_skips_mismatched_types_and_strings() {
+        let func_ident = FuncIdent(0);
+        let mut param_defaults = ParamDefaults::default();
+        param_defaults.remember(func_ident, 0, &LitExpr::Uint(3));
+        param_defaults.remember(func_ident, 1, &LitExpr::String(String::from("/tmp")));
+        param_defaults.remember(FuncIdent(1), 2, &LitExpr::Int(7));
+
+        let mut args = vec![
+            Expr::Lit(LitExpr::Float(1.0)),
+            Expr::Lit(LitExpr::String(String::new())),
+            Expr::Lit(LitExpr::Int(0)),
+        ];
+        let expected_args = args.clone();
+        param_defaults.apply(func_ident, &mut args);
+
+        assert_eq!(args, expected_args);
+    }
+
+    #[test]
+    fn test_param_defaults_remember_recent_path() {
+        let func_ident = FuncIdent(0);
+        let mut param_defaults = ParamDefaults::default();
+
+        for index in 0..RECENT_PATHS_CAPACITY + 2 {
+            param_defaults.remember_recent_path(func_ident, 0, &format!("{}.obj", index));
+        }
+        param_defaults.remember_recent_path(func_ident, 0, "5.obj");
+        param_defaults.remember_recent_path(func_ident, 0, "");
+
+        let recent_paths = param_defaults.recent_paths(func_ident, 0);
+        assert_eq!(recent_paths.len(), RECENT_PATHS_CAPACITY);
+        assert_eq!(recent_paths[0], "5.obj");
+        assert_eq!(recent_paths[1], "9.obj");
+        assert_eq!(
+            recent_paths.iter().filter(|path| *path == "5.obj").count(),
+            1
+        );
+        assert!(!recent_paths.iter().any(|path| path == "0.obj"));
+
+        assert!(param_defaults.recent_paths(func_ident, 1).is_empty());
+    }
+}
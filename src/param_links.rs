@@ -0,0 +1,214 @@
+//! Numeric parameters driven by other parameters.
+//!
+//! A link makes a parameter follow the value of another parameter, optionally
+//! scaled and offset, e.g. to keep the voxel sizes of two Voxelize operations
+//! equal. Each parameter is driven by at most one other parameter, so the
+//! links form chains, which must not be cyclic.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::expression;
+use crate::interpreter::ast::{LitExpr, VarIdent};
+use crate::interpreter::ParamRefinement;
+
+/// A parameter of the statement declaring the variable. Unlike statement
+/// indices, variables stay the same when statements are added or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ParamId {
+    pub var_ident: VarIdent,
+    pub arg_index: usize,
+}
+
+/// Drives a parameter by the source parameter. Each component of the driven
+/// value is the corresponding component of the source value multiplied by
+/// the scale, plus the offset. A source with a single component drives all
+/// components.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ParamLink {
+    pub source: ParamId,
+    pub scale: f32,
+    pub offset: f32,
+}
+
+impl ParamLink {
+    /// Creates a link keeping the driven parameter equal to the source.
+    pub fn new(source: ParamId) -> Self {
+        Self {
+            source,
+            scale: 1.0,
+            offset: 0.0,
+        }
+    }
+
+    /// Returns the value of the driven parameter for the value of the
+    /// source, clamped to the range of the driven parameter. Values beyond
+    /// either bound are clamped to it, even if the type of the parameter
+    /// can't hold them, e.g. negative values of unsigned parameters. Returns
+    /// `None` if the source can not drive the parameter.
+    pub fn driven_lit(
+        &self,
+        source_lit: &LitExpr,
+        refinement: &ParamRefinement,
+    ) -> Option<LitExpr> {
+        let source_components = expression::lit_components(source_lit)?;
+        let component_count = expression::component_count(refinement)?;
+        if !components_compatible(source_components.len(), component_count) {
+            return None;
+        }
+
+        let components: Vec<_> = (0..component_count)
+            .map(|index| {
+                let source_component = if source_components.len() == 1 {
+                    source_components[0]
+                } else {
+                    source_components[index]
+                };
+                let component = source_component * f64::from(self.scale) + f64::from(self.offset);
+                clamp_to_ty(component, refinement)
+            })
+            .collect();
+
+        expression::lit_from_components(&components, refinement).ok()
+    }
+}
+
+/// Clamps the component to the numbers the type of the parameter can hold,
+/// so that it is then clamped to the range of the parameter like any other.
+fn clamp_to_ty(component: f64, refinement: &ParamRefinement) -> f64 {
+    let (min, max) = match refinement {
+        ParamRefinement::Int(_) => (f64::from(i32::MIN), f64::from(i32::MAX)),
+        ParamRefinement::Uint(_) => (0.0, f64::from(u32::MAX)),
+        _ => (f64::from(f32::MIN), f64::from(f32::MAX)),
+    };
+
+    component.max(min).min(max)
+}
+
+/// Whether a source with the number of components can drive a parameter
+/// with the number of components.
+pub fn components_compatible(source_component_count: usize, component_count: usize) -> bool {
+    source_component_count == component_count || source_component_count == 1
+}
+
+/// Whether driving the parameter by the source would close a cycle of the
+/// links, which are keyed by the driven parameters.
+pub fn creates_cycle(links: &HashMap<ParamId, ParamLink>, param: ParamId, source: ParamId) -> bool {
+    // The existing links are acyclic and each parameter has at most one
+    // source, so following the sources ends either at the parameter or at
+    // an undriven one.
+    let mut current = source;
+    while current != param {
+        match links.get(&current) {
+            Some(link) => current = link.source,
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{
+        Float2ParamRefinement, Float3ParamRefinement, FloatParamRefinement, IntParamRefinement,
+        UintParamRefinement,
+    };
+
+    use super::*;
+
+    fn param(var: u64, arg_index: usize) -> ParamId {
+        ParamId {
+            var_ident: VarIdent(var),
+            arg_index,
+        }
+    }
+
+    #[test]
+    fn test_param_links_driven_lit_scales_and_offsets() {
+        let link = ParamLink {
+            source: param(0, 1),
+            scale: 2.0,
+            offset: 0.5,
+        };
+        let float3_refinement = ParamRefinement::Float3(Float3ParamRefinement::default());
+
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float3([1.0, 2.0, 0.25]), &float3_refinement),
+            Some(LitExpr::Float3([2.5, 4.5, 1.0])),
+        );
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float(1.0), &float3_refinement),
+            Some(LitExpr::Float3([2.5, 2.5, 2.5])),
+        );
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float2([1.0, 2.0]), &float3_refinement),
+            None,
+        );
+        assert_eq!(
+            link.driven_lit(&LitExpr::Boolean(true), &float3_refinement),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_param_links_driven_lit_rounds_and_clamps() {
+        let link = ParamLink::new(param(0, 0));
+        let uint_refinement = ParamRefinement::Uint(UintParamRefinement {
+            default_value: Some(1),
+            min_value: Some(1),
+            max_value: Some(8),
+        });
+
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float(2.6), &uint_refinement),
+            Some(LitExpr::Uint(3)),
+        );
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float(20.0), &uint_refinement),
+            Some(LitExpr::Uint(8)),
+        );
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float(-2.0), &uint_refinement),
+            Some(LitExpr::Uint(1)),
+        );
+
+        let int_refinement = ParamRefinement::Int(IntParamRefinement {
+            default_value: Some(0),
+            min_value: Some(-4),
+            max_value: Some(4),
+        });
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float(-1e12), &int_refinement),
+            Some(LitExpr::Int(-4)),
+        );
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float(1e12), &int_refinement),
+            Some(LitExpr::Int(4)),
+        );
+
+        let float_refinement = ParamRefinement::Float(FloatParamRefinement::default());
+        let float2_refinement = ParamRefinement::Float2(Float2ParamRefinement::default());
+        assert_eq!(
+            link.driven_lit(&LitExpr::Float2([1.0, 2.0]), &float_refinement),
+            None,
+        );
+        assert_eq!(
+            link.driven_lit(&LitExpr::Uint(3), &float2_refinement),
+            Some(LitExpr::Float2([3.0, 3.0])),
+        );
+    }
+
+    #[test]
+    fn test_param_links_creates_cycle() {
+        let mut links = HashMap::new();
+        links.insert(param(1, 0), ParamLink::new(param(0, 0)));
+        links.insert(param(2, 0), ParamLink::new(param(1, 0)));
+
+        assert!(creates_cycle(&links, param(0, 0), param(2, 0)));
+        assert!(creates_cycle(&links, param(0, 0), param(0, 0)));
+        assert!(!creates_cycle(&links, param(3, 0), param(2, 0)));
+        assert!(!creates_cycle(&links, param(0, 0), param(2, 1)));
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::annotation::Annotation;
+use crate::panorama::CubeFace;
+use crate::remote::RemoteRequest;
+use crate::variants::VariantId;
+
+/// The recipient of a thumbnail rendered to an offscreen render target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailTarget {
+    Variant(VariantId),
+    /// A sample of the sweep with the given generation. The generation is
+    /// used to discard thumbnails of sweeps that no longer exist.
+    SweepSample(u64, usize),
+}
+
+/// What to do with the image data of an offscreen render target, once
+/// reading it finishes.
+pub enum PendingRead {
+    /// A screenshot saved to the screenshot directory, optionally with the
+    /// annotation panel composited over it.
+    Screenshot(Option<Annotation>),
+    Thumbnail(ThumbnailTarget),
+    /// An image of a shadow study written to the path.
+    ShadowStudy(PathBuf),
+    /// A matcap baked to the path and then used by the viewport.
+    Matcap(PathBuf),
+    /// A rendered camera view written to the path.
+    ViewRender(PathBuf),
+    /// A screenshot requested by a remote client, replied to once written.
+    RemoteScreenshot(RemoteRequest),
+    /// A face of the panorama being captured.
+    PanoramaFace(CubeFace),
+}
+
+/// Offscreen render target reads requested from the renderer, keyed by the
+/// render target id, waiting for their image data.
+#[derive(Default)]
+pub struct PendingReads {
+    reads: HashMap<u64, PendingRead>,
+}
+
+impl PendingReads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, render_target_id: u64, read: PendingRead) {
+        self.reads.insert(render_target_id, read);
+    }
+
+    /// Removes the read of the render target, once its data is ready or
+    /// reading it failed.
+    pub fn remove(&mut self, render_target_id: u64) -> Option<PendingRead> {
+        self.reads.remove(&render_target_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reads.is_empty()
+    }
+
+    /// Returns whether any of the reads still waiting matches the predicate,
+    /// e.g. whether the last image of an export is still being read.
+    pub fn any<F>(&self, predicate: F) -> bool
+    where
+        F: Fn(&PendingRead) -> bool,
+    {
+        self.reads.values().any(predicate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_reads_remove_returns_inserted_read_once() {
+        let mut pending_reads = PendingReads::new();
+        pending_reads.insert(1, PendingRead::PanoramaFace(CubeFace::ALL[0]));
+        pending_reads.insert(2, PendingRead::Screenshot(None));
+
+        assert!(matches!(
+            pending_reads.remove(1),
+            Some(PendingRead::PanoramaFace(_)),
+        ));
+        assert!(pending_reads.remove(1).is_none());
+        assert!(!pending_reads.is_empty());
+    }
+
+    #[test]
+    fn test_pending_reads_any_matches_waiting_reads() {
+        let mut pending_reads = PendingReads::new();
+        pending_reads.insert(1, PendingRead::ViewRender(PathBuf::from("view.png")));
+
+        assert!(pending_reads.any(|read| matches!(read, PendingRead::ViewRender(_))));
+        assert!(!pending_reads.any(|read| matches!(read, PendingRead::ShadowStudy(_))));
+
+        pending_reads.remove(1);
+
+        assert!(!pending_reads.any(|read| matches!(read, PendingRead::ViewRender(_))));
+        assert!(pending_reads.is_empty());
+    }
+}
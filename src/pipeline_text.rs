@@ -0,0 +1,536 @@
+//! Human-readable text format of the operation pipeline.
+//!
+//! The pipeline is written one statement per line, each declaring a
+//! variable by calling an operation by its name:
+//!
+//! ```text
+//! # H.U.R.B.A.N. selector pipeline
+//! v0 = Import OBJ("meshes/tower.obj", true, true, false, 1)
+//! v1 = Voxelize(v0, [0.5, 0.5, 0.5], 0, true, false, true, false)
+//! ```
+//!
+//! Arguments are literals or references to variables declared on previous
+//! lines. Numbers are read as the type of their parameter. Lines starting
+//! with `#` are comments. Operations without a known name are written by
+//! their identifier, e.g. `#8000`.
+//!
+//! The text contains exactly the statements of the project file, so that
+//! formatting and parsing them again gives the same statements. This makes
+//! the text suitable for diffing, code review and sharing in chat.
+
+use std::collections::{BTreeMap, HashSet};
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::interpreter::ast::{CallExpr, Expr, LitExpr, Stmt, VarDeclStmt, VarExpr};
+use crate::interpreter::{Func, FuncIdent, ParamRefinement, VarIdent};
+
+const HEADER: &str = "# H.U.R.B.A.N. selector pipeline";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineTextError {
+    Syntax(usize, String),
+    UnknownFunc(usize, String),
+    ArgCount(usize, &'static str, usize),
+    InvalidArg(usize, &'static str),
+    UndeclaredVar(usize, u64),
+    DuplicateVar(usize, u64),
+}
+
+impl error::Error for PipelineTextError {}
+
+impl fmt::Display for PipelineTextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipelineTextError::Syntax(line, message) => write!(f, "Line {}: {}", line, message),
+            PipelineTextError::UnknownFunc(line, name) => {
+                write!(f, "Line {}: Unknown operation \"{}\"", line, name)
+            }
+            PipelineTextError::ArgCount(line, func_name, arg_count) => write!(
+                f,
+                "Line {}: {} can not take {} arguments",
+                line, func_name, arg_count,
+            ),
+            PipelineTextError::InvalidArg(line, param_name) => write!(
+                f,
+                "Line {}: Invalid value for parameter \"{}\"",
+                line, param_name,
+            ),
+            PipelineTextError::UndeclaredVar(line, var) => write!(
+                f,
+                "Line {}: Variable v{} is not declared on a previous line",
+                line, var,
+            ),
+            PipelineTextError::DuplicateVar(line, var) => {
+                write!(f, "Line {}: Variable v{} is already declared", line, var)
+            }
+        }
+    }
+}
+
+/// Formats the statements as pipeline text.
+pub fn format(stmts: &[Stmt], function_table: &BTreeMap<FuncIdent, Box<dyn Func>>) -> String {
+    let mut text = String::from(HEADER);
+    text.push('\n');
+
+    for stmt in stmts {
+        let Stmt::VarDecl(var_decl) = stmt;
+        let call_expr = var_decl.init_expr();
+
+        write!(text, "v{} = ", var_decl.ident().0).expect("Writing to string must not fail");
+        match function_table.get(&call_expr.ident()) {
+            Some(func) => text.push_str(func.info().name),
+            None => {
+                write!(text, "#{}", call_expr.ident().0).expect("Writing to string must not fail")
+            }
+        }
+
+        text.push('(');
+        for (arg_index, arg) in call_expr.args().iter().enumerate() {
+            if arg_index > 0 {
+                text.push_str(", ");
+            }
+            format_arg(&mut text, arg);
+        }
+        text.push_str(")\n");
+    }
+
+    text
+}
+
+fn format_arg(text: &mut String, arg: &Expr) {
+    match arg {
+        Expr::Var(var) => write!(text, "v{}", var.ident().0),
+        Expr::Lit(LitExpr::Nil) => write!(text, "nil"),
+        Expr::Lit(LitExpr::Boolean(boolean)) => write!(text, "{}", boolean),
+        Expr::Lit(LitExpr::Int(int)) => write!(text, "{}", int),
+        Expr::Lit(LitExpr::Uint(uint)) => write!(text, "{}", uint),
+        Expr::Lit(LitExpr::Float(float)) => write!(text, "{}", float),
+        Expr::Lit(LitExpr::Float2(float2)) => write!(text, "[{}, {}]", float2[0], float2[1]),
+        Expr::Lit(LitExpr::Float3(float3)) => {
+            write!(text, "[{}, {}, {}]", float3[0], float3[1], float3[2])
+        }
+        Expr::Lit(LitExpr::String(string)) => {
+            text.push('"');
+            for c in string.chars() {
+                match c {
+                    '"' => text.push_str("\\\""),
+                    '\\' => text.push_str("\\\\"),
+                    '\n' => text.push_str("\\n"),
+                    '\r' => text.push_str("\\r"),
+                    '\t' => text.push_str("\\t"),
+                    c => text.push(c),
+                }
+            }
+            text.push('"');
+            Ok(())
+        }
+    }
+    .expect("Writing to string must not fail");
+}
+
+/// Parses pipeline text into statements. Operations are looked up in the
+/// function table and the arguments are checked against their parameters.
+pub fn parse(
+    text: &str,
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+) -> Result<Vec<Stmt>, PipelineTextError> {
+    let mut stmts = Vec::new();
+    let mut declared_vars = HashSet::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let stmt = parse_stmt(line_number, line, function_table, &declared_vars)?;
+        let Stmt::VarDecl(ref var_decl) = stmt;
+        if !declared_vars.insert(var_decl.ident()) {
+            return Err(PipelineTextError::DuplicateVar(
+                line_number,
+                var_decl.ident().0,
+            ));
+        }
+
+        stmts.push(stmt);
+    }
+
+    Ok(stmts)
+}
+
+fn parse_stmt(
+    line_number: usize,
+    line: &str,
+    function_table: &BTreeMap<FuncIdent, Box<dyn Func>>,
+    declared_vars: &HashSet<VarIdent>,
+) -> Result<Stmt, PipelineTextError> {
+    let syntax_error = |message: &str| PipelineTextError::Syntax(line_number, message.to_string());
+
+    let equals_index = line
+        .find('=')
+        .ok_or_else(|| syntax_error("Expected a variable declaration, e.g. \"v0 = ...\""))?;
+    let var_ident = parse_var(line[..equals_index].trim())
+        .ok_or_else(|| syntax_error("Expected a variable, e.g. \"v0\""))?;
+
+    let call = line[equals_index + 1..].trim();
+    let open_paren_index = call
+        .find('(')
+        .ok_or_else(|| syntax_error("Expected arguments in parentheses"))?;
+    let args_text = call[open_paren_index + 1..]
+        .strip_suffix(')')
+        .ok_or_else(|| syntax_error("Expected the arguments to end with \")\""))?;
+
+    let func_name = call[..open_paren_index].trim();
+    let func_ident = match func_name.strip_prefix('#') {
+        Some(func_id) => func_id
+            .parse()
+            .ok()
+            .map(FuncIdent)
+            .filter(|func_ident| function_table.contains_key(func_ident)),
+        None => function_table
+            .iter()
+            .find(|(_, func)| func.info().name == func_name)
+            .map(|(func_ident, _)| *func_ident),
+    }
+    .ok_or_else(|| PipelineTextError::UnknownFunc(line_number, func_name.to_string()))?;
+    let func = &function_table[&func_ident];
+
+    let arg_tokens = tokenize_args(line_number, args_text)?;
+    if !func.accepts_arg_count(arg_tokens.len()) {
+        return Err(PipelineTextError::ArgCount(
+            line_number,
+            func.info().name,
+            arg_tokens.len(),
+        ));
+    }
+
+    let mut args = Vec::with_capacity(arg_tokens.len());
+    for (arg_index, arg_token) in arg_tokens.iter().enumerate() {
+        let param_index = func
+            .param_index_for_arg(arg_index, arg_tokens.len())
+            .expect("Argument count must have been checked");
+        let param = &func.param_info()[param_index];
+
+        if let ArgToken::Var(var_ident) = arg_token {
+            if !declared_vars.contains(var_ident) {
+                return Err(PipelineTextError::UndeclaredVar(line_number, var_ident.0));
+            }
+        }
+
+        let arg = arg_from_token(arg_token, &param.refinement)
+            .ok_or(PipelineTextError::InvalidArg(line_number, param.name))?;
+        args.push(arg);
+    }
+
+    Ok(Stmt::VarDecl(VarDeclStmt::new(
+        var_ident,
+        CallExpr::new(func_ident, args),
+    )))
+}
+
+fn parse_var(text: &str) -> Option<VarIdent> {
+    text.strip_prefix('v')
+        .filter(|digits| digits.chars().all(|c| c.is_ascii_digit()))
+        .and_then(|digits| digits.parse().ok())
+        .map(VarIdent)
+}
+
+/// An argument as written in the text, before its type is known.
+#[derive(Debug, Clone, PartialEq)]
+enum ArgToken {
+    Word(String),
+    Array(Vec<String>),
+    String(String),
+    Var(VarIdent),
+}
+
+fn tokenize_args(line_number: usize, args_text: &str) -> Result<Vec<ArgToken>, PipelineTextError> {
+    let syntax_error = |message: &str| PipelineTextError::Syntax(line_number, message.to_string());
+
+    let mut tokens = Vec::new();
+    let mut chars = args_text.char_indices().peekable();
+
+    skip_whitespace(&mut chars);
+    if chars.peek().is_none() {
+        return Ok(tokens);
+    }
+
+    loop {
+        skip_whitespace(&mut chars);
+        let token = match chars.peek() {
+            Some((_, '"')) => {
+                chars.next();
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, '"')) => string.push('"'),
+                            Some((_, '\\')) => string.push('\\'),
+                            Some((_, 'n')) => string.push('\n'),
+                            Some((_, 'r')) => string.push('\r'),
+                            Some((_, 't')) => string.push('\t'),
+                            _ => return Err(syntax_error("Invalid escape sequence in string")),
+                        },
+                        Some((_, c)) => string.push(c),
+                        None => return Err(syntax_error("Unterminated string")),
+                    }
+                }
+                ArgToken::String(string)
+            }
+            Some((start, '[')) => {
+                let start = *start;
+                let end = args_text[start..]
+                    .find(']')
+                    .map(|offset| start + offset)
+                    .ok_or_else(|| syntax_error("Unterminated array"))?;
+                while chars.peek().map_or(false, |(index, _)| *index <= end) {
+                    chars.next();
+                }
+                ArgToken::Array(
+                    args_text[start + 1..end]
+                        .split(',')
+                        .map(|element| element.trim().to_string())
+                        .collect(),
+                )
+            }
+            Some((start, _)) => {
+                let start = *start;
+                let mut end = args_text.len();
+                while let Some((index, c)) = chars.peek() {
+                    if *c == ',' || c.is_whitespace() {
+                        end = *index;
+                        break;
+                    }
+                    chars.next();
+                }
+
+                let word = &args_text[start..end];
+                match parse_var(word) {
+                    Some(var_ident) => ArgToken::Var(var_ident),
+                    None => ArgToken::Word(word.to_string()),
+                }
+            }
+            None => return Err(syntax_error("Expected an argument")),
+        };
+        tokens.push(token);
+
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some((_, ',')) => (),
+            Some(_) => return Err(syntax_error("Expected \",\" between arguments")),
+            None => break,
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn skip_whitespace(chars: &mut Peekable<CharIndices>) {
+    while chars.peek().map_or(false, |(_, c)| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn arg_from_token(token: &ArgToken, refinement: &ParamRefinement) -> Option<Expr> {
+    let lit = match (token, refinement) {
+        (ArgToken::Var(var_ident), _) => return Some(Expr::Var(VarExpr::new(*var_ident))),
+        (ArgToken::Word(word), _) if word == "nil" => LitExpr::Nil,
+        (ArgToken::Word(word), ParamRefinement::Boolean(_)) => LitExpr::Boolean(word.parse().ok()?),
+        (ArgToken::Word(word), ParamRefinement::Int(_)) => LitExpr::Int(word.parse().ok()?),
+        (ArgToken::Word(word), ParamRefinement::Uint(_)) => LitExpr::Uint(word.parse().ok()?),
+        (ArgToken::Word(word), ParamRefinement::Float(_)) => LitExpr::Float(word.parse().ok()?),
+        (ArgToken::Array(elements), ParamRefinement::Float2(_)) if elements.len() == 2 => {
+            LitExpr::Float2([elements[0].parse().ok()?, elements[1].parse().ok()?])
+        }
+        (ArgToken::Array(elements), ParamRefinement::Float3(_)) if elements.len() == 3 => {
+            LitExpr::Float3([
+                elements[0].parse().ok()?,
+                elements[1].parse().ok()?,
+                elements[2].parse().ok()?,
+            ])
+        }
+        (ArgToken::String(string), ParamRefinement::String(_))
+        | (ArgToken::String(string), ParamRefinement::FilePath(_)) => {
+            LitExpr::String(string.clone())
+        }
+        _ => return None,
+    };
+
+    Some(Expr::Lit(lit))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter_funcs::{
+        self, FUNC_ID_CREATE_BOX, FUNC_ID_IMPORT_OBJ_JOIN, FUNC_ID_JOIN_MESHES,
+        FUNC_ID_LAPLACIAN_SMOOTHING, FUNC_ID_WELD,
+    };
+
+    use super::*;
+
+    fn var_decl(var_ident: u64, func_ident: FuncIdent, args: Vec<Expr>) -> Stmt {
+        Stmt::VarDecl(VarDeclStmt::new(
+            VarIdent(var_ident),
+            CallExpr::new(func_ident, args),
+        ))
+    }
+
+    fn var(var_ident: u64) -> Expr {
+        Expr::Var(VarExpr::new(VarIdent(var_ident)))
+    }
+
+    fn lit(lit: LitExpr) -> Expr {
+        Expr::Lit(lit)
+    }
+
+    #[test]
+    fn test_pipeline_text_round_trip() {
+        let function_table = interpreter_funcs::create_function_table();
+        let stmts = vec![
+            var_decl(
+                0,
+                FUNC_ID_CREATE_BOX,
+                vec![
+                    lit(LitExpr::Float3([0.0, -1.5, 1e-7])),
+                    lit(LitExpr::Float3([0.0, 0.0, 45.0])),
+                    lit(LitExpr::Float3([1.0, 2.0, 0.1])),
+                    lit(LitExpr::Boolean(false)),
+                ],
+            ),
+            var_decl(
+                3,
+                FUNC_ID_IMPORT_OBJ_JOIN,
+                vec![
+                    lit(LitExpr::String(String::from(
+                        "C:\\models\\\"tower\", #2\n.obj",
+                    ))),
+                    lit(LitExpr::Boolean(true)),
+                    lit(LitExpr::Boolean(true)),
+                    lit(LitExpr::Boolean(false)),
+                    lit(LitExpr::Float(0.001)),
+                ],
+            ),
+            var_decl(
+                4,
+                FUNC_ID_LAPLACIAN_SMOOTHING,
+                vec![
+                    var(0),
+                    lit(LitExpr::Uint(3)),
+                    lit(LitExpr::Uint(1)),
+                    lit(LitExpr::Boolean(false)),
+                ],
+            ),
+            var_decl(
+                5,
+                FUNC_ID_WELD,
+                vec![var(3), lit(LitExpr::Float(0.33333334)), lit(LitExpr::Nil)],
+            ),
+        ];
+
+        let text = format(&stmts, &function_table);
+        let parsed_stmts = parse(&text, &function_table).unwrap();
+
+        assert_eq!(parsed_stmts, stmts);
+    }
+
+    #[test]
+    fn test_pipeline_text_format_is_readable() {
+        let function_table = interpreter_funcs::create_function_table();
+        let stmts = vec![
+            var_decl(
+                0,
+                FUNC_ID_CREATE_BOX,
+                vec![
+                    lit(LitExpr::Float3([0.0, 0.0, 0.0])),
+                    lit(LitExpr::Float3([0.0, 0.0, 0.0])),
+                    lit(LitExpr::Float3([1.0, 1.0, 1.5])),
+                    lit(LitExpr::Boolean(false)),
+                ],
+            ),
+            var_decl(1, FuncIdent(999_999), vec![var(0)]),
+        ];
+
+        let text = format(&stmts, &function_table);
+
+        assert_eq!(
+            text,
+            "# H.U.R.B.A.N. selector pipeline\n\
+             v0 = Create Box([0, 0, 0], [0, 0, 0], [1, 1, 1.5], false)\n\
+             v1 = #999999(v0)\n",
+        );
+    }
+
+    #[test]
+    fn test_pipeline_text_parse_variadic_args_and_comments() {
+        let function_table = interpreter_funcs::create_function_table();
+        let box_args = "[0, 0, 0], [0, 0, 0], [1, 1, 1], false";
+        let join_meshes = function_table[&FUNC_ID_JOIN_MESHES].info().name;
+        let text = format!(
+            "# Two boxes\n\nv0 = Create Box({})\nv1 = Create Box({})\n  v2 = {}(v0, v1, false)  \n",
+            box_args, box_args, join_meshes,
+        );
+
+        let stmts = parse(&text, &function_table).unwrap();
+
+        assert_eq!(stmts.len(), 3);
+        let Stmt::VarDecl(var_decl) = &stmts[2];
+        assert_eq!(var_decl.ident(), VarIdent(2));
+        assert_eq!(var_decl.init_expr().ident(), FUNC_ID_JOIN_MESHES);
+        assert_eq!(
+            var_decl.init_expr().args(),
+            &[var(0), var(1), lit(LitExpr::Boolean(false))],
+        );
+    }
+
+    #[test]
+    fn test_pipeline_text_parse_errors() {
+        let function_table = interpreter_funcs::create_function_table();
+        let box_args = "[0, 0, 0], [0, 0, 0], [1, 1, 1], false";
+
+        assert_eq!(
+            parse("v0 = Make Box()", &function_table),
+            Err(PipelineTextError::UnknownFunc(1, String::from("Make Box"))),
+        );
+        assert_eq!(
+            parse("v0 = Create Box([0, 0, 0])", &function_table),
+            Err(PipelineTextError::ArgCount(1, "Create Box", 1)),
+        );
+        assert_eq!(
+            parse(
+                "v0 = Create Box([0, 0], [0, 0, 0], [1, 1, 1], false)",
+                &function_table
+            ),
+            Err(PipelineTextError::InvalidArg(1, "Center")),
+        );
+        assert_eq!(
+            parse(
+                &format!(
+                    "# Comment\nv0 = Create Box({})\nv1 = Weld(v7, 0.1, true)",
+                    box_args
+                ),
+                &function_table,
+            ),
+            Err(PipelineTextError::UndeclaredVar(3, 7)),
+        );
+        assert_eq!(
+            parse(
+                &format!(
+                    "v0 = Create Box({})\nv0 = Create Box({})",
+                    box_args, box_args
+                ),
+                &function_table,
+            ),
+            Err(PipelineTextError::DuplicateVar(2, 0)),
+        );
+        assert!(matches!(
+            parse("v0 = Import OBJ(\"tower.obj, true)", &function_table),
+            Err(PipelineTextError::Syntax(1, _)),
+        ));
+    }
+}
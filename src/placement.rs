@@ -0,0 +1,170 @@
+use nalgebra::{Matrix4, Point3, Rotation3, Vector3};
+
+use crate::importer::{EndlessCache, Importer};
+use crate::interpreter::{ast, VarIdent};
+use crate::mesh::{tools, Mesh};
+
+/// Index of the "Path" param of the Import OBJ func.
+const IMPORT_OBJ_PARAM_INDEX_PATH: usize = 0;
+
+/// Index of the "Mesh" param of the Transform func.
+const TRANSFORM_PARAM_INDEX_MESH: usize = 0;
+
+/// Index of the "Move" param of the Transform func.
+const TRANSFORM_PARAM_INDEX_MOVE: usize = 1;
+
+/// Index of the "Rotate" param of the Transform func.
+const TRANSFORM_PARAM_INDEX_ROTATE: usize = 2;
+
+/// Loads the OBJ file as a single mesh, placed the same way the Import OBJ
+/// operation places it by default: centered on the origin and standing on the
+/// ground.
+pub fn load_library_mesh(path: &str) -> Result<Mesh, String> {
+    let mut importer = Importer::new(EndlessCache::default());
+    let models = importer.import_obj(path).map_err(|err| err.to_string())?;
+    if models.is_empty() {
+        return Err(String::from("No mesh geometry contained in OBJ"));
+    }
+
+    let mesh = tools::join_multiple_meshes(models.iter().map(|model| &model.mesh));
+
+    let bbox = mesh.bounding_box();
+    let translation = Matrix4::new_translation(
+        &(Point3::origin() - bbox.center() + Vector3::new(0.0, 0.0, bbox.diagonal().z / 2.0)),
+    );
+
+    Ok(transformed_mesh(&mesh, &translation))
+}
+
+/// Returns where the ray hits the ground plane, if it does.
+pub fn ground_point(origin: &Point3<f32>, direction: &Vector3<f32>) -> Option<Point3<f32>> {
+    if direction.z.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let distance = -origin.z / direction.z;
+    if distance > 0.0 {
+        Some(origin + direction * distance)
+    } else {
+        None
+    }
+}
+
+/// Returns the library mesh rotated around the vertical axis by the angle in
+/// degrees and moved to the position, as the placed Transform will produce
+/// it.
+pub fn placed_mesh(library_mesh: &Mesh, position: &Point3<f32>, rotation: f32) -> Mesh {
+    let transform = Matrix4::new_translation(&position.coords)
+        * Matrix4::from(Rotation3::from_axis_angle(
+            &Vector3::z_axis(),
+            rotation.to_radians(),
+        ));
+
+    transformed_mesh(library_mesh, &transform)
+}
+
+/// Returns the args of a new Import OBJ of the library mesh file. The
+/// remaining args are kept at their defaults.
+pub fn import_args(mut default_args: Vec<ast::Expr>, path: &str) -> Vec<ast::Expr> {
+    default_args[IMPORT_OBJ_PARAM_INDEX_PATH] =
+        ast::Expr::Lit(ast::LitExpr::String(path.to_string()));
+
+    default_args
+}
+
+/// Returns the args of a new Transform, moving the imported mesh of the
+/// variable to the position and rotating it around the vertical axis. The
+/// remaining args are kept at their defaults.
+pub fn transform_args(
+    mut default_args: Vec<ast::Expr>,
+    var_ident: VarIdent,
+    position: &Point3<f32>,
+    rotation: f32,
+) -> Vec<ast::Expr> {
+    default_args[TRANSFORM_PARAM_INDEX_MESH] = ast::Expr::Var(ast::VarExpr::new(var_ident));
+    default_args[TRANSFORM_PARAM_INDEX_MOVE] =
+        ast::Expr::Lit(ast::LitExpr::Float3([position.x, position.y, position.z]));
+    default_args[TRANSFORM_PARAM_INDEX_ROTATE] =
+        ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, rotation]));
+
+    default_args
+}
+
+fn transformed_mesh(mesh: &Mesh, transform: &Matrix4<f32>) -> Mesh {
+    Mesh::from_faces_with_vertices_and_normals(
+        mesh.faces().iter().copied(),
+        mesh.vertices().iter().map(|v| transform.transform_point(v)),
+        mesh.normals().iter().map(|n| transform.transform_vector(n)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mesh::TriangleFace;
+
+    use super::*;
+
+    #[test]
+    fn test_ground_point() {
+        let point = ground_point(&Point3::new(1.0, 2.0, 10.0), &Vector3::new(0.0, 0.6, -0.8))
+            .expect("Must hit the ground");
+        approx::assert_relative_eq!(point, Point3::new(1.0, 9.5, 0.0));
+
+        assert!(ground_point(&Point3::new(0.0, 0.0, 10.0), &Vector3::new(0.0, 0.0, 1.0)).is_none());
+        assert!(ground_point(&Point3::new(0.0, 0.0, 10.0), &Vector3::new(1.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_placed_mesh_rotates_around_vertical_axis_and_moves() {
+        let library_mesh = Mesh::from_triangle_faces_with_vertices_and_normals(
+            vec![TriangleFace::new(0, 1, 2, 0, 0, 0)],
+            vec![
+                Point3::new(1.0, 0.0, 2.0),
+                Point3::new(0.0, 1.0, 0.0),
+                Point3::new(0.0, 0.0, 0.0),
+            ],
+            vec![Vector3::new(1.0, 0.0, 0.0)],
+        );
+
+        let mesh = placed_mesh(&library_mesh, &Point3::new(5.0, 5.0, 1.0), 90.0);
+
+        approx::assert_relative_eq!(
+            mesh.vertices()[0],
+            Point3::new(5.0, 6.0, 3.0),
+            epsilon = 0.0001,
+        );
+        approx::assert_relative_eq!(
+            mesh.normals()[0],
+            Vector3::new(0.0, 1.0, 0.0),
+            epsilon = 0.0001,
+        );
+    }
+
+    #[test]
+    fn test_transform_args_move_and_rotate_the_var() {
+        let default_args = vec![
+            ast::Expr::Lit(ast::LitExpr::Nil),
+            ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, 0.0])),
+            ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, 0.0])),
+            ast::Expr::Lit(ast::LitExpr::Float3([1.0, 1.0, 1.0])),
+            ast::Expr::Lit(ast::LitExpr::Boolean(true)),
+            ast::Expr::Lit(ast::LitExpr::Boolean(false)),
+        ];
+
+        let args = transform_args(default_args, VarIdent(3), &Point3::new(1.0, 2.0, 3.0), 45.0);
+
+        assert_eq!(args[0], ast::Expr::Var(ast::VarExpr::new(VarIdent(3))));
+        assert_eq!(
+            args[1],
+            ast::Expr::Lit(ast::LitExpr::Float3([1.0, 2.0, 3.0]))
+        );
+        assert_eq!(
+            args[2],
+            ast::Expr::Lit(ast::LitExpr::Float3([0.0, 0.0, 45.0]))
+        );
+        assert_eq!(
+            args[3],
+            ast::Expr::Lit(ast::LitExpr::Float3([1.0, 1.0, 1.0]))
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::frame_limiter::FrameLimiter;
+use crate::ground_plane::GroundPlaneOptions;
+use crate::interpreter_server::TimeoutPreferences;
+use crate::logger::LoggingPreferences;
+use crate::number_format::NumberFormat;
+use crate::project::ProjectFilePreferences;
+use crate::renderer::{GpuAdapterInfo, ShadowQuality};
+use crate::session::AutorunPreferences;
+use crate::snapping::Snapping;
+use crate::statistics::StatisticsConsent;
+
+/// Directory in the user configuration directory, where the configuration
+/// files are kept.
+const CONFIG_DIRECTORY: &str = "H.U.R.B.A.N. selector";
+
+const PREFERENCES_FILE_NAME: &str = "preferences.ron";
+
+/// User preferences. Unlike the project, they are kept per user and apply
+/// to all projects.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// How numbers are displayed in and typed into the UI.
+    pub number_format: NumberFormat,
+    pub snapping: Snapping,
+    pub autorun: AutorunPreferences,
+    pub timeout: TimeoutPreferences,
+    pub frame_limiter: FrameLimiter,
+    pub ground_plane: GroundPlaneOptions,
+    pub statistics: StatisticsConsent,
+    pub logging: LoggingPreferences,
+    pub project_files: ProjectFilePreferences,
+    /// GPU adapter explicitly chosen by the user. Takes effect on the next
+    /// start.
+    pub gpu_adapter: Option<GpuAdapterInfo>,
+    /// Image replacing the built-in matcap of shaded surfaces.
+    pub matcap: Option<PathBuf>,
+    pub shadow_quality: ShadowQuality,
+}
+
+#[derive(Debug)]
+pub enum ConfigFileError {
+    NoConfigDirectory,
+    SerializeError(ron::error::Error),
+    IoError(io::Error),
+}
+
+impl error::Error for ConfigFileError {}
+
+impl fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigFileError::NoConfigDirectory => {
+                write!(f, "No configuration directory to store the file in.")
+            }
+            ConfigFileError::SerializeError(err) => write!(
+                f,
+                "An error occurred while serializing or deserializing configuration file: {}",
+                err
+            ),
+            ConfigFileError::IoError(err) => write!(
+                f,
+                "An error occurred while accessing configuration file: {}",
+                err
+            ),
+        }
+    }
+}
+
+impl From<ron::error::Error> for ConfigFileError {
+    fn from(err: ron::error::Error) -> Self {
+        ConfigFileError::SerializeError(err)
+    }
+}
+
+impl From<io::Error> for ConfigFileError {
+    fn from(err: io::Error) -> Self {
+        ConfigFileError::IoError(err)
+    }
+}
+
+/// Loads preferences from the user configuration directory. Returns default
+/// preferences if they were never saved.
+pub fn load() -> Result<Preferences, ConfigFileError> {
+    read_config_file(PREFERENCES_FILE_NAME)
+}
+
+/// Saves preferences to the user configuration directory.
+pub fn save(preferences: &Preferences) -> Result<(), ConfigFileError> {
+    write_config_file(PREFERENCES_FILE_NAME, preferences)
+}
+
+/// Returns the path of the file in the user configuration directory.
+pub fn config_file_path(file_name: &str) -> Result<PathBuf, ConfigFileError> {
+    match dirs::config_dir() {
+        Some(config_dir) => Ok(config_dir.join(CONFIG_DIRECTORY).join(file_name)),
+        None => Err(ConfigFileError::NoConfigDirectory),
+    }
+}
+
+/// Reads the RON file from the user configuration directory. Returns the
+/// default value if the file doesn't exist yet.
+pub fn read_config_file<T>(file_name: &str) -> Result<T, ConfigFileError>
+where
+    T: DeserializeOwned + Default,
+{
+    let path = config_file_path(file_name)?;
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let value = ron::de::from_reader(BufReader::new(file))?;
+
+    Ok(value)
+}
+
+/// Writes the value as a RON file to the user configuration directory.
+pub fn write_config_file<T: Serialize>(file_name: &str, value: &T) -> Result<(), ConfigFileError> {
+    let path = config_file_path(file_name)?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let mut output: Vec<u8> = Vec::new();
+
+    let pretty_config = ron::ser::PrettyConfig::new()
+        .with_indentor("  ".to_string())
+        .with_new_line("\n".to_string());
+    let mut serializer = ron::ser::Serializer::new(&mut output, Some(pretty_config), true)?;
+
+    value.serialize(&mut serializer)?;
+
+    fs::write(path, &output)?;
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! Measurements of how long the stages of drawing a frame take.
+//!
+//! The stages are measured on the CPU. The GPU work is not timed directly,
+//! because the renderer's wgpu version has no timestamp queries, but waiting
+//! for the swap chain grows when the GPU can't keep up.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::convert::cast_u32;
+
+/// How many of the last frames the statistics are computed from.
+const HISTORY_LEN: usize = 120;
+
+const STAGE_COUNT: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Polling the session for the results of the interpreter, excluding the
+    /// mesh uploads.
+    SessionPoll,
+    /// Uploading meshes to the GPU.
+    MeshUpload,
+    /// Building the UI and handling its results.
+    Ui,
+    /// Waiting for the swap chain to provide the next frame.
+    SwapChainWait,
+    /// Recording the scene and shadow passes of the viewport.
+    ScenePasses,
+    /// Recording the UI pass.
+    UiPass,
+    /// Submitting the recorded passes to the GPU.
+    Submit,
+}
+
+impl Stage {
+    pub const ALL: [Stage; STAGE_COUNT] = [
+        Stage::SessionPoll,
+        Stage::MeshUpload,
+        Stage::Ui,
+        Stage::SwapChainWait,
+        Stage::ScenePasses,
+        Stage::UiPass,
+        Stage::Submit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SessionPoll => "Session poll",
+            Self::MeshUpload => "Mesh upload",
+            Self::Ui => "UI",
+            Self::SwapChainWait => "Swap chain wait",
+            Self::ScenePasses => "Scene and shadow passes",
+            Self::UiPass => "UI pass",
+            Self::Submit => "Submit",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Self::SessionPoll => 0,
+            Self::MeshUpload => 1,
+            Self::Ui => 2,
+            Self::SwapChainWait => 3,
+            Self::ScenePasses => 4,
+            Self::UiPass => 5,
+            Self::Submit => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct FrameTimes {
+    frame: Duration,
+    stages: [Duration; STAGE_COUNT],
+}
+
+/// Keeps the stage durations of the last frames.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    frames: VecDeque<FrameTimes>,
+    current: FrameTimes,
+    frame_start: Option<Instant>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            frames: VecDeque::with_capacity(HISTORY_LEN),
+            current: FrameTimes::default(),
+            frame_start: None,
+        }
+    }
+
+    /// Finishes the previous frame, if any, and starts measuring a new one.
+    pub fn begin_frame(&mut self, time: Instant) {
+        if let Some(frame_start) = self.frame_start {
+            self.current.frame = time.saturating_duration_since(frame_start);
+            if self.frames.len() == HISTORY_LEN {
+                self.frames.pop_front();
+            }
+            self.frames.push_back(self.current);
+        }
+
+        self.current = FrameTimes::default();
+        self.frame_start = Some(time);
+    }
+
+    /// Adds the duration to the stage of the current frame. Stages may be
+    /// recorded multiple times per frame.
+    pub fn record(&mut self, stage: Stage, duration: Duration) {
+        self.current.stages[stage.index()] += duration;
+    }
+
+    /// Returns the average duration of the stage over the last frames.
+    pub fn stage_average(&self, stage: Stage) -> Duration {
+        self.average(|frame_times| frame_times.stages[stage.index()])
+    }
+
+    /// Returns the longest duration of the stage over the last frames.
+    pub fn stage_max(&self, stage: Stage) -> Duration {
+        self.max(|frame_times| frame_times.stages[stage.index()])
+    }
+
+    /// Returns the average time between the starts of the last frames.
+    pub fn frame_average(&self) -> Duration {
+        self.average(|frame_times| frame_times.frame)
+    }
+
+    /// Returns the longest time between the starts of the last frames.
+    pub fn frame_max(&self) -> Duration {
+        self.max(|frame_times| frame_times.frame)
+    }
+
+    /// Returns the times between the starts of the last frames in
+    /// milliseconds, from the oldest to the newest.
+    pub fn frame_times_ms(&self) -> Vec<f32> {
+        self.frames
+            .iter()
+            .map(|frame_times| frame_times.frame.as_micros() as f32 / 1000.0)
+            .collect()
+    }
+
+    fn average<F: Fn(&FrameTimes) -> Duration>(&self, duration: F) -> Duration {
+        if self.frames.is_empty() {
+            return Duration::default();
+        }
+
+        let sum: Duration = self.frames.iter().map(duration).sum();
+        sum / cast_u32(self.frames.len())
+    }
+
+    fn max<F: Fn(&FrameTimes) -> Duration>(&self, duration: F) -> Duration {
+        self.frames.iter().map(duration).max().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiler_stage_statistics() {
+        let start = Instant::now();
+        let mut profiler = Profiler::new();
+
+        profiler.begin_frame(start);
+        profiler.record(Stage::Ui, Duration::from_millis(4));
+        profiler.record(Stage::Ui, Duration::from_millis(2));
+        profiler.begin_frame(start + Duration::from_millis(16));
+        profiler.record(Stage::Ui, Duration::from_millis(2));
+        profiler.record(Stage::Submit, Duration::from_millis(1));
+        profiler.begin_frame(start + Duration::from_millis(48));
+
+        assert_eq!(profiler.stage_average(Stage::Ui), Duration::from_millis(4));
+        assert_eq!(profiler.stage_max(Stage::Ui), Duration::from_millis(6));
+        assert_eq!(
+            profiler.stage_average(Stage::Submit),
+            Duration::from_micros(500),
+        );
+        assert_eq!(profiler.stage_max(Stage::MeshUpload), Duration::default());
+        assert_eq!(profiler.frame_average(), Duration::from_millis(24));
+        assert_eq!(profiler.frame_max(), Duration::from_millis(32));
+        assert_eq!(profiler.frame_times_ms(), vec![16.0, 32.0]);
+    }
+
+    #[test]
+    fn test_profiler_keeps_last_frames() {
+        let start = Instant::now();
+        let mut profiler = Profiler::new();
+        assert_eq!(profiler.frame_average(), Duration::default());
+
+        for frame in 0..=HISTORY_LEN as u64 + 10 {
+            profiler.begin_frame(start + Duration::from_millis(frame * 10));
+        }
+
+        assert_eq!(profiler.frame_times_ms().len(), HISTORY_LEN);
+        assert_eq!(profiler.frame_average(), Duration::from_millis(10));
+    }
+}
@@ -7,7 +7,19 @@ use std::path::{Path, PathBuf};
 
 use serde::Serialize as _;
 
+use crate::bookmarks::CameraBookmark;
+use crate::camera::CameraView;
+use crate::clip_box::ClipBox;
+use crate::display_material::DisplayMaterial;
+use crate::examples::ExampleProject;
+use crate::geolocation::Geolocation;
 use crate::interpreter::ast;
+use crate::interpreter_funcs;
+use crate::layers::{Layer, LayerId};
+use crate::param_links::{ParamId, ParamLink};
+use crate::reference_images::ReferenceImage;
+use crate::units::Unit;
+use crate::ViewportDrawMode;
 
 pub const DEFAULT_NEW_FILENAME: &str = "new_project.hurban";
 
@@ -15,11 +27,31 @@ pub const EXTENSION: &str = "hurban";
 pub const EXTENSION_DESCRIPTION: &str = "H.U.R.B.A.N. selector project (.hurban)";
 pub const EXTENSION_FILTER: &[&str] = &["*.hurban"];
 
+/// Filter of the open dialog, accepting both project files and bundles.
+pub const OPEN_EXTENSION_FILTER: &[&str] = &["*.hurban", "*.hurbanz"];
+pub const OPEN_EXTENSION_DESCRIPTION: &str = "H.U.R.B.A.N. selector project (.hurban, .hurbanz)";
+
+/// Significant digits kept of the float arguments in git-friendly project
+/// files. Fewer than the precision of `f32`, so that values like
+/// `0.30000004` produced by dragging are written as `0.3`.
+const GIT_FRIENDLY_SIGNIFICANT_DIGITS: usize = 6;
+
+/// Project file settings kept in user preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ProjectFilePreferences {
+    /// Whether to save projects in a form that diffs cleanly under version
+    /// control. Float arguments of operations are rounded and the file ends
+    /// with a new line.
+    pub git_friendly: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum NextAction {
     Exit,
     NewProject,
     OpenProject,
+    OpenExample(&'static ExampleProject),
 }
 
 #[derive(Debug, Default)]
@@ -28,14 +60,25 @@ pub struct ProjectStatus {
     pub error: Option<ProjectError>,
     pub new_requested: bool,
     pub open_requested: bool,
+    pub save_requested: bool,
+    /// Project file dropped onto the window or given on the command line,
+    /// opened instead of asking for a file once the open request is handled.
+    pub open_requested_path: Option<PathBuf>,
     pub changed_since_last_save: bool,
     pub prevent_overwrite_status: Option<NextAction>,
+    /// Name of the built-in example the project was opened from. Examples
+    /// are read-only and the project is not an example anymore, once a copy
+    /// of it is saved.
+    pub example: Option<&'static str>,
+    /// Built-in example to open once the open request is handled.
+    pub example_requested: Option<&'static ExampleProject>,
 }
 
 impl ProjectStatus {
     pub fn save<P: AsRef<Path>>(&mut self, path: P) {
         self.path = Some(path.as_ref().to_path_buf());
         self.changed_since_last_save = false;
+        self.example = None;
     }
 }
 
@@ -82,32 +125,228 @@ impl From<io::Error> for ProjectError {
     }
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct Project {
     pub version: u32,
     pub stmts: Vec<ast::Stmt>,
+    /// Design variants saved alongside the project. Older project files
+    /// don't contain any.
+    #[serde(default)]
+    pub variants: Vec<ProjectVariant>,
+    /// Units the project geometry is modeled in. Older project files don't
+    /// declare any and are considered to be in meters.
+    #[serde(default)]
+    pub unit: Unit,
+    /// Location of the site and orientation of the scene towards the true
+    /// north. Older project files don't contain any.
+    #[serde(default)]
+    pub geolocation: Geolocation,
+    /// Images shown in the viewport for reference. Older project files don't
+    /// contain any.
+    #[serde(default)]
+    pub reference_images: Vec<ReferenceImage>,
+    /// Variables whose values are kept visible and exported, even if they
+    /// are used. Older project files don't contain any.
+    #[serde(default)]
+    pub pinned_vars: Vec<ast::VarIdent>,
+    /// Materials overriding how the values of variables are drawn in the
+    /// viewport. Older project files don't contain any.
+    #[serde(default)]
+    pub display_materials: Vec<(ast::VarIdent, DisplayMaterial)>,
+    /// Layers organizing the values in the viewport. Older project files
+    /// don't contain any.
+    #[serde(default)]
+    pub layers: Vec<(LayerId, Layer)>,
+    /// Assignment of variables to layers. Older project files don't contain
+    /// any.
+    #[serde(default)]
+    pub var_layers: Vec<(ast::VarIdent, LayerId)>,
+    /// Custom names of variables, used instead of the names of their
+    /// operations. Older project files don't contain any.
+    #[serde(default)]
+    pub var_names: Vec<(ast::VarIdent, String)>,
+    /// Custom texts of the viewport labels of variables. Older project files
+    /// don't contain any.
+    #[serde(default)]
+    pub value_labels: Vec<(ast::VarIdent, String)>,
+    /// Notes documenting the intent of statements, keyed by the variables
+    /// they declare. Older project files don't contain any.
+    #[serde(default)]
+    pub stmt_notes: Vec<(ast::VarIdent, String)>,
+    /// Notes documenting the intent of the project. Older project files
+    /// don't contain any.
+    #[serde(default)]
+    pub notes: String,
+    /// Named camera views. Older project files don't contain any.
+    #[serde(default)]
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    /// Variables declared by imports that are re-run whenever the imported
+    /// file changes. Older project files don't contain any.
+    #[serde(default)]
+    pub live_linked_vars: Vec<ast::VarIdent>,
+    /// Voxel size inherited by voxel operations linked to it. Older project
+    /// files don't contain any.
+    #[serde(default)]
+    pub voxel_size: Option<f32>,
+    /// Variables declared by voxel operations inheriting the project voxel
+    /// size. Older project files don't contain any.
+    #[serde(default)]
+    pub voxel_size_inheriting_vars: Vec<ast::VarIdent>,
+    /// Numeric parameters driven by other parameters, keyed by the driven
+    /// parameter. Older project files don't contain any.
+    #[serde(default)]
+    pub param_links: Vec<(ParamId, ParamLink)>,
+    /// How the viewport looked when the project was saved. Older project
+    /// files don't contain any and open in the current viewport.
+    #[serde(default)]
+    pub viewport: Option<ProjectViewport>,
+}
+
+impl Project {
+    /// Replaces the calls of retired operations in the statements and
+    /// variants by calls of their replacements.
+    pub fn upgrade_retired_funcs(&mut self) {
+        let aliases = interpreter_funcs::create_func_alias_table();
+        interpreter_funcs::upgrade_stmts(&aliases, &mut self.stmts);
+        for variant in &mut self.variants {
+            interpreter_funcs::upgrade_stmts(&aliases, &mut variant.stmts);
+        }
+    }
+
+    /// Rounds the float arguments of the statements and variants, the
+    /// project voxel size and the viewport to
+    /// `GIT_FRIENDLY_SIGNIFICANT_DIGITS`, so that float noise doesn't show up
+    /// in diffs.
+    fn round_floats(&mut self) {
+        let variant_stmts = self.variants.iter_mut().map(|variant| &mut variant.stmts);
+        for stmts in std::iter::once(&mut self.stmts).chain(variant_stmts) {
+            for stmt in stmts.iter_mut() {
+                let ast::Stmt::VarDecl(var_decl) = stmt;
+                let args = var_decl
+                    .init_expr()
+                    .args()
+                    .iter()
+                    .map(|arg| match arg {
+                        ast::Expr::Lit(ast::LitExpr::Float(float)) => {
+                            ast::Expr::Lit(ast::LitExpr::Float(round_significant(*float)))
+                        }
+                        ast::Expr::Lit(ast::LitExpr::Float2(float2)) => {
+                            ast::Expr::Lit(ast::LitExpr::Float2([
+                                round_significant(float2[0]),
+                                round_significant(float2[1]),
+                            ]))
+                        }
+                        ast::Expr::Lit(ast::LitExpr::Float3(float3)) => {
+                            ast::Expr::Lit(ast::LitExpr::Float3([
+                                round_significant(float3[0]),
+                                round_significant(float3[1]),
+                                round_significant(float3[2]),
+                            ]))
+                        }
+                        arg => arg.clone(),
+                    })
+                    .collect();
+
+                let init_expr = ast::CallExpr::new(var_decl.init_expr().ident(), args);
+                *var_decl = var_decl.clone_with_init_expr(init_expr);
+            }
+        }
+
+        self.voxel_size = self.voxel_size.map(round_significant);
+
+        if let Some(viewport) = &mut self.viewport {
+            let clip_box = &mut viewport.clip_box;
+            for value in clip_box.min.iter_mut().chain(clip_box.max.iter_mut()) {
+                *value = round_significant(*value);
+            }
+
+            let camera_view = &mut viewport.camera_view;
+            for value in camera_view.origin.iter_mut() {
+                *value = round_significant(*value);
+            }
+            camera_view.radius = round_significant(camera_view.radius);
+            camera_view.azimuthal_angle = round_significant(camera_view.azimuthal_angle);
+            camera_view.polar_angle = round_significant(camera_view.polar_angle);
+        }
+    }
+}
+
+/// The viewport settings stored in the project file, so that the project
+/// opens in the view it was left in. Layer visibility is stored with the
+/// layers.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProjectViewport {
+    pub draw_mode: ViewportDrawMode,
+    pub draw_used_values: bool,
+    pub labels: bool,
+    pub clip_box: ClipBox,
+    /// The camera view, which is also the view of the camera bookmark last
+    /// gone to, unless the camera moved since.
+    pub camera_view: CameraView,
+}
+
+/// Rounds the value to `GIT_FRIENDLY_SIGNIFICANT_DIGITS` significant digits.
+fn round_significant(value: f32) -> f32 {
+    if !value.is_finite() || value == 0.0 {
+        return value;
+    }
+
+    format!("{:.*e}", GIT_FRIENDLY_SIGNIFICANT_DIGITS - 1, value)
+        .parse()
+        .unwrap_or(value)
+}
+
+/// A named snapshot of the pipeline program, as stored in the project file.
+/// Thumbnails are not stored.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ProjectVariant {
+    pub name: String,
+    pub stmts: Vec<ast::Stmt>,
 }
 
 /// Saves project to given path. If this path does not contain valid project
-/// extension, it is automatically added.
+/// extension, it is automatically added. Git-friendly projects are saved
+/// with rounded float arguments and a final new line, so that they diff
+/// cleanly under version control.
 ///
 /// Returns `PathBuf` which can be different than original path if the project
 /// extension was added.
-pub fn save<P: AsRef<Path>>(path: P, project: Project) -> Result<PathBuf, ProjectError> {
-    let mut path_buf = path.as_ref().to_path_buf();
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    project: Project,
+    git_friendly: bool,
+) -> Result<PathBuf, ProjectError> {
+    let path_buf = path_with_extension(path.as_ref(), EXTENSION);
+    let output = serialize(project, git_friendly)?;
+
+    let mut file = File::create(path_buf.as_path())?;
+    file.write_all(&output)?;
+    file.flush()?;
+
+    Ok(path_buf)
+}
+
+/// Returns the path with the extension added, unless it already has it.
+pub fn path_with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path_buf = path.to_path_buf();
     match path_buf.extension() {
-        Some(extension) => {
-            let extension = extension.to_string_lossy().into_owned();
+        Some(current_extension) => {
+            let current_extension = current_extension.to_string_lossy().into_owned();
 
-            if extension != EXTENSION {
-                path_buf.set_extension(format!("{}.{}", extension, EXTENSION));
+            if current_extension != extension {
+                path_buf.set_extension(format!("{}.{}", current_extension, extension));
             }
         }
         None => {
-            path_buf.set_extension(EXTENSION);
+            path_buf.set_extension(extension);
         }
     }
 
+    path_buf
+}
+
+/// Serializes the project into the contents of a project file.
+pub fn serialize(mut project: Project, git_friendly: bool) -> Result<Vec<u8>, ProjectError> {
     let mut output: Vec<u8> = Vec::new();
 
     let pretty_config = ron::ser::PrettyConfig::new()
@@ -117,19 +356,134 @@ pub fn save<P: AsRef<Path>>(path: P, project: Project) -> Result<PathBuf, Projec
         .with_enumerate_arrays(false);
     let mut serializer = ron::ser::Serializer::new(&mut output, Some(pretty_config), true)?;
 
+    if git_friendly {
+        project.round_floats();
+    }
     project.serialize(&mut serializer)?;
+    if git_friendly && !output.ends_with(b"\n") {
+        output.push(b'\n');
+    }
 
-    let mut file = File::create(path_buf.as_path())?;
-    file.write_all(&output)?;
-    file.flush()?;
-
-    Ok(path_buf)
+    Ok(output)
 }
 
+/// Opens the project at the path. Retired operations used by the project are
+/// upgraded to their replacements.
 pub fn open<P: AsRef<Path>>(path: P) -> Result<Project, ProjectError> {
     let file = File::open(path)?;
     let buf_reader = BufReader::new(file);
-    let project = ron::de::from_reader(buf_reader)?;
+    let mut project: Project = ron::de::from_reader(buf_reader)?;
+    project.upgrade_retired_funcs();
 
     Ok(project)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{FuncIdent, VarIdent};
+
+    use super::*;
+
+    fn project(stmts: Vec<ast::Stmt>, variant_stmts: Vec<ast::Stmt>) -> Project {
+        Project {
+            version: 1,
+            stmts,
+            variants: vec![ProjectVariant {
+                name: String::from("Variant"),
+                stmts: variant_stmts,
+            }],
+            unit: Unit::default(),
+            geolocation: Geolocation::default(),
+            reference_images: Vec::new(),
+            pinned_vars: Vec::new(),
+            display_materials: Vec::new(),
+            layers: Vec::new(),
+            var_layers: Vec::new(),
+            var_names: Vec::new(),
+            value_labels: Vec::new(),
+            stmt_notes: Vec::new(),
+            notes: String::new(),
+            camera_bookmarks: Vec::new(),
+            live_linked_vars: Vec::new(),
+            voxel_size: Some(0.300_000_04),
+            voxel_size_inheriting_vars: Vec::new(),
+            param_links: Vec::new(),
+            viewport: None,
+        }
+    }
+
+    fn stmt(args: Vec<ast::LitExpr>) -> ast::Stmt {
+        ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+            VarIdent(0),
+            ast::CallExpr::new(FuncIdent(0), args.into_iter().map(ast::Expr::Lit).collect()),
+        ))
+    }
+
+    #[test]
+    fn test_project_round_significant() {
+        assert_eq!(round_significant(0.300_000_04), 0.3);
+        assert_eq!(round_significant(1234.5678), 1234.57);
+        assert_eq!(round_significant(-0.005), -0.005);
+        assert_eq!(round_significant(0.0), 0.0);
+        assert!(round_significant(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn test_project_round_floats_of_stmts_and_variants() {
+        let mut project = project(
+            vec![stmt(vec![
+                ast::LitExpr::Float(0.300_000_04),
+                ast::LitExpr::Uint(3),
+                ast::LitExpr::Float3([1.000_000_1, 2.0, 0.333_333_34]),
+            ])],
+            vec![stmt(vec![ast::LitExpr::Float2([0.100_000_01, 9.999_999])])],
+        );
+
+        project.round_floats();
+
+        assert_eq!(
+            project.stmts,
+            vec![stmt(vec![
+                ast::LitExpr::Float(0.3),
+                ast::LitExpr::Uint(3),
+                ast::LitExpr::Float3([1.0, 2.0, 0.333_333]),
+            ])],
+        );
+        assert_eq!(
+            project.variants[0].stmts,
+            vec![stmt(vec![ast::LitExpr::Float2([0.1, 10.0])])],
+        );
+        assert_eq!(project.voxel_size, Some(0.3));
+    }
+
+    #[test]
+    fn test_project_viewport_round_trip() {
+        let mut project = project(Vec::new(), Vec::new());
+        project.viewport = Some(ProjectViewport {
+            draw_mode: ViewportDrawMode::ShadedWireframeXray,
+            draw_used_values: false,
+            labels: true,
+            clip_box: ClipBox {
+                enabled: true,
+                min: [-1.000_000_1, 0.0, 0.0],
+                max: [1.0, 2.0, 3.0],
+            },
+            camera_view: CameraView {
+                origin: [0.0, 0.0, 0.0],
+                radius: 25.000_002,
+                azimuthal_angle: 4.712_389,
+                polar_angle: 1.047_197_6,
+            },
+        });
+
+        let output = serialize(project, true).unwrap();
+        let project: Project = ron::de::from_bytes(&output).unwrap();
+        let viewport = project.viewport.unwrap();
+
+        assert_eq!(viewport.draw_mode, ViewportDrawMode::ShadedWireframeXray);
+        assert!(!viewport.draw_used_values);
+        assert_eq!(viewport.clip_box.min, [-1.0, 0.0, 0.0]);
+        assert_eq!(viewport.camera_view.radius, 25.0);
+        assert_eq!(viewport.camera_view.polar_angle, 1.047_2);
+    }
+}
@@ -0,0 +1,434 @@
+//! Python bindings for the geometry kernel and the operation pipeline.
+//!
+//! Built as an extension module with the "python" feature enabled. The
+//! bindings expose meshes, calling individual operations and running the
+//! pipelines of project files with modified parameters, so that design
+//! studies can be scripted with the same geometry code as the application.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write as _};
+use std::sync::Arc;
+
+use nalgebra::Point3;
+use pyo3::exceptions::{PyIOError, PyIndexError, PyKeyError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::convert::cast_u32;
+use crate::exporter;
+use crate::interpreter::ast::{self, Expr, LitExpr, Stmt, VarIdent};
+use crate::interpreter::{
+    Func, FuncIdent, Interpreter, LogMessage, LogMessageLevel, MeshArrayValue, Ty, Value,
+};
+use crate::interpreter_funcs;
+use crate::mesh::{Face, Mesh, NormalStrategy};
+use crate::project;
+use crate::units::Unit;
+
+#[pymodule]
+fn hurban_selector(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PyMesh>()?;
+    module.add_class::<PyPipeline>()?;
+    module.add_function(wrap_pyfunction!(funcs, module)?)?;
+    module.add_function(wrap_pyfunction!(call_func, module)?)?;
+
+    Ok(())
+}
+
+/// A triangle mesh, shared with the operations without copying.
+#[pyclass(name = Mesh)]
+#[derive(Clone)]
+pub struct PyMesh {
+    mesh: Arc<Mesh>,
+}
+
+#[pymethods]
+impl PyMesh {
+    /// Creates a mesh from vertex positions and triangles given as triples
+    /// of vertex indices. Normals are computed for each face.
+    #[new]
+    fn new(vertices: Vec<(f32, f32, f32)>, faces: Vec<(u32, u32, u32)>) -> PyResult<Self> {
+        let vertex_count = cast_u32(vertices.len());
+        if let Some(face) = faces
+            .iter()
+            .find(|(v1, v2, v3)| *v1 >= vertex_count || *v2 >= vertex_count || *v3 >= vertex_count)
+        {
+            return Err(PyValueError::new_err(format!(
+                "Face {:?} refers to a vertex out of range of {} vertices",
+                face, vertex_count,
+            )));
+        }
+
+        let mesh = Mesh::from_triangle_faces_with_vertices_and_computed_normals(
+            faces,
+            vertices.into_iter().map(|(x, y, z)| Point3::new(x, y, z)),
+            NormalStrategy::Sharp,
+        );
+
+        Ok(Self {
+            mesh: Arc::new(mesh),
+        })
+    }
+
+    #[getter]
+    fn vertices(&self) -> Vec<(f32, f32, f32)> {
+        self.mesh
+            .vertices()
+            .iter()
+            .map(|v| (v.x, v.y, v.z))
+            .collect()
+    }
+
+    #[getter]
+    fn normals(&self) -> Vec<(f32, f32, f32)> {
+        self.mesh
+            .normals()
+            .iter()
+            .map(|n| (n.x, n.y, n.z))
+            .collect()
+    }
+
+    /// Triangles of the mesh as triples of vertex indices.
+    #[getter]
+    fn faces(&self) -> Vec<(u32, u32, u32)> {
+        self.mesh
+            .faces()
+            .iter()
+            .map(|face| match face {
+                Face::Triangle(f) => f.vertices,
+            })
+            .collect()
+    }
+
+    /// Returns the minimum and maximum corner of the axis aligned bounding
+    /// box of the mesh.
+    #[allow(clippy::type_complexity)]
+    fn bounding_box(&self) -> ((f32, f32, f32), (f32, f32, f32)) {
+        let bounding_box = self.mesh.bounding_box();
+        let minimum = bounding_box.minimum_point();
+        let maximum = bounding_box.maximum_point();
+
+        (
+            (minimum.x, minimum.y, minimum.z),
+            (maximum.x, maximum.y, maximum.z),
+        )
+    }
+
+    /// Writes the mesh to a Wavefront OBJ file.
+    fn export_obj(&self, path: &str) -> PyResult<()> {
+        let file = File::create(path).map_err(|err| PyIOError::new_err(err.to_string()))?;
+        let mut writer = BufWriter::new(file);
+
+        exporter::export_obj(
+            &mut writer,
+            vec![("mesh", self.mesh.as_ref())],
+            f32::DIGITS,
+            None,
+        )
+        .and_then(|()| writer.flush())
+        .map_err(|err| PyIOError::new_err(err.to_string()))
+    }
+}
+
+/// The operation pipeline of a project, whose parameters can be changed
+/// before running it again. Results of operations unaffected by the changes
+/// are reused between runs.
+#[pyclass(name = Pipeline, unsendable)]
+pub struct PyPipeline {
+    interpreter: Interpreter,
+    function_table: BTreeMap<FuncIdent, Box<dyn Func>>,
+    unit: Unit,
+}
+
+#[pymethods]
+impl PyPipeline {
+    /// Opens the pipeline saved in a project file.
+    #[staticmethod]
+    fn open(path: &str) -> PyResult<Self> {
+        let project = project::open(path).map_err(|err| PyIOError::new_err(err.to_string()))?;
+
+        let mut interpreter = Interpreter::new(interpreter_funcs::create_function_table());
+        interpreter.set_prog(ast::Prog::new(project.stmts));
+
+        Ok(Self {
+            interpreter,
+            function_table: interpreter_funcs::create_function_table(),
+            unit: project.unit,
+        })
+    }
+
+    /// Name of the units the project geometry is modeled in.
+    #[getter]
+    fn unit(&self) -> &'static str {
+        self.unit.name()
+    }
+
+    /// Returns the name of the operation and of its resulting value for each
+    /// operation of the pipeline.
+    fn operations(&self) -> Vec<(&'static str, &'static str)> {
+        self.interpreter
+            .prog()
+            .stmts()
+            .iter()
+            .map(|stmt| {
+                let info = self.func(stmt).info();
+                (info.name, info.return_value_name)
+            })
+            .collect()
+    }
+
+    /// Returns the names of the parameters of the operation.
+    fn params(&self, stmt_index: usize) -> PyResult<Vec<&'static str>> {
+        let stmt = self.stmt(stmt_index)?;
+        let param_info = self.func(stmt).param_info();
+
+        Ok(param_info.iter().map(|param| param.name).collect())
+    }
+
+    /// Returns the value of the operation parameter, or `None` if it is the
+    /// result of a previous operation.
+    fn arg(&self, py: Python, stmt_index: usize, arg_index: usize) -> PyResult<PyObject> {
+        let Stmt::VarDecl(var_decl) = self.stmt(stmt_index)?;
+        match var_decl.init_expr().args().get(arg_index) {
+            Some(Expr::Lit(lit)) => Ok(lit_to_py(py, lit)),
+            Some(Expr::Var(_)) => Ok(py.None()),
+            None => Err(PyIndexError::new_err("Parameter index out of range")),
+        }
+    }
+
+    /// Changes the value of the operation parameter. Parameters that are the
+    /// results of previous operations can't be changed.
+    fn set_arg(&mut self, stmt_index: usize, arg_index: usize, value: &PyAny) -> PyResult<()> {
+        let stmt = self.stmt(stmt_index)?;
+        let Stmt::VarDecl(var_decl) = stmt;
+        let func = self.func(stmt);
+        let param_index = func
+            .param_index_for_arg(arg_index, var_decl.init_expr().args().len())
+            .ok_or_else(|| PyIndexError::new_err("Parameter index out of range"))?;
+        let param = &func.param_info()[param_index];
+
+        if let Some(Expr::Var(_)) = var_decl.init_expr().args().get(arg_index) {
+            return Err(PyValueError::new_err(format!(
+                "Parameter {} is the result of a previous operation",
+                param.name,
+            )));
+        }
+
+        let lit = if value.is_none() && param.optional {
+            LitExpr::Nil
+        } else {
+            lit_from_py(value, param.refinement.ty())?
+        };
+
+        let init_expr = var_decl
+            .init_expr()
+            .clone_with_arg_at(arg_index, Expr::Lit(lit));
+        let stmt = Stmt::VarDecl(var_decl.clone_with_init_expr(init_expr));
+        self.interpreter.set_prog_stmt_at(stmt_index, stmt);
+
+        Ok(())
+    }
+
+    /// Runs the pipeline and returns a dictionary with the "unused" and
+    /// "used" values, each a list of pairs of value names and values.
+    fn run(&mut self, py: Python) -> PyResult<PyObject> {
+        let outcome = self.interpreter.interpret();
+        for log_messages in &outcome.log_messages {
+            log_func_messages(log_messages);
+        }
+
+        let value = outcome
+            .result
+            .map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+
+        let named_values = |values: &[(VarIdent, Value)]| -> PyResult<Vec<(&str, PyObject)>> {
+            values
+                .iter()
+                .map(|(var_ident, value)| Ok((self.var_name(*var_ident), value_to_py(py, value)?)))
+                .collect()
+        };
+
+        let result = PyDict::new(py);
+        result.set_item("unused", named_values(&value.unused_values)?)?;
+        result.set_item("used", named_values(&value.used_values)?)?;
+
+        Ok(result.to_object(py))
+    }
+}
+
+impl PyPipeline {
+    fn stmt(&self, stmt_index: usize) -> PyResult<&Stmt> {
+        self.interpreter
+            .prog_stmt_at(stmt_index)
+            .ok_or_else(|| PyIndexError::new_err("Operation index out of range"))
+    }
+
+    fn func(&self, stmt: &Stmt) -> &dyn Func {
+        let Stmt::VarDecl(var_decl) = stmt;
+        self.function_table[&var_decl.init_expr().ident()].as_ref()
+    }
+
+    fn var_name(&self, var_ident: VarIdent) -> &'static str {
+        self.interpreter
+            .prog()
+            .stmts()
+            .iter()
+            .find(|Stmt::VarDecl(var_decl)| var_decl.ident() == var_ident)
+            .map(|stmt| self.func(stmt).info().return_value_name)
+            .unwrap_or("<Unnamed value>")
+    }
+}
+
+/// Returns the identifier, name and description of each operation available
+/// to `call_func`.
+#[pyfunction]
+fn funcs() -> Vec<(u64, &'static str, &'static str)> {
+    interpreter_funcs::create_function_table()
+        .iter()
+        .map(|(func_ident, func)| (func_ident.0, func.info().name, func.info().description))
+        .collect()
+}
+
+/// Calls the operation with the identifier on the arguments, converting them
+/// to the types of its parameters. Optional parameters accept `None`.
+#[pyfunction]
+fn call_func(py: Python, func_id: u64, args: Vec<&PyAny>) -> PyResult<PyObject> {
+    let mut function_table = interpreter_funcs::create_function_table();
+    let func = function_table
+        .get_mut(&FuncIdent(func_id))
+        .ok_or_else(|| PyKeyError::new_err(format!("No operation with identifier {}", func_id)))?;
+
+    if !func.accepts_arg_count(args.len()) {
+        return Err(PyValueError::new_err(format!(
+            "{} takes {}{} arguments, but {} were given",
+            func.info().name,
+            if func.variadic_param_index().is_some() {
+                "at least "
+            } else {
+                ""
+            },
+            func.param_info().len(),
+            args.len(),
+        )));
+    }
+
+    let values = args
+        .iter()
+        .enumerate()
+        .map(|(arg_index, arg)| {
+            let param_index = func
+                .param_index_for_arg(arg_index, args.len())
+                .expect("Argument count must have been checked");
+            let param = &func.param_info()[param_index];
+            if arg.is_none() && param.optional {
+                Ok(Value::Nil)
+            } else {
+                value_from_py(arg, param.refinement.ty())
+            }
+        })
+        .collect::<PyResult<Vec<Value>>>()?;
+
+    let mut log_messages = Vec::new();
+    let result = func.call(&values, &mut |log_message| log_messages.push(log_message));
+    log_func_messages(&log_messages);
+
+    let value = result.map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+    value_to_py(py, &value)
+}
+
+fn log_func_messages(log_messages: &[LogMessage]) {
+    for log_message in log_messages {
+        match log_message.level {
+            LogMessageLevel::Info => log::info!("{}", log_message.message),
+            LogMessageLevel::Warn => log::warn!("{}", log_message.message),
+            LogMessageLevel::Error => log::error!("{}", log_message.message),
+        }
+    }
+}
+
+fn value_from_py(obj: &PyAny, ty: Ty) -> PyResult<Value> {
+    match ty {
+        Ty::Mesh => {
+            let mesh: PyRef<PyMesh> = obj.extract()?;
+            Ok(Value::Mesh(Arc::clone(&mesh.mesh)))
+        }
+        Ty::MeshArray => {
+            let meshes: Vec<PyRef<PyMesh>> = obj.extract()?;
+            let meshes = meshes.iter().map(|mesh| Arc::clone(&mesh.mesh)).collect();
+            Ok(Value::MeshArray(Arc::new(MeshArrayValue::new(meshes))))
+        }
+        _ => match lit_from_py(obj, ty)? {
+            LitExpr::Nil => Ok(Value::Nil),
+            LitExpr::Boolean(boolean) => Ok(Value::Boolean(boolean)),
+            LitExpr::Int(int) => Ok(Value::Int(int)),
+            LitExpr::Uint(uint) => Ok(Value::Uint(uint)),
+            LitExpr::Float(float) => Ok(Value::Float(float)),
+            LitExpr::Float2(float2) => Ok(Value::Float2(float2)),
+            LitExpr::Float3(float3) => Ok(Value::Float3(float3)),
+            LitExpr::String(string) => Ok(Value::String(Arc::new(string))),
+        },
+    }
+}
+
+fn lit_from_py(obj: &PyAny, ty: Ty) -> PyResult<LitExpr> {
+    match ty {
+        Ty::Nil => Ok(LitExpr::Nil),
+        Ty::Boolean => Ok(LitExpr::Boolean(obj.extract()?)),
+        Ty::Int => Ok(LitExpr::Int(obj.extract()?)),
+        Ty::Uint => Ok(LitExpr::Uint(obj.extract()?)),
+        Ty::Float => Ok(LitExpr::Float(obj.extract()?)),
+        Ty::Float2 => {
+            let (x, y) = obj.extract()?;
+            Ok(LitExpr::Float2([x, y]))
+        }
+        Ty::Float3 => {
+            let (x, y, z) = obj.extract()?;
+            Ok(LitExpr::Float3([x, y, z]))
+        }
+        Ty::String => Ok(LitExpr::String(obj.extract()?)),
+        Ty::Mesh | Ty::MeshArray => Err(PyValueError::new_err(format!(
+            "Values of type {} can't be set directly",
+            ty,
+        ))),
+    }
+}
+
+fn lit_to_py(py: Python, lit: &LitExpr) -> PyObject {
+    match lit {
+        LitExpr::Nil => py.None(),
+        LitExpr::Boolean(boolean) => boolean.to_object(py),
+        LitExpr::Int(int) => int.to_object(py),
+        LitExpr::Uint(uint) => uint.to_object(py),
+        LitExpr::Float(float) => float.to_object(py),
+        LitExpr::Float2([x, y]) => (*x, *y).to_object(py),
+        LitExpr::Float3([x, y, z]) => (*x, *y, *z).to_object(py),
+        LitExpr::String(string) => string.to_object(py),
+    }
+}
+
+fn value_to_py(py: Python, value: &Value) -> PyResult<PyObject> {
+    match value {
+        Value::Nil => Ok(py.None()),
+        Value::Boolean(boolean) => Ok(boolean.to_object(py)),
+        Value::Int(int) => Ok(int.to_object(py)),
+        Value::Uint(uint) => Ok(uint.to_object(py)),
+        Value::Float(float) => Ok(float.to_object(py)),
+        Value::Float2([x, y]) => Ok((*x, *y).to_object(py)),
+        Value::Float3([x, y, z]) => Ok((*x, *y, *z).to_object(py)),
+        Value::String(string) => Ok(string.as_str().to_object(py)),
+        Value::Mesh(mesh) => {
+            let mesh = PyMesh {
+                mesh: Arc::clone(mesh),
+            };
+            Ok(Py::new(py, mesh)?.into_py(py))
+        }
+        Value::MeshArray(mesh_array) => {
+            let meshes = mesh_array
+                .iter_refcounted()
+                .map(|mesh| Py::new(py, PyMesh { mesh }))
+                .collect::<PyResult<Vec<Py<PyMesh>>>>()?;
+            Ok(meshes.into_py(py))
+        }
+    }
+}
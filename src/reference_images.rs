@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use image::{GenericImageView, Pixel};
+use nalgebra::{Point3, Rotation3, Vector3};
+
+/// Images larger than this in any dimension are downscaled before uploading
+/// to the GPU, as reference images don't need the detail and some GPUs can't
+/// hold larger textures.
+pub const MAX_IMAGE_DIMENSION: u32 = 4096;
+
+const DEFAULT_WIDTH: f32 = 10.0;
+const DEFAULT_OPACITY: f32 = 0.5;
+
+/// How the reference image plane is oriented in the scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ReferenceImagePlacement {
+    /// Lying on the ground, e.g. a site plan or a map.
+    Ground,
+    /// Standing upright, e.g. an elevation or a section.
+    Vertical,
+}
+
+impl ReferenceImagePlacement {
+    pub fn name(self) -> &'static str {
+        match self {
+            ReferenceImagePlacement::Ground => "Ground",
+            ReferenceImagePlacement::Vertical => "Vertical",
+        }
+    }
+}
+
+/// An image file shown as a textured plane in the scene for reference, as
+/// stored in the project. The height of the plane is derived from the width
+/// and the aspect ratio of the image.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReferenceImage {
+    pub path: String,
+    pub placement: ReferenceImagePlacement,
+    /// Center of the image plane in the scene.
+    pub center: [f32; 3],
+    /// Width of the image plane in project units.
+    pub width: f32,
+    /// Rotation around the scene Z axis in degrees, counterclockwise when
+    /// looking down at the ground.
+    pub rotation: f32,
+    pub opacity: f32,
+}
+
+impl ReferenceImage {
+    pub fn new(path: String, placement: ReferenceImagePlacement) -> Self {
+        Self {
+            path,
+            placement,
+            center: [0.0, 0.0, 0.0],
+            width: DEFAULT_WIDTH,
+            rotation: 0.0,
+            opacity: DEFAULT_OPACITY,
+        }
+    }
+
+    /// Returns the corners of the image plane in the scene for an image of
+    /// the aspect ratio (width / height).
+    ///
+    /// The corners are ordered bottom-left, bottom-right, top-right,
+    /// top-left as seen on the image. Images on the ground have their top
+    /// edge towards the scene +Y axis before rotation, vertical images face
+    /// the scene -Y axis.
+    pub fn corners(&self, aspect_ratio: f32) -> [Point3<f32>; 4] {
+        let half_width = self.width / 2.0;
+        let half_height = self.width / aspect_ratio / 2.0;
+
+        let (right, up) = match self.placement {
+            ReferenceImagePlacement::Ground => (Vector3::x(), Vector3::y()),
+            ReferenceImagePlacement::Vertical => (Vector3::x(), Vector3::z()),
+        };
+
+        let rotation = Rotation3::from_axis_angle(&Vector3::z_axis(), self.rotation.to_radians());
+        let right = rotation * right * half_width;
+        let up = rotation * up * half_height;
+        let center = Point3::from(self.center);
+
+        [
+            center - right - up,
+            center + right - up,
+            center + right + up,
+            center - right + up,
+        ]
+    }
+}
+
+/// Reads and decodes the image file into RGBA8 pixels. Returns the pixels,
+/// width and height.
+pub fn load_image_rgba8_unorm<P: AsRef<Path>>(
+    path: P,
+) -> Result<(Vec<u8>, u32, u32), image::ImageError> {
+    let mut image = image::open(path)?;
+
+    let (width, height) = image.dimensions();
+    if width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        image = image.thumbnail(MAX_IMAGE_DIMENSION, MAX_IMAGE_DIMENSION);
+    }
+
+    let (width, height) = image.dimensions();
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for (_, _, pixel) in image.pixels() {
+        rgba.extend_from_slice(&pixel.to_rgba().0);
+    }
+
+    Ok((rgba, width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_image_corners_on_ground() {
+        let mut image =
+            ReferenceImage::new(String::from("plan.png"), ReferenceImagePlacement::Ground);
+        image.center = [1.0, 2.0, 0.0];
+        image.width = 4.0;
+
+        let corners = image.corners(2.0);
+
+        approx::assert_relative_eq!(corners[0], Point3::new(-1.0, 1.0, 0.0));
+        approx::assert_relative_eq!(corners[1], Point3::new(3.0, 1.0, 0.0));
+        approx::assert_relative_eq!(corners[2], Point3::new(3.0, 3.0, 0.0));
+        approx::assert_relative_eq!(corners[3], Point3::new(-1.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn test_reference_image_corners_vertical_rotated() {
+        let mut image = ReferenceImage::new(
+            String::from("elevation.png"),
+            ReferenceImagePlacement::Vertical,
+        );
+        image.center = [0.0, 0.0, 1.0];
+        image.width = 2.0;
+        image.rotation = 90.0;
+
+        let corners = image.corners(1.0);
+
+        approx::assert_relative_eq!(corners[0], Point3::new(0.0, -1.0, 0.0), epsilon = 0.0001);
+        approx::assert_relative_eq!(corners[1], Point3::new(0.0, 1.0, 0.0), epsilon = 0.0001);
+        approx::assert_relative_eq!(corners[2], Point3::new(0.0, 1.0, 2.0), epsilon = 0.0001);
+        approx::assert_relative_eq!(corners[3], Point3::new(0.0, -1.0, 2.0), epsilon = 0.0001);
+    }
+}
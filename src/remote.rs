@@ -0,0 +1,420 @@
+use std::io;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+
+use crossbeam_channel as channel;
+use tungstenite::{Message, WebSocket};
+
+use crate::interpreter::ast::{CallExpr, Expr, FuncIdent, LitExpr, Stmt, VarExpr};
+use crate::interpreter::Ty;
+use crate::session::Session;
+
+/// A command sent by a remote client as a JSON text message, e.g.
+/// `{"command": "push_stmt", "func_id": 1001, "args": [{"lit": {"Float": 2.0}}]}`.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum RemoteCommand {
+    /// Lists the operations available for statements.
+    GetFuncs,
+    /// Lists the statements of the pipeline.
+    GetStmts,
+    /// Appends a statement calling the operation to the pipeline.
+    PushStmt { func_id: u64, args: Vec<RemoteArg> },
+    /// Replaces the statement at the index with a call of the operation.
+    SetStmt {
+        stmt_index: usize,
+        func_id: u64,
+        args: Vec<RemoteArg>,
+    },
+    /// Removes the last statement of the pipeline.
+    PopStmt,
+    /// Runs the pipeline and replies once it finishes.
+    Interpret,
+    /// Replies with the OBJ file of the unused values, once the pipeline
+    /// finishes.
+    ExportObj,
+    /// Saves a PNG screenshot of the viewport from the current camera to the
+    /// path, once the pipeline finishes. The path must be relative to the
+    /// working directory and must not leave it.
+    Screenshot {
+        path: PathBuf,
+        width: u32,
+        height: u32,
+    },
+}
+
+/// An argument of a statement. Either a literal value, or the value of a
+/// previous statement given by its index.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteArg {
+    Lit(LitExpr),
+    Var(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RemoteParam {
+    pub name: &'static str,
+    pub ty: String,
+    pub optional: bool,
+    /// Names of the options of a `Uint` parameter choosing one of them. The
+    /// argument is the index of the chosen option.
+    pub choices: Option<&'static [&'static str]>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RemoteFunc {
+    pub func_id: u64,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: Vec<RemoteParam>,
+    /// Index of the parameter taking one or more arguments, if any.
+    pub variadic_param_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RemoteStmt {
+    pub func_id: u64,
+    pub name: &'static str,
+    pub args: Vec<RemoteArg>,
+}
+
+/// A reply to a remote command, sent back as a JSON text message.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteResponse {
+    Done,
+    Funcs(Vec<RemoteFunc>),
+    Stmts(Vec<RemoteStmt>),
+    Obj(String),
+    Error(String),
+}
+
+/// A command received from a remote client, waiting for a reply.
+pub struct RemoteRequest {
+    pub command: RemoteCommand,
+    reply_sender: channel::Sender<RemoteResponse>,
+}
+
+impl RemoteRequest {
+    pub fn reply(self, response: RemoteResponse) {
+        // The client may have disconnected in the meantime, in which case
+        // there is nobody to reply to.
+        let _ = self.reply_sender.send(response);
+    }
+}
+
+/// A WebSocket server listening on localhost, so that other tools running
+/// alongside, such as Grasshopper, can edit and run the pipeline.
+///
+/// Each client is served by its own thread, which forwards the commands to
+/// be executed by the main loop and waits for their replies. The `wake`
+/// callback is called after each received command, so that the main loop
+/// doesn't sleep through it.
+pub struct RemoteServer {
+    receiver: channel::Receiver<RemoteRequest>,
+}
+
+impl RemoteServer {
+    pub fn start<W>(port: u16, wake: W) -> io::Result<Self>
+    where
+        W: Fn() + Clone + Send + 'static,
+    {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let (sender, receiver) = channel::unbounded();
+
+        thread::Builder::new()
+            .name(String::from("hurban_selector_remote"))
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            let sender = sender.clone();
+                            let wake = wake.clone();
+                            let result = thread::Builder::new()
+                                .name(String::from("hurban_selector_remote_client"))
+                                .spawn(move || serve_client(stream, &sender, &wake));
+
+                            if let Err(err) = result {
+                                log::error!("Failed to spawn remote client thread: {}", err);
+                            }
+                        }
+                        Err(err) => log::warn!("Failed to accept remote client: {}", err),
+                    }
+                }
+            })?;
+
+        Ok(Self { receiver })
+    }
+
+    /// Returns the next command waiting to be executed, if any.
+    pub fn try_recv(&self) -> Option<RemoteRequest> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+fn serve_client(stream: TcpStream, sender: &channel::Sender<RemoteRequest>, wake: &dyn Fn()) {
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(err) => {
+            log::warn!("Failed to accept remote client: {}", err);
+            return;
+        }
+    };
+
+    log::info!("Remote client connected");
+
+    loop {
+        let response = match socket.read_message() {
+            Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(command) => {
+                    let (reply_sender, reply_receiver) = channel::bounded(1);
+                    let request = RemoteRequest {
+                        command,
+                        reply_sender,
+                    };
+
+                    if sender.send(request).is_err() {
+                        break;
+                    }
+                    wake();
+
+                    match reply_receiver.recv() {
+                        Ok(response) => response,
+                        Err(_) => break,
+                    }
+                }
+                Err(err) => RemoteResponse::Error(format!("Invalid command: {}", err)),
+            },
+            Ok(Message::Binary(_)) => {
+                RemoteResponse::Error(String::from("Commands must be text messages"))
+            }
+            // Pongs are queued by the socket itself when reading pings, but
+            // still need to be sent
+            Ok(_) => {
+                if socket.write_pending().is_err() {
+                    break;
+                }
+                continue;
+            }
+            Err(_) => break,
+        };
+
+        if let Err(err) = send_response(&mut socket, &response) {
+            log::warn!("Failed to reply to remote client: {}", err);
+            break;
+        }
+    }
+
+    log::info!("Remote client disconnected");
+}
+
+fn send_response(
+    socket: &mut WebSocket<TcpStream>,
+    response: &RemoteResponse,
+) -> tungstenite::Result<()> {
+    let text = serde_json::to_string(response).expect("Failed to serialize remote response");
+    socket.write_message(Message::Text(text))
+}
+
+/// Describes the operations available for statements.
+pub fn funcs(session: &Session) -> Vec<RemoteFunc> {
+    session
+        .function_table()
+        .iter()
+        .map(|(func_ident, func)| RemoteFunc {
+            func_id: func_ident.0,
+            name: func.info().name,
+            description: func.info().description,
+            params: func
+                .param_info()
+                .iter()
+                .map(|param| RemoteParam {
+                    name: param.name,
+                    ty: param.refinement.ty().to_string(),
+                    optional: param.optional,
+                    choices: param.widget.choices(),
+                })
+                .collect(),
+            variadic_param_index: func.variadic_param_index(),
+        })
+        .collect()
+}
+
+/// Describes the statements of the pipeline, referring to variables by the
+/// indices of the statements declaring them.
+pub fn stmts(session: &Session) -> Vec<RemoteStmt> {
+    session
+        .stmts()
+        .iter()
+        .map(|stmt| {
+            let Stmt::VarDecl(var_decl) = stmt;
+            let init_expr = var_decl.init_expr();
+            let args = init_expr
+                .args()
+                .iter()
+                .map(|arg| match arg {
+                    Expr::Lit(lit) => RemoteArg::Lit(lit.clone()),
+                    Expr::Var(var) => {
+                        let (stmt_index, _) = session
+                            .var_decl_stmt_index_and_var_name_for_ident(var.ident())
+                            .expect("Variable must be declared by a statement");
+                        RemoteArg::Var(stmt_index)
+                    }
+                })
+                .collect();
+
+            RemoteStmt {
+                func_id: init_expr.ident().0,
+                name: session.function_table()[&init_expr.ident()]
+                    .info()
+                    .return_value_name,
+                args,
+            }
+        })
+        .collect()
+}
+
+/// Creates the call of the operation for the statement at the index,
+/// checking the arguments against the parameters of the operation.
+pub fn call_expr(
+    session: &Session,
+    stmt_index: usize,
+    func_id: u64,
+    args: &[RemoteArg],
+) -> Result<CallExpr, String> {
+    let func_ident = FuncIdent(func_id);
+    let func = session
+        .function_table()
+        .get(&func_ident)
+        .ok_or_else(|| format!("No operation with identifier {}", func_id))?;
+
+    let param_info = func.param_info();
+    if !func.accepts_arg_count(args.len()) {
+        return Err(format!(
+            "{} takes {}{} arguments, but {} were given",
+            func.info().name,
+            if func.variadic_param_index().is_some() {
+                "at least "
+            } else {
+                ""
+            },
+            param_info.len(),
+            args.len(),
+        ));
+    }
+
+    let exprs = args
+        .iter()
+        .enumerate()
+        .map(|(arg_index, arg)| {
+            let param_index = func
+                .param_index_for_arg(arg_index, args.len())
+                .expect("Argument count must have been checked");
+            let param = &param_info[param_index];
+            let param_ty = param.refinement.ty();
+            match arg {
+                RemoteArg::Lit(lit) => {
+                    let ty = lit_ty(lit);
+                    if ty == param_ty || (ty == Ty::Nil && param.optional) {
+                        Ok(Expr::Lit(lit.clone()))
+                    } else {
+                        Err(format!(
+                            "Parameter {} expects {}, but {} was given",
+                            param.name, param_ty, ty,
+                        ))
+                    }
+                }
+                RemoteArg::Var(var_stmt_index) => {
+                    let var_ident = session.stmts().get(*var_stmt_index).map(|stmt| {
+                        let Stmt::VarDecl(var_decl) = stmt;
+                        var_decl.ident()
+                    });
+
+                    session
+                        .visible_vars_at_stmt(stmt_index, param_ty)
+                        .find(|visible_var_ident| Some(*visible_var_ident) == var_ident)
+                        .map(|var_ident| Expr::Var(VarExpr::new(var_ident)))
+                        .ok_or_else(|| {
+                            format!(
+                                "Parameter {} can't use the value of statement {}",
+                                param.name, var_stmt_index,
+                            )
+                        })
+                }
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(CallExpr::new(func_ident, exprs))
+}
+
+/// Whether the path stays inside the directory it is relative to, i.e. it
+/// is neither absolute nor contains parent directory components.
+///
+/// Paths given by remote clients are checked before writing to them, so that
+/// a client can't overwrite files anywhere else on the machine.
+pub fn is_confined_path(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+fn lit_ty(lit: &LitExpr) -> Ty {
+    match lit {
+        LitExpr::Nil => Ty::Nil,
+        LitExpr::Boolean(_) => Ty::Boolean,
+        LitExpr::Int(_) => Ty::Int,
+        LitExpr::Uint(_) => Ty::Uint,
+        LitExpr::Float(_) => Ty::Float,
+        LitExpr::Float2(_) => Ty::Float2,
+        LitExpr::Float3(_) => Ty::Float3,
+        LitExpr::String(_) => Ty::String,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_command_deserialize_push_stmt() {
+        let command: RemoteCommand = serde_json::from_str(
+            r#"{"command": "push_stmt", "func_id": 4000, "args": [{"var": 0}, {"lit": {"Float3": [1.0, 2.0, 3.0]}}, {"lit": "Nil"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            command,
+            RemoteCommand::PushStmt {
+                func_id: 4000,
+                args: vec![
+                    RemoteArg::Var(0),
+                    RemoteArg::Lit(LitExpr::Float3([1.0, 2.0, 3.0])),
+                    RemoteArg::Lit(LitExpr::Nil),
+                ],
+            },
+        );
+    }
+
+    #[test]
+    fn test_is_confined_path() {
+        assert!(is_confined_path(Path::new("screenshot.png")));
+        assert!(is_confined_path(Path::new("./renders/screenshot.png")));
+        assert!(!is_confined_path(Path::new("/tmp/screenshot.png")));
+        assert!(!is_confined_path(Path::new("../screenshot.png")));
+        assert!(!is_confined_path(Path::new("renders/../../screenshot.png")));
+    }
+
+    #[test]
+    fn test_remote_response_serialize() {
+        assert_eq!(
+            serde_json::to_string(&RemoteResponse::Done).unwrap(),
+            r#""done""#,
+        );
+        assert_eq!(
+            serde_json::to_string(&RemoteResponse::Error(String::from("Busy"))).unwrap(),
+            r#"{"error":"Busy"}"#,
+        );
+    }
+}
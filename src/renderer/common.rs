@@ -10,6 +10,20 @@ macro_rules! include_shader {
     }};
 }
 
+/// Logs an operation creating, changing or destroying GPU resources. The
+/// operations are logged at the info level in the validation mode, so that
+/// they show up in bug reports, and at the debug level otherwise.
+#[macro_export]
+macro_rules! log_resource {
+    ($validation:expr, $($arg:tt)+) => {{
+        if $validation {
+            log::info!($($arg)+);
+        } else {
+            log::debug!($($arg)+);
+        }
+    }};
+}
+
 pub fn wgpu_size_of<T>() -> wgpu::BufferAddress {
     let size = mem::size_of::<T>();
     wgpu::BufferAddress::try_from(size)
@@ -1,6 +1,12 @@
-pub use self::scene_renderer::{AddMeshError, DirectionalLight, GpuMesh, GpuMeshHandle, Material};
+pub use self::scene_renderer::{
+    AddMeshError, DirectionalLight, GpuImageHandle, GpuMesh, GpuMeshHandle, Material,
+    ShadowQuality, Viewport,
+};
 
+use std::cmp;
 use std::collections::HashMap;
+use std::env;
+use std::error;
 use std::fmt;
 use std::future::Future;
 use std::iter;
@@ -10,8 +16,9 @@ use std::pin::Pin;
 use std::slice;
 use std::task::Poll;
 use std::thread;
+use std::time::{Duration, Instant};
 
-use nalgebra::Matrix4;
+use nalgebra::{Matrix4, Point3, Vector3};
 
 use crate::convert::cast_u32;
 
@@ -31,6 +38,10 @@ static DEFAULT_BACKEND_LIST: &[GpuBackend] = &[GpuBackend::Metal];
 #[cfg(target_os = "linux")]
 static DEFAULT_BACKEND_LIST: &[GpuBackend] = &[GpuBackend::Vulkan];
 
+/// Largest width or height of offscreen render targets in the validation
+/// mode. Larger textures exceed the limits of some GPUs.
+const VALIDATION_MAX_TEXTURE_DIMENSION: u32 = 4096;
+
 static SHADER_BLIT_VERT: &[u8] = include_shader!("blit.vert.spv");
 static SHADER_BLIT_FRAG: &[u8] = include_shader!("blit.frag.spv");
 
@@ -54,12 +65,26 @@ pub struct Options {
     pub backend: Option<GpuBackend>,
     /// Power preference for selecting a GPU.
     pub power_preference: GpuPowerPreference,
+    /// GPU adapter explicitly chosen by the user. If not available, the
+    /// adapter is selected based on the power preference.
+    pub adapter: Option<GpuAdapterInfo>,
     /// Level of multi-sampling based anti-aliasing to use in rendering.
     pub msaa: Msaa,
     /// The color with which to render surfaces in `Material::FlatWithShadows`.
     pub flat_material_color: [f64; 4],
     /// The transparency value of transparent matcap materials.
     pub transparent_matcap_shaded_material_alpha: f64,
+    /// Maximum size of mesh vertex and index buffers in bytes kept on the
+    /// GPU. Least recently drawn meshes are evicted when over budget. Helps
+    /// integrated GPUs with little memory draw large pipelines.
+    pub mesh_memory_budget: Option<u64>,
+    /// Resolution of the shadow map cascades.
+    pub shadow_quality: ShadowQuality,
+    /// Whether to check the use of the GPU with the validation layers of the
+    /// graphics API, avoid risky features, such as multisampling and large
+    /// textures, and log every operation on GPU resources. Validation errors
+    /// are logged instead of panicking.
+    pub validation: bool,
 }
 
 /// Level of multi-sampling based anti-aliasing to use in rendering.
@@ -163,6 +188,62 @@ impl fmt::Display for GpuPowerPreference {
     }
 }
 
+/// Identification of a GPU adapter, as reported by its driver. Used to list
+/// the available adapters and to remember the adapter chosen by the user.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GpuAdapterInfo {
+    pub name: String,
+    /// PCI vendor ID of the adapter.
+    pub vendor: usize,
+    /// PCI device ID of the adapter.
+    pub device: usize,
+    pub device_type: String,
+    pub backend: String,
+}
+
+impl From<wgpu::AdapterInfo> for GpuAdapterInfo {
+    fn from(info: wgpu::AdapterInfo) -> Self {
+        let device_type = match info.device_type {
+            wgpu::DeviceType::Other => "Other",
+            wgpu::DeviceType::IntegratedGpu => "Integrated GPU",
+            wgpu::DeviceType::DiscreteGpu => "Discrete GPU",
+            wgpu::DeviceType::VirtualGpu => "Virtual GPU",
+            wgpu::DeviceType::Cpu => "CPU",
+        };
+
+        Self {
+            name: info.name,
+            vendor: info.vendor,
+            device: info.device,
+            device_type: device_type.to_string(),
+            backend: format!("{:?}", info.backend),
+        }
+    }
+}
+
+impl fmt::Display for GpuAdapterInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}, {})", self.name, self.device_type, self.backend)
+    }
+}
+
+/// Lists the GPU adapters available for the backend. If no backend is
+/// chosen, lists adapters of the default backends for the current platform.
+pub fn enumerate_gpu_adapters(backend: Option<GpuBackend>) -> Vec<GpuAdapterInfo> {
+    backend_list(&backend)
+        .iter()
+        .flat_map(|gpu_backend| {
+            let backend_bit: wgpu::BackendBit = (*gpu_backend).into();
+            let instance = wgpu::Instance::new(backend_bit);
+
+            instance
+                .enumerate_adapters(backend_bit)
+                .map(|adapter| GpuAdapterInfo::from(adapter.get_info()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
 /// Opaque handle to collection of textures for rendering stored in
 /// renderer. Does not implement `Clone` on purpose. The handle is
 /// acquired by creating the render target and has to be relinquished
@@ -170,6 +251,71 @@ impl fmt::Display for GpuPowerPreference {
 #[derive(Debug, PartialEq, Eq)]
 pub struct OffscreenRenderTargetHandle(u64);
 
+impl OffscreenRenderTargetHandle {
+    /// Returns a number uniquely identifying the render target for the
+    /// lifetime of the renderer. Useful for pairing requested reads with
+    /// their purpose, once the handle is returned via `PollNotification`.
+    pub fn id(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Opaque handle to a secondary window the renderer draws into, e.g. a
+/// detached viewport. Does not implement `Clone` on purpose. The handle is
+/// acquired by adding the window and has to be relinquished to remove it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SecondaryWindowHandle(u64);
+
+/// An error preventing the renderer from starting or drawing.
+#[derive(Debug)]
+pub enum RendererError {
+    /// None of the GPU backends has an adapter able to draw to the window.
+    AdapterNotFound,
+    /// The adapter failed to provide a GPU device.
+    DeviceRequestFailed(wgpu::RequestDeviceError),
+    /// The UI referenced a texture not present in the renderer.
+    UiTextureNotFound(imgui::TextureId),
+    /// The swap chain of a window can not be drawn into anymore.
+    SwapChainFailed(wgpu::SwapChainError),
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RendererError::AdapterNotFound => write!(f, "Failed to find suitable GPU backend"),
+            RendererError::DeviceRequestFailed(err) => {
+                write!(f, "Failed to request GPU device: {}", err)
+            }
+            RendererError::UiTextureNotFound(texture_id) => {
+                write!(f, "UI texture {} not found", texture_id.id())
+            }
+            RendererError::SwapChainFailed(err) => {
+                write!(f, "Encountered GPU swapchain error: {}", err)
+            }
+        }
+    }
+}
+
+impl error::Error for RendererError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            RendererError::DeviceRequestFailed(err) => Some(err),
+            RendererError::SwapChainFailed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<imgui_renderer::Error> for RendererError {
+    fn from(err: imgui_renderer::Error) -> Self {
+        match err {
+            imgui_renderer::Error::BadTexture(texture_id) => {
+                RendererError::UiTextureNotFound(texture_id)
+            }
+        }
+    }
+}
+
 /// A notification from the renderer to the surrounding environment about what
 /// asynchronous tasks have finished and are ready to be published since the
 /// last poll.
@@ -220,11 +366,14 @@ impl<'a> OffscreenRenderTargetRead<'a> {
 /// recording draw commands and `render_pass.submit()` to execute
 /// them.
 pub struct Renderer {
+    instance: wgpu::Instance,
     device: wgpu::Device,
     queue: wgpu::Queue,
     surface: wgpu::Surface,
     swap_chain: wgpu::SwapChain,
     screen_render_target: RenderTarget,
+    secondary_windows: HashMap<u64, SecondaryWindow>,
+    secondary_window_next_handle: u64,
     offscreen_render_target_pending_reads: HashMap<u64, OffscreenRenderTargetPendingRead>,
     offscreen_render_targets: HashMap<u64, RenderTarget>,
     offscreen_render_target_next_handle: u64,
@@ -235,54 +384,89 @@ pub struct Renderer {
     scene_renderer: SceneRenderer,
     imgui_renderer: ImguiRenderer,
     options: Options,
+    adapter_info: GpuAdapterInfo,
+    mesh_upload_duration: Duration,
 }
 
 impl Renderer {
+    /// Creates the renderer drawing into the window. Fails if no GPU adapter
+    /// able to draw to the window is found or the GPU device can not be
+    /// acquired.
     pub fn new<H: raw_window_handle::HasRawWindowHandle>(
         window: &H,
         width: u32,
         height: u32,
         imgui_font_atlas: imgui::FontAtlasRefMut,
         options: Options,
-    ) -> Self {
-        let gpu_backend_list = options
-            .backend
-            .as_ref()
-            .map(|backend| slice::from_ref(backend))
-            .unwrap_or(DEFAULT_BACKEND_LIST);
+    ) -> Result<Self, RendererError> {
+        let options = if options.validation {
+            log::warn!("GPU validation is enabled, rendering will be slower");
+            enable_validation_layers();
+
+            if options.msaa.enabled() {
+                log::warn!("GPU validation disables multisampling");
+            }
+
+            Options {
+                msaa: Msaa::Disabled,
+                ..options
+            }
+        } else {
+            options
+        };
+
+        let gpu_backend_list = backend_list(&options.backend);
+
+        let chosen_adapter = options.adapter.as_ref().and_then(|adapter_info| {
+            log::info!("GPU will try to use the chosen adapter: {}", adapter_info);
+            let chosen_adapter = find_adapter(window, gpu_backend_list, adapter_info);
+            if chosen_adapter.is_none() {
+                log::warn!("The chosen GPU adapter is not available: {}", adapter_info);
+            }
+
+            chosen_adapter
+        });
 
         let gpu_power_preference = options.power_preference;
         log::info!("GPU will use power preference: {}", gpu_power_preference);
 
         let mut gpu_backend_iter = gpu_backend_list.iter().copied();
-        let (surface, adapter) = loop {
-            if let Some(gpu_backend) = gpu_backend_iter.next() {
-                log::info!("Trying to acquire GPU adapter for backend: {}", gpu_backend);
-
-                let instance = wgpu::Instance::new(gpu_backend.into());
-                let surface = unsafe { instance.create_surface(window) };
-                let adapter_result = futures::executor::block_on(instance.request_adapter(
-                    &wgpu::RequestAdapterOptions {
-                        power_preference: gpu_power_preference.into(),
-                        compatible_surface: Some(&surface),
-                    },
-                ));
-
-                match adapter_result {
-                    Some(adapter) => {
-                        log::info!("Found suitable GPU adapter for backend: {}", gpu_backend);
-                        break (surface, adapter);
-                    }
-                    None => {
-                        log::warn!("Failed to acquire GPU adapter for backend: {}", gpu_backend);
+        let (instance, surface, adapter) = if let Some(chosen_adapter) = chosen_adapter {
+            chosen_adapter
+        } else {
+            loop {
+                if let Some(gpu_backend) = gpu_backend_iter.next() {
+                    log::info!("Trying to acquire GPU adapter for backend: {}", gpu_backend);
+
+                    let instance = wgpu::Instance::new(gpu_backend.into());
+                    let surface = unsafe { instance.create_surface(window) };
+                    let adapter_result = futures::executor::block_on(instance.request_adapter(
+                        &wgpu::RequestAdapterOptions {
+                            power_preference: gpu_power_preference.into(),
+                            compatible_surface: Some(&surface),
+                        },
+                    ));
+
+                    match adapter_result {
+                        Some(adapter) => {
+                            log::info!("Found suitable GPU adapter for backend: {}", gpu_backend);
+                            break (instance, surface, adapter);
+                        }
+                        None => {
+                            log::warn!(
+                                "Failed to acquire GPU adapter for backend: {}",
+                                gpu_backend
+                            );
+                        }
                     }
+                } else {
+                    return Err(RendererError::AdapterNotFound);
                 }
-            } else {
-                panic!("Failed to find suitable GPU backend");
             }
         };
 
-        log::info!("GPU adapter info: {:?}", adapter.get_info());
+        let adapter_info = GpuAdapterInfo::from(adapter.get_info());
+        log::info!("GPU adapter info: {:?}", adapter_info);
 
         let (device, mut queue) = futures::executor::block_on(adapter.request_device(
             &wgpu::DeviceDescriptor {
@@ -296,7 +480,13 @@ impl Renderer {
             },
             None,
         ))
-        .expect("Failed to request GPU device");
+        .map_err(RendererError::DeviceRequestFailed)?;
+
+        if options.validation {
+            // A single validation error shouldn't end the session the bug
+            // report is produced in
+            device.on_uncaptured_error(|err| log::error!("GPU validation error: {}", err));
+        }
 
         let swap_chain = create_swap_chain(&device, &surface, width, height);
 
@@ -327,6 +517,8 @@ impl Renderer {
                 flat_material_color: options.flat_material_color,
                 transparent_matcap_shaded_material_alpha: options
                     .transparent_matcap_shaded_material_alpha,
+                mesh_memory_budget: options.mesh_memory_budget,
+                shadow_quality: options.shadow_quality,
             },
         );
 
@@ -337,8 +529,7 @@ impl Renderer {
             ImguiRendererOptions {
                 output_color_attachment_format: TEXTURE_FORMAT_SWAP_CHAIN,
             },
-        )
-        .expect("Failed to create imgui renderer");
+        )?;
 
         let blit_pass_buffer_color = common::create_buffer(
             &device,
@@ -445,11 +636,14 @@ impl Renderer {
             alpha_to_coverage_enabled: false,
         });
 
-        Self {
+        Ok(Self {
+            instance,
             device,
             queue,
             surface,
             swap_chain,
+            secondary_windows: HashMap::new(),
+            secondary_window_next_handle: 0,
             screen_render_target: RenderTarget {
                 width,
                 height,
@@ -471,7 +665,14 @@ impl Renderer {
             scene_renderer,
             imgui_renderer,
             options,
-        }
+            adapter_info,
+            mesh_upload_duration: Duration::default(),
+        })
+    }
+
+    /// Returns the GPU adapter the renderer uses.
+    pub fn adapter_info(&self) -> &GpuAdapterInfo {
+        &self.adapter_info
     }
 
     /// Update window size. Recreate swap chain, the screen render target
@@ -483,7 +684,8 @@ impl Renderer {
                 self.screen_render_target.height,
             )
         {
-            log::debug!(
+            log_resource!(
+                self.options.validation,
                 "Resizing renderer screen textures to dimensions: {}x{}",
                 width,
                 height,
@@ -543,12 +745,19 @@ impl Renderer {
     ) -> OffscreenRenderTargetHandle {
         let handle = OffscreenRenderTargetHandle(self.offscreen_render_target_next_handle);
 
+        let (width, height) = if self.options.validation {
+            fit_dimensions(width, height, VALIDATION_MAX_TEXTURE_DIMENSION)
+        } else {
+            (width, height)
+        };
+
         // FIXME: Add option to configure different MSAA for offscreen
         // render targets. This will require us to create multiple
         // pipelines, and therefore have a pipeline cache.
-        let msaa = &self.options.msaa;
+        let msaa = self.options.msaa;
 
-        log::debug!(
+        log_resource!(
+            self.options.validation,
             "Adding offscreen render target {} with dimensions {}x{} and multisampling: {}",
             handle.0,
             width,
@@ -556,40 +765,8 @@ impl Renderer {
             msaa,
         );
 
-        let msaa_texture = if msaa.enabled() {
-            Some(create_msaa_texture(
-                &self.device,
-                width,
-                height,
-                msaa.sample_count(),
-            ))
-        } else {
-            None
-        };
-
-        let color_texture = create_color_texture(&self.device, width, height);
-        let color_texture_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let depth_texture = create_depth_texture(&self.device, width, height, msaa.sample_count());
-
-        let color_texture_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: None,
-            layout: self.scene_renderer.sampled_texture_bind_group_layout(),
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(&color_texture_view),
-            }],
-        });
-
-        let offscreen_render_target = RenderTarget {
-            width,
-            height,
-            msaa_texture_view: msaa_texture
-                .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default())),
-            color_texture,
-            color_texture_view,
-            color_texture_bind_group,
-            depth_texture_view: depth_texture.create_view(&wgpu::TextureViewDescriptor::default()),
-        };
+        let offscreen_render_target =
+            create_render_target(&self.device, &self.scene_renderer, msaa, width, height);
 
         self.offscreen_render_targets
             .insert(handle.0, offscreen_render_target);
@@ -599,10 +776,106 @@ impl Renderer {
     }
 
     pub fn remove_offscreen_render_target(&mut self, handle: OffscreenRenderTargetHandle) {
-        log::debug!("Removing offscreen render target {}", handle.0);
+        log_resource!(
+            self.options.validation,
+            "Removing offscreen render target {}",
+            handle.0,
+        );
         self.offscreen_render_targets.remove(&handle.0);
     }
 
+    /// Adds a secondary window to draw into with
+    /// `Renderer::begin_secondary_window_command_buffer`.
+    ///
+    /// The window must be removed with `Renderer::remove_secondary_window`
+    /// before it is closed.
+    pub fn add_secondary_window<H: raw_window_handle::HasRawWindowHandle>(
+        &mut self,
+        window: &H,
+        width: u32,
+        height: u32,
+    ) -> SecondaryWindowHandle {
+        let handle = SecondaryWindowHandle(self.secondary_window_next_handle);
+
+        log_resource!(
+            self.options.validation,
+            "Adding secondary window {} with dimensions {}x{}",
+            handle.0,
+            width,
+            height,
+        );
+
+        let surface = unsafe { self.instance.create_surface(window) };
+        let swap_chain = create_swap_chain(&self.device, &surface, width, height);
+        let render_target = create_render_target(
+            &self.device,
+            &self.scene_renderer,
+            self.options.msaa,
+            width,
+            height,
+        );
+
+        self.secondary_windows.insert(
+            handle.0,
+            SecondaryWindow {
+                surface,
+                swap_chain,
+                render_target,
+            },
+        );
+        self.secondary_window_next_handle += 1;
+
+        handle
+    }
+
+    /// Update secondary window size. Recreate its swap chain and render
+    /// target.
+    pub fn set_secondary_window_size(
+        &mut self,
+        handle: &SecondaryWindowHandle,
+        width: u32,
+        height: u32,
+    ) {
+        let secondary_window = self
+            .secondary_windows
+            .get_mut(&handle.0)
+            .expect("Secondary window must exist");
+
+        if (width, height)
+            != (
+                secondary_window.render_target.width,
+                secondary_window.render_target.height,
+            )
+        {
+            log_resource!(
+                self.options.validation,
+                "Resizing secondary window {} to dimensions: {}x{}",
+                handle.0,
+                width,
+                height,
+            );
+
+            secondary_window.swap_chain =
+                create_swap_chain(&self.device, &secondary_window.surface, width, height);
+            secondary_window.render_target = create_render_target(
+                &self.device,
+                &self.scene_renderer,
+                self.options.msaa,
+                width,
+                height,
+            );
+        }
+    }
+
+    pub fn remove_secondary_window(&mut self, handle: SecondaryWindowHandle) {
+        log_resource!(
+            self.options.validation,
+            "Removing secondary window {}",
+            handle.0,
+        );
+        self.secondary_windows.remove(&handle.0);
+    }
+
     /// Requests data download from the offscreen render target on the GPU.
     ///
     /// Because we don't want to stall the renderer with the data download, the
@@ -633,6 +906,12 @@ impl Renderer {
             "A read is already requested for this render target",
         );
 
+        log_resource!(
+            self.options.validation,
+            "Requesting read of offscreen render target {}",
+            handle.0,
+        );
+
         // It is a WebGPU requirement that:
         //
         // - BufferCopyView.layout.bytes_per_row % wgpu::COPY_BYTES_PER_ROW_ALIGNMENT == 0
@@ -706,14 +985,160 @@ impl Renderer {
     /// The mesh will be available for drawing in subsequent render
     /// passes.
     pub fn add_scene_mesh(&mut self, mesh: &GpuMesh) -> Result<GpuMeshHandle, AddMeshError> {
-        self.scene_renderer.add_mesh(&self.device, mesh)
+        let upload_start = Instant::now();
+        let result = self.scene_renderer.add_mesh(&self.device, mesh);
+        self.mesh_upload_duration += upload_start.elapsed();
+
+        match &result {
+            Ok(handle) => log_resource!(self.options.validation, "Added scene mesh {:?}", handle),
+            Err(err) => log_resource!(self.options.validation, "Failed adding scene mesh: {}", err),
+        }
+
+        result
+    }
+
+    /// Replaces the data of a mesh previously uploaded to the GPU, reusing
+    /// its buffers if the new data fits in them.
+    pub fn update_scene_mesh(
+        &mut self,
+        handle: &GpuMeshHandle,
+        mesh: &GpuMesh,
+    ) -> Result<(), AddMeshError> {
+        log_resource!(self.options.validation, "Updating scene mesh {:?}", handle);
+
+        let upload_start = Instant::now();
+        let result = self
+            .scene_renderer
+            .update_mesh(&self.device, &mut self.queue, handle, mesh);
+        self.mesh_upload_duration += upload_start.elapsed();
+
+        result
+    }
+
+    /// Returns the total time spent adding and updating scene meshes since
+    /// the renderer was created.
+    pub fn mesh_upload_duration(&self) -> Duration {
+        self.mesh_upload_duration
     }
 
     /// Removes mesh from the GPU.
     pub fn remove_scene_mesh(&mut self, handle: GpuMeshHandle) {
+        log_resource!(self.options.validation, "Removing scene mesh {:?}", handle);
         self.scene_renderer.remove_mesh(handle);
     }
 
+    /// Uploads an RGBA8 image to the GPU to be drawn as a textured plane in
+    /// scene rendering.
+    ///
+    /// The plane is spanned by the `corners` of the image in the order
+    /// bottom-left, bottom-right, top-right, top-left. The image will be
+    /// available for drawing in subsequent render passes.
+    pub fn add_scene_image_rgba8_unorm(
+        &mut self,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        corners: &[Point3<f32>; 4],
+        opacity: f32,
+    ) -> GpuImageHandle {
+        let handle = self.scene_renderer.add_image(
+            &self.device,
+            &mut self.queue,
+            width,
+            height,
+            data,
+            corners,
+            opacity,
+        );
+        log_resource!(
+            self.options.validation,
+            "Added scene image {:?} with dimensions {}x{}",
+            handle,
+            width,
+            height,
+        );
+
+        handle
+    }
+
+    /// Moves a scene image to new `corners` and changes its opacity.
+    pub fn set_scene_image_placement(
+        &mut self,
+        handle: &GpuImageHandle,
+        corners: &[Point3<f32>; 4],
+        opacity: f32,
+    ) {
+        self.scene_renderer
+            .set_image_placement(&mut self.queue, handle, corners, opacity);
+    }
+
+    /// Changes the color of surfaces rendered in `Material::FlatWithShadows`.
+    pub fn set_flat_material_color(&mut self, color: [f64; 4]) {
+        self.scene_renderer
+            .set_flat_material_color(&mut self.queue, color);
+    }
+
+    /// Replaces the matcap texture of the matcap shaded materials by an RGBA8
+    /// image.
+    pub fn set_matcap_texture_rgba8_unorm(&mut self, width: u32, height: u32, data: &[u8]) {
+        log_resource!(
+            self.options.validation,
+            "Setting matcap texture with dimensions {}x{}",
+            width,
+            height,
+        );
+        self.scene_renderer.set_matcap_texture_rgba8_unorm(
+            &self.device,
+            &mut self.queue,
+            width,
+            height,
+            data,
+        );
+    }
+
+    /// Replaces the matcap texture of the matcap shaded materials by the
+    /// built-in matcap.
+    pub fn reset_matcap_texture(&mut self) {
+        log_resource!(self.options.validation, "Resetting matcap texture");
+        self.scene_renderer
+            .reset_matcap_texture(&self.device, &mut self.queue);
+    }
+
+    /// Changes the resolution of the shadow map cascades.
+    pub fn set_shadow_quality(&mut self, shadow_quality: ShadowQuality) {
+        log_resource!(
+            self.options.validation,
+            "Setting shadow quality {}",
+            shadow_quality,
+        );
+        self.scene_renderer
+            .set_shadow_quality(&self.device, shadow_quality);
+    }
+
+    /// Changes the color of a scene mesh, used when drawing it in one of the
+    /// colored materials.
+    pub fn set_scene_mesh_color(&mut self, handle: &GpuMeshHandle, color: [f64; 3]) {
+        self.scene_renderer
+            .set_mesh_color(&self.device, &mut self.queue, handle, color);
+    }
+
+    /// Moves a scene mesh by the translation when drawing it, without
+    /// uploading its geometry again.
+    pub fn set_scene_mesh_translation(
+        &mut self,
+        handle: &GpuMeshHandle,
+        translation: &Vector3<f32>,
+    ) {
+        self.scene_renderer
+            .set_mesh_translation(&mut self.queue, handle, translation);
+    }
+
+    /// Removes scene image from the GPU.
+    pub fn remove_scene_image(&mut self, handle: GpuImageHandle) {
+        log_resource!(self.options.validation, "Removing scene image {:?}", handle);
+        self.scene_renderer.remove_image(handle);
+    }
+
     /// Uploads an RGBA8 texture to the GPU to be used in UI
     /// rendering.
     ///
@@ -725,18 +1150,27 @@ impl Renderer {
         height: u32,
         data: &[u8],
     ) -> imgui::TextureId {
-        self.imgui_renderer.add_texture_rgba8_unorm(
+        let id = self.imgui_renderer.add_texture_rgba8_unorm(
             &self.device,
             &mut self.queue,
             width,
             height,
             data,
-        )
+        );
+        log_resource!(
+            self.options.validation,
+            "Added UI texture {} with dimensions {}x{}",
+            id.id(),
+            width,
+            height,
+        );
+
+        id
     }
 
     /// Removes texture from the GPU.
-    #[allow(dead_code)]
     pub fn remove_ui_texture(&mut self, id: imgui::TextureId) {
+        log_resource!(self.options.validation, "Removing UI texture {}", id.id());
         self.imgui_renderer.remove_texture(id);
     }
 
@@ -753,11 +1187,40 @@ impl Renderer {
     ///
     /// Render target resources, such as the offscreen texture or the shadow map
     /// are cleared exactly once per command buffer.
+    ///
+    /// Fails if the swap chain texture is requested, but the swap chain can
+    /// not be drawn into anymore.
     pub fn begin_command_buffer(
         &mut self,
         clear_color: [f64; 4],
         offscreen_render_target_handle: Option<&OffscreenRenderTargetHandle>,
         request_swap_chain_texture: bool,
+    ) -> Result<CommandBuffer, RendererError> {
+        let frame = if request_swap_chain_texture {
+            get_swap_chain_frame(&mut self.swap_chain)?
+        } else {
+            None
+        };
+
+        Ok(self.create_command_buffer(clear_color, offscreen_render_target_handle, frame))
+    }
+
+    /// Starts recording draw commands to the offscreen render target, e.g.
+    /// for screenshots. Unlike `Renderer::begin_command_buffer`, this never
+    /// touches the swap chain and therefore can't fail.
+    pub fn begin_offscreen_command_buffer(
+        &mut self,
+        clear_color: [f64; 4],
+        offscreen_render_target_handle: &OffscreenRenderTargetHandle,
+    ) -> CommandBuffer {
+        self.create_command_buffer(clear_color, Some(offscreen_render_target_handle), None)
+    }
+
+    fn create_command_buffer(
+        &mut self,
+        clear_color: [f64; 4],
+        offscreen_render_target_handle: Option<&OffscreenRenderTargetHandle>,
+        frame: Option<wgpu::SwapChainFrame>,
     ) -> CommandBuffer {
         let render_target = if let Some(handle) = offscreen_render_target_handle {
             &self.offscreen_render_targets[&handle.0]
@@ -765,26 +1228,6 @@ impl Renderer {
             &self.screen_render_target
         };
 
-        let frame = if request_swap_chain_texture {
-            match self.swap_chain.get_current_frame() {
-                Ok(frame) => Some(frame),
-                Err(err) => match err {
-                    wgpu::SwapChainError::Timeout | wgpu::SwapChainError::Outdated => {
-                        log::warn!("GPU swapchain error: {}", err);
-                        None
-                    }
-                    wgpu::SwapChainError::Lost | wgpu::SwapChainError::OutOfMemory => {
-                        // FIXME: @Correctness Try recovering at least for
-                        // wgpu::SwapChainError::Lost
-                        log::error!("Serious GPU swapchain error: {}", err);
-                        panic!("Encountered GPU swapchain error: {}", err);
-                    }
-                },
-            }
-        } else {
-            None
-        };
-
         let encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
@@ -792,6 +1235,8 @@ impl Renderer {
         CommandBuffer {
             swap_chain_needs_clearing: true,
             render_target_needs_clearing: true,
+            shadow_map_needs_clearing: true,
+            viewport: None,
             clear_color,
             device: &self.device,
             queue: &mut self.queue,
@@ -807,6 +1252,45 @@ impl Renderer {
         }
     }
 
+    /// Starts recording draw commands to the secondary window. Draw to its
+    /// render target and blit the render target to the swap chain of the
+    /// window, as when drawing to the main window.
+    pub fn begin_secondary_window_command_buffer(
+        &mut self,
+        clear_color: [f64; 4],
+        handle: &SecondaryWindowHandle,
+    ) -> Result<CommandBuffer, RendererError> {
+        let secondary_window = self
+            .secondary_windows
+            .get_mut(&handle.0)
+            .expect("Secondary window must exist");
+
+        let frame = get_swap_chain_frame(&mut secondary_window.swap_chain)?;
+
+        let encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        Ok(CommandBuffer {
+            swap_chain_needs_clearing: true,
+            render_target_needs_clearing: true,
+            shadow_map_needs_clearing: true,
+            viewport: None,
+            clear_color,
+            device: &self.device,
+            queue: &mut self.queue,
+            encoder: Some(encoder),
+            frame,
+            render_target: &secondary_window.render_target,
+            blit_pass_bind_group_color: &self.blit_pass_bind_group_color,
+            #[cfg(not(feature = "dist"))]
+            blit_pass_bind_group_depth: &self.blit_pass_bind_group_depth,
+            blit_render_pipeline: &self.blit_render_pipeline,
+            scene_renderer: &mut self.scene_renderer,
+            imgui_renderer: &mut self.imgui_renderer,
+        })
+    }
+
     /// Polls the renderer for any pending buffer mappings and resource
     /// cleanups. Should be called periodically.
     pub fn poll<C>(&mut self, mut callback: C)
@@ -865,6 +1349,8 @@ impl Renderer {
 pub struct CommandBuffer<'a> {
     swap_chain_needs_clearing: bool,
     render_target_needs_clearing: bool,
+    shadow_map_needs_clearing: bool,
+    viewport: Option<Viewport>,
     clear_color: [f64; 4],
     device: &'a wgpu::Device,
     queue: &'a mut wgpu::Queue,
@@ -882,7 +1368,11 @@ pub struct CommandBuffer<'a> {
 impl CommandBuffer<'_> {
     /// Update properties of the shadow casting light.
     pub fn set_light(&mut self, light: &DirectionalLight) {
-        self.scene_renderer.set_light(self.queue, light);
+        self.scene_renderer.set_light(
+            self.device,
+            self.encoder.as_mut().expect("Need encoder to record light"),
+            light,
+        );
     }
 
     /// Update camera matrices (projection and view).
@@ -891,8 +1381,32 @@ impl CommandBuffer<'_> {
         projection_matrix: &Matrix4<f32>,
         view_matrix: &Matrix4<f32>,
     ) {
-        self.scene_renderer
-            .set_camera_matrices(self.queue, projection_matrix, view_matrix);
+        self.scene_renderer.set_camera_matrices(
+            self.device,
+            self.encoder
+                .as_mut()
+                .expect("Need encoder to record camera matrices"),
+            projection_matrix,
+            view_matrix,
+        );
+    }
+
+    /// Hide surfaces outside the box given by its minimum and maximum
+    /// corner, or stop hiding them if `None` is given.
+    pub fn set_clip_box(&mut self, clip_box: Option<(Point3<f32>, Point3<f32>)>) {
+        self.scene_renderer.set_clip_box(self.queue, clip_box);
+    }
+
+    /// Restrict subsequent mesh drawing to an area of the render target, or
+    /// lift the restriction if `None` is given.
+    ///
+    /// Changing the viewport starts a new shadow map, so that meshes drawn in
+    /// different viewports do not cast shadows on each other.
+    pub fn set_viewport(&mut self, viewport: Option<Viewport>) {
+        if self.viewport != viewport {
+            self.viewport = viewport;
+            self.shadow_map_needs_clearing = true;
+        }
     }
 
     /// Record a mesh drawing operation targeting the render target to the
@@ -915,7 +1429,9 @@ impl CommandBuffer<'_> {
         P: Iterator<Item = (&'a GpuMeshHandle, Material, bool)> + Clone,
     {
         self.scene_renderer.draw_meshes(
+            self.device,
             self.render_target_needs_clearing,
+            self.shadow_map_needs_clearing,
             self.clear_color,
             self.encoder
                 .as_mut()
@@ -923,35 +1439,65 @@ impl CommandBuffer<'_> {
             self.render_target.msaa_texture_view.as_ref(),
             &self.render_target.color_texture_view,
             &self.render_target.depth_texture_view,
+            self.viewport,
             mesh_props,
         );
 
+        self.render_target_needs_clearing = false;
+        self.shadow_map_needs_clearing = false;
+    }
+
+    /// Record an image drawing operation targeting the render target to the
+    /// command buffer.
+    ///
+    /// Images don't write depth, so they should be drawn after the meshes
+    /// they could occlude.
+    pub fn draw_images_to_render_target<'a, I>(&mut self, images: I)
+    where
+        I: Iterator<Item = &'a GpuImageHandle>,
+    {
+        self.scene_renderer.draw_images(
+            self.render_target_needs_clearing,
+            self.clear_color,
+            self.encoder
+                .as_mut()
+                .expect("Need encoder to record drawing"),
+            self.render_target.msaa_texture_view.as_ref(),
+            &self.render_target.color_texture_view,
+            &self.render_target.depth_texture_view,
+            self.viewport,
+            images,
+        );
+
         self.render_target_needs_clearing = false;
     }
 
     /// Record a UI drawing operation targeting the swap chain to the
     /// command buffer. Textures referenced by the draw data must be
     /// present in the renderer.
-    pub fn draw_ui_to_swap_chain(&mut self, draw_data: &imgui::DrawData) {
+    pub fn draw_ui_to_swap_chain(
+        &mut self,
+        draw_data: &imgui::DrawData,
+    ) -> Result<(), RendererError> {
         if let Some(frame) = &self.frame {
-            self.imgui_renderer
-                .draw_ui(
-                    self.swap_chain_needs_clearing,
-                    self.clear_color,
-                    self.device,
-                    self.queue,
-                    self.encoder
-                        .as_mut()
-                        .expect("Need encoder to record drawing"),
-                    &frame.output.view,
-                    draw_data,
-                )
-                .expect("Imgui drawing failed");
+            self.imgui_renderer.draw_ui(
+                self.swap_chain_needs_clearing,
+                self.clear_color,
+                self.device,
+                self.queue,
+                self.encoder
+                    .as_mut()
+                    .expect("Need encoder to record drawing"),
+                &frame.output.view,
+                draw_data,
+            )?;
 
             self.swap_chain_needs_clearing = false;
         } else {
             log::warn!("Can not draw to absent swap chain texture");
         }
+
+        Ok(())
     }
 
     /// Record a copy operation from the render target to the swap chain.
@@ -1060,6 +1606,14 @@ enum BlitSampler {
     Depth = 1,
 }
 
+/// A window other than the main one, with its own swap chain and render
+/// target.
+struct SecondaryWindow {
+    surface: wgpu::Surface,
+    swap_chain: wgpu::SwapChain,
+    render_target: RenderTarget,
+}
+
 struct RenderTarget {
     width: u32,
     height: u32,
@@ -1083,6 +1637,122 @@ struct OffscreenRenderTargetPendingRead {
     future: Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>>>>,
 }
 
+/// Asks the graphics API to validate all GPU calls. wgpu doesn't expose
+/// the validation layers, but the Vulkan loader and Metal read them from the
+/// environment when the instance is created. The Vulkan validation layers
+/// must be installed, e.g. with the Vulkan SDK. The debug layer of D3D12
+/// can't be enabled this way.
+fn enable_validation_layers() {
+    env::set_var("VK_INSTANCE_LAYERS", "VK_LAYER_KHRONOS_validation");
+    env::set_var("METAL_DEVICE_WRAPPER_TYPE", "1");
+}
+
+/// Scales the dimensions down to fit the maximum dimension, keeping the
+/// aspect ratio.
+fn fit_dimensions(width: u32, height: u32, max_dimension: u32) -> (u32, u32) {
+    let larger_dimension = cmp::max(width, height);
+    if larger_dimension <= max_dimension {
+        return (width, height);
+    }
+
+    let scale = |dimension: u32| {
+        let scaled = u64::from(dimension) * u64::from(max_dimension) / u64::from(larger_dimension);
+        cmp::max(1, cast_u32(scaled))
+    };
+
+    (scale(width), scale(height))
+}
+
+fn backend_list(backend: &Option<GpuBackend>) -> &[GpuBackend] {
+    backend
+        .as_ref()
+        .map(|backend| slice::from_ref(backend))
+        .unwrap_or(DEFAULT_BACKEND_LIST)
+}
+
+/// Finds the adapter matching the adapter info in one of the backends.
+fn find_adapter<H: raw_window_handle::HasRawWindowHandle>(
+    window: &H,
+    gpu_backend_list: &[GpuBackend],
+    adapter_info: &GpuAdapterInfo,
+) -> Option<(wgpu::Instance, wgpu::Surface, wgpu::Adapter)> {
+    gpu_backend_list.iter().copied().find_map(|gpu_backend| {
+        let backend_bit: wgpu::BackendBit = gpu_backend.into();
+        let instance = wgpu::Instance::new(backend_bit);
+        let adapter = instance
+            .enumerate_adapters(backend_bit)
+            .find(|adapter| GpuAdapterInfo::from(adapter.get_info()) == *adapter_info)?;
+        let surface = unsafe { instance.create_surface(window) };
+
+        Some((instance, surface, adapter))
+    })
+}
+
+/// Acquires the next texture of the swap chain to draw into. Returns `None`,
+/// if the texture is momentarily unavailable.
+fn get_swap_chain_frame(
+    swap_chain: &mut wgpu::SwapChain,
+) -> Result<Option<wgpu::SwapChainFrame>, RendererError> {
+    match swap_chain.get_current_frame() {
+        Ok(frame) => Ok(Some(frame)),
+        Err(err) => match err {
+            wgpu::SwapChainError::Timeout | wgpu::SwapChainError::Outdated => {
+                log::warn!("GPU swapchain error: {}", err);
+                Ok(None)
+            }
+            wgpu::SwapChainError::Lost | wgpu::SwapChainError::OutOfMemory => {
+                // FIXME: @Correctness Try recovering at least for
+                // wgpu::SwapChainError::Lost
+                log::error!("Serious GPU swapchain error: {}", err);
+                Err(RendererError::SwapChainFailed(err))
+            }
+        },
+    }
+}
+
+fn create_render_target(
+    device: &wgpu::Device,
+    scene_renderer: &SceneRenderer,
+    msaa: Msaa,
+    width: u32,
+    height: u32,
+) -> RenderTarget {
+    let msaa_texture = if msaa.enabled() {
+        Some(create_msaa_texture(
+            device,
+            width,
+            height,
+            msaa.sample_count(),
+        ))
+    } else {
+        None
+    };
+
+    let color_texture = create_color_texture(device, width, height);
+    let color_texture_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let depth_texture = create_depth_texture(device, width, height, msaa.sample_count());
+
+    let color_texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: scene_renderer.sampled_texture_bind_group_layout(),
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(&color_texture_view),
+        }],
+    });
+
+    RenderTarget {
+        width,
+        height,
+        msaa_texture_view: msaa_texture
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default())),
+        color_texture,
+        color_texture_view,
+        color_texture_bind_group,
+        depth_texture_view: depth_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+    }
+}
+
 fn create_swap_chain(
     device: &wgpu::Device,
     surface: &wgpu::Surface,
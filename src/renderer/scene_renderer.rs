@@ -4,12 +4,14 @@ use std::error;
 use std::fmt;
 use std::io;
 use std::iter;
+use std::mem;
+use std::num::NonZeroU32;
 
 use bitflags::bitflags;
 use nalgebra::{Matrix4, Point3, Vector3};
 use zerocopy::AsBytes as _;
 
-use crate::convert::cast_usize;
+use crate::convert::{cast_u32, cast_u64, cast_usize};
 use crate::mesh::{Face, Mesh};
 
 use super::common;
@@ -19,8 +21,21 @@ static SHADER_COLOR_PASS_FRAG: &[u8] = include_shader!("scene_color_pass.frag.sp
 
 static SHADER_SHADOW_PASS_VERT: &[u8] = include_shader!("scene_shadow_pass.vert.spv");
 
+static SHADER_IMAGE_PASS_VERT: &[u8] = include_shader!("scene_image.vert.spv");
+static SHADER_IMAGE_PASS_FRAG: &[u8] = include_shader!("scene_image.frag.spv");
+
 static TEXTURE_MATCAP: &[u8] = include_bytes!("../../resources/matcap.png");
 
+/// Opacity of surfaces rendered in `Material::ColoredGlass`.
+const GLASS_MATERIAL_ALPHA: f32 = 0.3;
+
+/// Number of shadow map cascades. Must match `SHADOW_CASCADE_COUNT` in
+/// `scene_color_pass.frag`.
+const SHADOW_CASCADE_COUNT: usize = 3;
+/// Blend between logarithmic (1.0) and uniform (0.0) distribution of the
+/// cascade split distances.
+const SHADOW_CASCADE_SPLIT_LOG_WEIGHT: f32 = 0.75;
+
 /// The mesh containing index and vertex data in same-length
 /// format as will be uploaded on the GPU.
 #[derive(Debug, Clone, PartialEq)]
@@ -39,6 +54,7 @@ impl GpuMesh {
     pub fn from_mesh(mesh: &Mesh) -> Self {
         let vertices = mesh.vertices();
         let normals = mesh.normals();
+        let vertex_colors = mesh.vertex_colors();
 
         let faces_len = mesh.faces().len();
         let indices_len_estimate = faces_len * 3;
@@ -85,7 +101,11 @@ impl GpuMesh {
                                 let renderer_index = next_renderer_index;
                                 let position = vertices[cast_usize(vertex_index)];
                                 let normal = normals[cast_usize(normal_index)];
-                                let vertex = Self::vertex(position, normal, barycentric);
+                                let color = vertex_colors.map_or(NO_VERTEX_COLOR, |colors| {
+                                    let [r, g, b] = colors[cast_usize(vertex_index)];
+                                    [r, g, b, 255]
+                                });
+                                let vertex = Self::vertex(position, normal, barycentric, color);
 
                                 vacant.insert(renderer_index);
                                 next_renderer_index += 1;
@@ -141,7 +161,9 @@ impl GpuMesh {
             .copied()
             .zip(vertex_normals.into_iter())
             .zip(barycentric_sequence_iter())
-            .map(|((position, normal), barycentric)| Self::vertex(position, normal, barycentric))
+            .map(|((position, normal), barycentric)| {
+                Self::vertex(position, normal, barycentric, NO_VERTEX_COLOR)
+            })
             .collect();
 
         Self {
@@ -179,7 +201,9 @@ impl GpuMesh {
             .copied()
             .zip(vertex_normals.into_iter())
             .zip(barycentric_sequence_iter())
-            .map(|((position, normal), barycentric)| Self::vertex(position, normal, barycentric))
+            .map(|((position, normal), barycentric)| {
+                Self::vertex(position, normal, barycentric, NO_VERTEX_COLOR)
+            })
             .collect();
 
         Self {
@@ -189,11 +213,17 @@ impl GpuMesh {
         }
     }
 
-    fn vertex(position: Point3<f32>, normal: Vector3<f32>, barycentric: u32) -> GpuMeshVertex {
+    fn vertex(
+        position: Point3<f32>,
+        normal: Vector3<f32>,
+        barycentric: u32,
+        color: [u8; 4],
+    ) -> GpuMeshVertex {
         GpuMeshVertex {
             position: [position[0], position[1], position[2], 1.0],
             normal: [normal[0], normal[1], normal[2], 0.0],
             barycentric,
+            color,
         }
     }
 
@@ -210,6 +240,12 @@ impl GpuMesh {
 #[derive(Debug, PartialEq, Eq)]
 pub struct GpuMeshHandle(u64);
 
+/// Opaque handle to an image stored in scene renderer. Does not implement
+/// `Clone` on purpose. The handle is acquired by uploading the image and has
+/// to be relinquished to destroy it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GpuImageHandle(u64);
+
 #[derive(Debug)]
 pub enum AddMeshError {
     TooManyVertices(usize),
@@ -244,6 +280,62 @@ pub struct Options {
     pub output_depth_attachment_format: wgpu::TextureFormat,
     pub flat_material_color: [f64; 4],
     pub transparent_matcap_shaded_material_alpha: f64,
+    /// Maximum size of mesh vertex and index buffers in bytes kept on the
+    /// GPU. When over budget, the least recently drawn meshes are evicted
+    /// and uploaded again once drawn. Unlimited, if not set.
+    pub mesh_memory_budget: Option<u64>,
+    pub shadow_quality: ShadowQuality,
+}
+
+/// Resolution of each cascade of the shadow map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ShadowQuality {
+    Low,
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    pub const ALL: [ShadowQuality; 3] = [
+        ShadowQuality::Low,
+        ShadowQuality::Medium,
+        ShadowQuality::High,
+    ];
+
+    /// Width and height of each shadow map cascade in texels.
+    pub fn map_size(self) -> u32 {
+        match self {
+            ShadowQuality::Low => 1024,
+            ShadowQuality::Medium => 2048,
+            ShadowQuality::High => 4096,
+        }
+    }
+}
+
+impl Default for ShadowQuality {
+    fn default() -> Self {
+        ShadowQuality::Medium
+    }
+}
+
+impl fmt::Display for ShadowQuality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShadowQuality::Low => write!(f, "Low"),
+            ShadowQuality::Medium => write!(f, "Medium"),
+            ShadowQuality::High => write!(f, "High"),
+        }
+    }
+}
+
+/// A rectangular area of the render target, in physical pixels, that drawing
+/// is restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -255,15 +347,35 @@ pub struct DirectionalLight {
     pub width: f32,
 }
 
+/// Material of a drawn mesh.
+///
+/// Edges are not separate geometry. The fragment shader draws them from the
+/// barycentric coordinates of each vertex within its triangle, so meshes are
+/// drawn with edges in a single draw call.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Material {
     Edges,
+    /// Edges visible through other surfaces. Needs a draw call of its own,
+    /// because it ignores the depth buffer.
     EdgesXray,
     MatcapShaded,
     MatcapShadedEdges,
     TransparentMatcapShaded,
     TransparentMatcapShadedEdges,
+    /// Matcap shaded surface receiving shadows, e.g. to bake the shadows
+    /// into a new matcap.
+    MatcapShadedWithShadows,
     FlatWithShadows,
+    /// Flat surface in the color of the mesh, receiving shadows. The color
+    /// must be set with `SceneRenderer::set_mesh_color` first.
+    ColoredFlat,
+    /// Matcap shaded surface tinted with the color of the mesh. The color
+    /// must be set with `SceneRenderer::set_mesh_color` first.
+    ColoredMatcapShaded,
+    /// Transparent matcap shaded surface tinted with the color of the
+    /// mesh. The color must be set with `SceneRenderer::set_mesh_color`
+    /// first.
+    ColoredGlass,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -280,9 +392,13 @@ impl Material {
             Material::EdgesXray => MaterialTransparency::Xray,
             Material::MatcapShaded => MaterialTransparency::Opaque,
             Material::MatcapShadedEdges => MaterialTransparency::Opaque,
+            Material::MatcapShadedWithShadows => MaterialTransparency::Opaque,
             Material::TransparentMatcapShaded => MaterialTransparency::Transparent,
             Material::TransparentMatcapShadedEdges => MaterialTransparency::Transparent,
             Material::FlatWithShadows => MaterialTransparency::Transparent,
+            Material::ColoredFlat => MaterialTransparency::Opaque,
+            Material::ColoredMatcapShaded => MaterialTransparency::Opaque,
+            Material::ColoredGlass => MaterialTransparency::Transparent,
         }
     }
 }
@@ -291,10 +407,19 @@ impl Material {
 ///
 /// Can be used to upload meshes on the GPU and draw it in the
 /// viewport. Currently supports just shaded (matcap) and wireframe
-/// rendering, and their combinations.
+/// rendering, and their combinations. Images can be uploaded and drawn as
+/// textured planes.
 pub struct SceneRenderer {
     mesh_resources: HashMap<u64, MeshResource>,
     mesh_resources_next_handle: u64,
+    mesh_memory_budget: Option<u64>,
+    /// Size of the vertex and index buffers of meshes resident on the GPU
+    mesh_memory_used: u64,
+    /// Number of `draw_meshes` calls so far, used to find the least recently
+    /// drawn meshes
+    mesh_draw_count: u64,
+    image_resources: HashMap<u64, ImageResource>,
+    image_resources_next_handle: u64,
     /// Working memory for sorting opaque meshes by the projected z coord of
     /// their centroid
     render_list_opaque: Vec<(u64, Material, Point3<f32>)>,
@@ -306,25 +431,40 @@ pub struct SceneRenderer {
     render_list_xray: Vec<(u64, Material, Point3<f32>)>,
     render_list_sort_matrix: Matrix4<f32>,
     matrix_buffer: wgpu::Buffer,
+    clip_box_buffer: wgpu::Buffer,
     matrix_bind_group: wgpu::BindGroup,
     sampler_bind_group: wgpu::BindGroup,
     sampler_bind_group_layout: wgpu::BindGroupLayout,
     sampled_texture_bind_group_layout: wgpu::BindGroupLayout,
+    color_pass_bind_group_layout: wgpu::BindGroupLayout,
     color_pass_bind_group_edges: wgpu::BindGroup,
     color_pass_bind_group_matcap_shaded: wgpu::BindGroup,
     color_pass_bind_group_matcap_shaded_transparent: wgpu::BindGroup,
     color_pass_bind_group_matcap_shaded_edges: wgpu::BindGroup,
     color_pass_bind_group_matcap_shaded_edges_transparent: wgpu::BindGroup,
+    color_pass_bind_group_matcap_shaded_with_shadows: wgpu::BindGroup,
+    color_pass_buffer_flat_with_shadows: wgpu::Buffer,
     color_pass_bind_group_flat_with_shadows: wgpu::BindGroup,
     color_pass_matcap_texture_bind_group: wgpu::BindGroup,
     color_pass_pipeline_opaque_depth_read_write: wgpu::RenderPipeline,
     color_pass_pipeline_transparent_depth_read_only: wgpu::RenderPipeline,
     color_pass_pipeline_transparent_depth_always_pass: wgpu::RenderPipeline,
-    shadow_map_texture_view: wgpu::TextureView,
-    shadow_map_texture_bind_group: wgpu::BindGroup,
-    shadow_pass_buffer: wgpu::Buffer,
-    shadow_pass_bind_group: wgpu::BindGroup,
+    shadow_map_texture_bind_group_layout: wgpu::BindGroupLayout,
+    shadow_map: ShadowMap,
+    shadow_map_size: u32,
+    /// Light space matrix of each cascade, for rendering the cascade
+    shadow_pass_buffers: Vec<wgpu::Buffer>,
+    shadow_pass_bind_groups: Vec<wgpu::BindGroup>,
     shadow_pass_pipeline: wgpu::RenderPipeline,
+    /// Light space matrices of all cascades, for sampling the shadow map
+    shadow_cascades_buffer: wgpu::Buffer,
+    shadow_cascades_bind_group: wgpu::BindGroup,
+    /// The last set light, from which the cascades are fitted to the camera
+    shadow_light: Option<DirectionalLight>,
+    /// The last set projection and view matrices of the camera
+    shadow_camera_matrices: (Matrix4<f32>, Matrix4<f32>),
+    image_pass_bind_group_layout: wgpu::BindGroupLayout,
+    image_pass_pipeline: wgpu::RenderPipeline,
 }
 
 impl SceneRenderer {
@@ -341,6 +481,11 @@ impl SceneRenderer {
         let shadow_pass_vs_source = wgpu::util::make_spirv(SHADER_SHADOW_PASS_VERT);
         let shadow_pass_vs_module = device.create_shader_module(shadow_pass_vs_source);
 
+        let image_pass_vs_source = wgpu::util::make_spirv(SHADER_IMAGE_PASS_VERT);
+        let image_pass_fs_source = wgpu::util::make_spirv(SHADER_IMAGE_PASS_FRAG);
+        let image_pass_vs_module = device.create_shader_module(image_pass_vs_source);
+        let image_pass_fs_module = device.create_shader_module(image_pass_fs_source);
+
         let matrix_buffer_size = common::wgpu_size_of::<MatrixUniforms>();
         let matrix_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: None,
@@ -349,27 +494,51 @@ impl SceneRenderer {
             mapped_at_creation: false,
         });
 
+        let clip_box_buffer = common::create_buffer(
+            device,
+            wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            &[ClipBoxUniforms::disabled()],
+        );
+
         let matrix_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: None,
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStage::VERTEX,
-                    ty: wgpu::BindingType::UniformBuffer {
-                        dynamic: false,
-                        // FIXME: @Optimization Provide this for runtime speedup
-                        min_binding_size: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStage::VERTEX,
+                        ty: wgpu::BindingType::UniformBuffer {
+                            dynamic: false,
+                            // FIXME: @Optimization Provide this for runtime speedup
+                            min_binding_size: None,
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStage::FRAGMENT,
+                        ty: wgpu::BindingType::UniformBuffer {
+                            dynamic: false,
+                            // FIXME: @Optimization Provide this for runtime speedup
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
             });
         let matrix_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
             layout: &matrix_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::Buffer(matrix_buffer.slice(..)),
-            }],
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(matrix_buffer.slice(..)),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer(clip_box_buffer.slice(..)),
+                },
+            ],
         });
 
         let color_pass_buffer_edges = common::create_buffer(
@@ -427,22 +596,25 @@ impl SceneRenderer {
             }],
         );
 
-        let color_pass_buffer_flat_with_shadows = common::create_buffer(
+        let color_pass_buffer_matcap_shaded_with_shadows = common::create_buffer(
             device,
             wgpu::BufferUsage::UNIFORM,
             &[ColorPassUniforms {
-                shading_mode_flat_color: [
-                    options.flat_material_color[0] as f32,
-                    options.flat_material_color[1] as f32,
-                    options.flat_material_color[2] as f32,
-                    options.flat_material_color[3] as f32,
-                ],
+                shading_mode_flat_color: [0.0, 0.0, 0.0, 0.0],
                 shading_mode_edges_color: [0.0, 0.0, 0.0],
                 shading_mode_shaded_alpha: 1.0,
-                shading_mode: ShadingMode::FLAT | ShadingMode::SHADOWED,
+                shading_mode: ShadingMode::SHADED | ShadingMode::SHADOWED,
             }],
         );
 
+        let color_pass_buffer_flat_with_shadows = common::create_buffer(
+            device,
+            wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            &[ColorPassUniforms::flat_with_shadows(
+                options.flat_material_color,
+            )],
+        );
+
         let color_pass_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: None,
@@ -515,6 +687,18 @@ impl SceneRenderer {
                 }],
             });
 
+        let color_pass_bind_group_matcap_shaded_with_shadows =
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &color_pass_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(
+                        color_pass_buffer_matcap_shaded_with_shadows.slice(..),
+                    ),
+                }],
+            });
+
         let color_pass_bind_group_flat_with_shadows =
             device.create_bind_group(&wgpu::BindGroupDescriptor {
                 label: None,
@@ -527,41 +711,6 @@ impl SceneRenderer {
                 }],
             });
 
-        let (matcap_texture_width, matcap_texture_height, matcap_texture_data) = {
-            let cursor = io::Cursor::new(TEXTURE_MATCAP);
-            let decoder = png::Decoder::new(cursor);
-            let (info, mut reader) = decoder
-                .read_info()
-                .expect("Baked matcap texture decoding must succeed");
-
-            let mut buffer = vec![0; info.buffer_size()];
-            reader
-                .next_frame(&mut buffer)
-                .expect("Baked matcap texture decoding must succeed");
-
-            assert_eq!(
-                info.color_type,
-                png::ColorType::RGBA,
-                "Baked matcap texture must be RGBA",
-            );
-
-            (info.width, info.height, buffer)
-        };
-
-        let color_pass_matcap_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: None,
-            size: wgpu::Extent3d {
-                width: matcap_texture_width,
-                height: matcap_texture_height,
-                depth: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8Unorm,
-            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
-        });
-
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: None,
             address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -641,59 +790,39 @@ impl SceneRenderer {
             ],
         });
 
-        let color_pass_matcap_texture_bind_group =
-            device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: None,
-                layout: &sampled_texture_bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(
-                        &color_pass_matcap_texture
-                            .create_view(&wgpu::TextureViewDescriptor::default()),
-                    ),
-                }],
-            });
-
-        common::upload_texture_rgba8_unorm(
+        let (matcap_texture_width, matcap_texture_height, matcap_texture_data) =
+            decode_default_matcap_texture();
+        let color_pass_matcap_texture_bind_group = create_matcap_texture_bind_group(
+            device,
             queue,
-            &color_pass_matcap_texture,
+            &sampled_texture_bind_group_layout,
             matcap_texture_width,
             matcap_texture_height,
             &matcap_texture_data,
         );
 
-        let shadow_pass_buffer_size = common::wgpu_size_of::<ShadowPassUniforms>();
-        let shadow_pass_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size: shadow_pass_buffer_size,
-            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let shadow_map_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: None,
-            size: wgpu::Extent3d {
-                width: 2048,
-                height: 2048,
-                depth: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
-        });
-        let shadow_map_texture_view =
-            shadow_map_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let shadow_map_texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        dimension: wgpu::TextureViewDimension::D2Array,
+                        component_type: wgpu::TextureComponentType::Float,
+                        multisampled: false,
+                    },
+                    count: None,
+                }],
+            });
 
-        let shadow_map_texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: None,
-            layout: &sampled_texture_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: wgpu::BindingResource::TextureView(&shadow_map_texture_view),
-            }],
-        });
+        let shadow_map_size = options.shadow_quality.map_size();
+        let shadow_map = ShadowMap::new(
+            device,
+            &shadow_map_texture_bind_group_layout,
+            &sampled_texture_bind_group_layout,
+            shadow_map_size,
+        );
 
         let shadow_pass_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -709,12 +838,57 @@ impl SceneRenderer {
                     count: None,
                 }],
             });
-        let shadow_pass_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+
+        let shadow_pass_buffers: Vec<_> = (0..SHADOW_CASCADE_COUNT)
+            .map(|_| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: None,
+                    size: common::wgpu_size_of::<ShadowPassUniforms>(),
+                    usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+        let shadow_pass_bind_groups = shadow_pass_buffers
+            .iter()
+            .map(|buffer| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: &shadow_pass_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::Buffer(buffer.slice(..)),
+                    }],
+                })
+            })
+            .collect();
+
+        let shadow_cascades_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer {
+                        dynamic: false,
+                        // FIXME: @Optimization Provide this for runtime speedup
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let shadow_cascades_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: common::wgpu_size_of::<ShadowCascadesUniforms>(),
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let shadow_cascades_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
-            layout: &shadow_pass_bind_group_layout,
+            layout: &shadow_cascades_bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
-                resource: wgpu::BindingResource::Buffer(shadow_pass_buffer.slice(..)),
+                resource: wgpu::BindingResource::Buffer(shadow_cascades_buffer.slice(..)),
             }],
         });
 
@@ -725,8 +899,9 @@ impl SceneRenderer {
             &matrix_bind_group_layout,
             &sampler_bind_group_layout,
             &sampled_texture_bind_group_layout,
+            &shadow_map_texture_bind_group_layout,
             &color_pass_bind_group_layout,
-            &shadow_pass_bind_group_layout,
+            &shadow_cascades_bind_group_layout,
             false,
             true,
             true,
@@ -739,8 +914,9 @@ impl SceneRenderer {
             &matrix_bind_group_layout,
             &sampler_bind_group_layout,
             &sampled_texture_bind_group_layout,
+            &shadow_map_texture_bind_group_layout,
             &color_pass_bind_group_layout,
-            &shadow_pass_bind_group_layout,
+            &shadow_cascades_bind_group_layout,
             true,
             true,
             false,
@@ -753,8 +929,9 @@ impl SceneRenderer {
             &matrix_bind_group_layout,
             &sampler_bind_group_layout,
             &sampled_texture_bind_group_layout,
+            &shadow_map_texture_bind_group_layout,
             &color_pass_bind_group_layout,
-            &shadow_pass_bind_group_layout,
+            &shadow_cascades_bind_group_layout,
             true,
             false,
             false,
@@ -800,52 +977,106 @@ impl SceneRenderer {
             }),
             vertex_state: wgpu::VertexStateDescriptor {
                 index_format: wgpu::IndexFormat::Uint32,
-                vertex_buffers: &[wgpu::VertexBufferDescriptor {
-                    stride: common::wgpu_size_of::<GpuMeshVertex>(),
-                    step_mode: wgpu::InputStepMode::Vertex,
-                    attributes: &[
-                        wgpu::VertexAttributeDescriptor {
+                vertex_buffers: &[
+                    wgpu::VertexBufferDescriptor {
+                        stride: common::wgpu_size_of::<GpuMeshVertex>(),
+                        step_mode: wgpu::InputStepMode::Vertex,
+                        attributes: &[
+                            wgpu::VertexAttributeDescriptor {
+                                offset: 0,
+                                format: wgpu::VertexFormat::Float4,
+                                shader_location: 0,
+                            },
+                            // Note: We don't use other data from `GpuMeshVertex`,
+                            // just the position, we just stride over them.
+                        ],
+                    },
+                    // The translation of the mesh
+                    wgpu::VertexBufferDescriptor {
+                        stride: common::wgpu_size_of::<[f32; 4]>(),
+                        step_mode: wgpu::InputStepMode::Instance,
+                        attributes: &[wgpu::VertexAttributeDescriptor {
                             offset: 0,
                             format: wgpu::VertexFormat::Float4,
-                            shader_location: 0,
-                        },
-                        // Note: We don't use other data from `GpuMeshVertex`,
-                        // just the position, we just stride over them.
-                    ],
-                }],
+                            shader_location: 1,
+                        }],
+                    },
+                ],
             },
             sample_count: 1,
             sample_mask: !0,
             alpha_to_coverage_enabled: false,
         });
 
+        let image_pass_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::UniformBuffer {
+                        dynamic: false,
+                        // FIXME: @Optimization Provide this for runtime speedup
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let image_pass_pipeline = create_image_pass_pipeline(
+            device,
+            &image_pass_vs_module,
+            &image_pass_fs_module,
+            &matrix_bind_group_layout,
+            &sampler_bind_group_layout,
+            &sampled_texture_bind_group_layout,
+            &image_pass_bind_group_layout,
+            options,
+        );
+
         Self {
             mesh_resources: HashMap::new(),
             mesh_resources_next_handle: 0,
+            mesh_memory_budget: options.mesh_memory_budget,
+            mesh_memory_used: 0,
+            mesh_draw_count: 0,
+            image_resources: HashMap::new(),
+            image_resources_next_handle: 0,
             render_list_opaque: Vec::new(),
             render_list_transparent: Vec::new(),
             render_list_xray: Vec::new(),
             render_list_sort_matrix: Matrix4::identity(),
             matrix_buffer,
+            clip_box_buffer,
             matrix_bind_group,
             sampler_bind_group,
             sampler_bind_group_layout,
             sampled_texture_bind_group_layout,
+            color_pass_bind_group_layout,
             color_pass_bind_group_edges,
             color_pass_bind_group_matcap_shaded,
             color_pass_bind_group_matcap_shaded_transparent,
             color_pass_bind_group_matcap_shaded_edges,
             color_pass_bind_group_matcap_shaded_edges_transparent,
+            color_pass_bind_group_matcap_shaded_with_shadows,
+            color_pass_buffer_flat_with_shadows,
             color_pass_bind_group_flat_with_shadows,
             color_pass_matcap_texture_bind_group,
             color_pass_pipeline_opaque_depth_read_write,
             color_pass_pipeline_transparent_depth_read_only,
             color_pass_pipeline_transparent_depth_always_pass,
-            shadow_map_texture_view,
-            shadow_map_texture_bind_group,
-            shadow_pass_buffer,
-            shadow_pass_bind_group,
+            shadow_map_texture_bind_group_layout,
+            shadow_map,
+            shadow_map_size,
+            shadow_pass_buffers,
+            shadow_pass_bind_groups,
             shadow_pass_pipeline,
+            shadow_cascades_buffer,
+            shadow_cascades_bind_group,
+            shadow_light: None,
+            shadow_camera_matrices: (Matrix4::identity(), Matrix4::identity()),
+            image_pass_bind_group_layout,
+            image_pass_pipeline,
         }
     }
 
@@ -861,128 +1092,323 @@ impl SceneRenderer {
         &self.sampled_texture_bind_group_layout
     }
 
+    /// Bind group of the first shadow map cascade as a regular texture, for
+    /// debugging.
     #[cfg(not(feature = "dist"))]
     pub fn shadow_map_texture_bind_group(&self) -> &wgpu::BindGroup {
-        &self.shadow_map_texture_bind_group
+        &self.shadow_map.first_cascade_texture_bind_group
     }
 
     /// Update properties of the shadow casting light.
-    pub fn set_light(&self, queue: &mut wgpu::Queue, light: &DirectionalLight) {
-        let light_projection_matrix = Matrix4::new_orthographic(
-            -light.width / 2.0,
-            light.width / 2.0,
-            -light.width / 2.0,
-            light.width / 2.0,
-            light.min_range,
-            light.max_range,
+    ///
+    /// The shadow map cascades are fitted to the view of the camera and are
+    /// updated with the camera matrices as well. Like the camera matrices,
+    /// the cascades are copied in the command encoder.
+    pub fn set_light(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        light: &DirectionalLight,
+    ) {
+        self.shadow_light = Some(*light);
+        self.update_shadow_cascades(device, encoder);
+    }
+
+    /// Changes the resolution of the shadow map cascades. The shadow map is
+    /// recreated, and casts shadows once the light or camera is set again.
+    pub fn set_shadow_quality(&mut self, device: &wgpu::Device, shadow_quality: ShadowQuality) {
+        let shadow_map_size = shadow_quality.map_size();
+        if shadow_map_size == self.shadow_map_size {
+            return;
+        }
+
+        log::debug!(
+            "Setting shadow map cascade size to {}x{}",
+            shadow_map_size,
+            shadow_map_size,
+        );
+
+        self.shadow_map = ShadowMap::new(
+            device,
+            &self.shadow_map_texture_bind_group_layout,
+            &self.sampled_texture_bind_group_layout,
+            shadow_map_size,
         );
+        self.shadow_map_size = shadow_map_size;
+    }
+
+    fn update_shadow_cascades(&self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        let light = match &self.shadow_light {
+            Some(light) => light,
+            None => return,
+        };
 
-        let light_direction =
-            Vector3::new(light.direction.x, light.direction.y, light.direction.z).normalize();
+        let (projection_matrix, view_matrix) = &self.shadow_camera_matrices;
+        let cascades =
+            compute_shadow_cascades(light, projection_matrix, view_matrix, self.shadow_map_size);
 
-        // Compute light_up so that it is never the same as light_direction. We
-        // pick one vector that doesn't have the same direction as the light and
-        // use that.
-        let light_up = if approx::relative_eq!(Vector3::z().dot(&light_direction).abs(), 1.0) {
-            Vector3::y()
-        } else {
-            Vector3::z()
+        let mut uniforms = ShadowCascadesUniforms {
+            light_space_matrices: [[[0.0; 4]; 4]; SHADOW_CASCADE_COUNT],
         };
+        for (uniform, cascade) in uniforms.light_space_matrices.iter_mut().zip(&cascades) {
+            *uniform = (correction_matrix() * cascade).into();
+        }
+
+        let staging_buffer =
+            common::create_buffer(device, wgpu::BufferUsage::COPY_SRC, &[uniforms]);
 
-        let light_view_matrix = Matrix4::look_at_rh(
-            &light.position,
-            &(light.position + light_direction * light.max_range),
-            &light_up,
+        encoder.copy_buffer_to_buffer(
+            &staging_buffer,
+            0,
+            &self.shadow_cascades_buffer,
+            0,
+            common::wgpu_size_of::<ShadowCascadesUniforms>(),
         );
 
+        // The matrices are laid out without padding, so each cascade's
+        // shadow pass uniforms can be copied from the same staging buffer
+        let matrix_size = common::wgpu_size_of::<ShadowPassUniforms>();
+        for (i, shadow_pass_buffer) in self.shadow_pass_buffers.iter().enumerate() {
+            encoder.copy_buffer_to_buffer(
+                &staging_buffer,
+                cast_u64(i) * matrix_size,
+                shadow_pass_buffer,
+                0,
+                matrix_size,
+            );
+        }
+    }
+
+    /// Changes the color of surfaces rendered in `Material::FlatWithShadows`.
+    pub fn set_flat_material_color(&self, queue: &mut wgpu::Queue, color: [f64; 4]) {
         queue.write_buffer(
-            &self.shadow_pass_buffer,
+            &self.color_pass_buffer_flat_with_shadows,
             0,
-            [ShadowPassUniforms {
-                light_space_matrix: (correction_matrix()
-                    * light_projection_matrix
-                    * light_view_matrix)
-                    .into(),
-            }]
-            .as_bytes(),
+            [ColorPassUniforms::flat_with_shadows(color)].as_bytes(),
         );
     }
 
-    /// Update camera matrices (projection and view).
-    pub fn set_camera_matrices(
+    /// Replaces the matcap texture of all matcap shaded materials by an RGBA8
+    /// image.
+    ///
+    /// The image is sampled by the view space normal of the surface, its
+    /// first row being lit by normals pointing down.
+    pub fn set_matcap_texture_rgba8_unorm(
         &mut self,
+        device: &wgpu::Device,
         queue: &mut wgpu::Queue,
-        projection_matrix: &Matrix4<f32>,
-        view_matrix: &Matrix4<f32>,
+        width: u32,
+        height: u32,
+        data: &[u8],
     ) {
-        self.render_list_sort_matrix = *view_matrix;
+        log::debug!(
+            "Setting matcap texture with dimensions {}x{}",
+            width,
+            height
+        );
 
-        queue.write_buffer(
-            &self.matrix_buffer,
-            0,
-            [MatrixUniforms {
-                projection_matrix: (correction_matrix() * projection_matrix).into(),
-                view_matrix: view_matrix.clone().into(),
-            }]
-            .as_bytes(),
+        self.color_pass_matcap_texture_bind_group = create_matcap_texture_bind_group(
+            device,
+            queue,
+            &self.sampled_texture_bind_group_layout,
+            width,
+            height,
+            data,
         );
     }
 
-    /// Uploads mesh on the GPU.
-    ///
-    /// Whether indexed or not, the data must be in the
-    /// `TRIANGLE_LIST` format. The returned handle can be used to draw
-    /// the mesh, or remove it.
-    pub fn add_mesh(
+    /// Replaces the matcap texture of all matcap shaded materials by the
+    /// built-in matcap.
+    pub fn reset_matcap_texture(&mut self, device: &wgpu::Device, queue: &mut wgpu::Queue) {
+        let (width, height, data) = decode_default_matcap_texture();
+        self.set_matcap_texture_rgba8_unorm(device, queue, width, height, &data);
+    }
+
+    /// Changes the color of a previously uploaded mesh, used when drawing it
+    /// in `Material::ColoredFlat`, `Material::ColoredMatcapShaded` or
+    /// `Material::ColoredGlass`.
+    pub fn set_mesh_color(
         &mut self,
         device: &wgpu::Device,
-        mesh: &GpuMesh,
-    ) -> Result<GpuMeshHandle, AddMeshError> {
-        let handle = GpuMeshHandle(self.mesh_resources_next_handle);
+        queue: &mut wgpu::Queue,
+        handle: &GpuMeshHandle,
+        color: [f64; 3],
+    ) {
+        let color_pass_bind_group_layout = &self.color_pass_bind_group_layout;
+        let mesh_resource = self
+            .mesh_resources
+            .get_mut(&handle.0)
+            .expect("Mesh must be uploaded to set its color");
+
+        let uniforms = [
+            ColorPassUniforms::colored_flat(color),
+            ColorPassUniforms::colored_matcap_shaded(color),
+            ColorPassUniforms::colored_glass(color),
+        ];
 
-        let vertex_data = &mesh.vertex_data[..];
-        let vertex_data_count = u32::try_from(vertex_data.len())
-            .map_err(|_| AddMeshError::TooManyVertices(vertex_data.len()))?;
-        let mesh_resource = if let Some(indices) = &mesh.indices {
-            let index_count = u32::try_from(indices.len())
-                .map_err(|_| AddMeshError::TooManyIndices(indices.len()))?;
+        if let Some(color_resource) = &mesh_resource.color {
+            for (buffer, uniforms) in color_resource.buffers.iter().zip(uniforms.iter()) {
+                queue.write_buffer(buffer, 0, [*uniforms].as_bytes());
+            }
+        } else {
+            let buffers = [
+                common::create_buffer(
+                    device,
+                    wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+                    &[uniforms[0]],
+                ),
+                common::create_buffer(
+                    device,
+                    wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+                    &[uniforms[1]],
+                ),
+                common::create_buffer(
+                    device,
+                    wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+                    &[uniforms[2]],
+                ),
+            ];
+
+            let create_bind_group = |buffer: &wgpu::Buffer| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: None,
+                    layout: color_pass_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::Buffer(buffer.slice(..)),
+                    }],
+                })
+            };
+
+            let bind_group_flat = create_bind_group(&buffers[0]);
+            let bind_group_matcap_shaded = create_bind_group(&buffers[1]);
+            let bind_group_glass = create_bind_group(&buffers[2]);
+
+            mesh_resource.color = Some(MeshColorResource {
+                buffers,
+                bind_group_flat,
+                bind_group_matcap_shaded,
+                bind_group_glass,
+            });
+        }
+    }
 
-            log::debug!(
+    /// Update camera matrices (projection and view).
+    ///
+    /// Unlike other updates, the matrices are copied in the command encoder,
+    /// so that meshes drawn earlier in the same command buffer keep their
+    /// camera, e.g. the two eyes of a stereo image.
+    pub fn set_camera_matrices(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        projection_matrix: &Matrix4<f32>,
+        view_matrix: &Matrix4<f32>,
+    ) {
+        self.render_list_sort_matrix = *view_matrix;
+        self.shadow_camera_matrices = (*projection_matrix, *view_matrix);
+        self.update_shadow_cascades(device, encoder);
+
+        let staging_buffer = common::create_buffer(
+            device,
+            wgpu::BufferUsage::COPY_SRC,
+            &[MatrixUniforms {
+                projection_matrix: (correction_matrix() * projection_matrix).into(),
+                view_matrix: view_matrix.clone().into(),
+            }],
+        );
+
+        encoder.copy_buffer_to_buffer(
+            &staging_buffer,
+            0,
+            &self.matrix_buffer,
+            0,
+            common::wgpu_size_of::<MatrixUniforms>(),
+        );
+    }
+
+    /// Update the clipping box. Surfaces outside the box given by its minimum
+    /// and maximum corner are not drawn, except for surfaces in
+    /// `Material::FlatWithShadows`. The box only affects the color pass, so
+    /// clipped geometry still casts shadows.
+    pub fn set_clip_box(
+        &mut self,
+        queue: &mut wgpu::Queue,
+        clip_box: Option<(Point3<f32>, Point3<f32>)>,
+    ) {
+        let uniforms = match clip_box {
+            Some((min, max)) => ClipBoxUniforms {
+                min: [min.x, min.y, min.z, 1.0],
+                max: [max.x, max.y, max.z, 0.0],
+            },
+            None => ClipBoxUniforms::disabled(),
+        };
+
+        queue.write_buffer(&self.clip_box_buffer, 0, [uniforms].as_bytes());
+    }
+
+    /// Uploads mesh on the GPU.
+    ///
+    /// Whether indexed or not, the data must be in the
+    /// `TRIANGLE_LIST` format. The returned handle can be used to draw
+    /// the mesh, or remove it.
+    ///
+    /// If a mesh memory budget is set, a copy of the mesh is kept in main
+    /// memory, so that the mesh can be evicted from the GPU.
+    pub fn add_mesh(
+        &mut self,
+        device: &wgpu::Device,
+        mesh: &GpuMesh,
+    ) -> Result<GpuMeshHandle, AddMeshError> {
+        let handle = GpuMeshHandle(self.mesh_resources_next_handle);
+
+        let buffers = create_mesh_buffers(device, mesh)?;
+        if let Some((_, index_count)) = &buffers.indices {
+            log::debug!(
                 "Adding mesh {} with {} vertices and {} indices",
                 handle.0,
-                vertex_data_count,
+                buffers.vertices.1,
                 index_count,
             );
-
-            let vertex_buffer =
-                common::create_buffer(device, wgpu::BufferUsage::VERTEX, vertex_data);
-            let index_buffer = common::create_buffer(device, wgpu::BufferUsage::INDEX, indices);
-
-            MeshResource {
-                centroid: mesh.centroid,
-                vertices: (vertex_buffer, vertex_data_count),
-                indices: Some((index_buffer, index_count)),
-            }
         } else {
             log::debug!(
                 "Adding mesh {} with {} vertices",
                 handle.0,
-                vertex_data_count
+                buffers.vertices.1,
             );
+        }
 
-            let vertex_buffer =
-                common::create_buffer(device, wgpu::BufferUsage::VERTEX, vertex_data);
+        let byte_size = buffers.byte_size();
+        self.mesh_memory_used += byte_size;
 
-            MeshResource {
-                centroid: mesh.centroid,
-                vertices: (vertex_buffer, vertex_data_count),
-                indices: None,
-            }
+        // The translation is a per-instance vertex attribute, so that moving
+        // the mesh doesn't require uploading its vertices again
+        let translation_buffer = common::create_buffer(
+            device,
+            wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+            &[[0.0_f32; 4]],
+        );
+
+        let mesh_resource = MeshResource {
+            centroid: mesh.centroid,
+            translation: Vector3::zeros(),
+            translation_buffer,
+            mesh: if self.mesh_memory_budget.is_some() {
+                Some(mesh.clone())
+            } else {
+                None
+            },
+            buffers: Some(buffers),
+            byte_size,
+            last_drawn: self.mesh_draw_count,
+            color: None,
         };
 
         self.mesh_resources.insert(handle.0, mesh_resource);
         self.mesh_resources_next_handle += 1;
 
+        self.evict_meshes();
+
         Ok(handle)
     }
 
@@ -990,49 +1416,353 @@ impl SceneRenderer {
     pub fn remove_mesh(&mut self, handle: GpuMeshHandle) {
         log::debug!("Removing mesh {}", handle.0);
         // Dropping the mesh descriptor here unstreams the buffers from device memory
-        self.mesh_resources.remove(&handle.0);
+        if let Some(mesh_resource) = self.mesh_resources.remove(&handle.0) {
+            if mesh_resource.buffers.is_some() {
+                self.mesh_memory_used -= mesh_resource.byte_size;
+            }
+        }
+    }
+
+    /// Replaces the data of a previously uploaded mesh.
+    ///
+    /// The buffers of the mesh are reused if the new data fits in them, and
+    /// is indexed the same way, otherwise new buffers are created. The color
+    /// of the mesh is kept.
+    pub fn update_mesh(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &mut wgpu::Queue,
+        handle: &GpuMeshHandle,
+        mesh: &GpuMesh,
+    ) -> Result<(), AddMeshError> {
+        let (vertex_count, index_count) = mesh_counts(mesh)?;
+        let mesh_resource = self
+            .mesh_resources
+            .get_mut(&handle.0)
+            .expect("Mesh must be uploaded to update it");
+
+        match &mut mesh_resource.buffers {
+            Some(buffers) if buffers.fits(mesh) => {
+                log::debug!(
+                    "Updating mesh {} in place with {} vertices",
+                    handle.0,
+                    vertex_count,
+                );
+
+                queue.write_buffer(&buffers.vertices.0, 0, mesh.vertex_data.as_bytes());
+                buffers.vertices.1 = vertex_count;
+
+                if let (Some(indices), Some(index_buffer), Some(index_count)) =
+                    (&mesh.indices, &mut buffers.indices, index_count)
+                {
+                    queue.write_buffer(&index_buffer.0, 0, indices.as_bytes());
+                    index_buffer.1 = index_count;
+                }
+            }
+            _ => {
+                log::debug!(
+                    "Updating mesh {} with new buffers for {} vertices",
+                    handle.0,
+                    vertex_count,
+                );
+
+                let buffers = create_mesh_buffers(device, mesh)?;
+                if mesh_resource.buffers.is_some() {
+                    self.mesh_memory_used -= mesh_resource.byte_size;
+                }
+
+                mesh_resource.byte_size = buffers.byte_size();
+                mesh_resource.buffers = Some(buffers);
+                self.mesh_memory_used += mesh_resource.byte_size;
+            }
+        }
+
+        mesh_resource.centroid = mesh.centroid;
+        if mesh_resource.mesh.is_some() {
+            mesh_resource.mesh = Some(mesh.clone());
+        }
+        mesh_resource.last_drawn = self.mesh_draw_count;
+
+        self.evict_meshes();
+
+        Ok(())
+    }
+
+    /// Moves a previously uploaded mesh by the translation when drawing it.
+    /// The translation is kept when the mesh is updated.
+    pub fn set_mesh_translation(
+        &mut self,
+        queue: &mut wgpu::Queue,
+        handle: &GpuMeshHandle,
+        translation: &Vector3<f32>,
+    ) {
+        let mesh_resource = self
+            .mesh_resources
+            .get_mut(&handle.0)
+            .expect("Mesh must be uploaded to set its translation");
+
+        if mesh_resource.translation != *translation {
+            let data = [translation.x, translation.y, translation.z, 0.0];
+            queue.write_buffer(&mesh_resource.translation_buffer, 0, data.as_bytes());
+            mesh_resource.translation = *translation;
+        }
+    }
+
+    /// Size of the vertex and index buffers of meshes currently resident on
+    /// the GPU, in bytes.
+    pub fn mesh_memory_used(&self) -> u64 {
+        self.mesh_memory_used
+    }
+
+    /// Uploads the mesh on the GPU again, if it was evicted, and marks it as
+    /// drawn by the current `draw_meshes` call.
+    fn make_mesh_resident(&mut self, device: &wgpu::Device, raw_handle: u64) {
+        let mesh_resource = self
+            .mesh_resources
+            .get_mut(&raw_handle)
+            .expect("Mesh must be uploaded to draw it");
+
+        mesh_resource.last_drawn = self.mesh_draw_count;
+        if mesh_resource.buffers.is_none() {
+            log::debug!("Uploading evicted mesh {} on the GPU again", raw_handle);
+
+            let mesh = mesh_resource
+                .mesh
+                .as_ref()
+                .expect("Evicted mesh must have a copy in main memory");
+            let buffers =
+                create_mesh_buffers(device, mesh).expect("Mesh was validated when it was added");
+
+            mesh_resource.byte_size = buffers.byte_size();
+            mesh_resource.buffers = Some(buffers);
+            self.mesh_memory_used += mesh_resource.byte_size;
+        }
+    }
+
+    /// Evicts the least recently drawn meshes from the GPU until the meshes
+    /// fit in the mesh memory budget. Meshes drawn by the last `draw_meshes`
+    /// call or added since are never evicted, so the budget may still be
+    /// exceeded, if they don't fit in it by themselves.
+    fn evict_meshes(&mut self) {
+        let budget = match self.mesh_memory_budget {
+            Some(budget) => budget,
+            None => return,
+        };
+
+        if self.mesh_memory_used <= budget {
+            return;
+        }
+
+        let mesh_draw_count = self.mesh_draw_count;
+        let mut candidates: Vec<(u64, u64)> = self
+            .mesh_resources
+            .iter()
+            .filter(|(_, mesh_resource)| {
+                mesh_resource.buffers.is_some() && mesh_resource.last_drawn < mesh_draw_count
+            })
+            .map(|(raw_handle, mesh_resource)| (mesh_resource.last_drawn, *raw_handle))
+            .collect();
+        candidates.sort_unstable();
+
+        for (_, raw_handle) in candidates {
+            if self.mesh_memory_used <= budget {
+                break;
+            }
+
+            log::debug!("Evicting mesh {} from the GPU", raw_handle);
+
+            let mesh_resource = self
+                .mesh_resources
+                .get_mut(&raw_handle)
+                .expect("Eviction candidate must be uploaded");
+            mesh_resource.buffers = None;
+            self.mesh_memory_used -= mesh_resource.byte_size;
+        }
+
+        if self.mesh_memory_used > budget {
+            log::debug!(
+                "Meshes drawn recently take {} bytes, more than the budget of {} bytes",
+                self.mesh_memory_used,
+                budget,
+            );
+        }
+    }
+
+    /// Uploads RGBA8 image on the GPU to be drawn as a textured plane.
+    ///
+    /// The plane is spanned by the `corners` of the image in the order
+    /// bottom-left, bottom-right, top-right, top-left. The returned handle
+    /// can be used to draw the image, change its placement, or remove it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_image(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &mut wgpu::Queue,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        corners: &[Point3<f32>; 4],
+        opacity: f32,
+    ) -> GpuImageHandle {
+        let handle = GpuImageHandle(self.image_resources_next_handle);
+
+        log::debug!(
+            "Adding image {} with dimensions {}x{}",
+            handle.0,
+            width,
+            height
+        );
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+        });
+
+        common::upload_texture_rgba8_unorm(queue, &texture, width, height, data);
+
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.sampled_texture_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(
+                    &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                ),
+            }],
+        });
+
+        let vertex_buffer = common::create_buffer(
+            device,
+            wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+            &image_vertex_data(corners),
+        );
+
+        let image_pass_buffer = common::create_buffer(
+            device,
+            wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            &[ImagePassUniforms::new(opacity)],
+        );
+
+        let image_pass_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.image_pass_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(image_pass_buffer.slice(..)),
+            }],
+        });
+
+        self.image_resources.insert(
+            handle.0,
+            ImageResource {
+                vertex_buffer,
+                image_pass_buffer,
+                texture_bind_group,
+                image_pass_bind_group,
+            },
+        );
+        self.image_resources_next_handle += 1;
+
+        handle
+    }
+
+    /// Moves a previously uploaded image to new `corners` and changes its
+    /// opacity.
+    pub fn set_image_placement(
+        &self,
+        queue: &mut wgpu::Queue,
+        handle: &GpuImageHandle,
+        corners: &[Point3<f32>; 4],
+        opacity: f32,
+    ) {
+        let image_resource = &self.image_resources[&handle.0];
+
+        queue.write_buffer(
+            &image_resource.vertex_buffer,
+            0,
+            image_vertex_data(corners).as_bytes(),
+        );
+        queue.write_buffer(
+            &image_resource.image_pass_buffer,
+            0,
+            [ImagePassUniforms::new(opacity)].as_bytes(),
+        );
+    }
+
+    /// Remove a previously uploaded image from the GPU.
+    pub fn remove_image(&mut self, handle: GpuImageHandle) {
+        log::debug!("Removing image {}", handle.0);
+        // Dropping the image resource here unstreams the texture and buffers
+        // from device memory
+        self.image_resources.remove(&handle.0);
     }
 
     /// Optionally clear color and depth and draw previously uploaded
     /// meshes as one of the commands executed with the `encoder`
     /// to the `color_attachment`.
+    ///
+    /// The shadow map is cleared separately from color and depth, so that
+    /// multiple unrelated scenes can be drawn to the same render target. If
+    /// `viewport` is given, the color pass only draws to that area of the
+    /// render target. Clearing always affects the whole render target.
+    ///
+    /// Meshes evicted from the GPU are uploaded again before drawing.
     #[allow(clippy::too_many_arguments)]
     pub fn draw_meshes<'a, P>(
         &mut self,
+        device: &wgpu::Device,
         color_and_depth_need_clearing: bool,
+        shadow_map_needs_clearing: bool,
         clear_color: [f64; 4],
         encoder: &mut wgpu::CommandEncoder,
         msaa_attachment: Option<&wgpu::TextureView>,
         color_attachment: &wgpu::TextureView,
         depth_attachment: &wgpu::TextureView,
+        viewport: Option<Viewport>,
         mesh_props: P,
     ) where
         P: Iterator<Item = (&'a GpuMeshHandle, Material, bool)> + Clone,
     {
+        self.mesh_draw_count += 1;
+        for (handle, _, _) in mesh_props.clone() {
+            self.make_mesh_resident(device, handle.0);
+        }
+        self.evict_meshes();
+
         self.render_list_opaque.clear();
         self.render_list_transparent.clear();
         self.render_list_xray.clear();
 
         for (handle, material, _) in mesh_props.clone() {
             let mesh_resource = &self.mesh_resources[&handle.0];
+            let centroid = mesh_resource.centroid + mesh_resource.translation;
             match material.transparency() {
                 MaterialTransparency::Opaque => {
-                    self.render_list_opaque
-                        .push((handle.0, material, mesh_resource.centroid));
+                    self.render_list_opaque.push((handle.0, material, centroid));
                 }
                 MaterialTransparency::Transparent => {
                     self.render_list_transparent
-                        .push((handle.0, material, mesh_resource.centroid));
+                        .push((handle.0, material, centroid));
                 }
                 MaterialTransparency::Xray => {
-                    self.render_list_xray
-                        .push((handle.0, material, mesh_resource.centroid));
+                    self.render_list_xray.push((handle.0, material, centroid));
                 }
             }
         }
 
+        // The sorts are stable, so that meshes at the same depth are drawn in
+        // the order they were given, and transparent ones don't flicker
         let render_list_sort_matrix = self.render_list_sort_matrix;
-        self.render_list_opaque.sort_unstable_by(|left, right| {
+        self.render_list_opaque.sort_by(|left, right| {
             let left_point = render_list_sort_matrix.transform_point(&left.2);
             let right_point = render_list_sort_matrix.transform_point(&right.2);
             right_point
@@ -1040,16 +1770,15 @@ impl SceneRenderer {
                 .partial_cmp(&left_point.z)
                 .expect("Failed to compare floats")
         });
-        self.render_list_transparent
-            .sort_unstable_by(|left, right| {
-                let left_point = render_list_sort_matrix.transform_point(&left.2);
-                let right_point = render_list_sort_matrix.transform_point(&right.2);
-                left_point
-                    .z
-                    .partial_cmp(&right_point.z)
-                    .expect("Failed to compare floats")
-            });
-        self.render_list_xray.sort_unstable_by(|left, right| {
+        self.render_list_transparent.sort_by(|left, right| {
+            let left_point = render_list_sort_matrix.transform_point(&left.2);
+            let right_point = render_list_sort_matrix.transform_point(&right.2);
+            left_point
+                .z
+                .partial_cmp(&right_point.z)
+                .expect("Failed to compare floats")
+        });
+        self.render_list_xray.sort_by(|left, right| {
             let left_point = render_list_sort_matrix.transform_point(&left.2);
             let right_point = render_list_sort_matrix.transform_point(&right.2);
             left_point
@@ -1072,6 +1801,17 @@ impl SceneRenderer {
             (wgpu::LoadOp::Load, wgpu::LoadOp::Load)
         };
 
+        let shadow_map_load_op = if shadow_map_needs_clearing {
+            wgpu::LoadOp::Clear(1.0)
+        } else {
+            wgpu::LoadOp::Load
+        };
+
+        for (cascade_texture_view, shadow_pass_bind_group) in self
+            .shadow_map
+            .cascade_texture_views
+            .iter()
+            .zip(&self.shadow_pass_bind_groups)
         {
             // Even if we don't want to cast shadows, we should still clear the
             // shadow map once per command buffer, otherwise there will be
@@ -1079,9 +1819,9 @@ impl SceneRenderer {
             let mut shadow_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 color_attachments: &[],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
-                    attachment: &self.shadow_map_texture_view,
+                    attachment: cascade_texture_view,
                     depth_ops: Some(wgpu::Operations {
-                        load: depth_load_op,
+                        load: shadow_map_load_op,
                         store: true,
                     }),
                     stencil_ops: None,
@@ -1089,9 +1829,9 @@ impl SceneRenderer {
             });
 
             shadow_pass.set_pipeline(&self.shadow_pass_pipeline);
-            shadow_pass.set_bind_group(0, &self.shadow_pass_bind_group, &[]);
+            shadow_pass.set_bind_group(0, shadow_pass_bind_group, &[]);
 
-            for (handle, _, cast_shadows) in mesh_props {
+            for (handle, _, cast_shadows) in mesh_props.clone() {
                 if cast_shadows {
                     record(&self.mesh_resources, handle.0, &mut shadow_pass);
                 }
@@ -1131,6 +1871,18 @@ impl SceneRenderer {
             }),
         });
 
+        if let Some(viewport) = viewport {
+            color_pass.set_viewport(
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+                0.0,
+                1.0,
+            );
+            color_pass.set_scissor_rect(viewport.x, viewport.y, viewport.width, viewport.height);
+        }
+
         // FIXME: Mitigate self-transparency issues. The simplest mitigation
         // would be to draw back and front faces separately, with 2 different
         // pipelines (for culling settings). There are also more advanced
@@ -1154,9 +1906,9 @@ impl SceneRenderer {
                     color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
                     color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
                     color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
-                    color_pass.set_bind_group(3, &self.shadow_map_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
                     color_pass.set_bind_group(4, &self.color_pass_bind_group_matcap_shaded, &[]);
-                    color_pass.set_bind_group(5, &self.shadow_pass_bind_group, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
 
                     record(&self.mesh_resources, *raw_handle, &mut color_pass);
                 }
@@ -1165,13 +1917,54 @@ impl SceneRenderer {
                     color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
                     color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
                     color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
-                    color_pass.set_bind_group(3, &self.shadow_map_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
                     color_pass.set_bind_group(
                         4,
                         &self.color_pass_bind_group_matcap_shaded_edges,
                         &[],
                     );
-                    color_pass.set_bind_group(5, &self.shadow_pass_bind_group, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
+
+                    record(&self.mesh_resources, *raw_handle, &mut color_pass);
+                }
+                Material::MatcapShadedWithShadows => {
+                    color_pass.set_pipeline(&self.color_pass_pipeline_opaque_depth_read_write);
+                    color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
+                    color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
+                    color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
+                    color_pass.set_bind_group(
+                        4,
+                        &self.color_pass_bind_group_matcap_shaded_with_shadows,
+                        &[],
+                    );
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
+
+                    record(&self.mesh_resources, *raw_handle, &mut color_pass);
+                }
+                Material::ColoredFlat => {
+                    let color_resource = mesh_color_resource(&self.mesh_resources, *raw_handle);
+
+                    color_pass.set_pipeline(&self.color_pass_pipeline_opaque_depth_read_write);
+                    color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
+                    color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
+                    color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
+                    color_pass.set_bind_group(4, &color_resource.bind_group_flat, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
+
+                    record(&self.mesh_resources, *raw_handle, &mut color_pass);
+                }
+                Material::ColoredMatcapShaded => {
+                    let color_resource = mesh_color_resource(&self.mesh_resources, *raw_handle);
+
+                    color_pass.set_pipeline(&self.color_pass_pipeline_opaque_depth_read_write);
+                    color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
+                    color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
+                    color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
+                    color_pass.set_bind_group(4, &color_resource.bind_group_matcap_shaded, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
 
                     record(&self.mesh_resources, *raw_handle, &mut color_pass);
                 }
@@ -1186,9 +1979,9 @@ impl SceneRenderer {
                     color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
                     color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
                     color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
-                    color_pass.set_bind_group(3, &self.shadow_map_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
                     color_pass.set_bind_group(4, &self.color_pass_bind_group_edges, &[]);
-                    color_pass.set_bind_group(5, &self.shadow_pass_bind_group, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
 
                     record(&self.mesh_resources, *raw_handle, &mut color_pass);
                 }
@@ -1197,13 +1990,13 @@ impl SceneRenderer {
                     color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
                     color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
                     color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
-                    color_pass.set_bind_group(3, &self.shadow_map_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
                     color_pass.set_bind_group(
                         4,
                         &self.color_pass_bind_group_matcap_shaded_transparent,
                         &[],
                     );
-                    color_pass.set_bind_group(5, &self.shadow_pass_bind_group, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
 
                     record(&self.mesh_resources, *raw_handle, &mut color_pass);
                 }
@@ -1212,13 +2005,13 @@ impl SceneRenderer {
                     color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
                     color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
                     color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
-                    color_pass.set_bind_group(3, &self.shadow_map_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
                     color_pass.set_bind_group(
                         4,
                         &self.color_pass_bind_group_matcap_shaded_edges_transparent,
                         &[],
                     );
-                    color_pass.set_bind_group(5, &self.shadow_pass_bind_group, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
 
                     record(&self.mesh_resources, *raw_handle, &mut color_pass);
                 }
@@ -1227,13 +2020,26 @@ impl SceneRenderer {
                     color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
                     color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
                     color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
-                    color_pass.set_bind_group(3, &self.shadow_map_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
                     color_pass.set_bind_group(
                         4,
                         &self.color_pass_bind_group_flat_with_shadows,
                         &[],
                     );
-                    color_pass.set_bind_group(5, &self.shadow_pass_bind_group, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
+
+                    record(&self.mesh_resources, *raw_handle, &mut color_pass);
+                }
+                Material::ColoredGlass => {
+                    let color_resource = mesh_color_resource(&self.mesh_resources, *raw_handle);
+
+                    color_pass.set_pipeline(&self.color_pass_pipeline_transparent_depth_read_only);
+                    color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
+                    color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
+                    color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
+                    color_pass.set_bind_group(4, &color_resource.bind_group_glass, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
 
                     record(&self.mesh_resources, *raw_handle, &mut color_pass);
                 }
@@ -1249,9 +2055,9 @@ impl SceneRenderer {
                     color_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
                     color_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
                     color_pass.set_bind_group(2, &self.color_pass_matcap_texture_bind_group, &[]);
-                    color_pass.set_bind_group(3, &self.shadow_map_texture_bind_group, &[]);
+                    color_pass.set_bind_group(3, &self.shadow_map.texture_bind_group, &[]);
                     color_pass.set_bind_group(4, &self.color_pass_bind_group_edges, &[]);
-                    color_pass.set_bind_group(5, &self.shadow_pass_bind_group, &[]);
+                    color_pass.set_bind_group(5, &self.shadow_cascades_bind_group, &[]);
 
                     record(&self.mesh_resources, *raw_handle, &mut color_pass);
                 }
@@ -1259,30 +2065,371 @@ impl SceneRenderer {
             }
         }
     }
+
+    /// Optionally clear color and depth and draw previously uploaded images
+    /// as one of the commands executed with the `encoder` to the
+    /// `color_attachment`.
+    ///
+    /// Images are drawn transparent and don't write depth, so they should be
+    /// drawn after meshes they could occlude. Images don't cast or receive
+    /// shadows. If `viewport` is given, only that area of the render target is
+    /// drawn to. Clearing always affects the whole render target.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_images<'a, I>(
+        &self,
+        color_and_depth_need_clearing: bool,
+        clear_color: [f64; 4],
+        encoder: &mut wgpu::CommandEncoder,
+        msaa_attachment: Option<&wgpu::TextureView>,
+        color_attachment: &wgpu::TextureView,
+        depth_attachment: &wgpu::TextureView,
+        viewport: Option<Viewport>,
+        images: I,
+    ) where
+        I: Iterator<Item = &'a GpuImageHandle>,
+    {
+        let (color_load_op, depth_load_op) = if color_and_depth_need_clearing {
+            (
+                wgpu::LoadOp::Clear(wgpu::Color {
+                    r: clear_color[0],
+                    g: clear_color[1],
+                    b: clear_color[2],
+                    a: clear_color[3],
+                }),
+                wgpu::LoadOp::Clear(1.0),
+            )
+        } else {
+            (wgpu::LoadOp::Load, wgpu::LoadOp::Load)
+        };
+
+        let (attachment, resolve_target) = match msaa_attachment {
+            Some(msaa_attachment) => (msaa_attachment, Some(color_attachment)),
+            None => (color_attachment, None),
+        };
+
+        let mut image_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: color_load_op,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachmentDescriptor {
+                attachment: depth_attachment,
+                depth_ops: Some(wgpu::Operations {
+                    load: depth_load_op,
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+
+        if let Some(viewport) = viewport {
+            image_pass.set_viewport(
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+                0.0,
+                1.0,
+            );
+            image_pass.set_scissor_rect(viewport.x, viewport.y, viewport.width, viewport.height);
+        }
+
+        image_pass.set_pipeline(&self.image_pass_pipeline);
+        image_pass.set_bind_group(0, &self.matrix_bind_group, &[]);
+        image_pass.set_bind_group(1, &self.sampler_bind_group, &[]);
+
+        for handle in images {
+            let image_resource = &self.image_resources[&handle.0];
+
+            image_pass.set_bind_group(2, &image_resource.texture_bind_group, &[]);
+            image_pass.set_bind_group(3, &image_resource.image_pass_bind_group, &[]);
+            image_pass.set_vertex_buffer(0, image_resource.vertex_buffer.slice(..));
+            image_pass.draw(0..IMAGE_VERTEX_COUNT, 0..1);
+        }
+    }
+}
+
+fn record<'a, 'b>(
+    mesh_resources: &'a HashMap<u64, MeshResource>,
+    raw_handle: u64,
+    rpass: &'b mut wgpu::RenderPass<'a>,
+) {
+    let mesh_resource = &mesh_resources[&raw_handle];
+    let buffers = mesh_resource
+        .buffers
+        .as_ref()
+        .expect("Mesh must be resident on the GPU to draw it");
+
+    let (vertex_buffer, vertex_count) = &buffers.vertices;
+    rpass.set_vertex_buffer(0, vertex_buffer.slice(..));
+    rpass.set_vertex_buffer(1, mesh_resource.translation_buffer.slice(..));
+
+    if let Some((index_buffer, index_count)) = &buffers.indices {
+        rpass.set_index_buffer(index_buffer.slice(..));
+        rpass.draw_indexed(0..*index_count, 0, 0..1);
+    } else {
+        rpass.draw(0..*vertex_count, 0..1);
+    }
+}
+
+fn decode_default_matcap_texture() -> (u32, u32, Vec<u8>) {
+    let cursor = io::Cursor::new(TEXTURE_MATCAP);
+    let decoder = png::Decoder::new(cursor);
+    let (info, mut reader) = decoder
+        .read_info()
+        .expect("Baked matcap texture decoding must succeed");
+
+    let mut buffer = vec![0; info.buffer_size()];
+    reader
+        .next_frame(&mut buffer)
+        .expect("Baked matcap texture decoding must succeed");
+
+    assert_eq!(
+        info.color_type,
+        png::ColorType::RGBA,
+        "Baked matcap texture must be RGBA",
+    );
+
+    (info.width, info.height, buffer)
+}
+
+fn create_matcap_texture_bind_group(
+    device: &wgpu::Device,
+    queue: &mut wgpu::Queue,
+    sampled_texture_bind_group_layout: &wgpu::BindGroupLayout,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> wgpu::BindGroup {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+    });
+
+    common::upload_texture_rgba8_unorm(queue, &texture, width, height, data);
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: sampled_texture_bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(
+                &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            ),
+        }],
+    })
 }
 
-fn record<'a, 'b>(
-    mesh_resources: &'a HashMap<u64, MeshResource>,
+fn mesh_color_resource(
+    mesh_resources: &HashMap<u64, MeshResource>,
     raw_handle: u64,
-    rpass: &'b mut wgpu::RenderPass<'a>,
-) {
-    let mesh_resource = &mesh_resources[&raw_handle];
+) -> &MeshColorResource {
+    mesh_resources[&raw_handle]
+        .color
+        .as_ref()
+        .expect("Mesh color must be set to draw it in a colored material")
+}
 
-    let (vertex_buffer, vertex_count) = &mesh_resource.vertices;
-    rpass.set_vertex_buffer(0, vertex_buffer.slice(..));
+/// Returns the vertex and index counts of the mesh, if they fit in the
+/// buffers.
+fn mesh_counts(mesh: &GpuMesh) -> Result<(u32, Option<u32>), AddMeshError> {
+    let vertex_count = u32::try_from(mesh.vertex_data.len())
+        .map_err(|_| AddMeshError::TooManyVertices(mesh.vertex_data.len()))?;
+    let index_count = match &mesh.indices {
+        Some(indices) => Some(
+            u32::try_from(indices.len())
+                .map_err(|_| AddMeshError::TooManyIndices(indices.len()))?,
+        ),
+        None => None,
+    };
+
+    Ok((vertex_count, index_count))
+}
 
-    if let Some((index_buffer, index_count)) = &mesh_resource.indices {
-        rpass.set_index_buffer(index_buffer.slice(..));
-        rpass.draw_indexed(0..*index_count, 0, 0..1);
-    } else {
-        rpass.draw(0..*vertex_count, 0..1);
-    }
+fn create_mesh_buffers(device: &wgpu::Device, mesh: &GpuMesh) -> Result<MeshBuffers, AddMeshError> {
+    let (vertex_count, index_count) = mesh_counts(mesh)?;
+
+    // Copy destination, so that the buffers can be reused by `update_mesh`
+    let indices = match (&mesh.indices, index_count) {
+        (Some(indices), Some(index_count)) => {
+            let index_buffer = common::create_buffer(
+                device,
+                wgpu::BufferUsage::INDEX | wgpu::BufferUsage::COPY_DST,
+                indices,
+            );
+
+            Some((index_buffer, index_count))
+        }
+        _ => None,
+    };
+
+    let vertex_buffer = common::create_buffer(
+        device,
+        wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_DST,
+        &mesh.vertex_data,
+    );
+
+    Ok(MeshBuffers {
+        vertices: (vertex_buffer, vertex_count),
+        indices,
+        vertex_capacity: vertex_count,
+        index_capacity: index_count.unwrap_or(0),
+    })
 }
 
 struct MeshResource {
     centroid: Point3<f32>,
+    /// The offset the mesh is drawn at, also written to the translation
+    /// buffer. Not included in the centroid.
+    translation: Vector3<f32>,
+    translation_buffer: wgpu::Buffer,
+    /// Copy of the mesh in main memory to upload it again after eviction.
+    /// Only kept if a mesh memory budget is set.
+    mesh: Option<GpuMesh>,
+    /// The vertex and index buffers, if the mesh is resident on the GPU.
+    buffers: Option<MeshBuffers>,
+    byte_size: u64,
+    /// The `draw_meshes` call that last drew the mesh.
+    last_drawn: u64,
+    color: Option<MeshColorResource>,
+}
+
+/// Vertex and index buffers with the number of vertices and indices in use.
+/// Updated meshes may use less than the capacity of the buffers.
+struct MeshBuffers {
     vertices: (wgpu::Buffer, u32),
     indices: Option<(wgpu::Buffer, u32)>,
+    vertex_capacity: u32,
+    index_capacity: u32,
+}
+
+impl MeshBuffers {
+    /// Whether the data of the mesh can be written to the buffers.
+    fn fits(&self, mesh: &GpuMesh) -> bool {
+        let indices_fit = match (&mesh.indices, &self.indices) {
+            (Some(indices), Some(_)) => indices.len() <= cast_usize(self.index_capacity),
+            (None, None) => true,
+            _ => false,
+        };
+
+        mesh.vertex_data.len() <= cast_usize(self.vertex_capacity) && indices_fit
+    }
+
+    fn byte_size(&self) -> u64 {
+        let vertex_size = cast_u64(mem::size_of::<GpuMeshVertex>());
+        let index_size = cast_u64(mem::size_of::<u32>());
+
+        u64::from(self.vertex_capacity) * vertex_size + u64::from(self.index_capacity) * index_size
+    }
+}
+
+/// Uniforms of the colored materials of a mesh. The buffers are ordered
+/// flat, matcap shaded and glass.
+struct MeshColorResource {
+    buffers: [wgpu::Buffer; 3],
+    bind_group_flat: wgpu::BindGroup,
+    bind_group_matcap_shaded: wgpu::BindGroup,
+    bind_group_glass: wgpu::BindGroup,
+}
+
+struct ImageResource {
+    vertex_buffer: wgpu::Buffer,
+    image_pass_buffer: wgpu::Buffer,
+    texture_bind_group: wgpu::BindGroup,
+    image_pass_bind_group: wgpu::BindGroup,
+}
+
+/// Depth texture with a layer for each shadow cascade.
+struct ShadowMap {
+    /// Views of the individual layers for rendering the cascades
+    cascade_texture_views: Vec<wgpu::TextureView>,
+    /// Binds the view of all layers for sampling the cascades
+    texture_bind_group: wgpu::BindGroup,
+    #[cfg(not(feature = "dist"))]
+    first_cascade_texture_bind_group: wgpu::BindGroup,
+}
+
+impl ShadowMap {
+    fn new(
+        device: &wgpu::Device,
+        shadow_map_texture_bind_group_layout: &wgpu::BindGroupLayout,
+        sampled_texture_bind_group_layout: &wgpu::BindGroupLayout,
+        size: u32,
+    ) -> Self {
+        let cascade_count = cast_u32(SHADOW_CASCADE_COUNT);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth: cascade_count,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED,
+        });
+
+        let cascade_view = |cascade| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: cascade,
+                array_layer_count: NonZeroU32::new(1),
+                ..wgpu::TextureViewDescriptor::default()
+            })
+        };
+
+        let cascade_texture_views = (0..cascade_count).map(&cascade_view).collect();
+
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..wgpu::TextureViewDescriptor::default()
+        });
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: shadow_map_texture_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&texture_view),
+            }],
+        });
+
+        #[cfg(not(feature = "dist"))]
+        let first_cascade_texture_bind_group = {
+            let first_cascade_texture_view = cascade_view(0);
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: sampled_texture_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&first_cascade_texture_view),
+                }],
+            })
+        };
+        #[cfg(feature = "dist")]
+        let _ = sampled_texture_bind_group_layout;
+
+        Self {
+            cascade_texture_views,
+            texture_bind_group,
+            #[cfg(not(feature = "dist"))]
+            first_cascade_texture_bind_group,
+        }
+    }
 }
 
 /// The mesh vertex data as uploaded on the GPU.
@@ -1303,6 +2450,28 @@ struct GpuMeshVertex {
     /// triangle primitive. First bit means `(1, 0, 0)`, second `(0,
     /// 1, 0)`, and the third `(0, 0, 1)`. The rest of the bits are 0.
     barycentric: u32,
+
+    /// Color of the vertex tinting the shaded color. Zero alpha
+    /// means the vertex has no color.
+    color: [u8; 4],
+}
+
+/// Color of vertices of meshes without vertex colors.
+const NO_VERTEX_COLOR: [u8; 4] = [0, 0, 0, 0];
+
+/// Images are drawn as two non-indexed triangles.
+const IMAGE_VERTEX_COUNT: u32 = 6;
+
+/// The image plane vertex data as uploaded on the GPU.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, zerocopy::AsBytes)]
+struct GpuImageVertex {
+    /// The position of the vertex in world-space. Last component is 1.
+    position: [f32; 4],
+
+    /// The texture coordinates of the vertex. The top-left corner of the
+    /// image is `(0, 0)`.
+    tex_coords: [f32; 2],
 }
 
 #[repr(C)]
@@ -1312,6 +2481,24 @@ struct MatrixUniforms {
     view_matrix: [[f32; 4]; 4],
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, zerocopy::AsBytes)]
+struct ClipBoxUniforms {
+    /// The minimum corner of the box. The w component is 1.0 if the
+    /// clipping box is enabled.
+    min: [f32; 4],
+    max: [f32; 4],
+}
+
+impl ClipBoxUniforms {
+    fn disabled() -> Self {
+        Self {
+            min: [0.0; 4],
+            max: [0.0; 4],
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, zerocopy::AsBytes)]
 struct ColorPassUniforms {
@@ -1321,6 +2508,45 @@ struct ColorPassUniforms {
     shading_mode: ShadingMode,
 }
 
+impl ColorPassUniforms {
+    fn flat_with_shadows(flat_color: [f64; 4]) -> Self {
+        Self {
+            shading_mode_flat_color: [
+                flat_color[0] as f32,
+                flat_color[1] as f32,
+                flat_color[2] as f32,
+                flat_color[3] as f32,
+            ],
+            shading_mode_edges_color: [0.0, 0.0, 0.0],
+            shading_mode_shaded_alpha: 1.0,
+            shading_mode: ShadingMode::FLAT | ShadingMode::SHADOWED | ShadingMode::UNCLIPPED,
+        }
+    }
+
+    fn colored_flat(color: [f64; 3]) -> Self {
+        Self {
+            shading_mode: ShadingMode::FLAT | ShadingMode::SHADOWED,
+            ..Self::flat_with_shadows([color[0], color[1], color[2], 1.0])
+        }
+    }
+
+    fn colored_matcap_shaded(color: [f64; 3]) -> Self {
+        Self {
+            shading_mode_flat_color: [color[0] as f32, color[1] as f32, color[2] as f32, 1.0],
+            shading_mode_edges_color: [0.0, 0.0, 0.0],
+            shading_mode_shaded_alpha: 1.0,
+            shading_mode: ShadingMode::SHADED | ShadingMode::TINTED,
+        }
+    }
+
+    fn colored_glass(color: [f64; 3]) -> Self {
+        Self {
+            shading_mode_shaded_alpha: GLASS_MATERIAL_ALPHA,
+            ..Self::colored_matcap_shaded(color)
+        }
+    }
+}
+
 bitflags! {
     #[repr(C)]
     #[derive(zerocopy::AsBytes)]
@@ -1329,6 +2555,10 @@ bitflags! {
         const SHADED = 0x02;
         const EDGES = 0x04;
         const SHADOWED = 0x08;
+        /// Tints the shaded color with the flat color.
+        const TINTED = 0x10;
+        /// Draws the surface even outside the clipping box.
+        const UNCLIPPED = 0x20;
     }
 }
 
@@ -1338,6 +2568,31 @@ struct ShadowPassUniforms {
     light_space_matrix: [[f32; 4]; 4],
 }
 
+/// Light space matrices of all shadow cascades, ordered from the closest to
+/// the camera. The layout matches an std140 `mat4` array.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, zerocopy::AsBytes)]
+struct ShadowCascadesUniforms {
+    light_space_matrices: [[[f32; 4]; 4]; SHADOW_CASCADE_COUNT],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, zerocopy::AsBytes)]
+struct ImagePassUniforms {
+    opacity: f32,
+    /// Pads the struct to the 16 byte size of the std140 uniform block.
+    _padding: [f32; 3],
+}
+
+impl ImagePassUniforms {
+    fn new(opacity: f32) -> Self {
+        Self {
+            opacity,
+            _padding: [0.0; 3],
+        }
+    }
+}
+
 /// Returns a matrix that transforms left-handed OpenGL NDC into left-handed
 /// WebGPU NDC.
 ///
@@ -1357,6 +2612,158 @@ fn correction_matrix() -> Matrix4<f32> {
     opengl_to_webgpu_matrix
 }
 
+/// Computes the light space matrix of each shadow cascade, ordered from the
+/// closest to the camera.
+///
+/// All but the last cascade cover a slice of the camera view frustum, the
+/// closer slices being shorter. The last cascade covers the whole light, so
+/// that everything lit casts shadows, even outside the fitted slices. The
+/// cascades keep the depth range of the light, so that casters outside the
+/// view still cast shadows into it.
+///
+/// Cascades are fitted to the bounding sphere of their frustum slice and
+/// their position is snapped to whole texels, so that their shadows don't
+/// shimmer as the camera moves and rotates.
+fn compute_shadow_cascades(
+    light: &DirectionalLight,
+    projection_matrix: &Matrix4<f32>,
+    view_matrix: &Matrix4<f32>,
+    shadow_map_size: u32,
+) -> [Matrix4<f32>; SHADOW_CASCADE_COUNT] {
+    let light_direction =
+        Vector3::new(light.direction.x, light.direction.y, light.direction.z).normalize();
+
+    // Compute light_up so that it is never the same as light_direction. We
+    // pick one vector that doesn't have the same direction as the light and
+    // use that.
+    let light_up = if approx::relative_eq!(Vector3::z().dot(&light_direction).abs(), 1.0) {
+        Vector3::y()
+    } else {
+        Vector3::z()
+    };
+
+    let light_view_matrix = Matrix4::look_at_rh(
+        &light.position,
+        &(light.position + light_direction * light.max_range),
+        &light_up,
+    );
+
+    let light_projection_matrix = Matrix4::new_orthographic(
+        -light.width / 2.0,
+        light.width / 2.0,
+        -light.width / 2.0,
+        light.width / 2.0,
+        light.min_range,
+        light.max_range,
+    );
+
+    let whole_light_matrix = light_projection_matrix * light_view_matrix;
+    let mut cascades = [whole_light_matrix; SHADOW_CASCADE_COUNT];
+
+    let inverse_view_projection_matrix = match (projection_matrix * view_matrix).try_inverse() {
+        Some(matrix) => matrix,
+        None => return cascades,
+    };
+
+    // The corners of the near and the far plane of the camera, pairwise
+    // connected by the edges of the view frustum
+    let unproject = |x, y, z| inverse_view_projection_matrix.transform_point(&Point3::new(x, y, z));
+    let near_corners = [
+        unproject(-1.0, -1.0, -1.0),
+        unproject(1.0, -1.0, -1.0),
+        unproject(1.0, 1.0, -1.0),
+        unproject(-1.0, 1.0, -1.0),
+    ];
+    let far_corners = [
+        unproject(-1.0, -1.0, 1.0),
+        unproject(1.0, -1.0, 1.0),
+        unproject(1.0, 1.0, 1.0),
+        unproject(-1.0, 1.0, 1.0),
+    ];
+
+    let view_depth = |point: &Point3<f32>| -view_matrix.transform_point(point).z;
+    let near_depth = view_depth(&near_corners[0]);
+    let far_depth = view_depth(&far_corners[0]);
+    if !near_depth.is_finite() || !far_depth.is_finite() || far_depth <= near_depth {
+        return cascades;
+    }
+
+    // Only the part of the view reaching the light volume needs shadows
+    let light_center = light.position + light_direction * (light.min_range + light.max_range) / 2.0;
+    let light_radius = Vector3::new(
+        light.width / 2.0,
+        light.width / 2.0,
+        (light.max_range - light.min_range) / 2.0,
+    )
+    .norm();
+    let shadow_near_depth = near_depth.max(f32::EPSILON);
+    let shadow_far_depth = (view_depth(&light_center) + light_radius)
+        .min(far_depth)
+        .max(shadow_near_depth);
+
+    // The practical split scheme, blending logarithmic and uniform splits
+    let split_depth = |split: usize| {
+        let fraction = split as f32 / SHADOW_CASCADE_COUNT as f32;
+        let log_depth = shadow_near_depth * (shadow_far_depth / shadow_near_depth).powf(fraction);
+        let uniform_depth = shadow_near_depth + (shadow_far_depth - shadow_near_depth) * fraction;
+        SHADOW_CASCADE_SPLIT_LOG_WEIGHT * log_depth
+            + (1.0 - SHADOW_CASCADE_SPLIT_LOG_WEIGHT) * uniform_depth
+    };
+
+    let frustum_point = |corner: usize, depth: f32| {
+        let t = (depth - near_depth) / (far_depth - near_depth);
+        near_corners[corner] + (far_corners[corner] - near_corners[corner]) * t
+    };
+
+    for (split, cascade) in cascades
+        .iter_mut()
+        .take(SHADOW_CASCADE_COUNT - 1)
+        .enumerate()
+    {
+        let slice_near_depth = split_depth(split);
+        let slice_far_depth = split_depth(split + 1);
+
+        let slice_corners: Vec<Point3<f32>> = (0..4)
+            .map(|corner| frustum_point(corner, slice_near_depth))
+            .chain((0..4).map(|corner| frustum_point(corner, slice_far_depth)))
+            .collect();
+
+        let center = Point3::from(
+            slice_corners
+                .iter()
+                .fold(Vector3::zeros(), |sum, corner| sum + corner.coords)
+                / slice_corners.len() as f32,
+        );
+        let radius = slice_corners
+            .iter()
+            .map(|corner| nalgebra::distance(&center, corner))
+            .fold(0.0, f32::max);
+
+        // A cascade larger than the whole light is no sharper than it
+        if !radius.is_finite() || 2.0 * radius >= light.width {
+            continue;
+        }
+
+        let texel_size = 2.0 * radius / shadow_map_size as f32;
+        let light_space_center = light_view_matrix.transform_point(&center);
+        let x = (light_space_center.x / texel_size).floor() * texel_size;
+        let y = (light_space_center.y / texel_size).floor() * texel_size;
+
+        let cascade_projection_matrix = Matrix4::new_orthographic(
+            x - radius,
+            x + radius,
+            y - radius,
+            y + radius,
+            light.min_range,
+            light.max_range,
+        );
+
+        *cascade = cascade_projection_matrix * light_view_matrix;
+    }
+
+    cascades
+}
+
 /// Produces an infinite iterator over bit-packed barycentric
 /// coordinates of triangle vertices.
 ///
@@ -1374,6 +2781,32 @@ fn barycentric_sequence_iter() -> impl Iterator<Item = u32> {
     })
 }
 
+/// Produces vertices of the two triangles forming the image plane from its
+/// corners ordered bottom-left, bottom-right, top-right, top-left.
+fn image_vertex_data(corners: &[Point3<f32>; 4]) -> [GpuImageVertex; IMAGE_VERTEX_COUNT as usize] {
+    let vertex = |corner_index: usize, tex_coords: [f32; 2]| {
+        let corner = corners[corner_index];
+        GpuImageVertex {
+            position: [corner.x, corner.y, corner.z, 1.0],
+            tex_coords,
+        }
+    };
+
+    let bottom_left = vertex(0, [0.0, 1.0]);
+    let bottom_right = vertex(1, [1.0, 1.0]);
+    let top_right = vertex(2, [1.0, 0.0]);
+    let top_left = vertex(3, [0.0, 0.0]);
+
+    [
+        bottom_left,
+        bottom_right,
+        top_right,
+        bottom_left,
+        top_right,
+        top_left,
+    ]
+}
+
 #[allow(clippy::too_many_arguments)]
 fn create_color_pass_pipeline(
     device: &wgpu::Device,
@@ -1382,8 +2815,9 @@ fn create_color_pass_pipeline(
     matrix_bind_group_layout: &wgpu::BindGroupLayout,
     sampler_bind_group_layout: &wgpu::BindGroupLayout,
     sampled_texture_bind_group_layout: &wgpu::BindGroupLayout,
+    shadow_map_texture_bind_group_layout: &wgpu::BindGroupLayout,
     color_pass_bind_group_layout: &wgpu::BindGroupLayout,
-    shadow_pass_bind_group_layout: &wgpu::BindGroupLayout,
+    shadow_cascades_bind_group_layout: &wgpu::BindGroupLayout,
     transparency: bool,
     depth_read: bool,
     depth_write: bool,
@@ -1395,9 +2829,9 @@ fn create_color_pass_pipeline(
             &matrix_bind_group_layout,
             &sampler_bind_group_layout,
             &sampled_texture_bind_group_layout, // matcap
-            &sampled_texture_bind_group_layout, // shadow map
+            &shadow_map_texture_bind_group_layout,
             &color_pass_bind_group_layout,
-            &shadow_pass_bind_group_layout,
+            &shadow_cascades_bind_group_layout,
         ],
         push_constant_ranges: &[],
     });
@@ -1453,10 +2887,115 @@ fn create_color_pass_pipeline(
             },
             stencil: wgpu::StencilStateDescriptor::default(),
         }),
+        vertex_state: wgpu::VertexStateDescriptor {
+            index_format: wgpu::IndexFormat::Uint32,
+            vertex_buffers: &[
+                wgpu::VertexBufferDescriptor {
+                    stride: common::wgpu_size_of::<GpuMeshVertex>(),
+                    step_mode: wgpu::InputStepMode::Vertex,
+                    attributes: &[
+                        wgpu::VertexAttributeDescriptor {
+                            offset: 0,
+                            format: wgpu::VertexFormat::Float4,
+                            shader_location: 0,
+                        },
+                        wgpu::VertexAttributeDescriptor {
+                            offset: common::wgpu_size_of::<[f32; 4]>(), // 4 bytes * 4 components * 1 attrib
+                            format: wgpu::VertexFormat::Float4,
+                            shader_location: 1,
+                        },
+                        wgpu::VertexAttributeDescriptor {
+                            offset: common::wgpu_size_of::<[f32; 4]>() * 2, // 4 bytes * 4 components * 2 attribs
+                            format: wgpu::VertexFormat::Uint,
+                            shader_location: 2,
+                        },
+                        wgpu::VertexAttributeDescriptor {
+                            // 4 bytes * 4 components * 2 attribs + 4 bytes * 1 attrib
+                            offset: common::wgpu_size_of::<[f32; 4]>() * 2
+                                + common::wgpu_size_of::<u32>(),
+                            format: wgpu::VertexFormat::Uchar4Norm,
+                            shader_location: 3,
+                        },
+                    ],
+                },
+                // The translation of the mesh
+                wgpu::VertexBufferDescriptor {
+                    stride: common::wgpu_size_of::<[f32; 4]>(),
+                    step_mode: wgpu::InputStepMode::Instance,
+                    attributes: &[wgpu::VertexAttributeDescriptor {
+                        offset: 0,
+                        format: wgpu::VertexFormat::Float4,
+                        shader_location: 4,
+                    }],
+                },
+            ],
+        },
+        sample_count: options.sample_count,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_image_pass_pipeline(
+    device: &wgpu::Device,
+    vs_module: &wgpu::ShaderModule,
+    fs_module: &wgpu::ShaderModule,
+    matrix_bind_group_layout: &wgpu::BindGroupLayout,
+    sampler_bind_group_layout: &wgpu::BindGroupLayout,
+    sampled_texture_bind_group_layout: &wgpu::BindGroupLayout,
+    image_pass_bind_group_layout: &wgpu::BindGroupLayout,
+    options: Options,
+) -> wgpu::RenderPipeline {
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: None,
+        bind_group_layouts: &[
+            &matrix_bind_group_layout,
+            &sampler_bind_group_layout,
+            &sampled_texture_bind_group_layout, // image
+            &image_pass_bind_group_layout,
+        ],
+        push_constant_ranges: &[],
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: None,
+        layout: Some(&pipeline_layout),
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: fs_module,
+            entry_point: "main",
+        }),
+        // Images are visible from both sides.
+        rasterization_state: None,
+        primitive_topology: wgpu::PrimitiveTopology::TriangleList,
+        color_states: &[wgpu::ColorStateDescriptor {
+            format: options.output_color_attachment_format,
+            alpha_blend: wgpu::BlendDescriptor {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            },
+            color_blend: wgpu::BlendDescriptor {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            },
+            write_mask: wgpu::ColorWrite::ALL,
+        }],
+        depth_stencil_state: Some(wgpu::DepthStencilStateDescriptor {
+            format: options.output_depth_attachment_format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilStateDescriptor::default(),
+        }),
         vertex_state: wgpu::VertexStateDescriptor {
             index_format: wgpu::IndexFormat::Uint32,
             vertex_buffers: &[wgpu::VertexBufferDescriptor {
-                stride: common::wgpu_size_of::<GpuMeshVertex>(),
+                stride: common::wgpu_size_of::<GpuImageVertex>(),
                 step_mode: wgpu::InputStepMode::Vertex,
                 attributes: &[
                     wgpu::VertexAttributeDescriptor {
@@ -1466,14 +3005,9 @@ fn create_color_pass_pipeline(
                     },
                     wgpu::VertexAttributeDescriptor {
                         offset: common::wgpu_size_of::<[f32; 4]>(), // 4 bytes * 4 components * 1 attrib
-                        format: wgpu::VertexFormat::Float4,
+                        format: wgpu::VertexFormat::Float2,
                         shader_location: 1,
                     },
-                    wgpu::VertexAttributeDescriptor {
-                        offset: common::wgpu_size_of::<[f32; 4]>() * 2, // 4 bytes * 4 components * 2 attribs
-                        format: wgpu::VertexFormat::Uint,
-                        shader_location: 2,
-                    },
                 ],
             }],
         },
@@ -1570,16 +3104,19 @@ mod tests {
                     position: [-0.3, -0.5, 0.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
                     barycentric: 0x01,
+                    color: NO_VERTEX_COLOR,
                 },
                 GpuMeshVertex {
                     position: [0.3, -0.5, 0.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
                     barycentric: 0x02,
+                    color: NO_VERTEX_COLOR,
                 },
                 GpuMeshVertex {
                     position: [0.0, 0.5, 0.0, 1.0],
                     normal: [0.0, 0.0, 1.0, 0.0],
                     barycentric: 0x04,
+                    color: NO_VERTEX_COLOR,
                 },
             ]
         );
@@ -1596,16 +3133,19 @@ mod tests {
                 position: [-0.3, -0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x01,
+                color: NO_VERTEX_COLOR,
             },
             GpuMeshVertex {
                 position: [0.3, -0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x02,
+                color: NO_VERTEX_COLOR,
             },
             GpuMeshVertex {
                 position: [0.0, 0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x04,
+                color: NO_VERTEX_COLOR,
             },
         ];
 
@@ -1675,16 +3215,19 @@ mod tests {
                 position: [-0.3, -0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x01,
+                color: NO_VERTEX_COLOR,
             },
             GpuMeshVertex {
                 position: [0.3, -0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x02,
+                color: NO_VERTEX_COLOR,
             },
             GpuMeshVertex {
                 position: [0.0, 0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x04,
+                color: NO_VERTEX_COLOR,
             },
         ];
 
@@ -1692,6 +3235,54 @@ mod tests {
         assert_eq!(mesh.indices, Some(vec![0, 1, 2]));
     }
 
+    #[test]
+    fn test_gpu_mesh_from_mesh_uses_vertex_colors() {
+        let mesh = triangle_mesh_same_len().with_vertex_colors(vec![
+            [255, 0, 0],
+            [0, 255, 0],
+            [0, 0, 255],
+        ]);
+        let gpu_mesh = GpuMesh::from_mesh(&mesh);
+
+        let colors: Vec<_> = gpu_mesh
+            .vertex_data
+            .iter()
+            .map(|vertex| vertex.color)
+            .collect();
+
+        assert_eq!(
+            colors,
+            vec![[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]],
+        );
+    }
+
+    #[test]
+    fn test_image_vertex_data_maps_corners_to_tex_coords() {
+        let corners = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 0.0),
+            Point3::new(2.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ];
+
+        let vertex_data = image_vertex_data(&corners);
+
+        let tex_coords: Vec<_> = vertex_data.iter().map(|vertex| vertex.tex_coords).collect();
+        assert_eq!(
+            tex_coords,
+            vec![
+                [0.0, 1.0],
+                [1.0, 1.0],
+                [1.0, 0.0],
+                [0.0, 1.0],
+                [1.0, 0.0],
+                [0.0, 0.0],
+            ],
+        );
+        assert_eq!(vertex_data[2].position, [2.0, 1.0, 0.0, 1.0]);
+        assert_eq!(vertex_data[5].position, [0.0, 1.0, 0.0, 1.0]);
+    }
+
     #[test]
     fn test_gpu_mesh_from_mesh_duplicates_normals_in_var_len_mesh() {
         let mesh = GpuMesh::from_mesh(&triangle_mesh_var_len());
@@ -1701,20 +3292,64 @@ mod tests {
                 position: [-0.3, -0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x01,
+                color: NO_VERTEX_COLOR,
             },
             GpuMeshVertex {
                 position: [0.3, -0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x02,
+                color: NO_VERTEX_COLOR,
             },
             GpuMeshVertex {
                 position: [0.0, 0.5, 0.0, 1.0],
                 normal: [0.0, 0.0, 1.0, 0.0],
                 barycentric: 0x04,
+                color: NO_VERTEX_COLOR,
             },
         ];
 
         assert_eq!(mesh.vertex_data, expected_vertex_data);
         assert_eq!(mesh.indices, Some(vec![0, 1, 2]));
     }
+
+    fn site_light() -> DirectionalLight {
+        DirectionalLight {
+            position: Point3::new(0.0, 0.0, 55.0),
+            direction: Vector3::new(0.0, 0.0, -110.0),
+            min_range: 0.001,
+            max_range: 110.0,
+            width: 220.0,
+        }
+    }
+
+    #[test]
+    fn test_compute_shadow_cascades_sharpens_cascades_close_to_camera() {
+        let projection_matrix = Matrix4::new_perspective(1.0, 1.0, 0.1, 1000.0);
+        let view_matrix = Matrix4::look_at_rh(
+            &Point3::new(0.0, -50.0, 20.0),
+            &Point3::origin(),
+            &Vector3::z(),
+        );
+
+        let cascades =
+            compute_shadow_cascades(&site_light(), &projection_matrix, &view_matrix, 2048);
+
+        // The x scale of an orthographic projection is inversely
+        // proportional to its width
+        assert!(cascades[0][(0, 0)] > cascades[1][(0, 0)]);
+        assert!(cascades[1][(0, 0)] > cascades[2][(0, 0)]);
+        approx::assert_relative_eq!(cascades[2][(0, 0)], 2.0 / 220.0);
+    }
+
+    #[test]
+    fn test_compute_shadow_cascades_covers_whole_light_for_degenerate_camera() {
+        let light = site_light();
+        let cascades =
+            compute_shadow_cascades(&light, &Matrix4::zeros(), &Matrix4::identity(), 2048);
+
+        for cascade in &cascades[1..] {
+            assert_eq!(*cascade, cascades[0]);
+        }
+        approx::assert_relative_eq!(cascades[0][(0, 0)], 2.0 / light.width);
+    }
 }
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::bounding_box::BoundingBox;
+
+/// Bounding box of the values displayed in the scene, maintained
+/// incrementally as values are added and removed.
+///
+/// Adding a value only grows the bounding box. Removing a value only requires
+/// recomputing the bounding box from the remaining values if the removed value
+/// reached its boundary. Used values count only while they are drawn.
+#[derive(Debug, Clone)]
+pub struct SceneBounds<K> {
+    values: HashMap<K, (bool, BoundingBox<f32>)>,
+    draw_used_values: bool,
+    bounding_box: Option<BoundingBox<f32>>,
+    /// Whether the bounding box needs to be recomputed from the values.
+    stale: bool,
+}
+
+impl<K: Eq + Hash> SceneBounds<K> {
+    pub fn new(draw_used_values: bool) -> Self {
+        Self {
+            values: HashMap::new(),
+            draw_used_values,
+            bounding_box: None,
+            stale: false,
+        }
+    }
+
+    /// Adds the contribution of a value, replacing its previous one, if any.
+    pub fn insert(&mut self, key: K, used: bool, bounding_box: BoundingBox<f32>) {
+        if let Some(previous) = self.values.insert(key, (used, bounding_box)) {
+            self.forget(previous);
+        }
+
+        if self.counts(used) && !self.stale {
+            self.bounding_box = Some(match self.bounding_box {
+                Some(scene_bounding_box) => {
+                    BoundingBox::union([scene_bounding_box, bounding_box].iter().copied())
+                        .expect("Must produce a bounding box for non-empty iterator")
+                }
+                None => bounding_box,
+            });
+        }
+    }
+
+    /// Removes the contribution of a value, if any.
+    pub fn remove(&mut self, key: &K) {
+        if let Some(previous) = self.values.remove(key) {
+            self.forget(previous);
+        }
+    }
+
+    /// Changes whether the value is used, e.g. when it is pinned.
+    pub fn set_used(&mut self, key: &K, used: bool) {
+        let draw_used_values = self.draw_used_values;
+        if let Some((value_used, _)) = self.values.get_mut(key) {
+            if *value_used != used {
+                *value_used = used;
+                self.stale |= !draw_used_values;
+            }
+        }
+    }
+
+    pub fn set_draw_used_values(&mut self, draw_used_values: bool) {
+        if self.draw_used_values != draw_used_values {
+            self.draw_used_values = draw_used_values;
+            self.stale = true;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.bounding_box = None;
+        self.stale = false;
+    }
+
+    /// Returns the bounding box of the counted values, or the unit bounding
+    /// box, if there are none.
+    pub fn bounding_box(&mut self) -> BoundingBox<f32> {
+        if self.stale {
+            let draw_used_values = self.draw_used_values;
+            self.bounding_box = BoundingBox::union(
+                self.values
+                    .values()
+                    .filter(|(used, _)| draw_used_values || !used)
+                    .map(|(_, bounding_box)| *bounding_box),
+            );
+            self.stale = false;
+        }
+
+        self.bounding_box.unwrap_or_else(BoundingBox::unit)
+    }
+
+    fn counts(&self, used: bool) -> bool {
+        self.draw_used_values || !used
+    }
+
+    fn forget(&mut self, (used, bounding_box): (bool, BoundingBox<f32>)) {
+        if !self.counts(used) || self.stale {
+            return;
+        }
+
+        if let Some(scene_bounding_box) = self.bounding_box {
+            if reaches_boundary(&scene_bounding_box, &bounding_box) {
+                self.stale = true;
+            }
+        }
+    }
+}
+
+/// Whether the inner bounding box reaches the boundary of the outer one, i.e.
+/// the outer one could shrink without it.
+fn reaches_boundary(outer: &BoundingBox<f32>, inner: &BoundingBox<f32>) -> bool {
+    let outer_min = outer.minimum_point();
+    let outer_max = outer.maximum_point();
+    let inner_min = inner.minimum_point();
+    let inner_max = inner.maximum_point();
+
+    (0..3).any(|i| inner_min[i] <= outer_min[i] || inner_max[i] >= outer_max[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use super::*;
+
+    fn bounding_box(min: [f32; 3], max: [f32; 3]) -> BoundingBox<f32> {
+        BoundingBox::new(
+            &Point3::new(min[0], min[1], min[2]),
+            &Point3::new(max[0], max[1], max[2]),
+        )
+    }
+
+    #[test]
+    fn test_scene_bounds_insert_and_remove() {
+        let mut scene_bounds = SceneBounds::new(false);
+        assert_eq!(scene_bounds.bounding_box(), BoundingBox::unit());
+
+        scene_bounds.insert(0, false, bounding_box([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]));
+        scene_bounds.insert(1, false, bounding_box([0.5, 0.5, 0.5], [3.0, 2.0, 1.0]));
+        scene_bounds.insert(2, true, bounding_box([-9.0, -9.0, -9.0], [9.0, 9.0, 9.0]));
+        assert_eq!(
+            scene_bounds.bounding_box(),
+            bounding_box([0.0, 0.0, 0.0], [3.0, 2.0, 1.0]),
+        );
+
+        scene_bounds.remove(&1);
+        assert_eq!(
+            scene_bounds.bounding_box(),
+            bounding_box([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]),
+        );
+
+        scene_bounds.set_draw_used_values(true);
+        assert_eq!(
+            scene_bounds.bounding_box(),
+            bounding_box([-9.0, -9.0, -9.0], [9.0, 9.0, 9.0]),
+        );
+
+        scene_bounds.set_used(&2, false);
+        scene_bounds.set_draw_used_values(false);
+        scene_bounds.insert(2, false, bounding_box([0.0, 0.0, 0.0], [0.5, 0.5, 0.5]));
+        assert_eq!(
+            scene_bounds.bounding_box(),
+            bounding_box([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]),
+        );
+    }
+}
@@ -0,0 +1,702 @@
+//! The values displayed in the viewport, arranged in a scene graph between
+//! the interpreter values and the renderer.
+//!
+//! Each displayed value is a node with a child node for each of its meshes.
+//! While the viewport is exploded, single meshes get a child node for each of
+//! their disjoint components. The nodes keep the display state of the values,
+//! such as their visibility, display material and translation, so that
+//! display-only changes only update the nodes and the cheap per-mesh state of
+//! the GPU meshes, instead of uploading the geometry again.
+//!
+//! The graph doesn't talk to the renderer itself. Meshes are uploaded by the
+//! closures given to the graph, and GPU meshes the graph no longer needs are
+//! handed back to the caller to be removed from the GPU.
+
+use std::collections::BTreeMap;
+use std::iter;
+use std::mem;
+use std::sync::Arc;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::bounding_box::BoundingBox;
+use crate::display_material::DisplayMaterial;
+use crate::explode;
+use crate::interpreter::VarIdent;
+use crate::mesh::Mesh;
+use crate::renderer::GpuMeshHandle;
+
+/// A unique identifier assigned to a value or subvalue for purposes
+/// of displaying in the viewport.
+///
+/// Since we support value arrays, there can be multiple geometries
+/// contained in a single value that all need to be treated separately
+/// for purposes of scene geometry analysis and rendering.
+///
+/// For simple values, the path is always `(var_ident, 0)`. For array
+/// element values, the path is `(var_ident, array_index)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValuePath(pub VarIdent, pub usize);
+
+/// A value displayed in the viewport.
+#[derive(Debug)]
+pub struct ValueNode<G = GpuMeshHandle> {
+    /// Whether the value is used by another statement.
+    pub used: bool,
+    /// Whether the value is on a visible layer.
+    pub visible: bool,
+    /// The display material coloring the GPU meshes of the value, if any.
+    pub material: Option<DisplayMaterial>,
+    /// The point above the meshes of the value, where its label is drawn.
+    pub label_anchor: Point3<f32>,
+    children: Vec<MeshNode<G>>,
+}
+
+impl<G> ValueNode<G> {
+    /// Returns the nodes of the meshes of the value, ordered by their array
+    /// indices.
+    pub fn children(&self) -> &[MeshNode<G>] {
+        &self.children
+    }
+
+    /// Iterates over the mesh nodes drawn for the value.
+    pub fn drawn(&self) -> impl Iterator<Item = &MeshNode<G>> + Clone {
+        self.children.iter().flat_map(|child| child.drawn())
+    }
+}
+
+/// A mesh displayed in the viewport, together with its uploaded GPU mesh.
+#[derive(Debug)]
+pub struct MeshNode<G = GpuMeshHandle> {
+    pub mesh: Arc<Mesh>,
+    pub gpu: G,
+    /// The offset the GPU mesh is drawn at, e.g. when the viewport is
+    /// exploded. The mesh itself stays in place.
+    pub translation: Vector3<f32>,
+    centroid: Point3<f32>,
+    /// The disjoint components of the mesh, once it was split to be
+    /// exploded. Drawn instead of the mesh, if there are more than one.
+    parts: Option<Vec<MeshNode<G>>>,
+}
+
+impl<G> MeshNode<G> {
+    fn new(mesh: Arc<Mesh>, gpu: G) -> Self {
+        let centroid = explode::mesh_centroid(&mesh);
+        Self {
+            mesh,
+            gpu,
+            translation: Vector3::zeros(),
+            centroid,
+            parts: None,
+        }
+    }
+
+    /// Iterates over the mesh nodes drawn for the mesh, which are either its
+    /// parts, or the node itself.
+    pub fn drawn(&self) -> impl Iterator<Item = &MeshNode<G>> + Clone {
+        let parts = self.parts.as_deref().unwrap_or(&[]);
+        let whole = if parts.is_empty() { Some(self) } else { None };
+
+        whole.into_iter().chain(parts.iter())
+    }
+
+    /// Moves the GPU meshes of the parts of the mesh to the garbage.
+    fn release_parts(&mut self, garbage: &mut Vec<G>) {
+        if let Some(parts) = self.parts.take() {
+            garbage.extend(parts.into_iter().map(|part| part.gpu));
+        }
+    }
+
+    fn into_gpu_meshes(self) -> impl Iterator<Item = G> {
+        let parts = self.parts.unwrap_or_default();
+        iter::once(self.gpu).chain(parts.into_iter().map(|part| part.gpu))
+    }
+}
+
+/// The scene graph of the values displayed in the viewport.
+#[derive(Debug)]
+pub struct SceneGraph<G = GpuMeshHandle> {
+    nodes: BTreeMap<VarIdent, ValueNode<G>>,
+    /// The order of the variables set by the caller.
+    order: Vec<VarIdent>,
+    /// The variables of the nodes in the order, followed by the variables
+    /// missing from it. Kept up to date, so that the values can be drawn in
+    /// order without sorting them every frame.
+    ordered: Vec<VarIdent>,
+    /// Mesh nodes of removed values, reused by values added at the same
+    /// paths.
+    released: BTreeMap<ValuePath, MeshNode<G>>,
+    /// GPU meshes no longer in the graph, which must be removed from the GPU.
+    garbage: Vec<G>,
+    explode_factor: f32,
+}
+
+impl<G> SceneGraph<G> {
+    pub fn new() -> Self {
+        Self::with_explode_factor(0.0)
+    }
+
+    /// Creates a graph, which explodes the values added to it by the factor.
+    pub fn with_explode_factor(explode_factor: f32) -> Self {
+        Self {
+            nodes: BTreeMap::new(),
+            order: Vec::new(),
+            ordered: Vec::new(),
+            released: BTreeMap::new(),
+            garbage: Vec::new(),
+            explode_factor,
+        }
+    }
+
+    /// Adds the node of the value of the variable with a child node for each
+    /// of the meshes, replacing the previous node of the variable, if any.
+    /// Values without meshes are not added.
+    ///
+    /// The meshes are uploaded by `upload`, which is given the GPU mesh to
+    /// update, if any. Mesh nodes of removed values with the same meshes are
+    /// reused with their GPU meshes as they are, e.g. when switching variants
+    /// brings back a value cached by the interpreter, or when the interpreter
+    /// deduplicates a recomputed mesh to the one it already had. Otherwise
+    /// mesh nodes of removed values at the same paths are reused and their
+    /// GPU meshes updated.
+    pub fn insert<U>(
+        &mut self,
+        var_ident: VarIdent,
+        used: bool,
+        meshes: Vec<Arc<Mesh>>,
+        mut upload: U,
+    ) where
+        U: FnMut(&Mesh, Option<G>) -> G,
+    {
+        self.remove(var_ident);
+
+        let label_anchor = match BoundingBox::union(meshes.iter().map(|mesh| mesh.bounding_box())) {
+            Some(bounding_box) => {
+                let center = bounding_box.center();
+                Point3::new(center.x, center.y, bounding_box.maximum_point().z)
+            }
+            None => return,
+        };
+
+        let mut children = Vec::with_capacity(meshes.len());
+        for (index, mesh) in meshes.into_iter().enumerate() {
+            let child = match self.take_released(ValuePath(var_ident, index), &mesh) {
+                Some(released) if Arc::ptr_eq(&released.mesh, &mesh) => released,
+                Some(mut released) => {
+                    released.release_parts(&mut self.garbage);
+                    let gpu = upload(&mesh, Some(released.gpu));
+                    MeshNode::new(mesh, gpu)
+                }
+                None => {
+                    let gpu = upload(&mesh, None);
+                    MeshNode::new(mesh, gpu)
+                }
+            };
+            children.push(child);
+        }
+
+        self.nodes.insert(
+            var_ident,
+            ValueNode {
+                used,
+                visible: true,
+                material: None,
+                label_anchor,
+                children,
+            },
+        );
+        self.update_ordered();
+        self.explode_value(var_ident, &mut upload);
+    }
+
+    /// Removes the node of the value of the variable. Its mesh nodes are kept
+    /// to be reused by values added at the same paths until the garbage is
+    /// collected.
+    pub fn remove(&mut self, var_ident: VarIdent) {
+        if let Some(value_node) = self.nodes.remove(&var_ident) {
+            for (index, child) in value_node.children.into_iter().enumerate() {
+                if let Some(replaced) = self.released.insert(ValuePath(var_ident, index), child) {
+                    self.garbage.extend(replaced.into_gpu_meshes());
+                }
+            }
+
+            self.update_ordered();
+        }
+    }
+
+    /// Returns the GPU meshes no longer in the graph, which must be removed
+    /// from the GPU. These are the GPU meshes of removed values, which were
+    /// not reused by added values, and of the parts of meshes no longer
+    /// exploded.
+    pub fn collect_garbage(&mut self) -> Vec<G> {
+        for (_, released) in mem::take(&mut self.released) {
+            self.garbage.extend(released.into_gpu_meshes());
+        }
+
+        mem::take(&mut self.garbage)
+    }
+
+    /// Removes all nodes and returns all GPU meshes of the graph, which must
+    /// be removed from the GPU.
+    pub fn clear(&mut self) -> Vec<G> {
+        for (_, value_node) in mem::take(&mut self.nodes) {
+            for child in value_node.children {
+                self.garbage.extend(child.into_gpu_meshes());
+            }
+        }
+        self.ordered.clear();
+
+        self.collect_garbage()
+    }
+
+    /// Moves the meshes of each value apart by the factor for inspection by
+    /// changing the translations of the mesh nodes. The elements of a mesh
+    /// array are moved apart as a whole. A single mesh is split into its
+    /// disjoint components, which are uploaded by `upload` the first time the
+    /// mesh is exploded, and moved apart instead.
+    ///
+    /// The parts of meshes are released once the factor is zero.
+    pub fn set_explode_factor<U>(&mut self, explode_factor: f32, mut upload: U)
+    where
+        U: FnMut(&Mesh, Option<G>) -> G,
+    {
+        self.explode_factor = explode_factor;
+
+        let var_idents: Vec<_> = self.nodes.keys().copied().collect();
+        for var_ident in var_idents {
+            self.explode_value(var_ident, &mut upload);
+        }
+    }
+
+    /// Sets the order of the variables, e.g. the order of the statements
+    /// declaring them, in which `iter_values_ordered` iterates over the
+    /// values. Values of variables missing from the order come last. Setting
+    /// the same order again is cheap, so it can be set every frame.
+    pub fn set_order<I>(&mut self, var_idents: I)
+    where
+        I: IntoIterator<Item = VarIdent>,
+        I::IntoIter: Clone,
+    {
+        let var_idents = var_idents.into_iter();
+        if var_idents.clone().eq(self.order.iter().copied()) {
+            return;
+        }
+
+        self.order = var_idents.collect();
+        self.update_ordered();
+    }
+
+    /// Changes whether the value of the variable is used.
+    pub fn set_used(&mut self, var_ident: VarIdent, used: bool) {
+        if let Some(value_node) = self.nodes.get_mut(&var_ident) {
+            value_node.used = used;
+        }
+    }
+
+    /// Changes whether the value of the variable is on a visible layer.
+    pub fn set_visible(&mut self, var_ident: VarIdent, visible: bool) {
+        if let Some(value_node) = self.nodes.get_mut(&var_ident) {
+            value_node.visible = visible;
+        }
+    }
+
+    /// Changes the display material of the value of the variable.
+    pub fn set_material(&mut self, var_ident: VarIdent, material: Option<DisplayMaterial>) {
+        if let Some(value_node) = self.nodes.get_mut(&var_ident) {
+            value_node.material = material;
+        }
+    }
+
+    pub fn get(&self, var_ident: VarIdent) -> Option<&ValueNode<G>> {
+        self.nodes.get(&var_ident)
+    }
+
+    /// Iterates over the value nodes, ordered by their variables.
+    pub fn iter_values(&self) -> impl Iterator<Item = (VarIdent, &ValueNode<G>)> + Clone {
+        self.nodes
+            .iter()
+            .map(|(var_ident, value_node)| (*var_ident, value_node))
+    }
+
+    /// Iterates over the value nodes in the order set by `set_order`,
+    /// followed by the values of variables missing from it.
+    pub fn iter_values_ordered(&self) -> impl Iterator<Item = (VarIdent, &ValueNode<G>)> + Clone {
+        self.ordered
+            .iter()
+            .map(move |var_ident| (*var_ident, &self.nodes[var_ident]))
+    }
+
+    /// Iterates over the mesh nodes of all values, ordered by their paths.
+    pub fn iter(&self) -> impl Iterator<Item = (ValuePath, &ValueNode<G>, &MeshNode<G>)> + Clone {
+        self.iter_values().flat_map(|(var_ident, value_node)| {
+            value_node
+                .children
+                .iter()
+                .enumerate()
+                .map(move |(index, child)| (ValuePath(var_ident, index), value_node, child))
+        })
+    }
+
+    /// Iterates over the mesh nodes of the variable's value, ordered by their
+    /// array indices.
+    pub fn iter_var(&self, var_ident: VarIdent) -> impl Iterator<Item = (ValuePath, &MeshNode<G>)> {
+        self.nodes
+            .get(&var_ident)
+            .into_iter()
+            .flat_map(move |value_node| {
+                value_node
+                    .children
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, child)| (ValuePath(var_ident, index), child))
+            })
+    }
+
+    /// Returns the variables with values displayed in the viewport.
+    pub fn var_idents(&self) -> Vec<VarIdent> {
+        self.nodes.keys().copied().collect()
+    }
+
+    /// Takes the released mesh node of the mesh, preferably from the path,
+    /// or the released mesh node at the path, if there is none.
+    fn take_released(&mut self, value_path: ValuePath, mesh: &Arc<Mesh>) -> Option<MeshNode<G>> {
+        let same_mesh = |released: &MeshNode<G>| Arc::ptr_eq(&released.mesh, mesh);
+
+        let released_path = match self.released.get(&value_path) {
+            Some(released) if same_mesh(released) => value_path,
+            _ => self
+                .released
+                .iter()
+                .find(|(_, released)| same_mesh(released))
+                .map_or(value_path, |(released_path, _)| *released_path),
+        };
+
+        self.released.remove(&released_path)
+    }
+
+    fn update_ordered(&mut self) {
+        let nodes = &self.nodes;
+        let order = &self.order;
+
+        self.ordered.clear();
+        self.ordered.extend(
+            order
+                .iter()
+                .copied()
+                .filter(|var_ident| nodes.contains_key(var_ident)),
+        );
+        self.ordered.extend(
+            nodes
+                .keys()
+                .copied()
+                .filter(|var_ident| !order.contains(var_ident)),
+        );
+    }
+
+    fn explode_value<U>(&mut self, var_ident: VarIdent, upload: &mut U)
+    where
+        U: FnMut(&Mesh, Option<G>) -> G,
+    {
+        let explode_factor = self.explode_factor;
+        let garbage = &mut self.garbage;
+        let value_node = match self.nodes.get_mut(&var_ident) {
+            Some(value_node) => value_node,
+            None => return,
+        };
+
+        match value_node.children.as_mut_slice() {
+            [child] if explode_factor > 0.0 => {
+                if child.parts.is_none() {
+                    let components = explode::split_components(&child.mesh);
+                    // A mesh with a single component stays in place
+                    let parts = if components.len() > 1 {
+                        components
+                            .into_iter()
+                            .map(|component| {
+                                let gpu = upload(&component, None);
+                                MeshNode::new(Arc::new(component), gpu)
+                            })
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    child.parts = Some(parts);
+                }
+
+                if let Some(parts) = &mut child.parts {
+                    translate_apart(parts, explode_factor);
+                }
+            }
+            [child] => child.release_parts(garbage),
+            children => translate_apart(children, explode_factor),
+        }
+    }
+}
+
+impl<G> Default for SceneGraph<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sets the translations of the mesh nodes moving them away from their
+/// group centroid by the factor.
+fn translate_apart<G>(mesh_nodes: &mut [MeshNode<G>], explode_factor: f32) {
+    let centroids: Vec<_> = mesh_nodes
+        .iter()
+        .map(|mesh_node| mesh_node.centroid)
+        .collect();
+    let offsets = explode::explode_offsets(&centroids, explode_factor);
+
+    for (mesh_node, offset) in mesh_nodes.iter_mut().zip(offsets) {
+        mesh_node.translation = offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Rotation3;
+
+    use crate::mesh::{primitive, tools};
+
+    use super::*;
+
+    fn cube(x: f32) -> Mesh {
+        primitive::create_box(
+            Point3::new(x, 0.0, 0.0),
+            Rotation3::identity(),
+            Vector3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    /// Returns a closure uploading meshes as consecutive numbers, which
+    /// records the updated GPU meshes.
+    fn upload<'a>(
+        next_gpu: &'a mut u32,
+        updated: &'a mut Vec<u32>,
+    ) -> impl FnMut(&Mesh, Option<u32>) -> u32 + 'a {
+        move |_, gpu| match gpu {
+            Some(gpu) => {
+                updated.push(gpu);
+                gpu
+            }
+            None => {
+                *next_gpu += 1;
+                *next_gpu
+            }
+        }
+    }
+
+    /// Returns the GPU meshes drawn for the value and their translations.
+    fn translations(
+        scene_graph: &SceneGraph<u32>,
+        var_ident: VarIdent,
+    ) -> Vec<(u32, Vector3<f32>)> {
+        scene_graph
+            .get(var_ident)
+            .expect("Value must be displayed")
+            .drawn()
+            .map(|mesh_node| (mesh_node.gpu, mesh_node.translation))
+            .collect()
+    }
+
+    #[test]
+    fn test_scene_graph_insert_reuses_released_mesh_nodes() {
+        let mut scene_graph: SceneGraph<u32> = SceneGraph::new();
+        let mut next_gpu = 0;
+        let mut updated = Vec::new();
+        let var_a = VarIdent(0);
+        let var_b = VarIdent(1);
+        let cube_a = Arc::new(cube(0.0));
+
+        scene_graph.insert(
+            var_a,
+            false,
+            vec![Arc::clone(&cube_a)],
+            upload(&mut next_gpu, &mut updated),
+        );
+        scene_graph.insert(
+            var_b,
+            true,
+            vec![Arc::new(cube(2.0)), Arc::new(cube(4.0))],
+            upload(&mut next_gpu, &mut updated),
+        );
+        assert_eq!(next_gpu, 3);
+        assert!(scene_graph.collect_garbage().is_empty());
+
+        // The same mesh keeps its GPU mesh, a changed mesh updates the
+        // released one and the unused released one becomes garbage
+        scene_graph.remove(var_a);
+        scene_graph.remove(var_b);
+        scene_graph.insert(
+            var_a,
+            false,
+            vec![cube_a],
+            upload(&mut next_gpu, &mut updated),
+        );
+        scene_graph.insert(
+            var_b,
+            true,
+            vec![Arc::new(cube(6.0))],
+            upload(&mut next_gpu, &mut updated),
+        );
+        assert_eq!(next_gpu, 3);
+        assert_eq!(updated, vec![2]);
+        assert_eq!(scene_graph.collect_garbage(), vec![3]);
+
+        let gpus: Vec<_> = scene_graph
+            .iter()
+            .map(|(value_path, value_node, mesh_node)| (value_path, value_node.used, mesh_node.gpu))
+            .collect();
+        assert_eq!(
+            gpus,
+            vec![
+                (ValuePath(var_a, 0), false, 1),
+                (ValuePath(var_b, 0), true, 2)
+            ],
+        );
+        assert_eq!(
+            scene_graph
+                .get(var_b)
+                .map(|value_node| value_node.label_anchor),
+            Some(Point3::new(6.0, 0.0, 0.5)),
+        );
+
+        let mut cleared = scene_graph.clear();
+        cleared.sort_unstable();
+        assert_eq!(cleared, vec![1, 2]);
+        assert!(scene_graph.var_idents().is_empty());
+    }
+
+    #[test]
+    fn test_scene_graph_insert_reuses_released_mesh_node_of_same_mesh_at_other_path() {
+        let mut scene_graph: SceneGraph<u32> = SceneGraph::new();
+        let mut next_gpu = 0;
+        let mut updated = Vec::new();
+        let var_a = VarIdent(0);
+        let var_b = VarIdent(1);
+        let cube_a = Arc::new(cube(0.0));
+
+        scene_graph.insert(
+            var_a,
+            true,
+            vec![Arc::new(cube(2.0)), Arc::clone(&cube_a)],
+            upload(&mut next_gpu, &mut updated),
+        );
+        assert_eq!(next_gpu, 2);
+
+        // The mesh moved to another value keeps its GPU mesh
+        scene_graph.remove(var_a);
+        scene_graph.insert(
+            var_b,
+            true,
+            vec![cube_a],
+            upload(&mut next_gpu, &mut updated),
+        );
+        assert_eq!(next_gpu, 2);
+        assert!(updated.is_empty());
+        assert_eq!(scene_graph.collect_garbage(), vec![1]);
+        assert_eq!(
+            translations(&scene_graph, var_b),
+            vec![(2, Vector3::zeros())]
+        );
+    }
+
+    #[test]
+    fn test_scene_graph_explode_translates_mesh_nodes() {
+        let mut scene_graph: SceneGraph<u32> = SceneGraph::new();
+        let mut next_gpu = 0;
+        let mut updated = Vec::new();
+        let var_array = VarIdent(0);
+        let var_single = VarIdent(1);
+
+        scene_graph.insert(
+            var_array,
+            false,
+            vec![Arc::new(cube(-1.0)), Arc::new(cube(1.0))],
+            upload(&mut next_gpu, &mut updated),
+        );
+        scene_graph.insert(
+            var_single,
+            false,
+            vec![Arc::new(tools::join_multiple_meshes(&[
+                cube(-1.0),
+                cube(1.0),
+            ]))],
+            upload(&mut next_gpu, &mut updated),
+        );
+
+        // The array elements move without uploading, the disjoint parts of
+        // the single mesh are uploaded once
+        scene_graph.set_explode_factor(1.0, upload(&mut next_gpu, &mut updated));
+        scene_graph.set_explode_factor(0.5, upload(&mut next_gpu, &mut updated));
+        assert_eq!(next_gpu, 5);
+
+        assert_eq!(
+            translations(&scene_graph, var_array),
+            vec![
+                (1, Vector3::new(-0.5, 0.0, 0.0)),
+                (2, Vector3::new(0.5, 0.0, 0.0)),
+            ],
+        );
+        assert_eq!(
+            translations(&scene_graph, var_single),
+            vec![
+                (4, Vector3::new(-0.5, 0.0, 0.0)),
+                (5, Vector3::new(0.5, 0.0, 0.0)),
+            ],
+        );
+
+        scene_graph.set_explode_factor(0.0, upload(&mut next_gpu, &mut updated));
+        assert_eq!(scene_graph.collect_garbage(), vec![4, 5]);
+        assert_eq!(
+            translations(&scene_graph, var_single),
+            vec![(3, Vector3::zeros())],
+        );
+        assert_eq!(
+            translations(&scene_graph, var_array)[1],
+            (2, Vector3::zeros()),
+        );
+        assert!(updated.is_empty());
+    }
+
+    #[test]
+    fn test_scene_graph_iter_values_ordered_puts_unordered_values_last() {
+        let mut scene_graph: SceneGraph<u32> = SceneGraph::new();
+        let mut next_gpu = 0;
+        let mut updated = Vec::new();
+        let var_a = VarIdent(0);
+        let var_b = VarIdent(1);
+        let var_c = VarIdent(2);
+
+        for (var_ident, x) in &[(var_a, 0.0), (var_b, 2.0), (var_c, 4.0)] {
+            scene_graph.insert(
+                *var_ident,
+                false,
+                vec![Arc::new(cube(*x))],
+                upload(&mut next_gpu, &mut updated),
+            );
+        }
+        scene_graph.set_order(vec![VarIdent(3), var_c, var_a]);
+
+        let ordered = |scene_graph: &SceneGraph<u32>| -> Vec<VarIdent> {
+            scene_graph
+                .iter_values_ordered()
+                .map(|(var_ident, _)| var_ident)
+                .collect()
+        };
+        assert_eq!(ordered(&scene_graph), vec![var_c, var_a, var_b]);
+
+        scene_graph.remove(var_c);
+        assert_eq!(ordered(&scene_graph), vec![var_a, var_b]);
+
+        scene_graph.insert(
+            var_c,
+            false,
+            vec![Arc::new(cube(4.0))],
+            upload(&mut next_gpu, &mut updated),
+        );
+        scene_graph.set_order(vec![var_b, var_c]);
+        assert_eq!(ordered(&scene_graph), vec![var_b, var_c, var_a]);
+    }
+}
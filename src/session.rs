@@ -1,23 +1,102 @@
 use std::collections::hash_map::{Entry, HashMap};
 use std::collections::{BTreeMap, HashSet};
+use std::error;
 use std::fmt;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use crate::interpreter::ast::{FuncIdent, Prog, Stmt, VarIdent};
-use crate::interpreter::{Func, InterpretError, InterpretValue, LogMessage, Ty, Value};
+use crate::display_material::DisplayMaterial;
+use crate::event_log::{EventKind, EventLog};
+use crate::expression;
+use crate::interpreter::ast::{
+    CallExpr, Expr, FuncIdent, LitExpr, Prog, Stmt, VarDeclStmt, VarIdent,
+};
+use crate::interpreter::{
+    Func, InterpretError, InterpretValue, LogMessage, LogMessageLevel, Measurement, Ty, Value,
+};
 use crate::interpreter_funcs;
 use crate::interpreter_server::{
     InterpreterRequest, InterpreterResponse, InterpreterServer, PollResponseError, RequestId,
 };
+use crate::layers::Layers;
+use crate::mesh::Mesh;
+use crate::param_links::{self, ParamId, ParamLink};
+use crate::speculation;
+
+const DEFAULT_AUTORUN_DELAY_MS: u32 = 100;
+const DEFAULT_VOXEL_SIZE: f32 = 1.0;
+
+/// Autorun settings kept in user preferences. In manual mode the pipeline
+/// only runs when the user asks for it, so that heavy pipelines don't run
+/// again after each edited parameter value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct AutorunPreferences {
+    pub enabled: bool,
+    /// How long to wait after the last program edit before running the
+    /// pipeline automatically.
+    pub delay_ms: u32,
+}
+
+impl Default for AutorunPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            delay_ms: DEFAULT_AUTORUN_DELAY_MS,
+        }
+    }
+}
+
+impl AutorunPreferences {
+    /// Returns the autorun delay for `Session::set_autorun_delay`, or `None`
+    /// in manual mode.
+    pub fn autorun_delay(&self) -> Option<Duration> {
+        if self.enabled {
+            Some(Duration::from_millis(u64::from(self.delay_ms)))
+        } else {
+            None
+        }
+    }
+}
+
+/// An error setting a custom name of a variable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VarNameError {
+    /// Another variable of the program already has the name.
+    Duplicate(String),
+}
+
+impl fmt::Display for VarNameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VarNameError::Duplicate(var_name) => {
+                write!(
+                    f,
+                    "Another operation output is already named \"{}\"",
+                    var_name
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for VarNameError {}
 
 /// A notification from the session to the surrounding environment
 /// about what values have been added since the last poll, and what
 /// values have been removed are no longer required.
+///
+/// Values of pinned variables are reported as unused, even if they are used,
+/// so that they are displayed and exported like the results of the pipeline.
 pub enum PollNotification {
     UsedValueAdded(VarIdent, Value),
     UsedValueRemoved(VarIdent, Value),
     UnusedValueAdded(VarIdent, Value),
     UnusedValueRemoved(VarIdent, Value),
+    /// An element of a mesh array generated by a statement, which is still
+    /// being interpreted. Streamed meshes are a preview and should be
+    /// discarded once the interpreter finishes.
+    StreamedMeshAdded(Arc<Mesh>),
     FinishedSuccessfully,
     // FIXME: Replace String with InterpretError
     FinishedWithError(String),
@@ -47,10 +126,22 @@ enum DiffEvent {
 pub struct Session {
     autorun_delay: Option<Duration>,
     last_uninterpreted_edit: Option<Instant>,
+    /// Index of the first statement edited since the interpreter last
+    /// finished. Values of this statement and the following statements are
+    /// stale.
+    first_stale_stmt_index: Option<usize>,
 
     interpreter_server: InterpreterServer,
     interpreter_interpret_request_in_flight: Option<RequestId>,
     interpreter_edit_prog_requests_in_flight: HashSet<RequestId>,
+    interpreter_speculate_requests_in_flight: HashSet<RequestId>,
+    /// Whether the interpreter runs funcs with their cheaper draft
+    /// arguments.
+    draft: bool,
+    /// Statement index, argument index and previous value of the literal
+    /// argument edited last, if it was the last program edit. Neighboring
+    /// values of the argument are speculated once the interpreter is idle.
+    speculation_candidate: Option<(usize, usize, LitExpr)>,
 
     prog: Prog,
     // FIXME: Add variable identifier compaction or other ability to reclaim
@@ -69,6 +160,42 @@ pub struct Session {
 
     used_values: HashMap<VarIdent, Value>,
     unused_values: HashMap<VarIdent, Value>,
+    /// Names of variables removed from the program, whose values are still
+    /// displayed until the interpreter reports their removal.
+    removed_var_names: HashMap<VarIdent, String>,
+    /// Variables whose values are kept visible and exported, even if they
+    /// are used by a later statement.
+    pinned_vars: HashSet<VarIdent>,
+    /// Materials overriding how the values of variables are drawn in the
+    /// viewport.
+    display_materials: HashMap<VarIdent, DisplayMaterial>,
+    layers: Layers,
+    /// Variable whose statement is isolated in the viewport for editing.
+    focused_var: Option<VarIdent>,
+    /// Custom names of variables, used instead of the return value names of
+    /// their operations.
+    var_names: HashMap<VarIdent, String>,
+    /// Custom texts of the viewport labels of variables. Variables without
+    /// one are labeled by their operation.
+    value_labels: HashMap<VarIdent, String>,
+    /// Free-text notes documenting the intent of statements, keyed by the
+    /// variables they declare.
+    stmt_notes: HashMap<VarIdent, String>,
+    /// Free-text notes documenting the intent of the whole project.
+    notes: String,
+    /// Variables declared by imports that are re-run whenever the imported
+    /// file changes.
+    live_linked_vars: HashSet<VarIdent>,
+    /// Voxel size inherited by the voxel operations linked to it.
+    voxel_size: f32,
+    /// Variables declared by voxel operations whose voxel size follows the
+    /// project voxel size.
+    voxel_size_inheriting_vars: HashSet<VarIdent>,
+    /// Numeric parameters driven by other parameters, keyed by the driven
+    /// parameter.
+    param_links: HashMap<ParamId, ParamLink>,
+    /// All mutations of the program, saved next to the project.
+    event_log: EventLog,
 
     // Working memory for diffing interpreter responses
     diff_events: Vec<DiffEvent>,
@@ -91,10 +218,14 @@ impl Session {
         Self {
             autorun_delay: None,
             last_uninterpreted_edit: None,
+            first_stale_stmt_index: None,
 
             interpreter_server: InterpreterServer::new(),
             interpreter_interpret_request_in_flight: None,
             interpreter_edit_prog_requests_in_flight: HashSet::new(),
+            interpreter_speculate_requests_in_flight: HashSet::new(),
+            draft: false,
+            speculation_candidate: None,
 
             diff_events: Vec::with_capacity(64),
             diff_processed_idents: HashSet::with_capacity(64),
@@ -107,6 +238,20 @@ impl Session {
 
             used_values: HashMap::new(),
             unused_values: HashMap::new(),
+            removed_var_names: HashMap::new(),
+            pinned_vars: HashSet::new(),
+            display_materials: HashMap::new(),
+            layers: Layers::new(),
+            focused_var: None,
+            var_names: HashMap::new(),
+            value_labels: HashMap::new(),
+            stmt_notes: HashMap::new(),
+            notes: String::new(),
+            live_linked_vars: HashSet::new(),
+            voxel_size: DEFAULT_VOXEL_SIZE,
+            voxel_size_inheriting_vars: HashSet::new(),
+            param_links: HashMap::new(),
+            event_log: EventLog::with_checkpoint(Vec::new()),
 
             var_visibility_mesh: Vec::new(),
             var_visibility_mesh_array: Vec::new(),
@@ -139,6 +284,16 @@ impl Session {
         self.autorun_delay = autorun_delay;
     }
 
+    pub fn stmt_timeout(&self) -> Option<Duration> {
+        self.interpreter_server.stmt_timeout()
+    }
+
+    /// Sets how long a single statement may run before the interpreter is
+    /// abandoned and the run fails with a timeout error.
+    pub fn set_stmt_timeout(&mut self, stmt_timeout: Option<Duration>) {
+        self.interpreter_server.set_stmt_timeout(stmt_timeout);
+    }
+
     /// Pushes a new statement onto the program.
     ///
     /// If the `Stmt` is `Stmt::VarDecl`, this function ensures that the next
@@ -157,7 +312,10 @@ impl Session {
         );
 
         self.last_uninterpreted_edit = Some(current_time);
+        self.speculation_candidate = None;
+        self.mark_stale_from(self.prog.stmts().len());
         self.prog.push_stmt(stmt.clone());
+        self.event_log.record(EventKind::PushStmt(stmt.clone()));
         self.log_messages.push(Vec::new());
         self.error = None;
 
@@ -200,8 +358,30 @@ impl Session {
             "Program must not be empty when popping",
         );
 
+        if let Some(Stmt::VarDecl(var_decl)) = self.prog.stmts().last() {
+            let var_decl = var_decl.clone();
+            self.remember_removed_var_name(&var_decl);
+            self.pinned_vars.remove(&var_decl.ident());
+            self.display_materials.remove(&var_decl.ident());
+            self.layers.set_var_layer(var_decl.ident(), None);
+            self.var_names.remove(&var_decl.ident());
+            self.value_labels.remove(&var_decl.ident());
+            self.stmt_notes.remove(&var_decl.ident());
+            self.live_linked_vars.remove(&var_decl.ident());
+            self.voxel_size_inheriting_vars.remove(&var_decl.ident());
+            self.param_links.retain(|param, link| {
+                param.var_ident != var_decl.ident() && link.source.var_ident != var_decl.ident()
+            });
+            if self.focused_var == Some(var_decl.ident()) {
+                self.focused_var = None;
+            }
+        }
+
         self.last_uninterpreted_edit = Some(current_time);
+        self.speculation_candidate = None;
         self.prog.pop_stmt();
+        self.event_log.record(EventKind::PopStmt);
+        self.mark_stale_from(self.prog.stmts().len());
         self.log_messages.pop();
         self.error = None;
 
@@ -242,22 +422,33 @@ impl Session {
         let current_stmt = &self.prog.stmts()[stmt_index];
         match (current_stmt, &stmt) {
             (Stmt::VarDecl(current_var_decl), Stmt::VarDecl(new_var_decl)) => {
+                self.speculation_candidate = changed_lit_arg(current_var_decl, new_var_decl)
+                    .map(|(arg_index, previous_lit)| (stmt_index, arg_index, previous_lit));
+
                 if current_var_decl.init_expr().ident() != new_var_decl.init_expr().ident() {
                     self.log_messages[stmt_index].clear();
                 }
+                if current_var_decl.ident() != new_var_decl.ident() {
+                    let current_var_decl = current_var_decl.clone();
+                    self.remember_removed_var_name(&current_var_decl);
+                }
             }
         }
 
         self.last_uninterpreted_edit = Some(current_time);
+        self.mark_stale_from(stmt_index);
         self.prog.set_stmt_at(stmt_index, stmt.clone());
+        self.event_log
+            .record(EventKind::SetStmtAt(stmt_index, stmt.clone()));
         self.error = None;
 
         let Stmt::VarDecl(ref var_decl) = stmt;
+        let var_ident = var_decl.ident();
         // Take the max out of the current next ident and the successor of the
         // given ident. This ensures that modification of statements inside the
         // program does not forget our place in the sequence, if higher, but is
         // able to jump ahead, if needed.
-        self.next_var_ident = self.next_var_ident.max(var_decl.ident().0 + 1);
+        self.next_var_ident = self.next_var_ident.max(var_ident.0 + 1);
 
         let request_id = self
             .interpreter_server
@@ -271,6 +462,95 @@ impl Session {
         );
 
         self.recompute_var_visibility();
+
+        // Parameters driven by the statement follow its new arguments
+        self.apply_param_links(current_time, var_ident);
+    }
+
+    /// Replaces the whole program with new statements.
+    ///
+    /// This function ensures that the next variable identifier returned by
+    /// `Session::next_free_var_ident` will not conflict with any variable
+    /// identifier contained in the statements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interpreter is busy.
+    pub fn set_prog_stmts(&mut self, current_time: Instant, stmts: Vec<Stmt>) {
+        // This is because the current session could want to report
+        // errors and we would like to show them somewhere
+        assert!(
+            !self.interpreter_busy(),
+            "Can't submit a request while the interpreter is already interpreting",
+        );
+
+        self.last_uninterpreted_edit = Some(current_time);
+        self.speculation_candidate = None;
+        self.mark_stale_from(0);
+        self.log_messages.clear();
+        self.log_messages.resize_with(stmts.len(), Vec::new);
+        self.error = None;
+
+        for stmt in &stmts {
+            let Stmt::VarDecl(var_decl) = stmt;
+            self.next_var_ident = self.next_var_ident.max(var_decl.ident().0 + 1);
+        }
+
+        let declared = |var_ident: &VarIdent| {
+            stmts.iter().any(|stmt| {
+                let Stmt::VarDecl(var_decl) = stmt;
+                var_decl.ident() == *var_ident
+            })
+        };
+
+        let removed_var_decls: Vec<_> = self
+            .prog
+            .stmts()
+            .iter()
+            .map(|stmt| {
+                let Stmt::VarDecl(var_decl) = stmt;
+                var_decl
+            })
+            .filter(|var_decl| !declared(&var_decl.ident()))
+            .cloned()
+            .collect();
+        for var_decl in &removed_var_decls {
+            self.remember_removed_var_name(var_decl);
+        }
+
+        // Keep only the pins, display materials, layer assignments, names,
+        // labels, notes, live links, voxel size links, parameter links and
+        // focus of variables still declared by the program.
+        self.pinned_vars.retain(|var_ident| declared(var_ident));
+        self.display_materials
+            .retain(|var_ident, _| declared(var_ident));
+        self.layers.retain_vars(|var_ident| declared(&var_ident));
+        self.var_names.retain(|var_ident, _| declared(var_ident));
+        self.value_labels.retain(|var_ident, _| declared(var_ident));
+        self.stmt_notes.retain(|var_ident, _| declared(var_ident));
+        self.live_linked_vars
+            .retain(|var_ident| declared(var_ident));
+        self.voxel_size_inheriting_vars
+            .retain(|var_ident| declared(var_ident));
+        self.param_links
+            .retain(|param, link| declared(&param.var_ident) && declared(&link.source.var_ident));
+        self.focused_var = self.focused_var.filter(|var_ident| declared(var_ident));
+
+        self.prog = Prog::new(stmts.clone());
+        self.event_log.record(EventKind::SetStmts(stmts.clone()));
+
+        let request_id = self
+            .interpreter_server
+            .submit_request(InterpreterRequest::SetProg(Prog::new(stmts)));
+        let tracked = self
+            .interpreter_edit_prog_requests_in_flight
+            .insert(request_id);
+        assert!(
+            tracked,
+            "Interpreter server must provide unique request ids"
+        );
+
+        self.recompute_var_visibility();
     }
 
     /// Returns the statements currently contained in the current pipeline's
@@ -315,7 +595,8 @@ impl Session {
 
     /// Returns variable declaration statement index paired with human readable
     /// variable name for a variable identifier or `None` if the variable
-    /// identifier does not exist in the current program.
+    /// identifier does not exist in the current program. The name is the
+    /// custom name of the variable, if it has one.
     pub fn var_decl_stmt_index_and_var_name_for_ident(
         &self,
         var_ident: VarIdent,
@@ -327,9 +608,14 @@ impl Session {
             .find_map(|(stmt_index, stmt)| match stmt {
                 Stmt::VarDecl(var_decl) => {
                     if var_decl.ident() == var_ident {
-                        let name = self.function_table[&var_decl.init_expr().ident()]
-                            .info()
-                            .return_value_name;
+                        let name = match self.var_names.get(&var_ident) {
+                            Some(var_name) => var_name.as_str(),
+                            None => {
+                                self.function_table[&var_decl.init_expr().ident()]
+                                    .info()
+                                    .return_value_name
+                            }
+                        };
 
                         Some((stmt_index, name))
                     } else {
@@ -339,6 +625,17 @@ impl Session {
             })
     }
 
+    /// Returns the human readable variable name for a variable identifier.
+    ///
+    /// Unlike `Session::var_decl_stmt_index_and_var_name_for_ident`, this
+    /// also names variables already removed from the program, whose values
+    /// are still displayed until the interpreter runs again.
+    pub fn var_name_for_ident(&self, var_ident: VarIdent) -> Option<&str> {
+        self.var_decl_stmt_index_and_var_name_for_ident(var_ident)
+            .map(|(_, name)| name)
+            .or_else(|| self.removed_var_names.get(&var_ident).map(String::as_str))
+    }
+
     /// Returns all visible variable identifiers from a position
     /// (index) in the program.
     pub fn visible_vars_at_stmt(
@@ -363,6 +660,49 @@ impl Session {
         &self.log_messages[stmt_index]
     }
 
+    /// Returns the warnings logged by the stmt in its last run. Unlike errors,
+    /// warnings don't prevent the stmt from producing its value.
+    pub fn warnings_at_stmt(&self, stmt_index: usize) -> impl Iterator<Item = &LogMessage> + '_ {
+        self.log_messages[stmt_index]
+            .iter()
+            .filter(|log_message| log_message.level == LogMessageLevel::Warn)
+    }
+
+    /// Returns how many warnings all stmts logged in their last run.
+    pub fn warning_count(&self) -> usize {
+        (0..self.log_messages.len())
+            .map(|stmt_index| self.warnings_at_stmt(stmt_index).count())
+            .sum()
+    }
+
+    /// Returns the measurements reported in the log messages of all stmts,
+    /// paired with the human readable name of the reporting stmt.
+    pub fn measurements(&self) -> Vec<(String, &Measurement)> {
+        self.stmts()
+            .iter()
+            .zip(self.log_messages.iter())
+            .enumerate()
+            .flat_map(|(stmt_index, (stmt, log_messages))| {
+                let Stmt::VarDecl(var_decl) = stmt;
+                let name = self.function_table[&var_decl.init_expr().ident()]
+                    .info()
+                    .return_value_name;
+
+                log_messages.iter().filter_map(move |log_message| {
+                    log_message
+                        .measurement
+                        .as_ref()
+                        .map(|measurement| (format!("{} #{}", name, stmt_index + 1), measurement))
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the error the interpreter last failed on, if any.
+    pub fn error(&self) -> Option<&impl fmt::Display> {
+        self.error.as_ref()
+    }
+
     pub fn error_at_stmt(&self, stmt_index: usize) -> Option<&impl fmt::Display> {
         self.error.as_ref().and_then(|err| {
             if stmt_index == err.stmt_index() {
@@ -386,6 +726,92 @@ impl Session {
             && self.last_uninterpreted_edit.is_none()
     }
 
+    /// Returns whether the values of the statement at the index are stale,
+    /// because the statement or a statement before it was edited since the
+    /// interpreter last finished.
+    pub fn stmt_stale(&self, stmt_index: usize) -> bool {
+        self.first_stale_stmt_index
+            .map_or(false, |first_stale_stmt_index| {
+                stmt_index >= first_stale_stmt_index
+            })
+    }
+
+    fn mark_stale_from(&mut self, stmt_index: usize) {
+        self.first_stale_stmt_index = Some(match self.first_stale_stmt_index {
+            Some(first_stale_stmt_index) => first_stale_stmt_index.min(stmt_index),
+            None => stmt_index,
+        });
+    }
+
+    pub fn draft(&self) -> bool {
+        self.draft
+    }
+
+    /// Switches draft mode on or off. In draft mode, funcs run with cheaper
+    /// settings they declare, e.g. larger voxels or fewer smoothing
+    /// iterations. All values become stale, as the whole program has to run
+    /// again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interpreter is busy.
+    pub fn set_draft(&mut self, current_time: Instant, draft: bool) {
+        assert!(
+            !self.interpreter_busy(),
+            "Can't submit a request while the interpreter is already interpreting",
+        );
+
+        if self.draft == draft {
+            return;
+        }
+
+        self.draft = draft;
+        self.last_uninterpreted_edit = Some(current_time);
+        self.speculation_candidate = None;
+        self.mark_stale_from(0);
+
+        let request_id = self
+            .interpreter_server
+            .submit_request(InterpreterRequest::SetDraft(draft));
+        let tracked = self
+            .interpreter_edit_prog_requests_in_flight
+            .insert(request_id);
+        assert!(
+            tracked,
+            "Interpreter server must provide unique request ids"
+        );
+    }
+
+    /// Hands values saved by an earlier run, e.g. one interrupted by a
+    /// crash, to the interpreter, so that the next run doesn't compute them
+    /// again. Values are not restored in draft mode, as they were computed
+    /// with full settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interpreter is busy.
+    pub fn restore_values(&mut self, values: Vec<(VarIdent, CallExpr, Value)>) {
+        assert!(
+            !self.interpreter_busy(),
+            "Can't submit a request while the interpreter is already interpreting",
+        );
+
+        if self.draft || values.is_empty() {
+            return;
+        }
+
+        let request_id = self
+            .interpreter_server
+            .submit_request(InterpreterRequest::RestoreValues(values));
+        let tracked = self
+            .interpreter_edit_prog_requests_in_flight
+            .insert(request_id);
+        assert!(
+            tracked,
+            "Interpreter server must provide unique request ids"
+        );
+    }
+
     /// Returns whether the interpreter is currently running. Program
     /// modifications and running the interpreter (again) are
     /// disallowed in this state.
@@ -404,6 +830,13 @@ impl Session {
 
         self.last_uninterpreted_edit = None;
 
+        // Speculating is only worth it while the interpreter has nothing
+        // else to do.
+        if !self.interpreter_speculate_requests_in_flight.is_empty() {
+            log::info!("Cancelling speculate requests");
+            self.interpreter_server.cancel_speculation();
+        }
+
         let request_id = self
             .interpreter_server
             .submit_request(InterpreterRequest::Interpret);
@@ -411,6 +844,505 @@ impl Session {
             .replace(request_id);
     }
 
+    pub fn var_pinned(&self, var_ident: VarIdent) -> bool {
+        self.pinned_vars.contains(&var_ident)
+    }
+
+    /// Pins or unpins the value of the variable. The value of a pinned
+    /// variable is treated as unused, so that it stays visible and is
+    /// exported, even if it is used by a later statement.
+    ///
+    /// The values already reported to the environment are not reported
+    /// again. Use `Session::value_used` to update their usage.
+    pub fn set_var_pinned(&mut self, var_ident: VarIdent, pinned: bool) {
+        if pinned {
+            self.pinned_vars.insert(var_ident);
+        } else {
+            self.pinned_vars.remove(&var_ident);
+        }
+    }
+
+    /// Returns the pinned variables in no particular order.
+    pub fn pinned_vars(&self) -> impl Iterator<Item = VarIdent> + '_ {
+        self.pinned_vars.iter().copied()
+    }
+
+    pub fn var_live_linked(&self, var_ident: VarIdent) -> bool {
+        self.live_linked_vars.contains(&var_ident)
+    }
+
+    /// Links or unlinks the import declaring the variable. Pipelines with
+    /// linked imports are re-run whenever the imported files change.
+    pub fn set_var_live_linked(&mut self, var_ident: VarIdent, live_linked: bool) {
+        if live_linked {
+            self.live_linked_vars.insert(var_ident);
+        } else {
+            self.live_linked_vars.remove(&var_ident);
+        }
+    }
+
+    /// Returns the live linked variables in no particular order.
+    pub fn live_linked_vars(&self) -> impl Iterator<Item = VarIdent> + '_ {
+        self.live_linked_vars.iter().copied()
+    }
+
+    pub fn voxel_size(&self) -> f32 {
+        self.voxel_size
+    }
+
+    /// Sets the project voxel size and updates the voxel size arguments of
+    /// all statements inheriting it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interpreter is busy and a statement needs updating.
+    pub fn set_voxel_size(&mut self, current_time: Instant, voxel_size: f32) {
+        self.voxel_size = voxel_size;
+        self.apply_voxel_size(current_time);
+    }
+
+    pub fn var_inherits_voxel_size(&self, var_ident: VarIdent) -> bool {
+        self.voxel_size_inheriting_vars.contains(&var_ident)
+    }
+
+    /// Links the voxel size of the statement declaring the variable to the
+    /// project voxel size, or unlinks it. A linked statement gets the project
+    /// voxel size right away. An unlinked statement keeps its last voxel
+    /// size, which can then be overridden.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interpreter is busy and the statement needs updating.
+    pub fn set_var_inherits_voxel_size(
+        &mut self,
+        current_time: Instant,
+        var_ident: VarIdent,
+        inherits: bool,
+    ) {
+        if inherits {
+            self.voxel_size_inheriting_vars.insert(var_ident);
+            self.apply_voxel_size(current_time);
+        } else {
+            self.voxel_size_inheriting_vars.remove(&var_ident);
+        }
+    }
+
+    /// Returns the variables inheriting the project voxel size in no
+    /// particular order.
+    pub fn voxel_size_inheriting_vars(&self) -> impl Iterator<Item = VarIdent> + '_ {
+        self.voxel_size_inheriting_vars.iter().copied()
+    }
+
+    /// Returns the link driving the parameter, if any.
+    pub fn param_link(&self, param: ParamId) -> Option<ParamLink> {
+        self.param_links.get(&param).copied()
+    }
+
+    /// Whether the parameter drives any other parameters.
+    pub fn param_drives_others(&self, param: ParamId) -> bool {
+        self.param_links.values().any(|link| link.source == param)
+    }
+
+    /// Returns the parameter links keyed by the driven parameters in no
+    /// particular order.
+    pub fn param_links(&self) -> impl Iterator<Item = (ParamId, ParamLink)> + '_ {
+        self.param_links.iter().map(|(param, link)| (*param, *link))
+    }
+
+    /// Whether the parameter can be driven by the source parameter: both are
+    /// numeric parameters of declared statements, the source can drive the
+    /// components of the parameter, and the link would not close a cycle.
+    pub fn can_link_params(&self, param: ParamId, source: ParamId) -> bool {
+        match (self.numeric_param(param), self.numeric_param(source)) {
+            (Some((_, _, component_count)), Some((_, _, source_component_count))) => {
+                param_links::components_compatible(source_component_count, component_count)
+                    && !param_links::creates_cycle(&self.param_links, param, source)
+            }
+            _ => false,
+        }
+    }
+
+    /// Drives the parameter by the link, or unlinks it. A linked parameter
+    /// gets its driven value right away and follows the source from then
+    /// on. An unlinked parameter keeps its last value. Links between
+    /// parameters that can not be linked are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interpreter is busy and the statement needs updating.
+    pub fn set_param_link(
+        &mut self,
+        current_time: Instant,
+        param: ParamId,
+        link: Option<ParamLink>,
+    ) {
+        match link {
+            Some(link) => {
+                if self.param_links.get(&param).map(|current| current.source) == Some(link.source)
+                    || self.can_link_params(param, link.source)
+                {
+                    self.param_links.insert(param, link);
+                    self.apply_param_links(current_time, link.source.var_ident);
+                }
+            }
+            None => {
+                self.param_links.remove(&param);
+            }
+        }
+    }
+
+    /// Returns the log of all mutations of the program.
+    pub fn event_log(&self) -> &EventLog {
+        &self.event_log
+    }
+
+    /// Returns the log of all mutations of the program, e.g. to save it.
+    pub fn event_log_mut(&mut self) -> &mut EventLog {
+        &mut self.event_log
+    }
+
+    /// Replaces the log of the mutations of the program, e.g. with the log
+    /// loaded for an opened project.
+    pub fn set_event_log(&mut self, event_log: EventLog) {
+        self.event_log = event_log;
+    }
+
+    /// Sets the voxel size arguments of the statements inheriting the project
+    /// voxel size. Statements already having it are not touched, so that
+    /// they don't become stale.
+    fn apply_voxel_size(&mut self, current_time: Instant) {
+        let voxel_size = [self.voxel_size; 3];
+
+        let mut updated_stmts = Vec::new();
+        for (stmt_index, stmt) in self.prog.stmts().iter().enumerate() {
+            let Stmt::VarDecl(var_decl) = stmt;
+            if !self.voxel_size_inheriting_vars.contains(&var_decl.ident()) {
+                continue;
+            }
+
+            let init_expr = var_decl.init_expr();
+            let func = &self.function_table[&init_expr.ident()];
+            let voxel_size_param_index = match func.voxel_size_param_index() {
+                Some(voxel_size_param_index) => voxel_size_param_index,
+                None => continue,
+            };
+
+            let arg_count = init_expr.args().len();
+            let mut args = init_expr.args().to_vec();
+            let mut updated = false;
+            for (arg_index, arg) in args.iter_mut().enumerate() {
+                if func.param_index_for_arg(arg_index, arg_count) != Some(voxel_size_param_index) {
+                    continue;
+                }
+                if let Expr::Lit(LitExpr::Float3(float3)) = arg {
+                    if *float3 != voxel_size {
+                        *float3 = voxel_size;
+                        updated = true;
+                    }
+                }
+            }
+
+            if updated {
+                let call_expr = CallExpr::new(init_expr.ident(), args);
+                let stmt = Stmt::VarDecl(VarDeclStmt::new(var_decl.ident(), call_expr));
+                updated_stmts.push((stmt_index, stmt));
+            }
+        }
+
+        for (stmt_index, stmt) in updated_stmts {
+            self.set_prog_stmt_at(current_time, stmt_index, stmt);
+        }
+    }
+
+    /// Returns the statement index, the literal and the number of components
+    /// of a numeric parameter with a plain input, or `None` if the parameter
+    /// doesn't exist or is not numeric.
+    fn numeric_param(&self, param: ParamId) -> Option<(usize, &LitExpr, usize)> {
+        let stmt_index = self.prog.stmts().iter().position(|stmt| {
+            let Stmt::VarDecl(var_decl) = stmt;
+            var_decl.ident() == param.var_ident
+        })?;
+
+        let Stmt::VarDecl(var_decl) = &self.prog.stmts()[stmt_index];
+        let init_expr = var_decl.init_expr();
+        let func = &self.function_table[&init_expr.ident()];
+        let param_index = func.param_index_for_arg(param.arg_index, init_expr.args().len())?;
+        let param_info = &func.param_info()[param_index];
+        if param_info.widget.choices().is_some() {
+            return None;
+        }
+
+        let component_count = expression::component_count(&param_info.refinement)?;
+        match &init_expr.args()[param.arg_index] {
+            Expr::Lit(lit) => Some((stmt_index, lit, component_count)),
+            Expr::Var(_) => None,
+        }
+    }
+
+    /// Sets the parameters driven by the arguments of the statement declaring
+    /// the variable to their driven values. Parameters already having them
+    /// are not touched, so that they don't become stale. Updated statements
+    /// drive their own linked parameters in turn.
+    fn apply_param_links(&mut self, current_time: Instant, source_var_ident: VarIdent) {
+        // A statement can have multiple driven parameters, so the statements
+        // are updated one parameter at a time
+        while let Some((stmt_index, stmt)) = self.next_param_link_update(source_var_ident) {
+            self.set_prog_stmt_at(current_time, stmt_index, stmt);
+        }
+    }
+
+    /// Returns the statement index and the updated statement of a parameter
+    /// driven by the variable, which doesn't have its driven value yet.
+    fn next_param_link_update(&self, source_var_ident: VarIdent) -> Option<(usize, Stmt)> {
+        for (param, link) in &self.param_links {
+            if link.source.var_ident != source_var_ident {
+                continue;
+            }
+
+            let source_lit = match self.numeric_param(link.source) {
+                Some((_, source_lit, _)) => source_lit,
+                None => continue,
+            };
+            let (stmt_index, lit, _) = match self.numeric_param(*param) {
+                Some(numeric_param) => numeric_param,
+                None => continue,
+            };
+
+            let Stmt::VarDecl(var_decl) = &self.prog.stmts()[stmt_index];
+            let init_expr = var_decl.init_expr();
+            let func = &self.function_table[&init_expr.ident()];
+            let param_index = func
+                .param_index_for_arg(param.arg_index, init_expr.args().len())
+                .expect("Numeric parameter must have a parameter index");
+            let refinement = &func.param_info()[param_index].refinement;
+
+            if let Some(driven_lit) = link.driven_lit(source_lit, refinement) {
+                if driven_lit != *lit {
+                    let init_expr =
+                        init_expr.clone_with_arg_at(param.arg_index, Expr::Lit(driven_lit));
+                    let stmt = Stmt::VarDecl(var_decl.clone_with_init_expr(init_expr));
+                    return Some((stmt_index, stmt));
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn display_material(&self, var_ident: VarIdent) -> Option<DisplayMaterial> {
+        self.display_materials.get(&var_ident).copied()
+    }
+
+    /// Sets or clears the material overriding how the value of the variable
+    /// is drawn in the viewport.
+    pub fn set_display_material(
+        &mut self,
+        var_ident: VarIdent,
+        display_material: Option<DisplayMaterial>,
+    ) {
+        match display_material {
+            Some(display_material) => {
+                self.display_materials.insert(var_ident, display_material);
+            }
+            None => {
+                self.display_materials.remove(&var_ident);
+            }
+        }
+    }
+
+    pub fn display_materials(&self) -> &HashMap<VarIdent, DisplayMaterial> {
+        &self.display_materials
+    }
+
+    /// Returns the material the value of the variable is drawn in: its own
+    /// display material, or the color of its layer.
+    pub fn effective_display_material(&self, var_ident: VarIdent) -> Option<DisplayMaterial> {
+        self.display_material(var_ident)
+            .or_else(|| self.layers.var_display_material(var_ident))
+    }
+
+    pub fn layers(&self) -> &Layers {
+        &self.layers
+    }
+
+    pub fn layers_mut(&mut self) -> &mut Layers {
+        &mut self.layers
+    }
+
+    pub fn set_layers(&mut self, layers: Layers) {
+        self.layers = layers;
+    }
+
+    pub fn value_label(&self, var_ident: VarIdent) -> Option<&str> {
+        self.value_labels.get(&var_ident).map(String::as_str)
+    }
+
+    /// Sets or clears the custom text of the viewport label of the variable.
+    pub fn set_value_label(&mut self, var_ident: VarIdent, value_label: Option<String>) {
+        match value_label {
+            Some(value_label) => {
+                self.value_labels.insert(var_ident, value_label);
+            }
+            None => {
+                self.value_labels.remove(&var_ident);
+            }
+        }
+    }
+
+    pub fn value_labels(&self) -> &HashMap<VarIdent, String> {
+        &self.value_labels
+    }
+
+    pub fn var_name(&self, var_ident: VarIdent) -> Option<&str> {
+        self.var_names.get(&var_ident).map(String::as_str)
+    }
+
+    /// Sets or clears the custom name of the variable. Names must be unique
+    /// within the program, so that exported models and labels can be told
+    /// apart.
+    pub fn set_var_name(
+        &mut self,
+        var_ident: VarIdent,
+        var_name: Option<String>,
+    ) -> Result<(), VarNameError> {
+        match var_name {
+            Some(var_name) => {
+                let duplicate = self.prog.stmts().iter().any(|stmt| {
+                    let Stmt::VarDecl(var_decl) = stmt;
+                    var_decl.ident() != var_ident
+                        && self
+                            .var_decl_stmt_index_and_var_name_for_ident(var_decl.ident())
+                            .map_or(false, |(_, name)| name == var_name)
+                });
+                if duplicate {
+                    return Err(VarNameError::Duplicate(var_name));
+                }
+
+                self.var_names.insert(var_ident, var_name);
+            }
+            None => {
+                self.var_names.remove(&var_ident);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn var_names(&self) -> &HashMap<VarIdent, String> {
+        &self.var_names
+    }
+
+    /// Returns the text of the viewport label of the variable: its custom
+    /// label, or the number and the custom name or the name of the operation
+    /// declaring it.
+    pub fn value_label_text(&self, var_ident: VarIdent) -> Option<String> {
+        if let Some(value_label) = self.value_labels.get(&var_ident) {
+            return Some(value_label.clone());
+        }
+
+        self.prog
+            .stmts()
+            .iter()
+            .enumerate()
+            .find_map(|(stmt_index, stmt)| {
+                let Stmt::VarDecl(var_decl) = stmt;
+                if var_decl.ident() == var_ident {
+                    let name = match self.var_names.get(&var_ident) {
+                        Some(var_name) => var_name.as_str(),
+                        None => {
+                            self.function_table[&var_decl.init_expr().ident()]
+                                .info()
+                                .name
+                        }
+                    };
+                    Some(format!("#{} {}", stmt_index + 1, name))
+                } else {
+                    None
+                }
+            })
+    }
+
+    pub fn stmt_note(&self, var_ident: VarIdent) -> Option<&str> {
+        self.stmt_notes.get(&var_ident).map(String::as_str)
+    }
+
+    /// Sets or clears the note of the statement declaring the variable.
+    pub fn set_stmt_note(&mut self, var_ident: VarIdent, stmt_note: Option<String>) {
+        match stmt_note {
+            Some(stmt_note) => {
+                self.stmt_notes.insert(var_ident, stmt_note);
+            }
+            None => {
+                self.stmt_notes.remove(&var_ident);
+            }
+        }
+    }
+
+    pub fn stmt_notes(&self) -> &HashMap<VarIdent, String> {
+        &self.stmt_notes
+    }
+
+    pub fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    pub fn set_notes(&mut self, notes: String) {
+        self.notes = notes;
+    }
+
+    pub fn focused_var(&self) -> Option<VarIdent> {
+        self.focused_var
+    }
+
+    /// Isolates the statement declaring the variable in the viewport, or
+    /// ends the isolation.
+    pub fn set_focused_var(&mut self, var_ident: Option<VarIdent>) {
+        self.focused_var = var_ident;
+    }
+
+    /// Returns the index of the statement declaring the focused variable.
+    pub fn focused_stmt_index(&self) -> Option<usize> {
+        let focused_var = self.focused_var?;
+        self.prog.stmts().iter().position(|stmt| {
+            let Stmt::VarDecl(var_decl) = stmt;
+            var_decl.ident() == focused_var
+        })
+    }
+
+    /// Returns whether the value of the variable is shown while a statement
+    /// is focused, i.e. the variable is the focused one or one of the inputs
+    /// of its statement. All values are shown if nothing is focused.
+    pub fn var_in_focus(&self, var_ident: VarIdent) -> bool {
+        match self.focused_stmt_index() {
+            Some(stmt_index) => {
+                let Stmt::VarDecl(var_decl) = &self.prog.stmts()[stmt_index];
+                var_decl.ident() == var_ident
+                    || var_decl.init_expr().args().iter().any(|arg| match arg {
+                        Expr::Var(var_expr) => var_expr.ident() == var_ident,
+                        Expr::Lit(_) => false,
+                    })
+            }
+            None => true,
+        }
+    }
+
+    /// Returns whether the value of the variable is used by a later
+    /// statement and is not pinned, i.e. it is an intermediate result of the
+    /// pipeline.
+    pub fn value_used(&self, var_ident: VarIdent) -> bool {
+        self.used_values.contains_key(&var_ident) && !self.pinned_vars.contains(&var_ident)
+    }
+
+    /// Asks the interpreter to stop running. It stops before the next
+    /// operation, or sooner, if the current operation streams its results,
+    /// and finishes with a cancellation error.
+    pub fn cancel_interpret(&mut self) {
+        if self.interpreter_busy() {
+            log::info!("Cancelling interpret request");
+            self.interpreter_server.cancel_interpret();
+        }
+    }
+
     /// Poll the interpreter for responses and call the callback for each
     /// notification generated this way. Polls the interpreter until there are
     /// no more messages in the response channel.
@@ -457,6 +1389,23 @@ impl Session {
 
                             log::info!("Interpreter completed edit program request {}", request_id);
                         }
+                        InterpreterResponse::CompletedSpeculate => {
+                            let tracked = self
+                                .interpreter_speculate_requests_in_flight
+                                .remove(&request_id);
+                            assert!(tracked, "Each speculate request must have been tracked");
+
+                            log::info!("Interpreter completed speculate request {}", request_id);
+                        }
+                        InterpreterResponse::StreamedMesh { stmt_index, mesh } => {
+                            log::debug!(
+                                "Interpreter streamed mesh for statement {} of request {}",
+                                stmt_index,
+                                request_id,
+                            );
+
+                            callback(PollNotification::StreamedMeshAdded(mesh));
+                        }
                         InterpreterResponse::CompletedInterpret(interpret_outcome) => {
                             let tracked = self
                                 .interpreter_interpret_request_in_flight
@@ -466,6 +1415,8 @@ impl Session {
 
                             log::info!("Interpreter completed interpret request {}", request_id);
 
+                            self.first_stale_stmt_index = None;
+
                             match interpret_outcome.result {
                                 Ok(interpret_value) => {
                                     self.process_interpret_value(interpret_value, &mut callback);
@@ -501,6 +1452,54 @@ impl Session {
                             {
                                 self.log_messages[i].extend(log_messages_at_stmt);
                             }
+
+                            self.speculate();
+                        }
+                        InterpreterResponse::AbandonedInterpret(interpret_error) => {
+                            let tracked = self
+                                .interpreter_interpret_request_in_flight
+                                .take()
+                                .is_some();
+                            assert!(tracked, "The interpret request must have been tracked");
+
+                            log::error!(
+                                "Interpreter abandoned interpret request {}: {}",
+                                request_id,
+                                interpret_error,
+                            );
+
+                            // The new interpreter starts with an empty
+                            // program. Values of the statements before the
+                            // abandoned one are computed again on the next
+                            // run.
+                            let edit_request_id = self
+                                .interpreter_server
+                                .submit_request(InterpreterRequest::SetProg(self.prog.clone()));
+                            self.interpreter_edit_prog_requests_in_flight
+                                .insert(edit_request_id);
+                            // Speculate requests submitted before the
+                            // interpret request were completed, the rest
+                            // are lost with the abandoned interpreter.
+                            self.interpreter_speculate_requests_in_flight.clear();
+                            if self.draft {
+                                let draft_request_id = self
+                                    .interpreter_server
+                                    .submit_request(InterpreterRequest::SetDraft(true));
+                                self.interpreter_edit_prog_requests_in_flight
+                                    .insert(draft_request_id);
+                            }
+
+                            // No values were delivered, so the statements
+                            // edited since the last run stay stale. So does
+                            // the abandoned statement, even if it wasn't
+                            // edited.
+                            self.mark_stale_from(interpret_error.stmt_index());
+
+                            let error_message = format!("{}", interpret_error);
+
+                            self.error = Some(interpret_error);
+
+                            callback(PollNotification::FinishedWithError(error_message));
                         }
                     }
 
@@ -557,6 +1556,7 @@ impl Session {
         } = interpret_value;
 
         let events = &mut self.diff_events;
+        let pinned_vars = &self.pinned_vars;
         let processed_idents = &mut self.diff_processed_idents;
 
         // Look at new used values to detect addition, verification and transition events.
@@ -613,14 +1613,15 @@ impl Session {
             match event {
                 DiffEvent::AddUsed(var_ident, value) => {
                     self.used_values.insert(var_ident, value.clone());
-                    callback(PollNotification::UsedValueAdded(var_ident, value));
+                    callback(used_value_added(pinned_vars, var_ident, value));
                 }
                 DiffEvent::RemoveUsed(var_ident) => {
                     let value = self
                         .used_values
                         .remove(&var_ident)
                         .expect("Values scheduled for removal must be present");
-                    callback(PollNotification::UsedValueRemoved(var_ident, value));
+                    self.removed_var_names.remove(&var_ident);
+                    callback(used_value_removed(pinned_vars, var_ident, value));
                 }
                 DiffEvent::VerifyUsed(var_ident, value) => {
                     match self.used_values.entry(var_ident) {
@@ -629,8 +1630,8 @@ impl Session {
                                 let old_value = occupied.get().clone();
                                 occupied.insert(value.clone());
 
-                                callback(PollNotification::UsedValueRemoved(var_ident, old_value));
-                                callback(PollNotification::UsedValueAdded(var_ident, value));
+                                callback(used_value_removed(pinned_vars, var_ident, old_value));
+                                callback(used_value_added(pinned_vars, var_ident, value));
                             }
                         }
                         _ => panic!("Values scheduled for verification must be present"),
@@ -643,7 +1644,7 @@ impl Session {
                         .expect("Values scheduled for transition must be present");
                     self.unused_values.insert(var_ident, value.clone());
 
-                    callback(PollNotification::UsedValueRemoved(var_ident, old_value));
+                    callback(used_value_removed(pinned_vars, var_ident, old_value));
                     callback(PollNotification::UnusedValueAdded(var_ident, value));
                 }
                 DiffEvent::AddUnused(var_ident, value) => {
@@ -655,6 +1656,7 @@ impl Session {
                         .unused_values
                         .remove(&var_ident)
                         .expect("Values scheduled for removal must be present");
+                    self.removed_var_names.remove(&var_ident);
                     callback(PollNotification::UnusedValueRemoved(var_ident, value));
                 }
                 DiffEvent::VerifyUnused(var_ident, value) => {
@@ -681,12 +1683,87 @@ impl Session {
                     self.used_values.insert(var_ident, value.clone());
 
                     callback(PollNotification::UnusedValueRemoved(var_ident, old_value));
-                    callback(PollNotification::UsedValueAdded(var_ident, value));
+                    callback(used_value_added(pinned_vars, var_ident, value));
                 }
             }
         }
     }
 
+    /// Keeps the name of a variable removed from the program, if its value
+    /// is still displayed, so that exports and picking can name it until the
+    /// interpreter removes the value.
+    fn remember_removed_var_name(&mut self, var_decl: &VarDeclStmt) {
+        let var_ident = var_decl.ident();
+        if self.used_values.contains_key(&var_ident) || self.unused_values.contains_key(&var_ident)
+        {
+            let name = match self.var_names.get(&var_ident) {
+                Some(var_name) => var_name.clone(),
+                None => self.function_table[&var_decl.init_expr().ident()]
+                    .info()
+                    .return_value_name
+                    .to_string(),
+            };
+            self.removed_var_names.insert(var_ident, name);
+        }
+    }
+
+    /// Asks the interpreter to evaluate the program with the literal
+    /// argument edited last set to its neighboring values, so that
+    /// scrubbing the argument back and forth is instant. Only done when
+    /// running automatically and when there are no edits waiting for a run.
+    fn speculate(&mut self) {
+        if self.autorun_delay.is_none()
+            || self.last_uninterpreted_edit.is_some()
+            || self.error.is_some()
+        {
+            return;
+        }
+
+        let (stmt_index, arg_index, previous_lit) = match self.speculation_candidate.take() {
+            Some(speculation_candidate) => speculation_candidate,
+            None => return,
+        };
+        let var_decl = match self.prog.stmts().get(stmt_index) {
+            Some(Stmt::VarDecl(var_decl)) => var_decl,
+            None => return,
+        };
+        let init_expr = var_decl.init_expr();
+        let current_lit = match init_expr.args().get(arg_index) {
+            Some(Expr::Lit(current_lit)) => current_lit,
+            _ => return,
+        };
+        let func = &self.function_table[&init_expr.ident()];
+        let param_index = match func.param_index_for_arg(arg_index, init_expr.args().len()) {
+            Some(param_index) => param_index,
+            None => return,
+        };
+
+        let neighbor_lits = speculation::neighbor_lits(
+            &func.param_info()[param_index].refinement,
+            &previous_lit,
+            current_lit,
+        );
+        let speculated_progs: Vec<_> = neighbor_lits
+            .into_iter()
+            .map(|neighbor_lit| {
+                let mut stmts = self.prog.stmts().to_vec();
+                stmts[stmt_index] = Stmt::VarDecl(var_decl.clone_with_init_expr(
+                    init_expr.clone_with_arg_at(arg_index, Expr::Lit(neighbor_lit)),
+                ));
+
+                Prog::new(stmts)
+            })
+            .collect();
+
+        for speculated_prog in speculated_progs {
+            let request_id = self
+                .interpreter_server
+                .submit_request(InterpreterRequest::Speculate(speculated_prog));
+            self.interpreter_speculate_requests_in_flight
+                .insert(request_id);
+        }
+    }
+
     fn recompute_var_visibility(&mut self) {
         // FIXME: Get variable visibility analysis from interpreter
 
@@ -724,3 +1801,58 @@ impl Session {
         );
     }
 }
+
+/// Returns the index and the previous value of the literal argument, if it
+/// is the only argument that changed between the statements.
+fn changed_lit_arg(previous: &VarDeclStmt, current: &VarDeclStmt) -> Option<(usize, LitExpr)> {
+    let previous_call = previous.init_expr();
+    let current_call = current.init_expr();
+    if previous.ident() != current.ident()
+        || previous_call.ident() != current_call.ident()
+        || previous_call.args().len() != current_call.args().len()
+    {
+        return None;
+    }
+
+    let mut changed_args = previous_call
+        .args()
+        .iter()
+        .zip(current_call.args())
+        .enumerate()
+        .filter(|(_, (previous_arg, current_arg))| previous_arg != current_arg);
+
+    match (changed_args.next(), changed_args.next()) {
+        (Some((arg_index, (Expr::Lit(previous_lit), Expr::Lit(_)))), None) => {
+            Some((arg_index, previous_lit.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Reports an added used value, or an unused value, if the variable is
+/// pinned.
+fn used_value_added(
+    pinned_vars: &HashSet<VarIdent>,
+    var_ident: VarIdent,
+    value: Value,
+) -> PollNotification {
+    if pinned_vars.contains(&var_ident) {
+        PollNotification::UnusedValueAdded(var_ident, value)
+    } else {
+        PollNotification::UsedValueAdded(var_ident, value)
+    }
+}
+
+/// Reports a removed used value, or an unused value, if the variable is
+/// pinned.
+fn used_value_removed(
+    pinned_vars: &HashSet<VarIdent>,
+    var_ident: VarIdent,
+    value: Value,
+) -> PollNotification {
+    if pinned_vars.contains(&var_ident) {
+        PollNotification::UnusedValueRemoved(var_ident, value)
+    } else {
+        PollNotification::UsedValueRemoved(var_ident, value)
+    }
+}
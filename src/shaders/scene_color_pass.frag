@@ -1,9 +1,15 @@
 #version 450
 
+layout(set = 0, binding = 1, std140) uniform ClipBox {
+    // The w component of the minimum corner is 1.0 if the box is enabled.
+    vec4 u_clip_box_min;
+    vec4 u_clip_box_max;
+};
+
 layout(set = 1, binding = 0) uniform sampler u_sampler;
 layout(set = 1, binding = 1) uniform samplerShadow u_shadow_sampler;
 layout(set = 2, binding = 0) uniform texture2D u_matcap_texture;
-layout(set = 3, binding = 0) uniform texture2D u_shadow_map_texture;
+layout(set = 3, binding = 0) uniform texture2DArray u_shadow_map_texture;
 
 layout(set = 4, binding = 0, std140) uniform ColorPass {
     vec4 u_shading_mode_flat_color;
@@ -12,9 +18,18 @@ layout(set = 4, binding = 0, std140) uniform ColorPass {
     uint u_shading_mode;
 };
 
+// Must match SHADOW_CASCADE_COUNT in scene_renderer.rs
+const uint SHADOW_CASCADE_COUNT = 3u;
+
+// Light space matrices of the shadow cascades, from the closest to the camera.
+layout(set = 5, binding = 0, std140) uniform ShadowCascades {
+    mat4 u_light_space_matrices[SHADOW_CASCADE_COUNT];
+};
+
 layout(location = 0) in vec2 v_matcap_tex_coords;
 layout(location = 1) in vec3 v_barycentric;
-layout(location = 2) in vec4 v_frag_pos_light_space;
+layout(location = 2) in vec4 v_color;
+layout(location = 3) in vec3 v_world_position;
 
 layout(location = 0) out vec4 f_color;
 
@@ -22,6 +37,8 @@ const uint SHADING_MODE_FLAT = 0x01;
 const uint SHADING_MODE_SHADED = 0x02;
 const uint SHADING_MODE_EDGES = 0x04;
 const uint SHADING_MODE_SHADOWED = 0x08;
+const uint SHADING_MODE_TINTED = 0x10;
+const uint SHADING_MODE_UNCLIPPED = 0x20;
 
 const float EDGE_THICKNESS_MIN = 0.75;
 const float EDGE_THICKNESS_MAX = 1.00;
@@ -29,6 +46,15 @@ const float EDGE_THICKNESS_MAX = 1.00;
 const float SHADOW_INTENSITY = 0.3;
 
 void main() {
+    // -- Clip by the clipping box --
+
+    if (u_clip_box_min.w > 0.0 && !bool(u_shading_mode & SHADING_MODE_UNCLIPPED)) {
+        if (any(lessThan(v_world_position, u_clip_box_min.xyz))
+            || any(greaterThan(v_world_position, u_clip_box_max.xyz))) {
+            discard;
+        }
+    }
+
     // -- Compute edge color --
 
     // Find which edge this pixel is the closest to by finding the
@@ -52,43 +78,66 @@ void main() {
                                      v_matcap_tex_coords).rgb,
                              u_shading_mode_shaded_alpha);
 
-    // -- Compute shadow --
+    // Tint the matcap with the vertex color, if the mesh has any. Zero
+    // alpha means no vertex color.
+    if (v_color.a > 0.0) {
+        matcap_color.rgb *= v_color.rgb;
+    }
 
-    // Since this is not a glsl builtin, we have to perform perspective divide ourselves.
-    vec3 frag_pos_light_space = v_frag_pos_light_space.xyz / v_frag_pos_light_space.w;
+    // Tint the matcap with the flat color, if the material is tinted.
+    if (bool(u_shading_mode & SHADING_MODE_TINTED)) {
+        matcap_color.rgb *= u_shading_mode_flat_color.rgb;
+    }
 
-    // Remap NDC coords ([-1, 1]) to texture coords ([0, 1]) and keep depth.
-    vec2 shadow_tex_coords = vec2(frag_pos_light_space.x, -frag_pos_light_space.y) * 0.5 + 0.5;
-    float shadow_depth = frag_pos_light_space.z;
+    // -- Compute shadow --
 
     float shadow = 0.0;
-    vec2 shadow_map_texel_size = 1.0 / textureSize(sampler2DShadow(u_shadow_map_texture,
-                                                                   u_shadow_sampler), 0);
+    vec2 shadow_map_texel_size = 1.0 / textureSize(sampler2DArrayShadow(u_shadow_map_texture,
+                                                                        u_shadow_sampler), 0).xy;
 
     if (bool(u_shading_mode & SHADING_MODE_SHADOWED)) {
+        // Pick the closest cascade containing the fragment together with
+        // all of its PCF samples. The cascades are ordered from the
+        // sharpest.
+        int cascade = -1;
+        vec2 shadow_tex_coords;
+        float shadow_depth;
+
+        for (uint i = 0u; i < SHADOW_CASCADE_COUNT; i++) {
+            vec4 frag_pos_light_space = u_light_space_matrices[i] * vec4(v_world_position, 1.0);
+
+            // Since this is not a glsl builtin, we have to perform perspective divide ourselves.
+            vec3 frag_pos_ndc = frag_pos_light_space.xyz / frag_pos_light_space.w;
+
+            // Remap NDC coords ([-1, 1]) to texture coords ([0, 1]) and keep depth.
+            vec2 tex_coords = vec2(frag_pos_ndc.x, -frag_pos_ndc.y) * 0.5 + 0.5;
+            vec2 margin = 2.0 * shadow_map_texel_size;
+
+            if (all(greaterThanEqual(tex_coords, margin))
+                && all(lessThanEqual(tex_coords, 1.0 - margin))) {
+                cascade = int(i);
+                shadow_tex_coords = tex_coords;
+                shadow_depth = frag_pos_ndc.z;
+                break;
+            }
+        }
+
         // Use Percentage Closer Filtering (PCF) - sample the depth texture sixteen
         // times, each time between texels.
         // https://developer.nvidia.com/gpugems/gpugems/part-ii-lighting-and-shadows/chapter-11-shadow-map-antialiasing
 
         // Protect against sampling beyong depth 1.0 (the light's far plane).
-        if (shadow_depth <= 1.0) {
-
+        if (cascade >= 0 && shadow_depth <= 1.0) {
             for (float x = -1.5; x <= 1.5; x += 1.0) {
                 for (float y = -1.5; y <= 1.5; y += 1.0) {
                     vec2 offset = vec2(x, y) * shadow_map_texel_size;
-                    vec3 lookup_coords = vec3(shadow_tex_coords + offset, shadow_depth);
-
-                    // Protect against oversampling. If we were to sample
-                    // outside the shadow map, let's not accumulate any shadow.
-                    if (lookup_coords.x >= 0.0
-                        && lookup_coords.x <= 1.0
-                        && lookup_coords.y >= 0.0
-                        && lookup_coords.y <= 1.0)
-                        {
-                            // Accumulate shadow if the depth comparison succeeds
-                            shadow += texture(sampler2DShadow(u_shadow_map_texture,
-                                                              u_shadow_sampler), lookup_coords);
-                        }
+                    vec4 lookup_coords = vec4(shadow_tex_coords + offset,
+                                              float(cascade),
+                                              shadow_depth);
+
+                    // Accumulate shadow if the depth comparison succeeds
+                    shadow += texture(sampler2DArrayShadow(u_shadow_map_texture,
+                                                           u_shadow_sampler), lookup_coords);
                 }
             }
         }
@@ -5,17 +5,16 @@ layout(set = 0, binding = 0, std140) uniform Matrix {
     mat4 u_view_matrix;
 };
 
-layout(set = 5, binding = 0, std140) uniform ShadowPass {
-    mat4 u_light_space_matrix;
-};
-
 layout(location = 0) in vec4 a_position;
 layout(location = 1) in vec4 a_normal;
 layout(location = 2) in uint a_barycentric;
+layout(location = 3) in vec4 a_color;
+layout(location = 4) in vec4 a_translation;
 
 layout(location = 0) out vec2 v_matcap_tex_coords;
 layout(location = 1) out vec3 v_barycentric;
-layout(location = 2) out vec4 v_frag_pos_light_space;
+layout(location = 2) out vec4 v_color;
+layout(location = 3) out vec3 v_world_position;
 
 float remap(float value, vec2 from, vec2 to) {
     return (value - from.x) / (from.y - from.x) * (to.y - to.x) + to.x;
@@ -40,7 +39,11 @@ void main() {
 
     v_barycentric = get_barycentric_coord(a_barycentric);
 
-    v_frag_pos_light_space = u_light_space_matrix * a_position;
+    vec4 position = a_position + vec4(a_translation.xyz * a_position.w, 0.0);
+
+    v_color = a_color;
+
+    v_world_position = position.xyz / position.w;
 
-    gl_Position = u_projection_matrix * u_view_matrix * a_position;
+    gl_Position = u_projection_matrix * u_view_matrix * position;
 }
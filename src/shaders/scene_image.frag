@@ -0,0 +1,18 @@
+#version 450
+
+layout(set = 1, binding = 0) uniform sampler u_sampler;
+layout(set = 2, binding = 0) uniform texture2D u_image_texture;
+
+layout(set = 3, binding = 0, std140) uniform ImagePass {
+    float u_opacity;
+};
+
+layout(location = 0) in vec2 v_tex_coords;
+
+layout(location = 0) out vec4 f_color;
+
+void main() {
+    vec4 image_color = texture(sampler2D(u_image_texture, u_sampler), v_tex_coords);
+
+    f_color = vec4(image_color.rgb, image_color.a * u_opacity);
+}
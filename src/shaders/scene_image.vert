@@ -0,0 +1,17 @@
+#version 450
+
+layout(set = 0, binding = 0, std140) uniform Matrix {
+    mat4 u_projection_matrix;
+    mat4 u_view_matrix;
+};
+
+layout(location = 0) in vec4 a_position;
+layout(location = 1) in vec2 a_tex_coords;
+
+layout(location = 0) out vec2 v_tex_coords;
+
+void main() {
+    v_tex_coords = a_tex_coords;
+
+    gl_Position = u_projection_matrix * u_view_matrix * a_position;
+}
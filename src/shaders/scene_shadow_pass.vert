@@ -5,7 +5,9 @@ layout(set = 0, binding = 0, std140) uniform ShadowPass {
 };
 
 layout(location = 0) in vec4 a_position;
+layout(location = 1) in vec4 a_translation;
 
 void main() {
-    gl_Position = u_light_space_matrix * a_position;
+    vec4 position = a_position + vec4(a_translation.xyz * a_position.w, 0.0);
+    gl_Position = u_light_space_matrix * position;
 }
@@ -0,0 +1,134 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel as channel;
+
+/// Port on localhost the first running instance listens on for projects
+/// opened by later instances.
+const PORT: u16 = 47_813;
+const GREETING: &str = "hurban_selector open";
+const ACKNOWLEDGEMENT: &str = "ok";
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Sends the project path to an already running instance, e.g. when a
+/// project is opened from the file manager. Returns whether the running
+/// instance received it. If not, the current instance should open the
+/// project itself.
+pub fn forward(path: &Path) -> bool {
+    // The running instance may have a different working directory
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, PORT));
+    let result = TcpStream::connect_timeout(&address, TIMEOUT).and_then(|mut stream| {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        write!(stream, "{}\n{}\n", GREETING, path.to_string_lossy())?;
+
+        // Another application could be listening on the port, so only an
+        // acknowledged path counts as received
+        let mut acknowledgement = String::new();
+        BufReader::new(stream).read_line(&mut acknowledgement)?;
+
+        Ok(acknowledgement.trim_end() == ACKNOWLEDGEMENT)
+    });
+
+    match result {
+        Ok(received) => received,
+        Err(err) => {
+            log::debug!("No running instance to forward the project to: {}", err);
+            false
+        }
+    }
+}
+
+/// Receives the paths of projects forwarded by later instances.
+pub struct InstanceListener {
+    receiver: channel::Receiver<PathBuf>,
+}
+
+impl InstanceListener {
+    /// Starts listening, unless another instance is listening already. The
+    /// `wake` callback is called after each received path, so that the main
+    /// loop doesn't sleep through it.
+    pub fn start<W>(wake: W) -> io::Result<Self>
+    where
+        W: Fn() + Send + 'static,
+    {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, PORT))?;
+        let (sender, receiver) = channel::unbounded();
+
+        thread::Builder::new()
+            .name(String::from("hurban_selector_instance_listener"))
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(err) => {
+                            log::warn!("Failed to accept forwarded project: {}", err);
+                            continue;
+                        }
+                    };
+
+                    if let Err(err) = stream.set_read_timeout(Some(TIMEOUT)) {
+                        log::warn!("Failed to accept forwarded project: {}", err);
+                        continue;
+                    }
+
+                    let mut reader = BufReader::new(stream);
+                    if let Some(path) = read_forwarded_path(&mut reader) {
+                        let _ = writeln!(reader.get_mut(), "{}", ACKNOWLEDGEMENT);
+                        if sender.send(path).is_err() {
+                            break;
+                        }
+                        wake();
+                    }
+                }
+            })?;
+
+        Ok(Self { receiver })
+    }
+
+    /// Returns the next forwarded project path, if any.
+    pub fn try_recv(&self) -> Option<PathBuf> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+fn read_forwarded_path<R: BufRead>(reader: &mut R) -> Option<PathBuf> {
+    let mut greeting = String::new();
+    reader.read_line(&mut greeting).ok()?;
+    if greeting.trim_end() != GREETING {
+        return None;
+    }
+
+    let mut path = String::new();
+    reader.read_line(&mut path).ok()?;
+    let path = path.trim_end_matches(|c| c == '\n' || c == '\r');
+    if path.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_forwarded_path() {
+        let mut request: &[u8] = b"hurban_selector open\n/home/user/my project.hurban\n";
+        assert_eq!(
+            read_forwarded_path(&mut request),
+            Some(PathBuf::from("/home/user/my project.hurban")),
+        );
+
+        let mut request: &[u8] = b"GET / HTTP/1.1\r\n/etc/passwd\n";
+        assert_eq!(read_forwarded_path(&mut request), None);
+
+        let mut request: &[u8] = b"hurban_selector open\n";
+        assert_eq!(read_forwarded_path(&mut request), None);
+    }
+}
@@ -0,0 +1,181 @@
+use nalgebra::{Point2, Point3, Vector3};
+
+use crate::interpreter::ast;
+use crate::interpreter_funcs;
+use crate::mesh::{primitive, tools, Mesh};
+
+/// Clicks closer to the first point than this fraction of the radius of the
+/// visible scene close the outline.
+pub const CLOSE_DISTANCE_FRACTION: f32 = 0.02;
+
+/// Points closer to the previous point than this are considered accidental
+/// double clicks and are not added.
+const MIN_DISTANCE: f32 = 0.000_001;
+
+/// A footprint sketched in the viewport: an outline of points clicked on the
+/// ground, which is closed by clicking its first point again.
+#[derive(Debug, Clone, Default)]
+pub struct FootprintSketch {
+    points: Vec<Point2<f32>>,
+    cursor: Option<Point2<f32>>,
+}
+
+impl FootprintSketch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.points.clear();
+        self.cursor = None;
+    }
+
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Sets the ground point under the cursor, which is previewed as the next
+    /// point of the outline.
+    pub fn set_cursor(&mut self, cursor: Option<Point3<f32>>) {
+        self.cursor = cursor.map(|cursor| Point2::new(cursor.x, cursor.y));
+    }
+
+    /// Adds the ground point to the outline. Returns whether the point closes
+    /// the outline, i.e. whether it is within the distance from the first
+    /// point of an outline that can already be closed. Closing points are not
+    /// added.
+    pub fn add_point(&mut self, point: &Point3<f32>, close_distance: f32) -> bool {
+        let point = Point2::new(point.x, point.y);
+
+        if let Some(first) = self.points.first() {
+            if self.can_close() && nalgebra::distance(first, &point) <= close_distance {
+                return true;
+            }
+        }
+
+        if let Some(last) = self.points.last() {
+            if nalgebra::distance(last, &point) <= MIN_DISTANCE {
+                return false;
+            }
+        }
+
+        self.points.push(point);
+        false
+    }
+
+    /// Removes the last point of the outline.
+    pub fn remove_last_point(&mut self) {
+        self.points.pop();
+    }
+
+    /// Whether the outline has enough points and encloses an area, so that it
+    /// can be closed into a footprint.
+    pub fn can_close(&self) -> bool {
+        self.points.len() >= 3 && primitive::create_polygon(&self.points).is_some()
+    }
+
+    /// Returns the footprint sketched so far extruded to the height, for
+    /// previewing it in the viewport. The point under the cursor is included
+    /// as the last point of the outline.
+    pub fn mesh(&self, height: f32) -> Option<Mesh> {
+        let mut outline = self.points.clone();
+        if let Some(cursor) = self.cursor {
+            outline.push(cursor);
+        }
+
+        let polygon = primitive::create_polygon(&outline)?;
+        if height.abs() > MIN_DISTANCE {
+            Some(tools::extrude(&polygon, &Vector3::new(0.0, 0.0, height)))
+        } else {
+            Some(polygon)
+        }
+    }
+
+    /// Returns the args of a new Create Polygon producing the footprint, once
+    /// it can be closed. The remaining args are kept at their defaults.
+    pub fn create_polygon_args(&self, mut default_args: Vec<ast::Expr>) -> Option<Vec<ast::Expr>> {
+        if !self.can_close() {
+            return None;
+        }
+
+        default_args[interpreter_funcs::CREATE_POLYGON_PARAM_INDEX_OUTLINE] = ast::Expr::Lit(
+            ast::LitExpr::String(interpreter_funcs::format_outline(&self.points)),
+        );
+
+        Some(default_args)
+    }
+}
+
+/// Returns the args of a new Extrude, extruding the footprint polygon of the
+/// variable to the height. The remaining args are kept at their defaults.
+pub fn extrude_args(
+    mut default_args: Vec<ast::Expr>,
+    var_ident: ast::VarIdent,
+    height: f32,
+) -> Vec<ast::Expr> {
+    default_args[interpreter_funcs::EXTRUDE_PARAM_INDEX_MESH] =
+        ast::Expr::Var(ast::VarExpr::new(var_ident));
+    default_args[interpreter_funcs::EXTRUDE_PARAM_INDEX_HEIGHT] =
+        ast::Expr::Lit(ast::LitExpr::Float(height));
+
+    default_args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_create_polygon_args() -> Vec<ast::Expr> {
+        vec![
+            ast::Expr::Lit(ast::LitExpr::String(String::from("0 0; 1 0; 1 1; 0 1"))),
+            ast::Expr::Lit(ast::LitExpr::Boolean(false)),
+        ]
+    }
+
+    #[test]
+    fn test_sketch_closes_on_first_point() {
+        let mut sketch = FootprintSketch::new();
+        assert!(!sketch.add_point(&Point3::new(0.0, 0.0, 0.0), 0.5));
+        assert!(!sketch.add_point(&Point3::new(4.0, 0.0, 0.0), 0.5));
+
+        // Two points can not be closed yet, the point is added instead
+        assert!(!sketch.add_point(&Point3::new(0.2, 0.0, 0.0), 0.1));
+        sketch.remove_last_point();
+
+        assert!(!sketch.add_point(&Point3::new(4.0, 3.0, 0.0), 0.5));
+        assert!(sketch.add_point(&Point3::new(0.1, 0.1, 0.0), 0.5));
+        assert_eq!(sketch.point_count(), 3);
+
+        let args = sketch
+            .create_polygon_args(default_create_polygon_args())
+            .expect("Must create polygon from closed outline");
+        assert_eq!(
+            args[0],
+            ast::Expr::Lit(ast::LitExpr::String(String::from("0 0; 4 0; 4 3"))),
+        );
+        assert_eq!(args[1], ast::Expr::Lit(ast::LitExpr::Boolean(false)));
+    }
+
+    #[test]
+    fn test_sketch_skips_repeated_point() {
+        let mut sketch = FootprintSketch::new();
+        sketch.add_point(&Point3::new(1.0, 1.0, 0.0), 0.5);
+        sketch.add_point(&Point3::new(1.0, 1.0, 0.0), 0.5);
+
+        assert_eq!(sketch.point_count(), 1);
+    }
+
+    #[test]
+    fn test_sketch_collinear_points_can_not_be_closed() {
+        let mut sketch = FootprintSketch::new();
+        sketch.add_point(&Point3::new(0.0, 0.0, 0.0), 0.5);
+        sketch.add_point(&Point3::new(1.0, 0.0, 0.0), 0.5);
+        sketch.add_point(&Point3::new(2.0, 0.0, 0.0), 0.5);
+
+        assert!(!sketch.can_close());
+        assert!(!sketch.add_point(&Point3::new(0.0, 0.0, 0.0), 0.5));
+        assert!(sketch
+            .create_polygon_args(default_create_polygon_args())
+            .is_none());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::interpreter::Dimension;
+
+const DEFAULT_DISTANCE_INCREMENT: f32 = 0.5;
+const DEFAULT_ANGLE_INCREMENT: f32 = 15.0;
+
+/// Increments that transform values (positions and rotations) snap to when
+/// they are edited, so that massing elements align cleanly.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Snapping {
+    pub enabled: bool,
+    /// Distance increment in project units.
+    pub distance: f32,
+    /// Angle increment in degrees.
+    pub angle: f32,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            distance: DEFAULT_DISTANCE_INCREMENT,
+            angle: DEFAULT_ANGLE_INCREMENT,
+        }
+    }
+}
+
+impl Snapping {
+    /// Returns the increment values of the dimension snap to, or `None` if
+    /// snapping is disabled or doesn't apply to the dimension.
+    pub fn increment(&self, dimension: Dimension) -> Option<f32> {
+        if !self.enabled {
+            return None;
+        }
+
+        let increment = match dimension {
+            Dimension::Length => self.distance,
+            Dimension::Angle => self.angle,
+            Dimension::Dimensionless | Dimension::Area | Dimension::Volume => return None,
+        };
+
+        if increment > 0.0 {
+            Some(increment)
+        } else {
+            None
+        }
+    }
+}
+
+/// Rounds the value to the nearest multiple of the increment.
+pub fn snap(value: f32, increment: f32) -> f32 {
+    (value / increment).round() * increment
+}
+
+/// Rounds each component of the value to the nearest multiple of the
+/// increment.
+pub fn snap3(value: [f32; 3], increment: f32) -> [f32; 3] {
+    [
+        snap(value[0], increment),
+        snap(value[1], increment),
+        snap(value[2], increment),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapping_increment() {
+        let mut snapping = Snapping {
+            enabled: true,
+            distance: 0.25,
+            angle: 45.0,
+        };
+
+        assert_eq!(snapping.increment(Dimension::Length), Some(0.25));
+        assert_eq!(snapping.increment(Dimension::Angle), Some(45.0));
+        assert_eq!(snapping.increment(Dimension::Area), None);
+        assert_eq!(snapping.increment(Dimension::Dimensionless), None);
+
+        snapping.distance = 0.0;
+        assert_eq!(snapping.increment(Dimension::Length), None);
+
+        snapping.enabled = false;
+        assert_eq!(snapping.increment(Dimension::Angle), None);
+    }
+
+    #[test]
+    fn test_snap() {
+        approx::assert_relative_eq!(snap(1.3, 0.5), 1.5);
+        approx::assert_relative_eq!(snap(-1.2, 0.5), -1.0);
+        approx::assert_relative_eq!(snap(37.0, 15.0), 30.0);
+
+        let snapped = snap3([0.1, 7.6, -22.6], 15.0);
+        approx::assert_relative_eq!(snapped[0], 0.0);
+        approx::assert_relative_eq!(snapped[1], 15.0);
+        approx::assert_relative_eq!(snapped[2], -30.0);
+    }
+}
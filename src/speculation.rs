@@ -0,0 +1,137 @@
+use crate::interpreter::ast::LitExpr;
+use crate::interpreter::ParamRefinement;
+
+/// Returns the values neighboring the current value of a numeric parameter,
+/// one step back and one step forward. The step is the difference from the
+/// previous value, so that scrubbing a slider back and forth hits the
+/// neighbors. The neighbors are clamped to the range of the parameter and
+/// never equal the current value.
+///
+/// Returns no neighbors for non-numeric values, values of different types
+/// and unchanged values.
+pub fn neighbor_lits(
+    refinement: &ParamRefinement,
+    previous: &LitExpr,
+    current: &LitExpr,
+) -> Vec<LitExpr> {
+    let neighbors: Vec<LitExpr> = match (refinement, previous, current) {
+        (ParamRefinement::Int(refinement), LitExpr::Int(previous), LitExpr::Int(current)) => {
+            let step = current.wrapping_sub(*previous);
+            vec![current.checked_sub(step), current.checked_add(step)]
+                .into_iter()
+                .flatten()
+                .map(|neighbor| LitExpr::Int(refinement.clamp(neighbor)))
+                .collect()
+        }
+        (ParamRefinement::Uint(refinement), LitExpr::Uint(previous), LitExpr::Uint(current)) => {
+            let forward = if current > previous {
+                current.checked_add(current - previous)
+            } else {
+                current.checked_sub(previous - current)
+            };
+            vec![Some(*previous), forward]
+                .into_iter()
+                .flatten()
+                .map(|neighbor| LitExpr::Uint(refinement.clamp(neighbor)))
+                .collect()
+        }
+        (ParamRefinement::Float(refinement), LitExpr::Float(previous), LitExpr::Float(current)) => {
+            let step = current - previous;
+            vec![
+                LitExpr::Float(refinement.clamp(current - step)),
+                LitExpr::Float(refinement.clamp(current + step)),
+            ]
+        }
+        (
+            ParamRefinement::Float2(refinement),
+            LitExpr::Float2(previous),
+            LitExpr::Float2(current),
+        ) => {
+            let step = [current[0] - previous[0], current[1] - previous[1]];
+            vec![
+                LitExpr::Float2(refinement.clamp([current[0] - step[0], current[1] - step[1]])),
+                LitExpr::Float2(refinement.clamp([current[0] + step[0], current[1] + step[1]])),
+            ]
+        }
+        (
+            ParamRefinement::Float3(refinement),
+            LitExpr::Float3(previous),
+            LitExpr::Float3(current),
+        ) => {
+            let step = [
+                current[0] - previous[0],
+                current[1] - previous[1],
+                current[2] - previous[2],
+            ];
+            vec![
+                LitExpr::Float3(refinement.clamp([
+                    current[0] - step[0],
+                    current[1] - step[1],
+                    current[2] - step[2],
+                ])),
+                LitExpr::Float3(refinement.clamp([
+                    current[0] + step[0],
+                    current[1] + step[1],
+                    current[2] + step[2],
+                ])),
+            ]
+        }
+        _ => Vec::new(),
+    };
+
+    let mut unique_neighbors: Vec<LitExpr> = Vec::with_capacity(neighbors.len());
+    for neighbor in neighbors {
+        if neighbor != *current && !unique_neighbors.contains(&neighbor) {
+            unique_neighbors.push(neighbor);
+        }
+    }
+
+    unique_neighbors
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::{FloatParamRefinement, UintParamRefinement};
+
+    use super::*;
+
+    #[test]
+    fn test_speculation_neighbor_lits_float() {
+        let refinement = ParamRefinement::Float(FloatParamRefinement::default());
+
+        let neighbors = neighbor_lits(&refinement, &LitExpr::Float(1.0), &LitExpr::Float(1.5));
+
+        assert_eq!(neighbors, vec![LitExpr::Float(1.0), LitExpr::Float(2.0)]);
+    }
+
+    #[test]
+    fn test_speculation_neighbor_lits_uint_decreasing_and_clamped() {
+        let refinement = ParamRefinement::Uint(UintParamRefinement {
+            default_value: None,
+            min_value: Some(1),
+            max_value: Some(4),
+        });
+
+        let neighbors = neighbor_lits(&refinement, &LitExpr::Uint(4), &LitExpr::Uint(2));
+        assert_eq!(neighbors, vec![LitExpr::Uint(4), LitExpr::Uint(1)]);
+
+        let neighbors = neighbor_lits(&refinement, &LitExpr::Uint(3), &LitExpr::Uint(4));
+        assert_eq!(neighbors, vec![LitExpr::Uint(3)]);
+
+        let neighbors = neighbor_lits(&refinement, &LitExpr::Uint(2), &LitExpr::Uint(1));
+        assert_eq!(neighbors, vec![LitExpr::Uint(2)]);
+    }
+
+    #[test]
+    fn test_speculation_neighbor_lits_none_for_unchanged_or_non_numeric() {
+        let refinement = ParamRefinement::Float(FloatParamRefinement::default());
+
+        assert!(neighbor_lits(&refinement, &LitExpr::Float(1.0), &LitExpr::Float(1.0)).is_empty());
+        assert!(neighbor_lits(
+            &refinement,
+            &LitExpr::Boolean(false),
+            &LitExpr::Boolean(true)
+        )
+        .is_empty());
+    }
+}
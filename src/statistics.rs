@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::preferences::{self, ConfigFileError};
+
+/// Version of the statistics file schema. Increment when changing the
+/// meaning of existing fields.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const STATISTICS_FILE_NAME: &str = "statistics.ron";
+
+/// Whether the user agreed to keep usage statistics. Nothing is recorded
+/// until the user grants the consent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum StatisticsConsent {
+    Undecided,
+    Granted,
+    Denied,
+}
+
+impl Default for StatisticsConsent {
+    fn default() -> Self {
+        StatisticsConsent::Undecided
+    }
+}
+
+/// Usage statistics of the application. They are kept only on the user's
+/// machine, are never sent anywhere and can be viewed and cleared by the user
+/// at any time.
+///
+/// The statistics are stored in the user configuration directory in
+/// `statistics.ron` with the following fields:
+///
+/// - `version`: The schema version, see `SCHEMA_VERSION`.
+/// - `sessions`: How many times the application was started.
+/// - `projects_created`, `projects_opened`, `projects_saved`: How many times
+///   a project was created, opened or saved.
+/// - `pipeline_runs`: How many times the operation pipeline finished
+///   successfully.
+/// - `pipeline_runtime_total_ms`, `pipeline_runtime_max_ms`: The total and
+///   longest duration of the successful pipeline runs in milliseconds.
+/// - `operations`: For each operation name, in how many successful pipeline
+///   runs the operation was present.
+///
+/// No paths, file names, geometry or parameter values are recorded.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    pub version: u32,
+    pub sessions: u64,
+    pub projects_created: u64,
+    pub projects_opened: u64,
+    pub projects_saved: u64,
+    pub pipeline_runs: u64,
+    pub pipeline_runtime_total_ms: f64,
+    pub pipeline_runtime_max_ms: f64,
+    pub operations: BTreeMap<String, u64>,
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            sessions: 0,
+            projects_created: 0,
+            projects_opened: 0,
+            projects_saved: 0,
+            pipeline_runs: 0,
+            pipeline_runtime_total_ms: 0.0,
+            pipeline_runtime_max_ms: 0.0,
+            operations: BTreeMap::new(),
+        }
+    }
+}
+
+impl Statistics {
+    /// Records a successful pipeline run of the duration, containing the
+    /// operations of the names.
+    pub fn record_pipeline_run<'a, I>(&mut self, duration: Duration, operation_names: I)
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let duration_ms = duration.as_secs_f64() * 1000.0;
+
+        self.pipeline_runs += 1;
+        self.pipeline_runtime_total_ms += duration_ms;
+        self.pipeline_runtime_max_ms = self.pipeline_runtime_max_ms.max(duration_ms);
+
+        let mut names: Vec<_> = operation_names.into_iter().collect();
+        names.sort_unstable();
+        names.dedup();
+        for name in names {
+            *self.operations.entry(name.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// The average duration of the successful pipeline runs in milliseconds.
+    pub fn pipeline_runtime_average_ms(&self) -> Option<f64> {
+        if self.pipeline_runs == 0 {
+            None
+        } else {
+            Some(self.pipeline_runtime_total_ms / self.pipeline_runs as f64)
+        }
+    }
+
+    /// Returns the operation names and their counts, the most used first.
+    pub fn operations_by_count(&self) -> Vec<(&str, u64)> {
+        let mut operations: Vec<_> = self
+            .operations
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+            .collect();
+        operations.sort_by(|(name_a, count_a), (name_b, count_b)| {
+            count_b.cmp(count_a).then_with(|| name_a.cmp(name_b))
+        });
+
+        operations
+    }
+}
+
+/// Loads statistics from the user configuration directory. Returns empty
+/// statistics if none were saved yet.
+pub fn load() -> Result<Statistics, ConfigFileError> {
+    preferences::read_config_file(STATISTICS_FILE_NAME)
+}
+
+/// Saves statistics to the user configuration directory.
+pub fn save(statistics: &Statistics) -> Result<(), ConfigFileError> {
+    preferences::write_config_file(STATISTICS_FILE_NAME, statistics)
+}
+
+/// Deletes the saved statistics, if any.
+pub fn delete() -> Result<(), ConfigFileError> {
+    match fs::remove_file(path()?) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns where the statistics are stored.
+pub fn path() -> Result<PathBuf, ConfigFileError> {
+    preferences::config_file_path(STATISTICS_FILE_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistics_record_pipeline_run() {
+        let mut statistics = Statistics::default();
+        assert_eq!(statistics.pipeline_runtime_average_ms(), None);
+
+        statistics.record_pipeline_run(
+            Duration::from_millis(100),
+            vec!["Create Box", "Transform", "Transform"],
+        );
+        statistics.record_pipeline_run(Duration::from_millis(300), vec!["Transform"]);
+
+        assert_eq!(statistics.pipeline_runs, 2);
+        approx::assert_relative_eq!(statistics.pipeline_runtime_total_ms, 400.0);
+        approx::assert_relative_eq!(statistics.pipeline_runtime_max_ms, 300.0);
+        approx::assert_relative_eq!(statistics.pipeline_runtime_average_ms().unwrap(), 200.0);
+
+        assert_eq!(
+            statistics.operations_by_count(),
+            vec![("Transform", 2), ("Create Box", 1)],
+        );
+    }
+
+    #[test]
+    fn test_statistics_deserialize_older_schema() {
+        let statistics: Statistics =
+            ron::de::from_str("(version: 1, sessions: 3)").expect("Must deserialize");
+
+        assert_eq!(statistics.sessions, 3);
+        assert_eq!(statistics.pipeline_runs, 0);
+        assert!(statistics.operations.is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use nalgebra::{Matrix4, Vector3};
+
+use crate::camera::Camera;
+use crate::renderer::Viewport;
+
+/// Average distance between human eyes in meters. Using it makes the scene
+/// look life-sized in VR viewers.
+pub const EYE_SEPARATION_DEFAULT: f32 = 0.065;
+
+/// Largest eye separation offered. Separations larger than the human one make
+/// large scenes, e.g. urban massing, look like scale models.
+pub const EYE_SEPARATION_MAX: f32 = 10.0;
+
+/// A camera the scene is drawn with, and the area of the render target it
+/// draws to.
+#[derive(Debug, Clone, Copy)]
+pub struct View {
+    /// The area of the render target, or the whole render target if `None`.
+    pub viewport: Option<Viewport>,
+    pub projection_matrix: Matrix4<f32>,
+    pub view_matrix: Matrix4<f32>,
+}
+
+/// Returns the views to draw the scene with into a render target of the given
+/// dimensions. With eye separation, the left and the right eye are drawn side
+/// by side. Otherwise the camera fills the whole render target.
+pub fn views(camera: &Camera, width: u32, height: u32, eye_separation: Option<f32>) -> Vec<View> {
+    match eye_separation {
+        Some(eye_separation) => {
+            let eye_viewports = eye_viewports(width, height);
+
+            let mut eye_camera = camera.clone();
+            eye_camera.set_screen_dimensions(eye_viewports[0].width, height);
+            let eye_view_matrices = eye_view_matrices(&eye_camera.view_matrix(), eye_separation);
+
+            eye_viewports
+                .iter()
+                .zip(&eye_view_matrices)
+                .map(|(eye_viewport, eye_view_matrix)| View {
+                    viewport: Some(*eye_viewport),
+                    projection_matrix: eye_camera.projection_matrix(),
+                    view_matrix: *eye_view_matrix,
+                })
+                .collect()
+        }
+        None => vec![View {
+            viewport: None,
+            projection_matrix: camera.projection_matrix(),
+            view_matrix: camera.view_matrix(),
+        }],
+    }
+}
+
+/// Returns the view matrices of the left and the right eye. The eyes are moved
+/// apart along the horizontal axis of the view and look in parallel, so that
+/// the images have no vertical parallax.
+pub fn eye_view_matrices(view_matrix: &Matrix4<f32>, eye_separation: f32) -> [Matrix4<f32>; 2] {
+    let half_separation = eye_separation / 2.0;
+
+    // Moving an eye to the left moves the scene to the right in its view
+    [
+        Matrix4::new_translation(&Vector3::new(half_separation, 0.0, 0.0)) * view_matrix,
+        Matrix4::new_translation(&Vector3::new(-half_separation, 0.0, 0.0)) * view_matrix,
+    ]
+}
+
+/// Returns the viewports of the left and the right eye, side by side in a
+/// render target of the given dimensions. The viewports of both eyes have the
+/// same size, odd widths leave the last column of pixels empty.
+pub fn eye_viewports(width: u32, height: u32) -> [Viewport; 2] {
+    let eye_width = width / 2;
+
+    [
+        Viewport {
+            x: 0,
+            y: 0,
+            width: eye_width,
+            height,
+        },
+        Viewport {
+            x: eye_width,
+            y: 0,
+            width: eye_width,
+            height,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use super::*;
+
+    #[test]
+    fn test_eye_view_matrices_move_eyes_apart() {
+        let [left, right] = eye_view_matrices(&Matrix4::identity(), 0.5);
+
+        assert_eq!(
+            left.transform_point(&Point3::origin()),
+            Point3::new(0.25, 0.0, 0.0),
+        );
+        assert_eq!(
+            right.transform_point(&Point3::origin()),
+            Point3::new(-0.25, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn test_eye_viewports_are_side_by_side() {
+        let [left, right] = eye_viewports(101, 50);
+
+        assert_eq!(
+            left,
+            Viewport {
+                x: 0,
+                y: 0,
+                width: 50,
+                height: 50,
+            },
+        );
+        assert_eq!(
+            right,
+            Viewport {
+                x: 50,
+                y: 0,
+                width: 50,
+                height: 50,
+            },
+        );
+    }
+}
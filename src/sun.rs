@@ -0,0 +1,212 @@
+use std::f32;
+
+use nalgebra::Vector3;
+
+use crate::math;
+
+/// The latitude used until the user sets their own, roughly that of central
+/// Europe.
+pub const DEFAULT_LATITUDE: f32 = 48.15;
+
+/// The day of the summer solstice in a non-leap year.
+pub const DEFAULT_DAY_OF_YEAR: u32 = 172;
+
+pub const DAYS_IN_YEAR: u32 = 365;
+
+/// Options of the sun model driving the shadow casting light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunOptions {
+    /// If disabled, the light shines straight down, as if the sun was in the
+    /// zenith.
+    pub enabled: bool,
+    /// Day of the year, starting at 1 for January 1st.
+    pub day_of_year: u32,
+    /// Local solar time in hours, 12.0 being the solar noon.
+    pub solar_time: f32,
+}
+
+impl Default for SunOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            day_of_year: DEFAULT_DAY_OF_YEAR,
+            solar_time: 12.0,
+        }
+    }
+}
+
+impl SunOptions {
+    /// Returns the sun position for these options at the latitude, or `None`
+    /// if the sun model is disabled.
+    pub fn sun_position(&self, latitude: f32) -> Option<SunPosition> {
+        if self.enabled {
+            Some(SunPosition::compute(
+                latitude,
+                self.day_of_year,
+                self.solar_time,
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Options of a shadow study: an image sequence capturing the shadows cast
+/// over the course of a day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowStudyOptions {
+    /// Local solar time of the first image, in hours.
+    pub start_time: f32,
+    /// Local solar time of the last image, in hours.
+    pub end_time: f32,
+    /// The time between two images, in minutes.
+    pub step_minutes: u32,
+}
+
+impl Default for ShadowStudyOptions {
+    fn default() -> Self {
+        Self {
+            start_time: 6.0,
+            end_time: 18.0,
+            step_minutes: 60,
+        }
+    }
+}
+
+impl ShadowStudyOptions {
+    /// Returns the solar times of all images of the study.
+    pub fn times(&self) -> Vec<f32> {
+        let step = self.step_minutes.max(1) as f32 / 60.0;
+
+        let mut times = Vec::new();
+        let mut time = self.start_time;
+        while time <= self.end_time + step * 0.001 {
+            times.push(time);
+            time += step;
+        }
+
+        times
+    }
+}
+
+/// Position of the sun on the sky.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunPosition {
+    /// Azimuth in radians, measured clockwise from the north.
+    pub azimuth: f32,
+    /// Altitude above the horizon in radians. Negative if the sun is below the
+    /// horizon.
+    pub altitude: f32,
+}
+
+impl SunPosition {
+    /// Computes the sun position with a simple astronomical model, precise
+    /// to about a degree, which is plenty for early design studies.
+    ///
+    /// The time is the local solar time, i.e. the sun is highest at 12:00.
+    /// The model does not account for time zones, daylight saving time, or
+    /// the equation of time.
+    pub fn compute(latitude: f32, day_of_year: u32, solar_time: f32) -> Self {
+        let latitude = latitude.to_radians();
+
+        // Cooper's approximation of the solar declination
+        let declination = 23.45_f32.to_radians()
+            * (math::TAU * (284.0 + day_of_year as f32) / DAYS_IN_YEAR as f32).sin();
+        let hour_angle = ((solar_time - 12.0) * 15.0).to_radians();
+
+        let sin_altitude = latitude.sin() * declination.sin()
+            + latitude.cos() * declination.cos() * hour_angle.cos();
+        let altitude = math::clamp(sin_altitude, -1.0, 1.0).asin();
+
+        // The azimuth is undefined with the sun in the zenith, or at the
+        // poles. Any value does there.
+        let denominator = altitude.cos() * latitude.cos();
+        let azimuth = if denominator.abs() < f32::EPSILON {
+            f32::consts::PI
+        } else {
+            let cos_azimuth = (declination.sin() - sin_altitude * latitude.sin()) / denominator;
+            let azimuth = math::clamp(cos_azimuth, -1.0, 1.0).acos();
+
+            // The sun is in the west in the afternoon
+            if hour_angle > 0.0 {
+                math::TAU - azimuth
+            } else {
+                azimuth
+            }
+        };
+
+        Self { azimuth, altitude }
+    }
+
+    pub fn is_above_horizon(&self) -> bool {
+        self.altitude > 0.0
+    }
+
+    /// Returns the unit direction of the sunlight, i.e. from the sun towards
+    /// the scene. The azimuth is measured from the +Y axis and up is along the
+    /// +Z axis. Use `Geolocation::scene_sun_position` for scenes not facing
+    /// the true north.
+    pub fn light_direction(&self) -> Vector3<f32> {
+        let towards_sun = Vector3::new(
+            self.azimuth.sin() * self.altitude.cos(),
+            self.azimuth.cos() * self.altitude.cos(),
+            self.altitude.sin(),
+        );
+
+        -towards_sun
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sun_position_at_solstice_noon() {
+        let sun = SunPosition::compute(50.0, 172, 12.0);
+
+        // 90° - latitude + declination
+        assert!((sun.altitude.to_degrees() - 63.45).abs() < 0.1);
+        assert!((sun.azimuth.to_degrees() - 180.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_sun_position_is_in_the_zenith_at_equator_on_equinox() {
+        let sun = SunPosition::compute(0.0, 80, 12.0);
+
+        assert!(sun.altitude.to_degrees() > 89.0);
+        assert!(sun.light_direction().z < -0.99);
+    }
+
+    #[test]
+    fn test_sun_position_rises_in_the_east_and_sets_in_the_west() {
+        let morning = SunPosition::compute(50.0, 172, 8.0);
+        let afternoon = SunPosition::compute(50.0, 172, 16.0);
+
+        assert!(morning.is_above_horizon());
+        assert!(morning.azimuth.to_degrees() < 180.0);
+        assert!(morning.light_direction().x < 0.0);
+
+        assert!(afternoon.is_above_horizon());
+        assert!(afternoon.azimuth.to_degrees() > 180.0);
+        assert!(afternoon.light_direction().x > 0.0);
+    }
+
+    #[test]
+    fn test_sun_position_is_below_horizon_at_midnight() {
+        let sun = SunPosition::compute(50.0, 172, 0.0);
+
+        assert!(!sun.is_above_horizon());
+    }
+
+    #[test]
+    fn test_shadow_study_options_times_include_end_time() {
+        let options = ShadowStudyOptions {
+            start_time: 6.0,
+            end_time: 8.0,
+            step_minutes: 30,
+        };
+
+        assert_eq!(options.times(), vec![6.0, 6.5, 7.0, 7.5, 8.0]);
+    }
+}
@@ -0,0 +1,464 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::interpreter::ast::{Expr, LitExpr, Prog, Stmt};
+use crate::interpreter::{Value, VarIdent};
+use crate::interpreter_server::{
+    InterpreterRequest, InterpreterResponse, InterpreterServer, PollResponseError, RequestId,
+};
+use crate::mesh::{analysis, Mesh};
+
+/// The maximum number of steps of a single sweep axis. With two axes, a sweep
+/// can execute at most `MAX_STEPS * MAX_STEPS` samples.
+pub const MAX_STEPS: u32 = 8;
+
+/// Reference to a literal argument of a statement in the pipeline program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParamRef {
+    pub stmt_index: usize,
+    pub arg_index: usize,
+}
+
+/// A parameter varied by the sweep, and the range of its values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepAxis {
+    pub param: ParamRef,
+    pub min: f32,
+    pub max: f32,
+    pub steps: u32,
+}
+
+impl SweepAxis {
+    /// Returns the parameter value for the `step`-th step of the axis. The
+    /// first and last steps are exactly the `min` and `max` of the range.
+    pub fn value_at(&self, step: u32) -> f32 {
+        if self.steps <= 1 {
+            self.min
+        } else {
+            let t = step as f32 / (self.steps - 1) as f32;
+            self.min + (self.max - self.min) * t
+        }
+    }
+}
+
+/// Measurements of the geometry produced by a sample, for objective
+/// comparison of samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurements {
+    pub volume: f32,
+    pub surface_area: f32,
+}
+
+impl Measurements {
+    /// Measures the meshes together. The volume is only meaningful if all the
+    /// meshes are watertight.
+    pub fn from_meshes<'a, I>(meshes: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Mesh>,
+    {
+        meshes.into_iter().fold(
+            Measurements {
+                volume: 0.0,
+                surface_area: 0.0,
+            },
+            |measurements, mesh| Measurements {
+                volume: measurements.volume + analysis::mesh_volume(mesh),
+                surface_area: measurements.surface_area + analysis::mesh_surface_area(mesh),
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleOutcome {
+    Pending,
+    Finished(Measurements),
+    Failed(String),
+}
+
+/// A single combination of the swept parameter values.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    values: Vec<f32>,
+    stmts: Vec<Stmt>,
+    outcome: SampleOutcome,
+    thumbnail: Option<imgui::TextureId>,
+    /// The width, height and RGBA data of the thumbnail, kept so that the
+    /// thumbnail can be uploaded again for a variant made from the sample.
+    thumbnail_image: Option<(u32, u32, Vec<u8>)>,
+}
+
+impl Sample {
+    /// The parameter values of this sample, one for each sweep axis.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// The pipeline program with the swept parameters replaced by the values
+    /// of this sample.
+    pub fn stmts(&self) -> &[Stmt] {
+        &self.stmts
+    }
+
+    pub fn outcome(&self) -> &SampleOutcome {
+        &self.outcome
+    }
+
+    pub fn thumbnail(&self) -> Option<imgui::TextureId> {
+        self.thumbnail
+    }
+
+    pub fn thumbnail_image(&self) -> Option<&(u32, u32, Vec<u8>)> {
+        self.thumbnail_image.as_ref()
+    }
+}
+
+/// A notification from the sweep about a finished sample.
+pub enum PollNotification {
+    /// The sample was executed successfully, producing the given meshes. Only
+    /// unused values are reported, as those are the outputs of the pipeline.
+    SampleFinished(usize, Vec<Arc<Mesh>>),
+    SampleFailed(usize),
+}
+
+/// A batch exploration of the design space.
+///
+/// Executes the pipeline program for each combination of values of one or two
+/// numeric parameters. The samples are executed one by one in a separate
+/// interpreter, so that the session being edited is not affected. Because the
+/// samples differ only in a few parameters, the interpreter can reuse cached
+/// results of statements that precede the swept parameters.
+pub struct Sweep {
+    axes: Vec<SweepAxis>,
+    samples: Vec<Sample>,
+    interpreter_server: InterpreterServer,
+    interpret_request_in_flight: Option<(RequestId, usize)>,
+    next_sample_index: usize,
+}
+
+impl Sweep {
+    /// Creates a new sweep over the program statements. Samples with a
+    /// statement running longer than the statement timeout fail.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no axes or more than two axes, or if an axis does
+    /// not reference a numeric literal argument of the program.
+    pub fn new(stmts: &[Stmt], axes: Vec<SweepAxis>, stmt_timeout: Option<Duration>) -> Self {
+        assert!(
+            !axes.is_empty() && axes.len() <= 2,
+            "Sweep must have one or two axes",
+        );
+        for axis in &axes {
+            assert!(
+                numeric_params(stmts).any(|param| param == axis.param),
+                "Sweep axis must reference a numeric literal argument",
+            );
+        }
+
+        let steps: Vec<u32> = axes.iter().map(|axis| axis.steps.max(1)).collect();
+        let samples_len: u32 = steps.iter().product();
+
+        let mut samples = Vec::with_capacity(samples_len as usize);
+        for sample_index in 0..samples_len {
+            // The first axis changes the fastest, so that the samples are laid
+            // out in rows in the contact sheet.
+            let mut remainder = sample_index;
+            let mut values = Vec::with_capacity(axes.len());
+            let mut sample_stmts = stmts.to_vec();
+
+            for (axis, axis_steps) in axes.iter().zip(&steps) {
+                let value = axis.value_at(remainder % axis_steps);
+                remainder /= axis_steps;
+
+                set_numeric_param(&mut sample_stmts, axis.param, value);
+                values.push(value);
+            }
+
+            samples.push(Sample {
+                values,
+                stmts: sample_stmts,
+                outcome: SampleOutcome::Pending,
+                thumbnail: None,
+                thumbnail_image: None,
+            });
+        }
+
+        let mut interpreter_server = InterpreterServer::new();
+        interpreter_server.set_stmt_timeout(stmt_timeout);
+
+        Self {
+            axes,
+            samples,
+            interpreter_server,
+            interpret_request_in_flight: None,
+            next_sample_index: 0,
+        }
+    }
+
+    pub fn axes(&self) -> &[SweepAxis] {
+        &self.axes
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// Returns the number of samples that were already executed.
+    pub fn finished_samples_len(&self) -> usize {
+        self.samples
+            .iter()
+            .filter(|sample| sample.outcome != SampleOutcome::Pending)
+            .count()
+    }
+
+    /// Returns whether all samples were executed.
+    pub fn finished(&self) -> bool {
+        self.interpret_request_in_flight.is_none() && self.next_sample_index >= self.samples.len()
+    }
+
+    /// Sets the thumbnail of a sample uploaded from the image, returning the
+    /// replaced thumbnail, if any.
+    pub fn set_thumbnail(
+        &mut self,
+        sample_index: usize,
+        thumbnail: imgui::TextureId,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    ) -> Option<imgui::TextureId> {
+        let sample = &mut self.samples[sample_index];
+        sample.thumbnail_image = Some((width, height, rgba));
+        sample.thumbnail.replace(thumbnail)
+    }
+
+    /// Returns all thumbnail textures, so that they can be removed from the
+    /// renderer once the sweep is discarded.
+    pub fn thumbnails(&self) -> impl Iterator<Item = imgui::TextureId> + '_ {
+        self.samples.iter().filter_map(|sample| sample.thumbnail)
+    }
+
+    /// Polls the interpreter for results of the sample currently being
+    /// executed and starts executing the next sample, if the interpreter is
+    /// idle.
+    pub fn poll<C>(&mut self, mut callback: C)
+    where
+        C: FnMut(PollNotification),
+    {
+        #[allow(clippy::while_let_loop)]
+        loop {
+            match self.interpreter_server.poll_response() {
+                Ok((request_id, response)) => {
+                    let result = match response {
+                        InterpreterResponse::CompletedEditProg
+                        | InterpreterResponse::StreamedMesh { .. }
+                        | InterpreterResponse::CompletedSpeculate => continue,
+                        InterpreterResponse::CompletedInterpret(interpret_outcome) => {
+                            interpret_outcome.result
+                        }
+                        InterpreterResponse::AbandonedInterpret(interpret_error) => {
+                            Err(interpret_error)
+                        }
+                    };
+
+                    let (tracked_request_id, sample_index) = self
+                        .interpret_request_in_flight
+                        .take()
+                        .expect("The interpret request must have been tracked");
+                    assert_eq!(
+                        request_id, tracked_request_id,
+                        "Interpret response must match the request",
+                    );
+
+                    match result {
+                        Ok(interpret_value) => {
+                            let meshes = output_meshes(interpret_value.unused_values);
+                            self.samples[sample_index].outcome = SampleOutcome::Finished(
+                                Measurements::from_meshes(meshes.iter().map(AsRef::as_ref)),
+                            );
+
+                            callback(PollNotification::SampleFinished(sample_index, meshes));
+                        }
+                        Err(interpret_error) => {
+                            self.samples[sample_index].outcome =
+                                SampleOutcome::Failed(interpret_error.to_string());
+
+                            callback(PollNotification::SampleFailed(sample_index));
+                        }
+                    }
+                }
+                Err(PollResponseError::Pending) => break,
+            }
+        }
+
+        if self.interpret_request_in_flight.is_none() && self.next_sample_index < self.samples.len()
+        {
+            let sample_index = self.next_sample_index;
+            self.next_sample_index += 1;
+
+            let stmts = self.samples[sample_index].stmts.clone();
+            self.interpreter_server
+                .submit_request(InterpreterRequest::SetProg(Prog::new(stmts)));
+            let request_id = self
+                .interpreter_server
+                .submit_request(InterpreterRequest::Interpret);
+
+            self.interpret_request_in_flight = Some((request_id, sample_index));
+        }
+    }
+}
+
+/// Collects the meshes of the given values, e.g. the unused values of an
+/// interpreted program.
+pub fn output_meshes(values: Vec<(VarIdent, Value)>) -> Vec<Arc<Mesh>> {
+    let mut meshes = Vec::new();
+    for (_, value) in values {
+        match value {
+            Value::Mesh(mesh) => meshes.push(mesh),
+            Value::MeshArray(mesh_array) => meshes.extend(mesh_array.iter_refcounted()),
+            _ => (/* Only geometry is measured */),
+        }
+    }
+
+    meshes
+}
+
+/// Returns all literal arguments of the program that can be swept.
+pub fn numeric_params(stmts: &[Stmt]) -> impl Iterator<Item = ParamRef> + '_ {
+    stmts.iter().enumerate().flat_map(|(stmt_index, stmt)| {
+        let Stmt::VarDecl(var_decl) = stmt;
+        var_decl
+            .init_expr()
+            .args()
+            .iter()
+            .enumerate()
+            .filter_map(move |(arg_index, arg)| match arg {
+                Expr::Lit(LitExpr::Float(_))
+                | Expr::Lit(LitExpr::Int(_))
+                | Expr::Lit(LitExpr::Uint(_)) => Some(ParamRef {
+                    stmt_index,
+                    arg_index,
+                }),
+                _ => None,
+            })
+    })
+}
+
+/// Returns the current value of a numeric literal argument.
+///
+/// # Panics
+///
+/// Panics if the referenced argument is not a numeric literal.
+pub fn numeric_param_value(stmts: &[Stmt], param: ParamRef) -> f32 {
+    let Stmt::VarDecl(var_decl) = &stmts[param.stmt_index];
+    match var_decl.init_expr().args()[param.arg_index] {
+        Expr::Lit(LitExpr::Float(float)) => float,
+        Expr::Lit(LitExpr::Int(int)) => int as f32,
+        Expr::Lit(LitExpr::Uint(uint)) => uint as f32,
+        _ => panic!("Param must reference a numeric literal"),
+    }
+}
+
+/// Replaces a numeric literal argument of the program with a new value. The
+/// value is rounded for integer literals.
+///
+/// # Panics
+///
+/// Panics if the referenced argument is not a numeric literal.
+pub fn set_numeric_param(stmts: &mut [Stmt], param: ParamRef, value: f32) {
+    let Stmt::VarDecl(var_decl) = &stmts[param.stmt_index];
+    let lit = match var_decl.init_expr().args()[param.arg_index] {
+        Expr::Lit(LitExpr::Float(_)) => LitExpr::Float(value),
+        Expr::Lit(LitExpr::Int(_)) => LitExpr::Int(value.round() as i32),
+        Expr::Lit(LitExpr::Uint(_)) => LitExpr::Uint(value.round().max(0.0) as u32),
+        _ => panic!("Param must reference a numeric literal"),
+    };
+
+    let init_expr = var_decl
+        .init_expr()
+        .clone_with_arg_at(param.arg_index, Expr::Lit(lit));
+    let stmt = Stmt::VarDecl(var_decl.clone_with_init_expr(init_expr));
+
+    stmts[param.stmt_index] = stmt;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::ast::{CallExpr, FuncIdent, VarDeclStmt};
+
+    use super::*;
+
+    fn stmts() -> Vec<Stmt> {
+        vec![Stmt::VarDecl(VarDeclStmt::new(
+            VarIdent(0),
+            CallExpr::new(
+                FuncIdent(0),
+                vec![
+                    Expr::Lit(LitExpr::Float3([0.0, 0.0, 0.0])),
+                    Expr::Lit(LitExpr::Float(1.0)),
+                    Expr::Lit(LitExpr::Uint(4)),
+                ],
+            ),
+        ))]
+    }
+
+    #[test]
+    fn test_numeric_params_skips_non_numeric_literals() {
+        let params: Vec<_> = numeric_params(&stmts()).collect();
+
+        assert_eq!(
+            params,
+            vec![
+                ParamRef {
+                    stmt_index: 0,
+                    arg_index: 1,
+                },
+                ParamRef {
+                    stmt_index: 0,
+                    arg_index: 2,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_set_numeric_param_preserves_literal_type() {
+        let mut stmts = stmts();
+        let float_param = ParamRef {
+            stmt_index: 0,
+            arg_index: 1,
+        };
+        let uint_param = ParamRef {
+            stmt_index: 0,
+            arg_index: 2,
+        };
+
+        set_numeric_param(&mut stmts, float_param, 2.5);
+        set_numeric_param(&mut stmts, uint_param, 6.7);
+
+        let Stmt::VarDecl(var_decl) = &stmts[0];
+        assert_eq!(
+            var_decl.init_expr().args()[1],
+            Expr::Lit(LitExpr::Float(2.5)),
+        );
+        assert_eq!(var_decl.init_expr().args()[2], Expr::Lit(LitExpr::Uint(7)));
+        assert_eq!(numeric_param_value(&stmts, float_param), 2.5);
+    }
+
+    #[test]
+    fn test_sweep_axis_value_at_includes_range_bounds() {
+        let axis = SweepAxis {
+            param: ParamRef {
+                stmt_index: 0,
+                arg_index: 1,
+            },
+            min: 1.0,
+            max: 2.0,
+            steps: 5,
+        };
+
+        assert_eq!(axis.value_at(0), 1.0);
+        assert_eq!(axis.value_at(2), 1.5);
+        assert_eq!(axis.value_at(4), 2.0);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::preferences::{self, ConfigFileError};
+use crate::project::Project;
+use crate::ViewportDrawMode;
+
+const TEMPLATE_FILE_NAME: &str = "template.ron";
+
+/// Viewport settings applied to new projects. Unlike the viewport stored in
+/// project files, they leave out the camera and clip box, which depend on
+/// the scene.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TemplateViewport {
+    pub draw_mode: ViewportDrawMode,
+    pub draw_used_values: bool,
+    pub labels: bool,
+}
+
+/// The project every new project starts as, e.g. with a standard context
+/// import and scale setup.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ProjectTemplate {
+    pub project: Project,
+    pub viewport: TemplateViewport,
+}
+
+/// Loads the project template from the user configuration directory. Returns
+/// `None`, if no template was saved.
+pub fn load() -> Result<Option<ProjectTemplate>, ConfigFileError> {
+    preferences::read_config_file(TEMPLATE_FILE_NAME)
+}
+
+/// Saves the project template to the user configuration directory.
+pub fn save(project_template: ProjectTemplate) -> Result<(), ConfigFileError> {
+    preferences::write_config_file(TEMPLATE_FILE_NAME, &Some(project_template))
+}
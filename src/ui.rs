@@ -1,14 +1,59 @@
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::f32;
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
-
-use crate::convert::{cast_u8_color_to_f32, clamp_cast_i32_to_u32, clamp_cast_u32_to_i32};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::TimeZone;
+use nalgebra::{Matrix4, Point3, Vector2};
+
+use crate::annotation::{self, AnnotationCorner};
+use crate::bookmarks::{self, CameraBookmark};
+use crate::bounding_box::BoundingBox;
+use crate::box_drawing::BoxDrawing;
+use crate::bundle;
+use crate::calibration::{self, ScaleCalibration};
+use crate::camera::CameraView;
+use crate::clip_box::ClipBox;
+use crate::convert::{
+    cast_u32, cast_u8_color_to_f32, cast_usize, clamp_cast_i32_to_u32, clamp_cast_u32_to_i32,
+};
+use crate::display_material::{DisplayMaterial, DisplayMaterialKind};
+use crate::event_log;
+use crate::examples::ExampleProject;
+use crate::explode;
+use crate::export_job::ExportJob;
+use crate::export_preview::{self, ExportPreview};
+use crate::expression;
+use crate::find_replace::{self, FindQuery, Replacement};
+use crate::geolocation::Geolocation;
 use crate::imgui_winit_support::{HiDpiMode, WinitPlatform};
-use crate::interpreter::{ast, LogMessageLevel, ParamRefinement, Ty};
+use crate::interpreter::{ast, Dimension, Func, LogMessageLevel, ParamRefinement, ParamWidget, Ty};
+use crate::layers::{Layer, Layers};
+use crate::library::LibraryAsset;
+use crate::live_link;
+use crate::logger::{self, LogLevel, ModuleFilter};
+use crate::math;
 use crate::notifications::{NotificationLevel, Notifications};
+use crate::number_format::NumberFormat;
+use crate::optimization::{self, Goal, Measure, Objective, Optimization, OptimizationParam};
+use crate::param_defaults::ParamDefaults;
+use crate::param_links::{ParamId, ParamLink};
+use crate::preferences::Preferences;
+use crate::profiler::{self, Profiler};
 use crate::project;
-use crate::session::Session;
+use crate::reference_images::{ReferenceImage, ReferenceImagePlacement};
+use crate::renderer::{GpuAdapterInfo, ShadowQuality};
+use crate::session::{AutorunPreferences, Session};
+use crate::sketch::FootprintSketch;
+use crate::snapping::{self, Snapping};
+use crate::statistics::{Statistics, StatisticsConsent};
+use crate::stereo;
+use crate::sun::{self, ShadowStudyOptions, SunOptions};
+use crate::sweep::{self, ParamRef, SampleOutcome, Sweep, SweepAxis};
+use crate::units::Unit;
+use crate::variants::{self, VariantId, Variants};
 use crate::{ScreenshotOptions, Theme, ViewportDrawMode};
 
 const FONT_OPENSANS_REGULAR_BYTES: &[u8] = include_bytes!("../resources/SpaceMono-Regular.ttf");
@@ -27,7 +72,12 @@ const PIPELINE_WINDOW_HEIGHT_MULT: f32 = 1.0 - OPERATIONS_WINDOW_HEIGHT_MULT;
 const PIPELINE_OPERATION_CONSOLE_HEIGHT: f32 = 40.0;
 
 const MENU_WINDOW_WIDTH: f32 = 160.0;
-const MENU_WINDOW_HEIGHT: f32 = 321.0;
+const MENU_WINDOW_HEIGHT: f32 = 919.0;
+
+const VARIANTS_WINDOW_WIDTH: f32 = variants::THUMBNAIL_WIDTH as f32 + 28.0;
+const VARIANTS_WINDOW_HEIGHT: f32 = 360.0;
+
+const SWEEP_THUMBNAIL_SCALE: f32 = 0.5;
 
 const NOTIFICATIONS_WINDOW_WIDTH: f32 = 600.0;
 const NOTIFICATIONS_WINDOW_HEIGHT_MULT: f32 = 0.1;
@@ -36,8 +86,29 @@ const SUBDIGITAL_LOGO_WINDOW_WIDTH: f32 = 100.0;
 
 const ABOUT_WINDOW_WIDTH: f32 = 600.0;
 
+const NORTH_ARROW_RADIUS: f32 = 24.0;
+
+const VALUE_LABEL_PADDING: f32 = 4.0;
+
 const DRAG_SPEED: f32 = 0.01;
 
+const ITEM_INNER_SPACING: f32 = 2.0;
+
+const EXPRESSION_CAPACITY: usize = 256;
+
+const LINK_ICON_WIDTH: f32 = 16.0;
+
+const FIND_REPLACE_PREVIEW_WIDTH: f32 = 420.0;
+const FIND_REPLACE_PREVIEW_HEIGHT: f32 = 200.0;
+
+const HELP_WINDOW_TEXT_WIDTH: f32 = 360.0;
+
+const PROFILER_PLOT_WIDTH: f32 = 300.0;
+const PROFILER_PLOT_HEIGHT: f32 = 60.0;
+
+/// Same as the minimum voxel size of the voxel operations.
+const MIN_PROJECT_VOXEL_SIZE: f32 = 0.005;
+
 struct FontIds {
     regular: imgui::FontId,
     bold: imgui::FontId,
@@ -57,6 +128,8 @@ struct Colors {
     log_message_error: [f32; 4],
     header_error: [f32; 4],
     header_error_hovered: [f32; 4],
+    header_warning: [f32; 4],
+    header_warning_hovered: [f32; 4],
     tooltip_text: [f32; 4],
     notification_window: [f32; 4],
     popup_window_background: [f32; 4],
@@ -66,6 +139,24 @@ struct Colors {
 #[derive(Debug, Default)]
 struct PipelineWindowState {
     autoscroll: bool,
+    /// The value of a snapped parameter as dragged, before snapping. Small
+    /// drags would otherwise keep snapping back to the original value.
+    /// Contains the statement index, argument index and the value.
+    unsnapped_drag: Option<(usize, usize, [f32; 3])>,
+    /// The numeric parameter being typed as a math expression, if any.
+    expression_edit: Option<ExpressionEdit>,
+}
+
+#[derive(Debug)]
+struct ExpressionEdit {
+    stmt_index: usize,
+    arg_index: usize,
+    text: imgui::ImString,
+    /// The error of the last committed text, shown until it is committed
+    /// again.
+    error: Option<String>,
+    /// Whether the input takes keyboard focus in the next frame.
+    focus: bool,
 }
 
 #[derive(Debug, Default)]
@@ -78,6 +169,96 @@ struct ConsoleState {
     message_count: usize,
 }
 
+#[derive(Debug, Default)]
+struct VariantsWindowState {
+    name: imgui::ImString,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SweepAxisState {
+    enabled: bool,
+    param: Option<ParamRef>,
+    range: [f32; 2],
+    steps: i32,
+}
+
+impl Default for SweepAxisState {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            param: None,
+            range: [0.0, 1.0],
+            steps: 4,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SweepWindowState {
+    axes: [SweepAxisState; 2],
+}
+
+#[derive(Debug)]
+struct FindReplaceWindowState {
+    func_ident: Option<ast::FuncIdent>,
+    param_name: Option<&'static str>,
+    find: imgui::ImString,
+    replace: imgui::ImString,
+}
+
+impl Default for FindReplaceWindowState {
+    fn default() -> Self {
+        Self {
+            func_ident: None,
+            param_name: None,
+            find: imgui::ImString::with_capacity(EXPRESSION_CAPACITY),
+            replace: imgui::ImString::with_capacity(EXPRESSION_CAPACITY),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct HelpWindowState {
+    /// The operation shown in the help window. Set by hovering operations in
+    /// the operations and pipeline windows.
+    func_ident: Option<ast::FuncIdent>,
+    /// The focused variable when the help window was last drawn. Focusing
+    /// another variable shows the operation declaring it.
+    last_focused_var: Option<ast::VarIdent>,
+    /// The uploaded images of the operation docs and their sizes.
+    func_doc_images: HashMap<ast::FuncIdent, (imgui::TextureId, [f32; 2])>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct OptimizationParamState {
+    enabled: bool,
+    param: Option<ParamRef>,
+    range: [f32; 2],
+}
+
+#[derive(Debug)]
+struct OptimizationWindowState {
+    params: [OptimizationParamState; optimization::MAX_PARAMS],
+    measure: Measure,
+    goal: Goal,
+    target_volume_enabled: bool,
+    target_volume: f32,
+    max_evaluations: i32,
+}
+
+impl Default for OptimizationWindowState {
+    fn default() -> Self {
+        Self {
+            params: [OptimizationParamState::default(); optimization::MAX_PARAMS],
+            measure: Measure::SurfaceArea,
+            goal: Goal::Minimize,
+            target_volume_enabled: false,
+            target_volume: 1.0,
+            max_evaluations: 50,
+        }
+    }
+}
+
 pub enum OverwriteModalTrigger {
     NewProject,
     OpenProject,
@@ -86,14 +267,123 @@ pub enum OverwriteModalTrigger {
 #[derive(Default)]
 pub struct MenuStatus {
     pub viewport_draw_used_values_changed: bool,
+    pub viewport_explode_factor_changed: bool,
     pub reset_viewport: bool,
+    pub presentation_mode: bool,
+    pub toggle_detached_viewport: bool,
     pub export_obj: bool,
+    pub export_measurements: bool,
+    pub paste_obj: bool,
+    pub copy_pipeline_text: bool,
+    pub paste_pipeline_text: bool,
+    pub project_unit: Option<Unit>,
     pub new_project: bool,
+    pub save_template: bool,
     pub save_path: Option<PathBuf>,
+    pub save_bundle_path: Option<PathBuf>,
     pub open_path: Option<PathBuf>,
     pub prevent_overwrite_modal: Option<OverwriteModalTrigger>,
 }
 
+#[derive(Default)]
+pub struct PipelineStatus {
+    pub changed: bool,
+    pub copy_as_obj: Option<ast::VarIdent>,
+    /// Variable that was pinned or unpinned.
+    pub pinned_changed: Option<ast::VarIdent>,
+    /// Variable whose display material was changed.
+    pub display_material_changed: Option<ast::VarIdent>,
+    /// Variable that was assigned to another layer.
+    pub layer_changed: Option<ast::VarIdent>,
+    /// Variable whose custom name was changed.
+    pub var_name_changed: Option<ast::VarIdent>,
+    /// Why the submitted custom name of a variable was rejected.
+    pub var_name_error: Option<String>,
+    /// Variable whose custom label was changed.
+    pub value_label_changed: Option<ast::VarIdent>,
+    /// Whether the project notes or the note of a statement were changed.
+    pub notes_changed: bool,
+    /// Variable whose import was live linked or unlinked.
+    pub live_link_changed: Option<ast::VarIdent>,
+}
+
+#[derive(Default)]
+pub struct PresentationStatus {
+    pub restore: Option<VariantId>,
+    pub reset_viewport: bool,
+    pub exit: bool,
+}
+
+#[derive(Default)]
+pub struct VariantsStatus {
+    pub capture: Option<String>,
+    pub restore: Option<VariantId>,
+    pub remove: Option<VariantId>,
+    pub compare: Option<VariantId>,
+    pub compare_stop: bool,
+}
+
+#[derive(Default)]
+pub struct SweepStatus {
+    pub start: Option<Vec<SweepAxis>>,
+    pub cancel: bool,
+    pub keep_as_variant: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct OptimizationStatus {
+    pub start: Option<(Vec<OptimizationParam>, Objective, u32)>,
+    pub stop: bool,
+    pub discard: bool,
+    pub keep_best: bool,
+}
+
+#[derive(Default)]
+pub struct SunStatus {
+    pub export_shadow_study: Option<PathBuf>,
+    pub bake_matcap: Option<PathBuf>,
+    pub geolocation_changed: bool,
+}
+
+#[derive(Default)]
+pub struct ViewsStatus {
+    pub changed: bool,
+    pub go_to: Option<CameraView>,
+    pub render_all: Option<PathBuf>,
+}
+
+#[derive(Default)]
+pub struct CalibrationStatus {
+    pub apply: Option<f32>,
+    pub reset: bool,
+}
+
+#[derive(Default)]
+pub struct PlacementStatus {
+    pub choose: Option<PathBuf>,
+    pub changed: bool,
+}
+
+#[derive(Default)]
+pub struct SketchStatus {
+    pub finish: bool,
+    pub remove_last_point: bool,
+    pub reset: bool,
+    pub height_changed: bool,
+}
+
+#[derive(Default)]
+pub struct FindReplaceStatus {
+    pub replace: Option<Vec<Replacement>>,
+}
+
+#[derive(Default)]
+pub struct ReferenceImagesStatus {
+    pub add: Option<(PathBuf, ReferenceImagePlacement)>,
+    pub remove: Option<usize>,
+    pub changed: Option<usize>,
+}
+
 pub enum SaveModalResult {
     Save,
     DontSave,
@@ -101,6 +391,100 @@ pub enum SaveModalResult {
     Nothing,
 }
 
+pub enum UnitConversionModalResult {
+    Convert,
+    Keep,
+    Nothing,
+}
+
+pub enum ExportPreviewModalResult {
+    Export,
+    Cancel,
+    Nothing,
+}
+
+pub enum ExampleModalResult {
+    SaveCopy(PathBuf),
+    Explore,
+    Nothing,
+}
+
+pub enum CrashModalResult {
+    CopyReport,
+    SaveTo(PathBuf),
+    Dismiss,
+    Nothing,
+}
+
+/// Sets the imgui style colors of the high contrast themes: opaque windows,
+/// text and borders in the foreground color, and hovered, active and keyboard
+/// focused widgets highlighted by the accent color.
+fn set_high_contrast_style(
+    style: &mut imgui::Style,
+    background: [f32; 4],
+    foreground: [f32; 4],
+    accent: [f32; 4],
+) {
+    let [r, g, b, _] = accent;
+    let accent_transparent = [r, g, b, 0.35];
+    let accent_semitransparent = [r, g, b, 0.6];
+
+    style.window_border_size = 1.0;
+    style.frame_border_size = 1.0;
+    style.popup_border_size = 1.0;
+
+    style[imgui::StyleColor::Text] = foreground;
+    style[imgui::StyleColor::TextDisabled] = [
+        foreground[0] * 0.6 + background[0] * 0.4,
+        foreground[1] * 0.6 + background[1] * 0.4,
+        foreground[2] * 0.6 + background[2] * 0.4,
+        1.0,
+    ];
+    style[imgui::StyleColor::WindowBg] = background;
+    style[imgui::StyleColor::ChildBg] = background;
+    style[imgui::StyleColor::PopupBg] = background;
+    style[imgui::StyleColor::Border] = foreground;
+    style[imgui::StyleColor::BorderShadow] = [0.0, 0.0, 0.0, 0.0];
+    style[imgui::StyleColor::FrameBg] = background;
+    style[imgui::StyleColor::FrameBgHovered] = accent_transparent;
+    style[imgui::StyleColor::FrameBgActive] = accent_semitransparent;
+    style[imgui::StyleColor::TitleBg] = background;
+    style[imgui::StyleColor::TitleBgActive] = accent_transparent;
+    style[imgui::StyleColor::TitleBgCollapsed] = background;
+    style[imgui::StyleColor::MenuBarBg] = background;
+    style[imgui::StyleColor::ScrollbarBg] = background;
+    style[imgui::StyleColor::ScrollbarGrab] = foreground;
+    style[imgui::StyleColor::ScrollbarGrabHovered] = accent;
+    style[imgui::StyleColor::ScrollbarGrabActive] = accent;
+    style[imgui::StyleColor::CheckMark] = accent;
+    style[imgui::StyleColor::SliderGrab] = foreground;
+    style[imgui::StyleColor::SliderGrabActive] = accent;
+    style[imgui::StyleColor::Button] = background;
+    style[imgui::StyleColor::ButtonHovered] = accent_transparent;
+    style[imgui::StyleColor::ButtonActive] = accent_semitransparent;
+    style[imgui::StyleColor::Header] = accent_transparent;
+    style[imgui::StyleColor::HeaderHovered] = accent_semitransparent;
+    style[imgui::StyleColor::HeaderActive] = accent_semitransparent;
+    style[imgui::StyleColor::Separator] = foreground;
+    style[imgui::StyleColor::SeparatorHovered] = accent;
+    style[imgui::StyleColor::SeparatorActive] = accent;
+    style[imgui::StyleColor::ResizeGrip] = foreground;
+    style[imgui::StyleColor::ResizeGripHovered] = accent;
+    style[imgui::StyleColor::ResizeGripActive] = accent;
+    style[imgui::StyleColor::Tab] = background;
+    style[imgui::StyleColor::TabHovered] = accent_semitransparent;
+    style[imgui::StyleColor::TabActive] = accent_transparent;
+    style[imgui::StyleColor::TabUnfocused] = background;
+    style[imgui::StyleColor::TabUnfocusedActive] = accent_transparent;
+    style[imgui::StyleColor::PlotLines] = foreground;
+    style[imgui::StyleColor::PlotLinesHovered] = accent;
+    style[imgui::StyleColor::PlotHistogram] = foreground;
+    style[imgui::StyleColor::PlotHistogramHovered] = accent;
+    style[imgui::StyleColor::TextSelectedBg] = accent_semitransparent;
+    style[imgui::StyleColor::NavHighlight] = accent;
+    style[imgui::StyleColor::NavWindowingHighlight] = accent;
+}
+
 /// Thin wrapper around imgui and its winit platform. Its main responsibility
 /// is to create UI frames which draw the UI itself.
 pub struct Ui {
@@ -111,6 +495,11 @@ pub struct Ui {
     pipeline_window_state: RefCell<PipelineWindowState>,
     notifications_state: RefCell<NotificationsState>,
     console_state: RefCell<Vec<ConsoleState>>,
+    variants_window_state: RefCell<VariantsWindowState>,
+    sweep_window_state: RefCell<SweepWindowState>,
+    optimization_window_state: RefCell<OptimizationWindowState>,
+    find_replace_window_state: RefCell<FindReplaceWindowState>,
+    help_window_state: RefCell<HelpWindowState>,
 
     /// A preallocated string buffer used for imgui strings in the
     /// UI. Every user of this buffer has the responsibility to clear
@@ -136,6 +525,8 @@ impl Ui {
             log_message_error: [1.0, 0.15, 0.05, 1.0],
             header_error: [0.85, 0.15, 0.05, 0.4],
             header_error_hovered: [1.00, 0.15, 0.05, 0.4],
+            header_warning: [0.80, 0.80, 0.05, 0.4],
+            header_warning_hovered: [0.95, 0.95, 0.05, 0.4],
             tooltip_text: [1.0, 1.0, 1.0, 1.0],
             notification_window: [0.0, 0.0, 0.0, 0.1],
             popup_window_background: [0.0, 0.0, 0.0, 0.4],
@@ -145,7 +536,7 @@ impl Ui {
         style.window_padding = [4.0, 4.0];
         style.frame_padding = [4.0, 2.0];
         style.item_spacing = [2.0, 2.0];
-        style.item_inner_spacing = [2.0, 2.0];
+        style.item_inner_spacing = [ITEM_INNER_SPACING, ITEM_INNER_SPACING];
         style.indent_spacing = 8.0;
 
         style.scrollbar_size = 8.0;
@@ -156,7 +547,7 @@ impl Ui {
         style.scrollbar_rounding = 3.0;
         style.grab_rounding = 3.0;
 
-        if theme == Theme::Light {
+        if theme == Theme::Light || theme == Theme::LightHighContrast {
             style.window_rounding = 0.0;
             style.frame_rounding = 0.0;
             style.scrollbar_rounding = 0.0;
@@ -217,7 +608,7 @@ impl Ui {
             style[imgui::StyleColor::TabUnfocusedActive] = light_transparent;
             style[imgui::StyleColor::PlotLines] = orange;
             style[imgui::StyleColor::TextSelectedBg] = orange_light_transparent;
-            style[imgui::StyleColor::NavHighlight] = light_transparent;
+            style[imgui::StyleColor::NavHighlight] = orange_dark;
 
             colors.special_button_text = white;
             colors.special_button = green_light;
@@ -235,12 +626,83 @@ impl Ui {
 
             colors.header_error = red_transparent;
             colors.header_error_hovered = red;
+            colors.header_warning = orange_light_transparent;
+            colors.header_warning_hovered = orange_light;
 
             colors.notification_window = white_80_transparent;
 
             colors.popup_window_background = white_80_transparent;
         }
 
+        match theme {
+            Theme::DarkHighContrast => {
+                let black = [0.0, 0.0, 0.0, 1.0];
+                let white = [1.0, 1.0, 1.0, 1.0];
+                let yellow = [1.0, 0.85, 0.0, 1.0];
+                let yellow_light = [1.0, 0.95, 0.55, 1.0];
+
+                set_high_contrast_style(style, black, white, yellow);
+
+                colors.special_button_text = black;
+                colors.special_button = yellow;
+                colors.special_button_hovered = yellow_light;
+                colors.special_button_active = white;
+
+                colors.combo_box_selected_item = [1.0, 0.85, 0.0, 0.4];
+                colors.combo_box_selected_item_hovered = [1.0, 0.85, 0.0, 0.6];
+                colors.combo_box_selected_item_active = [1.0, 0.85, 0.0, 0.8];
+
+                colors.log_message_info = white;
+                colors.log_message_warn = yellow;
+                colors.log_message_error = [1.0, 0.4, 0.3, 1.0];
+
+                colors.header_error = [0.6, 0.0, 0.0, 1.0];
+                colors.header_error_hovered = [0.8, 0.0, 0.0, 1.0];
+                colors.header_warning = [0.45, 0.38, 0.0, 1.0];
+                colors.header_warning_hovered = [0.6, 0.5, 0.0, 1.0];
+
+                colors.tooltip_text = white;
+                colors.notification_window = black;
+                colors.popup_window_background = [0.0, 0.0, 0.0, 0.8];
+            }
+            Theme::LightHighContrast => {
+                let black = [0.0, 0.0, 0.0, 1.0];
+                let white = [1.0, 1.0, 1.0, 1.0];
+                let blue = [0.0, 0.2, 0.75, 1.0];
+                let blue_dark = [0.0, 0.1, 0.45, 1.0];
+
+                set_high_contrast_style(style, white, black, blue);
+
+                colors.special_button_text = white;
+                colors.special_button = blue;
+                colors.special_button_hovered = blue_dark;
+                colors.special_button_active = black;
+
+                colors.combo_box_selected_item = [0.0, 0.2, 0.75, 0.25];
+                colors.combo_box_selected_item_hovered = [0.0, 0.2, 0.75, 0.4];
+                colors.combo_box_selected_item_active = [0.0, 0.2, 0.75, 0.55];
+
+                colors.log_message_info = black;
+                colors.log_message_warn = [0.45, 0.3, 0.0, 1.0];
+                colors.log_message_error = [0.7, 0.0, 0.0, 1.0];
+
+                colors.header_error = [1.0, 0.65, 0.65, 1.0];
+                colors.header_error_hovered = [1.0, 0.5, 0.5, 1.0];
+                colors.header_warning = [1.0, 0.9, 0.5, 1.0];
+                colors.header_warning_hovered = [1.0, 0.85, 0.3, 1.0];
+
+                colors.tooltip_text = black;
+                colors.notification_window = white;
+                colors.popup_window_background = [1.0, 1.0, 1.0, 0.8];
+            }
+            Theme::Dark | Theme::Light => (),
+        }
+
+        // Tab, arrows, space and enter navigate and activate the widgets,
+        // and ctrl+tab switches windows, so that the editor can be used
+        // without a mouse
+        imgui_context.io_mut().config_flags |= imgui::ConfigFlags::NAV_ENABLE_KEYBOARD;
+
         imgui_context.set_ini_filename(None);
 
         let mut platform = WinitPlatform::init(&mut imgui_context);
@@ -286,6 +748,13 @@ impl Ui {
             pipeline_window_state: RefCell::new(PipelineWindowState::default()),
             console_state: RefCell::new(Vec::new()),
             notifications_state: RefCell::new(NotificationsState::default()),
+            variants_window_state: RefCell::new(VariantsWindowState {
+                name: imgui::ImString::with_capacity(128),
+            }),
+            sweep_window_state: RefCell::new(SweepWindowState::default()),
+            optimization_window_state: RefCell::new(OptimizationWindowState::default()),
+            find_replace_window_state: RefCell::new(FindReplaceWindowState::default()),
+            help_window_state: RefCell::new(HelpWindowState::default()),
             global_imstring_buffer: RefCell::new(imgui::ImString::with_capacity(1024)),
         }
     }
@@ -316,10 +785,29 @@ impl Ui {
             console_state: &self.console_state,
             pipeline_window_state: &self.pipeline_window_state,
             notifications_state: &self.notifications_state,
+            variants_window_state: &self.variants_window_state,
+            sweep_window_state: &self.sweep_window_state,
+            optimization_window_state: &self.optimization_window_state,
+            find_replace_window_state: &self.find_replace_window_state,
+            help_window_state: &self.help_window_state,
             global_imstring_buffer: &self.global_imstring_buffer,
         }
     }
 
+    /// Sets the uploaded image of the operation docs, shown in the help
+    /// window below the details.
+    pub fn set_func_doc_image(
+        &mut self,
+        func_ident: ast::FuncIdent,
+        texture_id: imgui::TextureId,
+        size: [f32; 2],
+    ) {
+        self.help_window_state
+            .get_mut()
+            .func_doc_images
+            .insert(func_ident, (texture_id, size));
+    }
+
     pub fn want_capture_keyboard(&self) -> bool {
         self.imgui_context.io().want_capture_keyboard
     }
@@ -343,6 +831,11 @@ pub struct UiFrame<'a> {
     console_state: &'a RefCell<Vec<ConsoleState>>,
     pipeline_window_state: &'a RefCell<PipelineWindowState>,
     notifications_state: &'a RefCell<NotificationsState>,
+    variants_window_state: &'a RefCell<VariantsWindowState>,
+    sweep_window_state: &'a RefCell<SweepWindowState>,
+    optimization_window_state: &'a RefCell<OptimizationWindowState>,
+    find_replace_window_state: &'a RefCell<FindReplaceWindowState>,
+    help_window_state: &'a RefCell<HelpWindowState>,
     global_imstring_buffer: &'a RefCell<imgui::ImString>,
 }
 
@@ -421,6 +914,156 @@ impl<'a> UiFrame<'a> {
                     &mut screenshot_options.transparent,
                 );
 
+                ui.checkbox(imgui::im_str!("Panorama"), &mut screenshot_options.panorama);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "360 DEGREE PANORAMA\n\
+                             \n\
+                             Captures the whole surroundings of the camera position into an \
+                             equirectangular image, which can be viewed in standard 360 degree \
+                             viewers. The center of the panorama looks in the direction of the \
+                             camera.\n\
+                             \n\
+                             The panorama is as wide as the screenshot and half as high. \
+                             Panoramas are not transparent, stereo or annotated.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                ui.checkbox(imgui::im_str!("Stereo"), &mut screenshot_options.stereo);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "STEREO SCREENSHOT\n\
+                             \n\
+                             Draws the views of the left and the right eye side by side, \
+                             each in one half of the screenshot, for previewing the scene \
+                             in simple VR viewers. Stereo screenshots are not annotated.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if screenshot_options.stereo {
+                    ui.indent();
+                    imgui::Drag::<f32>::new(imgui::im_str!("Eye Separation"))
+                        .range(0.0..=stereo::EYE_SEPARATION_MAX)
+                        .speed(0.001)
+                        .display_format(imgui::im_str!("%.3f"))
+                        .build(ui, &mut screenshot_options.stereo_eye_separation);
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(
+                                self.colors.tooltip_text,
+                                "EYE SEPARATION\n\
+                                 \n\
+                                 Distance between the eyes in scene units, also used by the \
+                                 stereo viewport. The human eye separation of 0.065 meters \
+                                 makes the scene look life-sized, larger separations make it \
+                                 look like a scale model.",
+                            );
+                            wrap_token.pop(ui);
+                        });
+                    }
+                    ui.unindent();
+                }
+
+                let annotation = &mut screenshot_options.annotation;
+                ui.checkbox(imgui::im_str!("Annotate"), &mut annotation.enabled);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "ANNOTATE SCREENSHOT\n\
+                             \n\
+                             Draws a panel with text, north arrow and scale bar \
+                             into a corner of the screenshot.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if annotation.enabled {
+                    ui.indent();
+
+                    let combo_box_color_token = self.push_combo_box_colors();
+                    if let Some(combo_token) = imgui::ComboBox::new(imgui::im_str!("Corner"))
+                        .preview_value(&imgui::ImString::new(annotation.corner.name()))
+                        .begin(ui)
+                    {
+                        for corner in &AnnotationCorner::ALL {
+                            if imgui::Selectable::new(&imgui::ImString::new(corner.name()))
+                                .selected(annotation.corner == *corner)
+                                .build(ui)
+                            {
+                                annotation.corner = *corner;
+                            }
+                        }
+
+                        combo_token.end(ui);
+                    }
+                    combo_box_color_token.pop(ui);
+
+                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+                    imstring_buffer.push_str(&annotation.template);
+                    if ui
+                        .input_text_multiline(
+                            imgui::im_str!("Text"),
+                            &mut imstring_buffer,
+                            [0.0, 60.0],
+                        )
+                        .build()
+                    {
+                        annotation.template = imstring_buffer.to_string();
+                    }
+                    imstring_buffer.clear();
+
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(
+                                self.colors.tooltip_text,
+                                "ANNOTATION TEXT\n\
+                                 \n\
+                                 Each line is drawn as a line of the annotation. \
+                                 {project} is replaced with the project name, \
+                                 {date} with the current date and {variant} with the name \
+                                 of the variant, if the current pipeline is a captured variant.\n\
+                                 \n\
+                                 Lines that end up empty are left out.",
+                            );
+                            wrap_token.pop(ui);
+                        });
+                    }
+
+                    ui.checkbox(imgui::im_str!("North Arrow"), &mut annotation.north_arrow);
+                    ui.checkbox(imgui::im_str!("Scale Bar"), &mut annotation.scale_bar);
+                    ui.checkbox(imgui::im_str!("Value Labels"), &mut annotation.value_labels);
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(
+                                self.colors.tooltip_text,
+                                "VALUE LABELS\n\
+                                 \n\
+                                 Draws the labels shown in the viewport into the screenshot. \
+                                 Labels are only drawn, if they are enabled in the menu.",
+                            );
+                            wrap_token.pop(ui);
+                        });
+                    }
+
+                    ui.unindent();
+                }
+
                 if ui.button(imgui::im_str!("Take Screenshot"), [0.0, 0.0]) {
                     take_screenshot_clicked = true;
                 }
@@ -459,6 +1102,7 @@ impl<'a> UiFrame<'a> {
         tex_logos: imgui::TextureId,
         width_logos: u32,
         height_logos: u32,
+        gpu_adapter: &GpuAdapterInfo,
     ) {
         let ui = &self.imgui_ui;
 
@@ -499,6 +1143,18 @@ impl<'a> UiFrame<'a> {
                 ui.new_line();
                 regular_font_token.pop(ui);
 
+                ui.text(imgui::im_str!("GPU"));
+                regular_font_token = ui.push_font(self.font_ids.regular);
+                ui.text(format!("Adapter: {}", gpu_adapter.name));
+                ui.text(format!("Type: {}", gpu_adapter.device_type));
+                ui.text(format!("Backend: {}", gpu_adapter.backend));
+                ui.text(format!(
+                    "Vendor ID: 0x{:04x}, Device ID: 0x{:04x}",
+                    gpu_adapter.vendor, gpu_adapter.device,
+                ));
+                ui.new_line();
+                regular_font_token.pop(ui);
+
                 imgui::Image::new(
                     tex_scheme,
                     [window_width * 0.95, window_width * 0.95 / width_scheme as f32 * height_scheme as f32],
@@ -725,6 +1381,145 @@ impl<'a> UiFrame<'a> {
         color_token.pop(ui);
     }
 
+    /// Draws progress of the running exports above the notifications window.
+    pub fn draw_export_progress_window(&self, export_jobs: &[ExportJob]) {
+        if export_jobs.is_empty() {
+            return;
+        }
+
+        let ui = &self.imgui_ui;
+
+        let window_logical_size = ui.io().display_size;
+        let window_inner_width = window_logical_size[0] - 2.0 * MARGIN;
+        let window_inner_height = window_logical_size[1] - 2.0 * MARGIN;
+
+        let notifications_window_vertical_position =
+            MARGIN * 2.0 + (1.0 - NOTIFICATIONS_WINDOW_HEIGHT_MULT) * window_inner_height;
+
+        let color_token =
+            ui.push_style_color(imgui::StyleColor::WindowBg, self.colors.notification_window);
+
+        imgui::Window::new(imgui::im_str!("Export Progress"))
+            .title_bar(false)
+            .movable(false)
+            .resizable(false)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    window_inner_width + MARGIN,
+                    notifications_window_vertical_position - MARGIN,
+                ],
+                imgui::Condition::Always,
+            )
+            .position_pivot([1.0, 1.0])
+            .build(ui, || {
+                for export_job in export_jobs {
+                    ui.text(imgui::im_str!(
+                        "Exporting {} to {}",
+                        export_job.description(),
+                        export_job.path(),
+                    ));
+                    imgui::ProgressBar::new(export_job.progress())
+                        .size([NOTIFICATIONS_WINDOW_WIDTH - 2.0 * MARGIN, 0.0])
+                        .build(ui);
+                }
+            });
+
+        color_token.pop(ui);
+    }
+
+    /// Draws a north arrow into the top left corner of the viewport. The
+    /// direction is in screen space, with the Y axis pointing down.
+    pub fn draw_north_arrow(&self, direction: Vector2<f32>) {
+        let ui = &self.imgui_ui;
+
+        let color = ui.style_color(imgui::StyleColor::Text);
+        // Leave space for the label around the arrow
+        let center = Vector2::new(
+            2.0 * MARGIN + OPERATIONS_WINDOW_WIDTH + 2.0 * NORTH_ARROW_RADIUS,
+            MARGIN + 2.0 * NORTH_ARROW_RADIUS,
+        );
+        let side = Vector2::new(-direction.y, direction.x);
+
+        let tip = center + direction * NORTH_ARROW_RADIUS * 0.8;
+        let tail = center - direction * NORTH_ARROW_RADIUS * 0.8;
+        let left = tail + side * NORTH_ARROW_RADIUS * 0.4;
+        let right = tail - side * NORTH_ARROW_RADIUS * 0.4;
+        let label = center + direction * (NORTH_ARROW_RADIUS + 8.0);
+
+        let draw_list = ui.get_background_draw_list();
+        draw_list
+            .add_circle([center.x, center.y], NORTH_ARROW_RADIUS, color)
+            .build();
+        draw_list
+            .add_triangle(
+                [tip.x, tip.y],
+                [left.x, left.y],
+                [center.x, center.y],
+                color,
+            )
+            .filled(true)
+            .build();
+        draw_list
+            .add_triangle(
+                [tip.x, tip.y],
+                [center.x, center.y],
+                [right.x, right.y],
+                color,
+            )
+            .build();
+        draw_list.add_text([label.x - 4.0, label.y - 8.0], color, "N");
+    }
+
+    /// Draws the value labels centered above their points in the scene.
+    pub fn draw_value_labels(
+        &self,
+        view_projection_matrix: &Matrix4<f32>,
+        value_labels: &[(Point3<f32>, String)],
+    ) {
+        let ui = &self.imgui_ui;
+
+        let [width, height] = ui.io().display_size;
+        let background_color = ui.style_color(imgui::StyleColor::WindowBg);
+        let text_color = ui.style_color(imgui::StyleColor::Text);
+
+        let draw_list = ui.get_background_draw_list();
+        for (point, text) in value_labels {
+            let position = match annotation::project_to_image(
+                view_projection_matrix,
+                point,
+                width as u32,
+                height as u32,
+            ) {
+                Some(position) => position,
+                None => continue,
+            };
+
+            let imstring = imgui::ImString::new(text.as_str());
+            let [text_width, text_height] = ui.calc_text_size(&imstring, false, -1.0);
+            let min = [
+                position.x - text_width / 2.0 - VALUE_LABEL_PADDING,
+                position.y - text_height - 3.0 * VALUE_LABEL_PADDING,
+            ];
+            let max = [
+                position.x + text_width / 2.0 + VALUE_LABEL_PADDING,
+                position.y - VALUE_LABEL_PADDING,
+            ];
+
+            draw_list
+                .add_rect(min, max, background_color)
+                .filled(true)
+                .rounding(VALUE_LABEL_PADDING)
+                .build();
+            draw_list.add_text(
+                [min[0] + VALUE_LABEL_PADDING, min[1] + VALUE_LABEL_PADDING],
+                text_color,
+                text,
+            );
+        }
+    }
+
     pub fn draw_subdigital_logo(
         &self,
         tex_subdigital_logo: imgui::TextureId,
@@ -788,9 +1583,32 @@ impl<'a> UiFrame<'a> {
         &self,
         current_time: Instant,
         screenshot_modal_open: &mut bool,
+        sweep_window_open: &mut bool,
+        optimization_window_open: &mut bool,
+        sun_window_open: &mut bool,
+        calibration_window_open: &mut bool,
+        reference_images_window_open: &mut bool,
+        placement_window_open: &mut bool,
+        box_drawing_window_open: &mut bool,
+        sketch_window_open: &mut bool,
+        find_replace_window_open: &mut bool,
+        help_window_open: &mut bool,
+        profiler_window_open: &mut bool,
+        library_window_open: &mut bool,
+        examples_window_open: &mut bool,
+        layers_window_open: &mut bool,
+        history_window_open: &mut bool,
+        clip_box_window_open: &mut bool,
+        views_window_open: &mut bool,
+        preferences_window_open: &mut bool,
         about_modal_open: &mut bool,
         viewport_draw_mode: &mut ViewportDrawMode,
         viewport_draw_used_values: &mut bool,
+        viewport_labels: &mut bool,
+        viewport_stereo: &mut bool,
+        viewport_explode_factor: &mut f32,
+        viewport_detached: bool,
+        project_unit: Unit,
         project_status: &mut project::ProjectStatus,
         session: &mut Session,
         notifications: &mut Notifications,
@@ -823,7 +1641,13 @@ impl<'a> UiFrame<'a> {
                         ui.text_colored(self.colors.tooltip_text, "MAIN MENU\n\
                         \n\
                         Viewport information and settings.\n\
-                        Screenshot and file management.");
+                        Screenshot and file management.\n\
+                        \n\
+                        KEYBOARD\n\
+                        Ctrl+Tab switches between windows, arrows move between the items \
+                        of a window, Space activates the item and Enter starts typing a value. \
+                        Ctrl+N, Ctrl+O and Ctrl+S create, open and save the project \
+                        (Cmd on macOS). F5 runs the pipeline.");
                         regular_font_token.pop(ui);
                         wrap_token.pop(ui);
                     });
@@ -847,6 +1671,20 @@ impl<'a> UiFrame<'a> {
                     });
                 }
 
+                if ui.button(imgui::im_str!("Profiler..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *profiler_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "PROFILER\n\
+                        \n\
+                        Opens an overlay showing how long the stages of drawing each frame \
+                        take, to find out what slows the application down on this computer.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
                 if ui.radio_button(
                     imgui::im_str!("Shaded"),
                     viewport_draw_mode,
@@ -962,31 +1800,153 @@ impl<'a> UiFrame<'a> {
                     });
                 }
 
-                status.reset_viewport =
-                    ui.button(imgui::im_str!("Reset viewport"), [-f32::MIN_POSITIVE, 0.0]);
-                if status.reset_viewport {
-                    notifications.push(
-                        current_time,
-                        NotificationLevel::Info,
-                        "Viewport camera reset to fit all visible geometry.",
-                    );
-                }
+                ui.checkbox(imgui::im_str!("Draw labels"), viewport_labels);
                 if ui.is_item_hovered() {
                     ui.tooltip(|| {
                         let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
-                        ui.text_colored(self.colors.tooltip_text, "RESET VIEWPORT CAMERA\n\
+                        ui.text_colored(self.colors.tooltip_text,"DRAW LABELS IN VIEWPORT\n\
                         \n\
-                        Set the viewport camera to look at all visible geometry in the scene.");
+                        When enabled, a label with the operation name, or a custom label set in \
+                        the Operation pipeline, is drawn above the geometry of each operation. \
+                        Labels can be turned off for individual layers.\n\
+                        \n\
+                        Screenshots include the labels, if they are annotated.");
                         wrap_token.pop(ui);
                     });
                 }
 
-                ui.separator();
-
-                if ui.button(imgui::im_str!("New"), [-f32::MIN_POSITIVE, 0.0])
-                    || project_status.new_requested
-                {
-                    if project_status.changed_since_last_save
+                ui.checkbox(imgui::im_str!("Stereo viewport"), viewport_stereo);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text,"STEREO VIEWPORT\n\
+                        \n\
+                        When enabled, the viewport is split into the views of the left and \
+                        the right eye side by side, as in stereo screenshots. The distance \
+                        between the eyes is set in the Screenshot window.\n\
+                        \n\
+                        Labels are not drawn in the stereo viewport. While comparing \
+                        variants, the viewport is split between the variants instead.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                status.viewport_explode_factor_changed = imgui::Slider::new(imgui::im_str!("Explode"))
+                    .range(0.0..=explode::EXPLODE_FACTOR_MAX)
+                    .display_format(imgui::im_str!("%.2f"))
+                    .build(ui, viewport_explode_factor);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text,"EXPLODE VIEW\n\
+                        \n\
+                        Moves the elements of mesh groups, and the disjoint parts of meshes, \
+                        apart from each other for inspection.\n\
+                        \n\
+                        Only the viewport is affected, the geometry used by the operations \
+                        and exports stays in place.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let combo_box_color_token = self.push_combo_box_colors();
+                if let Some(combo_token) = imgui::ComboBox::new(imgui::im_str!("Units"))
+                    .preview_value(&imgui::ImString::new(project_unit.name()))
+                    .begin(ui)
+                {
+                    for unit in &Unit::ALL {
+                        if imgui::Selectable::new(&imgui::ImString::new(unit.name()))
+                            .selected(project_unit == *unit)
+                            .build(ui)
+                            && project_unit != *unit
+                        {
+                            status.project_unit = Some(*unit);
+                        }
+                    }
+
+                    combo_token.end(ui);
+                }
+                combo_box_color_token.pop(ui);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "PROJECT UNITS\n\
+                        \n\
+                        The units of length the project is modeled in. \
+                        Lengths, areas and volumes are displayed in these units \
+                        and exported OBJ files declare them.\n\
+                        \n\
+                        Changing the units doesn't scale the existing geometry.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                status.reset_viewport =
+                    ui.button(imgui::im_str!("Reset viewport"), [-f32::MIN_POSITIVE, 0.0]);
+                if status.reset_viewport {
+                    notifications.push(
+                        current_time,
+                        NotificationLevel::Info,
+                        "Viewport camera reset to fit all visible geometry.",
+                    );
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "RESET VIEWPORT CAMERA\n\
+                        \n\
+                        Set the viewport camera to look at all visible geometry in the scene.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                status.presentation_mode =
+                    ui.button(imgui::im_str!("Presentation mode"), [-f32::MIN_POSITIVE, 0.0]);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "PRESENTATION MODE\n\
+                        \n\
+                        Switches to fullscreen and hides all editing windows, leaving only \
+                        the viewport and a small panel for switching variants and viewport \
+                        modes, e.g. for reviewing the results with clients.\n\
+                        \n\
+                        The project can't be edited in presentation mode. Press Escape or \
+                        the Exit button in the panel to leave it.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                status.toggle_detached_viewport = ui.button(
+                    if viewport_detached {
+                        imgui::im_str!("Close detached viewport")
+                    } else {
+                        imgui::im_str!("Detach viewport")
+                    },
+                    [-f32::MIN_POSITIVE, 0.0],
+                );
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "DETACHED VIEWPORT\n\
+                        \n\
+                        Opens a second viewport in its own window, e.g. to be moved to another \
+                        monitor. It has its own camera, controlled in the window as usual, \
+                        and draws the same geometry as the main viewport. Pressing A in the \
+                        window moves its camera to the camera of the main viewport.\n\
+                        \n\
+                        Only the current design is drawn in the detached viewport while \
+                        comparing variants.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                ui.separator();
+
+                if ui.button(imgui::im_str!("New"), [-f32::MIN_POSITIVE, 0.0])
+                    || project_status.new_requested
+                {
+                    if project_status.changed_since_last_save
                         && project_status.prevent_overwrite_status.is_none()
                     {
                         status.prevent_overwrite_modal = Some(OverwriteModalTrigger::NewProject);
@@ -1019,10 +1979,15 @@ impl<'a> UiFrame<'a> {
                         && project_status.prevent_overwrite_status.is_none()
                     {
                         status.prevent_overwrite_modal = Some(OverwriteModalTrigger::OpenProject);
+                    } else if let Some(path) = project_status.open_requested_path.take() {
+                        status.open_path = Some(path);
                     } else if let Some(path) = tinyfiledialogs::open_file_dialog(
                         "Open",
                         "",
-                        Some((project::EXTENSION_FILTER, project::EXTENSION_DESCRIPTION)),
+                        Some((
+                            project::OPEN_EXTENSION_FILTER,
+                            project::OPEN_EXTENSION_DESCRIPTION,
+                        )),
                     ) {
                         status.open_path = Some(PathBuf::from(path));
                     }
@@ -1036,7 +2001,8 @@ impl<'a> UiFrame<'a> {
                         let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
                         ui.text_colored(self.colors.tooltip_text, "OPEN PROJECT FROM A .hurban FILE\n\
                         \n\
-                        Opens the sequence of operations saved in a .hurban file.\n\
+                        Opens the sequence of operations saved in a .hurban file \
+                        or a .hurbanz bundle. The file can also be dropped onto the window.\n\
                         \n\
                         The .hurban project file contains only the operation pipeline. It does not contain any \
                         actual geometry, but rather just the sequence of operations that generates the geometry \
@@ -1047,9 +2013,26 @@ impl<'a> UiFrame<'a> {
                     });
                 }
 
+                if ui.button(imgui::im_str!("Examples..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *examples_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "EXAMPLE PROJECTS\n\
+                        \n\
+                        Opens a window with example projects bundled with the application. \
+                        Examples open read-only: save a copy of an example to keep changes to it.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
                 ui.separator();
 
-                if ui.button(imgui::im_str!("Save"), [-f32::MIN_POSITIVE, 0.0]) {
+                if ui.button(imgui::im_str!("Save"), [-f32::MIN_POSITIVE, 0.0])
+                    || project_status.save_requested
+                {
+                    project_status.save_requested = false;
                     match &project_status.path {
                         Some(project_path) => {
                             status.save_path = Some(project_path.clone())
@@ -1116,6 +2099,53 @@ impl<'a> UiFrame<'a> {
                     });
                 }
 
+                if ui.button(imgui::im_str!("Save as bundle..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    // FIXME: @Refactoring Factor out this use of
+                    // tinyfiledialogs from this module
+                    if let Some(path) = tinyfiledialogs::save_file_dialog_with_filter(
+                        "Save as bundle",
+                        "",
+                        bundle::EXTENSION_FILTER,
+                        bundle::EXTENSION_DESCRIPTION,
+                    ) {
+                        status.save_bundle_path = Some(PathBuf::from(path));
+                    }
+                }
+
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "SAVE PROJECT AS A .hurbanz BUNDLE\n\
+                        \n\
+                        Saves the current project into a single .hurbanz file together with the \
+                        files it imports and its reference images, so that it can be handed \
+                        to another computer. Opens a system dialog to specify save file location.\n\
+                        \n\
+                        Opening the bundle extracts the files into the user cache directory. \
+                        The opened project is new and has to be saved to keep changes.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Save as template"), [-f32::MIN_POSITIVE, 0.0]) {
+                    status.save_template = true;
+                }
+
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "SAVE PROJECT AS TEMPLATE\n\
+                        \n\
+                        Saves the current project, together with the viewport draw mode and \
+                        labels, as the template every new project starts as, e.g. with the \
+                        context geometry imported and the units and scale set up.\n\
+                        \n\
+                        The template is kept in the user configuration directory. To start new \
+                        projects empty again, save an empty project as the template.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
                 if ui.button(imgui::im_str!("Save screenshot..."), [-f32::MIN_POSITIVE, 0.0]) {
                     *screenshot_modal_open = true;
                 }
@@ -1129,6 +2159,218 @@ impl<'a> UiFrame<'a> {
                     });
                 }
 
+                if ui.button(imgui::im_str!("Design sweep..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *sweep_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "DESIGN SWEEP\n\
+                        \n\
+                        Opens a window for exploring the design space by executing the Operation \
+                        pipeline for ranges of values of one or two numeric parameters.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Optimize..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *optimization_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "OPTIMIZE\n\
+                        \n\
+                        Opens a window for searching the numeric parameter values that minimize \
+                        or maximize a measurement of the produced geometry.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Sun & shadows..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *sun_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "SUN & SHADOWS\n\
+                        \n\
+                        Opens a window for setting the location and north orientation of the site, \
+                        positioning the sun by date and time, and for exporting shadow studies.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Calibrate scale..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *calibration_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "CALIBRATE SCALE\n\
+                        \n\
+                        Opens a window for scaling imported geometry to its real world size \
+                        by picking two points on it in the viewport and entering their real distance.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Reference images..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *reference_images_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "REFERENCE IMAGES\n\
+                        \n\
+                        Opens a window for placing images, such as site plans or elevations, \
+                        in the viewport to model against.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Place objects..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *placement_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "PLACE OBJECTS\n\
+                        \n\
+                        Opens a window for placing copies of an OBJ file into the scene \
+                        by clicking in the viewport.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Draw box..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *box_drawing_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "DRAW BOX\n\
+                        \n\
+                        Opens a window for drawing massing boxes in the viewport. \
+                        Each drawn box is added to the pipeline as a Create Box operation.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Sketch footprint..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *sketch_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "SKETCH FOOTPRINT\n\
+                        \n\
+                        Opens a window for sketching massing footprints of any shape \
+                        on the ground in the viewport. Each footprint is added to the \
+                        pipeline as a Create Polygon operation extruded by an Extrude operation.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Find and replace..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *find_replace_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "FIND AND REPLACE\n\
+                        \n\
+                        Opens a window for finding numeric parameters across the whole \
+                        pipeline and changing their values at once.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Operation help..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *help_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "OPERATION HELP\n\
+                        \n\
+                        Opens a window explaining the operation last hovered in the \
+                        Operations or Pipeline windows, or the operation of the selected \
+                        geometry, including its parameters and typical uses.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Library..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *library_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "LIBRARY\n\
+                        \n\
+                        Opens a window with built-in context geometry, such as trees, \
+                        human figures and simple buildings, to insert into the pipeline.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Layers..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *layers_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "LAYERS\n\
+                        \n\
+                        Opens a window for organizing the geometry of the operations into \
+                        named layers, which can be hidden, colored and locked together.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("History..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *history_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "HISTORY\n\
+                        \n\
+                        Opens a window for replaying all edits of the Operation pipeline \
+                        recorded for the project and restoring any of its past states.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Clipping box..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *clip_box_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "CLIPPING BOX\n\
+                        \n\
+                        Opens a window with a box hiding the geometry outside of it in the \
+                        viewport, for looking inside dense geometry, such as voxel clouds.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Views..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *views_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "VIEWS\n\
+                        \n\
+                        Opens a window for saving named camera views, returning to them and \
+                        rendering all of them at once.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
                 let export_obj_disabled_unsynced = !session.synced();
                 let export_obj_disabled_empty = session.stmts().is_empty();
                 let export_obj_disabled = export_obj_disabled_unsynced || export_obj_disabled_empty;
@@ -1170,601 +2412,6090 @@ impl<'a> UiFrame<'a> {
 
                 status.export_obj = !export_obj_disabled && export_obj;
 
-                ui.separator();
-
-                if ui.button(imgui::im_str!("About"), [-f32::MIN_POSITIVE, 0.0]) {
-                    *about_modal_open = true;
-                }
+                let export_measurements_disabled = !session.synced();
+                let export_measurements_button_tokens = if export_measurements_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let export_measurements = ui.button(
+                    imgui::im_str!("Export measurements..."),
+                    [-f32::MIN_POSITIVE, 0.0],
+                );
+                if let Some((color_token, style_token)) = export_measurements_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
                 if ui.is_item_hovered() {
                     ui.tooltip(|| {
                         let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
-                        ui.text_colored(self.colors.tooltip_text, "ABOUT H.U.R.B.A.N. selector\n\
+                        ui.text_colored(self.colors.tooltip_text, "EXPORT MEASUREMENTS\n\
                         \n\
-                        Program description and credits.");
+                        Opens a system dialog for exporting all measurements reported by the operations \
+                        (areas, volumes, analysis results) into a CSV or JSON file, \
+                        e.g. for comparing variants in a spreadsheet.\n\
+                        \n\
+                        Some measurements are only reported when the analysis of the operation is turned on.");
+                        if export_measurements_disabled {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: All operations must be executed before exporting.",
+                            );
+                        }
                         wrap_token.pop(ui);
                     });
                 }
 
-                regular_font_token.pop(ui);
-            });
-        bold_font_token.pop(ui);
+                status.export_measurements = !export_measurements_disabled && export_measurements;
 
-        status
-    }
+                let paste_obj_disabled = session.interpreter_busy();
+                let paste_obj_button_tokens = if paste_obj_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let paste_obj = ui.button(imgui::im_str!("Paste OBJ"), [-f32::MIN_POSITIVE, 0.0]);
+                if let Some((color_token, style_token)) = paste_obj_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "PASTE OBJ\n\
+                        \n\
+                        Imports OBJ text from the system clipboard, e.g. copied from another tool, \
+                        as a new Import OBJ operation.\n\
+                        \n\
+                        The pasted text is saved as an OBJ file next to the project file, \
+                        or into the application data directory if the project was not saved yet.");
+                        if paste_obj_disabled {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: Can not paste while the pipeline is executing.",
+                            );
+                        }
+                        wrap_token.pop(ui);
+                    });
+                }
 
-    pub fn draw_error_modal(&self, project_error: &Option<project::ProjectError>) -> bool {
-        let ui = &self.imgui_ui;
-        let mut modal_closed = false;
+                status.paste_obj = !paste_obj_disabled && paste_obj;
 
-        ui.open_popup(imgui::im_str!("Error"));
-        ui.popup_modal(imgui::im_str!("Error"))
-            .resizable(false)
-            .build(|| {
-                let error_message = project_error
-                    .clone()
-                    .expect("Failed to read project error.")
-                    .to_string();
+                status.copy_pipeline_text =
+                    ui.button(imgui::im_str!("Copy pipeline text"), [-f32::MIN_POSITIVE, 0.0]);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "COPY PIPELINE TEXT\n\
+                        \n\
+                        Copies the Operation pipeline with all its parameters to the system \
+                        clipboard as human-readable text, one operation per line, \
+                        e.g. for diffing, code review or sharing in chat.");
+                        wrap_token.pop(ui);
+                    });
+                }
 
-                ui.text(error_message);
+                let paste_pipeline_text_disabled = session.interpreter_busy();
+                let paste_pipeline_text_button_tokens = if paste_pipeline_text_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let paste_pipeline_text =
+                    ui.button(imgui::im_str!("Paste pipeline text"), [-f32::MIN_POSITIVE, 0.0]);
+                if let Some((color_token, style_token)) = paste_pipeline_text_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "PASTE PIPELINE TEXT\n\
+                        \n\
+                        Replaces the Operation pipeline with the pipeline text from the system \
+                        clipboard, as copied by Copy pipeline text.");
+                        ui.text_colored(
+                            self.colors.log_message_warn,
+                            "\nChanges not captured in a variant will be lost!",
+                        );
+                        if paste_pipeline_text_disabled {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: Can not paste while the pipeline is executing.",
+                            );
+                        }
+                        wrap_token.pop(ui);
+                    });
+                }
 
-                if ui.button(imgui::im_str!("OK"), [0.0, 0.0]) {
-                    modal_closed = true;
+                status.paste_pipeline_text = !paste_pipeline_text_disabled && paste_pipeline_text;
 
-                    ui.close_current_popup();
+                ui.separator();
+
+                if ui.button(imgui::im_str!("Preferences..."), [-f32::MIN_POSITIVE, 0.0]) {
+                    *preferences_window_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "PREFERENCES\n\
+                        \n\
+                        Opens a window with settings that apply to all projects, \
+                        such as snapping of transform values.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("About"), [-f32::MIN_POSITIVE, 0.0]) {
+                    *about_modal_open = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "ABOUT H.U.R.B.A.N. selector\n\
+                        \n\
+                        Program description and credits.");
+                        wrap_token.pop(ui);
+                    });
                 }
+
+                regular_font_token.pop(ui);
             });
+        bold_font_token.pop(ui);
 
-        modal_closed
+        status
     }
 
-    pub fn draw_prevent_overwrite_modal(&self) -> SaveModalResult {
+    /// Draws the only panel shown in presentation mode, with the captured
+    /// variants and the viewport draw settings.
+    pub fn draw_presentation_window(
+        &self,
+        variants: &Variants,
+        session: &Session,
+        viewport_draw_mode: &mut ViewportDrawMode,
+        viewport_labels: &mut bool,
+    ) -> PresentationStatus {
         let ui = &self.imgui_ui;
-        let mut save_modal_result = SaveModalResult::Nothing;
-        let window_color_token = ui.push_style_color(
-            imgui::StyleColor::PopupBg,
-            self.colors.popup_window_background,
-        );
-        ui.open_popup(imgui::im_str!("Unsaved changes"));
-        ui.popup_modal(imgui::im_str!("Unsaved changes"))
-            .resizable(false)
-            .build(|| {
-                ui.text("To preserve unsaved changes in the pipeline please save the project.");
+        let mut status = PresentationStatus::default();
 
-                let width_unit = ui.window_size()[0] / 11.0;
+        let restore_disabled = session.interpreter_busy();
 
-                if ui.button(imgui::im_str!("Save"), [width_unit * 3.0, 0.0]) {
-                    save_modal_result = SaveModalResult::Save;
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Presentation"))
+            .movable(false)
+            .resizable(false)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position([MARGIN, MARGIN], imgui::Condition::Always)
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "PRESENTATION MODE\n\
+                        \n\
+                        Switch between the captured variants and the viewport modes. \
+                        The camera is controlled as usual.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
 
-                    ui.close_current_popup();
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                if variants.is_empty() {
+                    ui.text_colored(self.colors.tooltip_text, "No variants captured");
+                } else {
+                    let restore_button_tokens = if restore_disabled {
+                        Some(push_disabled_style(ui))
+                    } else {
+                        None
+                    };
+                    for variant in variants.iter() {
+                        let selected = variant.stmts() == session.stmts();
+                        if imgui::Selectable::new(&imgui::im_str!(
+                            "{}##presentation-variant-{:?}",
+                            variant.name(),
+                            variant.id(),
+                        ))
+                        .selected(selected)
+                        .build(ui)
+                            && !selected
+                            && !restore_disabled
+                        {
+                            status.restore = Some(variant.id());
+                        }
+                    }
+                    if let Some((color_token, style_token)) = restore_button_tokens {
+                        color_token.pop(ui);
+                        style_token.pop(ui);
+                    }
                 }
 
-                ui.same_line(width_unit * 4.0);
+                ui.separator();
 
-                if ui.button(imgui::im_str!("Discard changes"), [width_unit * 3.0, 0.0]) {
-                    save_modal_result = SaveModalResult::DontSave;
+                ui.radio_button(
+                    imgui::im_str!("Shaded"),
+                    viewport_draw_mode,
+                    ViewportDrawMode::Shaded,
+                );
+                ui.radio_button(
+                    imgui::im_str!("Wireframes"),
+                    viewport_draw_mode,
+                    ViewportDrawMode::Wireframe,
+                );
+                ui.radio_button(
+                    imgui::im_str!("Shaded with Edges"),
+                    viewport_draw_mode,
+                    ViewportDrawMode::ShadedWireframe,
+                );
+                ui.radio_button(
+                    imgui::im_str!("X-RAY"),
+                    viewport_draw_mode,
+                    ViewportDrawMode::ShadedWireframeXray,
+                );
+                ui.checkbox(imgui::im_str!("Draw labels"), viewport_labels);
 
-                    ui.close_current_popup();
-                }
+                status.reset_viewport =
+                    ui.button(imgui::im_str!("Reset viewport"), [-f32::MIN_POSITIVE, 0.0]);
 
-                ui.same_line(width_unit * 8.0);
+                ui.separator();
 
-                if ui.button(imgui::im_str!("Cancel"), [width_unit * 3.0, 0.0]) {
-                    save_modal_result = SaveModalResult::Cancel;
+                status.exit = ui.button(
+                    imgui::im_str!("Exit presentation (Esc)"),
+                    [-f32::MIN_POSITIVE, 0.0],
+                );
 
-                    ui.close_current_popup();
-                }
+                regular_font_token.pop(ui);
             });
+        bold_font_token.pop(ui);
 
-        window_color_token.pop(ui);
-
-        save_modal_result
+        status
     }
 
-    // FIXME: @Refactoring Refactor this once we have full-featured
-    // functionality. Until then, this is exploratory code and we
-    // don't care.
-    #[allow(clippy::cognitive_complexity)]
-    pub fn draw_pipeline_window(&self, current_time: Instant, session: &mut Session) -> bool {
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_variants_window(
+        &self,
+        current_time: Instant,
+        variants: &Variants,
+        session: &Session,
+        compared_variant: Option<VariantId>,
+        compare_busy: bool,
+        notifications: &mut Notifications,
+    ) -> VariantsStatus {
         let ui = &self.imgui_ui;
-        self.console_state
-            .borrow_mut()
-            .resize_with(session.stmts().len(), Default::default);
-
-        let function_table = session.function_table();
+        let mut status = VariantsStatus::default();
 
         let window_logical_size = ui.io().display_size;
-        let window_inner_height = window_logical_size[1] - 2.0 * MARGIN;
-
-        let pipeline_window_height = window_inner_height * PIPELINE_WINDOW_HEIGHT_MULT;
+        let window_inner_width = window_logical_size[0] - 2.0 * MARGIN;
 
-        let interpreter_busy = session.interpreter_busy();
-        let mut change = None;
+        let capture_disabled_unsynced = !session.synced();
+        let capture_disabled_empty = session.stmts().is_empty();
+        let capture_disabled = capture_disabled_unsynced || capture_disabled_empty;
+        let restore_disabled = session.interpreter_busy();
 
         let bold_font_token = ui.push_font(self.font_ids.bold);
-        imgui::Window::new(imgui::im_str!("Operation pipeline"))
+        imgui::Window::new(imgui::im_str!("Variants"))
             .movable(false)
             .resizable(false)
             .collapsible(false)
-            .size([PIPELINE_WINDOW_WIDTH, pipeline_window_height], imgui::Condition::Always)
-            .position([MARGIN, MARGIN], imgui::Condition::Always)
+            .size(
+                [VARIANTS_WINDOW_WIDTH, VARIANTS_WINDOW_HEIGHT],
+                imgui::Condition::Always,
+            )
+            .position(
+                [
+                    window_inner_width + MARGIN - VARIANTS_WINDOW_WIDTH,
+                    2.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::Always,
+            )
             .build(ui, || {
                 if ui.is_item_hovered() {
                     ui.tooltip(|| {
                         let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
                         let regular_font_token = ui.push_font(self.font_ids.regular);
-                        ui.text_colored(self.colors.tooltip_text,
-                            "OPERATION PIPELINE\n\
-                            \n\
-                            An ordered sequence of operations that generate the viewport geometry. \
-                            When the pipeline is run, the operations are being executed one after \
-                            another from top down. Each operation can be customized by setting the \
-                            input parameter values or specifying the input data (mesh geometry, \
-                            mesh group, path to file).\n\
-                            \n\
-                            Each operation in the pipeline generates data: either a mesh geometry or \
-                            a mesh group which can be later used in a subsequent operation. Only unused \
-                            (freshly generated) geometry (mesh or group) is rendered in the viewport \
-                            by default, \
-                            however even the geometry, which has been already used, can be reused in \
-                            subsequent operations. Operations can take as an input only that geometry, \
-                            which has been generated in the preceding operations in the pipeline.\n\
-                            \n\
-                            It is possible to change any input parameters of any operation at any time, \
-                            not only after the operation has been added to the pipeline. \
-                            This is useful when some parameters need to be adjusted only after the results \
-                            of subsequent operations can be visually evaluated. This approach is a gateway \
-                            to full-fledged parametric modeling paradigm.\n\
-                            \n\
-                            The .hurban project files can be build as a tool for achieving certain manipulations \
-                            with mesh geometry and because they don't contain the imported mesh models, \
-                            one sequence of operations (one project file) can be reused for various input mesh \
-                            models. Hence, H.U.R.B.A.N. selector is not only a geometry transformation tool, \
-                            but also a tool-building platform and the project files ara not only geometries but \
-                            also tools.",
-                        );
+                        ui.text_colored(self.colors.tooltip_text, "DESIGN VARIANTS\n\
+                        \n\
+                        Named snapshots of the Operation pipeline with all its parameters. \
+                        Capture the current design as a variant, continue exploring, \
+                        and restore any of the captured variants later to compare candidate designs.\n\
+                        \n\
+                        Variants are saved in the .hurban project file, their previews are not.");
                         regular_font_token.pop(ui);
                         wrap_token.pop(ui);
                     });
                 }
+
                 let regular_font_token = ui.push_font(self.font_ids.regular);
-                for (stmt_index, stmt) in session.stmts().iter().enumerate() {
-                    match stmt {
-                        ast::Stmt::VarDecl(var_decl) => {
-                            let call_expr = var_decl.init_expr();
-                            let func_ident = call_expr.ident();
-                            let func = &function_table[&func_ident];
 
-                            let error = session.error_at_stmt(stmt_index);
-                            let error_color_token = if error.is_some() {
-                                Some(ui.push_style_colors(&[
-                                    (imgui::StyleColor::Header, self.colors.header_error),
-                                    (imgui::StyleColor::HeaderHovered, self.colors.header_error_hovered),
-                                    (imgui::StyleColor::HeaderActive, self.colors.header_error_hovered),
-                                    (imgui::StyleColor::Text, self.colors.tooltip_text),
-                                ]))
-                            } else {
-                                None
-                            };
+                let mut variants_window_state = self.variants_window_state.borrow_mut();
+                ui.set_next_item_width(-f32::MIN_POSITIVE);
+                ui.input_text(
+                    imgui::im_str!("##variant-name"),
+                    &mut variants_window_state.name,
+                )
+                .build();
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "VARIANT NAME\n\
+                        \n\
+                        Name of the next captured variant. If left empty, a name will be generated.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let capture_button_tokens = if capture_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let capture = ui.button(
+                    imgui::im_str!("Capture variant"),
+                    [-f32::MIN_POSITIVE, 0.0],
+                );
+                if let Some((color_token, style_token)) = capture_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "CAPTURE VARIANT\n\
+                        \n\
+                        Stores the current Operation pipeline with all its parameters \
+                        together with a preview of the viewport.");
+                        if capture_disabled_unsynced {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: All operations must be executed before capturing.",
+                            );
+                        }
+                        if capture_disabled_empty {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: Can not capture an empty pipeline.",
+                            );
+                        }
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if capture && !capture_disabled {
+                    let name = if variants_window_state.name.to_str().trim().is_empty() {
+                        variants.suggest_name()
+                    } else {
+                        String::from(variants_window_state.name.to_str().trim())
+                    };
+                    variants_window_state.name.clear();
+
+                    notifications.push(
+                        current_time,
+                        NotificationLevel::Info,
+                        format!("Captured variant {}.", name),
+                    );
+                    status.capture = Some(name);
+                }
+
+                for (index, variant) in variants.iter().enumerate() {
+                    ui.separator();
+
+                    ui.text(variant.name());
+
+                    let restore_button_tokens = if restore_disabled {
+                        Some(push_disabled_style(ui))
+                    } else {
+                        None
+                    };
+
+                    let mut restore = match variant.thumbnail() {
+                        Some(thumbnail) => imgui::ImageButton::new(
+                            thumbnail,
+                            [
+                                variants::THUMBNAIL_WIDTH as f32,
+                                variants::THUMBNAIL_HEIGHT as f32,
+                            ],
+                        )
+                        .frame_padding(0)
+                        .build(ui),
+                        None => {
+                            ui.text_disabled(imgui::im_str!("No preview"));
+                            false
+                        }
+                    };
+
+                    restore |= ui.button(
+                        &imgui::im_str!("Restore##variant-restore-{}", index),
+                        [VARIANTS_WINDOW_WIDTH / 2.0 - 12.0, 0.0],
+                    );
+
+                    if let Some((color_token, style_token)) = restore_button_tokens {
+                        color_token.pop(ui);
+                        style_token.pop(ui);
+                    }
+
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(self.colors.tooltip_text, "RESTORE VARIANT\n\
+                            \n\
+                            Replaces the current Operation pipeline with the one stored in the variant.");
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "\nChanges not captured in a variant will be lost!",
+                            );
+                            wrap_token.pop(ui);
+                        });
+                    }
+
+                    ui.same_line(0.0);
+
+                    if ui.button(
+                        &imgui::im_str!("Remove##variant-remove-{}", index),
+                        [-f32::MIN_POSITIVE, 0.0],
+                    ) {
+                        status.remove = Some(variant.id());
+                        notifications.push(
+                            current_time,
+                            NotificationLevel::Warn,
+                            format!("Removed variant {}.", variant.name()),
+                        );
+                    }
+
+                    if restore && !restore_disabled {
+                        status.restore = Some(variant.id());
+                        notifications.push(
+                            current_time,
+                            NotificationLevel::Info,
+                            format!("Restored variant {}.", variant.name()),
+                        );
+                    }
+
+                    if compared_variant == Some(variant.id()) {
+                        if ui.button(
+                            &imgui::im_str!("Stop comparing##variant-compare-{}", index),
+                            [-f32::MIN_POSITIVE, 0.0],
+                        ) {
+                            status.compare_stop = true;
+                        }
+                    } else {
+                        let compare_button_tokens = if compare_busy {
+                            Some(push_disabled_style(ui))
+                        } else {
+                            None
+                        };
+
+                        let compare = ui.button(
+                            &imgui::im_str!("Compare##variant-compare-{}", index),
+                            [-f32::MIN_POSITIVE, 0.0],
+                        );
+
+                        if let Some((color_token, style_token)) = compare_button_tokens {
+                            color_token.pop(ui);
+                            style_token.pop(ui);
+                        }
+
+                        if ui.is_item_hovered() {
+                            ui.tooltip(|| {
+                                let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                ui.text_colored(self.colors.tooltip_text, "COMPARE VARIANT\n\
+                                \n\
+                                Splits the viewport in two. The current design is shown on the left, \
+                                the variant on the right. Both sides share the same camera.");
+                                wrap_token.pop(ui);
+                            });
+                        }
+
+                        if compare && !compare_busy {
+                            status.compare = Some(variant.id());
+                            notifications.push(
+                                current_time,
+                                NotificationLevel::Info,
+                                format!("Comparing with variant {}.", variant.name()),
+                            );
+                        }
+                    }
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    pub fn draw_sweep_window(
+        &self,
+        current_time: Instant,
+        sweep_window_open: &mut bool,
+        session: &Session,
+        sweep: Option<&Sweep>,
+        notifications: &mut Notifications,
+        number_format: NumberFormat,
+    ) -> SweepStatus {
+        let ui = &self.imgui_ui;
+        let mut status = SweepStatus::default();
+
+        if !*sweep_window_open {
+            return status;
+        }
+
+        let stmts = session.stmts();
+        let params = numeric_param_labels(session);
+
+        let run_disabled_unsynced = !session.synced();
+        let run_disabled_empty = params.is_empty();
+        let run_disabled_running = sweep.map_or(false, |sweep| !sweep.finished());
+        let run_disabled = run_disabled_unsynced || run_disabled_empty || run_disabled_running;
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Design sweep"))
+            .opened(sweep_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    2.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    2.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(self.colors.tooltip_text, "DESIGN SWEEP\n\
+                        \n\
+                        Executes the Operation pipeline for each combination of values \
+                        of one or two numeric parameters and lays out the results in a contact sheet, \
+                        together with the volume and surface area of the produced geometry.\n\
+                        \n\
+                        The sweep runs in the background and does not change the current pipeline.");
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+                let mut sweep_window_state = self.sweep_window_state.borrow_mut();
+
+                // The first axis is mandatory
+                sweep_window_state.axes[0].enabled = true;
+
+                for (axis_index, axis_state) in sweep_window_state.axes.iter_mut().enumerate() {
+                    let axis_name = if axis_index == 0 { "A" } else { "B" };
+
+                    // Forget params that are no longer present in the
+                    // pipeline, e.g. because the operation was removed.
+                    if let Some(param) = axis_state.param {
+                        if params.iter().all(|(p, _)| *p != param) {
+                            axis_state.param = None;
+                        }
+                    }
+
+                    ui.separator();
+
+                    if axis_index == 0 {
+                        ui.text(imgui::im_str!("Parameter {}", axis_name));
+                    } else {
+                        ui.checkbox(
+                            &imgui::im_str!("Parameter {}", axis_name),
+                            &mut axis_state.enabled,
+                        );
+                    }
+
+                    if !axis_state.enabled {
+                        continue;
+                    }
+
+                    if self.draw_numeric_param_combo(
+                        &imgui::im_str!("##sweep-param-{}", axis_index),
+                        &params,
+                        &mut axis_state.param,
+                    ) {
+                        let param = axis_state.param.expect("Param must have been selected");
+                        axis_state.range = initial_param_range(stmts, param);
+                    }
+
+                    ui.input_float2(
+                        &imgui::im_str!("Range##sweep-range-{}", axis_index),
+                        &mut axis_state.range,
+                    )
+                    .build();
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(self.colors.tooltip_text, "SWEEP RANGE\n\
+                            \n\
+                            The first and last value of the parameter. \
+                            Values of integer parameters are rounded. \
+                            Values outside of the range allowed by the operation may fail to execute.");
+                            wrap_token.pop(ui);
+                        });
+                    }
+
+                    if ui
+                        .input_int(
+                            &imgui::im_str!("Steps##sweep-steps-{}", axis_index),
+                            &mut axis_state.steps,
+                        )
+                        .build()
+                    {
+                        axis_state.steps = axis_state
+                            .steps
+                            .max(1)
+                            .min(clamp_cast_u32_to_i32(sweep::MAX_STEPS));
+                    }
+                }
+
+                ui.separator();
+
+                let axes: Vec<SweepAxis> = sweep_window_state
+                    .axes
+                    .iter()
+                    .filter(|axis_state| axis_state.enabled)
+                    .filter_map(|axis_state| {
+                        axis_state.param.map(|param| SweepAxis {
+                            param,
+                            min: axis_state.range[0],
+                            max: axis_state.range[1],
+                            steps: clamp_cast_i32_to_u32(axis_state.steps),
+                        })
+                    })
+                    .collect();
+                let run_disabled = run_disabled || axes.is_empty();
+
+                let run_button_tokens = if run_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let run = ui.button(imgui::im_str!("Run sweep"), [0.0, 0.0]);
+                if let Some((color_token, style_token)) = run_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "RUN SWEEP\n\
+                        \n\
+                        Executes the Operation pipeline for each combination of the parameter values.");
+                        if run_disabled_unsynced {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: All operations must be executed before running a sweep.",
+                            );
+                        }
+                        if run_disabled_empty {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: The pipeline contains no numeric parameters to sweep.",
+                            );
+                        }
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if run && !run_disabled {
+                    notifications.push(
+                        current_time,
+                        NotificationLevel::Info,
+                        "Design sweep started.",
+                    );
+                    status.start = Some(axes);
+                }
+
+                if let Some(sweep) = sweep {
+                    ui.same_line(0.0);
+                    if ui.button(imgui::im_str!("Discard"), [0.0, 0.0]) {
+                        status.cancel = true;
+                    }
+
+                    ui.same_line(0.0);
+                    ui.text(imgui::im_str!(
+                        "Executed {} of {} samples",
+                        sweep.finished_samples_len(),
+                        sweep.samples().len(),
+                    ));
+
+                    let columns = sweep.axes()[0].steps.max(1) as usize;
+                    let thumbnail_size = [
+                        variants::THUMBNAIL_WIDTH as f32 * SWEEP_THUMBNAIL_SCALE,
+                        variants::THUMBNAIL_HEIGHT as f32 * SWEEP_THUMBNAIL_SCALE,
+                    ];
+
+                    for (sample_index, sample) in sweep.samples().iter().enumerate() {
+                        if sample_index % columns != 0 {
+                            ui.same_line(0.0);
+                        }
+
+                        let group_token = ui.begin_group();
+
+                        let keep = match sample.thumbnail() {
+                            Some(thumbnail) => imgui::ImageButton::new(thumbnail, thumbnail_size)
+                                .frame_padding(0)
+                                .build(ui),
+                            None => {
+                                ui.button(
+                                    &imgui::im_str!("...##sweep-sample-{}", sample_index),
+                                    thumbnail_size,
+                                );
+                                false
+                            }
+                        };
+
+                        if ui.is_item_hovered() {
+                            ui.tooltip(|| {
+                                let wrap_token =
+                                    ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                for (axis, value) in sweep.axes().iter().zip(sample.values()) {
+                                    let label = params
+                                        .iter()
+                                        .find(|(p, _)| *p == axis.param)
+                                        .map(|(_, label)| label.to_str())
+                                        .unwrap_or("<Removed parameter>");
+                                    ui.text_colored(
+                                        self.colors.tooltip_text,
+                                        format!(
+                                            "{} = {}",
+                                            label,
+                                            number_format.format_float(f64::from(*value), 3),
+                                        ),
+                                    );
+                                }
+                                match sample.outcome() {
+                                    SampleOutcome::Pending => {
+                                        ui.text_colored(self.colors.tooltip_text, "Pending...");
+                                    }
+                                    SampleOutcome::Finished(measurements) => {
+                                        ui.text_colored(
+                                            self.colors.tooltip_text,
+                                            format!(
+                                                "Volume = {}\nSurface area = {}\n\
+                                                 \n\
+                                                 Click to keep the sample as a variant.",
+                                                number_format.format_float(
+                                                    f64::from(measurements.volume),
+                                                    3,
+                                                ),
+                                                number_format.format_float(
+                                                    f64::from(measurements.surface_area),
+                                                    3,
+                                                ),
+                                            ),
+                                        );
+                                    }
+                                    SampleOutcome::Failed(error_message) => {
+                                        ui.text_colored(
+                                            self.colors.log_message_error,
+                                            format!("Error: {}", error_message),
+                                        );
+                                    }
+                                }
+                                wrap_token.pop(ui);
+                            });
+                        }
+
+                        match sample.outcome() {
+                            SampleOutcome::Pending => ui.text_disabled(imgui::im_str!("-")),
+                            SampleOutcome::Finished(measurements) => ui.text(imgui::im_str!(
+                                "V {}\nA {}",
+                                number_format.format_float(f64::from(measurements.volume), 2),
+                                number_format.format_float(f64::from(measurements.surface_area), 2),
+                            )),
+                            SampleOutcome::Failed(_) => ui.text_colored(
+                                self.colors.log_message_error,
+                                imgui::im_str!("Failed\n "),
+                            ),
+                        }
+
+                        group_token.end(ui);
+
+                        if keep {
+                            if let SampleOutcome::Finished(_) = sample.outcome() {
+                                status.keep_as_variant = Some(sample_index);
+                            }
+                        }
+                    }
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    pub fn draw_optimization_window(
+        &self,
+        current_time: Instant,
+        optimization_window_open: &mut bool,
+        session: &Session,
+        optimization: Option<&Optimization>,
+        notifications: &mut Notifications,
+        number_format: NumberFormat,
+    ) -> OptimizationStatus {
+        let ui = &self.imgui_ui;
+        let mut status = OptimizationStatus::default();
+
+        if !*optimization_window_open {
+            return status;
+        }
+
+        let stmts = session.stmts();
+        let params = numeric_param_labels(session);
+
+        let run_disabled_unsynced = !session.synced();
+        let run_disabled_empty = params.is_empty();
+        let run_disabled_running =
+            optimization.map_or(false, |optimization| !optimization.finished());
+        let run_disabled = run_disabled_unsynced || run_disabled_empty || run_disabled_running;
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Optimization"))
+            .opened(optimization_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    3.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    3.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(self.colors.tooltip_text, "OPTIMIZATION\n\
+                        \n\
+                        Repeatedly executes the Operation pipeline, varying up to three numeric \
+                        parameters within their ranges, looking for the values that minimize \
+                        or maximize a measurement of the produced geometry. \
+                        The search starts from the current parameter values.\n\
+                        \n\
+                        The optimization runs in the background and does not change the current pipeline. \
+                        The best candidates can be kept as design variants.");
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+                let mut optimization_window_state = self.optimization_window_state.borrow_mut();
+
+                // The first parameter is mandatory
+                optimization_window_state.params[0].enabled = true;
+
+                for (param_index, param_state) in
+                    optimization_window_state.params.iter_mut().enumerate()
+                {
+                    // Forget params that are no longer present in the
+                    // pipeline, e.g. because the operation was removed.
+                    if let Some(param) = param_state.param {
+                        if params.iter().all(|(p, _)| *p != param) {
+                            param_state.param = None;
+                        }
+                    }
+
+                    ui.separator();
+
+                    if param_index == 0 {
+                        ui.text(imgui::im_str!("Parameter {}", param_index + 1));
+                    } else {
+                        ui.checkbox(
+                            &imgui::im_str!("Parameter {}", param_index + 1),
+                            &mut param_state.enabled,
+                        );
+                    }
+
+                    if !param_state.enabled {
+                        continue;
+                    }
+
+                    if self.draw_numeric_param_combo(
+                        &imgui::im_str!("##optimization-param-{}", param_index),
+                        &params,
+                        &mut param_state.param,
+                    ) {
+                        let param = param_state.param.expect("Param must have been selected");
+                        param_state.range = initial_param_range(stmts, param);
+                    }
+
+                    ui.input_float2(
+                        &imgui::im_str!("Range##optimization-range-{}", param_index),
+                        &mut param_state.range,
+                    )
+                    .build();
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(self.colors.tooltip_text, "OPTIMIZATION RANGE\n\
+                            \n\
+                            The lowest and highest value the optimization may try. \
+                            Values of integer parameters are rounded. \
+                            Values outside of the range allowed by the operation may fail to execute.");
+                            wrap_token.pop(ui);
+                        });
+                    }
+                }
+
+                ui.separator();
+
+                let measure = optimization_window_state.measure;
+                let combo_box_color_token = self.push_combo_box_colors();
+                if let Some(combo_token) = imgui::ComboBox::new(imgui::im_str!("Measure"))
+                    .preview_value(&imgui::ImString::new(measure.name()))
+                    .begin(ui)
+                {
+                    for measure in &Measure::ALL {
+                        if imgui::Selectable::new(&imgui::ImString::new(measure.name()))
+                            .selected(optimization_window_state.measure == *measure)
+                            .build(ui)
+                        {
+                            optimization_window_state.measure = *measure;
+                        }
+                    }
+
+                    combo_token.end(ui);
+                }
+                combo_box_color_token.pop(ui);
+
+                ui.radio_button(
+                    imgui::im_str!("Minimize"),
+                    &mut optimization_window_state.goal,
+                    Goal::Minimize,
+                );
+                ui.same_line(0.0);
+                ui.radio_button(
+                    imgui::im_str!("Maximize"),
+                    &mut optimization_window_state.goal,
+                    Goal::Maximize,
+                );
+
+                ui.checkbox(
+                    imgui::im_str!("Target volume"),
+                    &mut optimization_window_state.target_volume_enabled,
+                );
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "TARGET VOLUME\n\
+                        \n\
+                        Penalizes candidates whose volume deviates from the target, \
+                        e.g. to minimize the surface area at a fixed volume. \
+                        The volume is only meaningful for closed geometry.");
+                        wrap_token.pop(ui);
+                    });
+                }
+                if optimization_window_state.target_volume_enabled {
+                    ui.input_float(
+                        imgui::im_str!("##optimization-target-volume"),
+                        &mut optimization_window_state.target_volume,
+                    )
+                    .build();
+                }
+
+                if ui
+                    .input_int(
+                        imgui::im_str!("Evaluations"),
+                        &mut optimization_window_state.max_evaluations,
+                    )
+                    .build()
+                {
+                    optimization_window_state.max_evaluations = optimization_window_state
+                        .max_evaluations
+                        .max(1)
+                        .min(clamp_cast_u32_to_i32(optimization::MAX_EVALUATIONS));
+                }
+
+                ui.separator();
+
+                let optimization_params: Vec<OptimizationParam> = optimization_window_state
+                    .params
+                    .iter()
+                    .filter(|param_state| param_state.enabled)
+                    .filter_map(|param_state| {
+                        param_state.param.map(|param| OptimizationParam {
+                            param,
+                            min: param_state.range[0],
+                            max: param_state.range[1],
+                        })
+                    })
+                    .collect();
+                let run_disabled = run_disabled || optimization_params.is_empty();
+
+                let run_button_tokens = if run_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let run = ui.button(imgui::im_str!("Run optimization"), [0.0, 0.0]);
+                if let Some((color_token, style_token)) = run_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "RUN OPTIMIZATION\n\
+                        \n\
+                        Starts searching for the best parameter values.");
+                        if run_disabled_unsynced {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: All operations must be executed before running an optimization.",
+                            );
+                        }
+                        if run_disabled_empty {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: The pipeline contains no numeric parameters to optimize.",
+                            );
+                        }
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if run && !run_disabled {
+                    notifications.push(
+                        current_time,
+                        NotificationLevel::Info,
+                        "Optimization started.",
+                    );
+
+                    let objective = Objective {
+                        measure: optimization_window_state.measure,
+                        goal: optimization_window_state.goal,
+                        target_volume: if optimization_window_state.target_volume_enabled {
+                            Some(optimization_window_state.target_volume)
+                        } else {
+                            None
+                        },
+                    };
+                    status.start = Some((
+                        optimization_params,
+                        objective,
+                        clamp_cast_i32_to_u32(optimization_window_state.max_evaluations),
+                    ));
+                }
+
+                if let Some(optimization) = optimization {
+                    if !optimization.finished() {
+                        ui.same_line(0.0);
+                        if ui.button(imgui::im_str!("Stop"), [0.0, 0.0]) {
+                            status.stop = true;
+                        }
+                    }
+
+                    ui.same_line(0.0);
+                    if ui.button(imgui::im_str!("Discard"), [0.0, 0.0]) {
+                        status.discard = true;
+                    }
+
+                    ui.text(imgui::im_str!(
+                        "Evaluated {} of {}, executed {} candidates",
+                        optimization.evaluations(),
+                        optimization.max_evaluations(),
+                        optimization.candidates().len(),
+                    ));
+
+                    let best_candidates =
+                        optimization.best_candidates(optimization::KEEP_BEST_CANDIDATES_COUNT);
+                    let objective = optimization.objective();
+
+                    for (rank, candidate_index) in best_candidates.iter().enumerate() {
+                        let candidate = &optimization.candidates()[*candidate_index];
+                        let values = candidate
+                            .values()
+                            .iter()
+                            .map(|value| number_format.format_float(f64::from(*value), 3))
+                            .collect::<Vec<_>>()
+                            .join(number_format.list_separator());
+
+                        ui.bullet_text(&imgui::im_str!("#{} [{}]", rank + 1, values));
+                        if let SampleOutcome::Finished(measurements) = candidate.outcome() {
+                            ui.same_line(0.0);
+                            ui.text_disabled(imgui::im_str!(
+                                "{} = {}",
+                                objective.measure.name(),
+                                number_format.format_float(
+                                    f64::from(objective.measure.measure(measurements)),
+                                    3,
+                                ),
+                            ));
+                            if objective.target_volume.is_some() {
+                                ui.same_line(0.0);
+                                ui.text_disabled(imgui::im_str!(
+                                    "Volume = {}",
+                                    number_format.format_float(f64::from(measurements.volume), 3),
+                                ));
+                            }
+                        }
+                    }
+
+                    let keep_disabled = best_candidates.is_empty();
+                    let keep_button_tokens = if keep_disabled {
+                        Some(push_disabled_style(ui))
+                    } else {
+                        None
+                    };
+                    let keep = ui.button(imgui::im_str!("Keep best as variants"), [0.0, 0.0]);
+                    if let Some((color_token, style_token)) = keep_button_tokens {
+                        color_token.pop(ui);
+                        style_token.pop(ui);
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(self.colors.tooltip_text, "KEEP BEST AS VARIANTS\n\
+                            \n\
+                            Captures the best candidates found so far as design variants.");
+                            wrap_token.pop(ui);
+                        });
+                    }
+
+                    if keep && !keep_disabled {
+                        status.keep_best = true;
+                    }
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    pub fn draw_sun_window(
+        &self,
+        current_time: Instant,
+        sun_window_open: &mut bool,
+        sun_options: &mut SunOptions,
+        geolocation: &mut Geolocation,
+        viewport_north_arrow: &mut bool,
+        sun_animation: &mut bool,
+        shadow_study_options: &mut ShadowStudyOptions,
+        notifications: &mut Notifications,
+    ) -> SunStatus {
+        let ui = &self.imgui_ui;
+        let mut status = SunStatus::default();
+
+        if !*sun_window_open {
+            return status;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Sun & shadows"))
+            .opened(sun_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    4.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    4.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(self.colors.tooltip_text, "SUN & SHADOWS\n\
+                        \n\
+                        Positions the shadow casting light as the sun at the latitude of the site, \
+                        given date and local solar time (the sun is highest at 12:00). \
+                        The true north is rotated from the Y axis by the north angle of the site.\n\
+                        \n\
+                        The sun position is approximate and suitable for early design studies only.");
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                ui.text(imgui::im_str!("Site"));
+
+                if imgui::Slider::new(imgui::im_str!("Latitude"))
+                    .range(-90.0..=90.0)
+                    .display_format(imgui::im_str!("%.4f deg"))
+                    .build(ui, &mut geolocation.latitude)
+                {
+                    status.geolocation_changed = true;
+                }
+                if imgui::Slider::new(imgui::im_str!("Longitude"))
+                    .range(-180.0..=180.0)
+                    .display_format(imgui::im_str!("%.4f deg"))
+                    .build(ui, &mut geolocation.longitude)
+                {
+                    status.geolocation_changed = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "LONGITUDE\n\
+                        \n\
+                        Stored with the project for reference. The sun model uses \
+                        local solar time, which does not depend on the longitude.");
+                        wrap_token.pop(ui);
+                    });
+                }
+                if imgui::Slider::new(imgui::im_str!("North angle"))
+                    .range(-180.0..=180.0)
+                    .display_format(imgui::im_str!("%.1f deg"))
+                    .build(ui, &mut geolocation.north_angle)
+                {
+                    status.geolocation_changed = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "NORTH ANGLE\n\
+                        \n\
+                        Angle of the true north from the Y axis of the scene, \
+                        measured clockwise when looking down at the ground.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                ui.checkbox(imgui::im_str!("Show north arrow"), viewport_north_arrow);
+
+                ui.separator();
+
+                ui.checkbox(imgui::im_str!("Sun position"), &mut sun_options.enabled);
+
+                let sun_options_tokens = if sun_options.enabled {
+                    None
+                } else {
+                    Some(push_disabled_style(ui))
+                };
+
+                let date = chrono::NaiveDate::from_yo(2021, sun_options.day_of_year);
+                imgui::Slider::new(imgui::im_str!("Date"))
+                    .range(1..=sun::DAYS_IN_YEAR)
+                    .display_format(&imgui::im_str!("{}", date.format("%B %d")))
+                    .build(ui, &mut sun_options.day_of_year);
+
+                let total_minutes = (sun_options.solar_time * 60.0) as u32;
+                imgui::Slider::new(imgui::im_str!("Time"))
+                    .range(0.0..=24.0)
+                    .display_format(&imgui::im_str!("{:02}:{:02}", total_minutes / 60, total_minutes % 60))
+                    .build(ui, &mut sun_options.solar_time);
+
+                ui.checkbox(imgui::im_str!("Animate day"), sun_animation);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "ANIMATE DAY\n\
+                        \n\
+                        Continuously advances the time, so that the movement of the shadows \
+                        over the course of the day can be observed.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if let Some(sun_position) = sun_options.sun_position(geolocation.latitude) {
+                    if sun_position.is_above_horizon() {
+                        ui.text_disabled(imgui::im_str!(
+                            "Altitude {:.1} deg, azimuth {:.1} deg",
+                            sun_position.altitude.to_degrees(),
+                            sun_position.azimuth.to_degrees(),
+                        ));
+                    } else {
+                        ui.text_colored(
+                            self.colors.log_message_warn,
+                            imgui::im_str!("The sun is below the horizon"),
+                        );
+                    }
+                }
+
+                if let Some((color_token, style_token)) = sun_options_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                ui.separator();
+                ui.text(imgui::im_str!("Shadow study"));
+
+                let mut time_range = [
+                    shadow_study_options.start_time,
+                    shadow_study_options.end_time,
+                ];
+                if ui
+                    .input_float2(imgui::im_str!("Time range (h)"), &mut time_range)
+                    .build()
+                {
+                    shadow_study_options.start_time = math::clamp(time_range[0], 0.0, 24.0);
+                    shadow_study_options.end_time =
+                        math::clamp(time_range[1], shadow_study_options.start_time, 24.0);
+                }
+
+                let mut step_minutes = clamp_cast_u32_to_i32(shadow_study_options.step_minutes);
+                if ui
+                    .input_int(imgui::im_str!("Step (min)"), &mut step_minutes)
+                    .build()
+                {
+                    shadow_study_options.step_minutes = clamp_cast_i32_to_u32(step_minutes).max(1);
+                }
+
+                let export_disabled = !sun_options.enabled;
+                let export_button_tokens = if export_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let export = ui.button(imgui::im_str!("Export shadow study..."), [0.0, 0.0]);
+                if let Some((color_token, style_token)) = export_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "EXPORT SHADOW STUDY\n\
+                        \n\
+                        Opens a system dialog for choosing a directory, and saves an image \
+                        of the viewport for each time step of the day, in which the sun is above the horizon. \
+                        The images have the dimensions set for screenshots.");
+                        if export_disabled {
+                            ui.text_colored(
+                                self.colors.log_message_warn,
+                                "WARNING: Sun position must be enabled to export a shadow study.",
+                            );
+                        }
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if export && !export_disabled {
+                    if let Some(path) =
+                        tinyfiledialogs::select_folder_dialog("Export shadow study", "")
+                    {
+                        notifications.push(
+                            current_time,
+                            NotificationLevel::Info,
+                            format!("Exporting shadow study to {}", path),
+                        );
+                        status.export_shadow_study = Some(PathBuf::from(path));
+                    }
+                }
+
+                ui.separator();
+                ui.text(imgui::im_str!("Matcap"));
+
+                if ui.button(imgui::im_str!("Bake matcap..."), [0.0, 0.0]) {
+                    // FIXME: @Refactoring Factor out this use of
+                    // tinyfiledialogs from this module
+                    if let Some(path) = tinyfiledialogs::save_file_dialog_with_filter(
+                        "Bake matcap",
+                        "matcap.png",
+                        &["*.png"],
+                        "PNG image (*.png)",
+                    ) {
+                        status.bake_matcap = Some(PathBuf::from(path));
+                    }
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "BAKE MATCAP\n\
+                        \n\
+                        Opens a system dialog for choosing a file, and saves an image of a \
+                        sphere shaded by the current matcap, darkened by the shadows of the \
+                        sun and surrounded by the viewport background. The image becomes \
+                        the matcap of shaded surfaces, so the lighting stays the same in \
+                        other projects. The matcap can be changed in Preferences.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_calibration_window(
+        &self,
+        calibration_window_open: &mut bool,
+        calibration: &ScaleCalibration,
+        target_name: Option<&str>,
+        project_unit: Unit,
+        real_distance: &mut f32,
+    ) -> CalibrationStatus {
+        let ui = &self.imgui_ui;
+        let mut status = CalibrationStatus::default();
+
+        if !*calibration_window_open {
+            return status;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Calibrate scale"))
+            .opened(calibration_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    5.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    5.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "CALIBRATE SCALE\n\
+                        \n\
+                        Click two points on a mesh in the viewport and enter their real world \
+                        distance. The mesh gets uniformly scaled around its center, so that the \
+                        picked points are the entered distance apart.\n\
+                        \n\
+                        If the mesh is the result of a Transform operation, its scale is updated. \
+                        Otherwise a new Transform operation is added to the pipeline.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                match calibration.points().len() {
+                    0 => ui.text_wrapped(imgui::im_str!("Click the first point on a mesh.")),
+                    1 => ui.text_wrapped(imgui::im_str!("Click the second point.")),
+                    _ => (),
+                }
+
+                if let Some(target_name) = target_name {
+                    ui.text_disabled(imgui::im_str!("Mesh: {}", target_name));
+                }
+
+                let unit_abbreviation = project_unit.abbreviation();
+                let measured_distance = calibration.measured_distance();
+                if let Some(measured_distance) = measured_distance {
+                    ui.text(imgui::im_str!(
+                        "Picked distance: {:.4} {}",
+                        measured_distance,
+                        unit_abbreviation,
+                    ));
+
+                    ui.input_float(
+                        &imgui::im_str!("Real distance ({})", unit_abbreviation),
+                        real_distance,
+                    )
+                    .build();
+                }
+
+                let factor = measured_distance.and_then(|measured_distance| {
+                    calibration::scale_factor(measured_distance, *real_distance)
+                });
+                if let Some(factor) = factor {
+                    ui.text_disabled(imgui::im_str!("Scale by {:.6}", factor));
+                }
+
+                let apply_disabled = factor.is_none();
+                let apply_button_tokens = if apply_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let apply = ui.button(imgui::im_str!("Apply"), [0.0, 0.0]);
+                if let Some((color_token, style_token)) = apply_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if apply && !apply_disabled {
+                    status.apply = factor;
+                }
+
+                ui.same_line(0.0);
+                if ui.button(imgui::im_str!("Pick again"), [0.0, 0.0]) {
+                    status.reset = true;
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    pub fn draw_reference_images_window(
+        &self,
+        reference_images_window_open: &mut bool,
+        reference_images: &mut [ReferenceImage],
+        project_unit: Unit,
+    ) -> ReferenceImagesStatus {
+        let ui = &self.imgui_ui;
+        let mut status = ReferenceImagesStatus::default();
+
+        if !*reference_images_window_open {
+            return status;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Reference images"))
+            .opened(reference_images_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    6.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    6.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "REFERENCE IMAGES\n\
+                        \n\
+                        Shows images as planes in the viewport, either lying on the ground, \
+                        such as site plans and maps, or standing upright, such as elevations \
+                        and sections.\n\
+                        \n\
+                        The images are not part of the geometry and are not exported. \
+                        The project only stores the paths to the image files.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                let add_ground = ui.button(imgui::im_str!("Add ground image..."), [0.0, 0.0]);
+                ui.same_line(0.0);
+                let add_vertical = ui.button(imgui::im_str!("Add vertical image..."), [0.0, 0.0]);
+
+                let add_placement = if add_ground {
+                    Some(ReferenceImagePlacement::Ground)
+                } else if add_vertical {
+                    Some(ReferenceImagePlacement::Vertical)
+                } else {
+                    None
+                };
+
+                if let Some(placement) = add_placement {
+                    // FIXME: @Refactoring Factor out this use of
+                    // tinyfiledialogs from this module
+                    if let Some(path) = tinyfiledialogs::open_file_dialog(
+                        "Add reference image",
+                        "",
+                        Some((&["*.png", "*.jpg", "*.jpeg"], "Images (*.png, *.jpg)")),
+                    ) {
+                        status.add = Some((PathBuf::from(path), placement));
+                    }
+                }
+
+                if reference_images.is_empty() {
+                    ui.text_disabled(imgui::im_str!("No reference images"));
+                }
+
+                let unit_abbreviation = project_unit.abbreviation();
+                for (image_index, reference_image) in reference_images.iter_mut().enumerate() {
+                    ui.separator();
+
+                    let file_name = Path::new(&reference_image.path).file_name().map_or_else(
+                        || Cow::Borrowed(reference_image.path.as_str()),
+                        |file_name| file_name.to_string_lossy(),
+                    );
+                    ui.text(imgui::im_str!("{}", file_name));
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            ui.text_colored(self.colors.tooltip_text, &reference_image.path);
+                        });
+                    }
+
+                    let mut changed = false;
+
+                    for placement in &[
+                        ReferenceImagePlacement::Ground,
+                        ReferenceImagePlacement::Vertical,
+                    ] {
+                        if *placement == ReferenceImagePlacement::Vertical {
+                            ui.same_line(0.0);
+                        }
+                        changed |= ui.radio_button(
+                            &imgui::im_str!(
+                                "{}##reference-image-placement-{}",
+                                placement.name(),
+                                image_index,
+                            ),
+                            &mut reference_image.placement,
+                            *placement,
+                        );
+                    }
+
+                    changed |= imgui::Drag::<f32>::new(&imgui::im_str!(
+                        "Center ({})##reference-image-center-{}",
+                        unit_abbreviation,
+                        image_index,
+                    ))
+                    .speed(DRAG_SPEED)
+                    .build_array(ui, &mut reference_image.center);
+
+                    changed |= imgui::Drag::<f32>::new(&imgui::im_str!(
+                        "Width ({})##reference-image-width-{}",
+                        unit_abbreviation,
+                        image_index,
+                    ))
+                    .speed(DRAG_SPEED)
+                    .range(0.001..)
+                    .build(ui, &mut reference_image.width);
+
+                    changed |= imgui::Drag::<f32>::new(&imgui::im_str!(
+                        "Rotation (deg)##reference-image-rotation-{}",
+                        image_index,
+                    ))
+                    .range(-180.0..=180.0)
+                    .build(ui, &mut reference_image.rotation);
+
+                    changed |= imgui::Slider::new(&imgui::im_str!(
+                        "Opacity##reference-image-opacity-{}",
+                        image_index,
+                    ))
+                    .range(0.0..=1.0)
+                    .build(ui, &mut reference_image.opacity);
+
+                    if changed {
+                        status.changed = Some(image_index);
+                    }
+
+                    if ui.button(
+                        &imgui::im_str!("Remove##reference-image-remove-{}", image_index),
+                        [0.0, 0.0],
+                    ) {
+                        status.remove = Some(image_index);
+                    }
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    pub fn draw_placement_window(
+        &self,
+        placement_window_open: &mut bool,
+        library_mesh_path: Option<&str>,
+        rotation: &mut f32,
+        place_on_surfaces: &mut bool,
+    ) -> PlacementStatus {
+        let ui = &self.imgui_ui;
+        let mut status = PlacementStatus::default();
+
+        if !*placement_window_open {
+            return status;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Place objects"))
+            .opened(placement_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    8.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    8.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "PLACE OBJECTS\n\
+                        \n\
+                        Choose an OBJ file and move the cursor over the viewport to preview \
+                        where it will be placed. Each click adds an Import OBJ and a Transform \
+                        operation to the pipeline, placing a copy of the object at the cursor.\n\
+                        \n\
+                        Placed positions and rotations snap to the increments set in \
+                        the preferences.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                if ui.button(imgui::im_str!("Choose OBJ..."), [0.0, 0.0]) {
+                    // FIXME: @Refactoring Factor out this use of
+                    // tinyfiledialogs from this module
+                    if let Some(path) = tinyfiledialogs::open_file_dialog(
+                        "Choose object to place",
+                        "",
+                        Some((&["*.obj", "*.OBJ"], "Wavefront (.obj)")),
+                    ) {
+                        status.choose = Some(PathBuf::from(path));
+                    }
+                }
+
+                match library_mesh_path {
+                    Some(path) => {
+                        let file_name = Path::new(path).file_name().map_or_else(
+                            || Cow::Borrowed(path),
+                            |file_name| file_name.to_string_lossy(),
+                        );
+                        ui.text(imgui::im_str!("{}", file_name));
+                        if ui.is_item_hovered() {
+                            ui.tooltip(|| {
+                                ui.text_colored(self.colors.tooltip_text, path);
+                            });
+                        }
+                    }
+                    None => ui.text_disabled(imgui::im_str!("No object chosen")),
+                }
+
+                status.changed |= imgui::Drag::<f32>::new(imgui::im_str!("Rotation (deg)"))
+                    .range(-180.0..=180.0)
+                    .build(ui, rotation);
+
+                status.changed |=
+                    ui.checkbox(imgui::im_str!("Place on surfaces"), place_on_surfaces);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "PLACE ON SURFACES\n\
+                        \n\
+                        Places objects on the meshes under the cursor. \
+                        Otherwise objects are always placed on the ground.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    /// Draws the library window. Returns the asset to insert into the
+    /// pipeline, if any.
+    pub fn draw_library_window(
+        &self,
+        library_window_open: &mut bool,
+        assets: &'static [LibraryAsset],
+        interpreter_busy: bool,
+    ) -> Option<&'static LibraryAsset> {
+        let ui = &self.imgui_ui;
+        let mut insert = None;
+
+        if !*library_window_open {
+            return insert;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Library"))
+            .opened(library_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    9.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    9.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "LIBRARY\n\
+                        \n\
+                        Built-in context geometry. Inserting an asset adds a Library Mesh \
+                        operation to the pipeline, scaled to the project units. \
+                        Move the inserted mesh with a Transform operation.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                let insert_button_tokens = if interpreter_busy {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+
+                for asset in assets {
+                    let clicked = ui.button(
+                        &imgui::im_str!("Insert##library-asset-{}", asset.id),
+                        [0.0, 0.0],
+                    );
+                    if clicked && !interpreter_busy {
+                        insert = Some(asset);
+                    }
+
+                    ui.same_line(0.0);
+                    ui.text(imgui::im_str!("{}", asset.name));
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            ui.text_colored(self.colors.tooltip_text, asset.description);
+                        });
+                    }
+                }
+
+                if let Some((color_token, style_token)) = insert_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        insert
+    }
+
+    /// Draws the box drawing window. Returns whether to start the drawing
+    /// over.
+    pub fn draw_box_drawing_window(
+        &self,
+        box_drawing_window_open: &mut bool,
+        drawing: &BoxDrawing,
+        project_unit: Unit,
+    ) -> bool {
+        let ui = &self.imgui_ui;
+        let mut reset = false;
+
+        if !*box_drawing_window_open {
+            return reset;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Draw box"))
+            .opened(box_drawing_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    10.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    10.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "DRAW BOX\n\
+                        \n\
+                        Drag a rectangle on the ground in the viewport, then move the cursor \
+                        up to pull the box to its height and click. The box is added to the \
+                        pipeline as a Create Box operation.\n\
+                        \n\
+                        While the window is open, dragging with the left button draws instead \
+                        of rotating the camera. Panning and zooming work as usual. \
+                        Dimensions snap to the length increment set in the preferences.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                if drawing.pulling_height() {
+                    ui.text_wrapped(imgui::im_str!(
+                        "Move the cursor to pull up the height and click to add the box."
+                    ));
+                } else {
+                    ui.text_wrapped(imgui::im_str!("Drag the base rectangle on the ground."));
+                }
+
+                let unit_abbreviation = project_unit.abbreviation();
+                if let Some(dimensions) = drawing.dimensions() {
+                    ui.text(imgui::im_str!(
+                        "Width: {:.3} {}",
+                        dimensions.x,
+                        unit_abbreviation,
+                    ));
+                    ui.text(imgui::im_str!(
+                        "Depth: {:.3} {}",
+                        dimensions.y,
+                        unit_abbreviation,
+                    ));
+                    ui.text(imgui::im_str!(
+                        "Height: {:.3} {}",
+                        dimensions.z,
+                        unit_abbreviation,
+                    ));
+                }
+
+                if ui.button(imgui::im_str!("Start over"), [0.0, 0.0]) {
+                    reset = true;
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        reset
+    }
+
+    /// Draws the footprint sketch window.
+    pub fn draw_sketch_window(
+        &self,
+        sketch_window_open: &mut bool,
+        sketch: &FootprintSketch,
+        height: &mut f32,
+        project_unit: Unit,
+    ) -> SketchStatus {
+        let ui = &self.imgui_ui;
+        let mut status = SketchStatus::default();
+
+        if !*sketch_window_open {
+            return status;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Sketch footprint"))
+            .opened(sketch_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    10.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    10.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "SKETCH FOOTPRINT\n\
+                        \n\
+                        Click points of the footprint outline on the ground in the viewport. \
+                        Click the first point again or press Finish to close the outline. \
+                        The footprint is added to the pipeline as a Create Polygon operation \
+                        and extruded to the height by an Extrude operation.\n\
+                        \n\
+                        The outline may be concave, but must not intersect itself. \
+                        Points snap to the length increment set in the preferences.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                if sketch.can_close() {
+                    ui.text_wrapped(imgui::im_str!(
+                        "Click the first point or press Finish to close the footprint."
+                    ));
+                } else {
+                    ui.text_wrapped(imgui::im_str!("Click points of the outline on the ground."));
+                }
+
+                ui.text(imgui::im_str!("Points: {}", sketch.point_count()));
+
+                status.height_changed = ui
+                    .input_float(
+                        &imgui::im_str!("Height ({})", project_unit.abbreviation()),
+                        height,
+                    )
+                    .build();
+
+                let finish_disabled = !sketch.can_close();
+                let finish_button_tokens = if finish_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let finish = ui.button(imgui::im_str!("Finish"), [0.0, 0.0]);
+                if let Some((color_token, style_token)) = finish_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                status.finish = !finish_disabled && finish;
+
+                ui.same_line(0.0);
+                if ui.button(imgui::im_str!("Undo point"), [0.0, 0.0]) {
+                    status.remove_last_point = true;
+                }
+
+                ui.same_line(0.0);
+                if ui.button(imgui::im_str!("Start over"), [0.0, 0.0]) {
+                    status.reset = true;
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    pub fn draw_find_replace_window(
+        &self,
+        find_replace_window_open: &mut bool,
+        session: &Session,
+    ) -> FindReplaceStatus {
+        let ui = &self.imgui_ui;
+        let mut status = FindReplaceStatus::default();
+
+        if !*find_replace_window_open {
+            return status;
+        }
+
+        let stmts = session.stmts();
+        let function_table = session.function_table();
+        let interpreter_busy = session.interpreter_busy();
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Find and replace"))
+            .opened(find_replace_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    11.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    11.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "FIND AND REPLACE\n\
+                        \n\
+                        Finds numeric parameters across the whole Operation pipeline and \
+                        replaces their values at once, e.g. every Relax iteration count of 3 \
+                        with 5. The found parameters are listed with their new values before \
+                        replacing them.\n\
+                        \n\
+                        The operation and the parameter narrow down the search. Values are \
+                        typed as math expressions and an empty value finds any value. \
+                        Parameters with multiple values take comma separated values. The new \
+                        values are clamped to the range of each parameter.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+                let mut find_replace_window_state = self.find_replace_window_state.borrow_mut();
+
+                // Forget the operation and the parameter once they are no
+                // longer present in the pipeline.
+                let func_idents = find_replace::used_funcs(stmts, function_table);
+                if let Some(func_ident) = find_replace_window_state.func_ident {
+                    if !func_idents.contains(&func_ident) {
+                        find_replace_window_state.func_ident = None;
+                    }
+                }
+                let param_names = find_replace::param_names(
+                    stmts,
+                    function_table,
+                    find_replace_window_state.func_ident,
+                );
+                if let Some(param_name) = find_replace_window_state.param_name {
+                    if !param_names.contains(&param_name) {
+                        find_replace_window_state.param_name = None;
+                    }
+                }
+
+                let combo_box_color_token = self.push_combo_box_colors();
+
+                let func_preview = find_replace_window_state
+                    .func_ident
+                    .map_or("<Any operation>", |func_ident| {
+                        function_table[&func_ident].info().name
+                    });
+                if let Some(combo_token) = imgui::ComboBox::new(imgui::im_str!("Operation"))
+                    .preview_value(&imgui::ImString::new(func_preview))
+                    .begin(ui)
+                {
+                    if imgui::Selectable::new(imgui::im_str!("<Any operation>"))
+                        .selected(find_replace_window_state.func_ident.is_none())
+                        .build(ui)
+                    {
+                        find_replace_window_state.func_ident = None;
+                    }
+                    for func_ident in &func_idents {
+                        let name = function_table[func_ident].info().name;
+                        if imgui::Selectable::new(&imgui::ImString::new(name))
+                            .selected(find_replace_window_state.func_ident == Some(*func_ident))
+                            .build(ui)
+                        {
+                            find_replace_window_state.func_ident = Some(*func_ident);
+                        }
+                    }
+
+                    combo_token.end(ui);
+                }
+
+                let param_preview = find_replace_window_state
+                    .param_name
+                    .unwrap_or("<Any parameter>");
+                if let Some(combo_token) = imgui::ComboBox::new(imgui::im_str!("Parameter"))
+                    .preview_value(&imgui::ImString::new(param_preview))
+                    .begin(ui)
+                {
+                    if imgui::Selectable::new(imgui::im_str!("<Any parameter>"))
+                        .selected(find_replace_window_state.param_name.is_none())
+                        .build(ui)
+                    {
+                        find_replace_window_state.param_name = None;
+                    }
+                    for param_name in &param_names {
+                        if imgui::Selectable::new(&imgui::ImString::new(*param_name))
+                            .selected(find_replace_window_state.param_name == Some(*param_name))
+                            .build(ui)
+                        {
+                            find_replace_window_state.param_name = Some(*param_name);
+                        }
+                    }
+
+                    combo_token.end(ui);
+                }
+
+                combo_box_color_token.pop(ui);
+
+                ui.input_text(imgui::im_str!("Value"), &mut find_replace_window_state.find)
+                    .build();
+                ui.input_text(
+                    imgui::im_str!("Replace with"),
+                    &mut find_replace_window_state.replace,
+                )
+                .build();
+
+                let find_value = find_replace_window_state.find.to_str().trim();
+                let query = FindQuery {
+                    func_ident: find_replace_window_state.func_ident,
+                    param_name: find_replace_window_state.param_name,
+                    value: if find_value.is_empty() {
+                        None
+                    } else {
+                        Some(find_value)
+                    },
+                };
+                let new_value = find_replace_window_state.replace.to_str();
+                let replace_empty = new_value.trim().is_empty();
+                let replacements =
+                    find_replace::find_replacements(stmts, function_table, query, new_value);
+
+                ui.separator();
+
+                if replacements.is_empty() {
+                    ui.text_disabled(imgui::im_str!("No parameters found"));
+                } else {
+                    ui.text(imgui::im_str!("Found {} parameters", replacements.len()));
+                }
+
+                // Operations on locked layers can not be changed
+                let replaceable: Vec<_> = if replace_empty {
+                    Vec::new()
+                } else {
+                    replacements
+                        .iter()
+                        .filter(|replacement| {
+                            let ast::Stmt::VarDecl(var_decl) = &stmts[replacement.stmt_index];
+                            replacement.new_value.is_ok()
+                                && !session.layers().var_locked(var_decl.ident())
+                        })
+                        .cloned()
+                        .collect()
+                };
+
+                if let Some(window_token) = imgui::ChildWindow::new("##find-replace-preview")
+                    .size([FIND_REPLACE_PREVIEW_WIDTH, FIND_REPLACE_PREVIEW_HEIGHT])
+                    .scrollable(true)
+                    .scroll_bar(true)
+                    .begin(ui)
+                {
+                    for replacement in &replacements {
+                        let ast::Stmt::VarDecl(var_decl) = &stmts[replacement.stmt_index];
+                        let label =
+                            param_label(session, replacement.stmt_index, replacement.arg_index);
+                        let value = expression::format_lit(&replacement.value)
+                            .expect("Found parameters must be numeric");
+
+                        if session.layers().var_locked(var_decl.ident()) {
+                            ui.text_disabled(
+                                imgui::im_str!("{}: {} (locked layer)", label, value,),
+                            );
+                            continue;
+                        }
+
+                        match &replacement.new_value {
+                            _ if replace_empty => ui.text(imgui::im_str!("{}: {}", label, value)),
+                            Ok(new_value) => {
+                                let new_value = expression::format_lit(new_value)
+                                    .expect("Replaced parameters must be numeric");
+                                ui.text(imgui::im_str!("{}: {} -> {}", label, value, new_value));
+                            }
+                            Err(err) => ui.text_colored(
+                                self.colors.log_message_error,
+                                format!("{}: {} ({})", label, value, err),
+                            ),
+                        }
+                    }
+
+                    window_token.end(ui);
+                }
+
+                let replace_disabled = interpreter_busy || replaceable.is_empty();
+                let replace_button_tokens = if replace_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let replace = ui.button(
+                    &imgui::im_str!("Replace {} values", replaceable.len()),
+                    [0.0, 0.0],
+                );
+                if let Some((color_token, style_token)) = replace_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if ui.is_item_hovered() && interpreter_busy {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.log_message_warn,
+                            "WARNING: Values can not be replaced while the pipeline is running.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+                if replace && !replace_disabled {
+                    status.replace = Some(replaceable);
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    pub fn draw_profiler_window(&self, profiler_window_open: &mut bool, profiler: &Profiler) {
+        let ui = &self.imgui_ui;
+
+        if !*profiler_window_open {
+            return;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Profiler"))
+            .opened(profiler_window_open)
+            .always_auto_resize(true)
+            .bg_alpha(0.8)
+            .position(
+                [
+                    13.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    13.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "PROFILER\n\
+                        \n\
+                        Shows the times between the last frames and how long the stages of \
+                        drawing them took on average and at most, in milliseconds. Double \
+                        click the title to collapse the overlay.\n\
+                        \n\
+                        The stages are measured on the processor. Long swap chain waits mean \
+                        that the graphics card can't keep up, e.g. because the geometry is too \
+                        heavy, or that the frame rate is limited by the display. Long session \
+                        polls and mesh uploads mean that large geometry was just computed.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                let frame_average = profiler.frame_average();
+                let frame_average_ms = frame_average.as_secs_f32() * 1000.0;
+                let frame_times_ms = profiler.frame_times_ms();
+                imgui::PlotLines::new(
+                    ui,
+                    imgui::im_str!("##profiler-frame-times"),
+                    &frame_times_ms,
+                )
+                .overlay_text(&imgui::im_str!(
+                    "Frame {:.2} ms ({:.0} fps)",
+                    frame_average_ms,
+                    if frame_average_ms > 0.0 {
+                        1000.0 / frame_average_ms
+                    } else {
+                        0.0
+                    },
+                ))
+                .scale_min(0.0)
+                .graph_size([PROFILER_PLOT_WIDTH, PROFILER_PLOT_HEIGHT])
+                .build();
+
+                // The font is monospace, so the table is aligned by padding
+                ui.text(format!("{:<24}{:>9}{:>9}", "Stage", "Average", "Max"));
+                ui.separator();
+                for stage in &profiler::Stage::ALL {
+                    ui.text(format!(
+                        "{:<24}{:>9.2}{:>9.2}",
+                        stage.name(),
+                        profiler.stage_average(*stage).as_secs_f32() * 1000.0,
+                        profiler.stage_max(*stage).as_secs_f32() * 1000.0,
+                    ));
+                }
+                ui.separator();
+                ui.text(format!(
+                    "{:<24}{:>9.2}{:>9.2}",
+                    "Frame",
+                    frame_average_ms,
+                    profiler.frame_max().as_secs_f32() * 1000.0,
+                ));
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+    }
+
+    /// Draws the help window for the last hovered operation, or the operation
+    /// declaring the focused variable, if it was focused later.
+    pub fn draw_help_window(&self, help_window_open: &mut bool, session: &Session) {
+        let ui = &self.imgui_ui;
+
+        if !*help_window_open {
+            return;
+        }
+
+        let mut help_window_state = self.help_window_state.borrow_mut();
+        let focused_var = session.focused_var();
+        if focused_var != help_window_state.last_focused_var {
+            help_window_state.last_focused_var = focused_var;
+            if let Some(focused_var) = focused_var {
+                let focused_func_ident = session.stmts().iter().find_map(|stmt| {
+                    let ast::Stmt::VarDecl(var_decl) = stmt;
+                    if var_decl.ident() == focused_var {
+                        Some(var_decl.init_expr().ident())
+                    } else {
+                        None
+                    }
+                });
+                if focused_func_ident.is_some() {
+                    help_window_state.func_ident = focused_func_ident;
+                }
+            }
+        }
+
+        let function_table = session.function_table();
+        let func_ident = help_window_state.func_ident;
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Operation help"))
+            .opened(help_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    12.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    12.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "OPERATION HELP\n\
+                        \n\
+                        Explains the operation last hovered in the Operations or Pipeline \
+                        windows. Selecting geometry in the viewport or in the pipeline shows \
+                        the operation which created it.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let wrap_token = ui.push_text_wrap_pos(HELP_WINDOW_TEXT_WIDTH);
+
+                let func = match func_ident.and_then(|func_ident| function_table.get(&func_ident)) {
+                    Some(func) => func,
+                    None => {
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_disabled(
+                            "Hover an operation in the Operations or Pipeline windows to show \
+                             its help here.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                        return;
+                    }
+                };
+
+                let info = func.info();
+                ui.text(info.name);
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+                if !info.description.is_empty() {
+                    ui.text(info.description);
+                }
+
+                if let Some(docs) = func.docs() {
+                    ui.separator();
+                    ui.text(docs.details);
+
+                    if let Some((texture_id, size)) = func_ident
+                        .and_then(|func_ident| help_window_state.func_doc_images.get(&func_ident))
+                    {
+                        // Large images are scaled down to the text width,
+                        // small ones are kept at their size
+                        let scale = f32::min(1.0, HELP_WINDOW_TEXT_WIDTH / size[0]);
+                        ui.spacing();
+                        imgui::Image::new(*texture_id, [size[0] * scale, size[1] * scale])
+                            .build(ui);
+                    }
+                }
+
+                let param_info = func.param_info();
+                if !param_info.is_empty() {
+                    ui.separator();
+                    for param_info in param_info {
+                        let param_bold_font_token = ui.push_font(self.font_ids.bold);
+                        ui.text(param_info.name);
+                        param_bold_font_token.pop(ui);
+
+                        if !param_info.description.is_empty() {
+                            ui.text(param_info.description);
+                        }
+                        ui.spacing();
+                    }
+                }
+
+                let return_bold_font_token = ui.push_font(self.font_ids.bold);
+                ui.text(imgui::im_str!("Returns: {}", info.return_value_name));
+                return_bold_font_token.pop(ui);
+
+                regular_font_token.pop(ui);
+                wrap_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+    }
+
+    /// Draws the examples window. Returns the example to open, if any.
+    pub fn draw_examples_window(
+        &self,
+        examples_window_open: &mut bool,
+        examples: &'static [ExampleProject],
+        interpreter_busy: bool,
+    ) -> Option<&'static ExampleProject> {
+        let ui = &self.imgui_ui;
+        let mut open = None;
+
+        if !*examples_window_open {
+            return open;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Examples"))
+            .opened(examples_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    9.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    9.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "EXAMPLES\n\
+                        \n\
+                        Example projects bundled with the application. Opening an example \
+                        replaces the current project. Examples are read-only and \
+                        changes to them are kept only in a saved copy.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                let open_button_tokens = if interpreter_busy {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+
+                for example in examples {
+                    let clicked =
+                        ui.button(&imgui::im_str!("Open##example-{}", example.id), [0.0, 0.0]);
+                    if clicked && !interpreter_busy {
+                        open = Some(example);
+                    }
+
+                    ui.same_line(0.0);
+                    ui.text(imgui::im_str!("{}", example.name));
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            ui.text_colored(self.colors.tooltip_text, example.description);
+                        });
+                    }
+                }
+
+                if let Some((color_token, style_token)) = open_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        open
+    }
+
+    /// Draws the layers window. Returns whether any of the layers changed.
+    pub fn draw_layers_window(&self, layers_window_open: &mut bool, layers: &mut Layers) -> bool {
+        let ui = &self.imgui_ui;
+        let mut changed = false;
+
+        if !*layers_window_open {
+            return changed;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Layers"))
+            .opened(layers_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    10.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    10.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "LAYERS\n\
+                        \n\
+                        Named groups of operations, whose geometry is shown, colored and \
+                        locked together. Operations are assigned to layers in the \
+                        Operation pipeline.\n\
+                        \n\
+                        Hidden layers are not drawn in the viewport, but are still exported. \
+                        Labels of the values on a layer can be turned off separately. \
+                        Colored layers draw the geometry without a display material of its \
+                        own in the layer color. Parameters of operations on locked layers \
+                        can not be changed.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                if ui.button(imgui::im_str!("Add layer"), [0.0, 0.0]) {
+                    layers.add(Layer::new(layers.suggest_name()));
+                    changed = true;
+                }
+
+                if layers.is_empty() {
+                    ui.text_disabled(imgui::im_str!("No layers"));
+                }
+
+                let mut layer_to_remove = None;
+                for (layer_id, layer) in layers.iter_mut() {
+                    ui.separator();
+
+                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+                    imstring_buffer.push_str(&layer.name);
+                    ui.set_next_item_width(200.0);
+                    if ui
+                        .input_text(
+                            &imgui::im_str!("##layer-name-{:?}", layer_id),
+                            &mut imstring_buffer,
+                        )
+                        .build()
+                    {
+                        layer.name = imstring_buffer.to_string();
+                        changed = true;
+                    }
+                    imstring_buffer.clear();
+
+                    changed |= ui.checkbox(
+                        &imgui::im_str!("Visible##layer-visible-{:?}", layer_id),
+                        &mut layer.visible,
+                    );
+                    ui.same_line(0.0);
+                    changed |= ui.checkbox(
+                        &imgui::im_str!("Locked##layer-locked-{:?}", layer_id),
+                        &mut layer.locked,
+                    );
+                    ui.same_line(0.0);
+                    changed |= ui.checkbox(
+                        &imgui::im_str!("Labels##layer-labels-{:?}", layer_id),
+                        &mut layer.labels,
+                    );
+                    ui.same_line(0.0);
+                    changed |= ui.checkbox(
+                        &imgui::im_str!("Colored##layer-colored-{:?}", layer_id),
+                        &mut layer.colored,
+                    );
+
+                    if layer.colored {
+                        changed |= imgui::ColorEdit::new(
+                            &imgui::im_str!("Color##layer-color-{:?}", layer_id),
+                            &mut layer.color,
+                        )
+                        .build(ui);
+                    }
+
+                    if ui.button(
+                        &imgui::im_str!("Remove##layer-remove-{:?}", layer_id),
+                        [0.0, 0.0],
+                    ) {
+                        layer_to_remove = Some(layer_id);
+                    }
+                }
+
+                if let Some(layer_id) = layer_to_remove {
+                    layers.remove(layer_id);
+                    changed = true;
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        changed
+    }
+
+    /// Draws the history window for replaying the event log of the session.
+    /// Returns the statements of the past state to restore, if requested.
+    pub fn draw_history_window(
+        &self,
+        current_time: Instant,
+        history_window_open: &mut bool,
+        history_event_index: &mut u32,
+        session: &Session,
+        notifications: &mut Notifications,
+    ) -> Option<Vec<ast::Stmt>> {
+        let ui = &self.imgui_ui;
+        let mut restore_stmts = None;
+
+        if !*history_window_open {
+            return restore_stmts;
+        }
+
+        let events = session.event_log().events();
+        let restore_disabled = session.interpreter_busy();
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("History"))
+            .opened(history_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    10.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    10.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "HISTORY\n\
+                        \n\
+                        Every edit of the Operation pipeline is recorded with the time it \
+                        happened. The record is saved next to the .hurban project file \
+                        and continues across sessions, so the pipeline can be reconstructed \
+                        as it was at any point of the design process.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                if events.is_empty() {
+                    ui.text_disabled(imgui::im_str!("No recorded edits"));
+                    regular_font_token.pop(ui);
+                    return;
+                }
+
+                let last_event_index = cast_u32(events.len() - 1);
+                *history_event_index = (*history_event_index).min(last_event_index);
+
+                ui.set_next_item_width(400.0);
+                imgui::Slider::new(imgui::im_str!("Edit"))
+                    .range(0..=last_event_index)
+                    .display_format(&imgui::im_str!(
+                        "{} of {}",
+                        *history_event_index + 1,
+                        events.len(),
+                    ))
+                    .build(ui, history_event_index);
+
+                let event_index = cast_usize(*history_event_index);
+                let event = &events[event_index];
+                let time = chrono::Local.timestamp_millis(event.timestamp_ms);
+                ui.text(imgui::im_str!("{}", time.format("%Y-%m-%d %H:%M:%S")));
+                ui.text(imgui::im_str!("{}", event.kind));
+
+                ui.separator();
+
+                let stmts = event_log::replay(&events[..=event_index]);
+                let function_table = session.function_table();
+                if stmts.is_empty() {
+                    ui.text_disabled(imgui::im_str!("Empty pipeline"));
+                }
+                for (stmt_index, stmt) in stmts.iter().enumerate() {
+                    let ast::Stmt::VarDecl(var_decl) = stmt;
+                    let func_name = function_table
+                        .get(&var_decl.init_expr().ident())
+                        .map_or("Unknown operation", |func| func.info().name);
+                    ui.text(imgui::im_str!("#{} {}", stmt_index + 1, func_name));
+                }
+
+                ui.separator();
+
+                let restore_button_tokens = if restore_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let restore = ui.button(imgui::im_str!("Restore this state"), [0.0, 0.0]);
+                if let Some((color_token, style_token)) = restore_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "RESTORE THIS STATE\n\
+                        \n\
+                        Replaces the current Operation pipeline with the pipeline as it was \
+                        after the selected edit. The restore is recorded as an edit too, \
+                        so it can be reverted the same way.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if restore && !restore_disabled {
+                    notifications.push(
+                        current_time,
+                        NotificationLevel::Info,
+                        format!(
+                            "Restored the pipeline as of {}.",
+                            time.format("%Y-%m-%d %H:%M:%S")
+                        ),
+                    );
+                    restore_stmts = Some(stmts);
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        restore_stmts
+    }
+
+    /// Draws the clipping box window, where the box can be enabled and
+    /// resized.
+    pub fn draw_clip_box_window(
+        &self,
+        clip_box_window_open: &mut bool,
+        clip_box: &mut ClipBox,
+        scene_bounding_box: &BoundingBox<f32>,
+    ) {
+        let ui = &self.imgui_ui;
+
+        if !*clip_box_window_open {
+            return;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Clipping box"))
+            .opened(clip_box_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    11.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    11.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "CLIPPING BOX\n\
+                        \n\
+                        Hides the geometry outside of the box in the viewport, screenshots \
+                        and shadow studies, so that the inside of dense geometry, such as \
+                        voxel clouds, can be inspected. The geometry itself is not changed \
+                        and is exported whole.\n\
+                        \n\
+                        The hidden geometry still casts shadows. The ground plane is never \
+                        hidden.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                if ui.checkbox(imgui::im_str!("Enabled"), &mut clip_box.enabled)
+                    && clip_box.enabled
+                    && clip_box.is_empty()
+                {
+                    clip_box.fit(scene_bounding_box);
+                }
+
+                imgui::Drag::<f32>::new(imgui::im_str!("Min"))
+                    .speed(DRAG_SPEED)
+                    .build_array(ui, &mut clip_box.min);
+                imgui::Drag::<f32>::new(imgui::im_str!("Max"))
+                    .speed(DRAG_SPEED)
+                    .build_array(ui, &mut clip_box.max);
+
+                if ui.button(imgui::im_str!("Fit to scene"), [0.0, 0.0]) {
+                    clip_box.fit(scene_bounding_box);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "Resizes the box to contain all geometry in the scene.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+    }
+
+    /// Draws the window with camera bookmarks, which can be added from the
+    /// current view, renamed, visited and rendered.
+    pub fn draw_views_window(
+        &self,
+        current_time: Instant,
+        views_window_open: &mut bool,
+        camera_bookmarks: &mut Vec<CameraBookmark>,
+        current_view: &CameraView,
+        notifications: &mut Notifications,
+    ) -> ViewsStatus {
+        let ui = &self.imgui_ui;
+        let mut status = ViewsStatus::default();
+
+        if !*views_window_open {
+            return status;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Views"))
+            .opened(views_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    12.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    12.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "VIEWS\n\
+                        \n\
+                        Named camera views saved in the project. Going to a view places \
+                        the camera to it, updating a view replaces it with the current \
+                        camera.\n\
+                        \n\
+                        Rendering all views saves an image of each view into a chosen \
+                        directory, with the dimensions and transparency set for \
+                        screenshots. The images are numbered in the order of the views.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                if ui.button(imgui::im_str!("Add current view"), [0.0, 0.0]) {
+                    let name = bookmarks::suggest_name(camera_bookmarks);
+                    camera_bookmarks.push(CameraBookmark {
+                        name,
+                        view: *current_view,
+                    });
+                    status.changed = true;
+                }
+
+                ui.same_line(0.0);
+
+                let render_all_disabled = camera_bookmarks.is_empty();
+                let render_all_button_tokens = if render_all_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                let render_all = ui.button(imgui::im_str!("Render all views..."), [0.0, 0.0]);
+                if let Some((color_token, style_token)) = render_all_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                if render_all && !render_all_disabled {
+                    if let Some(path) =
+                        tinyfiledialogs::select_folder_dialog("Render all views", "")
+                    {
+                        notifications.push(
+                            current_time,
+                            NotificationLevel::Info,
+                            format!("Rendering all views to {}", path),
+                        );
+                        status.render_all = Some(PathBuf::from(path));
+                    }
+                }
+
+                if camera_bookmarks.is_empty() {
+                    ui.text_disabled(imgui::im_str!("No views"));
+                }
+
+                let mut bookmark_to_remove = None;
+                for (index, bookmark) in camera_bookmarks.iter_mut().enumerate() {
+                    ui.separator();
+
+                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+                    imstring_buffer.push_str(&bookmark.name);
+                    ui.set_next_item_width(200.0);
+                    if ui
+                        .input_text(
+                            &imgui::im_str!("##view-name-{}", index),
+                            &mut imstring_buffer,
+                        )
+                        .build()
+                    {
+                        bookmark.name = imstring_buffer.to_string();
+                        status.changed = true;
+                    }
+                    imstring_buffer.clear();
+
+                    if ui.button(&imgui::im_str!("Go to##view-go-to-{}", index), [0.0, 0.0]) {
+                        status.go_to = Some(bookmark.view);
+                    }
+                    ui.same_line(0.0);
+                    if ui.button(&imgui::im_str!("Update##view-update-{}", index), [0.0, 0.0]) {
+                        bookmark.view = *current_view;
+                        status.changed = true;
+                    }
+                    ui.same_line(0.0);
+                    if ui.button(&imgui::im_str!("Remove##view-remove-{}", index), [0.0, 0.0]) {
+                        bookmark_to_remove = Some(index);
+                    }
+                }
+
+                if let Some(index) = bookmark_to_remove {
+                    camera_bookmarks.remove(index);
+                    status.changed = true;
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        status
+    }
+
+    /// Draws the preferences window. Returns whether any of the preferences
+    /// changed.
+    pub fn draw_preferences_window(
+        &self,
+        preferences_window_open: &mut bool,
+        preferences: &mut Preferences,
+        project_unit: Unit,
+        statistics_window_open: &mut bool,
+        gpu_adapters: &[GpuAdapterInfo],
+    ) -> bool {
+        let ui = &self.imgui_ui;
+        let mut changed = false;
+
+        if !*preferences_window_open {
+            return changed;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Preferences"))
+            .opened(preferences_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    7.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    7.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "PREFERENCES\n\
+                        \n\
+                        Settings that apply to all projects. They are saved when this window \
+                        is closed.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                let combo_box_color_token = self.push_combo_box_colors();
+                if let Some(combo_token) = imgui::ComboBox::new(imgui::im_str!("Number format"))
+                    .preview_value(&imgui::im_str!("{}", preferences.number_format))
+                    .begin(ui)
+                {
+                    for number_format in &NumberFormat::ALL {
+                        if imgui::Selectable::new(&imgui::im_str!("{}", number_format))
+                            .selected(preferences.number_format == *number_format)
+                            .build(ui)
+                            && preferences.number_format != *number_format
+                        {
+                            preferences.number_format = *number_format;
+                            changed = true;
+                        }
+                    }
+
+                    combo_token.end(ui);
+                }
+                combo_box_color_token.pop(ui);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "NUMBER FORMAT\n\
+                        \n\
+                        How parameters and measurements are displayed. With a decimal comma, \
+                        numbers are typed as 0,5 and parameters with multiple values are \
+                        separated by semicolons, e.g. 0,5; 1. Decimal points are accepted \
+                        in any format.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                ui.separator();
+
+                let snapping = &mut preferences.snapping;
+
+                changed |= ui.checkbox(
+                    imgui::im_str!("Snap transform values"),
+                    &mut snapping.enabled,
+                );
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "SNAP TRANSFORM VALUES\n\
+                        \n\
+                        When dragging or entering positions, movements and rotations \
+                        of operations, the values snap to multiples of the increments below.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let snapping_disabled_tokens = if snapping.enabled {
+                    None
+                } else {
+                    Some(push_disabled_style(ui))
+                };
+
+                changed |= imgui::Drag::<f32>::new(&imgui::im_str!(
+                    "Distance increment ({})",
+                    project_unit.abbreviation(),
+                ))
+                .speed(DRAG_SPEED)
+                .range(0.001..)
+                .build(ui, &mut snapping.distance);
+
+                changed |= imgui::Drag::<f32>::new(imgui::im_str!("Angle increment (deg)"))
+                    .range(0.1..=180.0)
+                    .build(ui, &mut snapping.angle);
+
+                if let Some((color_token, style_token)) = snapping_disabled_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                ui.separator();
+
+                let autorun = &mut preferences.autorun;
+
+                changed |= ui.checkbox(imgui::im_str!("Run automatically"), &mut autorun.enabled);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "RUN AUTOMATICALLY\n\
+                        \n\
+                        Runs the Operation pipeline whenever an operation or a parameter \
+                        changes. Otherwise the pipeline only runs when the Run button, \
+                        Enter or F5 is pressed, and operations with out-of-date values \
+                        are marked as not run.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let autorun_disabled_tokens = if autorun.enabled {
+                    None
+                } else {
+                    Some(push_disabled_style(ui))
+                };
+
+                changed |= imgui::Drag::<u32>::new(imgui::im_str!("Autorun delay (ms)"))
+                    .range(0..=5000)
+                    .speed(10.0)
+                    .build(ui, &mut autorun.delay_ms);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "AUTORUN DELAY\n\
+                        \n\
+                        How long to wait after the last change before running the \
+                        Operation pipeline automatically. Longer delays let you finish \
+                        typing parameter values before the pipeline runs.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if let Some((color_token, style_token)) = autorun_disabled_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                ui.separator();
+
+                let timeout = &mut preferences.timeout;
+
+                changed |= ui.checkbox(
+                    imgui::im_str!("Abandon long-running operations"),
+                    &mut timeout.enabled,
+                );
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "ABANDON LONG-RUNNING OPERATIONS\n\
+                        \n\
+                        Stops waiting for an operation that runs longer than the timeout, \
+                        e.g. voxelization with a very fine resolution, and reports an \
+                        error, so that the pipeline can be edited and run again. The \
+                        abandoned operation keeps using the processor in the background \
+                        until it finishes.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let timeout_disabled_tokens = if timeout.enabled {
+                    None
+                } else {
+                    Some(push_disabled_style(ui))
+                };
+
+                changed |= imgui::Drag::<u32>::new(imgui::im_str!("Operation timeout (s)"))
+                    .range(1..=3600)
+                    .build(ui, &mut timeout.timeout_secs);
+
+                if let Some((color_token, style_token)) = timeout_disabled_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                ui.separator();
+
+                let frame_limiter = &mut preferences.frame_limiter;
+
+                changed |= ui.checkbox(
+                    imgui::im_str!("Save power when idle"),
+                    &mut frame_limiter.idle_enabled,
+                );
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "SAVE POWER WHEN IDLE\n\
+                        \n\
+                        When there is no input and nothing is running or animating, \
+                        the viewport is redrawn only a few times per second, saving \
+                        CPU, GPU and battery.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let idle_disabled_tokens = if frame_limiter.idle_enabled {
+                    None
+                } else {
+                    Some(push_disabled_style(ui))
+                };
+
+                changed |= imgui::Drag::<u32>::new(imgui::im_str!("Idle frame rate (FPS)"))
+                    .range(1..=30)
+                    .build(ui, &mut frame_limiter.idle_fps);
+
+                if let Some((color_token, style_token)) = idle_disabled_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                changed |= imgui::Drag::<u32>::new(imgui::im_str!("Frame rate limit (FPS)"))
+                    .range(0..=240)
+                    .display_format(if frame_limiter.max_fps == 0 {
+                        imgui::im_str!("Unlimited")
+                    } else {
+                        imgui::im_str!("%u")
+                    })
+                    .build(ui, &mut frame_limiter.max_fps);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "FRAME RATE LIMIT\n\
+                        \n\
+                        Maximum frame rate while the application is active. \
+                        Zero means the frame rate is only limited by the display.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                ui.separator();
+
+                let combo_box_color_token = self.push_combo_box_colors();
+                if let Some(combo_token) = imgui::ComboBox::new(imgui::im_str!("Shadow quality"))
+                    .preview_value(&imgui::im_str!("{}", preferences.shadow_quality))
+                    .begin(ui)
+                {
+                    for shadow_quality in &ShadowQuality::ALL {
+                        if imgui::Selectable::new(&imgui::im_str!("{}", shadow_quality))
+                            .selected(preferences.shadow_quality == *shadow_quality)
+                            .build(ui)
+                            && preferences.shadow_quality != *shadow_quality
+                        {
+                            preferences.shadow_quality = *shadow_quality;
+                            changed = true;
+                        }
+                    }
+
+                    combo_token.end(ui);
+                }
+                combo_box_color_token.pop(ui);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "SHADOW QUALITY\n\
+                        \n\
+                        Resolution of the shadows. Shadows close to the camera are \
+                        sharper than distant ones. Higher quality takes more GPU memory.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                match &preferences.matcap {
+                    Some(path) => {
+                        let file_name = path
+                            .file_name()
+                            .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+                        ui.text(imgui::im_str!("Matcap: {}", file_name));
+                        if ui.is_item_hovered() {
+                            ui.tooltip(|| {
+                                ui.text_colored(self.colors.tooltip_text, path.to_string_lossy());
+                            });
+                        }
+                    }
+                    None => ui.text(imgui::im_str!("Matcap: Built-in")),
+                }
+
+                if ui.button(imgui::im_str!("Load matcap..."), [0.0, 0.0]) {
+                    // FIXME: @Refactoring Factor out this use of
+                    // tinyfiledialogs from this module
+                    if let Some(path) = tinyfiledialogs::open_file_dialog(
+                        "Load matcap",
+                        "",
+                        Some((&["*.png", "*.jpg", "*.jpeg"], "Images (*.png, *.jpg)")),
+                    ) {
+                        preferences.matcap = Some(PathBuf::from(path));
+                        changed = true;
+                    }
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "LOAD MATCAP\n\
+                        \n\
+                        Opens a system dialog for choosing an image of a lit sphere, which \
+                        replaces the built-in matcap of shaded surfaces. Matcaps baked \
+                        in the Sun window can be loaded here.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                ui.same_line(0.0);
+
+                let reset_matcap_disabled = preferences.matcap.is_none();
+                let reset_matcap_tokens = if reset_matcap_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+                if ui.button(imgui::im_str!("Reset matcap"), [0.0, 0.0]) && !reset_matcap_disabled {
+                    preferences.matcap = None;
+                    changed = true;
+                }
+                if let Some((color_token, style_token)) = reset_matcap_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                ui.separator();
+
+                let ground_plane = &mut preferences.ground_plane;
+
+                changed |= ui.checkbox(
+                    imgui::im_str!("Show ground plane"),
+                    &mut ground_plane.visible,
+                );
+
+                let ground_plane_disabled_tokens = if ground_plane.visible {
+                    None
+                } else {
+                    Some(push_disabled_style(ui))
+                };
+
+                changed |= ui.checkbox(
+                    imgui::im_str!("Shadow catcher only"),
+                    &mut ground_plane.shadow_catcher_only,
+                );
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "SHADOW CATCHER ONLY\n\
+                        \n\
+                        The ground plane itself is invisible, only the shadows cast \
+                        on it are drawn.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                changed |= ui.checkbox(
+                    imgui::im_str!("Size ground plane automatically"),
+                    &mut ground_plane.auto_extent,
+                );
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "SIZE GROUND PLANE AUTOMATICALLY\n\
+                        \n\
+                        The ground plane grows with the scene. Otherwise it has \
+                        the fixed size below.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let fixed_extent_disabled_tokens = if ground_plane.auto_extent {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+
+                changed |= imgui::Drag::<f32>::new(&imgui::im_str!(
+                    "Ground plane size ({})",
+                    project_unit.abbreviation(),
+                ))
+                .speed(DRAG_SPEED)
+                .range(0.001..)
+                .build(ui, &mut ground_plane.fixed_extent);
+
+                if let Some((color_token, style_token)) = fixed_extent_disabled_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                changed |= imgui::ColorEdit::new(
+                    imgui::im_str!("Ground plane color"),
+                    &mut ground_plane.color,
+                )
+                .build(ui);
+
+                if let Some((color_token, style_token)) = ground_plane_disabled_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                ui.separator();
+
+                let mut keep_statistics = preferences.statistics == StatisticsConsent::Granted;
+                if ui.checkbox(
+                    imgui::im_str!("Keep usage statistics"),
+                    &mut keep_statistics,
+                ) {
+                    preferences.statistics = if keep_statistics {
+                        StatisticsConsent::Granted
+                    } else {
+                        StatisticsConsent::Denied
+                    };
+                    changed = true;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "KEEP USAGE STATISTICS\n\
+                        \n\
+                        Counts sessions, projects, pipeline runs and the operations used. \
+                        The statistics are kept only on this computer and are never sent \
+                        anywhere. Turning them off deletes the recorded statistics.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.button(imgui::im_str!("Show statistics..."), [0.0, 0.0]) {
+                    *statistics_window_open = true;
+                }
+
+                ui.separator();
+
+                changed |= ui.checkbox(
+                    imgui::im_str!("Git-friendly project files"),
+                    &mut preferences.project_files.git_friendly,
+                );
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "GIT-FRIENDLY PROJECT FILES\n\
+                        \n\
+                        Saves .hurban project files so that they diff cleanly under version \
+                        control, e.g. for teams tracking design iterations in git. Float \
+                        parameters of the operations are rounded to 6 significant digits, \
+                        removing noise such as 0.30000004 left by dragging.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                ui.separator();
+
+                let logging = &mut preferences.logging;
+
+                changed |= ui.checkbox(imgui::im_str!("Write log files"), &mut logging.to_file);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let log_directory = logger::log_directory().map_or_else(
+                            || String::from("<unavailable>"),
+                            |path| path.to_string_lossy().into_owned(),
+                        );
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            format!(
+                                "WRITE LOG FILES\n\
+                                 \n\
+                                 Writes the log to files in {}. The files are rotated \
+                                 once they grow large and only the most recent ones are kept.\n\
+                                 \n\
+                                 Logging changes take effect after restart.",
+                                log_directory,
+                            ),
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                ui.text("Module log levels");
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "MODULE LOG LEVELS\n\
+                        \n\
+                        Overrides the log level of a module and its submodules, \
+                        e.g. 'hurban_selector::renderer' or 'wgpu_core'.\n\
+                        \n\
+                        Logging changes take effect after restart.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let mut filter_to_remove = None;
+                for (index, filter) in logging.module_filters.iter_mut().enumerate() {
+                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+                    imstring_buffer.push_str(&filter.module);
+                    ui.set_next_item_width(200.0);
+                    if ui
+                        .input_text(
+                            &imgui::im_str!("##log-filter-module-{}", index),
+                            &mut imstring_buffer,
+                        )
+                        .build()
+                    {
+                        filter.module = imstring_buffer.to_string();
+                        changed = true;
+                    }
+                    imstring_buffer.clear();
+
+                    ui.same_line(0.0);
+                    ui.set_next_item_width(80.0);
+                    let combo_box_color_token = self.push_combo_box_colors();
+                    if let Some(combo_token) =
+                        imgui::ComboBox::new(&imgui::im_str!("##log-filter-level-{}", index))
+                            .preview_value(&imgui::ImString::new(filter.level.name()))
+                            .begin(ui)
+                    {
+                        for level in &LogLevel::ALL {
+                            if imgui::Selectable::new(&imgui::ImString::new(level.name()))
+                                .selected(filter.level == *level)
+                                .build(ui)
+                                && filter.level != *level
+                            {
+                                filter.level = *level;
+                                changed = true;
+                            }
+                        }
+
+                        combo_token.end(ui);
+                    }
+                    combo_box_color_token.pop(ui);
+
+                    ui.same_line(0.0);
+                    if ui.button(&imgui::im_str!("Remove##log-filter-{}", index), [0.0, 0.0]) {
+                        filter_to_remove = Some(index);
+                    }
+                }
+
+                if let Some(index) = filter_to_remove {
+                    logging.module_filters.remove(index);
+                    changed = true;
+                }
+
+                if ui.button(imgui::im_str!("Add module level"), [0.0, 0.0]) {
+                    logging.module_filters.push(ModuleFilter {
+                        module: String::from("hurban_selector"),
+                        level: LogLevel::Debug,
+                    });
+                    changed = true;
+                }
+
+                ui.separator();
+
+                ui.text("GPU adapter");
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "GPU ADAPTER\n\
+                        \n\
+                        The GPU used for rendering. Automatic picks the GPU based on \
+                        the power preference given on the command line. On laptops \
+                        with two GPUs, choosing one explicitly may avoid driver issues.\n\
+                        \n\
+                        The GPU adapter change takes effect after restart.",
+                        );
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if ui.radio_button_bool(
+                    imgui::im_str!("Automatic"),
+                    preferences.gpu_adapter.is_none(),
+                ) {
+                    preferences.gpu_adapter = None;
+                    changed = true;
+                }
+
+                for (index, gpu_adapter) in gpu_adapters.iter().enumerate() {
+                    let selected = preferences.gpu_adapter.as_ref() == Some(gpu_adapter);
+                    if ui.radio_button_bool(
+                        &imgui::im_str!("{}##gpu-adapter-{}", gpu_adapter, index),
+                        selected,
+                    ) && !selected
+                    {
+                        preferences.gpu_adapter = Some(gpu_adapter.clone());
+                        changed = true;
+                    }
+                }
+
+                if let Some(gpu_adapter) = &preferences.gpu_adapter {
+                    if !gpu_adapters.contains(gpu_adapter) {
+                        ui.text_disabled(imgui::im_str!(
+                            "Chosen adapter not available: {}",
+                            gpu_adapter,
+                        ));
+                    }
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        changed
+    }
+
+    /// Draws the usage statistics window. Returns whether the user requested
+    /// to clear the statistics.
+    pub fn draw_statistics_window(
+        &self,
+        statistics_window_open: &mut bool,
+        statistics: Option<&Statistics>,
+    ) -> bool {
+        let ui = &self.imgui_ui;
+        let mut clear = false;
+
+        if !*statistics_window_open {
+            return clear;
+        }
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Usage statistics"))
+            .opened(statistics_window_open)
+            .collapsible(false)
+            .always_auto_resize(true)
+            .position(
+                [
+                    10.0 * MARGIN + OPERATIONS_WINDOW_WIDTH,
+                    10.0 * MARGIN + MENU_WINDOW_HEIGHT,
+                ],
+                imgui::Condition::FirstUseEver,
+            )
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(
+                            self.colors.tooltip_text,
+                            "USAGE STATISTICS\n\
+                        \n\
+                        Everything the application recorded about its use. The statistics \
+                        are kept only on this computer and are never sent anywhere. No \
+                        paths, geometry or parameter values are recorded.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                match statistics {
+                    Some(statistics) => {
+                        ui.text(format!("Sessions: {}", statistics.sessions));
+                        ui.text(format!("Projects created: {}", statistics.projects_created));
+                        ui.text(format!("Projects opened: {}", statistics.projects_opened));
+                        ui.text(format!("Projects saved: {}", statistics.projects_saved));
+                        ui.text(format!("Pipeline runs: {}", statistics.pipeline_runs));
+                        if let Some(average_ms) = statistics.pipeline_runtime_average_ms() {
+                            ui.text(format!("Average pipeline runtime: {:.0} ms", average_ms));
+                            ui.text(format!(
+                                "Longest pipeline runtime: {:.0} ms",
+                                statistics.pipeline_runtime_max_ms,
+                            ));
+                        }
+
+                        ui.separator();
+
+                        let operations = statistics.operations_by_count();
+                        if operations.is_empty() {
+                            ui.text_disabled(imgui::im_str!("No operations used yet."));
+                        } else {
+                            ui.text("Operations (pipeline runs using them):");
+                            for (name, count) in operations {
+                                ui.bullet_text(&imgui::im_str!("{}: {}", name, count));
+                            }
+                        }
+
+                        ui.separator();
+
+                        if ui.button(imgui::im_str!("Clear statistics"), [0.0, 0.0]) {
+                            clear = true;
+                        }
+                    }
+                    None => {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text(
+                            "Usage statistics are not kept. They can be turned on in the \
+                             preferences.",
+                        );
+                        wrap_token.pop(ui);
+                    }
+                }
+
+                regular_font_token.pop(ui);
+            });
+        bold_font_token.pop(ui);
+
+        clear
+    }
+
+    /// Draws a combo box for selecting one of the numeric parameters of the
+    /// pipeline. Returns whether the selection changed.
+    fn draw_numeric_param_combo(
+        &self,
+        label: &imgui::ImStr,
+        params: &[(ParamRef, imgui::ImString)],
+        selected: &mut Option<ParamRef>,
+    ) -> bool {
+        let ui = &self.imgui_ui;
+        let mut changed = false;
+
+        let preview_value = selected
+            .and_then(|param| params.iter().find(|(p, _)| *p == param))
+            .map(|(_, label)| label.clone())
+            .unwrap_or_else(|| imgui::ImString::new("<Select one option>"));
+
+        let combo_box_color_token = self.push_combo_box_colors();
+        if let Some(combo_token) = imgui::ComboBox::new(label)
+            .preview_value(&preview_value)
+            .begin(ui)
+        {
+            for (param, label) in params {
+                if imgui::Selectable::new(label)
+                    .selected(*selected == Some(*param))
+                    .build(ui)
+                {
+                    changed = *selected != Some(*param);
+                    *selected = Some(*param);
+                }
+            }
+
+            combo_token.end(ui);
+        }
+        combo_box_color_token.pop(ui);
+
+        changed
+    }
+
+    fn push_combo_box_colors(&self) -> imgui::ColorStackToken {
+        self.imgui_ui.push_style_colors(&[
+            (
+                imgui::StyleColor::Header,
+                self.colors.combo_box_selected_item,
+            ),
+            (
+                imgui::StyleColor::HeaderHovered,
+                self.colors.combo_box_selected_item_hovered,
+            ),
+            (
+                imgui::StyleColor::HeaderActive,
+                self.colors.combo_box_selected_item_active,
+            ),
+            (
+                imgui::StyleColor::PopupBg,
+                self.colors.popup_window_background,
+            ),
+        ])
+    }
+
+    pub fn draw_error_modal(&self, project_error: &Option<project::ProjectError>) -> bool {
+        let ui = &self.imgui_ui;
+        let mut modal_closed = false;
+
+        ui.open_popup(imgui::im_str!("Error"));
+        ui.popup_modal(imgui::im_str!("Error"))
+            .resizable(false)
+            .build(|| {
+                let error_message = project_error
+                    .clone()
+                    .expect("Failed to read project error.")
+                    .to_string();
+
+                ui.text(error_message);
+
+                if ui.button(imgui::im_str!("OK"), [0.0, 0.0]) {
+                    modal_closed = true;
+
+                    ui.close_current_popup();
+                }
+            });
+
+        modal_closed
+    }
+
+    pub fn draw_prevent_overwrite_modal(&self) -> SaveModalResult {
+        let ui = &self.imgui_ui;
+        let mut save_modal_result = SaveModalResult::Nothing;
+        let window_color_token = ui.push_style_color(
+            imgui::StyleColor::PopupBg,
+            self.colors.popup_window_background,
+        );
+        ui.open_popup(imgui::im_str!("Unsaved changes"));
+        ui.popup_modal(imgui::im_str!("Unsaved changes"))
+            .resizable(false)
+            .build(|| {
+                ui.text("To preserve unsaved changes in the pipeline please save the project.");
+
+                let width_unit = ui.window_size()[0] / 11.0;
+
+                if ui.button(imgui::im_str!("Save"), [width_unit * 3.0, 0.0]) {
+                    save_modal_result = SaveModalResult::Save;
+
+                    ui.close_current_popup();
+                }
+
+                ui.same_line(width_unit * 4.0);
+
+                if ui.button(imgui::im_str!("Discard changes"), [width_unit * 3.0, 0.0]) {
+                    save_modal_result = SaveModalResult::DontSave;
+
+                    ui.close_current_popup();
+                }
+
+                ui.same_line(width_unit * 8.0);
+
+                if ui.button(imgui::im_str!("Cancel"), [width_unit * 3.0, 0.0]) {
+                    save_modal_result = SaveModalResult::Cancel;
+
+                    ui.close_current_popup();
+                }
+            });
+
+        window_color_token.pop(ui);
+
+        save_modal_result
+    }
+
+    pub fn draw_unit_conversion_modal(
+        &self,
+        file_name: &str,
+        declared_unit: Unit,
+        project_unit: Unit,
+    ) -> UnitConversionModalResult {
+        let ui = &self.imgui_ui;
+        let mut unit_conversion_modal_result = UnitConversionModalResult::Nothing;
+        let window_color_token = ui.push_style_color(
+            imgui::StyleColor::PopupBg,
+            self.colors.popup_window_background,
+        );
+        ui.open_popup(imgui::im_str!("Different units"));
+        ui.popup_modal(imgui::im_str!("Different units"))
+            .resizable(false)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(format!(
+                    "The imported file {} declares its units as {}, \
+                     but the project uses {}.",
+                    file_name,
+                    declared_unit.name().to_lowercase(),
+                    project_unit.name().to_lowercase(),
+                ));
+                ui.text(format!(
+                    "Convert the imported geometry to {} (scale by {})?",
+                    project_unit.name().to_lowercase(),
+                    declared_unit.conversion_factor(project_unit),
+                ));
+
+                if ui.button(imgui::im_str!("Convert"), [120.0, 0.0]) {
+                    unit_conversion_modal_result = UnitConversionModalResult::Convert;
+
+                    ui.close_current_popup();
+                }
+
+                ui.same_line(0.0);
+
+                if ui.button(imgui::im_str!("Keep as is"), [120.0, 0.0]) {
+                    unit_conversion_modal_result = UnitConversionModalResult::Keep;
+
+                    ui.close_current_popup();
+                }
+            });
+
+        window_color_token.pop(ui);
+
+        unit_conversion_modal_result
+    }
+
+    /// Draws the modal listing the values about to be exported, letting the
+    /// user exclude some of them before choosing where to export.
+    pub fn draw_export_preview_modal(
+        &self,
+        export_preview: &mut ExportPreview,
+        decimal_precision: u32,
+        unit: Unit,
+    ) -> ExportPreviewModalResult {
+        let ui = &self.imgui_ui;
+        let mut export_preview_modal_result = ExportPreviewModalResult::Nothing;
+        let window_color_token = ui.push_style_color(
+            imgui::StyleColor::PopupBg,
+            self.colors.popup_window_background,
+        );
+        ui.open_popup(imgui::im_str!("Export OBJ"));
+        ui.popup_modal(imgui::im_str!("Export OBJ"))
+            .resizable(false)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text("The following values will be exported:");
+
+                if export_preview.has_used_values() {
+                    ui.checkbox(
+                        imgui::im_str!("Include used values"),
+                        &mut export_preview.include_used_values,
+                    );
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(
+                                self.colors.tooltip_text,
+                                "INCLUDE USED VALUES\n\
+                                 \n\
+                                 Also exports the values used by later operations, \
+                                 i.e. the intermediate states of the pipeline. \
+                                 Their names are prefixed with \"used_\".",
+                            );
+                            wrap_token.pop(ui);
+                        });
+                    }
+                }
+
+                ui.separator();
+
+                ui.columns(3, imgui::im_str!("Export preview columns"), false);
+                for (index, value) in export_preview.values_mut().enumerate() {
+                    ui.checkbox(
+                        &imgui::im_str!("{}##export_value_{}", value.name, index),
+                        &mut value.included,
+                    );
+                    ui.next_column();
+
+                    ui.text(format!("{} triangles", value.triangle_count));
+                    ui.next_column();
+
+                    if value.watertight {
+                        ui.text("Watertight");
+                    } else {
+                        ui.text_colored(self.colors.log_message_warn, "Not watertight");
+                        if ui.is_item_hovered() {
+                            ui.tooltip(|| {
+                                let wrap_token =
+                                    ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                ui.text_colored(
+                                    self.colors.tooltip_text,
+                                    "NOT WATERTIGHT\n\
+                                     \n\
+                                     The mesh has holes or non-manifold edges. \
+                                     It may be rejected by 3D printing or analysis tools.",
+                                );
+                                wrap_token.pop(ui);
+                            });
+                        }
+                    }
+                    ui.next_column();
+                }
+                ui.columns(1, imgui::im_str!("Export preview columns end"), false);
+
+                ui.separator();
+
+                let triangle_count = export_preview.included_triangle_count();
+                ui.text(format!("Triangles: {}", triangle_count));
+                ui.text(format!(
+                    "Estimated file size: {}",
+                    export_preview::format_file_size(
+                        export_preview.estimated_obj_size(decimal_precision, Some(unit)),
+                    ),
+                ));
+
+                let non_watertight_count = export_preview.included_non_watertight_count();
+                if non_watertight_count > 0 {
+                    ui.text_colored(
+                        self.colors.log_message_warn,
+                        format!(
+                            "{} of the exported values {} not watertight.",
+                            non_watertight_count,
+                            if non_watertight_count == 1 {
+                                "is"
+                            } else {
+                                "are"
+                            },
+                        ),
+                    );
+                }
+
+                let export_disabled = !export_preview.has_included_values();
+                let export_button_tokens = if export_disabled {
+                    Some(push_disabled_style(ui))
+                } else {
+                    None
+                };
+
+                if ui.button(imgui::im_str!("Export..."), [120.0, 0.0]) && !export_disabled {
+                    export_preview_modal_result = ExportPreviewModalResult::Export;
+
+                    ui.close_current_popup();
+                }
+
+                if let Some((color_token, style_token)) = export_button_tokens {
+                    color_token.pop(ui);
+                    style_token.pop(ui);
+                }
+
+                ui.same_line(0.0);
+
+                if ui.button(imgui::im_str!("Cancel"), [120.0, 0.0]) {
+                    export_preview_modal_result = ExportPreviewModalResult::Cancel;
+
+                    ui.close_current_popup();
+                }
+            });
+
+        window_color_token.pop(ui);
+
+        export_preview_modal_result
+    }
+
+    /// Draws the modal telling that the opened example is read-only and
+    /// offering to save a copy of it.
+    pub fn draw_example_modal(&self, example: &ExampleProject) -> ExampleModalResult {
+        let ui = &self.imgui_ui;
+        let mut example_modal_result = ExampleModalResult::Nothing;
+        let window_color_token = ui.push_style_color(
+            imgui::StyleColor::PopupBg,
+            self.colors.popup_window_background,
+        );
+        ui.open_popup(imgui::im_str!("Example project"));
+        ui.popup_modal(imgui::im_str!("Example project"))
+            .resizable(false)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text(imgui::im_str!("Opened the example {}.", example.name));
+                ui.text(example.description);
+                ui.text(
+                    "Examples are read-only. Save a copy of the example \
+                     to keep the changes made to it.",
+                );
+
+                if ui.button(imgui::im_str!("Save a copy..."), [120.0, 0.0]) {
+                    // FIXME: @Refactoring Factor out this use of
+                    // tinyfiledialogs from this module
+                    if let Some(path) = tinyfiledialogs::save_file_dialog_with_filter(
+                        "Save a copy",
+                        project::DEFAULT_NEW_FILENAME,
+                        project::EXTENSION_FILTER,
+                        project::EXTENSION_DESCRIPTION,
+                    ) {
+                        example_modal_result = ExampleModalResult::SaveCopy(PathBuf::from(path));
+
+                        ui.close_current_popup();
+                    }
+                }
+
+                ui.same_line(0.0);
+
+                if ui.button(imgui::im_str!("Explore"), [120.0, 0.0]) {
+                    example_modal_result = ExampleModalResult::Explore;
+
+                    ui.close_current_popup();
+                }
+            });
+
+        window_color_token.pop(ui);
+
+        example_modal_result
+    }
+
+    /// Draws the modal offering the crash bundle written when the application
+    /// crashed the last time.
+    pub fn draw_crash_modal(&self, bundle_path: &Path) -> CrashModalResult {
+        let ui = &self.imgui_ui;
+        let mut crash_modal_result = CrashModalResult::Nothing;
+        let window_color_token = ui.push_style_color(
+            imgui::StyleColor::PopupBg,
+            self.colors.popup_window_background,
+        );
+        ui.open_popup(imgui::im_str!("Crash report"));
+        ui.popup_modal(imgui::im_str!("Crash report"))
+            .resizable(false)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text("H.U.R.B.A.N. selector crashed the last time it was running.");
+                ui.text(
+                    "A crash bundle with the crash report, the recent log \
+                     and the last autosaved project was written to:",
+                );
+                ui.text(bundle_path.to_string_lossy());
+                ui.text("Please attach the bundle or the report to a bug report.");
+
+                if ui.button(imgui::im_str!("Copy report"), [120.0, 0.0]) {
+                    crash_modal_result = CrashModalResult::CopyReport;
+                }
+
+                ui.same_line(0.0);
+
+                if ui.button(imgui::im_str!("Save bundle..."), [120.0, 0.0]) {
+                    if let Some(path) =
+                        tinyfiledialogs::select_folder_dialog("Save crash bundle", "")
+                    {
+                        crash_modal_result = CrashModalResult::SaveTo(PathBuf::from(path));
+
+                        ui.close_current_popup();
+                    }
+                }
+
+                ui.same_line(0.0);
+
+                if ui.button(imgui::im_str!("Dismiss"), [120.0, 0.0]) {
+                    crash_modal_result = CrashModalResult::Dismiss;
+
+                    ui.close_current_popup();
+                }
+            });
+
+        window_color_token.pop(ui);
+
+        crash_modal_result
+    }
+
+    /// Draws the modal asking whether to keep usage statistics. Returns the
+    /// decision, once the user makes it.
+    pub fn draw_statistics_consent_modal(&self) -> Option<bool> {
+        let ui = &self.imgui_ui;
+        let mut consent = None;
+        let window_color_token = ui.push_style_color(
+            imgui::StyleColor::PopupBg,
+            self.colors.popup_window_background,
+        );
+        ui.open_popup(imgui::im_str!("Usage statistics"));
+        ui.popup_modal(imgui::im_str!("Usage statistics"))
+            .resizable(false)
+            .always_auto_resize(true)
+            .build(|| {
+                ui.text("Would you like to keep statistics of how you use the application?");
+                ui.text("They count sessions, projects, pipeline runs and the operations used.");
+                ui.text(
+                    "The statistics stay on this computer, are never sent anywhere \
+                     and can be viewed or cleared in the preferences at any time.",
+                );
+
+                if ui.button(imgui::im_str!("Keep"), [120.0, 0.0]) {
+                    consent = Some(true);
+
+                    ui.close_current_popup();
+                }
+
+                ui.same_line(0.0);
+
+                if ui.button(imgui::im_str!("Don't keep"), [120.0, 0.0]) {
+                    consent = Some(false);
+
+                    ui.close_current_popup();
+                }
+            });
+
+        window_color_token.pop(ui);
+
+        consent
+    }
+
+    // FIXME: @Refactoring Refactor this once we have full-featured
+    // functionality. Until then, this is exploratory code and we
+    // don't care.
+    #[allow(clippy::cognitive_complexity)]
+    pub fn draw_pipeline_window(
+        &self,
+        current_time: Instant,
+        session: &mut Session,
+        project_unit: Unit,
+        snapping: Snapping,
+        number_format: NumberFormat,
+        param_defaults: &mut ParamDefaults,
+    ) -> PipelineStatus {
+        let ui = &self.imgui_ui;
+        self.console_state
+            .borrow_mut()
+            .resize_with(session.stmts().len(), Default::default);
+
+        if !ui.is_any_item_active() {
+            self.pipeline_window_state.borrow_mut().unsnapped_drag = None;
+        }
+
+        let function_table = session.function_table();
+
+        let window_logical_size = ui.io().display_size;
+        let window_inner_height = window_logical_size[1] - 2.0 * MARGIN;
+
+        let pipeline_window_height = window_inner_height * PIPELINE_WINDOW_HEIGHT_MULT;
+
+        let interpreter_busy = session.interpreter_busy();
+        let mut change = None;
+        let mut args_change = None;
+        let mut param_link_change = None;
+        let mut copy_as_obj = None;
+        let mut pinned_changed = None;
+        let mut live_link_changed = None;
+        let mut voxel_size_changed = None;
+        let mut voxel_size_inheritance_changed = None;
+        let mut display_material_changed = None;
+        let mut layer_changed = None;
+        let mut focus_changed = None;
+        let mut var_name_changed = None;
+        let mut value_label_changed = None;
+        let mut stmt_note_changed = None;
+        let mut notes_changed = None;
+
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        imgui::Window::new(imgui::im_str!("Operation pipeline"))
+            .movable(false)
+            .resizable(false)
+            .collapsible(false)
+            .size([PIPELINE_WINDOW_WIDTH, pipeline_window_height], imgui::Condition::Always)
+            .position([MARGIN, MARGIN], imgui::Condition::Always)
+            .build(ui, || {
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        let regular_font_token = ui.push_font(self.font_ids.regular);
+                        ui.text_colored(self.colors.tooltip_text,
+                            "OPERATION PIPELINE\n\
+                            \n\
+                            An ordered sequence of operations that generate the viewport geometry. \
+                            When the pipeline is run, the operations are being executed one after \
+                            another from top down. Each operation can be customized by setting the \
+                            input parameter values or specifying the input data (mesh geometry, \
+                            mesh group, path to file).\n\
+                            \n\
+                            Each operation in the pipeline generates data: either a mesh geometry or \
+                            a mesh group which can be later used in a subsequent operation. Only unused \
+                            (freshly generated) geometry (mesh or group) is rendered in the viewport \
+                            by default, \
+                            however even the geometry, which has been already used, can be reused in \
+                            subsequent operations. Operations can take as an input only that geometry, \
+                            which has been generated in the preceding operations in the pipeline.\n\
+                            \n\
+                            It is possible to change any input parameters of any operation at any time, \
+                            not only after the operation has been added to the pipeline. \
+                            This is useful when some parameters need to be adjusted only after the results \
+                            of subsequent operations can be visually evaluated. This approach is a gateway \
+                            to full-fledged parametric modeling paradigm.\n\
+                            \n\
+                            The .hurban project files can be build as a tool for achieving certain manipulations \
+                            with mesh geometry and because they don't contain the imported mesh models, \
+                            one sequence of operations (one project file) can be reused for various input mesh \
+                            models. Hence, H.U.R.B.A.N. selector is not only a geometry transformation tool, \
+                            but also a tool-building platform and the project files ara not only geometries but \
+                            also tools.\n\
+                            \n\
+                            Numeric parameters can also be typed as math expressions, e.g. 12*0.45+3 \
+                            or sqrt(2). Double-click or ctrl+click the parameter, type the expression \
+                            and press enter. Parameters with multiple values take one expression per \
+                            value, separated by commas, or by semicolons if numbers are typed with \
+                            a decimal comma.\n\
+                            \n\
+                            Right-click a numeric parameter to link it to a parameter of another \
+                            operation, e.g. to keep the voxel sizes of two operations equal. The \
+                            linked parameter follows the other one, optionally scaled and offset, \
+                            and is marked with a link icon.",
+                        );
+                        regular_font_token.pop(ui);
+                        wrap_token.pop(ui);
+                    });
+                }
+                let regular_font_token = ui.push_font(self.font_ids.regular);
+
+                let notes_header_open = imgui::CollapsingHeader::new(imgui::im_str!("Project notes"))
+                    .default_open(false)
+                    .build(ui);
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "PROJECT NOTES\n\
+                        \n\
+                        Free text documenting the intent of the project, saved in the project \
+                        file. Notes of the individual operations are edited in the operations \
+                        and shown when hovering their headers.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
+                if notes_header_open {
+                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+                    imstring_buffer.push_str(session.notes());
+                    if ui
+                        .input_text_multiline(
+                            imgui::im_str!("##project-notes"),
+                            &mut imstring_buffer,
+                            [-f32::MIN_POSITIVE, 100.0],
+                        )
+                        .build()
+                    {
+                        notes_changed = Some(imstring_buffer.to_string());
+                    }
+                    imstring_buffer.clear();
+                }
+
+                let has_voxel_stmts = session.stmts().iter().any(|stmt| {
+                    let ast::Stmt::VarDecl(var_decl) = stmt;
+                    function_table[&var_decl.init_expr().ident()]
+                        .voxel_size_param_index()
+                        .is_some()
+                });
+                if has_voxel_stmts {
+                    let mut voxel_size = session.voxel_size();
+                    if imgui::Drag::<f32>::new(&imgui::im_str!(
+                        "Project voxel size ({})",
+                        project_unit.symbol(Dimension::Length),
+                    ))
+                    .speed(DRAG_SPEED)
+                    .range(MIN_PROJECT_VOXEL_SIZE..)
+                    .build(ui, &mut voxel_size)
+                    {
+                        voxel_size_changed = Some(voxel_size.max(MIN_PROJECT_VOXEL_SIZE));
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            ui.text_colored(self.colors.tooltip_text, "PROJECT VOXEL SIZE\n\
+                            \n\
+                            Voxel size used by all voxel operations inheriting it. Lower \
+                            values produce finer results, higher values run faster. Tune it \
+                            high while blending and lower it for the final geometry.");
+                            wrap_token.pop(ui);
+                        });
+                    }
+                }
+
+                // Values are marked stale only in manual mode, as they are
+                // stale only momentarily in autorun mode.
+                let manual_run = session.autorun_delay().is_none();
+                for (stmt_index, stmt) in session.stmts().iter().enumerate() {
+                    match stmt {
+                        ast::Stmt::VarDecl(var_decl) => {
+                            let call_expr = var_decl.init_expr();
+                            let func_ident = call_expr.ident();
+                            let func = &function_table[&func_ident];
+
+                            let error = session.error_at_stmt(stmt_index);
+                            let warning_count = session.warnings_at_stmt(stmt_index).count();
+                            let error_color_token = if error.is_some() {
+                                Some(ui.push_style_colors(&[
+                                    (imgui::StyleColor::Header, self.colors.header_error),
+                                    (imgui::StyleColor::HeaderHovered, self.colors.header_error_hovered),
+                                    (imgui::StyleColor::HeaderActive, self.colors.header_error_hovered),
+                                    (imgui::StyleColor::Text, self.colors.tooltip_text),
+                                ]))
+                            } else if warning_count > 0 {
+                                Some(ui.push_style_colors(&[
+                                    (imgui::StyleColor::Header, self.colors.header_warning),
+                                    (imgui::StyleColor::HeaderHovered, self.colors.header_warning_hovered),
+                                    (imgui::StyleColor::HeaderActive, self.colors.header_warning_hovered),
+                                ]))
+                            } else {
+                                None
+                            };
+
+                            let stmt_locked = session.layers().var_locked(var_decl.ident());
+
+                            // The header ID stays the same, when the name,
+                            // or the stale, locked or warning markers are
+                            // changed.
+                            let collapsing_header_open = imgui::CollapsingHeader::new(&imgui::im_str!(
+                                    "#{} {}{}{}{}{} ##{}",
+                                    stmt_index + 1,
+                                    func.info().name,
+                                    match session.var_name(var_decl.ident()) {
+                                        Some(var_name) => format!(": {}", var_name),
+                                        None => String::new(),
+                                    },
+                                    if manual_run && session.stmt_stale(stmt_index) {
+                                        " (not run)"
+                                    } else {
+                                        ""
+                                    },
+                                    if stmt_locked { " (locked)" } else { "" },
+                                    match warning_count {
+                                        0 => String::new(),
+                                        1 => String::from(" (1 warning)"),
+                                        _ => format!(" ({} warnings)", warning_count),
+                                    },
+                                    stmt_index
+                                ))
+                                .default_open(true)
+                                .build(ui);
+
+                            if ui.is_item_hovered() {
+                                self.help_window_state.borrow_mut().func_ident = Some(func_ident);
+
+                                if let Some(error) = error {
+                                    let color_token = ui.push_style_color(
+                                        imgui::StyleColor::PopupBg,
+                                        self.colors.header_error_hovered,
+                                    );
+
+                                    ui.tooltip(|| {
+                                        let wrap_token = ui
+                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+
+                                        let mut imstring_buffer = self.global_imstring_buffer
+                                            .borrow_mut();
+
+                                        // FIXME: @Optimization don't allocate intermediate
+                                        // string and use `write!` once imgui-rs implements
+                                        // `io::Write` for `ImString`.
+                                        // https://github.com/Gekkio/imgui-rs/issues/290
+                                        imstring_buffer.push_str(&error.to_string());
+
+                                        ui.text_colored(
+                                            self.colors.tooltip_text,
+                                            &*imstring_buffer,
+                                        );
+
+                                        imstring_buffer.clear();
+
+                                        wrap_token.pop(ui);
+                                    });
+                                    color_token.pop(ui);
+                                } else {
+                                    let description = func.info().description;
+                                    let stmt_note = session.stmt_note(var_decl.ident());
+                                    if !description.is_empty() || stmt_note.is_some() || warning_count > 0 {
+                                        ui.tooltip(|| {
+                                            let wrap_token = ui
+                                                .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                            if !description.is_empty() {
+                                                ui.text_colored(self.colors.tooltip_text, description);
+                                            }
+                                            if let Some(stmt_note) = stmt_note {
+                                                if !description.is_empty() {
+                                                    ui.separator();
+                                                }
+                                                ui.text_colored(
+                                                    self.colors.tooltip_text,
+                                                    format!("NOTE\n\n{}", stmt_note),
+                                                );
+                                            }
+                                            if warning_count > 0 {
+                                                if !description.is_empty() || stmt_note.is_some() {
+                                                    ui.separator();
+                                                }
+                                                let warnings: Vec<_> = session
+                                                    .warnings_at_stmt(stmt_index)
+                                                    .map(|warning| &*warning.message)
+                                                    .collect();
+                                                ui.text_colored(
+                                                    self.colors.tooltip_text,
+                                                    format!("WARNINGS\n\n{}", warnings.join("\n")),
+                                                );
+                                            }
+                                            wrap_token.pop(ui);
+                                        })
+                                    }
+                                }
+                            }
+
+                            if let Some(color_token) = error_color_token {
+                                color_token.pop(ui);
+                            }
+
+                            if collapsing_header_open {
+                                ui.indent();
+
+                                assert!(
+                                    func.accepts_arg_count(call_expr.args().len()),
+                                    "Function call must be generated with correct number of arguments",
+                                );
+
+                                let operation_arg_style_tokens = if interpreter_busy || stmt_locked {
+                                    Some(push_disabled_style(ui))
+                                } else {
+                                    None
+                                };
+
+                                if let Some(arg_change) = self.draw_operation_args(
+                                    session,
+                                    stmt_index,
+                                    call_expr,
+                                    func.as_ref(),
+                                    project_unit,
+                                    snapping,
+                                    number_format,
+                                    param_defaults,
+                                    interpreter_busy,
+                                    &mut param_link_change,
+                                ) {
+                                    change = Some(arg_change);
+                                }
+
+                                if let Some(changed_args) = self.draw_variadic_param_buttons(
+                                    stmt_index,
+                                    call_expr,
+                                    func.as_ref(),
+                                ) {
+                                    args_change = Some(changed_args);
+                                }
+
+                                let mut pinned = session.var_pinned(var_decl.ident());
+                                if ui.checkbox(
+                                    &imgui::im_str!("Pin value##{}", stmt_index),
+                                    &mut pinned,
+                                ) {
+                                    pinned_changed = Some((var_decl.ident(), pinned));
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip(|| {
+                                        let wrap_token = ui
+                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                        ui.text_colored(self.colors.tooltip_text, "PIN VALUE\n\
+                                        \n\
+                                        Keeps the geometry produced by this operation rendered \
+                                        opaque in the viewport and included in exports, even if \
+                                        a subsequent operation uses it.");
+                                        wrap_token.pop(ui);
+                                    });
+                                }
+
+                                if live_link::func_linkable(func_ident) {
+                                    let mut live_linked = session.var_live_linked(var_decl.ident());
+                                    if ui.checkbox(
+                                        &imgui::im_str!("Live link##{}", stmt_index),
+                                        &mut live_linked,
+                                    ) {
+                                        live_link_changed = Some((var_decl.ident(), live_linked));
+                                    }
+                                    if ui.is_item_hovered() {
+                                        ui.tooltip(|| {
+                                            let wrap_token = ui
+                                                .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                            ui.text_colored(self.colors.tooltip_text, "LIVE LINK\n\
+                                            \n\
+                                            Re-runs the pipeline whenever the imported file \
+                                            changes, e.g. when it is saved again from Rhino or \
+                                            Blender.");
+                                            wrap_token.pop(ui);
+                                        });
+                                    }
+                                }
+
+                                if func.voxel_size_param_index().is_some() {
+                                    let mut inherits =
+                                        session.var_inherits_voxel_size(var_decl.ident());
+                                    if ui.checkbox(
+                                        &imgui::im_str!("Project voxel size##{}", stmt_index),
+                                        &mut inherits,
+                                    ) {
+                                        voxel_size_inheritance_changed =
+                                            Some((var_decl.ident(), inherits));
+                                    }
+                                    if ui.is_item_hovered() {
+                                        ui.tooltip(|| {
+                                            let wrap_token = ui
+                                                .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                            ui.text_colored(self.colors.tooltip_text, "PROJECT VOXEL SIZE\n\
+                                            \n\
+                                            Uses the project voxel size set at the top of the \
+                                            pipeline instead of the voxel size of this operation. \
+                                            Editing the voxel size of the operation overrides \
+                                            the project voxel size again.");
+                                            wrap_token.pop(ui);
+                                        });
+                                    }
+                                }
+
+                                let display_material = session.display_material(var_decl.ident());
+                                let combo_box_color_token = self.push_combo_box_colors();
+                                if let Some(combo_token) = imgui::ComboBox::new(
+                                    &imgui::im_str!("Display material##{}", stmt_index),
+                                )
+                                .preview_value(&imgui::ImString::new(
+                                    display_material.map_or("Default", |display_material| {
+                                        display_material.kind.name()
+                                    }),
+                                ))
+                                .begin(ui)
+                                {
+                                    if imgui::Selectable::new(imgui::im_str!("Default"))
+                                        .selected(display_material.is_none())
+                                        .build(ui)
+                                        && display_material.is_some()
+                                    {
+                                        display_material_changed = Some((var_decl.ident(), None));
+                                    }
+
+                                    for kind in &DisplayMaterialKind::ALL {
+                                        let selected = display_material.map_or(false, |display_material| {
+                                            display_material.kind == *kind
+                                        });
+                                        if imgui::Selectable::new(&imgui::ImString::new(kind.name()))
+                                            .selected(selected)
+                                            .build(ui)
+                                            && !selected
+                                        {
+                                            let display_material = DisplayMaterial {
+                                                kind: *kind,
+                                                ..display_material.unwrap_or_default()
+                                            };
+                                            display_material_changed =
+                                                Some((var_decl.ident(), Some(display_material)));
+                                        }
+                                    }
+
+                                    combo_token.end(ui);
+                                }
+                                combo_box_color_token.pop(ui);
+                                if ui.is_item_hovered() {
+                                    ui.tooltip(|| {
+                                        let wrap_token = ui
+                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                        ui.text_colored(self.colors.tooltip_text, "DISPLAY MATERIAL\n\
+                                        \n\
+                                        Draws the unused geometry produced by this operation in a \
+                                        flat color, a tinted matcap or as colored glass, so that \
+                                        e.g. context geometry can be told apart from the designed \
+                                        object. The material is only used in the shaded viewport \
+                                        draw modes and doesn't affect exports.");
+                                        wrap_token.pop(ui);
+                                    });
+                                }
+
+                                if let Some(mut display_material) = display_material {
+                                    if imgui::ColorEdit::new(
+                                        &imgui::im_str!("Display color##{}", stmt_index),
+                                        &mut display_material.color,
+                                    )
+                                    .build(ui)
+                                    {
+                                        display_material_changed =
+                                            Some((var_decl.ident(), Some(display_material)));
+                                    }
+                                }
+
+                                if !session.layers().is_empty() {
+                                    let layers = session.layers();
+                                    let layer_id = layers.var_layer(var_decl.ident());
+                                    let layer_name = layer_id
+                                        .and_then(|layer_id| layers.get(layer_id))
+                                        .map_or("No layer", |layer| layer.name.as_str());
+
+                                    let combo_box_color_token = self.push_combo_box_colors();
+                                    if let Some(combo_token) = imgui::ComboBox::new(
+                                        &imgui::im_str!("Layer##{}", stmt_index),
+                                    )
+                                    .preview_value(&imgui::ImString::new(layer_name))
+                                    .begin(ui)
+                                    {
+                                        if imgui::Selectable::new(imgui::im_str!("No layer"))
+                                            .selected(layer_id.is_none())
+                                            .build(ui)
+                                            && layer_id.is_some()
+                                        {
+                                            layer_changed = Some((var_decl.ident(), None));
+                                        }
+
+                                        for (id, layer) in layers.iter() {
+                                            if imgui::Selectable::new(&imgui::im_str!(
+                                                "{}##layer-{:?}",
+                                                layer.name,
+                                                id,
+                                            ))
+                                            .selected(layer_id == Some(id))
+                                            .build(ui)
+                                                && layer_id != Some(id)
+                                            {
+                                                layer_changed = Some((var_decl.ident(), Some(id)));
+                                            }
+                                        }
+
+                                        combo_token.end(ui);
+                                    }
+                                    combo_box_color_token.pop(ui);
+                                    if ui.is_item_hovered() {
+                                        ui.tooltip(|| {
+                                            let wrap_token = ui
+                                                .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                            ui.text_colored(self.colors.tooltip_text, "LAYER\n\
+                                            \n\
+                                            The layer the geometry produced by this operation \
+                                            belongs to. Layers are managed in the Layers window.");
+                                            wrap_token.pop(ui);
+                                        });
+                                    }
+                                }
+
+                                {
+                                    // The name is only submitted with enter,
+                                    // so that it is not validated while typed
+                                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+                                    imstring_buffer.push_str(
+                                        session.var_name(var_decl.ident()).unwrap_or(""),
+                                    );
+                                    if ui
+                                        .input_text(
+                                            &imgui::im_str!("Name##{}", stmt_index),
+                                            &mut imstring_buffer,
+                                        )
+                                        .enter_returns_true(true)
+                                        .build()
+                                    {
+                                        let var_name = imstring_buffer.to_str().trim();
+                                        var_name_changed = Some((
+                                            var_decl.ident(),
+                                            if var_name.is_empty() {
+                                                None
+                                            } else {
+                                                Some(String::from(var_name))
+                                            },
+                                        ));
+                                    }
+                                    imstring_buffer.clear();
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip(|| {
+                                        let wrap_token = ui
+                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                        ui.text_colored(self.colors.tooltip_text, "NAME\n\
+                                        \n\
+                                        Custom name of the geometry produced by this operation, \
+                                        used in exported files, labels and parameter choices. \
+                                        Must be unique in the pipeline. Press enter to apply. If \
+                                        empty, the geometry is named after the operation.");
+                                        wrap_token.pop(ui);
+                                    });
+                                }
+
+                                {
+                                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+                                    imstring_buffer.push_str(
+                                        session.value_label(var_decl.ident()).unwrap_or(""),
+                                    );
+                                    if ui
+                                        .input_text(
+                                            &imgui::im_str!("Label##{}", stmt_index),
+                                            &mut imstring_buffer,
+                                        )
+                                        .build()
+                                    {
+                                        let value_label = imstring_buffer.to_string();
+                                        value_label_changed = Some((
+                                            var_decl.ident(),
+                                            if value_label.is_empty() {
+                                                None
+                                            } else {
+                                                Some(value_label)
+                                            },
+                                        ));
+                                    }
+                                    imstring_buffer.clear();
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip(|| {
+                                        let wrap_token = ui
+                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                        ui.text_colored(self.colors.tooltip_text, "LABEL\n\
+                                        \n\
+                                        Custom text of the viewport label of the geometry produced \
+                                        by this operation. If empty, the geometry is labeled with \
+                                        the number and name of the operation.");
+                                        wrap_token.pop(ui);
+                                    });
+                                }
+
+                                {
+                                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+                                    imstring_buffer.push_str(
+                                        session.stmt_note(var_decl.ident()).unwrap_or(""),
+                                    );
+                                    if ui
+                                        .input_text_multiline(
+                                            &imgui::im_str!("Note##{}", stmt_index),
+                                            &mut imstring_buffer,
+                                            [0.0, 40.0],
+                                        )
+                                        .build()
+                                    {
+                                        let stmt_note = imstring_buffer.to_string();
+                                        stmt_note_changed = Some((
+                                            var_decl.ident(),
+                                            if stmt_note.trim().is_empty() {
+                                                None
+                                            } else {
+                                                Some(stmt_note)
+                                            },
+                                        ));
+                                    }
+                                    imstring_buffer.clear();
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip(|| {
+                                        let wrap_token = ui
+                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                        ui.text_colored(self.colors.tooltip_text, "NOTE\n\
+                                        \n\
+                                        Free text documenting the intent of this operation, saved \
+                                        in the project file and shown when hovering the header of \
+                                        the operation.");
+                                        wrap_token.pop(ui);
+                                    });
+                                }
+
+                                let focused = session.focused_var() == Some(var_decl.ident());
+                                if ui.button(
+                                    &if focused {
+                                        imgui::im_str!("Exit focus##{}", stmt_index)
+                                    } else {
+                                        imgui::im_str!("Focus##{}", stmt_index)
+                                    },
+                                    [-f32::MIN_POSITIVE, 0.0],
+                                ) {
+                                    focus_changed = Some(if focused {
+                                        None
+                                    } else {
+                                        Some(var_decl.ident())
+                                    });
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip(|| {
+                                        let wrap_token = ui
+                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                        ui.text_colored(self.colors.tooltip_text, "FOCUS\n\
+                                        \n\
+                                        Isolates this operation in the viewport for tuning its \
+                                        parameters. Only its input geometry, dimmed, and its \
+                                        output are shown, and its parameters are shown in a \
+                                        floating panel next to the viewport.");
+                                        wrap_token.pop(ui);
+                                    });
+                                }
+
+                                if ui.button(
+                                    &imgui::im_str!("Copy as OBJ##{}", stmt_index),
+                                    [-f32::MIN_POSITIVE, 0.0],
+                                ) {
+                                    copy_as_obj = Some(var_decl.ident());
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip(|| {
+                                        let wrap_token = ui
+                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                                        ui.text_colored(self.colors.tooltip_text, "COPY AS OBJ\n\
+                                        \n\
+                                        Copies the geometry produced by this operation to the system \
+                                        clipboard as OBJ text, so that it can be pasted into other tools.");
+                                        wrap_token.pop(ui);
+                                    });
+                                }
+
+                                let console_id = imgui::im_str!("##console{}", stmt_index);
+                                if let Some(window_token) = imgui::ChildWindow::new(&console_id)
+                                    .size([0.0, PIPELINE_OPERATION_CONSOLE_HEIGHT])
+                                    .scrollable(true)
+                                    .scroll_bar(true)
+                                    .always_vertical_scrollbar(true)
+                                    .begin(ui)
+                                {
+                                    let wrap_token = ui
+                                        .push_text_wrap_pos(WRAP_POS_CONSOLE_TEXT_PIXELS);
+
+                                    let log_messages = session.log_messages_at_stmt(stmt_index);
+                                    for log_message in log_messages {
+                                        // Measurements are formatted here, so that they follow
+                                        // the number format and get the unit
+                                        let message = match &log_message.measurement {
+                                            Some(measurement) => {
+                                                let mut message = format!(
+                                                    "{} = {}",
+                                                    measurement.name,
+                                                    number_format.format_float(f64::from(measurement.value), 2),
+                                                );
+                                                if measurement.dimension != Dimension::Dimensionless {
+                                                    message.push(' ');
+                                                    message.push_str(&project_unit.symbol(measurement.dimension));
+                                                }
+
+                                                Cow::Owned(message)
+                                            }
+                                            None => Cow::Borrowed(log_message.message.as_ref()),
+                                        };
+
+                                        ui.text_colored(match log_message.level {
+                                            LogMessageLevel::Info => self.colors.log_message_info,
+                                            LogMessageLevel::Warn => self.colors.log_message_warn,
+                                            LogMessageLevel::Error => self.colors.log_message_error,
+                                        }, &message);
+                                    }
+
+                                    let message_count = log_messages.len();
+                                    let mut console_state = self.console_state.borrow_mut();
+                                    if console_state[stmt_index].message_count < message_count {
+                                        ui.set_scroll_here_y();
+                                        console_state[stmt_index].message_count = message_count;
+                                    }
+
+                                    wrap_token.pop(ui);
+                                    window_token.end(ui);
+                                }
+
+                                if let Some((color_token, style_token)) = operation_arg_style_tokens {
+                                    color_token.pop(ui);
+                                    style_token.pop(ui);
+                                }
+
+                                ui.unindent();
+                            }
+                        }
+                    }
+                }
+                regular_font_token.pop(ui);
+
+                let mut pipeline_window_state = self.pipeline_window_state.borrow_mut();
+                if pipeline_window_state.autoscroll {
+                    ui.set_scroll_here_y();
+                    pipeline_window_state.autoscroll = false;
+                }
+            });
+        bold_font_token.pop(ui);
+
+        let mut changed =
+            submit_operation_arg_change(current_time, session, param_defaults, change)
+                | submit_operation_args_change(current_time, session, args_change)
+                | submit_param_link_change(current_time, session, param_link_change);
+
+        // The project voxel size rewrites the arguments of the inheriting
+        // statements, so it can only be changed while the interpreter is idle.
+        if !session.interpreter_busy() {
+            if let Some(voxel_size) = voxel_size_changed {
+                session.set_voxel_size(current_time, voxel_size);
+                changed = true;
+            }
+            if let Some((var_ident, inherits)) = voxel_size_inheritance_changed {
+                session.set_var_inherits_voxel_size(current_time, var_ident, inherits);
+                changed = true;
+            }
+        }
+
+        // Pinning, live links, display materials, layers, focus, labels and
+        // notes do not change the program, so they can be changed even while the
+        // interpreter is busy.
+        if let Some((var_ident, pinned)) = pinned_changed {
+            session.set_var_pinned(var_ident, pinned);
+        }
+        if let Some((var_ident, live_linked)) = live_link_changed {
+            session.set_var_live_linked(var_ident, live_linked);
+        }
+        if let Some((var_ident, display_material)) = display_material_changed {
+            session.set_display_material(var_ident, display_material);
+        }
+        if let Some((var_ident, layer_id)) = layer_changed {
+            session.layers_mut().set_var_layer(var_ident, layer_id);
+        }
+        if let Some(focused_var) = focus_changed {
+            session.set_focused_var(focused_var);
+        }
+        let mut var_name_error = None;
+        let var_name_changed = var_name_changed.and_then(|(var_ident, var_name)| {
+            match session.set_var_name(var_ident, var_name) {
+                Ok(()) => Some(var_ident),
+                Err(err) => {
+                    var_name_error = Some(err.to_string());
+                    None
+                }
+            }
+        });
+        let value_label_changed = value_label_changed.map(|(var_ident, value_label)| {
+            session.set_value_label(var_ident, value_label);
+            var_ident
+        });
+        let any_notes_changed = stmt_note_changed.is_some() || notes_changed.is_some();
+        if let Some((var_ident, stmt_note)) = stmt_note_changed {
+            session.set_stmt_note(var_ident, stmt_note);
+        }
+        if let Some(notes) = notes_changed {
+            session.set_notes(notes);
+        }
+
+        PipelineStatus {
+            changed,
+            copy_as_obj,
+            pinned_changed: pinned_changed.map(|(var_ident, _)| var_ident),
+            display_material_changed: display_material_changed.map(|(var_ident, _)| var_ident),
+            layer_changed: layer_changed.map(|(var_ident, _)| var_ident),
+            var_name_changed,
+            var_name_error,
+            value_label_changed,
+            notes_changed: any_notes_changed,
+            live_link_changed: live_link_changed.map(|(var_ident, _)| var_ident),
+        }
+    }
 
-                            let collapsing_header_open = imgui::CollapsingHeader::new(&imgui::im_str!(
-                                    "#{} {} ##{}",
-                                    stmt_index + 1,
-                                    func.info().name,
-                                    stmt_index
-                                ))
-                                .default_open(true)
-                                .build(ui);
+    /// Draws the floating panel with the parameters of the focused
+    /// operation, if any. Returns whether a parameter changed.
+    pub fn draw_focus_window(
+        &self,
+        current_time: Instant,
+        session: &mut Session,
+        project_unit: Unit,
+        snapping: Snapping,
+        number_format: NumberFormat,
+        param_defaults: &mut ParamDefaults,
+    ) -> bool {
+        let ui = &self.imgui_ui;
 
-                            if ui.is_item_hovered() {
-                                if let Some(error) = error {
-                                    let color_token = ui.push_style_color(
-                                        imgui::StyleColor::PopupBg,
-                                        self.colors.header_error_hovered,
-                                    );
+        let stmt_index = match session.focused_stmt_index() {
+            Some(stmt_index) => stmt_index,
+            None => return false,
+        };
 
-                                    ui.tooltip(|| {
-                                        let wrap_token = ui
-                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+        let function_table = session.function_table();
+        let ast::Stmt::VarDecl(var_decl) = &session.stmts()[stmt_index];
+        let call_expr = var_decl.init_expr();
+        let func = &function_table[&call_expr.ident()];
 
-                                        let mut imstring_buffer = self.global_imstring_buffer
-                                            .borrow_mut();
+        let interpreter_busy = session.interpreter_busy();
+        let stmt_locked = session.layers().var_locked(var_decl.ident());
 
-                                        // FIXME: @Optimization don't allocate intermediate
-                                        // string and use `write!` once imgui-rs implements
-                                        // `io::Write` for `ImString`.
-                                        // https://github.com/Gekkio/imgui-rs/issues/290
-                                        imstring_buffer.push_str(&error.to_string());
+        let mut window_open = true;
+        let mut exit_clicked = false;
+        let mut change = None;
+        let mut param_link_change = None;
 
-                                        ui.text_colored(
-                                            self.colors.tooltip_text,
-                                            &*imstring_buffer,
-                                        );
+        let bold_font_token = ui.push_font(self.font_ids.bold);
+        // The window ID stays the same, when another operation is focused.
+        imgui::Window::new(&imgui::im_str!(
+            "Focus: #{} {}###focus",
+            stmt_index + 1,
+            func.info().name,
+        ))
+        .opened(&mut window_open)
+        .collapsible(false)
+        .always_auto_resize(true)
+        .position(
+            [2.0 * MARGIN + PIPELINE_WINDOW_WIDTH, MARGIN],
+            imgui::Condition::FirstUseEver,
+        )
+        .build(ui, || {
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                    let regular_font_token = ui.push_font(self.font_ids.regular);
+                    ui.text_colored(
+                        self.colors.tooltip_text,
+                        "FOCUS\n\
+                        \n\
+                        Parameters of the focused operation. While an operation is \
+                        focused, the viewport shows only its output and its dimmed \
+                        input geometry. Closing the panel ends the focus.",
+                    );
+                    regular_font_token.pop(ui);
+                    wrap_token.pop(ui);
+                });
+            }
 
-                                        imstring_buffer.clear();
+            let regular_font_token = ui.push_font(self.font_ids.regular);
 
-                                        wrap_token.pop(ui);
-                                    });
-                                    color_token.pop(ui);
-                                } else if !func.info().description.is_empty() {
-                                    ui.tooltip(|| {
-                                        let wrap_token = ui
-                                            .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
-                                            ui.text_colored(self.colors.tooltip_text, func.info().description);
-                                        wrap_token.pop(ui);
-                                    })
-                                }
-                            }
+            let style_tokens = if interpreter_busy || stmt_locked {
+                Some(push_disabled_style(ui))
+            } else {
+                None
+            };
+
+            change = self.draw_operation_args(
+                session,
+                stmt_index,
+                call_expr,
+                func.as_ref(),
+                project_unit,
+                snapping,
+                number_format,
+                param_defaults,
+                interpreter_busy,
+                &mut param_link_change,
+            );
 
-                            if let Some(color_token) = error_color_token {
-                                color_token.pop(ui);
-                            }
+            if let Some((color_token, style_token)) = style_tokens {
+                color_token.pop(ui);
+                style_token.pop(ui);
+            }
 
-                            if collapsing_header_open {
-                                ui.indent();
+            ui.separator();
+            if ui.button(imgui::im_str!("Exit focus"), [-f32::MIN_POSITIVE, 0.0]) {
+                exit_clicked = true;
+            }
 
-                                assert_eq!(
-                                    call_expr.args().len(),
-                                    func.param_info().len(),
-                                    "Function call must be generated with correct number of arguments",
-                                );
+            regular_font_token.pop(ui);
+        });
+        bold_font_token.pop(ui);
 
-                                let operation_arg_style_tokens = if interpreter_busy {
-                                    Some(push_disabled_style(ui))
-                                } else {
-                                    None
-                                };
+        let changed = submit_operation_arg_change(current_time, session, param_defaults, change)
+            | submit_param_link_change(current_time, session, param_link_change);
 
-                                for (arg_index, (param_info, arg)) in func
-                                    .param_info()
-                                    .iter()
-                                    .zip(call_expr.args().iter())
-                                    .enumerate()
-                                {
-                                    let input_label = imgui::im_str!(
-                                        "{}##{}-{}",
-                                        &param_info.name,
-                                        stmt_index,
-                                        arg_index
-                                    );
+        if !window_open || exit_clicked {
+            session.set_focused_var(None);
+        }
 
-                                    match param_info.refinement {
-                                        ParamRefinement::Boolean(_) => {
-                                            let mut boolean_lit =
-                                                arg.unwrap_literal().unwrap_boolean();
-
-                                            if ui.checkbox(&input_label, &mut boolean_lit) {
-                                                change = Some((
-                                                    stmt_index,
-                                                    arg_index,
-                                                    ast::Expr::Lit(ast::LitExpr::Boolean(
-                                                        boolean_lit,
-                                                    )),
-                                                ));
-                                            }
-                                        }
-                                        ParamRefinement::Int(param_refinement_int) => {
-                                            let mut int_lit = arg.unwrap_literal().unwrap_int();
-
-                                            let mut drag_int = imgui::Drag::<i32>::new(&input_label)
-                                                .speed(DRAG_SPEED);
-
-                                            match (
-                                                param_refinement_int.min_value,
-                                                param_refinement_int.max_value,
-                                            ) {
-                                                (Some(min_value), Some(max_value)) => {
-                                                    drag_int = drag_int.range(min_value..=max_value);
-                                                }
-                                                (Some(min_value), None) => {
-                                                    drag_int = drag_int.range(min_value..);
-                                                }
-                                                (None, Some(max_value)) => {
-                                                    drag_int = drag_int.range(..=max_value);
-                                                }
-                                                (None, None) => (),
-                                            }
+        changed
+    }
 
-                                            if drag_int.build(ui, &mut int_lit) {
-                                                int_lit = param_refinement_int.clamp(int_lit);
-                                                change = Some((
-                                                    stmt_index,
-                                                    arg_index,
-                                                    ast::Expr::Lit(ast::LitExpr::Int(int_lit)),
-                                                ));
-                                            }
-                                        }
-                                        ParamRefinement::Uint(param_refinement_uint) => {
-                                            let mut uint_lit = arg.unwrap_literal().unwrap_uint();
-
-                                            let mut drag_uint = imgui::Drag::<u32>::new(&input_label)
-                                                .speed(DRAG_SPEED);
-
-                                            match (
-                                                param_refinement_uint.min_value,
-                                                param_refinement_uint.max_value,
-                                            ) {
-                                                (Some(min_value), Some(max_value)) => {
-                                                    drag_uint = drag_uint.range(min_value..=max_value);
-                                                }
-                                                (Some(min_value), None) => {
-                                                    drag_uint = drag_uint.range(min_value..);
-                                                }
-                                                (None, Some(max_value)) => {
-                                                    drag_uint = drag_uint.range(..=max_value);
-                                                }
-                                                (None, None) => (),
-                                            }
+    /// Draws the inputs of the arguments of the operation. Returns the
+    /// statement index, argument index and new argument of a changed
+    /// argument, if any. A linked or unlinked parameter is set to
+    /// `param_link_change`.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_operation_args(
+        &self,
+        session: &Session,
+        stmt_index: usize,
+        call_expr: &ast::CallExpr,
+        func: &dyn Func,
+        project_unit: Unit,
+        snapping: Snapping,
+        number_format: NumberFormat,
+        param_defaults: &ParamDefaults,
+        interpreter_busy: bool,
+        param_link_change: &mut Option<(ParamId, Option<ParamLink>)>,
+    ) -> Option<(usize, usize, ast::Expr)> {
+        let ui = &self.imgui_ui;
+        let mut change = None;
 
-                                            if drag_uint.build(ui, &mut uint_lit) {
-                                                let uint_value = param_refinement_uint.clamp(uint_lit);
-                                                change = Some((
-                                                    stmt_index,
-                                                    arg_index,
-                                                    ast::Expr::Lit(ast::LitExpr::Uint(uint_value)),
-                                                ));
-                                            }
-                                        }
-                                        ParamRefinement::Float(param_refinement_float) => {
-                                            let mut float_lit = arg.unwrap_literal().unwrap_float();
-
-                                            let mut drag_float = imgui::Drag::<f32>::new(&input_label)
-                                                .speed(DRAG_SPEED);
-
-                                            match (
-                                                param_refinement_float.min_value,
-                                                param_refinement_float.max_value,
-                                            ) {
-                                                (Some(min_value), Some(max_value)) => {
-                                                    drag_float = drag_float.range(min_value..=max_value);
-                                                }
-                                                (Some(min_value), None) => {
-                                                    drag_float = drag_float.range(min_value..);
-                                                }
-                                                (None, Some(max_value)) => {
-                                                    drag_float = drag_float.range(..=max_value);
-                                                }
-                                                (None, None) => (),
-                                            }
+        let ast::Stmt::VarDecl(var_decl) = &session.stmts()[stmt_index];
+        let var_ident = var_decl.ident();
+
+        let arg_count = call_expr.args().len();
+        for (arg_index, arg) in call_expr.args().iter().enumerate() {
+            let param_index = func
+                .param_index_for_arg(arg_index, arg_count)
+                .expect("Function call must be generated with correct number of arguments");
+            let param_info = &func.param_info()[param_index];
+            // Inputs of a variadic parameter are numbered by their position
+            let param_name = if func.variadic_param_index() == Some(param_index) {
+                Cow::Owned(format!("{} {}", param_info.name, arg_index + 1))
+            } else {
+                Cow::Borrowed(param_info.name)
+            };
+
+            let dimension = match param_info.refinement {
+                ParamRefinement::Float(refinement) => refinement.dimension,
+                ParamRefinement::Float2(refinement) => refinement.dimension,
+                ParamRefinement::Float3(refinement) => refinement.dimension,
+                _ => Dimension::Dimensionless,
+            };
+            let input_label = if dimension == Dimension::Dimensionless {
+                imgui::im_str!("{}##{}-{}", param_name, stmt_index, arg_index)
+            } else {
+                imgui::im_str!(
+                    "{} ({})##{}-{}",
+                    param_name,
+                    project_unit.symbol(dimension),
+                    stmt_index,
+                    arg_index
+                )
+            };
+
+            let expression_editable = param_info.widget.choices().is_none()
+                && expression::component_count(&param_info.refinement).is_some();
+            let param = ParamId {
+                var_ident,
+                arg_index,
+            };
+            let editing_expression = self
+                .pipeline_window_state
+                .borrow()
+                .expression_edit
+                .as_ref()
+                .map_or(false, |edit| {
+                    edit.stmt_index == stmt_index && edit.arg_index == arg_index
+                });
+
+            match param_info.refinement {
+                _ if editing_expression && !interpreter_busy => {
+                    if let Some(lit) = self.draw_expression_input(
+                        &input_label,
+                        &param_info.refinement,
+                        number_format,
+                    ) {
+                        change = Some((stmt_index, arg_index, ast::Expr::Lit(lit)));
+                    }
+                }
+                ParamRefinement::Boolean(_) => {
+                    let mut boolean_lit = arg.unwrap_literal().unwrap_boolean();
 
-                                            if drag_float.build(ui, &mut float_lit)
-                                            {
-                                                let float_value = param_refinement_float.clamp(float_lit);
-                                                change = Some((
-                                                    stmt_index,
-                                                    arg_index,
-                                                    ast::Expr::Lit(ast::LitExpr::Float(float_value)),
-                                                ));
-                                            }
-                                        }
-                                        ParamRefinement::Float2(param_refinement_float2) => {
-                                            let mut float2_lit =
-                                                arg.unwrap_literal().unwrap_float2();
-
-                                            let mut drag_float2 = imgui::Drag::<f32>::new(&input_label)
-                                                .speed(DRAG_SPEED);
-
-                                            match (
-                                                param_refinement_float2.min_value,
-                                                param_refinement_float2.max_value,
-                                            ) {
-                                                (Some(min_value), Some(max_value)) => {
-                                                    drag_float2 = drag_float2.range(min_value..=max_value);
-                                                }
-                                                (Some(min_value), None) => {
-                                                    drag_float2 = drag_float2.range(min_value..);
-                                                }
-                                                (None, Some(max_value)) => {
-                                                    drag_float2 = drag_float2.range(..=max_value);
-                                                }
-                                                (None, None) => (),
-                                            }
+                    if ui.checkbox(&input_label, &mut boolean_lit) {
+                        change = Some((
+                            stmt_index,
+                            arg_index,
+                            ast::Expr::Lit(ast::LitExpr::Boolean(boolean_lit)),
+                        ));
+                    }
+                }
+                ParamRefinement::Int(param_refinement_int) => {
+                    let mut int_lit = arg.unwrap_literal().unwrap_int();
 
-                                            if drag_float2.build_array(ui, &mut float2_lit)
-                                            {
-                                                let float2_value = param_refinement_float2.clamp(float2_lit);
-                                                change = Some((
-                                                    stmt_index,
-                                                    arg_index,
-                                                    ast::Expr::Lit(ast::LitExpr::Float2(
-                                                        float2_value,
-                                                    )),
-                                                ));
-                                            }
-                                        }
-                                        ParamRefinement::Float3(param_refinement_float3) => {
-                                            let mut float3_lit =
-                                                arg.unwrap_literal().unwrap_float3();
-
-                                            let mut drag_float3 = imgui::Drag::<f32>::new(&input_label)
-                                                .speed(DRAG_SPEED);
-
-                                            match (
-                                                param_refinement_float3.min_value,
-                                                param_refinement_float3.max_value,
-                                            ) {
-                                                (Some(min_value), Some(max_value)) => {
-                                                    drag_float3 = drag_float3.range(min_value..=max_value);
-                                                }
-                                                (Some(min_value), None) => {
-                                                    drag_float3 = drag_float3.range(min_value..);
-                                                }
-                                                (None, Some(max_value)) => {
-                                                    drag_float3 = drag_float3.range(..=max_value);
-                                                }
-                                                (None, None) => (),
-                                            }
+                    let mut drag_int = imgui::Drag::<i32>::new(&input_label).speed(DRAG_SPEED);
 
-                                            if drag_float3.build_array(ui, &mut float3_lit)
-                                            {
-                                                let float3_value = param_refinement_float3.clamp(float3_lit);
-                                                change = Some((
-                                                    stmt_index,
-                                                    arg_index,
-                                                    ast::Expr::Lit(ast::LitExpr::Float3(
-                                                        float3_value,
-                                                    )),
-                                                ));
-                                            }
-                                        }
-                                        ParamRefinement::String(param_refinement_string) => {
-                                            let mut imstring_buffer = self.global_imstring_buffer
-                                                .borrow_mut();
-
-                                            let string_lit = arg.unwrap_literal().unwrap_string();
-                                            imstring_buffer.push_str(string_lit);
-
-                                            if param_refinement_string.file_path {
-                                                if file_input(
-                                                    ui,
-                                                    &input_label,
-                                                    param_refinement_string.file_ext_filter,
-                                                    &mut imstring_buffer,
-                                                ) {
-                                                    let string_value = format!("{}", imstring_buffer);
-                                                    change = Some((
-                                                        stmt_index,
-                                                        arg_index,
-                                                        ast::Expr::Lit(ast::LitExpr::String(string_value)),
-                                                    ));
-                                                }
-                                            } else if ui
-                                                .input_text(&input_label, &mut imstring_buffer)
-                                                .read_only(interpreter_busy)
-                                                .build() {
-                                                    let string_value = format!("{}", imstring_buffer);
-                                                    change = Some((
-                                                        stmt_index,
-                                                        arg_index,
-                                                        ast::Expr::Lit(ast::LitExpr::String(string_value)),
-                                                    ));
-                                                }
+                    match (
+                        param_refinement_int.min_value,
+                        param_refinement_int.max_value,
+                    ) {
+                        (Some(min_value), Some(max_value)) => {
+                            drag_int = drag_int.range(min_value..=max_value);
+                        }
+                        (Some(min_value), None) => {
+                            drag_int = drag_int.range(min_value..);
+                        }
+                        (None, Some(max_value)) => {
+                            drag_int = drag_int.range(..=max_value);
+                        }
+                        (None, None) => (),
+                    }
 
-                                            imstring_buffer.clear();
-                                        }
-                                        ParamRefinement::Mesh => {
-                                            let changed_expr = self.draw_var_combo_box(
-                                                session,
-                                                stmt_index,
-                                                arg,
-                                                Ty::Mesh,
-                                                &input_label,
-                                            );
-
-                                            if let Some(changed_expr) = changed_expr {
-                                                change = Some((
-                                                    stmt_index,
-                                                    arg_index,
-                                                    changed_expr,
-                                                ));
-                                            }
-                                        }
-                                        ParamRefinement::MeshArray => {
-                                            let changed_expr = self.draw_var_combo_box(
-                                                session,
-                                                stmt_index,
-                                                arg,
-                                                Ty::MeshArray,
-                                                &input_label,
-                                            );
-
-                                            if let Some(changed_expr) = changed_expr {
-                                                change = Some((
-                                                    stmt_index,
-                                                    arg_index,
-                                                    changed_expr,
-                                                ));
-                                            }
-                                        }
-                                    }
+                    if drag_int.build(ui, &mut int_lit) {
+                        int_lit = param_refinement_int.clamp(int_lit);
+                        change = Some((
+                            stmt_index,
+                            arg_index,
+                            ast::Expr::Lit(ast::LitExpr::Int(int_lit)),
+                        ));
+                    }
+                }
+                ParamRefinement::Uint(_) if param_info.widget.choices().is_some() => {
+                    let choices = param_info
+                        .widget
+                        .choices()
+                        .expect("The widget must choose one of the options");
+                    let choice_index = arg.unwrap_literal().unwrap_uint();
+
+                    if let Some(choice_index) =
+                        self.draw_param_choice(&input_label, choices, choice_index)
+                    {
+                        change = Some((
+                            stmt_index,
+                            arg_index,
+                            ast::Expr::Lit(ast::LitExpr::Uint(choice_index)),
+                        ));
+                    }
+                }
+                ParamRefinement::Uint(param_refinement_uint) => {
+                    let mut uint_lit = arg.unwrap_literal().unwrap_uint();
 
-                                    if ui.is_item_hovered() && !param_info.description.is_empty() {
-                                        ui.tooltip(|| {
-                                            let wrap_token = ui
-                                                .push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
-                                                ui.text_colored(self.colors.tooltip_text, param_info.description);
-                                            wrap_token.pop(ui);
-                                        });
-                                    }
+                    let mut drag_uint = imgui::Drag::<u32>::new(&input_label).speed(DRAG_SPEED);
+
+                    match (
+                        param_refinement_uint.min_value,
+                        param_refinement_uint.max_value,
+                    ) {
+                        (Some(min_value), Some(max_value)) => {
+                            drag_uint = drag_uint.range(min_value..=max_value);
+                        }
+                        (Some(min_value), None) => {
+                            drag_uint = drag_uint.range(min_value..);
+                        }
+                        (None, Some(max_value)) => {
+                            drag_uint = drag_uint.range(..=max_value);
+                        }
+                        (None, None) => (),
+                    }
+
+                    if drag_uint.build(ui, &mut uint_lit) {
+                        let uint_value = param_refinement_uint.clamp(uint_lit);
+                        change = Some((
+                            stmt_index,
+                            arg_index,
+                            ast::Expr::Lit(ast::LitExpr::Uint(uint_value)),
+                        ));
+                    }
+                }
+                ParamRefinement::Float(param_refinement_float) => {
+                    let mut float_lit = arg.unwrap_literal().unwrap_float();
+
+                    // Imgui always displays a decimal point, so the value is
+                    // formatted beforehand and shown as a format without any
+                    // conversion specification
+                    let display_format =
+                        imgui::im_str!("{}", number_format.format_float(f64::from(float_lit), 3));
+                    let mut drag_float = imgui::Drag::<f32>::new(&input_label)
+                        .speed(DRAG_SPEED)
+                        .display_format(&display_format);
+
+                    match (
+                        param_refinement_float.min_value,
+                        param_refinement_float.max_value,
+                    ) {
+                        (Some(min_value), Some(max_value)) => {
+                            drag_float = drag_float.range(min_value..=max_value);
+                        }
+                        (Some(min_value), None) => {
+                            drag_float = drag_float.range(min_value..);
+                        }
+                        (None, Some(max_value)) => {
+                            drag_float = drag_float.range(..=max_value);
+                        }
+                        (None, None) => (),
+                    }
+
+                    if drag_float.build(ui, &mut float_lit) {
+                        let float_value = param_refinement_float.clamp(float_lit);
+                        change = Some((
+                            stmt_index,
+                            arg_index,
+                            ast::Expr::Lit(ast::LitExpr::Float(float_value)),
+                        ));
+                    }
+                }
+                ParamRefinement::Float2(param_refinement_float2) => {
+                    let mut float2_lit = arg.unwrap_literal().unwrap_float2();
+
+                    if self.draw_float_drags(
+                        &input_label,
+                        &mut float2_lit,
+                        param_refinement_float2.min_value,
+                        param_refinement_float2.max_value,
+                        number_format,
+                    ) {
+                        let float2_value = param_refinement_float2.clamp(float2_lit);
+                        change = Some((
+                            stmt_index,
+                            arg_index,
+                            ast::Expr::Lit(ast::LitExpr::Float2(float2_value)),
+                        ));
+                    }
+                }
+                ParamRefinement::Float3(param_refinement_float3)
+                    if param_info.widget == ParamWidget::Vector3 =>
+                {
+                    let arg_float3_lit = arg.unwrap_literal().unwrap_float3();
+                    let mut float3_lit = arg_float3_lit;
+
+                    // Each component gets its own labeled input, but the
+                    // label of the whole parameter is kept as the last item,
+                    // so that the tooltip and the reset popup work as with
+                    // other widgets.
+                    let mut component_changed = false;
+                    for (component_index, component_name) in ["X", "Y", "Z"].iter().enumerate() {
+                        let component_label = imgui::im_str!(
+                            "{}##{}-{}-{}",
+                            component_name,
+                            stmt_index,
+                            arg_index,
+                            component_index,
+                        );
+                        let mut component_text =
+                            imgui::ImString::with_capacity(EXPRESSION_CAPACITY);
+                        component_text.push_str(
+                            &number_format.format_float(f64::from(float3_lit[component_index]), 3),
+                        );
+
+                        // The value is only submitted with enter, so that
+                        // the pipeline doesn't run with every typed digit.
+                        // Invalid values are ignored, as with a float input.
+                        let committed = ui
+                            .input_text(&component_label, &mut component_text)
+                            .read_only(interpreter_busy)
+                            .enter_returns_true(true)
+                            .build();
+                        if committed {
+                            let text =
+                                number_format.delocalize_expression(component_text.to_str(), 1);
+                            if let Ok(value) = expression::evaluate(&text) {
+                                if (value as f32).is_finite() {
+                                    float3_lit[component_index] = value as f32;
+                                    component_changed = true;
                                 }
+                            }
+                        }
+                    }
+                    ui.text(input_label.to_str().split("##").next().unwrap_or_default());
+
+                    if component_changed {
+                        let float3_value =
+                            match snapping.increment(param_refinement_float3.dimension) {
+                                Some(increment) => param_refinement_float3
+                                    .clamp(snapping::snap3(float3_lit, increment)),
+                                None => param_refinement_float3.clamp(float3_lit),
+                            };
 
-                                let console_id = imgui::im_str!("##console{}", stmt_index);
-                                if let Some(window_token) = imgui::ChildWindow::new(&console_id)
-                                    .size([0.0, PIPELINE_OPERATION_CONSOLE_HEIGHT])
-                                    .scrollable(true)
-                                    .scroll_bar(true)
-                                    .always_vertical_scrollbar(true)
-                                    .begin(ui)
-                                {
-                                    let wrap_token = ui
-                                        .push_text_wrap_pos(WRAP_POS_CONSOLE_TEXT_PIXELS);
+                        if float3_value != arg_float3_lit {
+                            change = Some((
+                                stmt_index,
+                                arg_index,
+                                ast::Expr::Lit(ast::LitExpr::Float3(float3_value)),
+                            ));
+                        }
+                    }
+                }
+                ParamRefinement::Float3(param_refinement_float3) => {
+                    let arg_float3_lit = arg.unwrap_literal().unwrap_float3();
+                    let snapping_increment = snapping.increment(param_refinement_float3.dimension);
+
+                    // While dragging a snapped value, keep dragging the
+                    // unsnapped one, so that the drag accumulates.
+                    let mut float3_lit = match (
+                        snapping_increment,
+                        self.pipeline_window_state.borrow().unsnapped_drag,
+                    ) {
+                        (Some(_), Some((drag_stmt_index, drag_arg_index, unsnapped_value)))
+                            if drag_stmt_index == stmt_index && drag_arg_index == arg_index =>
+                        {
+                            unsnapped_value
+                        }
+                        _ => arg_float3_lit,
+                    };
+
+                    if self.draw_float_drags(
+                        &input_label,
+                        &mut float3_lit,
+                        param_refinement_float3.min_value,
+                        param_refinement_float3.max_value,
+                        number_format,
+                    ) {
+                        let float3_value = match snapping_increment {
+                            Some(increment) => {
+                                self.pipeline_window_state.borrow_mut().unsnapped_drag =
+                                    Some((stmt_index, arg_index, float3_lit));
+                                param_refinement_float3
+                                    .clamp(snapping::snap3(float3_lit, increment))
+                            }
+                            None => param_refinement_float3.clamp(float3_lit),
+                        };
+
+                        if float3_value != arg_float3_lit {
+                            change = Some((
+                                stmt_index,
+                                arg_index,
+                                ast::Expr::Lit(ast::LitExpr::Float3(float3_value)),
+                            ));
+                        }
+                    }
+                }
+                ParamRefinement::String(_) => {
+                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+
+                    let string_lit = arg.unwrap_literal().unwrap_string();
+                    imstring_buffer.push_str(string_lit);
+
+                    if ui
+                        .input_text(&input_label, &mut imstring_buffer)
+                        .read_only(interpreter_busy)
+                        .build()
+                    {
+                        let string_value = format!("{}", imstring_buffer);
+                        change = Some((
+                            stmt_index,
+                            arg_index,
+                            ast::Expr::Lit(ast::LitExpr::String(string_value)),
+                        ));
+                    }
+
+                    imstring_buffer.clear();
+                }
+                ParamRefinement::FilePath(param_refinement_file_path) => {
+                    let mut imstring_buffer = self.global_imstring_buffer.borrow_mut();
+
+                    let path_lit = arg.unwrap_literal().unwrap_string();
+                    imstring_buffer.push_str(path_lit);
+
+                    // The validation message is grouped with the input, so
+                    // that the tooltip is shown for both.
+                    let group_token = ui.begin_group();
+                    if file_input(
+                        ui,
+                        &input_label,
+                        param_refinement_file_path.file_ext_filter,
+                        param_defaults.recent_paths(call_expr.ident(), param_index),
+                        &mut imstring_buffer,
+                    ) {
+                        let path_value = format!("{}", imstring_buffer);
+                        change = Some((
+                            stmt_index,
+                            arg_index,
+                            ast::Expr::Lit(ast::LitExpr::String(path_value)),
+                        ));
+                    }
+                    if let Err(err) = param_refinement_file_path.validate(path_lit) {
+                        ui.text_colored(self.colors.log_message_warn, format!("{}", err));
+                    }
+                    group_token.end(ui);
+
+                    imstring_buffer.clear();
+                }
+                ParamRefinement::Mesh => {
+                    let changed_expr =
+                        self.draw_var_combo_box(session, stmt_index, arg, Ty::Mesh, &input_label);
+
+                    if let Some(changed_expr) = changed_expr {
+                        change = Some((stmt_index, arg_index, changed_expr));
+                    }
+                }
+                ParamRefinement::MeshArray => {
+                    let changed_expr = self.draw_var_combo_box(
+                        session,
+                        stmt_index,
+                        arg,
+                        Ty::MeshArray,
+                        &input_label,
+                    );
+
+                    if let Some(changed_expr) = changed_expr {
+                        change = Some((stmt_index, arg_index, changed_expr));
+                    }
+                }
+            }
+
+            if expression_editable
+                && !editing_expression
+                && !interpreter_busy
+                && expression_edit_requested(ui)
+            {
+                if let Some(text) = expression::format_lit(arg.unwrap_literal()) {
+                    let mut imstring = imgui::ImString::with_capacity(EXPRESSION_CAPACITY);
+                    imstring.push_str(&number_format.localize_expression(&text));
+
+                    self.pipeline_window_state.borrow_mut().expression_edit =
+                        Some(ExpressionEdit {
+                            stmt_index,
+                            arg_index,
+                            text: imstring,
+                            error: None,
+                            focus: true,
+                        });
+                }
+            }
+
+            if ui.is_item_hovered() && !param_info.description.is_empty() {
+                ui.tooltip(|| {
+                    let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                    ui.text_colored(self.colors.tooltip_text, param_info.description);
+                    wrap_token.pop(ui);
+                });
+            }
+
+            if let Some(default_lit) = param_info.refinement.default_lit() {
+                let popup_id = imgui::im_str!("Reset##{}-{}", stmt_index, arg_index);
+                if !interpreter_busy && ui.is_item_clicked(imgui::MouseButton::Right) {
+                    ui.open_popup(&popup_id);
+                }
+
+                ui.popup(&popup_id, || {
+                    if imgui::Selectable::new(imgui::im_str!("Reset to default")).build(ui) {
+                        change = Some((stmt_index, arg_index, ast::Expr::Lit(default_lit)));
+                    }
 
-                                    let log_messages = session.log_messages_at_stmt(stmt_index);
-                                    for log_message in log_messages {
-                                        ui.text_colored(match log_message.level {
-                                            LogMessageLevel::Info => self.colors.log_message_info,
-                                            LogMessageLevel::Warn => self.colors.log_message_warn,
-                                            LogMessageLevel::Error => self.colors.log_message_error,
-                                        }, &log_message.message);
-                                    }
+                    // Numeric parameters can be linked in the same popup
+                    if expression_editable {
+                        if let Some(link_change) = self.draw_param_link_menu(session, param) {
+                            *param_link_change = Some(link_change);
+                        }
+                    }
+                });
+            }
 
-                                    let message_count = log_messages.len();
-                                    let mut console_state = self.console_state.borrow_mut();
-                                    if console_state[stmt_index].message_count < message_count {
-                                        ui.set_scroll_here_y();
-                                        console_state[stmt_index].message_count = message_count;
-                                    }
+            if expression_editable {
+                let param_link = session.param_link(param);
+                if param_link.is_some() || session.param_drives_others(param) {
+                    ui.same_line(0.0);
+                    self.draw_link_icon();
 
-                                    wrap_token.pop(ui);
-                                    window_token.end(ui);
+                    if ui.is_item_hovered() {
+                        ui.tooltip(|| {
+                            let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                            match param_link {
+                                Some(param_link) => {
+                                    let source_label = session
+                                        .var_decl_stmt_index_and_var_name_for_ident(
+                                            param_link.source.var_ident,
+                                        )
+                                        .map(|(source_stmt_index, _)| {
+                                            param_label(
+                                                session,
+                                                source_stmt_index,
+                                                param_link.source.arg_index,
+                                            )
+                                        })
+                                        .unwrap_or_default();
+                                    ui.text_colored(
+                                        self.colors.tooltip_text,
+                                        format!(
+                                            "LINKED PARAMETER\n\
+                                            \n\
+                                            Driven by {} \u{d7} {} + {}. Editing this \
+                                            parameter unlinks it.",
+                                            source_label, param_link.scale, param_link.offset,
+                                        ),
+                                    );
                                 }
+                                None => ui.text_colored(
+                                    self.colors.tooltip_text,
+                                    "LINKED PARAMETER\n\
+                                    \n\
+                                    Drives other parameters, which follow its value.",
+                                ),
+                            }
+                            wrap_token.pop(ui);
+                        });
+                    }
+                }
+            }
+        }
 
-                                if let Some((color_token, style_token)) = operation_arg_style_tokens {
-                                    color_token.pop(ui);
-                                    style_token.pop(ui);
-                                }
+        change
+    }
 
-                                ui.unindent();
+    /// Draws the part of the parameter popup for linking the parameter to
+    /// another one, or for changing and removing its link. Returns the
+    /// parameter with its new link, if it changed.
+    fn draw_param_link_menu(
+        &self,
+        session: &Session,
+        param: ParamId,
+    ) -> Option<(ParamId, Option<ParamLink>)> {
+        let ui = &self.imgui_ui;
+        let mut link_change = None;
+
+        ui.separator();
+        match session.param_link(param) {
+            Some(param_link) => {
+                let mut scale = param_link.scale;
+                let mut offset = param_link.offset;
+
+                let scale_changed = ui
+                    .input_float(imgui::im_str!("Scale"), &mut scale)
+                    .enter_returns_true(true)
+                    .build();
+                let offset_changed = ui
+                    .input_float(imgui::im_str!("Offset"), &mut offset)
+                    .enter_returns_true(true)
+                    .build();
+                if (scale_changed || offset_changed) && scale.is_finite() && offset.is_finite() {
+                    link_change = Some((
+                        param,
+                        Some(ParamLink {
+                            scale,
+                            offset,
+                            ..param_link
+                        }),
+                    ));
+                }
+
+                if imgui::Selectable::new(imgui::im_str!("Unlink")).build(ui) {
+                    link_change = Some((param, None));
+                }
+            }
+            None => {
+                ui.menu(imgui::im_str!("Link to"), true, || {
+                    let mut any_source = false;
+                    for (source_stmt_index, stmt) in session.stmts().iter().enumerate() {
+                        let ast::Stmt::VarDecl(var_decl) = stmt;
+                        for source_arg_index in 0..var_decl.init_expr().args().len() {
+                            let source = ParamId {
+                                var_ident: var_decl.ident(),
+                                arg_index: source_arg_index,
+                            };
+                            if !session.can_link_params(param, source) {
+                                continue;
+                            }
+
+                            any_source = true;
+                            let label = param_label(session, source_stmt_index, source_arg_index);
+                            if imgui::Selectable::new(&label).build(ui) {
+                                link_change = Some((param, Some(ParamLink::new(source))));
                             }
                         }
                     }
+
+                    if !any_source {
+                        ui.text_disabled(imgui::im_str!("No compatible parameters"));
+                    }
+                });
+            }
+        }
+
+        link_change
+    }
+
+    /// Draws a chain link icon as a new item on the current line.
+    fn draw_link_icon(&self) {
+        let ui = &self.imgui_ui;
+        let height = ui.frame_height();
+        ui.dummy([LINK_ICON_WIDTH, height]);
+
+        let [x, y] = ui.item_rect_min();
+        let center_y = y + height / 2.0;
+        let link_width = LINK_ICON_WIDTH * 0.6;
+        let link_half_height = LINK_ICON_WIDTH * 0.2;
+
+        // Two rounded links overlapping in the middle
+        let draw_list = ui.get_window_draw_list();
+        for link_x in &[x, x + LINK_ICON_WIDTH - link_width] {
+            draw_list
+                .add_rect(
+                    [*link_x, center_y - link_half_height],
+                    [link_x + link_width, center_y + link_half_height],
+                    self.colors.special_button,
+                )
+                .rounding(link_half_height)
+                .thickness(1.5)
+                .build();
+        }
+    }
+
+    /// Draws a drag for each of the components side by side, followed by the
+    /// label. Unlike `imgui::Drag::build_array`, the components are displayed
+    /// in the number format. Returns whether any of the components changed.
+    fn draw_float_drags(
+        &self,
+        input_label: &imgui::ImStr,
+        components: &mut [f32],
+        min_value: Option<f32>,
+        max_value: Option<f32>,
+        number_format: NumberFormat,
+    ) -> bool {
+        let ui = &self.imgui_ui;
+        let component_count = components.len() as f32;
+        let component_width =
+            (ui.calc_item_width() - ITEM_INNER_SPACING * (component_count - 1.0)) / component_count;
+
+        let mut changed = false;
+        for (component_index, component) in components.iter_mut().enumerate() {
+            if component_index > 0 {
+                ui.same_line_with_spacing(0.0, ITEM_INNER_SPACING);
+            }
+
+            // Imgui always displays a decimal point, so the value is
+            // formatted beforehand and shown as a format without any
+            // conversion specification
+            let component_label = imgui::im_str!("##{}-{}", input_label.to_str(), component_index);
+            let display_format =
+                imgui::im_str!("{}", number_format.format_float(f64::from(*component), 3));
+            let mut drag_float = imgui::Drag::<f32>::new(&component_label)
+                .speed(DRAG_SPEED)
+                .display_format(&display_format);
+
+            match (min_value, max_value) {
+                (Some(min_value), Some(max_value)) => {
+                    drag_float = drag_float.range(min_value..=max_value);
                 }
-                regular_font_token.pop(ui);
+                (Some(min_value), None) => {
+                    drag_float = drag_float.range(min_value..);
+                }
+                (None, Some(max_value)) => {
+                    drag_float = drag_float.range(..=max_value);
+                }
+                (None, None) => (),
+            }
 
-                let mut pipeline_window_state = self.pipeline_window_state.borrow_mut();
-                if pipeline_window_state.autoscroll {
-                    ui.set_scroll_here_y();
-                    pipeline_window_state.autoscroll = false;
+            let width_token = ui.push_item_width(component_width);
+            changed |= drag_float.build(ui, component);
+            width_token.pop(ui);
+        }
+
+        ui.same_line_with_spacing(0.0, ITEM_INNER_SPACING);
+        ui.text(input_label.to_str().split("##").next().unwrap_or_default());
+
+        changed
+    }
+
+    /// Draws the text input of a numeric parameter typed as a math
+    /// expression. Returns the literal of the expression, once it is
+    /// committed with enter and evaluates. Invalid expressions are
+    /// underlined and keep the input open, leaving it cancels the edit.
+    fn draw_expression_input(
+        &self,
+        input_label: &imgui::ImStr,
+        refinement: &ParamRefinement,
+        number_format: NumberFormat,
+    ) -> Option<ast::LitExpr> {
+        let ui = &self.imgui_ui;
+        let mut pipeline_window_state = self.pipeline_window_state.borrow_mut();
+        let edit = pipeline_window_state
+            .expression_edit
+            .as_mut()
+            .expect("Expression edit must be in progress");
+
+        if edit.focus {
+            ui.set_keyboard_focus_here(imgui::FocusedWidget::Next);
+            edit.focus = false;
+        }
+
+        let input_width = ui.calc_item_width();
+        let committed = ui
+            .input_text(input_label, &mut edit.text)
+            .enter_returns_true(true)
+            .build();
+
+        if let Some(error) = &edit.error {
+            let [min_x, _] = ui.item_rect_min();
+            let [_, max_y] = ui.item_rect_max();
+            ui.get_window_draw_list()
+                .add_line(
+                    [min_x, max_y],
+                    [min_x + input_width, max_y],
+                    self.colors.log_message_error,
+                )
+                .thickness(2.0)
+                .build();
+
+            if ui.is_item_hovered() {
+                ui.tooltip(|| {
+                    ui.text_colored(self.colors.log_message_error, error);
+                });
+            }
+        }
+
+        if committed {
+            let component_count =
+                expression::component_count(refinement).expect("Parameter must be numeric");
+            let text = number_format.delocalize_expression(edit.text.to_str(), component_count);
+
+            match expression::evaluate_lit(&text, refinement) {
+                Ok(lit) => {
+                    pipeline_window_state.expression_edit = None;
+                    return Some(lit);
                 }
-            });
-        bold_font_token.pop(ui);
+                Err(err) => {
+                    // Enter deactivates the input, so it is focused again
+                    // for fixing the expression
+                    edit.error = Some(err.to_string());
+                    edit.focus = true;
+                }
+            }
+        } else if ui.is_item_deactivated() {
+            pipeline_window_state.expression_edit = None;
+        }
 
-        let changed = change.is_some();
+        None
+    }
 
-        // FIXME: Debounce changes to parameters
+    /// Draws the options of a choice parameter as a combo box. Returns the
+    /// index of the newly chosen option, if any.
+    fn draw_param_choice(
+        &self,
+        input_label: &imgui::ImStr,
+        choices: &[&str],
+        choice_index: u32,
+    ) -> Option<u32> {
+        let ui = &self.imgui_ui;
+        let mut new_choice_index = None;
 
-        // Only submit the change if interpreter is not busy. Not all
-        // imgui components can be made read-only, so we can not trust
-        // it.
-        if !interpreter_busy {
-            if let Some((stmt_index, arg_index, expr)) = change {
-                let stmt = &session.stmts()[stmt_index];
-                match stmt {
-                    ast::Stmt::VarDecl(var_decl) => {
-                        let init_expr = var_decl.init_expr();
-                        let new_var_decl = var_decl
-                            .clone_with_init_expr(init_expr.clone_with_arg_at(arg_index, expr));
+        let preview_value = choices
+            .get(cast_usize(choice_index))
+            .copied()
+            .unwrap_or("<Select one option>");
 
-                        session.set_prog_stmt_at(
-                            current_time,
-                            stmt_index,
-                            ast::Stmt::VarDecl(new_var_decl),
-                        );
-                    }
+        let combo_box_color_token = self.push_combo_box_colors();
+        if let Some(combo_token) = imgui::ComboBox::new(input_label)
+            .preview_value(&imgui::ImString::new(preview_value))
+            .begin(ui)
+        {
+            for (index, choice) in choices.iter().enumerate() {
+                if imgui::Selectable::new(&imgui::ImString::new(*choice))
+                    .selected(index == cast_usize(choice_index))
+                    .build(ui)
+                {
+                    new_choice_index = Some(cast_u32(index));
                 }
             }
+
+            combo_token.end(ui);
         }
+        combo_box_color_token.pop(ui);
 
-        changed
+        new_choice_index
+    }
+
+    /// Draws the buttons adding and removing inputs of the variadic
+    /// parameter of the operation, if it has one. Returns the statement index
+    /// and the new arguments, if an input was added or removed.
+    fn draw_variadic_param_buttons(
+        &self,
+        stmt_index: usize,
+        call_expr: &ast::CallExpr,
+        func: &dyn Func,
+    ) -> Option<(usize, Vec<ast::Expr>)> {
+        let variadic_param_index = func.variadic_param_index()?;
+        let ui = &self.imgui_ui;
+        let args = call_expr.args();
+        let variadic_arg_count = args.len() + 1 - func.param_info().len();
+        let last_variadic_arg_index = variadic_param_index + variadic_arg_count - 1;
+        let mut change = None;
+
+        if ui.button(&imgui::im_str!("Add input##{}", stmt_index), [0.0, 0.0]) {
+            // The new input starts as a copy of the last one, which is
+            // always visible to the statement
+            let mut new_args = args.to_vec();
+            new_args.insert(
+                last_variadic_arg_index + 1,
+                args[last_variadic_arg_index].clone(),
+            );
+            change = Some((stmt_index, new_args));
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip(|| {
+                let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                ui.text_colored(
+                    self.colors.tooltip_text,
+                    "ADD INPUT\n\
+                \n\
+                Adds another input to this operation, initially the same as the last one.",
+                );
+                wrap_token.pop(ui);
+            });
+        }
+
+        ui.same_line(0.0);
+
+        let remove_disabled = variadic_arg_count <= 1;
+        let remove_button_tokens = if remove_disabled {
+            Some(push_disabled_style(ui))
+        } else {
+            None
+        };
+        let remove = ui.button(&imgui::im_str!("Remove input##{}", stmt_index), [0.0, 0.0]);
+        if let Some((color_token, style_token)) = remove_button_tokens {
+            color_token.pop(ui);
+            style_token.pop(ui);
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip(|| {
+                let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                ui.text_colored(
+                    self.colors.tooltip_text,
+                    "REMOVE INPUT\n\
+                \n\
+                Removes the last input of this operation.",
+                );
+                wrap_token.pop(ui);
+            });
+        }
+
+        if remove && !remove_disabled {
+            let mut new_args = args.to_vec();
+            new_args.remove(last_variadic_arg_index);
+            change = Some((stmt_index, new_args));
+        }
+
+        change
     }
 
     pub fn draw_operations_window(
@@ -1772,7 +8503,8 @@ impl<'a> UiFrame<'a> {
         current_time: Instant,
         session: &mut Session,
         notifications: &mut Notifications,
-        duration_autorun_delay: Duration,
+        autorun: &mut AutorunPreferences,
+        param_defaults: &ParamDefaults,
     ) -> bool {
         let ui = &self.imgui_ui;
         let function_table = session.function_table();
@@ -1790,10 +8522,9 @@ impl<'a> UiFrame<'a> {
 
         let mut function_clicked = None;
         let mut interpret_clicked = false;
+        let mut cancel_clicked = false;
         let mut pop_stmt_clicked = false;
-
-        let mut autorun_enabled = session.autorun_delay().is_some();
-        let mut autorun_clicked = false;
+        let mut draft_changed = None;
 
         let bold_font_token = ui.push_font(self.font_ids.bold);
         imgui::Window::new(imgui::im_str!("Operations"))
@@ -1838,15 +8569,21 @@ impl<'a> UiFrame<'a> {
                     (imgui::StyleColor::Text, self.colors.special_button_text),
                     (imgui::StyleColor::TextDisabled, self.colors.special_button_text),
                 ]);
-                let running_tokens = if running_enabled {
+                // While running, the run button turns into a cancel button.
+                let running = session.interpreter_busy();
+                let running_tokens = if running_enabled || running {
                     None
                 } else {
                     Some(push_disabled_style(ui))
                 };
 
                 let bold_font_token = ui.push_font(self.font_ids.bold);
-                if ui.button(
-                    imgui::im_str!("Run (Enter)"),
+                if running {
+                    if ui.button(imgui::im_str!("Cancel run"), [-f32::MIN_POSITIVE, 25.0]) {
+                        cancel_clicked = true;
+                    }
+                } else if ui.button(
+                    imgui::im_str!("Run (Enter / F5)"),
                     [-f32::MIN_POSITIVE, 25.0],
                 ) && running_enabled
                 {
@@ -1859,7 +8596,18 @@ impl<'a> UiFrame<'a> {
                 }
                 pipeline_button_color_token.pop(ui);
 
-                if ui.is_item_hovered() {
+                if ui.is_item_hovered() && running {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "CANCEL RECOMPUTATION OF THE OPERATION PIPELINE\n\
+                        \n\
+                        Stops the computation before the next operation. Operations producing \
+                        mesh groups, such as Disjoint Mesh, show their meshes in the viewport \
+                        as they are generated and stop right away, once you have seen enough. \
+                        The cancelled operation is reported as failed.");
+                        wrap_token.pop(ui);
+                    });
+                } else if ui.is_item_hovered() {
                     ui.tooltip(|| {
                         let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
                         ui.text_colored(self.colors.tooltip_text, "RUN RECOMPUTATION OF THE OPERATION PIPELINE\n\
@@ -1870,8 +8618,9 @@ impl<'a> UiFrame<'a> {
                         the computation stops and the error will be reported in the console log of the \
                         respective operation.");
                         ui.text_colored(self.colors.log_message_warn,"\n\
-                        WARNING: The execution cannot be stopped. If it takes long time or crashes, \
-                        the unsaved progress of the .hurban project file will be lost!");
+                        WARNING: Most operations cannot be interrupted, the execution can only be \
+                        cancelled between them. If it crashes, the unsaved progress of the .hurban \
+                        project file will be lost!");
                         wrap_token.pop(ui);
                     });
                 }
@@ -1919,8 +8668,7 @@ impl<'a> UiFrame<'a> {
                 }
 
                 ui.columns(1, imgui::im_str!("Autorun columns"), false);
-                autorun_clicked =
-                    ui.checkbox(imgui::im_str!("Run automatically"), &mut autorun_enabled);
+                ui.checkbox(imgui::im_str!("Run automatically"), &mut autorun.enabled);
 
                     if ui.is_item_hovered() {
                         ui.tooltip(|| {
@@ -1933,12 +8681,30 @@ impl<'a> UiFrame<'a> {
                             ui.text_colored(self.colors.log_message_warn, "\n\
                             WARNING: The execution may take long or even hang the computer! If \
                             not sure how heavy is the geometry, turn the automatic recomputation off. \
-                            The execution cannot be stopped. If it takes long time or crashes, \
-                            the unsaved progress of the .hurban project file will be lost!");
+                            Most operations cannot be interrupted, the execution can only be cancelled \
+                            between them. If it crashes, the unsaved progress of the .hurban project \
+                            file will be lost!");
                             wrap_token.pop(ui);
                         });
                     }
 
+                let mut draft = session.draft();
+                if ui.checkbox(imgui::im_str!("Draft quality"), &mut draft) {
+                    draft_changed = Some(draft);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip(|| {
+                        let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
+                        ui.text_colored(self.colors.tooltip_text, "DRAFT QUALITY\n\
+                        \n\
+                        Runs the operations with cheaper settings, e.g. with twice as large \
+                        voxels or fewer smoothing iterations, to keep editing responsive. \
+                        Exports and screenshots always run the pipeline in full quality \
+                        first.");
+                        wrap_token.pop(ui);
+                    });
+                }
+
                 ui.separator();
 
                 let pushing_tokens = if pushing_enabled {
@@ -1972,6 +8738,9 @@ impl<'a> UiFrame<'a> {
                         );
                     }
 
+                    if ui.is_item_hovered() {
+                        self.help_window_state.borrow_mut().func_ident = Some(*func_ident);
+                    }
                     if ui.is_item_hovered() && !func.info().description.is_empty() {
                         ui.tooltip(|| {
                             let wrap_token = ui.push_text_wrap_pos(WRAP_POS_TOOLTIP_TEXT_PIXELS);
@@ -1997,86 +8766,24 @@ impl<'a> UiFrame<'a> {
         let function_added = function_clicked.is_some();
 
         if let Some(func_ident) = function_clicked {
-            let func = &function_table[&func_ident];
-            let mut args = Vec::with_capacity(func.param_info().len());
-
-            for param_info in func.param_info() {
-                let expr = match param_info.refinement {
-                    ParamRefinement::Boolean(boolean_refinement) => {
-                        ast::Expr::Lit(ast::LitExpr::Boolean(boolean_refinement.default_value))
-                    }
-                    ParamRefinement::Int(int_param_refinement) => ast::Expr::Lit(
-                        ast::LitExpr::Int(int_param_refinement.default_value.unwrap_or_default()),
-                    ),
-                    ParamRefinement::Uint(uint_param_refinement) => ast::Expr::Lit(
-                        ast::LitExpr::Uint(uint_param_refinement.default_value.unwrap_or_default()),
-                    ),
-                    ParamRefinement::Float(float_param_refinement) => {
-                        ast::Expr::Lit(ast::LitExpr::Float(
-                            float_param_refinement.default_value.unwrap_or_default(),
-                        ))
-                    }
-                    ParamRefinement::Float2(float2_param_refinement) => {
-                        ast::Expr::Lit(ast::LitExpr::Float2([
-                            float2_param_refinement.default_value_x.unwrap_or_default(),
-                            float2_param_refinement.default_value_y.unwrap_or_default(),
-                        ]))
-                    }
-                    ParamRefinement::Float3(float3_param_refinement) => {
-                        ast::Expr::Lit(ast::LitExpr::Float3([
-                            float3_param_refinement.default_value_x.unwrap_or_default(),
-                            float3_param_refinement.default_value_y.unwrap_or_default(),
-                            float3_param_refinement.default_value_z.unwrap_or_default(),
-                        ]))
-                    }
-                    ParamRefinement::String(string_param_refinement) => {
-                        let initial_value = String::from(string_param_refinement.default_value);
-                        ast::Expr::Lit(ast::LitExpr::String(initial_value))
-                    }
-                    ParamRefinement::Mesh => {
-                        let one_past_last_stmt = session.stmts().len();
-                        let visible_vars_iter =
-                            session.visible_vars_at_stmt(one_past_last_stmt, Ty::Mesh);
-
-                        if visible_vars_iter.clone().count() == 0 {
-                            ast::Expr::Lit(ast::LitExpr::Nil)
-                        } else {
-                            let last = visible_vars_iter
-                                .last()
-                                .expect("Need at least one variable to provide default value");
-
-                            ast::Expr::Var(ast::VarExpr::new(last))
-                        }
-                    }
-                    ParamRefinement::MeshArray => {
-                        let one_past_last_stmt = session.stmts().len();
-                        let visible_vars_iter =
-                            session.visible_vars_at_stmt(one_past_last_stmt, Ty::MeshArray);
-
-                        if visible_vars_iter.clone().count() == 0 {
-                            ast::Expr::Lit(ast::LitExpr::Nil)
-                        } else {
-                            let last = visible_vars_iter
-                                .last()
-                                .expect("Need at least one variable to provide default value");
-
-                            ast::Expr::Var(ast::VarExpr::new(last))
-                        }
-                    }
-                };
-
-                args.push(expr);
-            }
-
+            let mut args = default_args(session, *func_ident);
+            param_defaults.apply(*func_ident, &mut args);
             let init_expr = ast::CallExpr::new(*func_ident, args);
-            let stmt = ast::Stmt::VarDecl(ast::VarDeclStmt::new(
-                session
-                    .next_free_var_ident()
-                    .expect("Failed to find free variable identifier"),
-                init_expr,
-            ));
+            let var_ident = session
+                .next_free_var_ident()
+                .expect("Failed to find free variable identifier");
+            let stmt = ast::Stmt::VarDecl(ast::VarDeclStmt::new(var_ident, init_expr));
 
             session.push_prog_stmt(current_time, stmt);
+
+            // New voxel operations follow the project voxel size until their
+            // own voxel size is edited.
+            if session.function_table()[func_ident]
+                .voxel_size_param_index()
+                .is_some()
+            {
+                session.set_var_inherits_voxel_size(current_time, var_ident, true);
+            }
             self.pipeline_window_state.borrow_mut().autoscroll = true;
         }
 
@@ -2089,15 +8796,24 @@ impl<'a> UiFrame<'a> {
             session.interpret();
         }
 
+        if cancel_clicked {
+            notifications.push(
+                current_time,
+                NotificationLevel::Info,
+                "Cancelling execution of the Operation pipeline...",
+            );
+            session.cancel_interpret();
+        }
+
         if pop_stmt_clicked {
             session.pop_prog_stmt(current_time);
         }
 
-        if autorun_clicked {
-            if autorun_enabled {
-                session.set_autorun_delay(Some(duration_autorun_delay));
-            } else {
-                session.set_autorun_delay(None);
+        // Switching draft mode edits the interpreter, so it has to wait
+        // until the interpreter is idle.
+        if let Some(draft) = draft_changed {
+            if !session.interpreter_busy() {
+                session.set_draft(current_time, draft);
             }
         }
 
@@ -2215,6 +8931,65 @@ impl<'a> UiFrame<'a> {
     }
 }
 
+/// Returns the default arguments for calling the func, as if added at the end
+/// of the pipeline. Mesh and mesh array arguments reference the last visible
+/// variable of the type, if any.
+pub fn default_args(session: &Session, func_ident: ast::FuncIdent) -> Vec<ast::Expr> {
+    let func = &session.function_table()[&func_ident];
+    let mut args = Vec::with_capacity(func.param_info().len());
+
+    for param_info in func.param_info() {
+        let expr = match param_info.refinement {
+            ParamRefinement::Boolean(_)
+            | ParamRefinement::Int(_)
+            | ParamRefinement::Uint(_)
+            | ParamRefinement::Float(_)
+            | ParamRefinement::Float2(_)
+            | ParamRefinement::Float3(_)
+            | ParamRefinement::String(_)
+            | ParamRefinement::FilePath(_) => ast::Expr::Lit(
+                param_info
+                    .refinement
+                    .default_lit()
+                    .expect("Literal parameters must have a default value"),
+            ),
+            ParamRefinement::Mesh => {
+                let one_past_last_stmt = session.stmts().len();
+                let visible_vars_iter = session.visible_vars_at_stmt(one_past_last_stmt, Ty::Mesh);
+
+                if visible_vars_iter.clone().count() == 0 {
+                    ast::Expr::Lit(ast::LitExpr::Nil)
+                } else {
+                    let last = visible_vars_iter
+                        .last()
+                        .expect("Need at least one variable to provide default value");
+
+                    ast::Expr::Var(ast::VarExpr::new(last))
+                }
+            }
+            ParamRefinement::MeshArray => {
+                let one_past_last_stmt = session.stmts().len();
+                let visible_vars_iter =
+                    session.visible_vars_at_stmt(one_past_last_stmt, Ty::MeshArray);
+
+                if visible_vars_iter.clone().count() == 0 {
+                    ast::Expr::Lit(ast::LitExpr::Nil)
+                } else {
+                    let last = visible_vars_iter
+                        .last()
+                        .expect("Need at least one variable to provide default value");
+
+                    ast::Expr::Var(ast::VarExpr::new(last))
+                }
+            }
+        };
+
+        args.push(expr);
+    }
+
+    args
+}
+
 fn format_var_name(
     var_decl_stmt_index: usize,
     var_name: &str,
@@ -2227,6 +9002,183 @@ fn format_var_name(
     }
 }
 
+/// Returns the numeric parameters of the pipeline, together with labels
+/// identifying the operation and the parameter.
+fn numeric_param_labels(session: &Session) -> Vec<(ParamRef, imgui::ImString)> {
+    sweep::numeric_params(session.stmts())
+        .map(|param| {
+            (
+                param,
+                param_label(session, param.stmt_index, param.arg_index),
+            )
+        })
+        .collect()
+}
+
+/// Returns a label of the parameter, which tells it apart from the parameters
+/// of other statements.
+fn param_label(session: &Session, stmt_index: usize, arg_index: usize) -> imgui::ImString {
+    let ast::Stmt::VarDecl(var_decl) = &session.stmts()[stmt_index];
+    let func = &session.function_table()[&var_decl.init_expr().ident()];
+
+    imgui::im_str!(
+        "#{} {}: {}",
+        stmt_index + 1,
+        func.info().name,
+        func.param_info()[func
+            .param_index_for_arg(arg_index, var_decl.init_expr().args().len())
+            .expect("Function call must have correct number of arguments")]
+        .name,
+    )
+}
+
+/// Returns a range around the current value of the parameter, as a starting
+/// point for the user.
+fn initial_param_range(stmts: &[ast::Stmt], param: ParamRef) -> [f32; 2] {
+    let value = sweep::numeric_param_value(stmts, param);
+    let spread = if value == 0.0 { 1.0 } else { value.abs() / 2.0 };
+
+    [value - spread, value + spread]
+}
+
+/// Submits the change of an operation argument, unless the interpreter is
+/// busy. Changed literal values are remembered as defaults for new
+/// operations, changed file paths as recently used files. Returns whether
+/// there was a change.
+fn submit_operation_arg_change(
+    current_time: Instant,
+    session: &mut Session,
+    param_defaults: &mut ParamDefaults,
+    change: Option<(usize, usize, ast::Expr)>,
+) -> bool {
+    // Operations on locked layers are drawn disabled, but not all imgui
+    // components can be made read-only, so their changes are dropped
+    // here.
+    let change = change.filter(|(stmt_index, _, _)| {
+        let ast::Stmt::VarDecl(var_decl) = &session.stmts()[*stmt_index];
+        !session.layers().var_locked(var_decl.ident())
+    });
+    let changed = change.is_some();
+
+    // FIXME: Debounce changes to parameters
+
+    // Only submit the change if interpreter is not busy. Not all
+    // imgui components can be made read-only, so we can not trust
+    // it.
+    if !session.interpreter_busy() {
+        if let Some((stmt_index, arg_index, expr)) = change {
+            let stmt = &session.stmts()[stmt_index];
+            match stmt {
+                ast::Stmt::VarDecl(var_decl) => {
+                    let var_ident = var_decl.ident();
+                    let init_expr = var_decl.init_expr();
+                    let func = &session.function_table()[&init_expr.ident()];
+                    let param_index = func.param_index_for_arg(arg_index, init_expr.args().len());
+                    // Editing the voxel size overrides the project voxel size
+                    let voxel_size_overridden =
+                        param_index.is_some() && param_index == func.voxel_size_param_index();
+
+                    if let ast::Expr::Lit(lit) = &expr {
+                        if let Some(param_index) = param_index {
+                            match (func.param_info()[param_index].refinement, lit) {
+                                (ParamRefinement::FilePath(_), ast::LitExpr::String(path)) => {
+                                    param_defaults.remember_recent_path(
+                                        init_expr.ident(),
+                                        param_index,
+                                        path,
+                                    );
+                                }
+                                _ => param_defaults.remember(init_expr.ident(), param_index, lit),
+                            }
+                        }
+                    }
+
+                    let new_var_decl =
+                        var_decl.clone_with_init_expr(init_expr.clone_with_arg_at(arg_index, expr));
+
+                    // Editing a driven parameter unlinks it, before the new
+                    // value is overwritten by the link
+                    let param = ParamId {
+                        var_ident,
+                        arg_index,
+                    };
+                    if session.param_link(param).is_some() {
+                        session.set_param_link(current_time, param, None);
+                    }
+
+                    session.set_prog_stmt_at(
+                        current_time,
+                        stmt_index,
+                        ast::Stmt::VarDecl(new_var_decl),
+                    );
+                    if voxel_size_overridden {
+                        session.set_var_inherits_voxel_size(current_time, var_ident, false);
+                    }
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+/// Submits the new link of a parameter, unless the interpreter is busy.
+/// Returns whether there was a change.
+fn submit_param_link_change(
+    current_time: Instant,
+    session: &mut Session,
+    change: Option<(ParamId, Option<ParamLink>)>,
+) -> bool {
+    let change = change.filter(|(param, _)| !session.layers().var_locked(param.var_ident));
+    let changed = change.is_some();
+
+    // Linking rewrites the driven argument, so it can only be done while the
+    // interpreter is idle.
+    if !session.interpreter_busy() {
+        if let Some((param, param_link)) = change {
+            session.set_param_link(current_time, param, param_link);
+        }
+    }
+
+    changed
+}
+
+/// Submits the new arguments of an operation, e.g. with an input added or
+/// removed, unless the interpreter is busy. Returns whether there was a
+/// change.
+fn submit_operation_args_change(
+    current_time: Instant,
+    session: &mut Session,
+    change: Option<(usize, Vec<ast::Expr>)>,
+) -> bool {
+    let change = change.filter(|(stmt_index, _)| {
+        let ast::Stmt::VarDecl(var_decl) = &session.stmts()[*stmt_index];
+        !session.layers().var_locked(var_decl.ident())
+    });
+    let changed = change.is_some();
+
+    if !session.interpreter_busy() {
+        if let Some((stmt_index, args)) = change {
+            let ast::Stmt::VarDecl(var_decl) = &session.stmts()[stmt_index];
+            let init_expr = ast::CallExpr::new(var_decl.init_expr().ident(), args);
+            let new_var_decl = var_decl.clone_with_init_expr(init_expr);
+
+            session.set_prog_stmt_at(current_time, stmt_index, ast::Stmt::VarDecl(new_var_decl));
+        }
+    }
+
+    changed
+}
+
+/// Whether the last item was double-clicked or ctrl+clicked, which starts
+/// typing a numeric parameter as a math expression. Imgui uses the same
+/// gestures for typing plain numbers into drags.
+fn expression_edit_requested(ui: &imgui::Ui) -> bool {
+    ui.is_item_hovered()
+        && (ui.is_mouse_double_clicked(imgui::MouseButton::Left)
+            || (ui.io().key_ctrl && ui.is_mouse_clicked(imgui::MouseButton::Left)))
+}
+
 fn push_disabled_style(ui: &imgui::Ui) -> (imgui::ColorStackToken, imgui::StyleStackToken) {
     let button_color = ui.style_color(imgui::StyleColor::Button);
     let text_color = ui.style_color(imgui::StyleColor::TextDisabled);
@@ -2246,14 +9198,14 @@ fn file_input(
     ui: &imgui::Ui,
     label: &imgui::ImStr,
     file_ext_filter: Option<(&[&str], &str)>,
+    recent_paths: &[String],
     buffer: &mut imgui::ImString,
 ) -> bool {
     use std::env;
-    use std::path::Path;
 
     let open_button_label = imgui::im_str!("Open##{}", label);
     let open_button_width = ui.calc_text_size(&open_button_label, true, 50.0)[0] + 8.0;
-    let input_position = open_button_width + 2.0; // Padding
+    let mut input_position = open_button_width + 2.0; // Padding
 
     let mut changed = false;
 
@@ -2264,6 +9216,7 @@ fn file_input(
             tinyfiledialogs::open_file_dialog("Open", "", file_ext_filter)
         {
             buffer.clear();
+            changed = true;
 
             let current_dir = env::current_dir().expect("Couldn't get current dir");
             let absolute_path = Path::new(&absolute_path_string);
@@ -2279,8 +9232,27 @@ fn file_input(
                 }
             }
         }
+    }
+
+    if !recent_paths.is_empty() {
+        ui.same_line(input_position);
+        input_position += ui.frame_height() + 2.0; // Padding
+
+        let combo_box_label = imgui::im_str!("##Recent{}", label);
+        if let Some(combo_token) = imgui::ComboBox::new(&combo_box_label)
+            .flags(imgui::ComboBoxFlags::NO_PREVIEW)
+            .begin(ui)
+        {
+            for recent_path in recent_paths {
+                if imgui::Selectable::new(&imgui::ImString::new(recent_path)).build(ui) {
+                    buffer.clear();
+                    buffer.push_str(recent_path);
+                    changed = true;
+                }
+            }
 
-        changed = true;
+            combo_token.end(ui);
+        }
     }
 
     ui.same_line(input_position);
@@ -0,0 +1,493 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use nalgebra::Point3;
+
+use crate::box_drawing::BoxDrawing;
+use crate::clipboard::Clipboard;
+use crate::find_replace::{self, Replacement};
+use crate::interpreter::ast;
+use crate::interpreter_funcs;
+use crate::library::LibraryAsset;
+use crate::notifications::{NotificationLevel, Notifications};
+use crate::param_links::ParamId;
+use crate::pipeline_text;
+use crate::placement;
+use crate::project::ProjectStatus;
+use crate::remote::{self, RemoteCommand};
+use crate::session::Session;
+use crate::sketch::{self, FootprintSketch};
+use crate::ui;
+use crate::units::Unit;
+use crate::window::change_window_title;
+
+/// Appends default arguments to calls saved with fewer arguments than their
+/// funcs currently take, so that projects saved before a func gained new
+/// trailing params still load.
+///
+/// Boolean arguments of params that have since become a choice of two
+/// options, such as the normal strategy, are converted to the index of the
+/// matching option.
+pub fn complete_missing_args(session: &Session, stmts: Vec<ast::Stmt>) -> Vec<ast::Stmt> {
+    stmts
+        .into_iter()
+        .map(|stmt| {
+            let ast::Stmt::VarDecl(var_decl) = stmt;
+            let init_expr = var_decl.init_expr();
+            let func_ident = init_expr.ident();
+            if !session.function_table().contains_key(&func_ident) {
+                return ast::Stmt::VarDecl(var_decl);
+            }
+
+            let mut args = init_expr.args().to_vec();
+            let default_args = ui::default_args(session, func_ident);
+            let arg_count = args.len();
+            if arg_count < default_args.len() {
+                args.extend(default_args.into_iter().skip(arg_count));
+            }
+
+            let func = &session.function_table()[&func_ident];
+            let arg_count = args.len();
+            for (arg_index, arg) in args.iter_mut().enumerate() {
+                let is_two_option_choice = func
+                    .param_index_for_arg(arg_index, arg_count)
+                    .and_then(|param_index| func.param_info()[param_index].widget.choices())
+                    .map_or(false, |choices| choices.len() == 2);
+
+                if let ast::Expr::Lit(ast::LitExpr::Boolean(boolean)) = *arg {
+                    if is_two_option_choice {
+                        *arg = ast::Expr::Lit(ast::LitExpr::Uint(u32::from(boolean)));
+                    }
+                }
+            }
+
+            let init_expr = ast::CallExpr::new(func_ident, args);
+            ast::Stmt::VarDecl(var_decl.clone_with_init_expr(init_expr))
+        })
+        .collect()
+}
+
+/// Adds an Import OBJ of the library mesh file and a Transform moving the
+/// imported mesh to the position to the pipeline.
+pub fn place_library_mesh(
+    time: Instant,
+    session: &mut Session,
+    path: &str,
+    position: &Point3<f32>,
+    rotation: f32,
+) -> Result<(), String> {
+    if session.interpreter_busy() {
+        return Err(String::from(
+            "Objects can not be placed while the pipeline is running",
+        ));
+    }
+
+    let import_func_ident = interpreter_funcs::FUNC_ID_IMPORT_OBJ_JOIN;
+    let import_var_ident = session
+        .next_free_var_ident()
+        .ok_or_else(|| String::from("Failed to find free variable identifier"))?;
+    let import_args = placement::import_args(ui::default_args(session, import_func_ident), path);
+
+    let transform_func_ident = interpreter_funcs::FUNC_ID_TRANSFORM;
+    let transform_var_ident = session
+        .next_free_var_ident()
+        .ok_or_else(|| String::from("Failed to find free variable identifier"))?;
+    let transform_args = placement::transform_args(
+        ui::default_args(session, transform_func_ident),
+        import_var_ident,
+        position,
+        rotation,
+    );
+
+    session.push_prog_stmt(
+        time,
+        ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+            import_var_ident,
+            ast::CallExpr::new(import_func_ident, import_args),
+        )),
+    );
+    session.push_prog_stmt(
+        time,
+        ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+            transform_var_ident,
+            ast::CallExpr::new(transform_func_ident, transform_args),
+        )),
+    );
+
+    Ok(())
+}
+
+/// Adds a Create Box of the box drawn in the viewport to the pipeline.
+pub fn add_drawn_box(
+    time: Instant,
+    session: &mut Session,
+    drawing: &BoxDrawing,
+) -> Result<(), String> {
+    if session.interpreter_busy() {
+        return Err(String::from(
+            "Boxes can not be added while the pipeline is running",
+        ));
+    }
+
+    let func_ident = interpreter_funcs::FUNC_ID_CREATE_BOX;
+    let args = drawing
+        .create_box_args(ui::default_args(session, func_ident))
+        .ok_or_else(|| String::from("Pull the box up to a height before adding it"))?;
+
+    let stmt = ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+        session
+            .next_free_var_ident()
+            .ok_or_else(|| String::from("Failed to find free variable identifier"))?,
+        ast::CallExpr::new(func_ident, args),
+    ));
+    session.push_prog_stmt(time, stmt);
+
+    Ok(())
+}
+
+/// Adds a Create Polygon of the footprint sketched in the viewport and an
+/// Extrude extruding it to the height to the pipeline.
+pub fn add_sketched_footprint(
+    time: Instant,
+    session: &mut Session,
+    sketch: &FootprintSketch,
+    height: f32,
+) -> Result<(), String> {
+    if session.interpreter_busy() {
+        return Err(String::from(
+            "Footprints can not be added while the pipeline is running",
+        ));
+    }
+
+    let polygon_func_ident = interpreter_funcs::FUNC_ID_CREATE_POLYGON;
+    let polygon_args = sketch
+        .create_polygon_args(ui::default_args(session, polygon_func_ident))
+        .ok_or_else(|| String::from("The footprint needs at least 3 points enclosing an area"))?;
+    let polygon_var_ident = session
+        .next_free_var_ident()
+        .ok_or_else(|| String::from("Failed to find free variable identifier"))?;
+
+    let extrude_func_ident = interpreter_funcs::FUNC_ID_EXTRUDE;
+    let extrude_var_ident = session
+        .next_free_var_ident()
+        .ok_or_else(|| String::from("Failed to find free variable identifier"))?;
+    let extrude_args = sketch::extrude_args(
+        ui::default_args(session, extrude_func_ident),
+        polygon_var_ident,
+        height,
+    );
+
+    session.push_prog_stmt(
+        time,
+        ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+            polygon_var_ident,
+            ast::CallExpr::new(polygon_func_ident, polygon_args),
+        )),
+    );
+    session.push_prog_stmt(
+        time,
+        ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+            extrude_var_ident,
+            ast::CallExpr::new(extrude_func_ident, extrude_args),
+        )),
+    );
+
+    Ok(())
+}
+
+/// Replaces the values of the parameters found by find and replace. Replaced
+/// parameters driven by other parameters are unlinked, as if edited by hand.
+pub fn replace_param_values(
+    time: Instant,
+    session: &mut Session,
+    replacements: &[Replacement],
+) -> Result<(), String> {
+    if session.interpreter_busy() {
+        return Err(String::from(
+            "Values can not be replaced while the pipeline is running",
+        ));
+    }
+
+    for replacement in replacements {
+        let ast::Stmt::VarDecl(var_decl) = &session.stmts()[replacement.stmt_index];
+        let param = ParamId {
+            var_ident: var_decl.ident(),
+            arg_index: replacement.arg_index,
+        };
+        if session.param_link(param).is_some() {
+            session.set_param_link(time, param, None);
+        }
+    }
+
+    let mut stmt_indices: Vec<_> = replacements
+        .iter()
+        .map(|replacement| replacement.stmt_index)
+        .collect();
+    stmt_indices.dedup();
+
+    // Each statement is replaced from its current version, because replacing
+    // a statement can update parameters of later statements linked to it
+    for stmt_index in stmt_indices {
+        if let Some(stmt) = find_replace::replaced_stmt(session.stmts(), stmt_index, replacements) {
+            session.set_prog_stmt_at(time, stmt_index, stmt);
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds a Library Mesh of the asset to the pipeline, scaled from meters to
+/// the project units.
+pub fn insert_library_asset(
+    time: Instant,
+    session: &mut Session,
+    asset: &LibraryAsset,
+    project_unit: Unit,
+) -> Result<(), String> {
+    let func_ident = interpreter_funcs::FUNC_ID_LIBRARY_MESH;
+    let mut args = ui::default_args(session, func_ident);
+    args[interpreter_funcs::LIBRARY_MESH_PARAM_INDEX_ASSET] =
+        ast::Expr::Lit(ast::LitExpr::String(asset.id.to_string()));
+    args[interpreter_funcs::LIBRARY_MESH_PARAM_INDEX_UNIT_SCALE] = ast::Expr::Lit(
+        ast::LitExpr::Float(Unit::Meter.conversion_factor(project_unit)),
+    );
+
+    let stmt = ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+        session
+            .next_free_var_ident()
+            .ok_or_else(|| String::from("Failed to find free variable identifier"))?,
+        ast::CallExpr::new(func_ident, args),
+    ));
+    session.push_prog_stmt(time, stmt);
+
+    Ok(())
+}
+
+/// Appends an operation importing the OBJ file to the pipeline.
+pub fn import_obj_file(time: Instant, session: &mut Session, path: &Path) -> Result<(), String> {
+    if session.interpreter_busy() {
+        return Err(String::from(
+            "Can't import while the pipeline is running, try again once it finishes",
+        ));
+    }
+
+    let func_ident = interpreter_funcs::FUNC_ID_IMPORT_OBJ_JOIN;
+    let mut args = ui::default_args(session, func_ident);
+    args[0] = ast::Expr::Lit(ast::LitExpr::String(path.to_string_lossy().into_owned()));
+
+    let stmt = ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+        session
+            .next_free_var_ident()
+            .ok_or_else(|| String::from("Failed to find free variable identifier"))?,
+        ast::CallExpr::new(func_ident, args),
+    ));
+    session.push_prog_stmt(time, stmt);
+
+    Ok(())
+}
+
+/// Applies an edit of the pipeline requested by a remote client.
+pub fn apply_remote_edit(
+    time: Instant,
+    session: &mut Session,
+    command: &RemoteCommand,
+) -> Result<(), String> {
+    if session.interpreter_busy() {
+        return Err(String::from(
+            "Can't edit the pipeline while it is running, try again once it finishes",
+        ));
+    }
+
+    match command {
+        RemoteCommand::PushStmt { func_id, args } => {
+            let init_expr = remote::call_expr(session, session.stmts().len(), *func_id, args)?;
+            let var_ident = session
+                .next_free_var_ident()
+                .ok_or_else(|| String::from("Failed to find free variable identifier"))?;
+
+            session.push_prog_stmt(
+                time,
+                ast::Stmt::VarDecl(ast::VarDeclStmt::new(var_ident, init_expr)),
+            );
+        }
+        RemoteCommand::SetStmt {
+            stmt_index,
+            func_id,
+            args,
+        } => {
+            let var_decl = match session.stmts().get(*stmt_index) {
+                Some(ast::Stmt::VarDecl(var_decl)) => var_decl.clone(),
+                None => return Err(format!("No statement at index {}", stmt_index)),
+            };
+            let init_expr = remote::call_expr(session, *stmt_index, *func_id, args)?;
+
+            session.set_prog_stmt_at(
+                time,
+                *stmt_index,
+                ast::Stmt::VarDecl(var_decl.clone_with_init_expr(init_expr)),
+            );
+        }
+        RemoteCommand::PopStmt => {
+            if session.stmts().is_empty() {
+                return Err(String::from("The pipeline is empty"));
+            }
+
+            session.pop_prog_stmt(time);
+        }
+        _ => return Err(String::from("Not an edit of the pipeline")),
+    }
+
+    Ok(())
+}
+
+/// Saves OBJ text from the clipboard and appends an operation importing it to
+/// the pipeline.
+pub fn paste_obj(
+    time: Instant,
+    clipboard: &mut Clipboard,
+    session: &mut Session,
+    project_status: &mut ProjectStatus,
+    window: &winit::window::Window,
+    notifications: &mut Notifications,
+) {
+    match clipboard.text() {
+        Some(text) if looks_like_obj(&text) => match write_pasted_obj(project_status, &text) {
+            Ok(path) => {
+                log::info!("Pasted OBJ saved to: {}", path.display());
+
+                let func_ident = interpreter_funcs::FUNC_ID_IMPORT_OBJ_JOIN;
+                let mut args = ui::default_args(session, func_ident);
+                args[0] = ast::Expr::Lit(ast::LitExpr::String(path.to_string_lossy().into_owned()));
+
+                let stmt = ast::Stmt::VarDecl(ast::VarDeclStmt::new(
+                    session
+                        .next_free_var_ident()
+                        .expect("Failed to find free variable identifier"),
+                    ast::CallExpr::new(func_ident, args),
+                ));
+                session.push_prog_stmt(time, stmt);
+
+                project_status.changed_since_last_save = true;
+                change_window_title(window, project_status);
+
+                notifications.push(
+                    time,
+                    NotificationLevel::Info,
+                    format!("OBJ pasted and saved to: {}", path.display()),
+                );
+            }
+            Err(err) => {
+                log::error!("Failed to save pasted OBJ: {}", err);
+                notifications.push(time, NotificationLevel::Error, "Failed to save pasted OBJ");
+            }
+        },
+        _ => {
+            notifications.push(
+                time,
+                NotificationLevel::Warn,
+                "The clipboard does not contain OBJ text.",
+            );
+        }
+    }
+}
+
+/// Copies the pipeline to the clipboard as text.
+pub fn copy_pipeline_text(
+    time: Instant,
+    clipboard: &mut Clipboard,
+    session: &Session,
+    notifications: &mut Notifications,
+) {
+    let text = pipeline_text::format(session.stmts(), session.function_table());
+    if clipboard.set_text(text) {
+        notifications.push(
+            time,
+            NotificationLevel::Info,
+            "Pipeline copied to clipboard as text.",
+        );
+    } else {
+        notifications.push(time, NotificationLevel::Warn, "Failed to copy to clipboard");
+    }
+}
+
+/// Replaces the pipeline with the one parsed from the text in the clipboard.
+pub fn paste_pipeline_text(
+    time: Instant,
+    clipboard: &mut Clipboard,
+    session: &mut Session,
+    project_status: &mut ProjectStatus,
+    window: &winit::window::Window,
+    notifications: &mut Notifications,
+) {
+    match clipboard.text() {
+        Some(text) => match pipeline_text::parse(&text, session.function_table()) {
+            Ok(stmts) => {
+                session.set_prog_stmts(time, stmts);
+
+                project_status.changed_since_last_save = true;
+                change_window_title(window, project_status);
+
+                notifications.push(time, NotificationLevel::Info, "Pipeline pasted from text.");
+            }
+            Err(err) => {
+                log::warn!("Failed to parse pipeline text: {}", err);
+                notifications.push(
+                    time,
+                    NotificationLevel::Warn,
+                    format!("Failed to paste pipeline text. {}", err),
+                );
+            }
+        },
+        None => {
+            notifications.push(
+                time,
+                NotificationLevel::Warn,
+                "The clipboard does not contain text.",
+            );
+        }
+    }
+}
+
+/// Returns whether the text looks like OBJ data, i.e. contains at least one
+/// vertex and one face.
+fn looks_like_obj(text: &str) -> bool {
+    let mut has_vertex = false;
+    let mut has_face = false;
+    for line in text.lines() {
+        let line = line.trim_start();
+        has_vertex |= line.starts_with("v ");
+        has_face |= line.starts_with("f ");
+    }
+
+    has_vertex && has_face
+}
+
+/// Saves OBJ text pasted from the clipboard into a new file next to the
+/// project file, or into the application data directory, if the project was
+/// not saved yet. Returns the path to the file.
+fn write_pasted_obj(project_status: &ProjectStatus, text: &str) -> io::Result<PathBuf> {
+    let directory = match project_status.path.as_ref().and_then(|path| path.parent()) {
+        Some(project_directory) => project_directory.to_path_buf(),
+        None => match dirs::data_local_dir() {
+            Some(data_local_dir) => data_local_dir.join("H.U.R.B.A.N. selector/Pasted"),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "No data directory to save pasted OBJ to",
+                ))
+            }
+        },
+    };
+
+    fs::create_dir_all(&directory)?;
+
+    let path = directory.join(format!(
+        "pasted-{}.obj",
+        chrono::Local::now().format("%Y-%m-%d-%H-%M-%S"),
+    ));
+    fs::write(&path, text)?;
+
+    Ok(path)
+}
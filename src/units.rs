@@ -0,0 +1,117 @@
+use crate::interpreter::Dimension;
+
+/// The unit of length the project geometry is modeled in.
+///
+/// The interpreter and the geometry are unaware of units - all values are
+/// plain numbers in the project units. Units only matter when exchanging
+/// geometry with other software and when displaying values to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Unit {
+    Millimeter,
+    Centimeter,
+    Meter,
+    Foot,
+}
+
+impl Default for Unit {
+    fn default() -> Self {
+        Unit::Meter
+    }
+}
+
+impl Unit {
+    pub const ALL: [Unit; 4] = [Unit::Millimeter, Unit::Centimeter, Unit::Meter, Unit::Foot];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Millimeter => "Millimeters",
+            Unit::Centimeter => "Centimeters",
+            Unit::Meter => "Meters",
+            Unit::Foot => "Feet",
+        }
+    }
+
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Unit::Millimeter => "mm",
+            Unit::Centimeter => "cm",
+            Unit::Meter => "m",
+            Unit::Foot => "ft",
+        }
+    }
+
+    /// Returns the length of the unit in meters.
+    pub fn meters(self) -> f32 {
+        match self {
+            Unit::Millimeter => 0.001,
+            Unit::Centimeter => 0.01,
+            Unit::Meter => 1.0,
+            Unit::Foot => 0.3048,
+        }
+    }
+
+    /// Returns the factor converting lengths in this unit to lengths in the
+    /// target unit.
+    pub fn conversion_factor(self, target: Unit) -> f32 {
+        self.meters() / target.meters()
+    }
+
+    /// Returns the symbol displayed after values of the dimension, e.g. `m²`
+    /// for areas in meters. Empty for dimensionless values.
+    pub fn symbol(self, dimension: Dimension) -> String {
+        match dimension {
+            Dimension::Dimensionless => String::new(),
+            Dimension::Length => String::from(self.abbreviation()),
+            Dimension::Area => format!("{}²", self.abbreviation()),
+            Dimension::Volume => format!("{}³", self.abbreviation()),
+            Dimension::Angle => String::from("deg"),
+        }
+    }
+
+    /// Parses the unit from its abbreviation or its name, e.g. `mm`,
+    /// `millimeters` or `millimetre`. Case insensitive.
+    pub fn parse(s: &str) -> Option<Unit> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => {
+                Some(Unit::Millimeter)
+            }
+            "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => {
+                Some(Unit::Centimeter)
+            }
+            "m" | "meter" | "meters" | "metre" | "metres" => Some(Unit::Meter),
+            "ft" | "foot" | "feet" => Some(Unit::Foot),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_conversion_factor() {
+        approx::assert_relative_eq!(Unit::Millimeter.conversion_factor(Unit::Meter), 0.001);
+        approx::assert_relative_eq!(Unit::Meter.conversion_factor(Unit::Centimeter), 100.0);
+        approx::assert_relative_eq!(Unit::Foot.conversion_factor(Unit::Millimeter), 304.8);
+        approx::assert_relative_eq!(Unit::Foot.conversion_factor(Unit::Foot), 1.0);
+    }
+
+    #[test]
+    fn test_unit_parse() {
+        assert_eq!(Unit::parse("mm"), Some(Unit::Millimeter));
+        assert_eq!(Unit::parse(" Centimetres "), Some(Unit::Centimeter));
+        assert_eq!(Unit::parse("METERS"), Some(Unit::Meter));
+        assert_eq!(Unit::parse("feet"), Some(Unit::Foot));
+        assert_eq!(Unit::parse("inches"), None);
+    }
+
+    #[test]
+    fn test_unit_symbol() {
+        assert_eq!(Unit::Meter.symbol(Dimension::Dimensionless), "");
+        assert_eq!(Unit::Meter.symbol(Dimension::Length), "m");
+        assert_eq!(Unit::Millimeter.symbol(Dimension::Area), "mm²");
+        assert_eq!(Unit::Foot.symbol(Dimension::Volume), "ft³");
+        assert_eq!(Unit::Foot.symbol(Dimension::Angle), "deg");
+    }
+}
@@ -0,0 +1,194 @@
+use crate::interpreter::ast;
+
+/// Dimensions of the viewport thumbnail captured for each variant.
+pub const THUMBNAIL_WIDTH: u32 = 192;
+pub const THUMBNAIL_HEIGHT: u32 = 108;
+
+/// Opaque identifier of a variant. Identifiers are never reused within one
+/// `Variants` collection, so it is safe to hold on to them while waiting for
+/// asynchronous work (such as thumbnail rendering) to finish, even if the
+/// variant is removed in the meantime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VariantId(u64);
+
+/// A named snapshot of the pipeline program.
+///
+/// Because all parameters of all operations are stored as literals in the
+/// program statements, capturing the statements captures the complete
+/// parameter set of the design. Restoring a variant means replacing the
+/// program in the session with the captured statements.
+#[derive(Debug, Clone)]
+pub struct Variant {
+    id: VariantId,
+    name: String,
+    stmts: Vec<ast::Stmt>,
+    thumbnail: Option<imgui::TextureId>,
+}
+
+impl Variant {
+    pub fn id(&self) -> VariantId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stmts(&self) -> &[ast::Stmt] {
+        &self.stmts
+    }
+
+    /// The UI texture containing the viewport thumbnail of this variant, if it
+    /// was already rendered.
+    pub fn thumbnail(&self) -> Option<imgui::TextureId> {
+        self.thumbnail
+    }
+}
+
+/// An ordered collection of design variants.
+///
+/// The collection only owns the UI texture identifiers of the thumbnails, not
+/// the textures themselves. Whenever a thumbnail is replaced or a variant is
+/// removed, the texture is returned to the caller, who is responsible for
+/// removing it from the renderer.
+#[derive(Debug, Default)]
+pub struct Variants {
+    variants: Vec<Variant>,
+    next_id: u64,
+}
+
+impl Variants {
+    pub fn new() -> Self {
+        Self {
+            variants: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Variant> + Clone {
+        self.variants.iter()
+    }
+
+    pub fn get(&self, id: VariantId) -> Option<&Variant> {
+        self.variants.iter().find(|variant| variant.id == id)
+    }
+
+    /// Returns a name for the next captured variant, unique unless the user
+    /// renamed other variants to look like generated names.
+    pub fn suggest_name(&self) -> String {
+        let mut number = self.variants.len() + 1;
+        loop {
+            let name = format!("Variant {}", number);
+            if self.variants.iter().all(|variant| variant.name != name) {
+                return name;
+            }
+
+            number += 1;
+        }
+    }
+
+    /// Captures a new variant from program statements. The thumbnail has to be
+    /// provided later via `Variants::set_thumbnail`, once it is rendered.
+    pub fn capture(&mut self, name: String, stmts: Vec<ast::Stmt>) -> VariantId {
+        let id = VariantId(self.next_id);
+        self.next_id += 1;
+
+        self.variants.push(Variant {
+            id,
+            name,
+            stmts,
+            thumbnail: None,
+        });
+
+        id
+    }
+
+    /// Sets the thumbnail of a variant.
+    ///
+    /// Returns the texture that is no longer referenced by the collection, if
+    /// any: either the thumbnail that was replaced, or the given thumbnail
+    /// itself, if the variant does not exist (anymore).
+    pub fn set_thumbnail(
+        &mut self,
+        id: VariantId,
+        thumbnail: imgui::TextureId,
+    ) -> Option<imgui::TextureId> {
+        match self.variants.iter_mut().find(|variant| variant.id == id) {
+            Some(variant) => variant.thumbnail.replace(thumbnail),
+            None => Some(thumbnail),
+        }
+    }
+
+    /// Removes a variant from the collection and returns it.
+    pub fn remove(&mut self, id: VariantId) -> Option<Variant> {
+        let index = self.variants.iter().position(|variant| variant.id == id)?;
+        Some(self.variants.remove(index))
+    }
+
+    /// Removes all variants, returning their thumbnail textures.
+    pub fn clear(&mut self) -> Vec<imgui::TextureId> {
+        self.variants
+            .drain(..)
+            .filter_map(|variant| variant.thumbnail)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variants_capture_and_remove() {
+        let mut variants = Variants::new();
+        let first = variants.capture(String::from("First"), Vec::new());
+        let second = variants.capture(String::from("Second"), Vec::new());
+
+        assert_ne!(first, second);
+        assert_eq!(variants.len(), 2);
+
+        let removed = variants.remove(first).expect("Variant must be present");
+        assert_eq!(removed.name(), "First");
+        assert!(variants.get(first).is_none());
+        assert_eq!(variants.get(second).map(Variant::name), Some("Second"));
+
+        // Identifiers are not reused after removal
+        let third = variants.capture(String::from("Third"), Vec::new());
+        assert_ne!(first, third);
+    }
+
+    #[test]
+    fn test_variants_set_thumbnail_returns_unreferenced_texture() {
+        let mut variants = Variants::new();
+        let id = variants.capture(String::from("First"), Vec::new());
+
+        assert_eq!(variants.set_thumbnail(id, imgui::TextureId::from(1)), None);
+        assert_eq!(
+            variants.set_thumbnail(id, imgui::TextureId::from(2)),
+            Some(imgui::TextureId::from(1)),
+        );
+
+        variants.remove(id);
+        assert_eq!(
+            variants.set_thumbnail(id, imgui::TextureId::from(3)),
+            Some(imgui::TextureId::from(3)),
+        );
+    }
+
+    #[test]
+    fn test_variants_suggest_name_skips_taken_names() {
+        let mut variants = Variants::new();
+        assert_eq!(variants.suggest_name(), "Variant 1");
+
+        variants.capture(String::from("Variant 2"), Vec::new());
+        assert_eq!(variants.suggest_name(), "Variant 3");
+    }
+}
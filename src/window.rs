@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+
+use crate::camera::Camera;
+use crate::input::InputManager;
+use crate::project::ProjectStatus;
+use crate::renderer::{Renderer, SecondaryWindowHandle};
+use crate::window_state::WindowState;
+
+pub const BASE_WINDOW_TITLE: &str = "H.U.R.B.A.N. selector";
+
+/// A viewport in its own window, e.g. on another monitor, with its own
+/// camera.
+pub struct DetachedViewport {
+    pub window: winit::window::Window,
+    pub handle: SecondaryWindowHandle,
+    pub camera: Camera,
+    pub input_manager: InputManager,
+}
+
+impl DetachedViewport {
+    /// Opens a new window and starts drawing into it, looking at the scene the
+    /// same way as the camera.
+    pub fn open<T>(
+        event_loop_window_target: &winit::event_loop::EventLoopWindowTarget<T>,
+        renderer: &mut Renderer,
+        camera: &Camera,
+    ) -> Result<Self, winit::error::OsError> {
+        let window = winit::window::WindowBuilder::new()
+            .with_title(format!("{} - Viewport", BASE_WINDOW_TITLE))
+            .with_inner_size(winit::dpi::LogicalSize::new(960, 540))
+            .build(event_loop_window_target)?;
+
+        Ok(Self::new(renderer, window, camera))
+    }
+
+    /// Starts drawing into the window, looking at the scene the same way as
+    /// the camera.
+    fn new(renderer: &mut Renderer, window: winit::window::Window, camera: &Camera) -> Self {
+        let window_size = window.inner_size();
+        let handle = renderer.add_secondary_window(&window, window_size.width, window_size.height);
+
+        let mut camera = camera.clone();
+        camera.set_screen_dimensions(window_size.width, window_size.height);
+
+        Self {
+            window,
+            handle,
+            camera,
+            input_manager: InputManager::new(),
+        }
+    }
+
+    /// Moves the camera of the viewport by the input received by its window
+    /// during the frame. Returns whether the window is requested to close.
+    pub fn process_input(&mut self, renderer: &mut Renderer, main_camera: &Camera) -> bool {
+        let input_state = *self.input_manager.input_state();
+        let camera = &mut self.camera;
+
+        if let Some(([old_x, old_y], [new_x, new_y])) = input_state.camera_pan_ground {
+            camera.pan_ground(old_x, old_y, new_x, new_y);
+        }
+        if let Some(([old_x, old_y], [new_x, new_y])) = input_state.camera_pan_screen {
+            camera.pan_screen(old_x, old_y, new_x, new_y);
+        }
+        camera.rotate(input_state.camera_rotate[0], input_state.camera_rotate[1]);
+        camera.zoom(input_state.camera_zoom);
+        camera.zoom_step(input_state.camera_zoom_steps);
+
+        if input_state.camera_reset_viewport {
+            camera.set_view(&main_camera.view());
+        }
+
+        if let Some(physical_size) = input_state.window_resized {
+            let width = physical_size.width;
+            let height = physical_size.height;
+
+            // Same limit as for the main window
+            if width >= 16 && height >= 16 {
+                camera.set_screen_dimensions(width, height);
+                renderer.set_secondary_window_size(&self.handle, width, height);
+            }
+        }
+
+        input_state.close_requested
+    }
+
+    /// Stops drawing into the window and closes it.
+    pub fn destroy(self, renderer: &mut Renderer) {
+        renderer.remove_secondary_window(self.handle);
+    }
+}
+
+/// Switches the window to borderless fullscreen on its current monitor, or
+/// back. The window state before becoming fullscreen is captured, as it can't
+/// be queried while fullscreen.
+pub fn set_fullscreen(
+    window: &winit::window::Window,
+    fullscreen: bool,
+    windowed_window_state: &mut Option<WindowState>,
+) {
+    if fullscreen {
+        if window.fullscreen().is_none() {
+            *windowed_window_state = Some(WindowState::capture(window, false));
+        }
+
+        window.set_fullscreen(Some(winit::window::Fullscreen::Borderless(
+            window.current_monitor(),
+        )));
+    } else {
+        window.set_fullscreen(None);
+    }
+}
+
+pub fn change_window_title(window: &winit::window::Window, project_status: &ProjectStatus) {
+    let filename: Cow<str> = match &project_status.path {
+        Some(project_path) => project_path
+            .file_name()
+            .map_or(Cow::Borrowed("unnamed project"), |file_name| {
+                file_name.to_string_lossy()
+            }),
+        None => match project_status.example {
+            Some(example_name) => Cow::Owned(format!("{} (read-only example)", example_name)),
+            None => Cow::Borrowed("unsaved project"),
+        },
+    };
+    let join_str = if project_status.changed_since_last_save {
+        " - *"
+    } else {
+        " - "
+    };
+
+    window.set_title(&[BASE_WINDOW_TITLE, &*filename].join(join_str));
+}
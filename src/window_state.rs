@@ -0,0 +1,81 @@
+use crate::preferences::{self, ConfigFileError};
+
+const WINDOW_STATE_FILE_NAME: &str = "window.ron";
+
+/// Size, position and fullscreen state of the main window, restored on the
+/// next start. All dimensions are in physical pixels.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WindowState {
+    /// Position of the outer top left corner of the window. Not all platforms
+    /// report it, e.g. Wayland.
+    pub position: Option<[i32; 2]>,
+    /// Size of the window contents.
+    pub size: [u32; 2],
+    /// Name of the monitor the window was on.
+    pub monitor: Option<String>,
+    pub fullscreen: bool,
+}
+
+impl WindowState {
+    /// Captures the current position and size of the window. The window must
+    /// not be fullscreen, otherwise the fullscreen dimensions are captured.
+    pub fn capture(window: &winit::window::Window, fullscreen: bool) -> Self {
+        let size = window.inner_size();
+
+        Self {
+            position: window
+                .outer_position()
+                .ok()
+                .map(|position| [position.x, position.y]),
+            size: [size.width, size.height],
+            monitor: window.current_monitor().and_then(|monitor| monitor.name()),
+            fullscreen,
+        }
+    }
+}
+
+/// Loads the window state saved by the previous session, if any.
+pub fn load() -> Result<Option<WindowState>, ConfigFileError> {
+    preferences::read_config_file(WINDOW_STATE_FILE_NAME)
+}
+
+/// Saves the window state to the user configuration directory.
+pub fn save(window_state: &WindowState) -> Result<(), ConfigFileError> {
+    preferences::write_config_file(WINDOW_STATE_FILE_NAME, &Some(window_state))
+}
+
+/// Whether the window position lies on one of the monitors, given by their
+/// positions and sizes. A window saved on a monitor that was disconnected
+/// since would otherwise open off-screen.
+pub fn position_visible(position: [i32; 2], monitors: &[([i32; 2], [u32; 2])]) -> bool {
+    let [x, y] = position;
+    monitors
+        .iter()
+        .any(|([monitor_x, monitor_y], [width, height])| {
+            let x = i64::from(x);
+            let y = i64::from(y);
+            let monitor_x = i64::from(*monitor_x);
+            let monitor_y = i64::from(*monitor_y);
+
+            x >= monitor_x
+                && x < monitor_x + i64::from(*width)
+                && y >= monitor_y
+                && y < monitor_y + i64::from(*height)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_visible_on_any_monitor() {
+        let monitors = [([0, 0], [1920, 1080]), ([-1280, 0], [1280, 1024])];
+
+        assert!(position_visible([100, 50], &monitors));
+        assert!(position_visible([-1280, 1023], &monitors));
+        assert!(!position_visible([1920, 0], &monitors));
+        assert!(!position_visible([-200, 1050], &monitors));
+        assert!(!position_visible([0, 0], &[]));
+    }
+}